@@ -0,0 +1,315 @@
+//! Threshold checks for parsed files
+//!
+//! Shared by the parallel traverser and the single-file fast path, which skips
+//! the filesystem AST, directory walking and concurrent maps entirely
+
+use dei_core::{
+    detector::{Context, DetectorRegistry, Finding},
+    error::Result,
+    metrics::*,
+    models::*,
+    thresholds::{Complexity, MethodCount, Thresholds},
+    traits::{ClusterAnalyzer, Parser},
+};
+use dei_clustering::ClusteringAnalyzer;
+use dei_metrics::CohesionAnalyzer;
+use rayon::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Everything found in one file
+#[derive(Debug, Clone)]
+pub struct FileAnalysis {
+    pub metrics: FileMetrics,
+    pub god_file: Option<GodFileResult>,
+    pub import_hygiene: Option<ImportHygieneResult>,
+    pub results: Vec<AnalysisResult>,
+    pub parse_time: Duration,
+    pub analyze_time: Duration,
+}
+
+/// Parse and analyze a single file, for editors and other latency-sensitive callers
+///
+/// Gives the same findings as a [`ParallelTraverser`](crate::ParallelTraverser) run over just this file
+pub fn analyze_file<P>(parser: &P, path: &Path, thresholds: &Thresholds) -> Result<FileAnalysis>
+where
+    P: Parser + ?Sized,
+{
+    analyze_file_with(parser, path, thresholds, &DetectorRegistry::default(), &PathMetadata::default())
+}
+
+/// [`analyze_file`], running the given detectors instead of the built-in ones, on a file the
+/// `[[metadata]]` path rules gave `metadata`
+pub fn analyze_file_with<P>(
+    parser: &P,
+    path: &Path,
+    thresholds: &Thresholds,
+    detectors: &DetectorRegistry,
+    metadata: &PathMetadata,
+) -> Result<FileAnalysis>
+where
+    P: Parser + ?Sized,
+{
+    let started = Instant::now();
+    let metrics = parser.parse_file(path)?;
+    let parse_time = started.elapsed();
+
+    Ok(FileAnalysis { parse_time, ..analyze_metrics_with(metrics, thresholds, detectors, metadata) })
+}
+
+/// [`analyze_file_with`] for a file parsed elsewhere, such as from an editor's unsaved buffer
+pub fn analyze_metrics_with(
+    metrics: FileMetrics,
+    thresholds: &Thresholds,
+    detectors: &DetectorRegistry,
+    metadata: &PathMetadata,
+) -> FileAnalysis {
+    let started = Instant::now();
+    let (god_file, import_hygiene) = file_results_with(&metrics, thresholds, detectors, metadata);
+    let results = metrics
+        .classes
+        .iter()
+        .map(|class| analyze_class_with(class, thresholds, detectors, metadata))
+        .collect();
+
+    FileAnalysis {
+        metrics,
+        god_file,
+        import_hygiene,
+        results,
+        parse_time: Duration::ZERO,
+        analyze_time: started.elapsed(),
+    }
+}
+
+impl FileAnalysis {
+    /// Fill in [`suggest_extractions`] for every god class in the file
+    pub fn with_suggested_extractions(mut self, thresholds: &Thresholds) -> Self {
+        self.results.par_iter_mut().for_each(|result| {
+            (result.suggested_extractions, result.cluster_stats) = suggest_extractions(result, thresholds);
+        });
+        self
+    }
+}
+
+/// Apply thresholds to one class with the built-in detectors
+pub fn analyze_class(class: &ClassMetrics, thresholds: &Thresholds) -> AnalysisResult {
+    analyze_class_with(class, thresholds, &DetectorRegistry::default(), &PathMetadata::default())
+}
+
+/// Apply thresholds to one class, running the given detectors; `metadata` is what the path
+/// rules say about its file, for the detectors to see and the result to carry
+pub fn analyze_class_with(
+    class: &ClassMetrics,
+    thresholds: &Thresholds,
+    detectors: &DetectorRegistry,
+    metadata: &PathMetadata,
+) -> AnalysisResult {
+    let thresholds = &*thresholds.for_entry_point(class.entry_point);
+    let lcom4 = CohesionAnalyzer::lcom4(class).map(|c| c.lcom4);
+    let ctx = Context { lcom4, metadata: Some(metadata), ..Context::new(thresholds) };
+
+    let mut god_class_violations = Vec::new();
+    let mut god_methods = Vec::new();
+    let mut violations = Vec::new();
+    for finding in detectors.inspect(class, &ctx) {
+        match finding {
+            Finding::GodClass(violation) => god_class_violations.push(violation),
+            Finding::GodMethod(method) => god_methods.push(*method),
+            Finding::Class(violation) => violations.push(violation),
+            Finding::GodFile(_) | Finding::Imports(_) => {}
+        }
+    }
+    let is_god_class = !god_class_violations.is_empty();
+    let violations: Arc<[Violation]> = violations.into();
+
+    if !is_god_class && god_methods.is_empty() {
+        if violations.is_empty() {
+            return AnalysisResult {
+                lcom4,
+                metadata: metadata.clone(),
+                ..AnalysisResult::healthy(class.without_ast_snippets())
+            };
+        }
+
+        return AnalysisResult {
+            summary: class_violation_summary(class, &violations).into(),
+            lcom4,
+            violations,
+            metadata: metadata.clone(),
+            ..AnalysisResult::healthy(class.without_ast_snippets())
+        };
+    }
+
+    let summary = if is_god_class {
+        format!(
+            "God class detected: {} (lines: {}, methods: {}, complexity: {})",
+            class.name, class.lines.0, class.method_count.0, class.complexity.0
+        )
+    } else {
+        format!("Class '{}' has {} god method(s)", class.name, god_methods.len())
+    };
+
+    AnalysisResult {
+        class_metrics: class.without_ast_snippets(),
+        is_god_class,
+        // Clustering is slow, so callers opt in with `suggest_extractions`
+        suggested_extractions: Arc::new([]),
+        cluster_stats: None,
+        god_methods: god_methods.into(),
+        god_class_violations: god_class_violations.into(),
+        lcom4,
+        coupling: None,
+        violations,
+        code_age: None,
+        base_comparison: None,
+        owners: Arc::new([]),
+        name_clashes: Arc::new([]),
+        metadata: metadata.clone(),
+        analyzed_at: std::time::SystemTime::now(),
+        summary: summary.into(),
+    }
+}
+
+/// Groups of methods a god class could be split along, from responsibility clustering
+///
+/// When clustering finds nothing short of the whole class, method groups sharing no state
+/// with the rest (LCOM4 components besides the largest, which stays behind) are suggested
+/// instead. Empty for classes that aren't god classes. Comes with the quality of the
+/// clustering, whether or not its clusters were kept.
+pub fn suggest_extractions(
+    result: &AnalysisResult,
+    thresholds: &Thresholds,
+) -> (Arc<[ResponsibilityCluster]>, Option<ClusterStats>) {
+    if !result.is_god_class {
+        return (Arc::from([]), None);
+    }
+
+    let class = &result.class_metrics;
+    let thresholds = &*thresholds.for_entry_point(class.entry_point);
+    let analyzer = ClusteringAnalyzer::new();
+    // A cluster holding every method moves the class rather than splitting it
+    let (clusters, stats) = analyzer.analyze_with_stats(class, thresholds).unwrap_or_default();
+    let clusters: Vec<ResponsibilityCluster> =
+        clusters.into_iter().filter(|c| c.methods.len() < class.methods.len()).collect();
+    if !clusters.is_empty() {
+        return (clusters.into(), stats);
+    }
+
+    let mut components = CohesionAnalyzer::lcom4(class).map(|c| c.components).unwrap_or_default();
+    components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    let components = components
+        .iter()
+        .skip(1)
+        .filter(|c| c.len() >= thresholds.min_cluster_size)
+        .map(|c| analyzer.cluster_for(class, c))
+        .collect();
+    (components, stats)
+}
+
+fn class_violation_summary(class: &ClassMetrics, violations: &[Violation]) -> String {
+    let issues: Vec<String> = violations
+        .iter()
+        .map(|v| match v.kind {
+            ViolationKind::LowCohesion => {
+                format!("splits into {} unrelated method groups (LCOM4)", v.actual)
+            }
+            ViolationKind::FieldCount => format!("holds {} fields", v.actual),
+            ViolationKind::DependencyCount => format!("depends on {} imports", v.actual),
+            ViolationKind::Rule => format!("breaks rule {}", v.rule.as_deref().unwrap_or_default()),
+            kind => format!("{:?} {} exceeds {}", kind, v.actual, v.threshold),
+        })
+        .collect();
+
+    format!("Class '{}' {}", class.name, issues.join(", "))
+}
+
+/// Import findings from the built-in detectors; `None` when clean
+pub fn import_hygiene_result(file_metrics: &FileMetrics, thresholds: &Thresholds) -> Option<ImportHygieneResult> {
+    file_results_with(file_metrics, thresholds, &DetectorRegistry::default(), &PathMetadata::default()).1
+}
+
+/// God-file verdict from the built-in detectors; `None` for a file within thresholds
+pub fn god_file_result(file_metrics: &FileMetrics, thresholds: &Thresholds) -> Option<GodFileResult> {
+    file_results_with(file_metrics, thresholds, &DetectorRegistry::default(), &PathMetadata::default()).0
+}
+
+/// God-file verdict and import findings from one pass of the given detectors over the file;
+/// each `None` when they raise nothing of its kind
+pub fn file_results_with(
+    file_metrics: &FileMetrics,
+    thresholds: &Thresholds,
+    detectors: &DetectorRegistry,
+    metadata: &PathMetadata,
+) -> (Option<GodFileResult>, Option<ImportHygieneResult>) {
+    let thresholds = &*thresholds.for_entry_point(file_metrics.entry_point);
+    // Clustering is the expensive signal, so it only runs when asked for
+    let responsibilities = thresholds
+        .max_file_responsibilities
+        .map(|_| file_responsibilities(file_metrics, thresholds));
+    let ctx = Context { responsibilities, metadata: Some(metadata), ..Context::new(thresholds) };
+
+    let mut god_file = Vec::new();
+    let mut imports = Vec::new();
+    for finding in detectors.inspect_file(file_metrics, &ctx) {
+        match finding {
+            Finding::GodFile(violation) => god_file.push(violation),
+            Finding::Imports(violation) => imports.push(violation),
+            Finding::GodClass(_) | Finding::GodMethod(_) | Finding::Class(_) => {}
+        }
+    }
+
+    let god_file = (!god_file.is_empty()).then(|| GodFileResult {
+        file_path: file_metrics.path.clone(),
+        class_count: file_metrics.classes.len(),
+        total_lines: file_metrics.lines.0,
+        class_names: file_metrics
+            .classes
+            .iter()
+            .map(|c| c.name.clone())
+            .collect(),
+        violations: god_file.into(),
+        entry_point: file_metrics.entry_point,
+    });
+    let import_hygiene = (!imports.is_empty()).then(|| ImportHygieneResult {
+        file_path: file_metrics.path.clone(),
+        imported_symbols: file_metrics.imported_symbols,
+        wildcard_imports: file_metrics.wildcard_imports.clone(),
+        violations: imports.into(),
+    });
+    (god_file, import_hygiene)
+}
+
+/// Responsibility clusters across every method in the file, as if it were one class
+fn file_responsibilities(file_metrics: &FileMetrics, thresholds: &Thresholds) -> usize {
+    let methods: Arc<[MethodMetrics]> = file_metrics
+        .classes
+        .iter()
+        .flat_map(|c| c.methods.iter().cloned())
+        .collect();
+    let name: Arc<str> = Path::new(file_metrics.path.as_ref())
+        .file_stem()
+        .map_or("module".into(), |s| s.to_string_lossy().into());
+
+    let scope = ClassMetrics {
+        name: name.clone(),
+        fully_qualified_name: name,
+        file_path: file_metrics.path.clone(),
+        lines: file_metrics.lines,
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(methods.iter().map(|m| m.complexity.0).sum()),
+        methods,
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    ClusteringAnalyzer::new()
+        .analyze(&scope, thresholds)
+        .map_or(0, |clusters| clusters.len())
+}
@@ -1,81 +1,186 @@
 //! Arena allocator for efficient AST node management
 //! 
-//! Provides cache-friendly memory layout and fast traversal
+//! Provides cache-friendly memory layout and fast traversal. Nodes are handed out as
+//! shared `Arc`s, so reading one never copies its metrics or results.
 
 use std::sync::{Arc, RwLock};
 
+use crate::metadata::{MetadataTable, PathRule};
 use crate::node::{Node, NodeId};
+use crate::symbols::SymbolTable;
 
 /// Thread-safe arena for AST nodes
 /// Uses generational indexing to prevent use-after-free
 #[derive(Debug)]
 pub struct Arena {
-    nodes: RwLock<Vec<Node>>,
+    slots: RwLock<Slots>,
+    symbols: SymbolTable,
+    metadata: MetadataTable,
+}
+
+#[derive(Debug, Default)]
+struct Slots {
+    entries: Vec<Slot>,
+    /// Indices of removed nodes, reused by the next allocations
+    free: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    generation: u32,
+    node: Option<Arc<Node>>,
+}
+
+impl Slots {
+    fn get(&self, id: NodeId) -> Option<&Arc<Node>> {
+        let slot = self.entries.get(id.index)?;
+        (slot.generation == id.generation).then_some(slot.node.as_ref()).flatten()
+    }
+
+    fn get_mut(&mut self, id: NodeId) -> Option<&mut Arc<Node>> {
+        let slot = self.entries.get_mut(id.index)?;
+        (slot.generation == id.generation).then_some(slot.node.as_mut()).flatten()
+    }
+
+    fn live(&self) -> impl Iterator<Item = &Arc<Node>> {
+        self.entries.iter().filter_map(|slot| slot.node.as_ref())
+    }
 }
 
 impl Arena {
     pub fn new() -> Self {
-        Self {
-            nodes: RwLock::new(Vec::new()),
-        }
+        Self::with_capacity(0)
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            nodes: RwLock::new(Vec::with_capacity(capacity)),
+            slots: RwLock::new(Slots { entries: Vec::with_capacity(capacity), free: Vec::new() }),
+            symbols: SymbolTable::new(),
+            metadata: MetadataTable::new(),
         }
     }
 
-    /// Allocate a new node in the arena
+    /// Allocate a new node in the arena, reusing the slot of a removed node if there is one
     pub fn alloc(&self, mut node: Node) -> NodeId {
-        let mut nodes = self.nodes.write().unwrap();
-        let id = NodeId(nodes.len());
+        let mut slots = self.slots.write().unwrap();
+        let id = match slots.free.pop() {
+            Some(index) => NodeId { index, generation: slots.entries[index].generation },
+            None => {
+                slots.entries.push(Slot { generation: 0, node: None });
+                NodeId::new(slots.entries.len() - 1)
+            }
+        };
         node.id = id; // Update the node's ID field to match its arena position
-        nodes.push(node);
+        slots.entries[id.index].node = Some(Arc::new(node));
         id
     }
 
-    /// Get a node by ID
-    pub fn get(&self, id: NodeId) -> Option<Node> {
-        self.nodes.read().unwrap().get(id.0).cloned()
+    /// Get a node by ID; `None` once the node has been removed
+    ///
+    /// Analysis results are set on the shared node directly (see [`Node::set_analysis_results`]).
+    pub fn get(&self, id: NodeId) -> Option<Arc<Node>> {
+        self.slots.read().unwrap().get(id).cloned()
+    }
+
+    /// Whether `id` still refers to a node
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.slots.read().unwrap().get(id).is_some()
     }
 
-    /// Get a mutable reference to a node
-    pub fn get_mut(&self, id: NodeId) -> Option<Node> {
-        self.nodes.read().unwrap().get(id.0).cloned()
+    /// Change a node's structure in place
+    ///
+    /// Copy-on-write: the node is only cloned if a reader still holds it, which never happens
+    /// while the tree is being built.
+    pub fn update(&self, id: NodeId, f: impl FnOnce(&mut Node)) {
+        if let Some(slot) = self.slots.write().unwrap().get_mut(id) {
+            f(Arc::make_mut(slot));
+        }
     }
 
-    /// Update a node in place
-    pub fn update(&self, id: NodeId, node: Node) {
-        if let Some(slot) = self.nodes.write().unwrap().get_mut(id.0) {
-            *slot = node;
+    /// Remove a node and everything under it, freeing their slots for reuse
+    ///
+    /// The node is unlinked from its parent, and the symbols and metadata of every removed
+    /// node are dropped. Returns how many nodes were removed.
+    pub fn remove(&self, id: NodeId) -> usize {
+        let removed = {
+            let mut slots = self.slots.write().unwrap();
+            let Some(parent) = slots.get(id).map(|node| node.parent) else {
+                return 0;
+            };
+            if let Some(parent) = parent.and_then(|p| slots.get_mut(p)) {
+                let parent = Arc::make_mut(parent);
+                parent.children = parent.children.iter().copied().filter(|&c| c != id).collect();
+            }
+
+            let mut removed = Vec::new();
+            let mut pending = vec![id];
+            while let Some(id) = pending.pop() {
+                let Some(node) = slots.get(id).cloned() else {
+                    continue;
+                };
+                pending.extend(node.children.iter().copied());
+                let slot = &mut slots.entries[id.index];
+                slot.node = None;
+                slot.generation = slot.generation.wrapping_add(1);
+                slots.free.push(id.index);
+                removed.push(id);
+            }
+            removed
+        };
+
+        for &id in &removed {
+            self.symbols.remove_node(id);
+            self.metadata.clear_node(id);
         }
+        removed.len()
     }
 
     /// Get all children of a node
-    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+    pub fn children(&self, id: NodeId) -> Arc<[NodeId]> {
         self.get(id)
-            .map(|n| n.children.to_vec())
-            .unwrap_or_default()
+            .map(|n| n.children.clone())
+            .unwrap_or_else(|| Arc::new([]))
     }
 
-    /// Total number of nodes
+    /// Project-wide symbol table populated during traversal
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Typed user metadata (tags, owner, layer, ...) attached to nodes
+    pub fn metadata(&self) -> &MetadataTable {
+        &self.metadata
+    }
+
+    /// Attach metadata from path rules to every matching node
+    pub fn apply_path_rules(&self, rules: &[PathRule]) {
+        if rules.is_empty() {
+            return;
+        }
+        for node in self.slots.read().unwrap().live() {
+            for rule in rules.iter().filter(|r| r.matches(&node.path)) {
+                self.metadata.apply_rule(node.id, rule);
+            }
+        }
+    }
+
+    /// Number of live nodes
     pub fn len(&self) -> usize {
-        self.nodes.read().unwrap().len()
+        let slots = self.slots.read().unwrap();
+        slots.entries.len() - slots.free.len()
     }
 
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
-    /// Iterate over all nodes with their IDs
-    pub fn iter(&self) -> impl Iterator<Item = (NodeId, Node)> {
-        self.nodes
+    /// Iterate over all live nodes with their IDs
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, Arc<Node>)> {
+        self.slots
             .read()
             .unwrap()
-            .iter()
-            .enumerate()
-            .map(|(i, n)| (NodeId(i), n.clone()))
+            .live()
+            .map(|n| (n.id, n.clone()))
             .collect::<Vec<_>>()
             .into_iter()
     }
@@ -110,18 +215,38 @@ impl SharedArena {
         self.inner.alloc(node)
     }
 
-    pub fn get(&self, id: NodeId) -> Option<Node> {
+    pub fn get(&self, id: NodeId) -> Option<Arc<Node>> {
         self.inner.get(id)
     }
 
-    pub fn update(&self, id: NodeId, node: Node) {
-        self.inner.update(id, node)
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.inner.contains(id)
     }
 
-    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+    pub fn update(&self, id: NodeId, f: impl FnOnce(&mut Node)) {
+        self.inner.update(id, f)
+    }
+
+    pub fn remove(&self, id: NodeId) -> usize {
+        self.inner.remove(id)
+    }
+
+    pub fn children(&self, id: NodeId) -> Arc<[NodeId]> {
         self.inner.children(id)
     }
 
+    pub fn symbols(&self) -> &SymbolTable {
+        self.inner.symbols()
+    }
+
+    pub fn metadata(&self) -> &MetadataTable {
+        self.inner.metadata()
+    }
+
+    pub fn apply_path_rules(&self, rules: &[PathRule]) {
+        self.inner.apply_path_rules(rules)
+    }
+
     pub fn len(&self) -> usize {
         self.inner.len()
     }
@@ -129,6 +254,10 @@ impl SharedArena {
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, Arc<Node>)> {
+        self.inner.iter()
+    }
 }
 
 impl Default for SharedArena {
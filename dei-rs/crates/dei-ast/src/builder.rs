@@ -1,19 +1,45 @@
 //! AST builder for constructing filesystem trees
 
 use dei_core::{error::Result, Error};
-use ignore::WalkBuilder;
-use std::path::Path;
-use std::sync::Arc;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use dashmap::DashMap;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use crate::{
     arena::SharedArena,
+    metadata::PathRule,
+    minified::is_minified,
     node::{Node, NodeId},
+    stamp::FileStamp,
+    vendored::is_vendor_dir,
 };
 
 /// Builds filesystem AST with smart filtering
 pub struct AstBuilder {
     arena: SharedArena,
     ignore_patterns: Vec<String>,
+    /// Gitignore-style globs, relative to the built root
+    include_globs: Vec<String>,
+    exclude_globs: Vec<String>,
+    path_rules: Vec<PathRule>,
+    /// When set, only these (canonical) files are added under directories
+    only_files: Option<HashSet<PathBuf>>,
+    include_minified: bool,
+    skipped_minified: Mutex<Vec<PathBuf>>,
+    /// Files larger than this many bytes are skipped
+    max_file_size: Option<u64>,
+    skipped_large: Mutex<Vec<PathBuf>>,
+    include_vendored: bool,
+    vendored: Mutex<Vec<PathBuf>>,
+    follow_symlinks: bool,
+    symlink_loops: Mutex<Vec<PathBuf>>,
+    /// Paths left out because another path reaches the same file
+    duplicates: Mutex<Vec<PathBuf>>,
+    /// Root of the last build, which globs are relative to
+    root: Mutex<Option<PathBuf>>,
 }
 
 impl AstBuilder {
@@ -21,14 +47,25 @@ impl AstBuilder {
         Self {
             arena: SharedArena::new(),
             ignore_patterns: Self::default_ignore_patterns(),
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            path_rules: Vec::new(),
+            only_files: None,
+            include_minified: false,
+            skipped_minified: Mutex::new(Vec::new()),
+            max_file_size: None,
+            skipped_large: Mutex::new(Vec::new()),
+            include_vendored: false,
+            vendored: Mutex::new(Vec::new()),
+            follow_symlinks: false,
+            symlink_loops: Mutex::new(Vec::new()),
+            duplicates: Mutex::new(Vec::new()),
+            root: Mutex::new(None),
         }
     }
 
     pub fn with_arena(arena: SharedArena) -> Self {
-        Self {
-            arena,
-            ignore_patterns: Self::default_ignore_patterns(),
-        }
+        Self { arena, ..Self::new() }
     }
 
     /// Default patterns to ignore (build artifacts, etc.)
@@ -49,65 +86,285 @@ impl AstBuilder {
         self.ignore_patterns.push(pattern);
     }
 
+    /// Only add files matching one of these globs (`src/**`, `*.py`); directories are still walked
+    pub fn add_include_glob(&mut self, glob: String) {
+        self.include_globs.push(glob);
+    }
+
+    /// Skip files and whole directories matching this glob (`tests/`, `**/generated/**`)
+    pub fn add_exclude_glob(&mut self, glob: String) {
+        self.exclude_globs.push(glob);
+    }
+
+    /// Attach metadata to matching nodes once the tree is built
+    pub fn add_path_rule(&mut self, rule: PathRule) {
+        self.path_rules.push(rule);
+    }
+
+    /// Analyze minified/compiled JS and CSS instead of skipping it
+    pub fn include_minified(&mut self, include: bool) {
+        self.include_minified = include;
+    }
+
+    /// Minified files left out of the last build
+    pub fn skipped_minified(&self) -> Vec<PathBuf> {
+        self.skipped_minified.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Skip files larger than `bytes`; no limit by default
+    pub fn max_file_size(&mut self, bytes: Option<u64>) {
+        self.max_file_size = bytes;
+    }
+
+    /// Files over the size limit left out of the last build
+    pub fn skipped_large(&self) -> Vec<PathBuf> {
+        self.skipped_large.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Analyze vendored directories (`vendor/`, `third_party/`) instead of skipping them
+    pub fn include_vendored(&mut self, include: bool) {
+        self.include_vendored = include;
+    }
+
+    /// Vendored directories found by the last build, analyzed or not
+    pub fn vendored_dirs(&self) -> Vec<PathBuf> {
+        self.vendored.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Whether a path lies in one of the vendored directories found by the last build
+    pub fn is_vendored(&self, path: &Path) -> bool {
+        self.vendored
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|dir| path.starts_with(dir))
+    }
+
+    /// Follow symbolic links to files and directories instead of skipping them
+    pub fn follow_symlinks(&mut self, follow: bool) {
+        self.follow_symlinks = follow;
+    }
+
+    /// Symbolic links skipped by the last build because they lead back into their own ancestor
+    pub fn symlink_loops(&self) -> Vec<PathBuf> {
+        self.symlink_loops.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Paths skipped by the last build because another path (a symlink or hard link) reaches
+    /// the same file, which is analyzed once
+    pub fn skipped_duplicates(&self) -> Vec<PathBuf> {
+        self.duplicates.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Skip every file under the analyzed directory except these
+    pub fn restrict_to_files(&mut self, files: impl IntoIterator<Item = PathBuf>) {
+        let files = files.into_iter().filter_map(|f| f.canonicalize().ok());
+        self.only_files.get_or_insert_with(HashSet::new).extend(files);
+    }
+
     /// Build AST from a directory path
     pub fn build(&self, root: &Path) -> Result<NodeId> {
         if !root.exists() {
             return Err(Error::PathNotFound(root.to_path_buf()));
         }
 
+        let _span = tracing::info_span!("build", root = %root.display()).entered();
+        *self.root.lock().unwrap_or_else(|e| e.into_inner()) = Some(root.to_path_buf());
         let root_id = if root.is_dir() {
-            self.build_directory(root, 0, None)?
+            self.build_directory(root, 0, None, &self.globs(root)?)?
         } else {
             self.build_file(root, 0, None)?
         };
 
+        self.arena.apply_path_rules(&self.path_rules);
+
         Ok(root_id)
     }
 
-    fn build_directory(&self, path: &Path, depth: usize, parent: Option<NodeId>) -> Result<NodeId> {
-        let node = Node::new_directory(NodeId(0), path.to_path_buf(), depth);
+    /// Rebuild the subtree at `node_id` from disk after files under it changed
+    ///
+    /// The old subtree is removed from the arena, so its IDs stop resolving, and the new one
+    /// takes its place under the same parent. Returns `None` if the path no longer exists.
+    pub fn rebuild(&self, node_id: NodeId) -> Result<Option<NodeId>> {
+        let node = self
+            .arena
+            .get(node_id)
+            .ok_or_else(|| Error::Analysis(format!("Node {:?} not found", node_id)))?;
+        self.arena.remove(node_id);
+
+        let path = PathBuf::from(node.path.as_ref());
+        let forget = |paths: &Mutex<Vec<PathBuf>>| {
+            paths.lock().unwrap_or_else(|e| e.into_inner()).retain(|p| !p.starts_with(&path));
+        };
+        forget(&self.skipped_minified);
+        forget(&self.skipped_large);
+        forget(&self.symlink_loops);
+        forget(&self.duplicates);
+        forget(&self.vendored);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let root = self.root.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let globs = self.globs(root.as_deref().unwrap_or(&path))?;
+        let new_id = if path.is_dir() {
+            self.build_directory(&path, node.depth, node.parent, &globs)?
+        } else if self.is_selected(&path)
+            && globs.includes(&path)
+            && (self.include_minified || !is_minified(&path))
+            && !self.is_too_large(&path, std::fs::metadata(&path).map(|m| m.len()).unwrap_or_default())
+        {
+            self.build_file(&path, node.depth, node.parent)?
+        } else {
+            return Ok(None);
+        };
+        if let Some(parent) = node.parent {
+            self.arena.update(parent, |p| p.children = p.children.iter().copied().chain([new_id]).collect());
+        }
+        self.arena.apply_path_rules(&self.path_rules);
+
+        Ok(Some(new_id))
+    }
+
+    fn globs(&self, root: &Path) -> Result<PathGlobs> {
+        Ok(PathGlobs {
+            include: compile_globs(root, &self.include_globs)?,
+            exclude: compile_globs(root, &self.exclude_globs)?,
+        })
+    }
+
+    /// Walk the directory with the parallel walker, adding nodes to the arena as entries arrive
+    fn build_directory(&self, path: &Path, depth: usize, parent: Option<NodeId>, globs: &PathGlobs) -> Result<NodeId> {
+        let mut node = Node::new_directory(NodeId::new(0), path.to_path_buf(), depth);
+        node.parent = parent;
         let node_id = self.arena.alloc(node);
 
-        let mut children = Vec::new();
+        let root = self.root.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let tree = WalkedTree {
+            root: root.as_deref().unwrap_or(path).canonicalize()?,
+            ..WalkedTree::default()
+        };
+        tree.dirs.insert(path.to_path_buf(), node_id);
+        let error = Mutex::new(None);
 
-        // Use ignore crate for smart traversal
-        for entry in WalkBuilder::new(path)
-            .max_depth(Some(1))
+        // The walker hands out a directory before reading it, so a parent's node always
+        // exists by the time its entries arrive
+        WalkBuilder::new(path)
             .hidden(false)
-            .build()
-            .skip(1) // Skip root itself
-        {
-            let entry = entry.map_err(|e| Error::Io(std::io::Error::other(e.to_string())))?;
-            let entry_path = entry.path();
+            .follow_links(self.follow_symlinks)
+            .build_parallel()
+            .run(|| {
+                Box::new(|entry| {
+                    let entry = match entry {
+                        Ok(entry) if entry.depth() == 0 => return WalkState::Continue,
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            if let Some(link) = symlink_loop(&e) {
+                                let link = link.to_path_buf();
+                                self.symlink_loops.lock().unwrap_or_else(|e| e.into_inner()).push(link);
+                                return WalkState::Continue;
+                            }
+                            let e = Error::Io(std::io::Error::other(e.to_string()));
+                            error.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(e);
+                            return WalkState::Quit;
+                        }
+                    };
+                    self.add_entry(&entry, depth, globs, &tree)
+                })
+            });
+        if let Some(e) = error.into_inner().unwrap_or_else(|e| e.into_inner()) {
+            return Err(e);
+        }
 
-            // Skip ignored patterns
-            if self.should_ignore(entry_path) {
-                continue;
+        // A file reached by several paths is kept once: at a path that isn't itself a
+        // symlink if there is one, otherwise at the first path in order
+        let mut duplicates = HashSet::new();
+        for (_, mut paths) in tree.files.into_iter().filter(|(_, paths)| paths.len() > 1) {
+            paths.sort_by(|(a_link, a, _), (b_link, b, _)| (a_link, a).cmp(&(b_link, b)));
+            for (_, path, id) in paths.into_iter().skip(1) {
+                self.arena.remove(id);
+                self.duplicates.lock().unwrap_or_else(|e| e.into_inner()).push(path);
+                duplicates.insert(id);
             }
+        }
 
-            let child_id = if entry_path.is_dir() {
-                self.build_directory(entry_path, depth + 1, Some(node_id))?
-            } else {
-                self.build_file(entry_path, depth + 1, Some(node_id))?
-            };
+        // Entries arrive in no particular order; keep siblings sorted so the tree is stable
+        for (dir, mut children) in tree.children {
+            children.retain(|(_, id)| !duplicates.contains(id));
+            children.sort_by_key(|(path, _)| path.clone());
+            self.arena.update(dir, |node| node.children = children.into_iter().map(|(_, id)| id).collect());
+        }
+
+        Ok(node_id)
+    }
+
+    /// Allocate a node for one walked entry, or say why the walk shouldn't go into it
+    fn add_entry(&self, entry: &DirEntry, base_depth: usize, globs: &PathGlobs, tree: &WalkedTree) -> WalkState {
+        let entry_path = entry.path();
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+        let Some(parent) = entry_path.parent().and_then(|p| tree.dirs.get(p).map(|id| *id)) else {
+            return WalkState::Skip;
+        };
+
+        // Skip ignored patterns
+        if self.should_ignore(entry_path) || globs.excludes(entry_path) {
+            return WalkState::Skip;
+        }
 
-            children.push(child_id);
+        // Linked directories inside the root are walked at their real path anyway
+        if entry.path_is_symlink()
+            && (!self.follow_symlinks || (is_dir && entry_path.canonicalize().is_ok_and(|p| p.starts_with(&tree.root))))
+        {
+            return WalkState::Skip;
         }
 
-        // Update node with children
-        if let Some(mut node) = self.arena.get(node_id) {
-            node = node.with_children(children.into());
-            if let Some(parent_id) = parent {
-                node = node.with_parent(parent_id);
+        if is_dir && is_vendor_dir(entry_path) {
+            self.vendored.lock().unwrap_or_else(|e| e.into_inner()).push(entry_path.to_path_buf());
+            if !self.include_vendored {
+                return WalkState::Skip;
             }
-            self.arena.update(node_id, node);
         }
 
-        Ok(node_id)
+        let depth = base_depth + entry.depth();
+        let child_id = if is_dir {
+            let mut node = Node::new_directory(NodeId::new(0), entry_path.to_path_buf(), depth);
+            node.parent = Some(parent);
+            let id = self.arena.alloc(node);
+            tree.dirs.insert(entry_path.to_path_buf(), id);
+            id
+        } else if !self.is_selected(entry_path) || !globs.includes(entry_path) {
+            return WalkState::Continue;
+        } else if !self.include_minified && is_minified(entry_path) {
+            self.skipped_minified
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(entry_path.to_path_buf());
+            return WalkState::Continue;
+        } else if self.is_too_large(entry_path, entry.metadata().map(|m| m.len()).unwrap_or_default()) {
+            return WalkState::Continue;
+        } else {
+            let node = Self::file_node(entry_path, depth).with_parent(parent);
+            let id = self.arena.alloc(node);
+            if let Some(key) = file_key(entry_path) {
+                let path = (entry.path_is_symlink(), entry_path.to_path_buf(), id);
+                tree.files.entry(key).or_default().push(path);
+            }
+            id
+        };
+
+        tree.children.entry(parent).or_default().push((entry_path.to_path_buf(), child_id));
+        WalkState::Continue
+    }
+
+    /// A file node, stamped if it is source code
+    fn file_node(path: &Path, depth: usize) -> Node {
+        let node = Node::new_file(NodeId::new(0), path.to_path_buf(), depth);
+        let stamp = node.language().and_then(|_| FileStamp::read(path).ok());
+        node.with_stamp(stamp)
     }
 
     fn build_file(&self, path: &Path, depth: usize, parent: Option<NodeId>) -> Result<NodeId> {
-        let mut node = Node::new_file(NodeId(0), path.to_path_buf(), depth);
+        let mut node = Self::file_node(path, depth);
         
         if let Some(parent_id) = parent {
             node = node.with_parent(parent_id);
@@ -117,6 +374,22 @@ impl AstBuilder {
         Ok(node_id)
     }
 
+    /// Whether a file of `len` bytes is over the size limit, recording it as skipped if so
+    fn is_too_large(&self, path: &Path, len: u64) -> bool {
+        let too_large = self.max_file_size.is_some_and(|max| len > max);
+        if too_large {
+            self.skipped_large.lock().unwrap_or_else(|e| e.into_inner()).push(path.to_path_buf());
+        }
+        too_large
+    }
+
+    fn is_selected(&self, path: &Path) -> bool {
+        match &self.only_files {
+            Some(only) => path.canonicalize().is_ok_and(|p| only.contains(&p)),
+            None => true,
+        }
+    }
+
     fn should_ignore(&self, path: &Path) -> bool {
         path.components().any(|c| {
             if let Some(name) = c.as_os_str().to_str() {
@@ -132,6 +405,81 @@ impl AstBuilder {
     }
 }
 
+/// Nodes created so far by one parallel walk
+#[derive(Default)]
+struct WalkedTree {
+    /// Canonical root of the build
+    root: PathBuf,
+    dirs: DashMap<PathBuf, NodeId>,
+    children: DashMap<NodeId, Vec<(PathBuf, NodeId)>>,
+    /// Every path walked to each file, and whether that path is a symlink
+    files: DashMap<FileKey, Vec<(bool, PathBuf, NodeId)>>,
+}
+
+/// Identity of a file on disk, shared by every path that reaches it
+#[cfg(unix)]
+type FileKey = (u64, u64);
+#[cfg(not(unix))]
+type FileKey = PathBuf;
+
+fn file_key(path: &Path) -> Option<FileKey> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        path.canonicalize().ok()
+    }
+}
+
+/// The link that leads back into its own ancestor, if `error` is a symlink loop
+fn symlink_loop(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => symlink_loop(err),
+        _ => None,
+    }
+}
+
+/// `--include`/`--exclude` globs compiled against the built root
+struct PathGlobs {
+    include: Option<Gitignore>,
+    exclude: Option<Gitignore>,
+}
+
+impl PathGlobs {
+    fn excludes(&self, path: &Path) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|m| m.matched_path_or_any_parents(path, path.is_dir()).is_ignore())
+    }
+
+    /// Files only; without include globs everything is included
+    fn includes(&self, path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|m| m.matched_path_or_any_parents(path, false).is_ignore())
+    }
+}
+
+fn compile_globs(root: &Path, globs: &[String]) -> Result<Option<Gitignore>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    for glob in globs {
+        builder
+            .add_line(None, glob)
+            .map_err(|e| Error::Config(format!("invalid glob '{}': {}", glob, e)))?;
+    }
+    let matcher = builder.build().map_err(|e| Error::Config(e.to_string()))?;
+    Ok(Some(matcher))
+}
+
 impl Default for AstBuilder {
     fn default() -> Self {
         Self::new()
@@ -2,18 +2,39 @@
 //! 
 //! Uses arena allocation for cache-friendly memory layout and zero-copy operations
 
+pub mod analysis;
 pub mod arena;
 pub mod node;
 pub mod builder;
+pub mod metadata;
+pub mod minified;
+pub mod profiling;
+pub mod rules;
+pub mod progress;
+pub mod simulation;
+pub mod stamp;
+pub mod state;
+pub mod symbols;
 pub mod traverser;
+pub mod vendored;
 pub mod visitor;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
 
+pub use analysis::{analyze_file, analyze_file_with, analyze_metrics_with, FileAnalysis};
 pub use arena::Arena;
 pub use node::{Node, NodeId, NodeKind};
 pub use builder::AstBuilder;
+pub use metadata::{Layer, MetadataTable, Owner, PathRule, Tags};
+pub use profiling::{Phase, SlowFile, TimeBudget};
+pub use rules::ScriptRule;
+pub use progress::ProgressObserver;
+pub use simulation::{simulate_split, SimulatedSplit};
+pub use stamp::FileStamp;
+pub use state::SavedState;
+pub use symbols::{Symbol, SymbolKind, SymbolTable};
 pub use traverser::ParallelTraverser;
 pub use visitor::Visitor;
 
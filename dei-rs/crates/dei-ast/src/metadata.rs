@@ -0,0 +1,116 @@
+//! Typed user metadata attached to arena nodes
+//!
+//! A type-map side table keyed by node: any `Send + Sync` value can be stored
+//! per node and read back by type, without growing `Node` itself. The `[[metadata]]` path
+//! rules of `dei.toml` fill in [`Tags`], [`Owner`] and [`Layer`] this way.
+
+use dei_core::models::PathMetadata;
+use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use crate::node::NodeId;
+
+pub use dei_core::config::PathRule;
+
+/// Free-form labels (e.g. `generated`, `legacy`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tags(pub Vec<Arc<str>>);
+
+impl Tags {
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|t| t.as_ref() == tag)
+    }
+}
+
+/// Team or person responsible for a node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Owner(pub Arc<str>);
+
+/// Architectural layer a node belongs to (e.g. `ui`, `service`, `data`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layer(pub Arc<str>);
+
+/// Side table mapping `(node, type)` to a value
+#[derive(Default)]
+pub struct MetadataTable {
+    entries: DashMap<(NodeId, TypeId), Arc<dyn Any + Send + Sync>>,
+}
+
+impl MetadataTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach a value to a node, replacing any previous value of the same type
+    pub fn insert<T: Any + Send + Sync>(&self, id: NodeId, value: T) {
+        self.entries.insert((id, TypeId::of::<T>()), Arc::new(value));
+    }
+
+    /// Read a value of type `T` attached to a node
+    pub fn get<T: Any + Send + Sync + Clone>(&self, id: NodeId) -> Option<T> {
+        self.entries
+            .get(&(id, TypeId::of::<T>()))
+            .and_then(|v| v.downcast_ref::<T>().cloned())
+    }
+
+    pub fn contains<T: Any + Send + Sync>(&self, id: NodeId) -> bool {
+        self.entries.contains_key(&(id, TypeId::of::<T>()))
+    }
+
+    pub fn remove<T: Any + Send + Sync>(&self, id: NodeId) {
+        self.entries.remove(&(id, TypeId::of::<T>()));
+    }
+
+    /// Apply a path rule's metadata to a node (tags accumulate, owner/layer overwrite)
+    pub fn apply_rule(&self, id: NodeId, rule: &PathRule) {
+        if !rule.tags.is_empty() {
+            let mut tags = self.get::<Tags>(id).unwrap_or_default();
+            for tag in &rule.tags {
+                if !tags.contains(tag) {
+                    tags.0.push(tag.clone());
+                }
+            }
+            self.insert(id, tags);
+        }
+        if let Some(owner) = &rule.owner {
+            self.insert(id, Owner(owner.clone()));
+        }
+        if let Some(layer) = &rule.layer {
+            self.insert(id, Layer(layer.clone()));
+        }
+    }
+
+    /// The tags, owner and layer path rules gave a node, as analysis results carry them
+    pub fn path_metadata(&self, id: NodeId) -> PathMetadata {
+        PathMetadata {
+            tags: self.get::<Tags>(id).map(|t| t.0.into()).unwrap_or_default(),
+            owner: self.get::<Owner>(id).map(|o| o.0),
+            layer: self.get::<Layer>(id).map(|l| l.0),
+        }
+    }
+
+    /// Drop every value attached to a node
+    pub fn clear_node(&self, id: NodeId) {
+        self.entries.retain(|(node, _), _| *node != id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Debug for MetadataTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetadataTable")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
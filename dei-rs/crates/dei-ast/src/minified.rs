@@ -0,0 +1,71 @@
+//! Minified and compiled front-end asset detection
+//!
+//! Bundles are one enormous "class" per file as far as the parsers can tell,
+//! which produces absurd findings and most of the runtime on front-end repos.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Extensions of files that bundlers and minifiers emit
+const ASSET_EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "css"];
+
+/// Bytes sampled from the start of a file
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Hand-written code rarely averages this many characters per line
+const MAX_AVERAGE_LINE: usize = 200;
+
+/// A single line this long is almost always generated
+const MAX_LINE: usize = 1000;
+
+/// Whether a JS/CSS file looks minified or compiled
+///
+/// Signals: a `.min.` name, a sibling `.map` file, a `sourceMappingURL`
+/// comment, or very long lines in the first 64 KiB.
+pub fn is_minified(path: &Path) -> bool {
+    let is_asset = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| ASSET_EXTENSIONS.contains(&ext));
+    if !is_asset {
+        return false;
+    }
+
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    if name.contains(".min.") || has_source_map(path) {
+        return true;
+    }
+
+    let mut sample = String::new();
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    if file.take(SAMPLE_BYTES).read_to_string(&mut sample).is_err() {
+        // Invalid UTF-8 inside a JS/CSS sample is itself a strong bundle signal
+        return true;
+    }
+
+    looks_minified(&sample)
+}
+
+fn has_source_map(path: &Path) -> bool {
+    let mut map = path.as_os_str().to_owned();
+    map.push(".map");
+    Path::new(&map).is_file()
+}
+
+fn looks_minified(sample: &str) -> bool {
+    if sample.contains("sourceMappingURL=") {
+        return true;
+    }
+
+    let lines: Vec<&str> = sample.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return false;
+    }
+
+    let longest = lines.iter().map(|l| l.len()).max().unwrap_or_default();
+    let average = lines.iter().map(|l| l.len()).sum::<usize>() / lines.len();
+    longest > MAX_LINE || average > MAX_AVERAGE_LINE
+}
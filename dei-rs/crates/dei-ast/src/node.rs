@@ -3,13 +3,39 @@
 use dei_core::{metrics::*, models::*};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+
+use crate::stamp::FileStamp;
 
 /// Node identifier using generational indexing for safety
+///
+/// The generation changes whenever a slot is freed, so the ID of a removed node never
+/// resolves again, even once its slot is reused.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct NodeId(pub usize);
+pub struct NodeId {
+    pub(crate) index: usize,
+    pub(crate) generation: u32,
+}
+
+impl NodeId {
+    /// First-generation ID for slot `index`; [`Arena::alloc`](crate::Arena::alloc) assigns the real one
+    pub const fn new(index: usize) -> Self {
+        Self { index, generation: 0 }
+    }
+
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
 
 /// AST node representing file system or code structure
+///
+/// The tree structure is fixed once built; analysis results are filled in through `&self`
+/// during traversal, so nodes can be shared across threads instead of copied.
 #[derive(Debug, Clone)]
 pub struct Node {
     pub id: NodeId,
@@ -19,11 +45,37 @@ pub struct Node {
     pub depth: usize,
     pub parent: Option<NodeId>,
     pub children: Arc<[NodeId]>,
+    /// How a source file looked when the tree was built
+    pub stamp: Option<FileStamp>,
     
     // Analysis results (populated during traversal)
-    pub file_metrics: Option<FileMetrics>,
-    pub analysis_results: Arc<[AnalysisResult]>,
-    pub god_file_result: Option<GodFileResult>,
+    file_metrics: Slot<Option<Arc<FileMetrics>>>,
+    analysis_results: Slot<Arc<[AnalysisResult]>>,
+    god_file_result: Slot<Option<Arc<GodFileResult>>>,
+}
+
+/// A field set after the node is shared; reads hand out cheap `Arc` clones
+#[derive(Debug, Default)]
+struct Slot<T>(RwLock<T>);
+
+impl<T: Clone> Slot<T> {
+    fn new(value: T) -> Self {
+        Self(RwLock::new(value))
+    }
+
+    fn get(&self) -> T {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set(&self, value: T) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = value;
+    }
+}
+
+impl<T: Clone> Clone for Slot<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -48,9 +100,10 @@ impl Node {
             depth,
             parent: None,
             children: Arc::new([]),
-            file_metrics: None,
-            analysis_results: Arc::new([]),
-            god_file_result: None,
+            stamp: None,
+            file_metrics: Slot::new(None),
+            analysis_results: Slot::new(Arc::new([])),
+            god_file_result: Slot::new(None),
         }
     }
 
@@ -69,9 +122,10 @@ impl Node {
             depth,
             parent: None,
             children: Arc::new([]),
-            file_metrics: None,
-            analysis_results: Arc::new([]),
-            god_file_result: None,
+            stamp: None,
+            file_metrics: Slot::new(None),
+            analysis_results: Slot::new(Arc::new([])),
+            god_file_result: Slot::new(None),
         }
     }
 
@@ -96,8 +150,34 @@ impl Node {
     }
 
     pub fn has_issues(&self) -> bool {
-        self.analysis_results.iter().any(|r| r.has_issues())
-            || self.god_file_result.as_ref().is_some()
+        self.analysis_results().iter().any(|r| r.has_issues())
+            || self.god_file_result().is_some()
+    }
+
+    /// Metrics from the parse phase, once the file is parsed
+    pub fn file_metrics(&self) -> Option<Arc<FileMetrics>> {
+        self.file_metrics.get()
+    }
+
+    /// One result per class, once the file is analyzed
+    pub fn analysis_results(&self) -> Arc<[AnalysisResult]> {
+        self.analysis_results.get()
+    }
+
+    pub fn god_file_result(&self) -> Option<Arc<GodFileResult>> {
+        self.god_file_result.get()
+    }
+
+    pub fn set_file_metrics(&self, metrics: Arc<FileMetrics>) {
+        self.file_metrics.set(Some(metrics));
+    }
+
+    pub fn set_analysis_results(&self, results: Arc<[AnalysisResult]>) {
+        self.analysis_results.set(results);
+    }
+
+    pub fn set_god_file_result(&self, result: GodFileResult) {
+        self.god_file_result.set(Some(Arc::new(result)));
     }
 
     pub fn with_children(mut self, children: Arc<[NodeId]>) -> Self {
@@ -105,23 +185,28 @@ impl Node {
         self
     }
 
+    pub fn with_stamp(mut self, stamp: Option<FileStamp>) -> Self {
+        self.stamp = stamp;
+        self
+    }
+
     pub fn with_parent(mut self, parent: NodeId) -> Self {
         self.parent = Some(parent);
         self
     }
 
-    pub fn with_file_metrics(mut self, metrics: FileMetrics) -> Self {
-        self.file_metrics = Some(metrics);
+    pub fn with_file_metrics(self, metrics: FileMetrics) -> Self {
+        self.set_file_metrics(Arc::new(metrics));
         self
     }
 
-    pub fn with_analysis_results(mut self, results: Arc<[AnalysisResult]>) -> Self {
-        self.analysis_results = results;
+    pub fn with_analysis_results(self, results: Arc<[AnalysisResult]>) -> Self {
+        self.set_analysis_results(results);
         self
     }
 
-    pub fn with_god_file_result(mut self, result: GodFileResult) -> Self {
-        self.god_file_result = Some(result);
+    pub fn with_god_file_result(self, result: GodFileResult) -> Self {
+        self.set_god_file_result(result);
         self
     }
 }
@@ -0,0 +1,60 @@
+//! Per-file time budget
+//!
+//! One pathological input (minified bundles, generated tables) can dominate a
+//! run. Files that overrun the budget are recorded and, optionally, dropped.
+
+use dei_core::models::Language;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Time allowed for a single file in each phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeBudget {
+    pub max_duration: Duration,
+    /// Drop results of files over budget instead of only recording them
+    pub skip_slow: bool,
+}
+
+impl TimeBudget {
+    pub fn new(max_duration: Duration) -> Self {
+        Self {
+            max_duration,
+            skip_slow: false,
+        }
+    }
+
+    pub fn with_skip_slow(mut self, skip_slow: bool) -> Self {
+        self.skip_slow = skip_slow;
+        self
+    }
+
+    pub fn is_exceeded(&self, elapsed: Duration) -> bool {
+        elapsed > self.max_duration
+    }
+}
+
+impl Default for TimeBudget {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+/// Traversal phase a file overran its budget in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Phase {
+    Parse,
+    Analyze,
+}
+
+/// A file that took longer than its [`TimeBudget`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowFile {
+    pub path: PathBuf,
+    pub language: Option<Language>,
+    pub bytes: u64,
+    pub phase: Phase,
+    pub elapsed: Duration,
+    /// Whether its results were dropped
+    pub skipped: bool,
+}
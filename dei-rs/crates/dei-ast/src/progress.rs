@@ -0,0 +1,35 @@
+//! Progress reporting for long traversals
+
+use dei_core::models::AnalysisResult;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Told about a [`ParallelTraverser`](crate::ParallelTraverser) run as it goes; every method
+/// does nothing by default
+///
+/// Calls come from worker threads, in no particular order across files.
+pub trait ProgressObserver: Send + Sync {
+    /// Files in the selected languages found under the root, before any is parsed
+    fn files_discovered(&self, _count: usize) {}
+
+    /// A file was parsed, failed to parse, or was skipped for overrunning its time budget
+    fn file_parsed(&self, _path: &Path) {}
+
+    /// A file was analyzed; `results` is empty when it was skipped for overrunning its time budget
+    fn file_analyzed(&self, _path: &Path, _results: &[AnalysisResult]) {}
+}
+
+/// Passes every event on to each observer in turn
+impl ProgressObserver for Vec<Arc<dyn ProgressObserver>> {
+    fn files_discovered(&self, count: usize) {
+        self.iter().for_each(|o| o.files_discovered(count));
+    }
+
+    fn file_parsed(&self, path: &Path) {
+        self.iter().for_each(|o| o.file_parsed(path));
+    }
+
+    fn file_analyzed(&self, path: &Path, results: &[AnalysisResult]) {
+        self.iter().for_each(|o| o.file_analyzed(path, results));
+    }
+}
@@ -0,0 +1,220 @@
+//! Custom rules written in Rhai
+//!
+//! Every `*.rhai` file in a rules directory (`.dei/rules/` next to `dei.toml`, by convention)
+//! becomes a [`Detector`] named after the file. A rule defines `inspect(class)`, which gets the
+//! class metrics as a map and returns what it found:
+//!
+//! ```rhai
+//! fn inspect(class) {
+//!     if class.name.ends_with("Manager") && class.method_count > 10 {
+//!         return `${class.name} is a Manager with ${class.method_count} methods`;
+//!     }
+//! }
+//! ```
+//!
+//! Nothing (`()` or `false`) passes the class; a string, a map with a `message` (and optionally
+//! `severity`, `actual` and `threshold`) or an array of either flags it. Rules run in a sandbox
+//! with an operation limit, so a runaway loop fails the rule rather than hanging the analysis.
+//!
+//! A rule may define `inspect_file(file)` as well, or instead, to judge a file's imports
+//! (`path`, `imports`, `imported_symbols`, `wildcard_imports`, ...). What it flags is reported
+//! with the file's import hygiene, so a rule can stand in for the built-in `import_hygiene`
+//! detector once `[smells]` turns that off.
+//!
+//! Both maps carry the `tags`, `owner` and `layer` that `[[metadata]]` path rules give the
+//! file, so a rule can hold `legacy` code to looser limits or skip `generated` code.
+
+use dei_core::{
+    detector::{Context, Detector, Finding},
+    error::Result,
+    metrics::{ClassMetrics, FileMetrics, MethodMetrics},
+    models::{Severity, Violation},
+    Error,
+};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Conventional location of rule scripts, relative to the config directory
+pub const RULES_DIR: &str = ".dei/rules";
+
+/// Operations a rule may run per class before it is stopped
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A custom rule loaded from a Rhai script
+pub struct ScriptRule {
+    name: Arc<str>,
+    engine: Engine,
+    ast: AST,
+    inspects_classes: bool,
+    inspects_files: bool,
+}
+
+impl ScriptRule {
+    /// Compile the rule in `path`, named after the file
+    pub fn load(path: &Path) -> Result<Self> {
+        let name: Arc<str> = path
+            .file_stem()
+            .map_or("rule".into(), |s| s.to_string_lossy().into());
+        let source = fs::read_to_string(path)?;
+        let invalid = |message: String| Error::Parse { path: path.to_path_buf(), message };
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        // Debug builds of Rhai default to shallower limits than release builds
+        engine.set_max_expr_depths(64, 32);
+        // Stdout may be carrying a JSON report
+        engine.on_print(|text| eprintln!("{}", text));
+        let ast = engine.compile(&source).map_err(|e| invalid(e.to_string()))?;
+        let defines = |name: &str| ast.iter_functions().any(|f| f.name == name && f.params.len() == 1);
+        let (inspects_classes, inspects_files) = (defines("inspect"), defines("inspect_file"));
+        if !inspects_classes && !inspects_files {
+            return Err(invalid("defines no `inspect(class)` or `inspect_file(file)` function".into()));
+        }
+
+        Ok(Self { name, engine, ast, inspects_classes, inspects_files })
+    }
+
+    /// Every rule in `dir`, in file name order; none if the directory doesn't exist
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>> {
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+        paths.iter().map(|path| Self::load(path)).collect()
+    }
+
+    /// Findings, made by `finding`, from what `function` returned for `argument`
+    fn call(&self, function: &str, argument: Map, finding: fn(Violation) -> Finding) -> Vec<Finding> {
+        let mut scope = Scope::new();
+        let returned = match self.engine.call_fn::<Dynamic>(&mut scope, &self.ast, function, (argument,)) {
+            Ok(returned) => returned,
+            // A broken rule is reported where it broke rather than passing silently
+            Err(e) => {
+                let failed = Violation::from_rule(self.name.clone(), format!("rule failed: {}", e));
+                return vec![finding(Violation { severity: Severity::Error, ..failed })];
+            }
+        };
+        let returned = if returned.is_array() { returned.cast::<Array>() } else { vec![returned] };
+        returned
+            .into_iter()
+            .filter_map(|item| self.violation(item))
+            .map(finding)
+            .collect()
+    }
+
+    fn violation(&self, item: Dynamic) -> Option<Violation> {
+        if item.is_unit() || item.as_bool() == Ok(false) {
+            return None;
+        }
+        if item.is_string() {
+            return Some(Violation::from_rule(self.name.clone(), item.to_string()));
+        }
+        let Some(map) = item.clone().try_cast::<Map>() else {
+            return Some(Violation::from_rule(self.name.clone(), item.to_string()));
+        };
+
+        let message = map.get("message").map_or_else(|| self.name.to_string(), |m| m.to_string());
+        let mut violation = Violation::from_rule(self.name.clone(), message);
+        let count = |key: &str| map.get(key).and_then(|v| v.as_int().ok()).map(|n| n.max(0) as usize);
+        violation.actual = count("actual").unwrap_or(violation.actual);
+        violation.threshold = count("threshold").unwrap_or(violation.threshold);
+        if let Some(severity) = map.get("severity").and_then(|s| s.to_string().parse::<Severity>().ok()) {
+            violation.severity = severity;
+        }
+        Some(violation)
+    }
+}
+
+impl Detector for ScriptRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        if !self.inspects_classes {
+            return Vec::new();
+        }
+        self.call("inspect", class_map(class, ctx), Finding::Class)
+    }
+
+    fn inspect_file(&self, file: &FileMetrics, ctx: &Context) -> Vec<Finding> {
+        if !self.inspects_files {
+            return Vec::new();
+        }
+        self.call("inspect_file", file_map(file, ctx), Finding::Imports)
+    }
+}
+
+fn class_map(class: &ClassMetrics, ctx: &Context) -> Map {
+    let mut map = Map::new();
+    map.insert("name".into(), class.name.to_string().into());
+    map.insert("qualified_name".into(), class.fully_qualified_name.to_string().into());
+    map.insert("file_path".into(), class.file_path.to_string().into());
+    map.insert("lines".into(), count(class.lines.0));
+    map.insert("method_count".into(), count(class.method_count.0));
+    map.insert("property_count".into(), count(class.property_count));
+    map.insert("field_count".into(), count(class.field_count));
+    map.insert("complexity".into(), count(class.complexity.0));
+    map.insert("is_abstract".into(), class.is_abstract.into());
+    map.insert("dependencies".into(), strings(&class.dependencies));
+    map.insert("lcom4".into(), ctx.lcom4.map_or(Dynamic::UNIT, count));
+    insert_metadata(&mut map, ctx);
+    let methods: Array = class.methods.iter().map(|m| Dynamic::from_map(method_map(m))).collect();
+    map.insert("methods".into(), methods.into());
+    map
+}
+
+fn file_map(file: &FileMetrics, ctx: &Context) -> Map {
+    let mut map = Map::new();
+    map.insert("path".into(), file.path.to_string().into());
+    map.insert("lines".into(), count(file.lines.0));
+    map.insert("class_count".into(), count(file.classes.len()));
+    map.insert("top_level_functions".into(), count(file.top_level_functions));
+    map.insert("imports".into(), count(file.imports));
+    map.insert("imported_symbols".into(), count(file.imported_symbols));
+    map.insert("wildcard_imports".into(), strings(&file.wildcard_imports));
+    insert_metadata(&mut map, ctx);
+    map
+}
+
+/// `tags`, `owner` and `layer` from the `[[metadata]]` path rules; `()` where none says
+fn insert_metadata(map: &mut Map, ctx: &Context) {
+    let metadata = ctx.metadata.cloned().unwrap_or_default();
+    let text = |value: Option<Arc<str>>| value.map_or(Dynamic::UNIT, |v| v.to_string().into());
+    map.insert("tags".into(), strings(&metadata.tags));
+    map.insert("owner".into(), text(metadata.owner));
+    map.insert("layer".into(), text(metadata.layer));
+}
+
+fn method_map(method: &MethodMetrics) -> Map {
+    let mut map = Map::new();
+    map.insert("name".into(), method.name.to_string().into());
+    map.insert("lines".into(), count(method.lines.0));
+    map.insert("complexity".into(), count(method.complexity.0));
+    map.insert("parameters".into(), count(method.parameters.0));
+    map.insert("nesting_depth".into(), count(method.nesting_depth.0));
+    map.insert("chain_length".into(), count(method.chain_length.0));
+    map.insert("exit_points".into(), count(method.exit_points));
+    map.insert("magic_literals".into(), count(method.magic_literals));
+    map.insert("return_type".into(), method.return_type.to_string().into());
+    map.insert("is_public".into(), method.is_public.into());
+    map.insert("is_static".into(), method.is_static.into());
+    map.insert("is_async".into(), method.is_async.into());
+    map.insert("called_methods".into(), strings(&method.called_methods));
+    map.insert("accessed_fields".into(), strings(&method.accessed_fields));
+    map
+}
+
+fn count(n: usize) -> Dynamic {
+    Dynamic::from_int(n as rhai::INT)
+}
+
+fn strings(items: &[Arc<str>]) -> Dynamic {
+    items.iter().map(|s| Dynamic::from(s.to_string())).collect::<Array>().into()
+}
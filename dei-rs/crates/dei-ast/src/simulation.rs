@@ -0,0 +1,156 @@
+//! Refactoring simulation - class metrics as if suggested extractions were performed
+//!
+//! Each cluster becomes its own class holding the cluster's methods and the fields
+//! only those methods touch; everything else stays behind in the original class.
+
+use dei_core::{
+    metrics::*,
+    models::*,
+    thresholds::{Complexity, Lines, MethodCount, Thresholds},
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::analysis::analyze_class;
+
+/// One class before a split and every class after it
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedSplit {
+    pub before: AnalysisResult,
+    /// The original class with the extracted methods and fields gone
+    pub remainder: AnalysisResult,
+    pub extracted: Vec<AnalysisResult>,
+}
+
+impl SimulatedSplit {
+    /// The original class had findings and no class left after the split does
+    pub fn resolves(&self) -> bool {
+        self.before.has_issues()
+            && !self.remainder.has_issues()
+            && self.extracted.iter().all(|r| !r.has_issues())
+    }
+}
+
+/// Move each cluster's methods, and the fields only they access, into a class of their own
+///
+/// Overloads move together since clusters name methods, not signatures; a method named by
+/// several clusters goes to the first one.
+pub fn simulate_split(
+    class: &ClassMetrics,
+    clusters: &[ResponsibilityCluster],
+    thresholds: &Thresholds,
+) -> SimulatedSplit {
+    let mut taken: HashSet<&str> = HashSet::new();
+    let groups: Vec<(&ResponsibilityCluster, Vec<MethodMetrics>)> = clusters
+        .iter()
+        .map(|cluster| {
+            let names: HashSet<&str> = cluster
+                .methods
+                .iter()
+                .map(|n| n.as_ref())
+                .filter(|n| !taken.contains(n))
+                .collect();
+            taken.extend(names.iter().copied());
+            let methods = class
+                .methods
+                .iter()
+                .filter(|m| names.contains(m.name.as_ref()))
+                .cloned()
+                .collect();
+            (cluster, methods)
+        })
+        .filter(|(_, methods): &(_, Vec<MethodMetrics>)| !methods.is_empty())
+        .collect();
+
+    let remaining: Vec<MethodMetrics> = class
+        .methods
+        .iter()
+        .filter(|m| !taken.contains(m.name.as_ref()))
+        .cloned()
+        .collect();
+    // Fields the original still uses stay put; a field shared by clusters goes to the first
+    let mut claimed: HashSet<Arc<str>> = accessed_fields(&remaining);
+
+    let mut moved_fields = 0;
+    let mut moved_lines = 0;
+    let mut moved_complexity = 0;
+    let mut extracted_classes = Vec::new();
+    for (cluster, methods) in groups {
+        let fields = accessed_fields(&methods)
+            .into_iter()
+            .filter(|f| claimed.insert(f.clone()))
+            .count();
+        let extracted = extracted_class(class, cluster.suggested_name.clone(), methods, fields);
+        moved_fields += fields;
+        moved_lines += extracted.lines.0;
+        moved_complexity += extracted.complexity.0;
+        extracted_classes.push(extracted);
+    }
+
+    // The original keeps a reference to every extracted class it still calls into
+    let called: HashSet<&str> = remaining
+        .iter()
+        .flat_map(|m| m.called_methods.iter().map(|c| c.as_ref()))
+        .collect();
+    let delegates = extracted_classes
+        .iter()
+        .filter(|e| e.methods.iter().any(|m| called.contains(m.name.as_ref())))
+        .map(|e| e.name.clone());
+    let dependencies: Arc<[Arc<str>]> = class.dependencies.iter().cloned().chain(delegates).collect();
+
+    let remainder = ClassMetrics {
+        lines: Lines(class.lines.0.saturating_sub(moved_lines)),
+        method_count: MethodCount(remaining.len()),
+        field_count: class.field_count.saturating_sub(moved_fields),
+        complexity: Complexity(class.complexity.0.saturating_sub(moved_complexity)),
+        methods: remaining.into(),
+        dependencies,
+        ..class.clone()
+    };
+
+    SimulatedSplit {
+        before: analyze_class(class, thresholds),
+        remainder: analyze_class(&remainder, thresholds),
+        extracted: extracted_classes
+            .iter()
+            .map(|c| analyze_class(c, thresholds))
+            .collect(),
+    }
+}
+
+fn extracted_class(
+    original: &ClassMetrics,
+    name: Arc<str>,
+    methods: Vec<MethodMetrics>,
+    field_count: usize,
+) -> ClassMetrics {
+    let fully_qualified_name = match original.fully_qualified_name.rsplit_once(original.name.as_ref()) {
+        Some((prefix, "")) => format!("{}{}", prefix, name).into(),
+        _ => name.clone(),
+    };
+
+    ClassMetrics {
+        name,
+        fully_qualified_name,
+        file_path: original.file_path.clone(),
+        lines: Lines(methods.iter().map(|m| m.lines.0).sum()),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count,
+        complexity: Complexity(methods.iter().map(|m| m.complexity.0).sum()),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    }
+}
+
+fn accessed_fields(methods: &[MethodMetrics]) -> HashSet<Arc<str>> {
+    methods
+        .iter()
+        .flat_map(|m| m.accessed_fields.iter().cloned())
+        .collect()
+}
@@ -0,0 +1,53 @@
+//! File stamps for telling changed files apart from untouched ones
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::SystemTime;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Size, modification time and content hash of a file as it was when the tree was built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    /// `None` where the platform doesn't record it
+    pub mtime: Option<SystemTime>,
+    /// XXH3 of the contents, stable across runs and platforms
+    pub hash: u64,
+}
+
+impl FileStamp {
+    /// Stamp the file at `path` as it is now
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let mut hasher = Xxh3::new();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            match file.read(&mut buffer)? {
+                0 => break,
+                n => hasher.update(&buffer[..n]),
+            }
+        }
+
+        Ok(Self { size: metadata.len(), mtime: metadata.modified().ok(), hash: hasher.digest() })
+    }
+
+    /// Whether the file at `path` no longer matches this stamp
+    ///
+    /// Size and modification time settle most cases without reading the file; a file that was
+    /// only touched is hashed again and counts as unchanged if its contents are.
+    pub fn is_stale(&self, path: &Path) -> bool {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return true;
+        };
+        if metadata.len() != self.size {
+            return true;
+        }
+        if self.mtime.is_some() && metadata.modified().ok() == self.mtime {
+            return false;
+        }
+        Self::read(path).map_or(true, |now| now.hash != self.hash)
+    }
+}
@@ -0,0 +1,133 @@
+//! Saving a built and parsed tree for later runs
+//!
+//! A [`SavedState`] holds the file tree and every file's metrics, as JSON, so another command
+//! can restore the arena and analyze it without walking the filesystem or parsing again.
+//! Thresholds aren't part of the state: restored metrics are judged by whoever restores them.
+
+use dei_core::{error::Result, metrics::FileMetrics, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{
+    arena::SharedArena,
+    node::{Node, NodeId, NodeKind},
+    stamp::FileStamp,
+};
+
+/// Version of the saved state; older or newer files are refused rather than misread
+pub const STATE_VERSION: u32 = 1;
+
+/// The tree built from one path, with the metrics of every parsed file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedState {
+    version: u32,
+    /// Path the tree was built from, canonicalized
+    pub path: PathBuf,
+    root: NodeId,
+    nodes: Vec<SavedNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedNode {
+    id: NodeId,
+    kind: NodeKind,
+    path: Arc<str>,
+    depth: usize,
+    parent: Option<NodeId>,
+    children: Arc<[NodeId]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stamp: Option<FileStamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_metrics: Option<Arc<FileMetrics>>,
+}
+
+impl SavedState {
+    /// Capture the tree under `root`, built from `path`, after its parse phase
+    pub fn capture(arena: &SharedArena, root: NodeId, path: &Path) -> Self {
+        let mut nodes = Vec::new();
+        let mut pending = vec![root];
+        while let Some(id) = pending.pop() {
+            let Some(node) = arena.get(id) else {
+                continue;
+            };
+            pending.extend(node.children.iter().rev().copied());
+            nodes.push(SavedNode {
+                id,
+                kind: node.kind,
+                path: node.path.clone(),
+                depth: node.depth,
+                parent: node.parent.filter(|_| id != root),
+                children: node.children.clone(),
+                stamp: node.stamp,
+                file_metrics: node.file_metrics(),
+            });
+        }
+
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        Self { version: STATE_VERSION, path, root, nodes }
+    }
+
+    pub fn save(&self, file: &Path) -> Result<()> {
+        let json = serde_json::to_string(self).map_err(|e| Error::Analysis(e.to_string()))?;
+        fs::write(file, json)?;
+        Ok(())
+    }
+
+    pub fn load(file: &Path) -> Result<Self> {
+        let text = fs::read_to_string(file)?;
+        let state: Self = serde_json::from_str(&text)
+            .map_err(|e| Error::Parse { path: file.to_path_buf(), message: e.to_string() })?;
+        if state.version != STATE_VERSION {
+            return Err(Error::Parse {
+                path: file.to_path_buf(),
+                message: format!("saved state version {} is not {}; save it again", state.version, STATE_VERSION),
+            });
+        }
+        Ok(state)
+    }
+
+    /// Files whose contents changed, or that were removed, since the state was saved
+    pub fn stale_files(&self) -> Vec<PathBuf> {
+        self.nodes
+            .iter()
+            .filter(|node| node.stamp.is_some_and(|stamp| stamp.is_stale(Path::new(node.path.as_ref()))))
+            .map(|node| PathBuf::from(node.path.as_ref()))
+            .collect()
+    }
+
+    /// Rebuild the tree in a fresh arena, returning it and the new root
+    ///
+    /// Parsed files carry their metrics, so a [`ParallelTraverser`](crate::ParallelTraverser)
+    /// over the arena analyzes them without parsing again.
+    pub fn restore(&self) -> (SharedArena, NodeId) {
+        let arena = SharedArena::with_capacity(self.nodes.len());
+        let ids: HashMap<NodeId, NodeId> = self
+            .nodes
+            .iter()
+            .map(|saved| {
+                let path = PathBuf::from(saved.path.as_ref());
+                let node = match saved.kind {
+                    NodeKind::Directory => Node::new_directory(NodeId::new(0), path, saved.depth),
+                    NodeKind::File => Node::new_file(NodeId::new(0), path, saved.depth).with_stamp(saved.stamp),
+                };
+                if let Some(metrics) = &saved.file_metrics {
+                    node.set_file_metrics(metrics.clone());
+                }
+                (saved.id, arena.alloc(node))
+            })
+            .collect();
+
+        for saved in &self.nodes {
+            arena.update(ids[&saved.id], |node| {
+                node.parent = saved.parent.and_then(|p| ids.get(&p).copied());
+                node.children = saved.children.iter().filter_map(|c| ids.get(c).copied()).collect();
+            });
+        }
+
+        let root = ids.get(&self.root).copied().unwrap_or(NodeId::new(0));
+        (arena, root)
+    }
+}
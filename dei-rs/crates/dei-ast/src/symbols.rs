@@ -0,0 +1,157 @@
+//! Project-wide symbol table for cross-file reference resolution
+//!
+//! Populated during traversal and shared through the arena so that later
+//! analyses can map a referenced name back to the node that defines it
+
+use dashmap::DashMap;
+use dei_core::metrics::{FileMetrics, LineSpan};
+use std::sync::Arc;
+
+use crate::node::NodeId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Type,
+    Function,
+}
+
+/// A defined type or function and where it lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: Arc<str>,
+    pub qualified_name: Arc<str>,
+    pub kind: SymbolKind,
+    /// File node that defines the symbol
+    pub node: NodeId,
+    /// Defining type for functions (`None` for types)
+    pub owner: Option<Arc<str>>,
+    pub span: LineSpan,
+    /// Type synthesized from a file's free functions rather than declared
+    pub synthetic: bool,
+}
+
+impl Symbol {
+    /// Type that a reference to this symbol ultimately depends on
+    pub fn owning_type(&self) -> &Arc<str> {
+        self.owner.as_ref().unwrap_or(&self.name)
+    }
+}
+
+/// Concurrent map from simple and qualified names to their definitions
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    by_name: DashMap<Arc<str>, Vec<Symbol>>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register every class and method of a parsed file
+    pub fn register_file(&self, node: NodeId, metrics: &FileMetrics) {
+        for class in metrics.classes.iter() {
+            self.insert(Symbol {
+                name: class.name.clone(),
+                qualified_name: class.fully_qualified_name.clone(),
+                kind: SymbolKind::Type,
+                node,
+                owner: None,
+                span: class.span,
+                synthetic: metrics.is_module_class(class),
+            });
+
+            for method in class.methods.iter() {
+                self.insert(Symbol {
+                    name: method.name.clone(),
+                    qualified_name: format!("{}::{}", class.fully_qualified_name, method.name).into(),
+                    kind: SymbolKind::Function,
+                    node,
+                    owner: Some(class.name.clone()),
+                    span: method.span,
+                    synthetic: false,
+                });
+            }
+        }
+    }
+
+    /// Drop all symbols defined by a file node (e.g. before re-parsing it)
+    pub fn remove_node(&self, node: NodeId) {
+        self.by_name.retain(|_, symbols| {
+            symbols.retain(|s| s.node != node);
+            !symbols.is_empty()
+        });
+    }
+
+    fn insert(&self, symbol: Symbol) {
+        let mut keys = vec![symbol.name.clone()];
+        if symbol.qualified_name != symbol.name {
+            keys.push(symbol.qualified_name.clone());
+        }
+        if let Some(owner) = &symbol.owner {
+            keys.push(format!("{}::{}", owner, symbol.name).into());
+        }
+
+        for key in keys {
+            let mut entry = self.by_name.entry(key).or_default();
+            if !entry.contains(&symbol) {
+                entry.push(symbol.clone());
+            }
+        }
+    }
+
+    /// All definitions registered under a name (`Type`, `a.b.Type`, `Type::method`, `Type.method`)
+    pub fn lookup(&self, name: &str) -> Vec<Symbol> {
+        let key = name.replace('.', "::");
+        self.by_name
+            .get(name)
+            .or_else(|| self.by_name.get(key.as_str()))
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /// Resolve a name to a single definition, `None` if unknown or ambiguous
+    pub fn resolve(&self, name: &str, kind: SymbolKind) -> Option<Symbol> {
+        let mut candidates = self.lookup(name).into_iter().filter(|s| s.kind == kind);
+        let first = candidates.next()?;
+        candidates.next().is_none().then_some(first)
+    }
+
+    /// Declared types with exactly this simple name, wherever they live
+    pub fn types_named(&self, name: &str) -> Vec<Symbol> {
+        self.by_name
+            .get(name)
+            .map(|symbols| {
+                symbols
+                    .iter()
+                    .filter(|s| s.kind == SymbolKind::Type && !s.synthetic && s.name.as_ref() == name)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn resolve_type(&self, name: &str) -> Option<Symbol> {
+        self.resolve(name, SymbolKind::Type)
+    }
+
+    pub fn resolve_function(&self, name: &str) -> Option<Symbol> {
+        self.resolve(name, SymbolKind::Function)
+    }
+
+    /// Resolve any reference (type or function) to the type it depends on
+    pub fn resolve_owner(&self, name: &str) -> Option<Arc<str>> {
+        self.resolve_type(name)
+            .or_else(|| self.resolve_function(name))
+            .map(|s| s.owning_type().clone())
+    }
+
+    /// Number of distinct names registered
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+}
@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod tests {
     use crate::{arena::*, node::*};
-    use dei_core::models::Language;
     use std::path::PathBuf;
     use std::sync::Arc;
 
@@ -9,7 +8,7 @@ mod tests {
     fn test_arena_allocation() {
         let arena = Arena::new();
         
-        let node = Node::new_file(NodeId(0), PathBuf::from("/test.rs"), 0);
+        let node = Node::new_file(NodeId::new(0), PathBuf::from("/test.rs"), 0);
         let id = arena.alloc(node.clone());
         
         let retrieved = arena.get(id);
@@ -17,15 +16,63 @@ mod tests {
         assert_eq!(retrieved.unwrap().name, node.name);
     }
 
+    #[test]
+    fn test_arena_shares_nodes() {
+        let arena = SharedArena::new();
+        let dir = arena.alloc(Node::new_directory(NodeId::new(0), PathBuf::from("/src"), 0));
+        let file = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/src/lib.rs"), 1));
+        arena.update(dir, |node| node.children = Arc::new([file]));
+
+        // Readers share one node instead of getting copies
+        let first = arena.get(file).unwrap();
+        assert!(Arc::ptr_eq(&first, &arena.get(file).unwrap()));
+
+        // Results set through one handle are seen through every other
+        let god_file = dei_core::models::GodFileResult {
+            file_path: first.path.clone(),
+            class_count: 0,
+            total_lines: 0,
+            class_names: Arc::new([]),
+            violations: Arc::new([]),
+            entry_point: None,
+        };
+        first.set_god_file_result(god_file);
+        assert!(arena.get(file).unwrap().has_issues());
+        assert_eq!(arena.children(dir).as_ref(), &[file]);
+    }
+
+    #[test]
+    fn test_removed_ids_never_resolve() {
+        let arena = SharedArena::new();
+        let dir = arena.alloc(Node::new_directory(NodeId::new(0), PathBuf::from("/src"), 0));
+        let file = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/src/lib.rs"), 1).with_parent(dir));
+        arena.update(dir, |node| node.children = Arc::new([file]));
+        arena.metadata().insert(file, crate::metadata::Owner("core".into()));
+
+        assert_eq!(arena.remove(dir), 2);
+        assert!(arena.get(dir).is_none() && arena.get(file).is_none());
+        assert!(arena.metadata().is_empty());
+        assert_eq!(arena.remove(dir), 0);
+
+        // Slots are reused under a new generation
+        let reused = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/main.rs"), 0));
+        assert!(reused.index() == dir.index() || reused.index() == file.index());
+        assert_ne!(reused, dir);
+        assert_ne!(reused, file);
+        assert!(arena.get(dir).is_none() && arena.get(file).is_none());
+        assert_eq!(arena.get(reused).unwrap().id, reused);
+        assert_eq!(arena.len(), 1);
+    }
+
     #[test]
     fn test_node_language_detection() {
-        let rust_node = Node::new_file(NodeId(0), PathBuf::from("test.rs"), 0);
+        let rust_node = Node::new_file(NodeId::new(0), PathBuf::from("test.rs"), 0);
         assert_eq!(rust_node.language(), Some(dei_core::models::Language::Rust));
 
-        let csharp_node = Node::new_file(NodeId(1), PathBuf::from("test.cs"), 0);
+        let csharp_node = Node::new_file(NodeId::new(1), PathBuf::from("test.cs"), 0);
         assert_eq!(csharp_node.language(), Some(dei_core::models::Language::CSharp));
 
-        let unknown_node = Node::new_file(NodeId(2), PathBuf::from("test.txt"), 0);
+        let unknown_node = Node::new_file(NodeId::new(2), PathBuf::from("test.txt"), 0);
         assert_eq!(unknown_node.language(), None);
     }
 
@@ -33,8 +80,8 @@ mod tests {
     fn test_shared_arena() {
         let arena = SharedArena::new();
         
-        let node1 = Node::new_file(NodeId(0), PathBuf::from("/test1.rs"), 0);
-        let node2 = Node::new_file(NodeId(1), PathBuf::from("/test2.rs"), 0);
+        let node1 = Node::new_file(NodeId::new(0), PathBuf::from("/test1.rs"), 0);
+        let node2 = Node::new_file(NodeId::new(1), PathBuf::from("/test2.rs"), 0);
         
         let id1 = arena.alloc(node1);
         let id2 = arena.alloc(node2);
@@ -47,13 +94,133 @@ mod tests {
     fn test_node_with_children() {
         let arena = SharedArena::new();
         
-        let child1_id = arena.alloc(Node::new_file(NodeId(0), PathBuf::from("/child1.rs"), 1));
-        let child2_id = arena.alloc(Node::new_file(NodeId(1), PathBuf::from("/child2.rs"), 1));
+        let child1_id = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/child1.rs"), 1));
+        let child2_id = arena.alloc(Node::new_file(NodeId::new(1), PathBuf::from("/child2.rs"), 1));
         
-        let parent = Node::new_directory(NodeId(2), PathBuf::from("/parent"), 0)
+        let parent = Node::new_directory(NodeId::new(2), PathBuf::from("/parent"), 0)
             .with_children(Arc::new([child1_id, child2_id]));
         
         assert_eq!(parent.children.len(), 2);
     }
-}
 
+    #[test]
+    fn test_symbol_table_resolution() {
+        use crate::symbols::*;
+        use dei_core::{metrics::*, thresholds::*};
+
+        let method = MethodMetrics {
+            name: "send".into(),
+            lines: Lines(5),
+            complexity: Complexity(1),
+            parameters: ParamCount(1),
+            params: Arc::new([]),
+            generics: Arc::new([]),
+            nesting_depth: NestingDepth(0),
+            chain_length: ChainLength(0),
+            exit_points: 0,
+            magic_literals: 0,
+            span: LineSpan::default(),
+            called_methods: Arc::new([]),
+            accessed_fields: Arc::new([]),
+            return_type: "()".into(),
+            is_public: true,
+            is_static: false,
+            is_async: false,
+            tokens: Arc::new([]),
+            ast_snippet: None,
+        };
+        let file = FileMetrics {
+            path: "/mailer.rs".into(),
+            lines: Lines(10),
+            classes: Arc::new([ClassMetrics {
+                name: "Mailer".into(),
+                fully_qualified_name: "app.mail.Mailer".into(),
+                file_path: "/mailer.rs".into(),
+                lines: Lines(10),
+                method_count: MethodCount(1),
+                property_count: 0,
+                field_count: 0,
+                complexity: Complexity(1),
+                methods: Arc::new([method]),
+                dependencies: Arc::new([]),
+                span: LineSpan::default(),
+                entry_point: None,
+                is_abstract: false,
+                is_public: true,
+            }]),
+            top_level_functions: 0,
+            imports: 0,
+            imported_symbols: 0,
+            wildcard_imports: Arc::new([]),
+            entry_point: None,
+            encoding: None,
+        };
+
+        let arena = SharedArena::new();
+        let file_id = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/mailer.rs"), 0));
+        arena.symbols().register_file(file_id, &file);
+
+        let ty = arena.symbols().resolve_type("app.mail.Mailer").unwrap();
+        assert_eq!(ty.node, file_id);
+        assert_eq!(ty.name.as_ref(), "Mailer");
+
+        let func = arena.symbols().resolve_function("Mailer.send").unwrap();
+        assert_eq!(func.kind, SymbolKind::Function);
+        assert_eq!(arena.symbols().resolve_owner("send").as_deref(), Some("Mailer"));
+        assert!(arena.symbols().resolve_type("Unknown").is_none());
+
+        arena.symbols().remove_node(file_id);
+        assert!(arena.symbols().is_empty());
+    }
+
+    #[test]
+    fn test_node_metadata_type_map() {
+        use crate::metadata::*;
+
+        #[derive(Debug, Clone, PartialEq)]
+        struct Reviewed(bool);
+
+        let arena = SharedArena::new();
+        let id = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/src/api.rs"), 0));
+
+        arena.metadata().insert(id, Reviewed(true));
+        arena.metadata().insert(id, Owner("platform".into()));
+
+        assert_eq!(arena.metadata().get::<Reviewed>(id), Some(Reviewed(true)));
+        assert_eq!(arena.metadata().get::<Owner>(id), Some(Owner("platform".into())));
+        assert_eq!(arena.metadata().get::<Layer>(id), None);
+
+        arena.metadata().clear_node(id);
+        assert!(arena.metadata().is_empty());
+    }
+
+    #[test]
+    fn test_path_rules_assign_metadata() {
+        use crate::metadata::*;
+
+        let arena = SharedArena::new();
+        let ui = arena.alloc(Node::new_file(NodeId::new(0), PathBuf::from("/repo/src/ui/view.rs"), 3));
+        let db = arena.alloc(Node::new_file(NodeId::new(1), PathBuf::from("/repo/src/db/store.rs"), 3));
+
+        arena.apply_path_rules(&[
+            PathRule {
+                pattern: "src/ui/*".into(),
+                tags: vec!["frontend".into()],
+                layer: Some("ui".into()),
+                ..Default::default()
+            },
+            PathRule {
+                pattern: "*.rs".into(),
+                tags: vec!["rust".into()],
+                owner: Some("core-team".into()),
+                ..Default::default()
+            },
+        ]);
+
+        let ui_tags = arena.metadata().get::<Tags>(ui).unwrap();
+        assert!(ui_tags.contains("frontend") && ui_tags.contains("rust"));
+        assert_eq!(arena.metadata().get::<Layer>(ui), Some(Layer("ui".into())));
+        assert_eq!(arena.metadata().get::<Layer>(db), None);
+        assert_eq!(arena.metadata().get::<Owner>(db), Some(Owner("core-team".into())));
+    }
+}
@@ -7,6 +7,7 @@
 
 use dashmap::DashMap;
 use dei_core::{
+    detector::DetectorRegistry,
     error::Result,
     metrics::*,
     models::*,
@@ -15,18 +16,51 @@ use dei_core::{
     Error,
 };
 use rayon::prelude::*;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug_span, info_span, Span};
+
+use crate::{
+    analysis,
+    arena::SharedArena,
+    node::{Node, NodeId},
+    profiling::{Phase, SlowFile, TimeBudget},
+    progress::ProgressObserver,
+};
 
-use crate::{arena::SharedArena, node::{Node, NodeId}};
+/// A class by file path and qualified name
+type ClassKey = (Arc<str>, Arc<str>);
 
 /// Parallel AST traverser with intelligent work distribution
+///
+/// Runs in two phases: every file is parsed first, then analyzed, so that
+/// cross-file analyses see the whole project before judging any class. Files that can't be
+/// read or parsed are set aside with their error unless the traverser is strict. Files whose
+/// nodes already carry metrics, as in an arena restored from a [`SavedState`](crate::SavedState),
+/// aren't parsed again.
 pub struct ParallelTraverser<P>
 where
     P: Parser,
 {
     parser: Arc<P>,
     arena: SharedArena,
-    results: Arc<DashMap<NodeId, Vec<AnalysisResult>>>,
+    parsed: Arc<DashMap<NodeId, Arc<FileMetrics>>>,
+    /// Shared with each file node, so results are held once
+    results: Arc<DashMap<NodeId, Arc<[AnalysisResult]>>>,
+    import_hygiene: Arc<DashMap<NodeId, ImportHygieneResult>>,
+    god_files: Arc<DashMap<NodeId, GodFileResult>>,
+    budget: TimeBudget,
+    languages: LanguageFilter,
+    suggest_extractions: bool,
+    slow_files: Arc<Mutex<Vec<SlowFile>>>,
+    observer: Option<Arc<dyn ProgressObserver>>,
+    strict: bool,
+    file_errors: Arc<Mutex<Vec<FileError>>>,
+    /// Results of an earlier run, by file and qualified class name
+    previous: Arc<HashMap<ClassKey, AnalysisResult>>,
+    detectors: DetectorRegistry,
 }
 
 impl<P> ParallelTraverser<P>
@@ -37,198 +71,394 @@ where
         Self {
             parser: Arc::new(parser),
             arena,
+            parsed: Arc::new(DashMap::new()),
             results: Arc::new(DashMap::new()),
+            import_hygiene: Arc::new(DashMap::new()),
+            god_files: Arc::new(DashMap::new()),
+            budget: TimeBudget::default(),
+            languages: LanguageFilter::default(),
+            suggest_extractions: false,
+            slow_files: Arc::new(Mutex::new(Vec::new())),
+            observer: None,
+            strict: false,
+            file_errors: Arc::new(Mutex::new(Vec::new())),
+            previous: Arc::new(HashMap::new()),
+            detectors: DetectorRegistry::default(),
         }
     }
 
+    /// Replace the default per-file time budget
+    pub fn with_time_budget(mut self, budget: TimeBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Only parse files in the selected languages
+    pub fn with_languages(mut self, languages: LanguageFilter) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Cluster the methods of every god class into suggested extractions
+    pub fn with_suggested_extractions(mut self, suggest: bool) -> Self {
+        self.suggest_extractions = suggest;
+        self
+    }
+
+    /// Fail the whole traversal on the first file that can't be read or parsed, instead of
+    /// recording it in [`Self::file_errors`] and carrying on
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Reuse results from an earlier run with the same settings for classes the parser reports
+    /// unchanged (see [`Parser::changed_classes`]) that haven't moved within their file
+    pub fn with_previous_results(mut self, results: impl IntoIterator<Item = AnalysisResult>) -> Self {
+        let key = |r: &AnalysisResult| (r.class_metrics.file_path.clone(), r.class_metrics.fully_qualified_name.clone());
+        self.previous = Arc::new(results.into_iter().map(|r| (key(&r), r)).collect());
+        self
+    }
+
+    /// Run these detectors instead of the built-in ones
+    pub fn with_detectors(mut self, detectors: DetectorRegistry) -> Self {
+        self.detectors = detectors;
+        self
+    }
+
+    /// Report progress to `observer` as files are discovered, parsed and analyzed
+    pub fn with_observer(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
     /// Traverse and analyze AST in parallel using Rayon
     pub fn traverse_and_analyze(
         &self,
         root_id: NodeId,
         thresholds: &Thresholds,
     ) -> Result<()> {
-        self.traverse_node(root_id, thresholds)
+        self.parse_phase(root_id)?;
+        self.analysis_phase(thresholds)
     }
 
-    fn traverse_node(&self, node_id: NodeId, thresholds: &Thresholds) -> Result<()> {
+    /// Phase 1: parse every supported file, populating file metrics and the symbol table
+    pub fn parse_phase(&self, root_id: NodeId) -> Result<()> {
+        let files = self.count_files(root_id);
+        if let Some(observer) = &self.observer {
+            observer.files_discovered(files);
+        }
+        let phase = info_span!("parse", files);
+        let _entered = phase.enter();
+        self.parse_node(root_id, &phase)
+    }
+
+    /// Files under `node_id` the parse phase will pick up
+    fn count_files(&self, node_id: NodeId) -> usize {
+        let Some(node) = self.arena.get(node_id) else {
+            return 0;
+        };
+        if node.is_file() {
+            usize::from(node.language().is_some_and(|l| self.languages.allows(l)))
+        } else {
+            node.children.iter().map(|&child| self.count_files(child)).sum()
+        }
+    }
+
+    /// Phase 2: apply thresholds to every parsed file with the full project in view
+    pub fn analysis_phase(&self, thresholds: &Thresholds) -> Result<()> {
+        let files: Vec<(NodeId, Arc<FileMetrics>)> = self
+            .parsed
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+
+        let phase = info_span!("analyze", files = files.len());
+        let _entered = phase.enter();
+        files.par_iter().for_each(|(node_id, file_metrics)| {
+            let _file = debug_span!(parent: &phase, "analyze_file", path = %file_metrics.path).entered();
+            let started = Instant::now();
+            self.analyze_file(*node_id, file_metrics, thresholds);
+
+            let elapsed = started.elapsed();
+            let path = Path::new(file_metrics.path.as_ref());
+            if self.budget.is_exceeded(elapsed) {
+                self.record_slow_file(path, Phase::Analyze, elapsed);
+                if self.budget.skip_slow {
+                    self.results.remove(node_id);
+                    if let Some(node) = self.arena.get(*node_id) {
+                        node.set_analysis_results(Arc::new([]));
+                    }
+                }
+            }
+
+            if let Some(observer) = &self.observer {
+                // Shared so the observer never runs while holding a lock on the results
+                let results = self.results.get(node_id).map(|r| r.clone()).unwrap_or_default();
+                observer.file_analyzed(path, &results);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Parse the files under `node_id`, tracing each file under the `phase` span
+    fn parse_node(&self, node_id: NodeId, phase: &Span) -> Result<()> {
         let node = self.arena.get(node_id).ok_or_else(|| {
             Error::Analysis(format!("Node {:?} not found", node_id))
         })?;
 
         if node.is_file() {
-            self.analyze_file_node(&node, thresholds)?;
+            self.parse_file_node(&node, phase)?;
         } else if node.is_directory() {
-            self.traverse_directory(&node, thresholds)?;
+            self.parse_directory(&node, phase)?;
         }
 
         Ok(())
     }
 
-    fn traverse_directory(&self, node: &Node, thresholds: &Thresholds) -> Result<()> {
+    fn parse_directory(&self, node: &Node, phase: &Span) -> Result<()> {
         // Rayon's par_iter uses work-stealing for optimal load balancing
         node.children
             .par_iter()
-            .try_for_each(|&child_id| self.traverse_node(child_id, thresholds))?;
+            .try_for_each(|&child_id| self.parse_node(child_id, phase))?;
 
         Ok(())
     }
 
-    fn analyze_file_node(&self, node: &Node, thresholds: &Thresholds) -> Result<()> {
-        // Only analyze files in supported languages
-        if node.language().is_none() {
+    fn parse_file_node(&self, node: &Node, phase: &Span) -> Result<()> {
+        // Only parse files in supported, selected languages
+        if !node.language().is_some_and(|l| self.languages.allows(l)) {
             return Ok(());
         }
 
-        // Parse file to get metrics
-        let path = std::path::Path::new(node.path.as_ref());
-        let file_metrics = self.parser.parse_file(path)?;
+        let path = Path::new(node.path.as_ref());
+        // Nodes restored from a saved state already carry their metrics
+        if let Some(file_metrics) = node.file_metrics() {
+            if let Some(observer) = &self.observer {
+                observer.file_parsed(path);
+            }
+            self.arena.symbols().register_file(node.id, &file_metrics);
+            self.parsed.insert(node.id, file_metrics);
+            return Ok(());
+        }
 
-        // Update node with file metrics
-        if let Some(mut updated_node) = self.arena.get(node.id) {
-            updated_node = updated_node.with_file_metrics(file_metrics.clone());
+        let _file = debug_span!(parent: phase, "parse_file", path = %node.path).entered();
+        let started = Instant::now();
+        let parsed = self.parser.parse_file(path);
 
-            // Check for god file
-            if file_metrics.is_god_file(thresholds) {
-                let god_file = self.create_god_file_result(&file_metrics, thresholds);
-                updated_node = updated_node.with_god_file_result(god_file);
+        let elapsed = started.elapsed();
+        if let Some(observer) = &self.observer {
+            observer.file_parsed(path);
+        }
+        let file_metrics = match parsed {
+            Ok(file_metrics) => file_metrics,
+            Err(e) if self.strict => return Err(e),
+            Err(e) => {
+                let error = FileError { file_path: node.path.clone(), message: e.to_string().into() };
+                self.file_errors.lock().unwrap_or_else(|e| e.into_inner()).push(error);
+                return Ok(());
             }
-
-            // Analyze each class
-            let mut analysis_results = Vec::new();
-            for class in file_metrics.classes.iter() {
-                let result = self.analyze_class(class, thresholds);
-                analysis_results.push(result);
+        };
+        if self.budget.is_exceeded(elapsed) {
+            self.record_slow_file(path, Phase::Parse, elapsed);
+            if self.budget.skip_slow {
+                return Ok(());
             }
-
-            // Store results
-            self.results.insert(node.id, analysis_results.clone());
-            
-            updated_node = updated_node.with_analysis_results(analysis_results.into());
-            self.arena.update(node.id, updated_node);
         }
+        self.arena.symbols().register_file(node.id, &file_metrics);
+
+        let file_metrics = Arc::new(file_metrics);
+        node.set_file_metrics(file_metrics.clone());
+        self.parsed.insert(node.id, file_metrics);
 
         Ok(())
     }
 
-    fn analyze_class(&self, class: &ClassMetrics, thresholds: &Thresholds) -> AnalysisResult {
-        if !class.is_god_class(thresholds) && class.god_method_count(thresholds) == 0 {
-            return AnalysisResult::healthy(class.clone());
+    fn record_slow_file(&self, path: &Path, phase: Phase, elapsed: Duration) {
+        let slow = SlowFile {
+            path: path.to_path_buf(),
+            language: Language::from_path(path),
+            bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+            phase,
+            elapsed,
+            skipped: self.budget.skip_slow,
+        };
+        self.slow_files.lock().unwrap_or_else(|e| e.into_inner()).push(slow);
+    }
+
+    fn analyze_file(&self, node_id: NodeId, file_metrics: &FileMetrics, thresholds: &Thresholds) {
+        let Some(node) = self.arena.get(node_id) else {
+            return;
+        };
+
+        let metadata = self.arena.metadata().path_metadata(node_id);
+        let (god_file, import_hygiene) = analysis::file_results_with(file_metrics, thresholds, &self.detectors, &metadata);
+        if let Some(god_file) = god_file {
+            self.god_files.insert(node_id, god_file.clone());
+            node.set_god_file_result(god_file);
+        }
+        if let Some(hygiene) = import_hygiene {
+            self.import_hygiene.insert(node_id, hygiene);
         }
 
-        // Detect god methods
-        let god_methods: Arc<[GodMethodResult]> = class
-            .methods
-            .iter()
-            .filter(|m| m.is_god_method(thresholds))
-            .map(|m| self.create_god_method_result(m, class, thresholds))
+        // Analyze each class; clustering makes god classes slow, so a file holding several
+        // of them spreads across threads too
+        let changed = self.parser.changed_classes(Path::new(file_metrics.path.as_ref()));
+        // Spans don't follow work onto other threads, so each class names its file's span
+        let file_span = Span::current();
+        let analysis_results: Vec<AnalysisResult> = file_metrics
+            .classes
+            .par_iter()
+            .map(|class| {
+                let _class = debug_span!(parent: &file_span, "analyze_class", class = %class.name).entered();
+                let name_clashes = self.name_clashes(node_id, file_metrics, class);
+                if let Some(previous) = self.reusable_result(class, changed.as_deref()) {
+                    return AnalysisResult { name_clashes, ..previous };
+                }
+                let mut result = AnalysisResult {
+                    name_clashes,
+                    ..analysis::analyze_class_with(class, thresholds, &self.detectors, &metadata)
+                };
+                if self.suggest_extractions {
+                    (result.suggested_extractions, result.cluster_stats) = analysis::suggest_extractions(&result, thresholds);
+                }
+                result
+            })
             .collect();
 
-        let summary = if class.is_god_class(thresholds) {
-            format!(
-                "God class detected: {} (lines: {}, methods: {}, complexity: {})",
-                class.name, class.lines.0, class.method_count.0, class.complexity.0
-            )
-        } else {
-            format!("Class '{}' has {} god method(s)", class.name, god_methods.len())
-        };
+        // Store results, once, for both the traverser and the tree
+        let analysis_results: Arc<[AnalysisResult]> = analysis_results.into();
+        self.results.insert(node_id, analysis_results.clone());
+        node.set_analysis_results(analysis_results);
+    }
 
-        AnalysisResult {
-            class_metrics: class.clone(),
-            is_god_class: class.is_god_class(thresholds),
-            suggested_extractions: Arc::new([]), // Will be filled by clustering analyzer
-            god_methods,
-            analyzed_at: std::time::SystemTime::now(),
-            summary: summary.into(),
+    /// The earlier result for `class`, if the parser says it's unchanged and it still sits at the
+    /// same lines with the same headline metrics
+    ///
+    /// Imports and annotations live outside the class body, so an edit to them isn't seen as a
+    /// change to the class; what the class takes from them is compared too.
+    fn reusable_result(&self, class: &ClassMetrics, changed: Option<&[Arc<str>]>) -> Option<AnalysisResult> {
+        if changed?.contains(&class.fully_qualified_name) {
+            return None;
         }
+        let previous = self.previous.get(&(class.file_path.clone(), class.fully_qualified_name.clone()))?;
+        let before = &previous.class_metrics;
+        let unmoved = before.span == class.span
+            && before.lines == class.lines
+            && before.complexity == class.complexity
+            && before.method_count == class.method_count
+            && before.dependencies == class.dependencies
+            && before.entry_point == class.entry_point
+            && before.is_abstract == class.is_abstract
+            && before.is_public == class.is_public;
+        unmoved.then(|| previous.clone())
     }
 
-    fn create_god_method_result(
-        &self,
-        method: &MethodMetrics,
-        class: &ClassMetrics,
-        thresholds: &Thresholds,
-    ) -> GodMethodResult {
-        let mut violations = Vec::new();
-
-        if method.lines > thresholds.max_method_lines {
-            violations.push(Violation {
-                kind: ViolationKind::Lines,
-                actual: method.lines.0,
-                threshold: thresholds.max_method_lines.0,
-            });
+    /// Other declared classes with the same simple name, from the symbol table
+    fn name_clashes(&self, node_id: NodeId, file: &FileMetrics, class: &ClassMetrics) -> Arc<[ClassLocation]> {
+        if file.is_module_class(class) {
+            return Arc::new([]);
         }
 
-        if method.complexity > thresholds.max_method_complexity {
-            violations.push(Violation {
-                kind: ViolationKind::Complexity,
-                actual: method.complexity.0,
-                threshold: thresholds.max_method_complexity.0,
-            });
-        }
+        self.arena
+            .symbols()
+            .types_named(&class.name)
+            .into_iter()
+            .filter(|s| s.node != node_id || s.qualified_name != class.fully_qualified_name)
+            .filter_map(|s| {
+                Some(ClassLocation {
+                    file_path: self.arena.get(s.node)?.path.clone(),
+                    qualified_name: s.qualified_name,
+                    span: s.span,
+                })
+            })
+            .collect()
+    }
 
-        if method.parameters > thresholds.max_parameters {
-            violations.push(Violation {
-                kind: ViolationKind::ParameterCount,
-                actual: method.parameters.0,
-                threshold: thresholds.max_parameters.0,
-            });
-        }
+    /// File metrics produced by the parse phase, shared with their file nodes
+    pub fn parsed_files(&self) -> Vec<Arc<FileMetrics>> {
+        self.parsed.iter().map(|entry| entry.value().clone()).collect()
+    }
 
-        GodMethodResult {
-            method_name: method.name.clone(),
-            class_name: class.name.clone(),
-            file_path: class.file_path.clone(),
-            metrics: method.clone(),
-            violations: violations.into(),
-            violation_score: method.violation_score(thresholds),
-        }
+    /// Number of files the parse phase produced metrics for
+    pub fn parsed_file_count(&self) -> usize {
+        self.parsed.len()
     }
 
-    fn create_god_file_result(
-        &self,
-        file_metrics: &FileMetrics,
-        thresholds: &Thresholds,
-    ) -> GodFileResult {
-        let mut violations = Vec::new();
-
-        if file_metrics.classes.len() > thresholds.max_classes_per_file {
-            violations.push(Violation {
-                kind: ViolationKind::ClassesPerFile,
-                actual: file_metrics.classes.len(),
-                threshold: thresholds.max_classes_per_file,
-            });
-        }
+    pub fn get_results(&self, node_id: NodeId) -> Option<Vec<AnalysisResult>> {
+        self.results.get(&node_id).map(|r| r.to_vec())
+    }
 
-        if file_metrics.lines > thresholds.max_file_lines {
-            violations.push(Violation {
-                kind: ViolationKind::Lines,
-                actual: file_metrics.lines.0,
-                threshold: thresholds.max_file_lines.0,
-            });
-        }
+    /// Files over the wildcard or imported-name limits, by path
+    pub fn import_hygiene_results(&self) -> Vec<ImportHygieneResult> {
+        let mut results: Vec<_> = self.import_hygiene.iter().map(|entry| entry.value().clone()).collect();
+        results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        results
+    }
 
-        GodFileResult {
-            file_path: file_metrics.path.clone(),
-            class_count: file_metrics.classes.len(),
-            total_lines: file_metrics.lines.0,
-            class_names: file_metrics
-                .classes
-                .iter()
-                .map(|c| c.name.clone())
-                .collect(),
-            violations: violations.into(),
-        }
+    /// Files over the god-file thresholds, by path
+    pub fn god_file_results(&self) -> Vec<GodFileResult> {
+        let mut results: Vec<_> = self.god_files.iter().map(|entry| entry.value().clone()).collect();
+        results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        results
     }
 
-    pub fn get_results(&self, node_id: NodeId) -> Option<Vec<AnalysisResult>> {
-        self.results.get(&node_id).map(|r| r.clone())
+    /// Files that couldn't be read or parsed, by path
+    pub fn file_errors(&self) -> Vec<FileError> {
+        let mut errors = self.file_errors.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        errors.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        errors
     }
 
+    /// Files analyzed with a caveat, such as being transcoded from a legacy encoding, by path
+    pub fn file_warnings(&self) -> Vec<FileWarning> {
+        let mut warnings: Vec<_> = self.parsed.iter().filter_map(|entry| FileWarning::transcoded(entry.value())).collect();
+        warnings.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        warnings
+    }
+
+    /// Files that overran the time budget, slowest first
+    pub fn slow_files(&self) -> Vec<SlowFile> {
+        let mut slow = self.slow_files.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        slow.sort_by_key(|s| std::cmp::Reverse(s.elapsed));
+        slow
+    }
+
+    /// Results of every analyzed file, each class once, by file and position
+    ///
+    /// A file reachable by several paths, such as trees built from a directory and a link to it,
+    /// would otherwise count its classes once per path. Classes are keyed by their canonical file
+    /// path and span (and name, where the span is unknown). Results are sorted before duplicates
+    /// are dropped, so the order and the copy kept (the one under the first path as given) don't
+    /// depend on which thread finished first.
     pub fn all_results(&self) -> Vec<AnalysisResult> {
-        self.results
+        let mut canonical: HashMap<Arc<str>, PathBuf> = HashMap::new();
+        let mut keyed: Vec<_> = self
+            .results
             .iter()
-            .flat_map(|entry| entry.value().clone())
-            .collect()
+            .flat_map(|entry| entry.value().to_vec())
+            .map(|result| {
+                let class = &result.class_metrics;
+                let path = canonical
+                    .entry(class.file_path.clone())
+                    .or_insert_with(|| {
+                        let path = Path::new(class.file_path.as_ref());
+                        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+                    })
+                    .clone();
+                let name = (!class.span.is_known()).then(|| class.name.clone());
+                ((path, class.span.start, class.span.end, name), result)
+            })
+            .collect();
+
+        keyed.sort_by(|(a, x), (b, y)| {
+            a.cmp(b)
+                .then_with(|| x.class_metrics.file_path.cmp(&y.class_metrics.file_path))
+                .then_with(|| x.class_metrics.fully_qualified_name.cmp(&y.class_metrics.fully_qualified_name))
+        });
+        keyed.dedup_by(|(a, _), (b, _)| a == b);
+        keyed.into_iter().map(|(_, result)| result).collect()
     }
 }
 
@@ -0,0 +1,16 @@
+//! Vendored third-party code detection
+//!
+//! Copied-in dependencies are someone else's design, so they are kept out of
+//! the findings that gate a check.
+
+use std::path::Path;
+
+/// Directory names that conventionally hold vendored code
+pub const VENDOR_DIRS: &[&str] = &["vendor", "vendored", "third_party", "third-party"];
+
+/// Whether a directory holds vendored code, judged by its name
+pub fn is_vendor_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| VENDOR_DIRS.contains(&name))
+}
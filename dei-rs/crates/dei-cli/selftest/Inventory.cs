@@ -0,0 +1,60 @@
+using System.Collections.Generic;
+
+public class Point
+{
+    private int x;
+    private int y;
+
+    public Point(int x, int y) { this.x = x; this.y = y; }
+
+    public int Manhattan() { return System.Math.Abs(x) + System.Math.Abs(y); }
+}
+
+public class Inventory
+{
+    private Dictionary<string, int> items = new Dictionary<string, int>();
+
+    public void Add(string name, int count) { items[name] = count; }
+
+    public void Remove(string name) { items.Remove(name); }
+
+    public int Count() { return items.Count; }
+
+    public int Total()
+    {
+        int total = 0;
+        foreach (var count in items.Values) total += count;
+        return total;
+    }
+
+    public void Clear() { items.Clear(); }
+
+    public int reconcile(int expected, bool strict)
+    {
+        int drift = 0;
+        foreach (var entry in items)
+        {
+            if (entry.Value < 0)
+            {
+                drift -= 1;
+            }
+            else if (entry.Value > expected)
+            {
+                drift += entry.Value - expected;
+            }
+            else if (entry.Key.Length == 0 && strict)
+            {
+                drift += 1;
+            }
+            else if (entry.Key.StartsWith("_") || entry.Key.EndsWith("_"))
+            {
+                drift -= 1;
+            }
+            while (drift > 1000)
+            {
+                drift -= 1000;
+            }
+        }
+        return drift;
+    }
+}
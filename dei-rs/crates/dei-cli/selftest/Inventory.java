@@ -0,0 +1,48 @@
+import java.util.HashMap;
+import java.util.Map;
+
+class Point {
+    private int x;
+    private int y;
+
+    Point(int x, int y) { this.x = x; this.y = y; }
+
+    int manhattan() { return Math.abs(x) + Math.abs(y); }
+}
+
+public class Inventory {
+    private final Map<String, Integer> items = new HashMap<>();
+
+    public void add(String name, int count) { items.put(name, count); }
+
+    public void remove(String name) { items.remove(name); }
+
+    public int count() { return items.size(); }
+
+    public int total() {
+        int total = 0;
+        for (int count : items.values()) total += count;
+        return total;
+    }
+
+    public void clear() { items.clear(); }
+
+    public int reconcile(int expected, boolean strict) {
+        int drift = 0;
+        for (Map.Entry<String, Integer> entry : items.entrySet()) {
+            if (entry.getValue() < 0) {
+                drift -= 1;
+            } else if (entry.getValue() > expected) {
+                drift += entry.getValue() - expected;
+            } else if (entry.getKey().isEmpty() && strict) {
+                drift += 1;
+            } else if (entry.getKey().startsWith("_") || entry.getKey().endsWith("_")) {
+                drift -= 1;
+            }
+            while (drift > 1000) {
+                drift -= 1000;
+            }
+        }
+        return drift;
+    }
+}
@@ -0,0 +1,59 @@
+class Point {
+  constructor(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  manhattan() {
+    return Math.abs(this.x) + Math.abs(this.y);
+  }
+}
+
+class Inventory {
+  constructor() {
+    this.items = new Map();
+  }
+
+  add(name, count) {
+    this.items.set(name, count);
+  }
+
+  remove(name) {
+    this.items.delete(name);
+  }
+
+  count() {
+    return this.items.size;
+  }
+
+  total() {
+    let total = 0;
+    for (const count of this.items.values()) total += count;
+    return total;
+  }
+
+  clear() {
+    this.items.clear();
+  }
+
+  reconcile(expected, strict) {
+    let drift = 0;
+    for (const [name, count] of this.items) {
+      if (count < 0) {
+        drift -= 1;
+      } else if (count > expected) {
+        drift += count - expected;
+      } else if (name.length === 0 && strict) {
+        drift += 1;
+      } else if (name.startsWith("_") || name.endsWith("_")) {
+        drift -= 1;
+      }
+      while (drift > 1000) {
+        drift -= 1000;
+      }
+    }
+    return drift;
+  }
+}
+
+module.exports = { Point, Inventory };
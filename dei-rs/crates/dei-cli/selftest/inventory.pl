@@ -0,0 +1,61 @@
+package Point;
+
+sub new {
+    my ($class, $row, $col) = @_;
+    return bless { row => $row, col => $col }, $class;
+}
+
+sub manhattan {
+    my ($self) = @_;
+    return abs($self->{row}) + abs($self->{col});
+}
+
+package Inventory;
+
+sub new {
+    my ($class) = @_;
+    return bless { items => {} }, $class;
+}
+
+sub add {
+    my ($self, $name, $count) = @_;
+    $self->{items}{$name} = $count;
+}
+
+sub remove {
+    my ($self, $name) = @_;
+    delete $self->{items}{$name};
+}
+
+sub count {
+    my ($self) = @_;
+    return scalar keys %{ $self->{items} };
+}
+
+sub clear {
+    my ($self) = @_;
+    $self->{items} = {};
+}
+
+sub reconcile {
+    my ($self, $expected, $strict) = @_;
+    my $drift = 0;
+    foreach my $name (keys %{ $self->{items} }) {
+        my $count = $self->{items}{$name};
+        if ($count < 0) {
+            $drift -= 1;
+        } elsif ($count > $expected) {
+            $drift += $count - $expected;
+        } elsif ($name eq '' && $strict) {
+            $drift += 1;
+        } elsif ($name =~ /^_/ || $name =~ /_$/) {
+            $drift -= 1;
+        }
+        while ($drift > 1000) {
+            $drift -= 1000;
+        }
+    }
+    return $drift;
+}
+
+1;
@@ -0,0 +1,42 @@
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def manhattan(self):
+        return abs(self.x) + abs(self.y)
+
+
+class Inventory:
+    def __init__(self):
+        self.items = {}
+
+    def add(self, name, count):
+        self.items[name] = count
+
+    def remove(self, name):
+        self.items.pop(name, None)
+
+    def count(self):
+        return len(self.items)
+
+    def total(self):
+        return sum(self.items.values())
+
+    def clear(self):
+        self.items.clear()
+
+    def reconcile(self, expected, strict):
+        drift = 0
+        for name, count in self.items.items():
+            if count < 0:
+                drift -= 1
+            elif count > expected:
+                drift += count - expected
+            elif not name and strict:
+                drift += 1
+            elif name.startswith("_") or name.endswith("_"):
+                drift -= 1
+            while drift > 1000:
+                drift -= 1000
+        return drift
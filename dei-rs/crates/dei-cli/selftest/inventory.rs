@@ -0,0 +1,59 @@
+pub struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(&self) -> i64 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+pub struct Inventory {
+    items: Vec<(String, i64)>,
+}
+
+impl Inventory {
+    pub fn add(&mut self, name: String, count: i64) {
+        self.items.push((name, count));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.items.retain(|(n, _)| n != name);
+    }
+
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn total(&self) -> i64 {
+        self.items.iter().map(|(_, c)| c).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn reconcile(&mut self, expected: i64, strict: bool) -> i64 {
+        let mut drift = 0;
+        for (name, count) in self.items.iter_mut() {
+            if *count < 0 {
+                *count = 0;
+            } else if *count > expected {
+                drift += *count - expected;
+            } else if name.is_empty() && strict {
+                drift += 1;
+            } else if name.starts_with('_') || name.ends_with('_') {
+                drift -= 1;
+            }
+            while *count > 1000 {
+                *count -= 1000;
+            }
+        }
+        drift
+    }
+}
@@ -0,0 +1,63 @@
+export class Point {
+  private x: number;
+  private y: number;
+
+  constructor(x: number, y: number) {
+    this.x = x;
+    this.y = y;
+  }
+
+  manhattan() {
+    return Math.abs(this.x) + Math.abs(this.y);
+  }
+}
+
+export class Inventory {
+  private items: Map<string, number>;
+
+  constructor() {
+    this.items = new Map();
+  }
+
+  add(name: string, count: number): void {
+    this.items.set(name, count);
+  }
+
+  remove(name: string): void {
+    this.items.delete(name);
+  }
+
+  count() {
+    return this.items.size;
+  }
+
+  total() {
+    let total = 0;
+    for (const count of this.items.values()) total += count;
+    return total;
+  }
+
+  clear() {
+    this.items.clear();
+  }
+
+  reconcile(expected: number, strict: boolean): number {
+    let drift = 0;
+    for (const [name, count] of this.items) {
+      if (count < 0) {
+        drift -= 1;
+      } else if (count > expected) {
+        drift += count - expected;
+      } else if (name.length === 0 && strict) {
+        drift += 1;
+      } else if (name.startsWith("_") || name.endsWith("_")) {
+        drift -= 1;
+      }
+      while (drift > 1000) {
+        drift -= 1000;
+      }
+    }
+    return drift;
+  }
+}
+
@@ -0,0 +1,197 @@
+//! API command - public surface per module, for library authors
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_core::{
+    config::DeiConfig,
+    metrics::FileMetrics,
+    models::{Severity, Violation, ViolationKind},
+};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::pipeline;
+
+/// Limits on how much a single module may expose
+#[derive(Debug, Clone, Copy)]
+pub struct ApiLimits {
+    pub max_public_types: usize,
+    pub max_public_methods: usize,
+}
+
+/// When oversized APIs fail the run, decided as `dei check` decides for its findings
+#[derive(Debug, Clone, Copy)]
+pub struct ApiGate {
+    pub fail_on: Option<Severity>,
+    pub max_issues: Option<usize>,
+    pub no_fail: bool,
+}
+
+/// Public surface of one module (source file)
+#[derive(Debug, Serialize)]
+struct ModuleApi {
+    path: String,
+    lines: usize,
+    types: Vec<TypeApi>,
+    public_methods: usize,
+    /// Lines spent in public methods
+    public_lines: usize,
+    violations: Vec<Violation>,
+}
+
+#[derive(Debug, Serialize)]
+struct TypeApi {
+    name: String,
+    methods: Vec<MethodApi>,
+}
+
+#[derive(Debug, Serialize)]
+struct MethodApi {
+    signature: String,
+    lines: usize,
+    complexity: usize,
+}
+
+impl ModuleApi {
+    fn from_file(file: &FileMetrics, limits: ApiLimits, config: &DeiConfig) -> Self {
+        // A type is part of the surface when it is public and exposes at least one public method
+        let types: Vec<TypeApi> = file
+            .classes
+            .iter()
+            .filter(|class| class.is_public)
+            .filter_map(|class| {
+                let methods: Vec<MethodApi> = class
+                    .methods
+                    .iter()
+                    .filter(|m| m.is_public)
+                    .map(|m| MethodApi {
+                        signature: m.signature(),
+                        lines: m.lines.0,
+                        complexity: m.complexity.0,
+                    })
+                    .collect();
+
+                (!methods.is_empty()).then(|| TypeApi {
+                    name: class.name.to_string(),
+                    methods,
+                })
+            })
+            .collect();
+
+        let public_methods = types.iter().map(|t| t.methods.len()).sum();
+        let public_lines = types
+            .iter()
+            .flat_map(|t| t.methods.iter())
+            .map(|m| m.lines)
+            .sum();
+
+        // Severities follow `[violations]` like any other finding's
+        let violations = [
+            (ViolationKind::PublicTypes, types.len(), limits.max_public_types),
+            (ViolationKind::PublicMethods, public_methods, limits.max_public_methods),
+        ]
+        .into_iter()
+        .filter(|&(_, actual, threshold)| actual > threshold)
+        .map(|(kind, actual, threshold)| Violation::new(kind, actual, threshold))
+        .filter_map(|violation| {
+            let severity = config.violations.severity(&violation)?;
+            Some(Violation { severity, ..violation })
+        })
+        .collect();
+
+        Self {
+            path: file.path.to_string(),
+            lines: file.lines.0,
+            types,
+            public_methods,
+            public_lines,
+            violations,
+        }
+    }
+}
+
+pub async fn run(path: PathBuf, limits: ApiLimits, gate: ApiGate, format: String, verbose: bool) -> Result<()> {
+    let is_json = format == "json";
+
+    let (config_path, config) = pipeline::load_config(None, &path)?;
+    let analysis = pipeline::project_engine(config_path.as_deref(), &config, &path)?.analyze(&path)?;
+
+    // In path order, as the engine returns files
+    let modules: Vec<ModuleApi> = analysis
+        .files
+        .iter()
+        .map(|file| ModuleApi::from_file(file, limits, &config))
+        .filter(|module| !module.types.is_empty())
+        .collect();
+
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(&modules)?);
+    } else {
+        print_text(&modules, verbose);
+    }
+
+    // Like `dei check`: exit 1 past --max-issues violations at or above --fail-on
+    let fail_on = gate.fail_on.or(config.fail_on).unwrap_or(Severity::Warning);
+    let issues = modules
+        .iter()
+        .flat_map(|module| &module.violations)
+        .filter(|violation| violation.severity >= fail_on)
+        .count();
+    let allowed = gate.max_issues.or(config.max_issues).unwrap_or(0);
+    if !gate.no_fail && issues > allowed {
+        crate::telemetry::exit(1);
+    }
+    Ok(())
+}
+
+fn print_text(modules: &[ModuleApi], verbose: bool) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║              DEI - PUBLIC API SURFACE                      ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+
+    let flagged = modules.iter().filter(|m| !m.violations.is_empty()).count();
+
+    println!("{}", "SUMMARY:".bright_green().bold());
+    println!();
+    println!("  {} {}", "Modules:".bold(), modules.len());
+    println!(
+        "  {} {}",
+        "Public Methods:".bold(),
+        modules.iter().map(|m| m.public_methods).sum::<usize>()
+    );
+    println!("  {} {}", "Oversized APIs:".bold(), flagged.to_string().red());
+    println!();
+
+    for module in modules {
+        let marker = if module.violations.is_empty() { "📦".normal() } else { "❌".red() };
+        println!("  {} {}", marker, module.path.bold());
+        println!(
+            "     Types: {} | Public Methods: {} | Public Lines: {} / {}",
+            module.types.len().to_string().yellow(),
+            module.public_methods.to_string().yellow(),
+            module.public_lines,
+            module.lines
+        );
+
+        for violation in &module.violations {
+            let what = match violation.kind {
+                ViolationKind::PublicTypes => "public types",
+                _ => "public methods",
+            };
+            let line = format!("{} {} exceeds {}", violation.actual, what, violation.threshold);
+            println!("     • {}", line.red());
+        }
+
+        if verbose {
+            for ty in &module.types {
+                println!("       {}", ty.name.bright_cyan());
+                for method in &ty.methods {
+                    println!("         {} ({} lines)", method.signature, method.lines);
+                }
+            }
+        }
+
+        println!();
+    }
+}
@@ -1,41 +1,91 @@
 //! Architecture analysis command
 
 use anyhow::Result;
+use clap::ValueEnum;
 use colored::Colorize;
-use dei_ast::{AstBuilder, ParallelTraverser};
-use dei_core::thresholds::Thresholds;
-use dei_languages::MultiLanguageParser;
-use dei_metrics::CouplingAnalyzer;
+use dei_metrics::{graph::EdgeKind, CargoWorkspace, DirectionViolationKind, Edge, LayerRules, PackageGrouping};
 use std::path::PathBuf;
 
-pub async fn run(path: PathBuf) -> Result<()> {
+use super::pipeline::{self, load_saved_state};
+
+/// What `--packages` groups classes by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Grouping {
+    /// Directory of the declaring file
+    Directory,
+    /// Namespace or module path of the class
+    Namespace,
+}
+
+impl From<Grouping> for PackageGrouping {
+    fn from(grouping: Grouping) -> Self {
+        match grouping {
+            Grouping::Directory => PackageGrouping::Directory,
+            Grouping::Namespace => PackageGrouping::Namespace,
+        }
+    }
+}
+
+pub async fn run(
+    path: PathBuf,
+    max_third_party: usize,
+    packages: Option<Grouping>,
+    min_betweenness: f64,
+    load_state: Option<PathBuf>,
+) -> Result<()> {
     println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
     println!("{}", "║         DEI - ARCHITECTURE QUALITY ANALYSIS                ║".bright_cyan());
     println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
     println!();
 
-    // Build AST and analyze
-    let builder = AstBuilder::new();
-    let root_id = builder.build(&path)?;
+    // Package manifests tell third-party imports apart from internal coupling, and dei.toml
+    // next to them sets the ignores, rules and layers
+    let manifest_root = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(&path) };
+    let (config_path, config) = pipeline::load_config(None, manifest_root)?;
 
-    let parser = MultiLanguageParser::new()?;
-    let traverser = ParallelTraverser::new(parser, builder.arena().clone());
-    let thresholds = Thresholds::default();
-    traverser.traverse_and_analyze(root_id, &thresholds)?;
-
-    let all_results = traverser.all_results();
+    // Build AST, or restore a saved one, and analyze
+    let engine = pipeline::project_engine(config_path.as_deref(), &config, &path)?;
+    let engine = match &load_state {
+        Some(file) => {
+            let (arena, root) = load_saved_state(file, &path)?;
+            engine.restored(arena, root)
+        }
+        None => engine.keep_tree(true),
+    };
+    let analysis = engine.analyze(&path)?;
 
     // Extract all classes
-    let classes: Vec<_> = all_results
+    let classes: Vec<_> = analysis
+        .results
         .iter()
         .map(|r| r.class_metrics.clone())
         .collect();
 
-    // Build coupling graph
-    let mut coupling_analyzer = CouplingAnalyzer::new();
-    coupling_analyzer.build_graph(&classes);
+    // Build coupling graph, resolving references through the project symbol table
+    let coupling_analyzer = pipeline::coupling_analyzer(&path, &analysis)?;
+
+    let package_analysis = packages.map(|grouping| coupling_analyzer.packages(&classes, grouping.into(), manifest_root));
+    let mut metrics = coupling_analyzer.architecture_quality();
+    let choke_points = coupling_analyzer.choke_points(min_betweenness);
+    if !choke_points.is_empty() {
+        println!("{}", "⚠️  CHOKE POINTS:".yellow().bold());
+        println!();
+
+        for point in &choke_points {
+            println!(
+                "  🚧 {} lies on {} of dependency paths ({} dependents, {} dependencies)",
+                point.node.bold(),
+                format!("{:.0}%", point.betweenness * 100.0).red(),
+                point.fan_in,
+                point.fan_out
+            );
+        }
+        println!();
+    }
 
-    let metrics = coupling_analyzer.architecture_quality();
+    if let Some(analysis) = &package_analysis {
+        metrics = metrics.with_packages(analysis);
+    }
 
     println!("{}", "ARCHITECTURE METRICS:".bright_green().bold());
     println!();
@@ -43,16 +93,145 @@ pub async fn run(path: PathBuf) -> Result<()> {
     println!("  {} {}", "Circular Dependencies:".bold(), metrics.n_cycles);
     println!("  {} {:.2}", "Cyclomatic Quality:".bold(), metrics.cyclomatic_quality);
     println!("  {} {:.2}", "Maintainability Index:".bold(), metrics.maintainability_index);
+    if let Some(distance) = metrics.mean_distance() {
+        println!("  {} {:.2}", "Mean Distance from Main Sequence:".bold(), distance);
+    }
     println!();
 
     if metrics.n_cycles > 0 {
         println!("{}", "⚠️  CIRCULAR DEPENDENCIES DETECTED:".yellow().bold());
         println!();
         
+        let graph = coupling_analyzer.graph();
         for cycle in coupling_analyzer.find_tight_coupling() {
             println!("  🔄 {}", cycle.join(" → ").red());
+            for from in &cycle {
+                for to in cycle.iter().filter(|to| *to != from) {
+                    let edges = graph.edges_between(from, to);
+                    if !edges.is_empty() {
+                        println!("     {} → {} via {}", from, to, describe_dependency(&edges).dimmed());
+                    }
+                }
+            }
+        }
+        println!();
+    }
+
+    if let Some(analysis) = &package_analysis {
+        println!("{}", "PACKAGE COUPLING:".bright_green().bold());
+        println!();
+        let width = metrics.packages.iter().map(|p| p.name.len()).max().unwrap_or(0).max("Package".len());
+        println!(
+            "  {:<width$}  {:>7}  {:>4}  {:>4}  {:>11}  {:>12}  {:>8}",
+            "Package".bold(), "Classes", "Ca", "Ce", "Instability", "Abstractness", "Distance"
+        );
+        for package in &metrics.packages {
+            println!(
+                "  {:<width$}  {:>7}  {:>4}  {:>4}  {:>11.2}  {:>12.2}  {:>8.2}",
+                package.name,
+                package.classes,
+                package.afferent,
+                package.efferent,
+                package.instability,
+                package.abstractness,
+                package.distance
+            );
+        }
+        println!();
+
+        let cycles = analysis.cycles();
+        if !cycles.is_empty() {
+            println!("{}", "⚠️  CROSS-PACKAGE CYCLES:".yellow().bold());
+            println!();
+            for cycle in &cycles {
+                println!("  🔄 {}", cycle.join(" ⇄ ").red());
+            }
+            println!();
+        }
+    }
+
+    // Layers from dei.toml; breaking them fails the run
+    let mut layers = LayerRules::new(config.architecture.layers.iter().map(String::as_str));
+    for result in &analysis.results {
+        if let Some(layer) = &result.metadata.layer {
+            layers.assign(result.class_metrics.file_path.clone(), layer.clone());
+        }
+    }
+    let layer_violations = layers.check(&coupling_analyzer, &classes, manifest_root);
+    if !layers.is_empty() {
+        println!("{}", "LAYERING:".bright_green().bold());
+        println!();
+        println!("  {} {}", "Layers:".bold(), layers.layers().join(" → "));
+        println!("  {} {}", "Violations:".bold(), layer_violations.len());
+        println!();
+
+        for violation in &layer_violations {
+            println!(
+                "  {} {} ({}) depends on {} ({})",
+                "⛔".red(),
+                violation.class.bold(),
+                violation.layer,
+                violation.target.red(),
+                violation.target_layer
+            );
+            let edges = coupling_analyzer.graph().edges_between(&violation.class, &violation.target);
+            println!("     {}", violation.file.dimmed());
+            println!("     via {}", describe_dependency(&edges).dimmed());
+        }
+        if !layer_violations.is_empty() {
+            println!();
         }
+    }
+
+    let heavy_importers = coupling_analyzer.excessive_third_party(max_third_party);
+    if !heavy_importers.is_empty() {
+        println!("{}", "⚠️  THIRD-PARTY FAN-OUT:".yellow().bold());
+        println!();
+
+        for (class, count) in &heavy_importers {
+            let packages = coupling_analyzer.third_party_dependencies(class);
+            println!(
+                "  📦 {} imports {} packages (limit {})",
+                class.bold(),
+                count.to_string().red(),
+                max_third_party
+            );
+            println!("     {}", packages.join(", ").dimmed());
+        }
+        println!();
+    }
+
+    // Cargo manifests: declared vs actual dependency direction
+    if path.join("Cargo.toml").is_file() {
+        let workspace = CargoWorkspace::load(&path)?;
+        let violations = workspace.check_direction();
+
+        println!("{}", "DEPENDENCY DIRECTION:".bright_green().bold());
         println!();
+        println!("  {} {}", "Crates:".bold(), workspace.members.len());
+        println!("  {} {}", "Violations:".bold(), violations.len());
+        println!();
+
+        for violation in &violations {
+            let what = match violation.kind {
+                DirectionViolationKind::UndeclaredCrate => {
+                    format!("uses undeclared crate '{}'", violation.target)
+                }
+                DirectionViolationKind::LayerViolation => {
+                    format!("reaches up into layer '{}'", violation.target)
+                }
+            };
+            println!(
+                "  {} {}:{} {}",
+                "⛔".red(),
+                violation.file.display(),
+                violation.line,
+                what.red()
+            );
+        }
+        if !violations.is_empty() {
+            println!();
+        }
     }
 
     // Quality assessment
@@ -69,6 +248,42 @@ pub async fn run(path: PathBuf) -> Result<()> {
     println!("{} {}", "Overall Quality:".bold(), quality.bold());
     println!();
 
+    if !layer_violations.is_empty() {
+        crate::telemetry::exit(1);
+    }
     Ok(())
 }
 
+/// "14 calls in 3 methods (load, save, sync), 1 reference"
+fn describe_dependency(edges: &[&Edge]) -> String {
+    let mut calls = 0;
+    let mut references = 0;
+    let mut methods: Vec<&str> = Vec::new();
+    for edge in edges {
+        match edge.kind {
+            EdgeKind::Calls => {
+                calls += edge.weight;
+                for method in edge.sites.iter().filter_map(|site| site.method.as_deref()) {
+                    if !methods.contains(&method) {
+                        methods.push(method);
+                    }
+                }
+            }
+            _ => references += edge.weight,
+        }
+    }
+
+    let plural = |n: usize, word: &str| if n == 1 { format!("{n} {word}") } else { format!("{n} {word}s") };
+    let mut parts = Vec::new();
+    if calls > 0 {
+        let mut part = plural(calls, "call");
+        if !methods.is_empty() {
+            part += &format!(" in {} ({})", plural(methods.len(), "method"), methods.join(", "));
+        }
+        parts.push(part);
+    }
+    if references > 0 {
+        parts.push(plural(references, "reference"));
+    }
+    parts.join(", ")
+}
@@ -1,113 +1,1071 @@
 //! Check command - main analysis entry point
 
-use anyhow::Result;
+use anyhow::{bail, Context as _, Result};
+use clap::{Args, ValueEnum};
 use colored::Colorize;
-use dei_ast::{AstBuilder, ParallelTraverser};
-use dei_clustering::ClusteringAnalyzer;
 use dei_core::{
-    thresholds::{Complexity, Lines, MethodCount, Thresholds},
-    traits::ClusterAnalyzer,
+    baseline::{Decisions, Finding},
+    config::DeiConfig,
+    detector::DetectorRegistry,
+    metrics::FileMetrics,
+    models::{
+        AnalysisResult, BaseComparison, FileError, FileWarning, GodFileResult, ImportHygieneResult, Language, Severity,
+    },
+    thresholds::{ChainLength, Complexity, Lines, MethodCount, Preset, Thresholds},
+    traits::Parser,
+};
+use dei_engine::{project, Analysis, ParseOptions, Phase, ProgressObserver, SlowFile, TimeBudget, RULES_DIR};
+use dei_git::{
+    annotate_code_age, annotate_ownership, attribute_violations, blame::DEFAULT_MIN_SHARE,
+    prioritize_by_growth, GitRepo,
 };
 use dei_languages::MultiLanguageParser;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::pipeline::{self, decisions_root, load_detectors};
+use crate::filter::Filter;
+use crate::report::{
+    self, FileCounts, JsonReport, MethodEntry, ReportGenerator, ReportGroup, ReportMetadata, ReportSummary, TemplateReport,
+};
 
-use crate::report::ReportGenerator;
+/// Options for a single `dei check` run
+///
+/// Threshold flags and `format` are `None` when not given, so `dei.toml` can supply them
+#[derive(Debug, Clone, Args)]
+pub struct CheckOptions {
+    /// Built-in thresholds: strict, default, lenient, or a language (rust, python, ...) for its idiomatic limits
+    #[arg(long, value_name = "PRESET")]
+    pub preset: Option<Preset>,
 
-pub async fn run(
-    path: PathBuf,
-    max_lines: usize,
-    max_methods: usize,
-    max_complexity: usize,
-    format: String,
-    verbose: bool,
-) -> Result<()> {
-    let is_json = format == "json";
+    /// Maximum class lines [default: 300]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Maximum methods per class [default: 20]
+    #[arg(long)]
+    pub max_methods: Option<usize>,
+
+    /// Maximum cyclomatic complexity [default: 50]
+    #[arg(long)]
+    pub max_complexity: Option<usize>,
+
+    /// Maximum fields per class [default: 15]
+    #[arg(long)]
+    pub max_fields: Option<usize>,
+
+    /// Maximum imported dependencies per class [default: 20]
+    #[arg(long)]
+    pub max_dependencies: Option<usize>,
+
+    /// Maximum LCOM4 (unrelated method groups per class) [default: 2]
+    #[arg(long)]
+    pub max_lcom4: Option<usize>,
+
+    /// Maximum accessor chain length in a method (`a.b().c()` is 2) [default: 4]
+    #[arg(long)]
+    pub max_chain_length: Option<usize>,
+
+    /// Flag methods with more return/break/continue/throw statements than this
+    #[arg(long)]
+    pub max_exit_points: Option<usize>,
+
+    /// Flag god methods with more magic literals per line than this
+    #[arg(long)]
+    pub max_magic_density: Option<f64>,
+
+    /// Output format (text, json, html), or FORMAT:PATH to also write one to a file; repeatable [default: text]
+    #[arg(long, value_name = "FORMAT[:PATH]")]
+    pub format: Vec<String>,
+
+    /// Write the report to this file instead of stdout
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Config file to use instead of the nearest dei.toml
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Show detailed analysis
+    #[arg(long, short)]
+    pub verbose: bool,
+
+    /// Include structural AST snippets of flagged methods in JSON output
+    #[arg(long)]
+    pub ast_snippets: bool,
+
+    /// List the metrics of every method in JSON output, healthy ones too
+    #[arg(long)]
+    pub include_all_methods: bool,
+
+    /// Cluster the methods of god classes into suggested extractions (slower)
+    #[arg(long)]
+    pub suggest_extractions: bool,
+
+    /// Render the report with a Tera template (text, Markdown, HTML, ...)
+    #[arg(long)]
+    pub template: Option<PathBuf>,
+
+    /// Attribute flagged code to its main git authors (text report)
+    #[arg(long)]
+    pub blame: bool,
+
+    /// List the dominant git authors of each flagged class and method (text and JSON)
+    #[arg(long)]
+    pub owners: bool,
+
+    /// Rank fast-growing flagged code first, using git blame ages
+    #[arg(long)]
+    pub age_weighting: bool,
+
+    /// Days of history that count as recent growth for --age-weighting
+    #[arg(long, default_value = "90")]
+    pub growth_window_days: u64,
+
+    /// Only analyze files changed since this git ref and compare god classes against it
+    #[arg(long, value_name = "REF")]
+    pub changed_since: Option<String>,
+
+    /// Warn about files taking longer than this many seconds to parse or analyze
+    #[arg(long, default_value = "10")]
+    pub max_file_seconds: f64,
+
+    /// Leave files over --max-file-seconds out of the report
+    #[arg(long)]
+    pub skip_slow_files: bool,
+
+    /// Fail on the first file that can't be read or parsed instead of skipping it
+    #[arg(long)]
+    pub strict: bool,
 
-    if !is_json {
+    /// Analyze minified/compiled JS and CSS bundles instead of skipping them
+    #[arg(long)]
+    pub include_minified: bool,
+
+    /// Skip files larger than this, e.g. 512K or 10M; 0 for no limit
+    #[arg(long, value_name = "SIZE", default_value = "10M", value_parser = parse_size)]
+    pub max_file_size: u64,
+
+    /// Analyze vendored directories (vendor/, third_party/) into a separate, ungated report section
+    #[arg(long)]
+    pub include_vendored: bool,
+
+    /// Follow symbolic links; loops are skipped and each file is analyzed once
+    #[arg(long)]
+    pub follow_symlinks: bool,
+
+    /// Save the file tree and parsed metrics here for `--load-state` in arch, trend and serve
+    #[arg(long, value_name = "FILE")]
+    pub save_state: Option<PathBuf>,
+
+    /// Only analyze files matching this gitignore-style glob, relative to PATH; repeatable
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip files and directories matching this gitignore-style glob, relative to PATH; repeatable
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Only analyze these languages, e.g. rust,python
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<Language>,
+
+    /// Skip these languages, e.g. javascript,typescript
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub exclude_lang: Vec<Language>,
+
+    /// Only report results matching an expression, e.g. 'lang == "python" && complexity > 30'
+    #[arg(long, value_name = "EXPR")]
+    pub filter: Option<Filter>,
+
+    /// Lowest severity that makes the check exit 1 (info, warning, error) [default: warning]
+    #[arg(long, value_name = "SEVERITY")]
+    pub fail_on: Option<Severity>,
+
+    /// Only exit 1 when more than N findings are at or above --fail-on
+    #[arg(long, value_name = "N")]
+    pub max_issues: Option<usize>,
+
+    /// Always exit 0, whatever the findings; the summary line still reports them
+    #[arg(long)]
+    pub no_fail: bool,
+
+    /// Order results by a metric, largest first
+    #[arg(long, value_enum)]
+    pub sort: Option<SortKey>,
+
+    /// Only report classes with a finding at least this severe (info, warning, error)
+    #[arg(long, value_name = "SEVERITY")]
+    pub min_severity: Option<Severity>,
+
+    /// Only report god classes, classes with god methods, or classes in god files; repeatable
+    #[arg(long, value_enum)]
+    pub only: Vec<FindingKind>,
+
+    /// Only report the aggregate counts
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Only report the N flagged classes with the highest violation score, worst first
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Report subtotals and the worst classes per directory, language or project instead of every class
+    #[arg(long, value_enum, value_name = "GROUPING")]
+    pub group_by: Option<GroupBy>,
+
+    /// Also report findings accepted into the baseline or suppressed by `dei triage`
+    #[arg(long)]
+    pub no_baseline: bool,
+
+    /// Print the JSON Schema of `--format json` output and exit
+    #[arg(long)]
+    pub schema: bool,
+}
+
+pub async fn run(path: PathBuf, options: CheckOptions) -> Result<()> {
+    // The repository config can pick the output format, so it is loaded first
+    let (config_path, config) = pipeline::load_config(options.config.clone(), &path)?;
+    let config = match options.preset {
+        Some(preset) => config.with_preset(preset)?,
+        None => config,
+    };
+    let targets = report_targets(&options.format, config.format.as_deref(), options.template.clone(), options.output.clone())?;
+    let root = decisions_root(config_path.as_deref(), &path);
+    let (detectors, rule_count) = load_detectors(&root)?;
+
+    // JSON, HTML and templates on stdout own the whole output, so skip the banner and progress lines
+    let raw_output = targets
+        .iter()
+        .any(|t| t.path.is_none() && t.format != ReportFormat::Text);
+
+    if !raw_output {
         println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
         println!("{}", "║           DEI - CODE ANALYSIS (Rust Edition)               ║".bright_cyan());
         println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
         println!();
+        if let Some(config_path) = &config_path {
+            println!("⚙️  Config: {}", config_path.display().to_string().bright_yellow());
+        }
+        if let Some(preset) = config.preset {
+            println!("🎚️  Preset: {}", preset.to_string().bright_yellow());
+        }
+        if rule_count > 0 {
+            let dir = root.join(RULES_DIR).display().to_string();
+            println!("📜 Rules: {} from {}", rule_count, dir.bright_yellow());
+        }
     }
 
-    // Setup thresholds
-    let thresholds = Thresholds {
-        max_class_lines: Lines(max_lines),
-        max_methods: MethodCount(max_methods),
-        max_class_complexity: Complexity(max_complexity),
-        ..Default::default()
-    };
-
-    thresholds.validate().map_err(|e| anyhow::anyhow!(e))?;
+    let thresholds = options.thresholds(&config)?;
 
-    if !is_json {
+    if !raw_output {
         println!("📂 Analyzing: {}", path.display().to_string().bright_yellow());
         println!();
     }
 
-    // Build AST
-    let spinner = if !is_json {
+    // Every history-based option shares one repository handle
+    let needs_git = options.blame || options.owners || options.age_weighting || options.changed_since.is_some();
+    let repo = if needs_git { Some(GitRepo::discover(&path)?) } else { None };
+
+    let analysis = analyze(&path, &options, &config, detectors, &thresholds, repo.as_ref(), raw_output)?;
+    let findings = Findings::judge(analysis, &path, &options, &config, &root, repo.as_ref(), raw_output)?;
+    write_reports(&path, &options, &thresholds, &targets, &findings, repo.as_ref(), raw_output)?;
+
+    let line = SummaryLine::of(&findings.results, &findings.god_files, &findings.baseline).to_string();
+    if raw_output {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+
+    crate::telemetry::exit(exit_code(&options, &config, &findings));
+}
+
+impl CheckOptions {
+    /// Flags override dei.toml, which overrides the preset and then the defaults
+    fn thresholds(&self, config: &DeiConfig) -> Result<Thresholds> {
+        let mut thresholds = config.thresholds.clone();
+        if let Some(max_lines) = self.max_lines {
+            thresholds.max_class_lines = Lines(max_lines);
+        }
+        if let Some(max_methods) = self.max_methods {
+            thresholds.max_methods = MethodCount(max_methods);
+        }
+        if let Some(max_complexity) = self.max_complexity {
+            thresholds.max_class_complexity = Complexity(max_complexity);
+        }
+        if let Some(max_chain_length) = self.max_chain_length {
+            thresholds.max_chain_length = ChainLength(max_chain_length);
+        }
+        thresholds.max_fields = self.max_fields.unwrap_or(thresholds.max_fields);
+        thresholds.max_dependencies = self.max_dependencies.unwrap_or(thresholds.max_dependencies);
+        thresholds.max_lcom4 = self.max_lcom4.unwrap_or(thresholds.max_lcom4);
+        thresholds.max_exit_points = self.max_exit_points.or(thresholds.max_exit_points);
+        thresholds.max_magic_density = self.max_magic_density.or(thresholds.max_magic_density);
+
+        thresholds.validate().map_err(|e| anyhow::anyhow!(e))?;
+        Ok(thresholds)
+    }
+}
+
+/// Run the engine as the options ask, printing progress and what was skipped unless the
+/// report owns stdout
+fn analyze(
+    path: &Path,
+    options: &CheckOptions,
+    config: &DeiConfig,
+    detectors: DetectorRegistry,
+    thresholds: &Thresholds,
+    repo: Option<&GitRepo>,
+    raw_output: bool,
+) -> Result<Analysis> {
+    let budget = TimeBudget::new(Duration::try_from_secs_f64(options.max_file_seconds)?)
+        .with_skip_slow(options.skip_slow_files);
+    let mut engine = pipeline::engine(config, detectors)
+        .thresholds(thresholds.clone())
+        .time_budget(budget)
+        .languages(options.lang.iter().copied())
+        .exclude_languages(options.exclude_lang.iter().copied())
+        .suggest_extractions(options.suggest_extractions)
+        .strict(options.strict)
+        .include_minified(options.include_minified)
+        .max_file_size(options.max_file_size)
+        .include_vendored(options.include_vendored)
+        .follow_symlinks(options.follow_symlinks)
+        .parse_options(ParseOptions::default().with_ast_snippets(options.ast_snippets))
+        .keep_tree(options.save_state.is_some());
+    for glob in &options.include {
+        engine = engine.include(glob.clone());
+    }
+    for glob in &options.exclude {
+        engine = engine.exclude(glob.clone());
+    }
+
+    // Diff-aware runs only look at what changed since the base ref
+    let changes = match (&options.changed_since, repo) {
+        (Some(base), Some(repo)) => Some(repo.changed_files(base)?),
+        _ => None,
+    };
+    if let (Some(files), Some(repo)) = (&changes, repo) {
+        engine = engine.only_files(files.iter().map(|f| repo.workdir().join(f)));
+    }
+
+    // Parse and analyze
+    let spinner = if !raw_output {
         let s = ProgressBar::new_spinner();
         s.set_style(
             ProgressStyle::default_spinner()
                 .template("{spinner:.cyan} {msg}")
                 .unwrap(),
         );
-        s.set_message("Building filesystem AST...");
+        s.set_message("Analyzing files in parallel...");
+        engine = engine.observer(Arc::new(SpinnerProgress::new(s.clone())));
         Some(s)
     } else {
         None
     };
+    let started = Instant::now();
+    let analysis = engine.analyze(path)?;
 
-    let builder = AstBuilder::new();
-    let root_id = builder.build(&path)?;
-    
     if let Some(s) = spinner {
         s.finish_and_clear();
-        println!("{}", "✓ AST built".green());
+        println!("{} in {:.1}ms", "✓ Analysis complete".green(), started.elapsed().as_secs_f64() * 1000.0);
+        if let (Some(base), Some(files)) = (&options.changed_since, &changes) {
+            println!("🔀 {} file(s) changed since {}", files.len(), base.bright_yellow());
+        }
+        print_skipped(&analysis, options.include_vendored);
+    }
+    if let (Some(file), Some(tree)) = (&options.save_state, &analysis.tree) {
+        tree.save_state(path).save(file)?;
+        if !raw_output {
+            println!("💾 Saved state to {}", file.display().to_string().bright_yellow());
+        }
     }
+    for slow in &analysis.slow_files {
+        print_slow_file(slow);
+    }
+    if !raw_output {
+        println!();
+    }
+    Ok(analysis)
+}
 
-    // Parse and analyze
-    let spinner = if !is_json {
-        let s = ProgressBar::new_spinner();
-        s.set_message("Analyzing files in parallel...");
-        Some(s)
-    } else {
-        None
+/// What the walk left out, one dimmed line per reason
+fn print_skipped(analysis: &Analysis, include_vendored: bool) {
+    if analysis.skipped_minified > 0 {
+        println!(
+            "{}",
+            format!("⏭️  Skipped {} minified/compiled file(s) (--include-minified to analyze)", analysis.skipped_minified)
+                .dimmed()
+        );
+    }
+    if analysis.skipped_large > 0 {
+        println!(
+            "{}",
+            format!("⏭️  Skipped {} file(s) over --max-file-size", analysis.skipped_large).dimmed()
+        );
+    }
+    if analysis.skipped_duplicates > 0 {
+        println!(
+            "{}",
+            format!("⏭️  Skipped {} duplicate path(s) to files already analyzed", analysis.skipped_duplicates)
+                .dimmed()
+        );
+    }
+    for link in &analysis.symlink_loops {
+        println!("{}", format!("⏭️  Skipped symlink loop at {}", link.display()).dimmed());
+    }
+    if analysis.vendored_dirs > 0 && !include_vendored {
+        println!(
+            "{}",
+            format!("⏭️  Skipped {} vendored director(ies) (--include-vendored to analyze)", analysis.vendored_dirs)
+                .dimmed()
+        );
+    }
+}
+
+/// What a check reports: the engine's findings after config, triage decisions, the report
+/// filters and git annotations
+struct Findings {
+    results: Vec<AnalysisResult>,
+    /// Vendored classes, reported on their own and never gating the check
+    external: Vec<AnalysisResult>,
+    god_files: Vec<GodFileResult>,
+    import_hygiene: Vec<ImportHygieneResult>,
+    file_errors: Vec<FileError>,
+    file_warnings: Vec<FileWarning>,
+    files_analyzed: usize,
+    /// Accepted and suppressed findings, loaded even under --no-baseline to tell new ones apart
+    baseline: Decisions,
+}
+
+impl Findings {
+    fn judge(
+        analysis: Analysis,
+        path: &Path,
+        options: &CheckOptions,
+        config: &DeiConfig,
+        root: &Path,
+        repo: Option<&GitRepo>,
+        raw_output: bool,
+    ) -> Result<Self> {
+        let coupling = pipeline::coupling_analyzer(path, &analysis)?;
+        let analysis = project::apply_config(config, analysis);
+        let Analysis { results, vendored, god_files, import_hygiene, file_errors, file_warnings, files, .. } = analysis;
+        let only = &options.only;
+        let min_severity = options.min_severity;
+
+        // Accepted and suppressed findings stay out of the report and the exit code
+        // The baseline still tells new findings apart for the summary line under --no-baseline
+        let baseline = Decisions::load(root)?;
+        let no_decisions = Decisions::default();
+        let decisions = if options.no_baseline { &no_decisions } else { &baseline };
+
+        let findings = |results: &[AnalysisResult]| -> usize {
+            results.iter().map(|r| Finding::of(r, &r.class_metrics.file_path).len()).sum()
+        };
+        let before_decisions = findings(&results);
+        let mut results: Vec<AnalysisResult> = results.into_iter().map(|r| decisions.apply(r)).collect();
+        // Afferent/efferent coupling on the same graph as `dei arch`
+        for result in &mut results {
+            result.coupling = coupling.class_coupling(&result.class_metrics.name);
+        }
+        let hidden = before_decisions - findings(&results);
+        if hidden > 0 && !raw_output {
+            println!(
+                "📋 {} finding(s) accepted or suppressed by triage are hidden (--no-baseline shows them)",
+                hidden
+            );
+            println!();
+        }
+        let mut external = vendored;
+        if let Some(filter) = &options.filter {
+            results.retain(|r| filter.matches(r));
+            external.retain(|r| filter.matches(r));
+        }
+        if let Some(min_severity) = min_severity {
+            results.retain(|r| r.fails_check(min_severity));
+            external.retain(|r| r.fails_check(min_severity));
+        }
+        if !only.is_empty() {
+            let god_files: HashSet<&str> = god_files.iter().map(|f| f.file_path.as_ref()).collect();
+            let keep = |r: &AnalysisResult| only.iter().any(|kind| kind.matches(r, &god_files));
+            results.retain(keep);
+            external.retain(keep);
+        }
+        // Import hygiene is neither a god class, method nor file, so --only drops it
+        let import_hygiene: Vec<ImportHygieneResult> = import_hygiene
+            .into_iter()
+            .filter(|_| only.is_empty())
+            .filter(|result| {
+                min_severity.is_none_or(|min| result.violations.iter().any(|v| v.severity >= min))
+            })
+            .collect();
+        // --filter expressions describe classes, so files drop out under one as under other --only kinds
+        let god_files: Vec<GodFileResult> = god_files
+            .into_iter()
+            .filter(|_| options.filter.is_none())
+            .filter(|_| only.is_empty() || only.contains(&FindingKind::File))
+            .filter(|result| {
+                min_severity.is_none_or(|min| result.violations.iter().any(|v| v.severity >= min))
+            })
+            .collect();
+
+        if let Some(repo) = repo {
+            if let Some(base) = &options.changed_since {
+                compare_with_base(repo, base, &mut results)?;
+            }
+
+            if options.owners {
+                annotate_ownership(repo, &mut results, DEFAULT_MIN_SHARE);
+            }
+
+            if options.age_weighting {
+                let window = Duration::from_secs(options.growth_window_days * 24 * 60 * 60);
+                annotate_code_age(repo, &mut results, SystemTime::now(), window);
+                prioritize_by_growth(&mut results);
+            }
+        }
+
+        if let Some(sort) = options.sort {
+            results.sort_by(|a, b| sort.key(b).total_cmp(&sort.key(a)));
+            external.sort_by(|a, b| sort.key(b).total_cmp(&sort.key(a)));
+        }
+
+        Ok(Self {
+            results,
+            external,
+            god_files,
+            import_hygiene,
+            file_errors,
+            file_warnings,
+            files_analyzed: files.len(),
+            baseline,
+        })
+    }
+}
+
+/// Render the findings in every requested format, to stdout or a file each
+fn write_reports(
+    path: &Path,
+    options: &CheckOptions,
+    thresholds: &Thresholds,
+    targets: &[ReportTarget],
+    findings: &Findings,
+    repo: Option<&GitRepo>,
+    raw_output: bool,
+) -> Result<()> {
+    let Findings { results, external, god_files, import_hygiene, file_errors, file_warnings, .. } = findings;
+    let (verbose, summary_only) = (options.verbose, options.summary);
+
+    // Counts cover every class; --summary and --top trim the listed ones
+    let summary = ReportSummary::from_results(results);
+    let worst: Vec<AnalysisResult>;
+    let listed: &[AnalysisResult] = match (summary_only, options.top) {
+        (true, _) => &[],
+        (false, Some(n)) => {
+            let mut flagged: Vec<AnalysisResult> = results.iter().filter(|r| r.has_issues()).cloned().collect();
+            let rank = options.sort.unwrap_or(SortKey::Score);
+            flagged.sort_by(|a, b| rank.key(b).total_cmp(&rank.key(a)));
+            flagged.truncate(n);
+            worst = flagged;
+            &worst
+        }
+        (false, None) => results,
     };
 
-    let parser = MultiLanguageParser::new()?;
-    let traverser = ParallelTraverser::new(parser, builder.arena().clone());
-    traverser.traverse_and_analyze(root_id, &thresholds)?;
+    // Generate reports
+    let files = FileCounts {
+        analyzed: findings.files_analyzed,
+        skipped: file_errors.len(),
+        with_warnings: file_warnings.len(),
+    };
+    let metadata = ReportMetadata::new(path, thresholds, files);
+    let distribution = report::distribution(results, thresholds);
+    let groups = match options.group_by {
+        Some(group_by) => group_results(results, group_by, path),
+        None => Vec::new(),
+    };
+    let methods = match options.include_all_methods {
+        true => MethodEntry::all(results, thresholds),
+        false => Vec::new(),
+    };
+    let generator = ReportGenerator::new(thresholds.clone());
+    let attributions = match repo {
+        Some(repo) if options.blame && targets.iter().any(|t| t.format == ReportFormat::Text) => {
+            attribute_violations(repo, listed, DEFAULT_MIN_SHARE)
+        }
+        _ => Vec::new(),
+    };
 
-    if let Some(s) = spinner {
-        s.finish_and_clear();
-        println!("{}", "✓ Analysis complete".green());
+    for target in targets {
+        // Files get plain text, whatever the terminal supports
+        if target.path.is_some() {
+            colored::control::set_override(false);
+        }
+
+        let mut out = Vec::new();
+        match &target.format {
+            ReportFormat::Json => {
+                let report = JsonReport::new(&metadata, summary.clone(), listed)
+                    .with_god_files(god_files)
+                    .with_skipped_files(file_errors)
+                    .with_file_warnings(file_warnings)
+                    .with_groups(&groups)
+                    .with_methods(&methods);
+                serde_json::to_writer_pretty(&mut out, &report)?;
+                writeln!(out)?;
+            }
+            ReportFormat::Html => {
+                write!(out, "{}", TemplateReport::html()?.render(&generator, &metadata, &summary, listed, god_files, &distribution)?)?
+            }
+            ReportFormat::Template(template) => {
+                write!(out, "{}", TemplateReport::load(template)?.render(&generator, &metadata, &summary, listed, god_files, &distribution)?)?
+            }
+            ReportFormat::Text => {
+                match options.group_by {
+                    Some(group_by) => {
+                        generator.write_summary(&mut out, &summary)?;
+                        report::write_groups(&mut out, group_by.title(), &groups)?;
+                    }
+                    None => generator.write_text_report(&mut out, &summary, listed, verbose)?,
+                }
+                if verbose && !summary_only {
+                    report::write_distribution(&mut out, &distribution)?;
+                }
+                if !summary_only {
+                    generator.write_god_files(&mut out, god_files)?;
+                    generator.write_import_hygiene(&mut out, import_hygiene)?;
+                    generator.write_external_report(&mut out, external, verbose)?;
+                }
+                generator.write_skipped_files(&mut out, file_errors)?;
+                generator.write_file_warnings(&mut out, file_warnings)?;
+                if options.blame && !summary_only {
+                    generator.write_contributors(&mut out, &attributions, verbose)?;
+                }
+            }
+        }
+
+        match &target.path {
+            Some(path) => {
+                colored::control::unset_override();
+                std::fs::write(path, &out).with_context(|| format!("Failed to write {}", path.display()))?;
+                let note = format!("📝 {} report written to {}", target.format, path.display());
+                // Keep a report on stdout clean
+                if raw_output {
+                    eprintln!("{}", note);
+                } else {
+                    println!("{}", note);
+                }
+            }
+            None => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(&out)?;
+                stdout.flush()?;
+            }
+        }
     }
+    Ok(())
+}
 
-    if !is_json {
-        println!();
+/// 1 when more than `--max-issues` findings are at or above `--fail-on`, else 0; findings below
+/// `--fail-on` are reported but don't fail, and `--no-fail` never does
+fn exit_code(options: &CheckOptions, config: &DeiConfig, findings: &Findings) -> i32 {
+    let fail_on = options.fail_on.or(config.fail_on).unwrap_or(Severity::Warning);
+    let issues = failing_findings(&findings.results, fail_on)
+        + findings
+            .god_files
+            .iter()
+            .filter(|r| r.violations.iter().any(|v| v.severity >= fail_on))
+            .count()
+        + findings
+            .import_hygiene
+            .iter()
+            .flat_map(|r| r.violations.iter())
+            .filter(|v| v.severity >= fail_on)
+            .count();
+    let allowed = options.max_issues.or(config.max_issues).unwrap_or(0);
+    let failed = !options.no_fail && issues > allowed;
+    if failed { 1 } else { 0 }
+}
+
+/// Last line of a check, for CI scripts: `dei: 3 god classes, 7 god methods, 1 god files, 0 new`
+///
+/// New findings are god classes and god methods neither accepted into the baseline nor
+/// suppressed, whether or not `--no-baseline` shows the others.
+struct SummaryLine {
+    god_classes: usize,
+    god_methods: usize,
+    god_files: usize,
+    new: usize,
+}
+
+impl SummaryLine {
+    fn of(results: &[AnalysisResult], god_files: &[GodFileResult], baseline: &Decisions) -> Self {
+        let new = results
+            .iter()
+            .flat_map(|r| Finding::of(r, &baseline.relative(&r.class_metrics.file_path)))
+            .filter(|f| (f.is_god_class || f.id.method.is_some()) && baseline.is_new(f))
+            .count();
+        Self {
+            god_classes: results.iter().filter(|r| r.is_god_class).count(),
+            god_methods: results.iter().map(|r| r.god_methods.len()).sum(),
+            god_files: god_files.len(),
+            new,
+        }
     }
+}
+
+impl std::fmt::Display for SummaryLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "dei: {} god classes, {} god methods, {} god files, {} new",
+            self.god_classes, self.god_methods, self.god_files, self.new
+        )
+    }
+}
 
-    // Get results
-    let all_results = traverser.all_results();
+/// Class-level findings and god methods at least `fail_on`, each counted once
+fn failing_findings(results: &[AnalysisResult], fail_on: Severity) -> usize {
+    results
+        .iter()
+        .map(|r| {
+            // Results built without reasons still count as a warning-level god class
+            let unexplained = (r.is_god_class && r.god_class_violations.is_empty()).then_some(Severity::Warning);
+            let class = r
+                .god_class_violations
+                .iter()
+                .chain(r.violations.iter())
+                .map(|v| v.severity)
+                .chain(unexplained)
+                .max()
+                .is_some_and(|s| s >= fail_on);
+            let methods = r
+                .god_methods
+                .iter()
+                .filter(|m| m.violations.iter().any(|v| v.severity >= fail_on))
+                .count();
+            usize::from(class) + methods
+        })
+        .sum()
+}
+
+/// Metric `--sort` orders results by, largest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    Lines,
+    Complexity,
+    Methods,
+    /// Weighted violation score (highest first)
+    Score,
+}
 
-    // Generate report
-    let generator = ReportGenerator::new(thresholds);
-    
-    match format.as_str() {
-        "json" => {
-            let json = serde_json::to_string_pretty(&all_results)?;
-            println!("{}", json);
+impl SortKey {
+    fn key(self, result: &AnalysisResult) -> f64 {
+        let metrics = &result.class_metrics;
+        match self {
+            SortKey::Lines => metrics.lines.0 as f64,
+            SortKey::Complexity => metrics.complexity.0 as f64,
+            SortKey::Methods => metrics.method_count.0 as f64,
+            SortKey::Score => result.violation_score(),
         }
-        _ => {
-            generator.print_text_report(&all_results, verbose);
+    }
+}
+
+/// How `--group-by` splits the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// Directory of the declaring file
+    Dir,
+    Language,
+    /// Nearest directory with a build manifest, such as a Cargo.toml or package.json
+    Project,
+}
+
+impl GroupBy {
+    fn title(self) -> &'static str {
+        match self {
+            GroupBy::Dir => "directory",
+            GroupBy::Language => "language",
+            GroupBy::Project => "project",
         }
     }
+}
+
+/// Files marking the root of a project for `--group-by project`; `.csproj` files too
+const PROJECT_MANIFESTS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "pyproject.toml",
+    "setup.py",
+    "go.mod",
+    "Makefile.PL",
+    "DESCRIPTION",
+];
 
-    // Exit with appropriate code
-    let has_issues = all_results.iter().any(|r| r.has_issues());
-    std::process::exit(if has_issues { 1 } else { 0 });
+fn group_results(results: &[AnalysisResult], group_by: GroupBy, path: &Path) -> Vec<ReportGroup> {
+    let root = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    // Classes of a directory share their project
+    let mut projects: HashMap<PathBuf, String> = HashMap::new();
+    ReportGroup::collect(results, |result| {
+        let file = Path::new(result.class_metrics.file_path.as_ref());
+        let dir = file.parent().unwrap_or(Path::new(""));
+        match group_by {
+            GroupBy::Dir => dir_name(dir),
+            GroupBy::Language => Language::from_path(file).map_or_else(|| "Other".into(), |l| format!("{:?}", l)),
+            GroupBy::Project => projects
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| dir_name(project_root(dir, root)))
+                .clone(),
+        }
+    })
 }
 
+fn dir_name(dir: &Path) -> String {
+    if dir.as_os_str().is_empty() {
+        ".".into()
+    } else {
+        dir.display().to_string()
+    }
+}
+
+/// The closest directory from `dir` up to `root` holding a build manifest, else `root`
+fn project_root<'a>(dir: &'a Path, root: &'a Path) -> &'a Path {
+    let is_project = |dir: &Path| {
+        PROJECT_MANIFESTS.iter().any(|name| dir.join(name).is_file())
+            || std::fs::read_dir(dir)
+                .map(|entries| entries.flatten().any(|e| e.path().extension().is_some_and(|ext| ext == "csproj")))
+                .unwrap_or(false)
+    };
+    dir.ancestors()
+        .take_while(|ancestor| ancestor.starts_with(root))
+        .find(|ancestor| is_project(ancestor))
+        .unwrap_or(root)
+}
+
+/// Finding `--only` keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FindingKind {
+    #[value(name = "god-class")]
+    Class,
+    #[value(name = "god-method")]
+    Method,
+    /// Classes declared in a file over the god-file thresholds
+    #[value(name = "god-file")]
+    File,
+}
+
+impl FindingKind {
+    fn matches(self, result: &AnalysisResult, god_files: &HashSet<&str>) -> bool {
+        match self {
+            FindingKind::Class => result.is_god_class,
+            FindingKind::Method => !result.god_methods.is_empty(),
+            FindingKind::File => god_files.contains(result.class_metrics.file_path.as_ref()),
+        }
+    }
+}
+
+/// Kind of report written by `dei check`
+#[derive(Debug, Clone, PartialEq)]
+enum ReportFormat {
+    Text,
+    Json,
+    Html,
+    /// A user-supplied Tera template
+    Template(PathBuf),
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFormat::Text => write!(f, "text"),
+            ReportFormat::Json => write!(f, "JSON"),
+            ReportFormat::Html => write!(f, "HTML"),
+            ReportFormat::Template(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A report and where it goes; `None` is stdout
+#[derive(Debug, Clone, PartialEq)]
+struct ReportTarget {
+    format: ReportFormat,
+    path: Option<PathBuf>,
+}
+
+impl std::str::FromStr for ReportTarget {
+    type Err = anyhow::Error;
+
+    /// `json` or `json:reports/dei.json`
+    fn from_str(spec: &str) -> Result<Self> {
+        let (format, path) = match spec.split_once(':') {
+            Some((format, path)) if !path.is_empty() => (format, Some(PathBuf::from(path))),
+            _ => (spec, None),
+        };
+        let format = match format {
+            "text" => ReportFormat::Text,
+            "json" => ReportFormat::Json,
+            "html" => ReportFormat::Html,
+            other => bail!("Unknown format '{}' (expected text, json or html)", other),
+        };
+        Ok(Self { format, path })
+    }
+}
+
+/// Reports to write: `--format` values, `--template`, and the default format (from
+/// `dei.toml`, else text) when nothing else goes to stdout; `--output` redirects
+/// the report that would go to stdout
+fn report_targets(
+    formats: &[String],
+    config_format: Option<&str>,
+    template: Option<PathBuf>,
+    output: Option<PathBuf>,
+) -> Result<Vec<ReportTarget>> {
+    let mut targets: Vec<ReportTarget> = formats.iter().map(|f| f.parse()).collect::<Result<_>>()?;
+    if let Some(template) = template {
+        // The template takes the place of a plain `--format` going to stdout
+        targets.retain(|t| t.path.is_some());
+        targets.push(ReportTarget { format: ReportFormat::Template(template), path: None });
+    } else if targets.iter().all(|t| t.path.is_some()) {
+        let mut default: ReportTarget = config_format.unwrap_or("text").parse()?;
+        default.path = None;
+        targets.push(default);
+    }
+
+    let mut to_stdout = targets.iter_mut().filter(|t| t.path.is_none());
+    let primary = to_stdout.next();
+    if to_stdout.next().is_some() {
+        bail!("Only one report can go to stdout; give the others a path (--format json:report.json)");
+    }
+    if let (Some(primary), Some(output)) = (primary, output) {
+        primary.path = Some(output);
+    }
+    Ok(targets)
+}
+
+/// Counts files through the spinner as the traverser parses and analyzes them
+///
+/// The spinner draws on stderr, which keeps JSON and template output on stdout clean.
+struct SpinnerProgress {
+    spinner: ProgressBar,
+    total: AtomicUsize,
+    parsed: AtomicUsize,
+    analyzed: AtomicUsize,
+}
+
+impl SpinnerProgress {
+    fn new(spinner: ProgressBar) -> Self {
+        Self { spinner, total: AtomicUsize::new(0), parsed: AtomicUsize::new(0), analyzed: AtomicUsize::new(0) }
+    }
+}
+
+impl ProgressObserver for SpinnerProgress {
+    fn files_discovered(&self, count: usize) {
+        self.total.store(count, Ordering::Relaxed);
+    }
+
+    fn file_parsed(&self, _path: &Path) {
+        let parsed = self.parsed.fetch_add(1, Ordering::Relaxed) + 1;
+        let total = self.total.load(Ordering::Relaxed);
+        self.spinner.set_message(format!("Parsing files in parallel... {parsed}/{total}"));
+    }
+
+    fn file_analyzed(&self, _path: &Path, _results: &[AnalysisResult]) {
+        let analyzed = self.analyzed.fetch_add(1, Ordering::Relaxed) + 1;
+        let parsed = self.parsed.load(Ordering::Relaxed);
+        self.spinner.set_message(format!("Analyzing files in parallel... {analyzed}/{parsed}"));
+    }
+}
+
+fn print_slow_file(slow: &SlowFile) {
+    let language = slow.language.map_or("unknown".to_string(), |l| format!("{:?}", l));
+    let phase = match slow.phase {
+        Phase::Parse => "parse",
+        Phase::Analyze => "analyze",
+    };
+    eprintln!(
+        "{} {} ({}, {} bytes) took {:.2}s to {}{}",
+        "⏱️  Slow file:".yellow(),
+        slow.path.display(),
+        language,
+        slow.bytes,
+        slow.elapsed.as_secs_f64(),
+        phase,
+        if slow.skipped { " - skipped" } else { "" }
+    );
+}
+
+/// Attach a [`BaseComparison`] to every god class, parsing its file as of `base`
+fn compare_with_base(repo: &GitRepo, base: &str, results: &mut [AnalysisResult]) -> Result<()> {
+    let parser = MultiLanguageParser::new()?;
+    let scratch = tempfile::tempdir()?;
+    let mut base_files: HashMap<Arc<str>, Option<FileMetrics>> = HashMap::new();
+
+    for result in results.iter_mut().filter(|r| r.is_god_class) {
+        let file_path = result.class_metrics.file_path.clone();
+        if !base_files.contains_key(&file_path) {
+            let parsed = parse_at_base(repo, base, Path::new(file_path.as_ref()), &parser, scratch.path())?;
+            base_files.insert(file_path.clone(), parsed);
+        }
+
+        let current = &result.class_metrics;
+        // Rust splits a type over several items, so prefer the largest namesake
+        let base_class = base_files[&file_path]
+            .as_ref()
+            .and_then(|file| {
+                file.classes
+                    .iter()
+                    .filter(|c| c.name == current.name)
+                    .max_by_key(|c| c.complexity)
+            });
+        result.base_comparison = Some(BaseComparison::between(base_class, current));
+    }
+
+    Ok(())
+}
+
+/// Parse `path` as it was at `base`, `None` when it didn't exist or can't be parsed
+pub(crate) fn parse_at_base(
+    repo: &GitRepo,
+    base: &str,
+    path: &Path,
+    parser: &MultiLanguageParser,
+    scratch: &Path,
+) -> Result<Option<FileMetrics>> {
+    let Some(relative) = repo.relative(path) else {
+        return Ok(None);
+    };
+    let Some(contents) = repo.file_at(base, &relative)? else {
+        return Ok(None);
+    };
+
+    // Parsers read from disk; keep the file name so language detection still works
+    let copy = scratch.join(relative.file_name().unwrap_or_default());
+    std::fs::write(&copy, contents)?;
+    Ok(parser.parse_file(&copy).ok())
+}
+
+/// Parse a byte count with an optional K, M or G suffix, also spelled KB or KiB (all powers
+/// of 1024)
+pub(crate) fn parse_size(text: &str) -> std::result::Result<u64, String> {
+    let text = text.trim();
+    let (digits, unit) = text.split_at(text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len()));
+    let shift = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        _ => return Err(format!("unknown size unit '{}', expected K, M or G", unit)),
+    };
+    let count: u64 = digits.trim().parse().map_err(|_| format!("invalid size '{}'", text))?;
+    count.checked_mul(1 << shift).ok_or_else(|| format!("size '{}' is too large", text))
+}
@@ -0,0 +1,234 @@
+//! Compare command - god classes found and fixed, and per-class metric changes, between
+//! two directories or two git revisions
+
+use anyhow::{Context as _, Result};
+use colored::Colorize;
+use dei_core::{
+    config::DeiConfig,
+    diff::{diff_classes, Change, ClassDiff, MetricChange},
+    metrics::ClassMetrics,
+    models::AnalysisResult,
+};
+use dei_engine::AnalysisEngine;
+use dei_git::GitRepo;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::diff_file::{change_marker, metric};
+use super::pipeline;
+
+/// A class on either side, matched by file (relative to its side's root) and name
+#[derive(Debug, Serialize)]
+struct ComparedClass {
+    file: Arc<str>,
+    was_god_class: bool,
+    is_god_class: bool,
+    #[serde(flatten)]
+    diff: ClassDiff,
+}
+
+impl ComparedClass {
+    fn is_new_god_class(&self) -> bool {
+        self.is_god_class && !self.was_god_class
+    }
+
+    fn is_resolved_god_class(&self) -> bool {
+        self.was_god_class && !self.is_god_class
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Comparison {
+    before: String,
+    after: String,
+    god_classes: MetricChange,
+    new_god_classes: usize,
+    resolved_god_classes: usize,
+    classes: Vec<ComparedClass>,
+}
+
+/// `before` and `after` are each a path, or else a git ref checked out from the repository
+/// containing `path`; with refs, `path` also picks the directory analyzed inside the checkout
+pub async fn run(
+    before: String,
+    after: String,
+    path: PathBuf,
+    config: Option<PathBuf>,
+    format: String,
+    verbose: bool,
+) -> Result<()> {
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    // Both sides are judged by today's config and rules
+    let engine = pipeline::project_engine(config_path.as_deref(), &config, &path)?.build();
+    let scratch = tempfile::tempdir()?;
+
+    let before_root = resolve(&before, &path, &scratch.path().join("before"))?;
+    let after_root = resolve(&after, &path, &scratch.path().join("after"))?;
+    let comparison = compare(
+        before,
+        after,
+        &analyze(&before_root, &engine, &config)?,
+        &analyze(&after_root, &engine, &config)?,
+    );
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+    } else {
+        print_text(&comparison, verbose);
+    }
+
+    Ok(())
+}
+
+/// Directory to analyze for one side: the path itself, or a checkout of the ref
+fn resolve(side: &str, path: &Path, checkout: &Path) -> Result<PathBuf> {
+    let as_path = Path::new(side);
+    if as_path.exists() {
+        return Ok(as_path.to_path_buf());
+    }
+
+    let repo = GitRepo::discover(path)
+        .with_context(|| format!("'{}' is not a path, and {} is not in a git repository", side, path.display()))?;
+    repo.checkout_to(side, checkout)
+        .with_context(|| format!("'{}' is neither a path nor a git ref", side))?;
+    let inside = repo.relative(path).unwrap_or_default();
+    Ok(checkout.join(inside))
+}
+
+/// Results per file, keyed by the path relative to `root` with `/` separators
+fn analyze(
+    root: &Path,
+    engine: &AnalysisEngine,
+    config: &DeiConfig,
+) -> Result<BTreeMap<Arc<str>, Vec<AnalysisResult>>> {
+    let mut files: BTreeMap<Arc<str>, Vec<AnalysisResult>> = BTreeMap::new();
+    for result in engine.analyze(root)?.results {
+        let result = config.violations.apply(config.smells.apply(result));
+        let file = Path::new(result.class_metrics.file_path.as_ref());
+        let relative = match file.strip_prefix(root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => Path::new(file.file_name().unwrap_or_default()),
+        };
+        let key: Arc<str> = relative.to_string_lossy().replace('\\', "/").into();
+        files.entry(key).or_default().push(result);
+    }
+    Ok(files)
+}
+
+fn compare(
+    before_label: String,
+    after_label: String,
+    before: &BTreeMap<Arc<str>, Vec<AnalysisResult>>,
+    after: &BTreeMap<Arc<str>, Vec<AnalysisResult>>,
+) -> Comparison {
+    let files: BTreeSet<&Arc<str>> = before.keys().chain(after.keys()).collect();
+    let no_results = Vec::new();
+
+    let mut classes = Vec::new();
+    for file in files {
+        let before = before.get(file).unwrap_or(&no_results);
+        let after = after.get(file).unwrap_or(&no_results);
+        let metrics = |results: &[AnalysisResult]| -> Vec<ClassMetrics> {
+            results.iter().map(|r| r.class_metrics.clone()).collect()
+        };
+        // Rust splits a type over several items; any of them being flagged flags the type
+        let is_god = |results: &[AnalysisResult], name: &str| {
+            results.iter().any(|r| r.is_god_class && r.class_metrics.name.as_ref() == name)
+        };
+
+        for diff in diff_classes(&metrics(before), &metrics(after)) {
+            classes.push(ComparedClass {
+                file: file.clone(),
+                was_god_class: is_god(before, &diff.name),
+                is_god_class: is_god(after, &diff.name),
+                diff,
+            });
+        }
+    }
+
+    Comparison {
+        before: before_label,
+        after: after_label,
+        god_classes: MetricChange {
+            before: classes.iter().filter(|c| c.was_god_class).count(),
+            after: classes.iter().filter(|c| c.is_god_class).count(),
+        },
+        new_god_classes: classes.iter().filter(|c| c.is_new_god_class()).count(),
+        resolved_god_classes: classes.iter().filter(|c| c.is_resolved_god_class()).count(),
+        classes,
+    }
+}
+
+fn print_text(comparison: &Comparison, verbose: bool) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - COMPARE                                    ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+    println!("  {} {}", "Before:".bold(), comparison.before.bright_yellow());
+    println!("  {} {}", "After:".bold(), comparison.after.bright_yellow());
+    println!();
+    println!("  {} {}", "God Classes:".bold(), metric(&comparison.god_classes));
+    println!("  {} {}", "New God Classes:".bold(), comparison.new_god_classes.to_string().red());
+    println!("  {} {}", "Resolved God Classes:".bold(), comparison.resolved_god_classes.to_string().green());
+    println!();
+
+    let new: Vec<&ComparedClass> = comparison.classes.iter().filter(|c| c.is_new_god_class()).collect();
+    if !new.is_empty() {
+        println!("{}", "⚠️  NEW GOD CLASSES:".red().bold());
+        println!();
+        for class in new {
+            println!("  ❌ {}  {}", class.diff.name.bold(), class.file.dimmed());
+        }
+        println!();
+    }
+
+    let resolved: Vec<&ComparedClass> = comparison.classes.iter().filter(|c| c.is_resolved_god_class()).collect();
+    if !resolved.is_empty() {
+        println!("{}", "✅ RESOLVED GOD CLASSES:".green().bold());
+        println!();
+        for class in resolved {
+            println!("  ✓ {}  {}", class.diff.name.bold(), class.file.dimmed());
+        }
+        println!();
+    }
+
+    let changed: Vec<&ComparedClass> = comparison
+        .classes
+        .iter()
+        .filter(|c| verbose || c.diff.change != Change::Unchanged)
+        .collect();
+    if changed.is_empty() {
+        println!("{}", "✅ No metric changes between the two sides.".green());
+        println!();
+        return;
+    }
+
+    println!("{}", "METRIC CHANGES:".bold());
+    println!();
+    for class in changed {
+        let diff = &class.diff;
+        println!("  {} {}  {}", change_marker(diff.change), diff.name.bold(), class.file.dimmed());
+        println!("     Lines: {} | Methods: {} | Complexity: {} | Fields: {}",
+            metric(&diff.lines),
+            metric(&diff.methods),
+            metric(&diff.complexity),
+            metric(&diff.fields)
+        );
+
+        if verbose {
+            for method in &diff.method_diffs {
+                println!("       {} {}  lines {} | complexity {} | params {} | nesting {}",
+                    change_marker(method.change),
+                    method.name,
+                    metric(&method.lines),
+                    metric(&method.complexity),
+                    metric(&method.parameters),
+                    metric(&method.nesting_depth)
+                );
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,240 @@
+//! Daemon command - warm analyses over a local socket
+//!
+//! Listens on a Unix domain socket for JSON-RPC 2.0 requests, one per line, and answers each
+//! on a line of its own. Every analyzed path keeps its parsers, syntax trees, rules and last
+//! results between requests, so analyzing it again is incremental, as in `dei serve`. Config
+//! and rules are read the first time a path is analyzed.
+//!
+//! - `analyze {"path", "config"?}` - the `--format json` report of a file or directory
+//! - `analyze_file {"path", "text"?}` - the classes of one file, from `text` when given
+//! - `status` - version, uptime and the paths kept warm
+//! - `shutdown` - stop listening and remove the socket
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use dei_core::config::DeiConfig;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Notify;
+
+use super::pipeline::{self, decisions_root, load_detectors};
+use super::lsp::Analyzer;
+use super::serve::{analyze_tree, Analysis, Session};
+use crate::report::JsonReport;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The analysis itself failed, e.g. for a missing path or a broken `dei.toml`
+const ANALYSIS_FAILED: i64 = -32000;
+
+/// An analyzed path and the `config` it was analyzed with
+type ProjectKey = (PathBuf, Option<PathBuf>);
+
+struct Daemon {
+    started: Instant,
+    files: Analyzer,
+    /// Warm state per analyzed path and config
+    projects: Mutex<HashMap<ProjectKey, Arc<Project>>>,
+    stop: Notify,
+}
+
+struct Project {
+    path: PathBuf,
+    config: DeiConfig,
+    decisions_root: PathBuf,
+    session: Session,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    /// Absent for notifications, which get no answer
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct AnalyzeParams {
+    path: PathBuf,
+    /// Config file to use instead of the nearest dei.toml
+    #[serde(default)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct AnalyzeFileParams {
+    path: PathBuf,
+    /// Contents to analyze instead of the file on disk, such as an unsaved buffer
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// A JSON-RPC error
+struct Failure {
+    code: i64,
+    message: String,
+}
+
+impl Failure {
+    fn analysis(error: anyhow::Error) -> Self {
+        Self { code: ANALYSIS_FAILED, message: format!("{:#}", error) }
+    }
+}
+
+pub async fn run(socket: PathBuf) -> Result<()> {
+    // A socket file nobody answers on was left by a daemon that didn't shut down cleanly
+    if socket.exists() {
+        if UnixStream::connect(&socket).await.is_ok() {
+            anyhow::bail!("a daemon is already listening on {}", socket.display());
+        }
+        std::fs::remove_file(&socket)?;
+    }
+    let listener = UnixListener::bind(&socket).with_context(|| format!("can't listen on {}", socket.display()))?;
+    println!("🔌 Listening on {}", socket.display().to_string().bright_yellow());
+
+    let daemon = Arc::new(Daemon {
+        started: Instant::now(),
+        files: Analyzer::default(),
+        projects: Mutex::default(),
+        stop: Notify::new(),
+    });
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                tokio::spawn(daemon.clone().serve(stream));
+            }
+            _ = daemon.stop.notified() => break,
+        }
+    }
+
+    std::fs::remove_file(&socket)?;
+    Ok(())
+}
+
+impl Daemon {
+    /// Answer one connection's requests in order until it closes
+    async fn serve(self: Arc<Self>, stream: UnixStream) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(response) = self.clone().answer(&line).await else {
+                continue;
+            };
+            if writer.write_all(format!("{}\n", response).as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// The response to a request line; `None` for notifications
+    async fn answer(self: Arc<Self>, line: &str) -> Option<Value> {
+        let request = match serde_json::from_str::<Value>(line) {
+            Ok(value) => serde_json::from_value::<Request>(value).map_err(|e| (INVALID_REQUEST, e)),
+            Err(e) => Err((PARSE_ERROR, e)),
+        };
+        let request = match request {
+            Ok(request) => request,
+            Err((code, e)) => return Some(error(Value::Null, code, e.to_string())),
+        };
+
+        let outcome = self.call(&request.method, request.params).await;
+        let id = request.id?;
+        Some(match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(failure) => error(id, failure.code, failure.message),
+        })
+    }
+
+    async fn call(self: Arc<Self>, method: &str, params: Value) -> Result<Value, Failure> {
+        match method {
+            "analyze" => {
+                let params: AnalyzeParams = parse_params(params)?;
+                blocking(move || self.analyze(params)).await
+            }
+            "analyze_file" => {
+                let params: AnalyzeFileParams = parse_params(params)?;
+                blocking(move || self.analyze_file(params)).await
+            }
+            "status" => Ok(self.status()),
+            "shutdown" => {
+                self.stop.notify_one();
+                Ok(Value::Null)
+            }
+            _ => Err(Failure { code: METHOD_NOT_FOUND, message: format!("no method '{}'", method) }),
+        }
+    }
+
+    fn analyze(&self, params: AnalyzeParams) -> Result<Value> {
+        let project = self.project(params)?;
+        let Analysis { metadata, summary, results, god_files } =
+            analyze_tree(&project.path, &project.config, &project.decisions_root, &project.session, None)?;
+        Ok(serde_json::to_value(JsonReport::new(&metadata, summary, &results).with_god_files(&god_files))?)
+    }
+
+    fn analyze_file(&self, params: AnalyzeFileParams) -> Result<Value> {
+        let path = params.path.canonicalize().with_context(|| format!("{} not found", params.path.display()))?;
+        let text = match params.text {
+            Some(text) => text,
+            None => std::fs::read_to_string(&path)?,
+        };
+        let results = self.files.analyze(&path, &text)?;
+        Ok(json!({ "results": results }))
+    }
+
+    fn status(&self) -> Value {
+        let projects = self.projects.lock().unwrap_or_else(|e| e.into_inner());
+        let paths: Vec<_> = projects.values().map(|project| &project.path).collect();
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_seconds": self.started.elapsed().as_secs(),
+            "paths": paths,
+        })
+    }
+
+    /// The warm state for a path, set up on its first analysis
+    fn project(&self, params: AnalyzeParams) -> Result<Arc<Project>> {
+        let path = params.path.canonicalize().with_context(|| format!("{} not found", params.path.display()))?;
+        let mut projects = self.projects.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (path.clone(), params.config.clone());
+        if let Some(project) = projects.get(&key) {
+            return Ok(project.clone());
+        }
+
+        let (config_path, config) = pipeline::load_config(params.config, &path)?;
+        let decisions_root = decisions_root(config_path.as_deref(), &path);
+        let session = Session::new(load_detectors(&decisions_root)?.0);
+        let project = Arc::new(Project { path, config, decisions_root, session });
+        projects.insert(key, project.clone());
+        Ok(project)
+    }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, Failure> {
+    serde_json::from_value(params).map_err(|e| Failure { code: INVALID_PARAMS, message: e.to_string() })
+}
+
+/// Run an analysis off the async threads
+async fn blocking(analysis: impl FnOnce() -> Result<Value> + Send + 'static) -> Result<Value, Failure> {
+    tokio::task::spawn_blocking(analysis)
+        .await
+        .map_err(|e| Failure::analysis(e.into()))?
+        .map_err(Failure::analysis)
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
@@ -0,0 +1,98 @@
+//! Diff-file command - per-class and per-method metric changes of one file since a git ref
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_core::diff::{diff_classes, Change, ClassDiff, MetricChange};
+use dei_core::traits::Parser;
+use dei_git::GitRepo;
+use dei_languages::MultiLanguageParser;
+use std::path::{Path, PathBuf};
+
+use super::check::parse_at_base;
+
+pub async fn run(path: PathBuf, base: String, format: String, verbose: bool) -> Result<()> {
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let repo = GitRepo::discover(dir)?;
+    let parser = MultiLanguageParser::new()?;
+    let scratch = tempfile::tempdir()?;
+
+    let before = parse_at_base(&repo, &base, &path, &parser, scratch.path())?;
+    // A deleted file diffs as every class removed
+    let after = if path.exists() { Some(parser.parse_file(&path)?) } else { None };
+    if before.is_none() && after.is_none() {
+        anyhow::bail!("{} exists neither in the working tree nor at {}", path.display(), base);
+    }
+
+    let classes = |file: &Option<dei_core::metrics::FileMetrics>| {
+        file.as_ref().map(|f| f.classes.to_vec()).unwrap_or_default()
+    };
+    let diffs = diff_classes(&classes(&before), &classes(&after));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&diffs)?);
+    } else {
+        print_text(&path, &base, &diffs, verbose);
+    }
+
+    Ok(())
+}
+
+fn print_text(path: &Path, base: &str, diffs: &[ClassDiff], verbose: bool) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - FILE DIFF                                  ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+    println!("  {} {}", "File:".bold(), path.display().to_string().bright_yellow());
+    println!("  {} {} → working tree", "Base:".bold(), base.bright_yellow());
+    println!();
+
+    if diffs.iter().all(|d| d.change == Change::Unchanged) {
+        println!("{}", "✅ No metric changes since the base revision.".green());
+        println!();
+        if !verbose {
+            return;
+        }
+    }
+
+    for diff in diffs.iter().filter(|d| verbose || d.change != Change::Unchanged) {
+        println!("  {} {}", change_marker(diff.change), diff.name.bold());
+        println!("     Lines: {} | Methods: {} | Complexity: {} | Fields: {}",
+            metric(&diff.lines),
+            metric(&diff.methods),
+            metric(&diff.complexity),
+            metric(&diff.fields)
+        );
+
+        for method in diff.method_diffs.iter().filter(|m| verbose || m.change != Change::Unchanged) {
+            println!("       {} {}  lines {} | complexity {} | params {} | nesting {}",
+                change_marker(method.change),
+                method.name,
+                metric(&method.lines),
+                metric(&method.complexity),
+                metric(&method.parameters),
+                metric(&method.nesting_depth)
+            );
+        }
+        println!();
+    }
+}
+
+pub(super) fn change_marker(change: Change) -> colored::ColoredString {
+    match change {
+        Change::Added => "+".green().bold(),
+        Change::Removed => "-".red().bold(),
+        Change::Modified => "~".yellow().bold(),
+        Change::Unchanged => "=".dimmed(),
+    }
+}
+
+/// `before → after (±delta)`, with growth in red
+pub(super) fn metric(change: &MetricChange) -> String {
+    let delta = change.delta();
+    let delta = match delta {
+        0 => String::new(),
+        d if d > 0 => format!(" ({})", format!("+{}", d).red()),
+        d => format!(" ({})", d.to_string().green()),
+    };
+    format!("{} → {}{}", change.before, change.after, delta)
+}
@@ -0,0 +1,327 @@
+//! Explain command - a deep dive into one class: every method's metrics, the thresholds it
+//! violates and by how much, its cohesion components, and the classes it depends on and
+//! that depend on it
+
+use anyhow::{bail, Context as _, Result};
+use colored::Colorize;
+use dei_core::{
+    config::DeiConfig,
+    metrics::LineSpan,
+    models::{AnalysisResult, Violation},
+    traits::ClusterAnalyzer,
+};
+use dei_metrics::{CohesionAnalyzer, CouplingAnalyzer};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::pipeline;
+use super::simulate::ProposedCluster;
+
+/// A violated threshold and how far past it the metric is
+#[derive(Debug, Serialize)]
+struct ExplainedViolation {
+    #[serde(flatten)]
+    violation: Violation,
+    /// `actual - threshold`
+    overrun: usize,
+    /// Overrun as a percentage of the threshold
+    overrun_percent: f64,
+}
+
+impl From<&Violation> for ExplainedViolation {
+    fn from(violation: &Violation) -> Self {
+        let overrun = violation.actual.saturating_sub(violation.threshold);
+        Self {
+            violation: violation.clone(),
+            overrun,
+            overrun_percent: overrun as f64 * 100.0 / violation.threshold.max(1) as f64,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ExplainedMethod {
+    name: Arc<str>,
+    span: LineSpan,
+    lines: usize,
+    complexity: usize,
+    parameters: usize,
+    nesting_depth: usize,
+    chain_length: usize,
+    exit_points: usize,
+    violations: Vec<ExplainedViolation>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExplainedCohesion {
+    lcom4: usize,
+    /// Method names grouped by connected component
+    components: Vec<Vec<Arc<str>>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExplainedCoupling {
+    /// Classes depending on this one
+    fan_in: Vec<Arc<str>>,
+    /// Classes this one depends on
+    fan_out: Vec<Arc<str>>,
+    instability: f64,
+    third_party: Vec<Arc<str>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Explanation {
+    class: Arc<str>,
+    file: Arc<str>,
+    span: LineSpan,
+    is_god_class: bool,
+    lines: usize,
+    methods: usize,
+    complexity: usize,
+    fields: usize,
+    dependencies: usize,
+    /// Class-level thresholds exceeded, god-class ones first
+    violations: Vec<ExplainedViolation>,
+    method_details: Vec<ExplainedMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cohesion: Option<ExplainedCohesion>,
+    /// Extractions proposed by clustering; only computed for classes with findings
+    suggested_extractions: Vec<ProposedCluster>,
+    coupling: ExplainedCoupling,
+}
+
+/// `target` is `path/to/file::Class`, or just the file when it declares a single class;
+/// `path` is the project scanned for fan-in and fan-out
+pub async fn run(target: String, path: PathBuf, config: Option<PathBuf>, format: String) -> Result<()> {
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+
+    let (file, class_name) = match target.rsplit_once("::") {
+        Some((file, class)) => (PathBuf::from(file), Some(class)),
+        None => (PathBuf::from(&target), None),
+    };
+    let canonical = file
+        .canonicalize()
+        .with_context(|| format!("Cannot read {}", file.display()))?;
+    if !canonical.starts_with(path.canonicalize()?) {
+        bail!("{} is not under the project {} (pass --path)", file.display(), path.display());
+    }
+
+    let analysis = pipeline::project_engine(config_path.as_deref(), &config, &path)?
+        .keep_tree(true)
+        .analyze(&path)?;
+    let coupling = pipeline::coupling_analyzer(&path, &analysis)?;
+
+    let results: Vec<AnalysisResult> = analysis
+        .results
+        .into_iter()
+        .map(|result| config.violations.apply(config.smells.apply(result)))
+        .collect();
+
+    let in_file: Vec<&AnalysisResult> = results
+        .iter()
+        .filter(|r| Path::new(r.class_metrics.file_path.as_ref()).canonicalize().ok().as_ref() == Some(&canonical))
+        .collect();
+    if in_file.is_empty() {
+        bail!("No classes found in {} (unsupported language, or ignored?)", file.display());
+    }
+    let names: BTreeSet<&str> = in_file.iter().map(|r| r.class_metrics.name.as_ref()).collect();
+    let listed = names.iter().copied().collect::<Vec<_>>().join(", ");
+    let class_name = match class_name {
+        Some(name) if names.contains(name) => name,
+        Some(name) => bail!("No class '{}' in {} (found: {})", name, file.display(), listed),
+        None if names.len() == 1 => names.iter().next().copied().unwrap_or_default(),
+        None => bail!("{} declares several classes, pick one with {}::<Class> (found: {})", file.display(), target, listed),
+    };
+    // Rust splits a type over several items; the most complex one stands for the type
+    let Some(result) = in_file
+        .into_iter()
+        .filter(|r| r.class_metrics.name.as_ref() == class_name)
+        .max_by_key(|r| r.class_metrics.complexity)
+    else {
+        bail!("No class '{}' in {}", class_name, file.display());
+    };
+
+    let explanation = explain(result, &config, &coupling)?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&explanation)?);
+    } else {
+        print_text(&explanation);
+    }
+
+    Ok(())
+}
+
+fn explain(result: &AnalysisResult, config: &DeiConfig, coupling: &CouplingAnalyzer) -> Result<Explanation> {
+    let class = &result.class_metrics;
+    let thresholds = config.thresholds.for_entry_point(class.entry_point);
+
+    let method_details = class
+        .methods
+        .iter()
+        .map(|method| ExplainedMethod {
+            name: method.name.clone(),
+            span: method.span,
+            lines: method.lines.0,
+            complexity: method.complexity.0,
+            parameters: method.parameters.0,
+            nesting_depth: method.nesting_depth.0,
+            chain_length: method.chain_length.0,
+            exit_points: method.exit_points,
+            violations: result
+                .god_methods
+                .iter()
+                .filter(|g| g.method_name == method.name && g.metrics.span == method.span)
+                .flat_map(|g| g.violations.iter().map(ExplainedViolation::from))
+                .collect(),
+        })
+        .collect();
+
+    let suggested_extractions = if result.has_issues() {
+        pipeline::clusterer(config)
+            .analyze(class, &thresholds)?
+            .iter()
+            .map(ProposedCluster::from)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(Explanation {
+        class: class.name.clone(),
+        file: class.file_path.clone(),
+        span: class.span,
+        is_god_class: result.is_god_class,
+        lines: class.lines.0,
+        methods: class.method_count.0,
+        complexity: class.complexity.0,
+        fields: class.field_count,
+        dependencies: class.dependencies.len(),
+        violations: result
+            .god_class_violations
+            .iter()
+            .chain(result.violations.iter())
+            .map(ExplainedViolation::from)
+            .collect(),
+        method_details,
+        cohesion: CohesionAnalyzer::lcom4(class).map(|c| ExplainedCohesion {
+            lcom4: c.lcom4,
+            components: c.components,
+        }),
+        suggested_extractions,
+        coupling: ExplainedCoupling {
+            fan_in: coupling.dependents(&class.name),
+            fan_out: coupling.dependencies(&class.name),
+            instability: coupling.get_coupling(&class.name).map_or(0.0, |c| c.instability),
+            third_party: coupling.third_party_dependencies(&class.name),
+        },
+    })
+}
+
+fn print_text(explanation: &Explanation) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - EXPLAIN                                    ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+
+    let verdict = if explanation.is_god_class {
+        "god class".red().bold()
+    } else if explanation.violations.is_empty() && explanation.method_details.iter().all(|m| m.violations.is_empty()) {
+        "healthy".green()
+    } else {
+        "has findings".yellow()
+    };
+    println!("  {} {} ({})", "Class:".bold(), explanation.class.bright_yellow(), verdict);
+    println!("  {} {}:{}-{}", "File:".bold(), explanation.file, explanation.span.start, explanation.span.end);
+    println!("  Lines: {} | Methods: {} | Complexity: {} | Fields: {} | Dependencies: {}",
+        explanation.lines,
+        explanation.methods,
+        explanation.complexity,
+        explanation.fields,
+        explanation.dependencies
+    );
+    println!();
+
+    if !explanation.violations.is_empty() {
+        println!("{}", "VIOLATIONS:".bold());
+        for violation in &explanation.violations {
+            print_explained("  ", violation);
+        }
+        println!();
+    }
+
+    println!("{}", "METHODS:".bold());
+    println!(
+        "  {}",
+        format!(
+            "{:<32}  {:>5}  {:>10}  {:>6}  {:>7}  {:>5}  {:>5}",
+            "NAME", "LINES", "COMPLEXITY", "PARAMS", "NESTING", "CHAIN", "EXITS"
+        )
+        .dimmed()
+    );
+    for method in &explanation.method_details {
+        let row = format!(
+            "{:<32}  {:>5}  {:>10}  {:>6}  {:>7}  {:>5}  {:>5}",
+            method.name, method.lines, method.complexity, method.parameters, method.nesting_depth, method.chain_length, method.exit_points
+        );
+        if method.violations.is_empty() {
+            println!("  {}", row);
+        } else {
+            println!("  {}", row.red());
+            for violation in &method.violations {
+                print_explained("      ", violation);
+            }
+        }
+    }
+    println!();
+
+    if let Some(cohesion) = &explanation.cohesion {
+        println!("{} LCOM4 {}", "COHESION:".bold(), cohesion.lcom4);
+        for (i, component) in cohesion.components.iter().enumerate() {
+            println!("  {}. {}", i + 1, component.join(", "));
+        }
+        println!();
+    }
+
+    if !explanation.suggested_extractions.is_empty() {
+        println!("{}", "SUGGESTED EXTRACTIONS:".bold());
+        for cluster in &explanation.suggested_extractions {
+            println!(
+                "  → {} (cohesion {:.2}): {}",
+                cluster.name.bright_cyan(),
+                cluster.cohesion,
+                cluster.methods.join(", ")
+            );
+        }
+        println!();
+    }
+
+    let coupling = &explanation.coupling;
+    println!("{} instability {:.2}", "COUPLING:".bold(), coupling.instability);
+    print_names("Fan-in", &coupling.fan_in);
+    print_names("Fan-out", &coupling.fan_out);
+    if !coupling.third_party.is_empty() {
+        print_names("Third-party", &coupling.third_party);
+    }
+    println!();
+}
+
+/// `• Kind: actual exceeds threshold by overrun (+percent%)`
+fn print_explained(indent: &str, explained: &ExplainedViolation) {
+    let violation = &explained.violation;
+    println!("{}• {:?}: {} exceeds {} by {} (+{:.0}%) [{}]",
+        indent,
+        violation.kind,
+        violation.actual.to_string().red(),
+        violation.threshold.to_string().green(),
+        explained.overrun,
+        explained.overrun_percent,
+        violation.severity.as_str()
+    );
+}
+
+fn print_names(label: &str, names: &[Arc<str>]) {
+    let listed = if names.is_empty() { "none".dimmed().to_string() } else { names.join(", ") };
+    println!("  {:<12} {:>3}  {}", format!("{}:", label), names.len(), listed);
+}
@@ -0,0 +1,68 @@
+//! Hotspots command - rank files by git churn × complexity
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_git::{rank_hotspots, GitRepo, Hotspot};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use super::pipeline;
+
+pub async fn run(path: PathBuf, since_days: Option<u64>, top: usize, format: String) -> Result<()> {
+    let is_json = format == "json";
+
+    let repo = GitRepo::discover(&path)?;
+    let since = since_days.map(|days| SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60));
+    let churn = repo.churn(since)?;
+
+    let (config_path, config) = pipeline::load_config(None, &path)?;
+    let analysis = pipeline::project_engine(config_path.as_deref(), &config, &path)?.analyze(&path)?;
+
+    let mut hotspots = rank_hotspots(&repo, analysis.files.iter().map(|file| file.as_ref()), &churn);
+    hotspots.truncate(top);
+
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(&hotspots)?);
+    } else {
+        print_text(&hotspots, since_days);
+    }
+
+    Ok(())
+}
+
+fn print_text(hotspots: &[Hotspot], since_days: Option<u64>) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - CHURN × COMPLEXITY HOTSPOTS                ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+
+    let window = match since_days {
+        Some(days) => format!("last {} days", days),
+        None => "full history".to_string(),
+    };
+    println!("  {} {}", "History:".bold(), window);
+    println!();
+
+    if hotspots.is_empty() {
+        println!("{}", "✅ No hotspots: nothing complex has changed in this window.".green());
+        println!();
+        return;
+    }
+
+    for (rank, hotspot) in hotspots.iter().enumerate() {
+        println!(
+            "  {:>2}. {} {}",
+            rank + 1,
+            "🔥".red(),
+            hotspot.path.display().to_string().bold()
+        );
+        println!(
+            "      Score: {} | Commits: {} | Complexity: {} | Lines changed: {}",
+            hotspot.score.to_string().red(),
+            hotspot.churn.commits.to_string().yellow(),
+            hotspot.complexity.to_string().yellow(),
+            hotspot.churn.lines_changed()
+        );
+    }
+    println!();
+}
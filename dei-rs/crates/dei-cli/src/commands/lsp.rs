@@ -0,0 +1,303 @@
+//! Lsp command - a language server showing findings while editing
+//!
+//! Speaks LSP over stdin and stdout. Each document is analyzed when it is opened or saved,
+//! from the editor's text, with the nearest `dei.toml`, its custom rules, baseline and
+//! suppressions, as `dei check` would. Findings are published as diagnostics on the class or
+//! method they are about. Every class gets a code lens with its metrics.
+//!
+//! Saving a `dei.toml` or a rule script reloads the rules.
+
+use dei_ast::analyze_metrics_with;
+use dei_core::{
+    baseline::Decisions,
+    config::DeiConfig,
+    detector::DetectorRegistry,
+    metrics::{ClassMetrics, LineSpan},
+    models::{AnalysisResult, PathMetadata, Severity, Violation},
+    Error,
+};
+use dei_languages::MultiLanguageParser;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tower_lsp::jsonrpc;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+use super::pipeline::{decisions_root, load_detectors};
+
+/// Name diagnostics are reported under
+const SOURCE: &str = "dei";
+
+pub async fn run(config: Option<PathBuf>) -> anyhow::Result<()> {
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        analyzer: Arc::new(Analyzer { config, ..Analyzer::default() }),
+        documents: Mutex::default(),
+    });
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket).serve(service).await;
+    Ok(())
+}
+
+struct Backend {
+    client: Client,
+    analyzer: Arc<Analyzer>,
+    /// Open documents: their latest text, and the classes found at the last analysis
+    documents: Mutex<HashMap<Url, Document>>,
+}
+
+#[derive(Default)]
+struct Document {
+    text: Arc<str>,
+    classes: Vec<AnalysisResult>,
+}
+
+/// Analysis of single documents, shared with `dei daemon`
+#[derive(Default)]
+pub(super) struct Analyzer {
+    /// `--config`, used for every document instead of the nearest `dei.toml`
+    config: Option<PathBuf>,
+    parser: MultiLanguageParser,
+    /// Built-in detectors and custom rules per decisions root, compiled once
+    detectors: Mutex<HashMap<PathBuf, DetectorRegistry>>,
+}
+
+impl Analyzer {
+    /// Every class in `text`, with config, baseline and suppressions applied
+    pub(super) fn analyze(&self, path: &Path, text: &str) -> dei_core::Result<Vec<AnalysisResult>> {
+        let (config_path, config) = match &self.config {
+            Some(file) => (Some(file.clone()), DeiConfig::load(file)?),
+            None => match DeiConfig::discover(path)? {
+                Some((file, config)) => (Some(file), config),
+                None => (None, DeiConfig::default()),
+            },
+        };
+        let root = decisions_root(config_path.as_deref(), path);
+        let detectors = self.detectors(&root)?;
+        let decisions = Decisions::load(&root)?;
+
+        let (metrics, _) = self.parser.parse_source(path, text, None)?;
+        let metadata = PathMetadata::from_rules(&config.metadata, &path.to_string_lossy());
+        let analysis = analyze_metrics_with(metrics, &config.thresholds, &detectors, &metadata);
+        Ok(analysis
+            .results
+            .into_iter()
+            .map(|result| decisions.apply(config.violations.apply(config.smells.apply(result))))
+            .collect())
+    }
+
+    fn detectors(&self, root: &Path) -> dei_core::Result<DetectorRegistry> {
+        let mut detectors = self.detectors.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(registry) = detectors.get(root) {
+            return Ok(registry.clone());
+        }
+        let registry = load_detectors(root).map_err(|e| Error::Analysis(format!("{:#}", e)))?.0;
+        detectors.insert(root.to_path_buf(), registry.clone());
+        Ok(registry)
+    }
+
+    fn forget_rules(&self) {
+        self.detectors.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl Backend {
+    fn documents(&self) -> std::sync::MutexGuard<'_, HashMap<Url, Document>> {
+        self.documents.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Analyze the document's latest text and publish what was found
+    async fn check(&self, uri: Url) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        let Some(text) = self.documents().get(&uri).map(|d| d.text.clone()) else {
+            return;
+        };
+
+        let analyzer = self.analyzer.clone();
+        let analyzed = {
+            let text = text.clone();
+            tokio::task::spawn_blocking(move || analyzer.analyze(&path, &text)).await
+        };
+        let classes = match analyzed {
+            Ok(Ok(classes)) => classes,
+            // Files in languages dei doesn't parse just have nothing to show
+            Ok(Err(Error::UnsupportedLanguage(_))) => Vec::new(),
+            Ok(Err(e)) => {
+                self.client.log_message(MessageType::ERROR, format!("dei: {}", e)).await;
+                Vec::new()
+            }
+            Err(e) => {
+                self.client.log_message(MessageType::ERROR, format!("dei: analysis panicked: {}", e)).await;
+                Vec::new()
+            }
+        };
+
+        let diagnostics = classes.iter().flat_map(|result| diagnostics(result, &text)).collect();
+        if let Some(document) = self.documents().get_mut(&uri) {
+            document.classes = classes;
+        }
+        self.client.publish_diagnostics(uri, diagnostics, None).await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> jsonrpc::Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::FULL),
+                    save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                    ..TextDocumentSyncOptions::default()
+                })),
+                code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
+                ..ServerCapabilities::default()
+            },
+            server_info: Some(ServerInfo {
+                name: SOURCE.into(),
+                version: Some(env!("CARGO_PKG_VERSION").into()),
+            }),
+        })
+    }
+
+    async fn shutdown(&self) -> jsonrpc::Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let document = Document { text: params.text_document.text.into(), classes: Vec::new() };
+        self.documents().insert(uri.clone(), document);
+        self.check(uri).await;
+    }
+
+    /// Keep the text current; it is only analyzed again on save
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let Some(change) = params.content_changes.into_iter().next_back() else {
+            return;
+        };
+        if let Some(document) = self.documents().get_mut(&params.text_document.uri) {
+            document.text = change.text.into();
+        }
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let path = uri.to_file_path().unwrap_or_default();
+        let is_rules = path.file_name().is_some_and(|name| name == dei_core::config::CONFIG_FILE)
+            || path.extension().is_some_and(|ext| ext == "rhai");
+        if is_rules {
+            self.analyzer.forget_rules();
+        }
+        self.check(uri).await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents().remove(&uri);
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> jsonrpc::Result<Option<Vec<CodeLens>>> {
+        let documents = self.documents();
+        let Some(document) = documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let lenses = document
+            .classes
+            .iter()
+            .filter(|result| result.class_metrics.span.is_known())
+            .map(|result| CodeLens {
+                range: line_range(result.class_metrics.span, &document.text),
+                command: Some(Command {
+                    title: lens_title(result),
+                    command: String::new(),
+                    arguments: None,
+                }),
+                data: None,
+            })
+            .collect();
+        Ok(Some(lenses))
+    }
+}
+
+/// Diagnostics for a class: one for the god class, one per other class-level violation and
+/// one per god method
+fn diagnostics(result: &AnalysisResult, text: &str) -> Vec<Diagnostic> {
+    let class = &result.class_metrics;
+    let mut diagnostics = Vec::new();
+
+    if result.is_god_class && !result.god_class_violations.is_empty() {
+        let message = format!("God class '{}': {}", class.name, describe(&result.god_class_violations));
+        diagnostics.push(diagnostic(class.span, text, message, &result.god_class_violations));
+    }
+
+    for violation in result.violations.iter() {
+        let message = match (&violation.rule, &violation.message) {
+            (Some(rule), Some(message)) => format!("{}: {}", rule, message),
+            _ => format!("Class '{}': {}", class.name, describe(std::slice::from_ref(violation))),
+        };
+        diagnostics.push(diagnostic(class.span, text, message, std::slice::from_ref(violation)));
+    }
+
+    for method in result.god_methods.iter() {
+        let message = format!("God method '{}': {}", method.method_name, describe(&method.violations));
+        diagnostics.push(diagnostic(method.metrics.span, text, message, &method.violations));
+    }
+
+    diagnostics
+}
+
+/// A diagnostic on the first line of `span`, as severe as its worst violation
+fn diagnostic(span: LineSpan, text: &str, message: String, violations: &[Violation]) -> Diagnostic {
+    let severity = match violations.iter().map(|v| v.severity).max().unwrap_or_default() {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+    };
+    let rule = violations.iter().find_map(|v| v.rule_id);
+    Diagnostic {
+        range: line_range(span, text),
+        severity: Some(severity),
+        code: rule.map(|rule| NumberOrString::String(rule.id().into())),
+        code_description: rule
+            .and_then(|rule| Url::parse(&rule.help_url()).ok())
+            .map(|href| CodeDescription { href }),
+        source: Some(SOURCE.into()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+/// `Lines 450 exceeds 300, MethodCount 25 exceeds 20`
+fn describe(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("{:?} {} exceeds {}", v.kind, v.actual, v.threshold))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The first line of `span`, where a class or method is declared, without its indentation
+fn line_range(span: LineSpan, text: &str) -> Range {
+    let line = span.start.saturating_sub(1);
+    let source = text.lines().nth(line).unwrap_or_default();
+    let start = source.chars().take_while(|c| c.is_whitespace()).map(char::len_utf16).sum::<usize>();
+    let end = source.chars().map(char::len_utf16).sum::<usize>();
+    Range::new(Position::new(line as u32, start as u32), Position::new(line as u32, end as u32))
+}
+
+fn lens_title(result: &AnalysisResult) -> String {
+    let ClassMetrics { lines, method_count, complexity, field_count, .. } = &result.class_metrics;
+    let mut title = format!(
+        "{} lines · {} methods · complexity {} · {} fields",
+        lines.0, method_count.0, complexity.0, field_count
+    );
+    if let Some(lcom4) = result.lcom4 {
+        title.push_str(&format!(" · LCOM4 {}", lcom4));
+    }
+    title
+}
@@ -0,0 +1,147 @@
+//! Metrics command - every raw metric, one row per class and per method, as CSV or TSV
+
+use anyhow::{bail, Result};
+use dei_core::{
+    metrics::{ClassMetrics, MethodMetrics},
+    models::{AnalysisResult, Language},
+};
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::pipeline;
+
+/// Columns in output order; class rows leave method columns empty and vice versa
+const COLUMNS: &[&str] = &[
+    "kind",
+    "language",
+    "file",
+    "class",
+    "method",
+    "start_line",
+    "end_line",
+    "lines",
+    "complexity",
+    "methods",
+    "fields",
+    "properties",
+    "dependencies",
+    "lcom4",
+    "god_class",
+    "god_method",
+    "entry_point",
+    "parameters",
+    "nesting_depth",
+    "chain_length",
+    "exit_points",
+    "magic_literals",
+    "public",
+    "static",
+    "async",
+];
+
+pub async fn run(path: PathBuf, config: Option<PathBuf>, format: String) -> Result<()> {
+    let separator = match format.as_str() {
+        "csv" => ',',
+        "tsv" => '\t',
+        other => bail!("Unknown metrics format '{}' (expected csv or tsv)", other),
+    };
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    let results = pipeline::project_engine(config_path.as_deref(), &config, &path)?
+        .analyze(&path)?
+        .results;
+
+    match write_rows(&mut std::io::stdout().lock(), separator, &results) {
+        // Piped into `head` and the like
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        other => other.map_err(Into::into),
+    }
+}
+
+fn write_rows(out: &mut impl Write, separator: char, results: &[AnalysisResult]) -> std::io::Result<()> {
+    write_row(out, separator, COLUMNS.iter().map(|c| c.to_string()))?;
+    for result in results {
+        write_row(out, separator, class_row(result))?;
+        for method in result.class_metrics.methods.iter() {
+            write_row(out, separator, method_row(result, method))?;
+        }
+    }
+    out.flush()
+}
+
+fn class_row(result: &AnalysisResult) -> Vec<String> {
+    let class = &result.class_metrics;
+    let mut row = location("class", class);
+    row.extend([
+        String::new(),
+        class.span.start.to_string(),
+        class.span.end.to_string(),
+        class.lines.0.to_string(),
+        class.complexity.0.to_string(),
+        class.method_count.0.to_string(),
+        class.field_count.to_string(),
+        class.property_count.to_string(),
+        class.dependencies.len().to_string(),
+        result.lcom4.map(|l| l.to_string()).unwrap_or_default(),
+        result.is_god_class.to_string(),
+        String::new(),
+        class.entry_point.map(|e| format!("{:?}", e)).unwrap_or_default(),
+    ]);
+    row.extend(std::iter::repeat_n(String::new(), 8));
+    row
+}
+
+fn method_row(result: &AnalysisResult, method: &MethodMetrics) -> Vec<String> {
+    let class = &result.class_metrics;
+    let is_god_method = result.god_methods.iter().any(|g| g.method_name == method.name);
+    let mut row = location("method", class);
+    row.extend([
+        method.name.to_string(),
+        method.span.start.to_string(),
+        method.span.end.to_string(),
+        method.lines.0.to_string(),
+        method.complexity.0.to_string(),
+    ]);
+    row.extend(std::iter::repeat_n(String::new(), 6));
+    row.extend([
+        is_god_method.to_string(),
+        String::new(),
+        method.parameters.0.to_string(),
+        method.nesting_depth.0.to_string(),
+        method.chain_length.0.to_string(),
+        method.exit_points.to_string(),
+        method.magic_literals.to_string(),
+        method.is_public.to_string(),
+        method.is_static.to_string(),
+        method.is_async.to_string(),
+    ]);
+    row
+}
+
+/// `kind`, `language`, `file` and `class`
+fn location(kind: &str, class: &ClassMetrics) -> Vec<String> {
+    vec![
+        kind.to_string(),
+        Language::from_path(class.file_path.as_ref())
+            .map(|l| format!("{:?}", l).to_lowercase())
+            .unwrap_or_default(),
+        class.file_path.to_string(),
+        class.name.to_string(),
+    ]
+}
+
+fn write_row(out: &mut impl Write, separator: char, row: impl IntoIterator<Item = String>) -> std::io::Result<()> {
+    let fields: Vec<String> = row.into_iter().map(|f| escape(&f, separator)).collect();
+    writeln!(out, "{}", fields.join(&separator.to_string()))
+}
+
+/// RFC 4180 quoting for CSV; TSV has no quoting, so tabs and newlines become spaces
+fn escape(field: &str, separator: char) -> String {
+    if separator == '\t' {
+        return field.replace(['\t', '\n', '\r'], " ");
+    }
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -1,3 +1,20 @@
 pub mod check;
 pub mod arch;
+pub mod api;
+pub mod hotspots;
+pub mod diff_file;
+pub mod compare;
+pub mod explain;
+pub mod trend;
+pub mod serve;
+pub mod lsp;
+#[cfg(unix)]
+pub mod daemon;
+pub mod simulate;
+pub mod plan;
+pub mod refactor;
+pub mod triage;
+pub mod metrics;
+pub(crate) mod pipeline;
 
+pub mod selftest;
@@ -0,0 +1,90 @@
+//! The analysis every command runs
+//!
+//! Commands analyze through one [`AnalysisEngine`](dei_engine::AnalysisEngine) set up the
+//! same way: thresholds, ignores and `[[metadata]]` path rules from `dei.toml`, the built-in
+//! detectors plus the custom rules next to it, minified and vendored files left out and files
+//! over [`MAX_FILE_SIZE`](dei_engine::project::MAX_FILE_SIZE) skipped. `dei check` loosens
+//! or tightens these with its flags; the other commands take them as they are. The setup
+//! lives in [`dei_engine::project`], shared with the C API.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use dei_clustering::ClusteringAnalyzer;
+use dei_core::{config::DeiConfig, metrics::ClassMetrics};
+use dei_engine::{Analysis, AnalysisEngineBuilder, NodeId, SavedState, SharedArena};
+use dei_metrics::{CouplingAnalyzer, PackageManifests};
+use std::path::Path;
+use std::sync::Arc;
+
+pub(crate) use dei_engine::project::{decisions_root, engine, load_config, load_detectors};
+
+/// An engine set up from `config`, running the built-in detectors and the custom rules that
+/// belong to the config loaded from `config_path` for `path`
+pub(crate) fn project_engine(config_path: Option<&Path>, config: &DeiConfig, path: &Path) -> Result<AnalysisEngineBuilder> {
+    let (detectors, _) = load_detectors(&decisions_root(config_path, path))?;
+    Ok(engine(config, detectors))
+}
+
+/// The coupling graph of the classes `analysis` found under `path`, the same for every command
+///
+/// References resolve through the symbols of the tree `analysis` walked, or by class names
+/// alone for a lone file, which has no tree; the package manifests next to `path` tell
+/// third-party imports apart.
+pub(crate) fn coupling_analyzer(path: &Path, analysis: &Analysis) -> Result<CouplingAnalyzer> {
+    let classes: Vec<ClassMetrics> = analysis.results.iter().map(|r| r.class_metrics.clone()).collect();
+    let manifest_root = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    let analyzer = CouplingAnalyzer::new().with_manifests(PackageManifests::discover(manifest_root)?);
+    let mut analyzer = match analysis.tree {
+        Some(_) => analyzer.with_resolver(symbol_resolver(analysis)),
+        None => analyzer.with_class_resolver(&classes),
+    };
+    analyzer.build_graph(&classes);
+    Ok(analyzer)
+}
+
+/// Resolves a referenced type to the class declaring it, by the symbols of the tree `analysis`
+/// walked
+fn symbol_resolver(analysis: &Analysis) -> impl Fn(&str) -> Option<Arc<str>> + Send + Sync + 'static {
+    let arena = analysis.tree.as_ref().map(|tree| tree.arena.clone());
+    move |name| arena.as_ref()?.symbols().resolve_owner(name)
+}
+
+/// The clusterer for suggested extractions, on the embeddings of the `[clustering] model`
+/// when one is configured and this build has the `embeddings` feature
+pub(crate) fn clusterer(config: &DeiConfig) -> ClusteringAnalyzer {
+    let analyzer = ClusteringAnalyzer::new();
+    match &config.clustering.model {
+        #[cfg(feature = "embeddings")]
+        Some(model) => analyzer.with_embedder(Arc::new(dei_clustering::SentenceEmbedder::new(model))),
+        #[cfg(not(feature = "embeddings"))]
+        Some(_) => {
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| {
+                eprintln!(
+                    "{}",
+                    "⚠️  [clustering] model is ignored: this dei was built without the `embeddings` feature".yellow()
+                )
+            });
+            analyzer
+        }
+        None => analyzer,
+    }
+}
+
+/// The tree saved by `dei check --save-state` for `path`, warning about files changed since
+pub(crate) fn load_saved_state(file: &Path, path: &Path) -> Result<(SharedArena, NodeId)> {
+    let state = SavedState::load(file)?;
+    let expected = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if state.path != expected {
+        bail!("{} was saved for {}, not {}", file.display(), state.path.display(), path.display());
+    }
+    let stale = state.stale_files().len();
+    if stale > 0 {
+        eprintln!(
+            "{}",
+            format!("⚠️  {} file(s) changed since {} was saved; their saved metrics are used", stale, file.display())
+                .yellow()
+        );
+    }
+    Ok(state.restore())
+}
@@ -0,0 +1,274 @@
+//! Plan command - an ordered refactoring backlog built from violation scores, clustering
+//! and coupling
+//!
+//! Every flagged class yields steps: extracting each suggested cluster (or, when clustering
+//! has nothing to offer, each method group sharing no state with the rest per LCOM4) and
+//! splitting each god method. A step's benefit is the share of its class's violation score
+//! it addresses; its effort grows with the lines moved and the classes depending on the
+//! class. Steps are ranked by benefit per unit of effort, quick wins first.
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_clustering::ClusteringAnalyzer;
+use dei_core::{
+    baseline::Decisions,
+    config::DeiConfig,
+    models::AnalysisResult,
+    traits::ClusterAnalyzer,
+};
+use dei_metrics::{CohesionAnalyzer, CouplingAnalyzer};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::pipeline::{self, decisions_root, load_detectors};
+
+/// Lines moved that count as one effort point
+const LINES_PER_POINT: f64 = 50.0;
+
+/// Cohesion assumed for method groups found by LCOM4 rather than clustering
+const COMPONENT_COHESION: f64 = 0.75;
+
+/// Methods to extract: suggested class name, if any, method names and cohesion
+type Extraction = (Option<Arc<str>>, Vec<Arc<str>>, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum StepKind {
+    /// Move a group of methods into a class of their own
+    ExtractClass,
+    /// Break a god method into smaller ones
+    SplitMethod,
+    /// A god class with no extraction to suggest
+    Decompose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Effort {
+    Small,
+    Medium,
+    Large,
+}
+
+impl Effort {
+    fn from_points(points: f64) -> Self {
+        if points <= 2.0 {
+            Effort::Small
+        } else if points <= 5.0 {
+            Effort::Medium
+        } else {
+            Effort::Large
+        }
+    }
+
+    fn label(self) -> colored::ColoredString {
+        match self {
+            Effort::Small => "small".green(),
+            Effort::Medium => "medium".yellow(),
+            Effort::Large => "large".red(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PlanStep {
+    rank: usize,
+    kind: StepKind,
+    class: Arc<str>,
+    file: Arc<str>,
+    /// Suggested name of the extracted class, or the method to split
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<Arc<str>>,
+    methods: Vec<Arc<str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cohesion: Option<f64>,
+    /// Lines moved or rewritten
+    lines: usize,
+    /// Classes depending on the class, each a call site to revisit
+    dependents: usize,
+    benefit: f64,
+    effort_points: f64,
+    effort: Effort,
+    /// `benefit / effort_points`, the ranking key
+    priority: f64,
+    description: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Plan {
+    flagged_classes: usize,
+    steps: Vec<PlanStep>,
+}
+
+pub async fn run(path: PathBuf, config: Option<PathBuf>, top: Option<usize>, format: String) -> Result<()> {
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    let root = decisions_root(config_path.as_deref(), &path);
+    let decisions = Decisions::load(&root)?;
+
+    let analysis = pipeline::engine(&config, load_detectors(&root)?.0)
+        .keep_tree(true)
+        .analyze(&path)?;
+    let coupling = pipeline::coupling_analyzer(&path, &analysis)?;
+
+    let results: Vec<AnalysisResult> = analysis
+        .results
+        .into_iter()
+        .map(|result| decisions.apply(config.violations.apply(config.smells.apply(result))))
+        .collect();
+
+    let flagged: Vec<&AnalysisResult> = results.iter().filter(|r| r.has_issues()).collect();
+    let mut steps = Vec::new();
+    let clusterer = pipeline::clusterer(&config);
+    for result in &flagged {
+        steps.extend(class_steps(result, &config, &clusterer, &coupling)?);
+    }
+    steps.sort_by(|a, b| {
+        b.priority
+            .total_cmp(&a.priority)
+            .then_with(|| (&a.file, &a.class, &a.target).cmp(&(&b.file, &b.class, &b.target)))
+    });
+    if let Some(top) = top {
+        steps.truncate(top);
+    }
+    for (i, step) in steps.iter_mut().enumerate() {
+        step.rank = i + 1;
+    }
+
+    let plan = Plan {
+        flagged_classes: flagged.len(),
+        steps,
+    };
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        print_text(&path, &plan);
+    }
+
+    Ok(())
+}
+
+/// Steps for one flagged class, unranked
+fn class_steps(
+    result: &AnalysisResult,
+    config: &DeiConfig,
+    clusterer: &ClusteringAnalyzer,
+    coupling: &CouplingAnalyzer,
+) -> Result<Vec<PlanStep>> {
+    let class = &result.class_metrics;
+    let thresholds = config.thresholds.for_entry_point(class.entry_point);
+    let dependents = coupling.dependents(&class.name).len();
+    let method_lines = |names: &[Arc<str>]| -> usize {
+        class.methods.iter().filter(|m| names.contains(&m.name)).map(|m| m.lines.0).sum()
+    };
+
+    // God-method scores are accounted for by their own steps
+    let class_score = result.violation_score() - result.god_methods.iter().map(|m| m.violation_score).sum::<f64>();
+    let total_methods = class.methods.len().max(1) as f64;
+    let step = |kind, target: Option<Arc<str>>, methods: Vec<Arc<str>>, cohesion: Option<f64>, lines, dependents, benefit: f64| {
+        let effort_points = 1.0 + lines as f64 / LINES_PER_POINT + dependents as f64;
+        PlanStep {
+            rank: 0,
+            kind,
+            class: class.name.clone(),
+            file: class.file_path.clone(),
+            description: describe(kind, &class.name, target.as_deref(), &methods, cohesion),
+            target,
+            methods,
+            cohesion,
+            lines,
+            dependents,
+            benefit,
+            effort_points,
+            effort: Effort::from_points(effort_points),
+            priority: benefit / effort_points,
+        }
+    };
+
+    let mut steps = Vec::new();
+    if class_score > 0.0 {
+        // An extraction holding every method moves the class rather than splitting it
+        let clusters: Vec<_> = clusterer
+            .analyze(class, &thresholds)?
+            .into_iter()
+            .filter(|c| c.methods.len() < class.methods.len())
+            .collect();
+        let groups: Vec<Extraction> = if clusters.is_empty() {
+            // Every component but the largest, which stays behind as the class itself
+            let mut components = CohesionAnalyzer::lcom4(class).map(|c| c.components).unwrap_or_default();
+            components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+            components.into_iter().skip(1).map(|c| (None, c, COMPONENT_COHESION)).collect()
+        } else {
+            clusters
+                .iter()
+                .map(|c| (Some(c.suggested_name.clone()), c.methods.to_vec(), c.cohesion_score))
+                .collect()
+        };
+
+        for (target, methods, cohesion) in groups {
+            let share = methods.len() as f64 / total_methods;
+            let benefit = class_score * share * (0.5 + cohesion / 2.0);
+            let lines = method_lines(&methods);
+            steps.push(step(StepKind::ExtractClass, target, methods, Some(cohesion), lines, dependents, benefit));
+        }
+        if steps.is_empty() && result.is_god_class {
+            steps.push(step(StepKind::Decompose, None, Vec::new(), None, class.lines.0, dependents, class_score));
+        }
+    }
+
+    for god_method in result.god_methods.iter() {
+        steps.push(step(
+            StepKind::SplitMethod,
+            Some(god_method.method_name.clone()),
+            vec![god_method.method_name.clone()],
+            None,
+            god_method.metrics.lines.0,
+            0,
+            god_method.violation_score,
+        ));
+    }
+
+    Ok(steps)
+}
+
+/// One-line summary, e.g. `Extract NotificationService from MegaUserManager: 6 methods, cohesion 0.82`
+fn describe(kind: StepKind, class: &str, target: Option<&str>, methods: &[Arc<str>], cohesion: Option<f64>) -> String {
+    match kind {
+        StepKind::ExtractClass => {
+            let what = target.map_or_else(|| methods.join(", "), str::to_string);
+            let cohesion = cohesion.map(|c| format!(", cohesion {:.2}", c)).unwrap_or_default();
+            format!("Extract {} from {}: {} methods{}", what, class, methods.len(), cohesion)
+        }
+        StepKind::SplitMethod => format!("Split {}::{} into smaller methods", class, target.unwrap_or_default()),
+        StepKind::Decompose => format!("Break up {}: no cohesive method groups found", class),
+    }
+}
+
+fn print_text(path: &Path, plan: &Plan) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - REFACTORING PLAN                           ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+    println!("  {} {}", "Path:".bold(), path.display().to_string().bright_yellow());
+    println!("  {} {}", "Flagged Classes:".bold(), plan.flagged_classes);
+    println!();
+
+    if plan.steps.is_empty() {
+        println!("{}", "✅ Nothing to refactor.".green());
+        println!();
+        return;
+    }
+
+    for step in &plan.steps {
+        println!("  {:>2}. [{}] {}", step.rank, step.effort.label(), step.description.bold());
+        println!(
+            "      {}",
+            format!(
+                "{} · {} lines · {} dependents · priority {:.2}",
+                step.file, step.lines, step.dependents, step.priority
+            )
+            .dimmed()
+        );
+    }
+    println!();
+}
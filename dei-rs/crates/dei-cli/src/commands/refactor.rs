@@ -0,0 +1,192 @@
+//! Refactor command - scaffold the class a cluster of methods would be extracted into
+//!
+//! Rust and C# only. The skeleton (the cluster's method signatures, the fields they use
+//! and the source file's imports) is written as a patch creating a file next to the
+//! original; nothing in the working tree is touched.
+
+use anyhow::{bail, Context as _, Result};
+use colored::Colorize;
+use dei_clustering::ClusteringAnalyzer;
+use dei_core::{
+    config::DeiConfig,
+    metrics::ClassMetrics,
+    models::{Language, ResponsibilityCluster},
+    traits::{ClusterAnalyzer, Parser},
+};
+use dei_git::GitRepo;
+use dei_languages::{scaffold, MultiLanguageParser};
+use dei_metrics::CohesionAnalyzer;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::pipeline;
+
+/// How to pick the extracted methods
+pub struct Selection {
+    /// Suggested cluster to scaffold
+    pub cluster: Option<String>,
+    /// Methods to extract instead of a suggested cluster
+    pub methods: Vec<String>,
+    /// Name of the new class, overriding the suggested one
+    pub name: Option<String>,
+}
+
+pub async fn run(
+    target: String,
+    selection: Selection,
+    config: Option<PathBuf>,
+    output: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<()> {
+    let (file, class_name) = match target.rsplit_once("::") {
+        Some((file, class)) => (PathBuf::from(file), Some(class)),
+        None => (PathBuf::from(&target), None),
+    };
+    if !Language::from_path(&file).is_some_and(scaffold::supports) {
+        bail!("Scaffolding supports Rust and C# files, not {}", file.display());
+    }
+    let dir = file.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let config = match config {
+        Some(path) => DeiConfig::load(&path)?,
+        None => DeiConfig::discover(dir)?.map(|(_, config)| config).unwrap_or_default(),
+    };
+
+    let parsed = MultiLanguageParser::new()?
+        .parse_file(&file)
+        .with_context(|| format!("Cannot parse {}", file.display()))?;
+    let class = find_class(&parsed.classes, class_name, &file)?;
+    let cluster = select(class, &config, &selection)?;
+    let name = selection.name.as_deref().unwrap_or(&cluster.suggested_name);
+
+    // Fields the extracted methods touch, first use first
+    let mut fields: Vec<Arc<str>> = Vec::new();
+    for method in class.methods.iter().filter(|m| cluster.methods.contains(&m.name)) {
+        for field in method.accessed_fields.iter() {
+            if !fields.contains(field) {
+                fields.push(field.clone());
+            }
+        }
+    }
+
+    let extraction = scaffold::Extraction {
+        class: &class.name,
+        name,
+        methods: &cluster.methods,
+        fields: &fields,
+    };
+    let skeleton = scaffold::scaffold(&file, &extraction)?;
+    let new_file = scaffold::scaffold_path(&file, name).context("No file name for the extracted class")?;
+    if new_file.exists() {
+        bail!("{} already exists; pick another name with --name", new_file.display());
+    }
+
+    // Repository-relative when possible, so the patch applies from the repository root
+    let patch_path = GitRepo::discover(dir)
+        .ok()
+        .and_then(|repo| repo.relative(&new_file))
+        .unwrap_or_else(|| new_file.strip_prefix(".").unwrap_or(&new_file).to_path_buf());
+    let patch = new_file_patch(&patch_path.to_string_lossy().replace('\\', "/"), &skeleton);
+
+    if dry_run {
+        print!("{}", patch);
+        return Ok(());
+    }
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.patch", name)));
+    std::fs::write(&output, &patch).with_context(|| format!("Cannot write {}", output.display()))?;
+    println!(
+        "📝 Scaffolded {} from {} ({} methods, {} fields): {}",
+        name.bright_cyan(),
+        class.name.bold(),
+        cluster.methods.len(),
+        fields.len(),
+        output.display().to_string().bright_yellow()
+    );
+    println!("{}", format!("   Apply with: git apply {}", output.display()).dimmed());
+    Ok(())
+}
+
+/// The named class, or the file's only one; Rust namesakes resolve to the most complex item
+fn find_class<'a>(classes: &'a [ClassMetrics], name: Option<&str>, file: &Path) -> Result<&'a ClassMetrics> {
+    let mut names: Vec<&str> = classes.iter().map(|c| c.name.as_ref()).collect();
+    names.sort();
+    names.dedup();
+    let name = match name {
+        Some(name) => name,
+        None if names.len() == 1 => names[0],
+        None if names.is_empty() => bail!("No classes found in {}", file.display()),
+        None => bail!("{} declares several classes, pick one with {}::<Class> (found: {})",
+            file.display(), file.display(), names.join(", ")),
+    };
+    classes
+        .iter()
+        .filter(|c| c.name.as_ref() == name)
+        .max_by_key(|c| c.complexity)
+        .with_context(|| format!("No class '{}' in {} (found: {})", name, file.display(), names.join(", ")))
+}
+
+/// The cluster to scaffold: hand-picked methods, or one of the suggested extractions
+fn select(class: &ClassMetrics, config: &DeiConfig, selection: &Selection) -> Result<ResponsibilityCluster> {
+    let analyzer = pipeline::clusterer(config);
+    if !selection.methods.is_empty() {
+        let unknown: Vec<&str> = selection
+            .methods
+            .iter()
+            .map(String::as_str)
+            .filter(|m| !class.methods.iter().any(|method| method.name.as_ref() == *m))
+            .collect();
+        if !unknown.is_empty() {
+            bail!("{} has no method {}", class.name, unknown.join(", "));
+        }
+        let methods: Vec<Arc<str>> = selection.methods.iter().map(|m| Arc::from(m.as_str())).collect();
+        return Ok(analyzer.cluster_for(class, &methods));
+    }
+
+    let candidates = suggestions(class, config, &analyzer)?;
+    let listed = || candidates.iter().map(|c| c.suggested_name.as_ref()).collect::<Vec<_>>().join(", ");
+    match &selection.cluster {
+        Some(name) => candidates
+            .iter()
+            .find(|c| c.suggested_name.as_ref() == name)
+            .cloned()
+            .with_context(|| format!("No suggested cluster '{}' for {} (suggested: {})", name, class.name, listed())),
+        None if candidates.len() == 1 => Ok(candidates[0].clone()),
+        None if candidates.is_empty() => {
+            bail!("No extraction suggested for {}; name the methods with --methods", class.name)
+        }
+        None => bail!("Several extractions suggested for {}, pick one with --cluster (suggested: {})", class.name, listed()),
+    }
+}
+
+/// Suggested clusters, or else every LCOM4 component but the largest, which stays behind
+fn suggestions(class: &ClassMetrics, config: &DeiConfig, analyzer: &ClusteringAnalyzer) -> Result<Vec<ResponsibilityCluster>> {
+    let thresholds = config.thresholds.for_entry_point(class.entry_point);
+    // A cluster holding every method moves the class rather than splitting it
+    let clusters: Vec<_> = analyzer
+        .analyze(class, &thresholds)?
+        .into_iter()
+        .filter(|c| c.methods.len() < class.methods.len())
+        .collect();
+    if !clusters.is_empty() {
+        return Ok(clusters);
+    }
+
+    let mut components = CohesionAnalyzer::lcom4(class).map(|c| c.components).unwrap_or_default();
+    components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    Ok(components.iter().skip(1).map(|c| analyzer.cluster_for(class, c)).collect())
+}
+
+/// Unified diff creating `path` with `content`, as `git apply` expects
+fn new_file_patch(path: &str, content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut patch = format!(
+        "diff --git a/{path} b/{path}\nnew file mode 100644\n--- /dev/null\n+++ b/{path}\n@@ -0,0 +1,{} @@\n",
+        lines.len()
+    );
+    for line in lines {
+        patch.push('+');
+        patch.push_str(line);
+        patch.push('\n');
+    }
+    patch
+}
@@ -0,0 +1,145 @@
+//! Selftest command - run the bundled fixture suite through every language parser
+//!
+//! Each fixture holds the same design in a different language: an `Inventory`
+//! god class with a `reconcile` god method next to a small, healthy `Point`.
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_ast::analyze_file;
+use dei_core::{
+    models::{AnalysisResult, Language},
+    thresholds::{Complexity, MethodCount, Thresholds},
+};
+use dei_languages::MultiLanguageParser;
+use serde::Serialize;
+
+const GOD_CLASS: &str = "Inventory";
+const GOD_METHOD: &str = "reconcile";
+const HEALTHY_CLASS: &str = "Point";
+
+/// (language, file name, source); the file name drives language detection
+const FIXTURES: &[(Language, &str, &str)] = &[
+    (Language::Rust, "inventory.rs", include_str!("../../selftest/inventory.rs")),
+    (Language::CSharp, "Inventory.cs", include_str!("../../selftest/Inventory.cs")),
+    (Language::Python, "inventory.py", include_str!("../../selftest/inventory.py")),
+    (Language::JavaScript, "inventory.js", include_str!("../../selftest/inventory.js")),
+    (Language::TypeScript, "inventory.ts", include_str!("../../selftest/inventory.ts")),
+    (Language::Java, "Inventory.java", include_str!("../../selftest/Inventory.java")),
+    (Language::Perl, "inventory.pl", include_str!("../../selftest/inventory.pl")),
+    (Language::R, "inventory.R", include_str!("../../selftest/inventory.R")),
+];
+
+/// Expected findings for one language
+#[derive(Debug, Serialize)]
+struct LanguageCheck {
+    language: Language,
+    god_class: bool,
+    god_method: bool,
+    healthy_class: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl LanguageCheck {
+    fn passed(&self) -> bool {
+        self.god_class && self.god_method && self.healthy_class
+    }
+
+    fn missed(&self) -> Vec<String> {
+        if let Some(error) = &self.error {
+            return vec![error.clone()];
+        }
+
+        let mut missed = Vec::new();
+        if !self.god_class {
+            missed.push(format!("god class {} not detected", GOD_CLASS));
+        }
+        if !self.god_method {
+            missed.push(format!("god method {} not detected", GOD_METHOD));
+        }
+        if !self.healthy_class {
+            missed.push(format!("{} missing or flagged", HEALTHY_CLASS));
+        }
+        missed
+    }
+}
+
+pub async fn run(format: String) -> Result<()> {
+    // Strict enough for the small fixtures to trip, loose enough for the healthy class
+    let thresholds = Thresholds {
+        max_methods: MethodCount(5),
+        max_method_complexity: Complexity(5),
+        ..Thresholds::default()
+    };
+    thresholds.validate().map_err(|e| anyhow::anyhow!(e))?;
+
+    let parser = MultiLanguageParser::new()?;
+    let scratch = tempfile::tempdir()?;
+
+    let mut checks = Vec::new();
+    for &(language, file_name, source) in FIXTURES {
+        let path = scratch.path().join(file_name);
+        std::fs::write(&path, source)?;
+
+        let check = match analyze_file(&parser, &path, &thresholds) {
+            Ok(analysis) => check_results(language, &analysis.results),
+            Err(e) => LanguageCheck {
+                language,
+                god_class: false,
+                god_method: false,
+                healthy_class: false,
+                error: Some(e.to_string()),
+            },
+        };
+        checks.push(check);
+    }
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        print_text(&checks);
+    }
+
+    let all_passed = checks.iter().all(LanguageCheck::passed);
+    crate::telemetry::exit(if all_passed { 0 } else { 1 });
+}
+
+fn check_results(language: Language, results: &[AnalysisResult]) -> LanguageCheck {
+    let find = |name: &str| results.iter().find(|r| r.class_metrics.name.as_ref() == name);
+    let god_class = find(GOD_CLASS);
+
+    LanguageCheck {
+        language,
+        god_class: god_class.is_some_and(|r| r.is_god_class),
+        god_method: god_class
+            .is_some_and(|r| r.god_methods.iter().any(|m| m.method_name.as_ref() == GOD_METHOD)),
+        healthy_class: find(HEALTHY_CLASS).is_some_and(|r| !r.has_issues()),
+        error: None,
+    }
+}
+
+fn print_text(checks: &[LanguageCheck]) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - LANGUAGE SELFTEST                          ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+
+    for check in checks {
+        let language = format!("{:?}", check.language);
+        if check.passed() {
+            println!("  {} {}", "✅".green(), language.bold());
+        } else {
+            println!("  {} {}: {}", "❌".red(), language.bold(), check.missed().join(", ").red());
+        }
+    }
+    println!();
+
+    let passed = checks.iter().filter(|c| c.passed()).count();
+    let summary = format!("{}/{} languages detect what they claim", passed, checks.len());
+    if passed == checks.len() {
+        println!("{}", summary.green().bold());
+    } else {
+        println!("{}", summary.red().bold());
+    }
+    println!();
+}
@@ -0,0 +1,261 @@
+//! Serve command - a local dashboard and JSON API over the latest analysis
+//!
+//! The path is re-analyzed every `--interval` seconds (or on `POST /api/refresh`);
+//! requests are always answered from the last finished analysis. Re-analysis is incremental:
+//! unchanged files aren't parsed again, changed ones are re-parsed from their last syntax
+//! tree, and only classes an edit touched are re-judged.
+//!
+//! - `GET /` - the `--format html` report
+//! - `GET /api/report` - the `--format json` document
+//! - `GET /api/summary` - headline counts and when they were taken
+//! - `GET /api/schema` - JSON Schema of `/api/report`
+
+use anyhow::Result;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use colored::Colorize;
+use dei_core::{
+    baseline::Decisions,
+    config::DeiConfig,
+    detector::DetectorRegistry,
+    models::{AnalysisResult, GodFileResult},
+};
+use dei_engine::{IncrementalParser, NodeId, SharedArena};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use super::pipeline::{self, decisions_root, load_detectors, load_saved_state};
+use crate::report::{
+    self, FileCounts, JsonReport, ReportGenerator, ReportMetadata, ReportSummary, TemplateReport, JSON_SCHEMA,
+};
+
+/// One finished analysis, shared by every request until the next one lands
+struct Snapshot {
+    /// Unix seconds
+    analyzed_at: u64,
+    metadata: ReportMetadata,
+    summary: ReportSummary,
+    results: Vec<AnalysisResult>,
+    god_files: Vec<GodFileResult>,
+    html: String,
+}
+
+struct Dashboard {
+    path: PathBuf,
+    config: DeiConfig,
+    decisions_root: PathBuf,
+    session: Session,
+    latest: RwLock<Arc<Snapshot>>,
+}
+
+/// What one analysis hands the next so it can be incremental
+#[derive(Clone, Default)]
+pub(super) struct Session {
+    parser: IncrementalParser,
+    /// Results of the last analysis, before config and baseline decisions
+    previous: Arc<Mutex<Arc<[AnalysisResult]>>>,
+    /// Built-in detectors and custom rules, loaded once so reused results stay comparable
+    detectors: DetectorRegistry,
+}
+
+impl Session {
+    pub(super) fn new(detectors: DetectorRegistry) -> Self {
+        Self { detectors, ..Self::default() }
+    }
+}
+
+/// Findings of one analysis of a tree
+pub(super) struct Analysis {
+    pub(super) metadata: ReportMetadata,
+    pub(super) summary: ReportSummary,
+    pub(super) results: Vec<AnalysisResult>,
+    pub(super) god_files: Vec<GodFileResult>,
+}
+
+#[derive(Serialize)]
+struct SummaryResponse<'a> {
+    analyzed_at: u64,
+    summary: &'a ReportSummary,
+}
+
+pub async fn run(
+    path: PathBuf,
+    config: Option<PathBuf>,
+    host: String,
+    port: u16,
+    interval: u64,
+    load_state: Option<PathBuf>,
+) -> Result<()> {
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    let decisions_root = decisions_root(config_path.as_deref(), &path);
+
+    // Analyze once up front so a bad path fails before the server starts
+    let session = Session::new(load_detectors(&decisions_root)?.0);
+    let first = {
+        let (path, config, root, session) = (path.clone(), config.clone(), decisions_root.clone(), session.clone());
+        tokio::task::spawn_blocking(move || {
+            let restored = load_state.map(|file| load_saved_state(&file, &path)).transpose()?;
+            analyze(&path, &config, &root, &session, restored)
+        })
+        .await??
+    };
+    let dashboard = Arc::new(Dashboard {
+        path,
+        config,
+        decisions_root,
+        session,
+        latest: RwLock::new(Arc::new(first)),
+    });
+
+    if interval > 0 {
+        let dashboard = dashboard.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = dashboard.refresh().await {
+                    eprintln!("{} {:#}", "Re-analysis failed, still serving the previous one:".red(), e);
+                }
+            }
+        });
+    }
+
+    let app = Router::new()
+        .route("/", get(dashboard_page))
+        .route("/api/report", get(report))
+        .route("/api/summary", get(summary))
+        .route("/api/schema", get(schema))
+        .route("/api/refresh", post(refresh))
+        .with_state(dashboard.clone());
+
+    let listener = tokio::net::TcpListener::bind((host.as_str(), port)).await?;
+    println!(
+        "🌐 Serving {} on http://{}",
+        dashboard.path.display().to_string().bright_yellow(),
+        listener.local_addr()?
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+impl Dashboard {
+    fn latest(&self) -> Arc<Snapshot> {
+        self.latest.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    async fn refresh(self: &Arc<Self>) -> Result<()> {
+        let this = self.clone();
+        let snapshot = tokio::task::spawn_blocking(move || {
+            analyze(&this.path, &this.config, &this.decisions_root, &this.session, None)
+        })
+        .await??;
+        *self.latest.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(snapshot);
+        Ok(())
+    }
+}
+
+fn analyze(
+    path: &Path,
+    config: &DeiConfig,
+    decisions_root: &Path,
+    session: &Session,
+    restored: Option<(SharedArena, NodeId)>,
+) -> Result<Snapshot> {
+    let Analysis { metadata, summary, results, god_files } = analyze_tree(path, config, decisions_root, session, restored)?;
+    let generator = ReportGenerator::new(config.thresholds.clone());
+    let distribution = report::distribution(&results, &config.thresholds);
+    let html = TemplateReport::html()?.render(&generator, &metadata, &summary, &results, &god_files, &distribution)?;
+
+    Ok(Snapshot {
+        analyzed_at: metadata.generated_at,
+        metadata,
+        summary,
+        results,
+        god_files,
+        html,
+    })
+}
+
+/// The same pipeline as `dei check`, baseline and suppressions included
+///
+/// A tree `restored` from a saved state is analyzed as saved instead of being built from `path`.
+pub(super) fn analyze_tree(
+    path: &Path,
+    config: &DeiConfig,
+    decisions_root: &Path,
+    session: &Session,
+    restored: Option<(SharedArena, NodeId)>,
+) -> Result<Analysis> {
+    let decisions = Decisions::load(decisions_root)?;
+    let mut previous = session.previous.lock().unwrap_or_else(|e| e.into_inner());
+    let engine = pipeline::engine(config, session.detectors.clone())
+        .incremental(session.parser.clone())
+        .previous_results(previous.clone());
+    let engine = match restored {
+        Some((arena, root)) => engine.restored(arena, root),
+        None => engine,
+    };
+    // The cache is only replaced once the analysis succeeds; a failed refresh keeps it whole
+    let analysis = engine.analyze(path)?;
+    *previous = analysis.results.iter().chain(&analysis.vendored).cloned().collect();
+
+    // In file order, as the engine returns them
+    let results: Vec<AnalysisResult> = analysis
+        .results
+        .into_iter()
+        .map(|result| decisions.apply(config.violations.apply(config.smells.apply(result))))
+        .collect();
+
+    let god_files: Vec<GodFileResult> = analysis
+        .god_files
+        .into_iter()
+        .filter_map(|result| config.violations.apply_files(result))
+        .collect();
+
+    let files = FileCounts {
+        analyzed: analysis.files.len(),
+        skipped: analysis.file_errors.len(),
+        with_warnings: analysis.file_warnings.len(),
+    };
+    let metadata = ReportMetadata::new(path, &config.thresholds, files);
+    let summary = ReportSummary::from_results(&results);
+    Ok(Analysis { metadata, summary, results, god_files })
+}
+
+async fn dashboard_page(State(dashboard): State<Arc<Dashboard>>) -> Html<String> {
+    Html(dashboard.latest().html.clone())
+}
+
+async fn report(State(dashboard): State<Arc<Dashboard>>) -> Response {
+    let latest = dashboard.latest();
+    Json(JsonReport::new(&latest.metadata, latest.summary.clone(), &latest.results).with_god_files(&latest.god_files)).into_response()
+}
+
+async fn summary(State(dashboard): State<Arc<Dashboard>>) -> Response {
+    let latest = dashboard.latest();
+    Json(SummaryResponse {
+        analyzed_at: latest.analyzed_at,
+        summary: &latest.summary,
+    })
+    .into_response()
+}
+
+async fn schema() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/schema+json")], JSON_SCHEMA)
+}
+
+/// Re-analyze now; answers once the new analysis is being served
+async fn refresh(State(dashboard): State<Arc<Dashboard>>) -> Response {
+    match dashboard.refresh().await {
+        Ok(()) => summary(State(dashboard)).await,
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+    }
+}
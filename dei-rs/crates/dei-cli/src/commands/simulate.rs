@@ -0,0 +1,170 @@
+//! Simulate command - before/after metrics of flagged classes as if their clusters were extracted
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_ast::{simulate_split, SimulatedSplit};
+use dei_clustering::ClusteringAnalyzer;
+use dei_core::{
+    models::{AnalysisResult, ResponsibilityCluster},
+    thresholds::Thresholds,
+    traits::ClusterAnalyzer,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::pipeline;
+use crate::report::print_violation;
+
+/// A flagged class and the extractions proposed for it
+#[derive(Debug, Serialize)]
+struct Proposal {
+    class: String,
+    file: String,
+    clusters: Vec<ProposedCluster>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    simulation: Option<SimulatedSplit>,
+    /// Whether the simulated split leaves no findings behind
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ProposedCluster {
+    pub(super) name: String,
+    pub(super) methods: Vec<String>,
+    pub(super) cohesion: f64,
+}
+
+impl From<&ResponsibilityCluster> for ProposedCluster {
+    fn from(cluster: &ResponsibilityCluster) -> Self {
+        Self {
+            name: cluster.suggested_name.to_string(),
+            methods: cluster.methods.iter().map(|m| m.to_string()).collect(),
+            cohesion: cluster.cohesion_score,
+        }
+    }
+}
+
+pub async fn run(
+    path: PathBuf,
+    apply_clusters: bool,
+    config: Option<PathBuf>,
+    format: String,
+) -> Result<()> {
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    let analysis = pipeline::project_engine(config_path.as_deref(), &config, &path)?.analyze(&path)?;
+    let clusterer = pipeline::clusterer(&config);
+    let thresholds = config.thresholds;
+
+    let mut proposals: Vec<Proposal> = analysis
+        .results
+        .iter()
+        .filter_map(|result| propose(result, &clusterer, &thresholds, apply_clusters).transpose())
+        .collect::<Result<_>>()?;
+    proposals.sort_by(|a, b| (&a.file, &a.class).cmp(&(&b.file, &b.class)));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&proposals)?);
+    } else {
+        print_text(&proposals, apply_clusters);
+    }
+
+    Ok(())
+}
+
+/// Clusters for a class with findings, simulated when asked; `None` for healthy classes
+fn propose(
+    result: &AnalysisResult,
+    clusterer: &ClusteringAnalyzer,
+    thresholds: &Thresholds,
+    apply: bool,
+) -> Result<Option<Proposal>> {
+    if !result.has_issues() {
+        return Ok(None);
+    }
+    let class = &result.class_metrics;
+
+    let clusters = clusterer.analyze(class, thresholds)?;
+    let simulation = (apply && !clusters.is_empty()).then(|| simulate_split(class, &clusters, thresholds));
+    Ok(Some(Proposal {
+        class: class.name.to_string(),
+        file: class.file_path.to_string(),
+        clusters: clusters.iter().map(ProposedCluster::from).collect(),
+        resolved: simulation.as_ref().map(SimulatedSplit::resolves),
+        simulation,
+    }))
+}
+
+fn print_text(proposals: &[Proposal], apply: bool) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - REFACTORING SIMULATION                     ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+
+    if proposals.is_empty() {
+        println!("{}", "✅ No flagged classes to split.".green());
+        println!();
+        return;
+    }
+
+    for proposal in proposals {
+        println!("  {} {}", proposal.class.bold(), format!("({})", proposal.file).dimmed());
+
+        if proposal.clusters.is_empty() {
+            println!("     {}", "No extraction suggested.".dimmed());
+            println!();
+            continue;
+        }
+
+        for cluster in &proposal.clusters {
+            println!(
+                "     → {} (cohesion {:.2}): {}",
+                cluster.name.bright_cyan(),
+                cluster.cohesion,
+                cluster.methods.join(", ")
+            );
+        }
+
+        if let Some(simulation) = &proposal.simulation {
+            println!();
+            print_metrics("Before", &simulation.before);
+            print_metrics("After", &simulation.remainder);
+            for extracted in &simulation.extracted {
+                print_metrics("Extracted", extracted);
+            }
+
+            if simulation.resolves() {
+                println!("     {}", "✅ Split resolves every finding.".green());
+            } else {
+                println!("     {}", "❌ Findings remain after the split.".red());
+            }
+        }
+        println!();
+    }
+
+    if !apply {
+        println!("{}", "Run with --apply-clusters to recompute metrics as if the extractions were performed.".dimmed());
+        println!();
+    }
+}
+
+fn print_metrics(label: &str, result: &AnalysisResult) {
+    let class = &result.class_metrics;
+    let marker = if result.has_issues() { "❌".red() } else { "✅".green() };
+    println!(
+        "     {} {:<10} {}  Lines: {} | Methods: {} | Complexity: {} | Fields: {}",
+        marker,
+        label,
+        class.name.bold(),
+        class.lines.0,
+        class.method_count.0,
+        class.complexity.0,
+        class.field_count
+    );
+    for violation in result.god_class_violations.iter().chain(result.violations.iter()) {
+        print_violation("        ", violation);
+    }
+    for god_method in result.god_methods.iter() {
+        println!("        • God method: {}", god_method.method_name.yellow());
+    }
+}
@@ -0,0 +1,281 @@
+//! Trend command - record summary metrics per run and show how code health evolves
+//!
+//! Runs are kept in an SQLite file, `dei-trend.db`, next to `dei.toml` (or in the
+//! analyzed directory), so it can be committed or cached between CI jobs.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use dei_core::config::DeiConfig;
+use dei_engine::AnalysisEngineBuilder;
+use dei_git::GitRepo;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::pipeline::{self, decisions_root, load_detectors, load_saved_state};
+use crate::report::ReportSummary;
+
+pub const TREND_FILE: &str = "dei-trend.db";
+
+/// Sparkline levels, lowest first
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A plotted metric: label, value, and whether growth is an improvement
+type Series = (&'static str, fn(&TrendPoint) -> f64, bool);
+
+/// Summary metrics of one recorded run
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    /// Unix seconds
+    pub recorded_at: i64,
+    /// `recorded_at` as UTC `YYYY-MM-DD HH:MM`
+    pub date: String,
+    /// HEAD when the run was recorded, outside a repository `None`
+    pub commit: Option<String>,
+    pub total_classes: usize,
+    pub god_classes: usize,
+    pub classes_with_god_methods: usize,
+    pub low_cohesion_classes: usize,
+    pub oversized_classes: usize,
+    pub healthy_classes: usize,
+    /// Coupling-based, 0 to 1, as reported by `dei arch`
+    pub maintainability_index: f64,
+}
+
+/// Recorded runs, oldest first
+pub struct TrendStore {
+    conn: Connection,
+}
+
+impl TrendStore {
+    /// Open the store, creating the file and table on first use
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY,
+                recorded_at INTEGER NOT NULL,
+                commit_id TEXT,
+                total_classes INTEGER NOT NULL,
+                god_classes INTEGER NOT NULL,
+                classes_with_god_methods INTEGER NOT NULL,
+                low_cohesion_classes INTEGER NOT NULL,
+                oversized_classes INTEGER NOT NULL,
+                healthy_classes INTEGER NOT NULL,
+                maintainability_index REAL NOT NULL
+            )",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn record(&self, point: &TrendPoint) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO runs (recorded_at, commit_id, total_classes, god_classes, classes_with_god_methods,
+                low_cohesion_classes, oversized_classes, healthy_classes, maintainability_index)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                point.recorded_at,
+                point.commit,
+                point.total_classes,
+                point.god_classes,
+                point.classes_with_god_methods,
+                point.low_cohesion_classes,
+                point.oversized_classes,
+                point.healthy_classes,
+                point.maintainability_index,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The latest `last` runs (all without a limit), oldest first
+    pub fn points(&self, last: Option<usize>) -> Result<Vec<TrendPoint>> {
+        let mut statement = self.conn.prepare(
+            "SELECT recorded_at, strftime('%Y-%m-%d %H:%M', recorded_at, 'unixepoch'), commit_id,
+                total_classes, god_classes, classes_with_god_methods, low_cohesion_classes,
+                oversized_classes, healthy_classes, maintainability_index
+             FROM (SELECT * FROM runs ORDER BY recorded_at DESC, id DESC LIMIT ?1)
+             ORDER BY recorded_at, id",
+        )?;
+        let limit = last.map_or(-1, |n| n as i64);
+        let points = statement
+            .query_map([limit], |row| {
+                Ok(TrendPoint {
+                    recorded_at: row.get(0)?,
+                    date: row.get(1)?,
+                    commit: row.get(2)?,
+                    total_classes: row.get(3)?,
+                    god_classes: row.get(4)?,
+                    classes_with_god_methods: row.get(5)?,
+                    low_cohesion_classes: row.get(6)?,
+                    oversized_classes: row.get(7)?,
+                    healthy_classes: row.get(8)?,
+                    maintainability_index: row.get(9)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(points)
+    }
+}
+
+pub async fn run(
+    path: PathBuf,
+    config: Option<PathBuf>,
+    db: Option<PathBuf>,
+    record: bool,
+    last: Option<usize>,
+    format: String,
+    load_state: Option<PathBuf>,
+) -> Result<()> {
+    if format != "text" && format != "json" {
+        bail!("Unknown trend format '{}' (expected text or json)", format);
+    }
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    let root = decisions_root(config_path.as_deref(), &path);
+    let db = db.unwrap_or_else(|| root.join(TREND_FILE));
+    if !record && !db.is_file() {
+        bail!("No runs recorded in {} yet (dei trend {} --record)", db.display(), path.display());
+    }
+    let store = TrendStore::open(&db)?;
+
+    if record {
+        let engine = pipeline::engine(&config, load_detectors(&root)?.0);
+        let point = measure(&path, engine, &config, load_state.as_deref())?;
+        store.record(&point)?;
+        if format == "text" {
+            println!("📈 Recorded run: {} god classes of {} ({})", point.god_classes, point.total_classes, db.display());
+            println!();
+        }
+    }
+
+    let points = store.points(last)?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&points)?);
+    } else {
+        print_text(&db, &points);
+    }
+    Ok(())
+}
+
+/// Analyze `path`, or the tree saved in `state`, into a point stamped with the current time and HEAD
+fn measure(path: &Path, engine: AnalysisEngineBuilder, config: &DeiConfig, state: Option<&Path>) -> Result<TrendPoint> {
+    let engine = match state {
+        Some(file) => {
+            let (arena, root) = load_saved_state(file, path)?;
+            engine.restored(arena, root)
+        }
+        None => engine.keep_tree(true),
+    };
+    let analysis = engine.analyze(path)?;
+    let coupling = pipeline::coupling_analyzer(path, &analysis)?;
+
+    let results: Vec<_> = analysis
+        .results
+        .into_iter()
+        .map(|result| config.violations.apply(config.smells.apply(result)))
+        .collect();
+    let summary = ReportSummary::from_results(&results);
+
+    let recorded_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    Ok(TrendPoint {
+        recorded_at,
+        date: String::new(),
+        commit: GitRepo::discover(path).ok().and_then(|repo| repo.head_commit()),
+        total_classes: summary.total_classes,
+        god_classes: summary.god_classes,
+        classes_with_god_methods: summary.classes_with_god_methods,
+        low_cohesion_classes: summary.low_cohesion_classes,
+        oversized_classes: summary.oversized_classes,
+        healthy_classes: summary.healthy_classes,
+        maintainability_index: coupling.architecture_quality().maintainability_index,
+    })
+}
+
+fn print_text(db: &Path, points: &[TrendPoint]) {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - TREND                                      ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+    println!("  {} {} ({} runs)", "Store:".bold(), db.display().to_string().bright_yellow(), points.len());
+    println!();
+
+    let (Some(first), Some(latest)) = (points.first(), points.last()) else {
+        println!("{}", "No runs recorded yet.".yellow());
+        println!();
+        return;
+    };
+
+    let series = |value: fn(&TrendPoint) -> f64| -> Vec<f64> { points.iter().map(value).collect() };
+    let counts: [Series; 5] = [
+        ("God Classes", |p| p.god_classes as f64, false),
+        ("Classes with God Methods", |p| p.classes_with_god_methods as f64, false),
+        ("Low Cohesion Classes", |p| p.low_cohesion_classes as f64, false),
+        ("Too Many Fields/Dependencies", |p| p.oversized_classes as f64, false),
+        ("Healthy Classes", |p| p.healthy_classes as f64, true),
+    ];
+    for (label, value, higher_is_better) in counts {
+        let change = change(value(first), value(latest), higher_is_better, 0);
+        println!("  {:<30} {}  {}", label.bold(), sparkline(&series(value)), change);
+    }
+    let mi = |p: &TrendPoint| p.maintainability_index;
+    println!(
+        "  {:<30} {}  {}",
+        "Maintainability Index".bold(),
+        sparkline(&series(mi)),
+        change(mi(first), mi(latest), true, 2)
+    );
+    println!();
+
+    println!(
+        "  {}",
+        format!(
+            "{:<16}  {:<8}  {:>7}  {:>4}  {:>11}  {:>7}  {:>5}",
+            "WHEN", "COMMIT", "CLASSES", "GOD", "GOD METHODS", "HEALTHY", "MI"
+        )
+        .dimmed()
+    );
+    for point in points {
+        let commit = point.commit.as_deref().map_or("-", |c| &c[..c.len().min(8)]);
+        println!(
+            "  {:<16}  {:<8}  {:>7}  {:>4}  {:>11}  {:>7}  {:>5.2}",
+            point.date,
+            commit,
+            point.total_classes,
+            point.god_classes,
+            point.classes_with_god_methods,
+            point.healthy_classes,
+            point.maintainability_index
+        );
+    }
+    println!();
+}
+
+/// One bar per run, scaled between the series' minimum and maximum
+fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|&v| {
+            let level = if max > min { (v - min) / (max - min) * (BARS.len() - 1) as f64 } else { 0.0 };
+            BARS[level.round() as usize]
+        })
+        .collect()
+}
+
+/// `first → latest (±delta)`, the delta green when it is an improvement
+fn change(first: f64, latest: f64, higher_is_better: bool, decimals: usize) -> String {
+    let delta = latest - first;
+    let text = format!("{:.*} → {:.*}", decimals, first, decimals, latest);
+    if delta.abs() < 1e-9 {
+        return text;
+    }
+    let signed = format!("{:+.*}", decimals, delta);
+    let signed = if (delta > 0.0) == higher_is_better { signed.green() } else { signed.red() };
+    format!("{} ({})", text, signed)
+}
@@ -0,0 +1,244 @@
+//! Triage command - step through new findings and record a decision for each
+//!
+//! Accepted findings go to `dei-baseline.json`, suppressed ones (with a reason) to
+//! `dei-suppressions.toml`; `dei check` hides both from then on.
+
+use anyhow::Result;
+use colored::Colorize;
+use dei_core::{
+    baseline::{Decisions, Finding, BASELINE_FILE, SUPPRESSIONS_FILE},
+    models::AnalysisResult,
+};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use super::pipeline::{self, decisions_root, load_detectors};
+use crate::report::print_violation;
+
+/// What the user chose for one finding
+enum Action {
+    Accept,
+    Suppress,
+    Open,
+    Details,
+    Skip,
+    Quit,
+}
+
+impl Action {
+    fn parse(input: &str) -> Option<Self> {
+        match input.trim() {
+            "a" | "accept" => Some(Action::Accept),
+            "s" | "suppress" => Some(Action::Suppress),
+            "o" | "open" => Some(Action::Open),
+            "d" | "details" => Some(Action::Details),
+            "" | "n" | "next" => Some(Action::Skip),
+            "q" | "quit" => Some(Action::Quit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    accepted: usize,
+    suppressed: usize,
+    skipped: usize,
+}
+
+pub async fn run(path: PathBuf, config: Option<PathBuf>, accept_all: bool) -> Result<()> {
+    let (config_path, config) = pipeline::load_config(config, &path)?;
+    let root = decisions_root(config_path.as_deref(), &path);
+    let mut decisions = Decisions::load(&root)?;
+
+    // In file order, as the engine returns them
+    let results: Vec<AnalysisResult> = pipeline::engine(&config, load_detectors(&root)?.0)
+        .analyze(&path)?
+        .results
+        .into_iter()
+        .map(|result| config.violations.apply(config.smells.apply(result)))
+        .collect();
+
+    let findings: Vec<(Finding, &AnalysisResult)> = results
+        .iter()
+        .flat_map(|result| {
+            let file = decisions.relative(&result.class_metrics.file_path);
+            Finding::of(result, &file).into_iter().map(move |f| (f, result))
+        })
+        .filter(|(finding, _)| decisions.is_new(finding))
+        .collect();
+
+    print_banner();
+    if findings.is_empty() {
+        println!("{}", "✅ No new findings to triage.".green());
+        println!();
+        return Ok(());
+    }
+
+    if accept_all {
+        for (finding, _) in &findings {
+            decisions.accept(finding);
+        }
+        decisions.save()?;
+        println!(
+            "📋 Accepted {} finding(s) into {}",
+            findings.len(),
+            decisions.root().join(BASELINE_FILE).display().to_string().bright_yellow()
+        );
+        println!();
+        return Ok(());
+    }
+
+    println!("{} new finding(s). For each one:", findings.len().to_string().yellow());
+    println!("  [a]ccept into the baseline, [s]uppress with a reason, [o]pen in $EDITOR,");
+    println!("  [d]etails, [n]ext (skip), [q]uit");
+    println!();
+
+    let mut input = std::io::stdin().lock();
+    let mut tally = Tally::default();
+    'findings: for (index, (finding, result)) in findings.iter().enumerate() {
+        print_finding(index + 1, findings.len(), finding);
+
+        loop {
+            let Some(answer) = prompt(&mut input, "> ")? else {
+                break 'findings;
+            };
+            match Action::parse(&answer) {
+                Some(Action::Accept) => {
+                    decisions.accept(finding);
+                    decisions.save()?;
+                    tally.accepted += 1;
+                    break;
+                }
+                Some(Action::Suppress) => {
+                    let Some(reason) = prompt(&mut input, "Reason: ")? else {
+                        break 'findings;
+                    };
+                    if reason.trim().is_empty() {
+                        println!("{}", "A suppression needs a reason.".yellow());
+                        continue;
+                    }
+                    decisions.suppress(finding, reason.trim());
+                    decisions.save()?;
+                    tally.suppressed += 1;
+                    break;
+                }
+                Some(Action::Open) => open_in_editor(finding),
+                Some(Action::Details) => print_details(finding, result),
+                Some(Action::Skip) => {
+                    tally.skipped += 1;
+                    break;
+                }
+                Some(Action::Quit) => break 'findings,
+                None => println!("{}", "Choose a, s, o, d, n or q.".yellow()),
+            }
+        }
+        println!();
+    }
+
+    println!(
+        "{} accepted → {}, {} suppressed → {}, {} skipped",
+        tally.accepted.to_string().green(),
+        BASELINE_FILE,
+        tally.suppressed.to_string().green(),
+        SUPPRESSIONS_FILE,
+        tally.skipped
+    );
+    println!();
+    Ok(())
+}
+
+fn print_banner() {
+    println!("{}", "╔════════════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║           DEI - TRIAGE                                     ║".bright_cyan());
+    println!("{}", "╚════════════════════════════════════════════════════════════╝".bright_cyan());
+    println!();
+}
+
+/// Print a prompt and read one line; `None` at end of input
+fn prompt(input: &mut impl BufRead, label: &str) -> Result<Option<String>> {
+    print!("{}", label.bright_cyan());
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        println!();
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end().to_string()))
+}
+
+fn print_finding(position: usize, total: usize, finding: &Finding) {
+    let what = match (&finding.id.method, finding.is_god_class) {
+        (Some(_), _) => "God method",
+        (None, true) => "God class",
+        (None, false) => "Class",
+    };
+    println!(
+        "[{}/{}] {} {}: {}",
+        position,
+        total,
+        "❌".red(),
+        what,
+        finding.id.to_string().bold()
+    );
+    println!("     File: {}:{}", finding.id.file, finding.span.start);
+    for violation in &finding.violations {
+        print_violation("     ", violation);
+    }
+}
+
+fn print_details(finding: &Finding, result: &AnalysisResult) {
+    let class = &result.class_metrics;
+    let method = finding
+        .id
+        .method
+        .as_ref()
+        .and_then(|name| result.god_methods.iter().find(|m| &m.method_name == name));
+
+    match method {
+        Some(god_method) => {
+            let m = &god_method.metrics;
+            println!(
+                "     Lines: {} | Complexity: {} | Parameters: {} | Nesting: {}",
+                m.lines.0, m.complexity.0, m.parameters.0, m.nesting_depth.0
+            );
+            println!("     Signature: {}", m.signature());
+        }
+        None => {
+            println!(
+                "     Lines: {} | Methods: {} | Complexity: {} | Fields: {} | Dependencies: {}",
+                class.lines.0,
+                class.method_count.0,
+                class.complexity.0,
+                class.field_count,
+                class.dependencies.len()
+            );
+            if let Some(lcom4) = result.lcom4 {
+                println!("     LCOM4: {}", lcom4);
+            }
+            println!("     {}", result.summary);
+        }
+    }
+}
+
+/// `$VISUAL` or `$EDITOR` at the finding's line (`+N` is understood by vi, nano and emacs)
+fn open_in_editor(finding: &Finding) {
+    let Some(editor) = std::env::var("VISUAL").or_else(|_| std::env::var("EDITOR")).ok() else {
+        println!("{}", "Set $EDITOR to open findings.".yellow());
+        return;
+    };
+
+    let mut words = editor.split_whitespace();
+    let Some(program) = words.next() else {
+        return;
+    };
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(format!("+{}", finding.span.start.max(1)))
+        .arg(finding.path.as_ref())
+        .status();
+    if let Err(e) = status {
+        println!("{}", format!("Could not run {}: {}", program, e).red());
+    }
+}
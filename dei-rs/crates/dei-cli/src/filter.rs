@@ -0,0 +1,413 @@
+//! `--filter` expressions for slicing results before they are reported
+//!
+//! ```text
+//! lang == "python" && complexity > 30 && path ~ "services/**"
+//! god_class || (god_methods >= 2 && !(name ~ "*Test*"))
+//! ```
+//!
+//! Comparisons are `==`, `!=`, `<`, `<=`, `>`, `>=` and the glob matches `~`
+//! and `!~`; combine them with `&&`, `||`, `!` and parentheses. A path glob
+//! matches the whole path or any trailing part of it starting at a `/`.
+
+use dei_core::{
+    models::{AnalysisResult, Language},
+    thresholds::wildcard_match,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed `--filter` expression
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn matches(&self, result: &AnalysisResult) -> bool {
+        self.expr.eval(result)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = ExprParser { tokens: tokenize(s)?, pos: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Self { expr }),
+            Some(token) => Err(FilterError(format!("unexpected {} after expression", token))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError(String);
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid filter: {}", self.0)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Language,
+    Path,
+    Name,
+    Lines,
+    Methods,
+    Complexity,
+    Fields,
+    Dependencies,
+    Lcom4,
+    GodMethods,
+    GodClass,
+    Healthy,
+    Severity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Number,
+    Text,
+    Flag,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("lang", Field::Language),
+    ("language", Field::Language),
+    ("path", Field::Path),
+    ("file", Field::Path),
+    ("name", Field::Name),
+    ("class", Field::Name),
+    ("lines", Field::Lines),
+    ("methods", Field::Methods),
+    ("complexity", Field::Complexity),
+    ("fields", Field::Fields),
+    ("dependencies", Field::Dependencies),
+    ("lcom4", Field::Lcom4),
+    ("god_methods", Field::GodMethods),
+    ("god_class", Field::GodClass),
+    ("healthy", Field::Healthy),
+    ("severity", Field::Severity),
+];
+
+impl Field {
+    fn parse(name: &str) -> Result<Self, FilterError> {
+        FIELDS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, field)| field)
+            .ok_or_else(|| {
+                let known: Vec<&str> = FIELDS.iter().map(|(n, _)| *n).collect();
+                FilterError(format!("unknown field '{}' (expected one of {})", name, known.join(", ")))
+            })
+    }
+
+    fn ty(self) -> FieldType {
+        match self {
+            Field::Language | Field::Path | Field::Name | Field::Severity => FieldType::Text,
+            Field::GodClass | Field::Healthy => FieldType::Flag,
+            _ => FieldType::Number,
+        }
+    }
+
+    fn value(self, result: &AnalysisResult) -> Value {
+        let metrics = &result.class_metrics;
+        match self {
+            Field::Language => Value::Text(
+                Language::from_path(metrics.file_path.as_ref())
+                    .map(|l| format!("{:?}", l).to_lowercase())
+                    .unwrap_or_default(),
+            ),
+            Field::Path => Value::Text(metrics.file_path.to_string()),
+            Field::Name => Value::Text(metrics.name.to_string()),
+            Field::Lines => Value::Number(metrics.lines.0 as f64),
+            Field::Methods => Value::Number(metrics.method_count.0 as f64),
+            Field::Complexity => Value::Number(metrics.complexity.0 as f64),
+            Field::Fields => Value::Number(metrics.field_count as f64),
+            Field::Dependencies => Value::Number(metrics.dependencies.len() as f64),
+            Field::Lcom4 => Value::Number(result.lcom4.unwrap_or_default() as f64),
+            Field::GodMethods => Value::Number(result.god_methods.len() as f64),
+            Field::GodClass => Value::Flag(result.is_god_class),
+            Field::Healthy => Value::Flag(!result.has_issues()),
+            Field::Severity => Value::Text(result.max_severity().map_or("none", |s| s.as_str()).to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Flag(bool),
+}
+
+impl Value {
+    fn ty(&self) -> FieldType {
+        match self {
+            Value::Number(_) => FieldType::Number,
+            Value::Text(_) => FieldType::Text,
+            Value::Flag(_) => FieldType::Flag,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Glob,
+    NotGlob,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Flag(Field),
+    Compare(Field, Op, Value),
+}
+
+impl Expr {
+    fn eval(&self, result: &AnalysisResult) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(result) && b.eval(result),
+            Expr::Or(a, b) => a.eval(result) || b.eval(result),
+            Expr::Not(e) => !e.eval(result),
+            Expr::Flag(field) => field.value(result) == Value::Flag(true),
+            Expr::Compare(field, op, expected) => compare(*field, &field.value(result), *op, expected),
+        }
+    }
+}
+
+fn compare(field: Field, actual: &Value, op: Op, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => match op {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Glob | Op::NotGlob => false,
+        },
+        (Value::Text(a), Value::Text(b)) => {
+            // Language names are matched case-insensitively (`Python`, `python`)
+            let b = if field == Field::Language { b.to_lowercase() } else { b.clone() };
+            match op {
+                Op::Eq => *a == b,
+                Op::Ne => *a != b,
+                Op::Glob => glob_matches(field, &b, a),
+                Op::NotGlob => !glob_matches(field, &b, a),
+                _ => false,
+            }
+        }
+        (Value::Flag(a), Value::Flag(b)) => match op {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn glob_matches(field: Field, pattern: &str, text: &str) -> bool {
+    if field != Field::Path {
+        return wildcard_match(pattern, text);
+    }
+
+    let path = text.replace('\\', "/");
+    wildcard_match(pattern, &path)
+        || path.match_indices('/').any(|(i, _)| wildcard_match(pattern, &path[i + 1..]))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Text(s) => write!(f, "\"{}\"", s),
+            Token::Op(op) => write!(f, "operator {:?}", op),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Not => write!(f, "'!'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('!', Some('~')) => (Token::Op(Op::NotGlob), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('~', _) => (Token::Op(Op::Glob), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"' | '\'', _) => {
+                let mut text = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(FilterError("unterminated string".into())),
+                        Some('\\') if j + 1 < chars.len() => {
+                            text.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&ch) => {
+                            text.push(ch);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Text(text), j + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() => {
+                let len = chars[i..].iter().take_while(|c| c.is_ascii_digit() || **c == '.').count();
+                let literal: String = chars[i..i + len].iter().collect();
+                let number = literal
+                    .parse()
+                    .map_err(|_| FilterError(format!("invalid number '{}'", literal)))?;
+                (Token::Number(number), len)
+            }
+            (c, _) if c.is_ascii_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                    .count();
+                (Token::Ident(chars[i..i + len].iter().collect()), len)
+            }
+            (c, _) => return Err(FilterError(format!("unexpected character '{}'", c))),
+        };
+        tokens.push(token);
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent: `||` binds loosest, then `&&`, then `!`
+struct ExprParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(FilterError("missing ')'".into())),
+                }
+            }
+            Some(Token::Ident(name)) => self.comparison(Field::parse(&name)?),
+            Some(token) => Err(FilterError(format!("expected a field, found {}", token))),
+            None => Err(FilterError("unexpected end of expression".into())),
+        }
+    }
+
+    fn comparison(&mut self, field: Field) -> Result<Expr, FilterError> {
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            // A bare flag field (`god_class`) is a test on its own
+            _ if field.ty() == FieldType::Flag => return Ok(Expr::Flag(field)),
+            _ => return Err(FilterError(format!("expected a comparison after '{:?}'", field))),
+        };
+        self.pos += 1;
+
+        let value = match self.next() {
+            Some(Token::Number(n)) => Value::Number(n),
+            Some(Token::Text(s)) => Value::Text(s),
+            Some(Token::Ident(word)) if word == "true" || word == "false" => Value::Flag(word == "true"),
+            Some(token) => return Err(FilterError(format!("expected a value, found {}", token))),
+            None => return Err(FilterError("missing value after operator".into())),
+        };
+
+        if value.ty() != field.ty() {
+            return Err(FilterError(format!("{:?} cannot be compared with {:?}", field, value)));
+        }
+        let supported = match field.ty() {
+            FieldType::Number => !matches!(op, Op::Glob | Op::NotGlob),
+            FieldType::Text => matches!(op, Op::Eq | Op::Ne | Op::Glob | Op::NotGlob),
+            FieldType::Flag => matches!(op, Op::Eq | Op::Ne),
+        };
+        if !supported {
+            return Err(FilterError(format!("operator {:?} does not apply to {:?}", op, field)));
+        }
+
+        Ok(Expr::Compare(field, op, value))
+    }
+}
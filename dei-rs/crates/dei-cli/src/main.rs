@@ -3,9 +3,12 @@
 //! Beautiful, fast, and extensible
 
 mod commands;
+mod filter;
 mod report;
+mod resources;
+mod telemetry;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
@@ -14,6 +17,26 @@ use clap::{Parser, Subcommand};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Print how long each phase took to stderr: building the tree, parsing, analyzing
+    #[arg(long, global = true)]
+    trace: bool,
+
+    /// Send the phase spans to an OpenTelemetry collector at this base URL over OTLP/HTTP
+    #[arg(long, global = true, value_name = "URL", env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+
+    /// Parse and analyze on at most N threads [default: one per core]
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    threads: Option<u16>,
+
+    /// Abort rather than grow the heap past this, e.g. 512M or 2GB; at least 64M (Unix only)
+    #[arg(long, global = true, value_name = "SIZE", value_parser = resources::parse_memory)]
+    max_memory: Option<u64>,
+
+    /// Run at low CPU priority and, on Linux, idle I/O priority, to share a machine politely
+    #[arg(long, global = true)]
+    nice: bool,
 }
 
 #[derive(Subcommand)]
@@ -21,64 +44,468 @@ enum Commands {
     /// Check a directory for god classes
     Check {
         /// Path to analyze
-        path: std::path::PathBuf,
-        
-        /// Maximum class lines
-        #[arg(long, default_value = "300")]
-        max_lines: usize,
+        #[arg(required_unless_present = "schema")]
+        path: Option<std::path::PathBuf>,
         
-        /// Maximum methods per class
-        #[arg(long, default_value = "20")]
-        max_methods: usize,
-        
-        /// Maximum cyclomatic complexity
+        #[command(flatten)]
+        options: Box<commands::check::CheckOptions>,
+    },
+    
+    /// Analyze architecture quality
+    Arch {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Maximum distinct third-party packages a class may import
+        #[arg(long, default_value = "8")]
+        max_third_party: usize,
+
+        /// Also report coupling and cycles between packages, grouped by directory (default) or namespace
+        #[arg(long, value_enum, value_name = "GROUPING", num_args = 0..=1, default_missing_value = "directory")]
+        packages: Option<commands::arch::Grouping>,
+
+        /// Minimum betweenness centrality (0-1) for a class to be reported as a choke point
+        #[arg(long, default_value = "0.1")]
+        min_betweenness: f64,
+
+        /// Reuse the tree and metrics saved by `dei check --save-state` instead of parsing PATH
+        #[arg(long, value_name = "FILE")]
+        load_state: Option<std::path::PathBuf>,
+    },
+
+    /// List the public API surface per module
+    Api {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Maximum public types per module
+        #[arg(long, default_value = "10")]
+        max_public_types: usize,
+
+        /// Maximum public methods per module
         #[arg(long, default_value = "50")]
-        max_complexity: usize,
-        
+        max_public_methods: usize,
+
         /// Output format (text, json)
         #[arg(long, default_value = "text")]
         format: String,
-        
-        /// Show detailed analysis
+
+        /// List every public signature
         #[arg(long, short)]
         verbose: bool,
+
+        /// Lowest severity that makes the run exit 1 (info, warning, error) [default: warning]
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on: Option<dei_core::models::Severity>,
+
+        /// Only exit 1 when more than N violations are at or above --fail-on
+        #[arg(long, value_name = "N")]
+        max_issues: Option<usize>,
+
+        /// Always exit 0, whatever the violations
+        #[arg(long)]
+        no_fail: bool,
     },
-    
-    /// Analyze architecture quality
-    Arch {
+
+    /// Rank files by git churn × complexity
+    Hotspots {
+        /// Path to analyze (inside a git repository)
+        path: std::path::PathBuf,
+
+        /// Only count commits from the last N days
+        #[arg(long)]
+        since_days: Option<u64>,
+
+        /// Number of files to show
+        #[arg(long, default_value = "20")]
+        top: usize,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Show per-class and per-method metric changes of one file since a git ref
+    DiffFile {
+        /// File to compare (inside a git repository)
+        path: std::path::PathBuf,
+
+        /// Git ref to compare the working tree against
+        #[arg(long, default_value = "HEAD")]
+        base: String,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Also list unchanged classes and methods
+        #[arg(long, short)]
+        verbose: bool,
+    },
+
+    /// Compare god classes and class metrics between two directories or git refs
+    Compare {
+        /// Directory, or git ref, before the change
+        before: String,
+
+        /// Directory, or git ref, after the change
+        after: String,
+
+        /// With git refs, the repository and directory within it to analyze
+        #[arg(long, default_value = ".")]
+        path: std::path::PathBuf,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Also list unchanged classes, and method changes
+        #[arg(long, short)]
+        verbose: bool,
+    },
+
+    /// Deep dive into one class: method metrics, violations, cohesion and coupling
+    Explain {
+        /// Class to explain, as path/to/file::Class (the file alone if it declares one class)
+        target: String,
+
+        /// Project scanned for classes depending on it and that it depends on
+        #[arg(long, default_value = ".")]
+        path: std::path::PathBuf,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Record summary metrics per run and show how code health evolves
+    Trend {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Analyze PATH and add a run before showing the trend
+        #[arg(long)]
+        record: bool,
+
+        /// Trend store to use instead of dei-trend.db next to dei.toml
+        #[arg(long)]
+        db: Option<std::path::PathBuf>,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Only show the latest N runs
+        #[arg(long, value_name = "N")]
+        last: Option<usize>,
+
+        /// Reuse the tree and metrics saved by `dei check --save-state` instead of parsing PATH
+        #[arg(long, value_name = "FILE")]
+        load_state: Option<std::path::PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Serve a dashboard and JSON API over the latest analysis
+    Serve {
         /// Path to analyze
         path: std::path::PathBuf,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Port to listen on (0 picks a free one)
+        #[arg(long, default_value = "8080")]
+        port: u16,
+
+        /// Re-analyze every N seconds (0 only on POST /api/refresh)
+        #[arg(long, value_name = "SECONDS", default_value = "300")]
+        interval: u64,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Serve the tree and metrics saved by `dei check --save-state` until the first re-analysis
+        #[arg(long, value_name = "FILE")]
+        load_state: Option<std::path::PathBuf>,
+    },
+
+    /// Language server publishing diagnostics and metric code lenses to editors, over stdio
+    Lsp {
+        /// Config file to use instead of the nearest dei.toml of each document
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+    },
+
+    /// Keep parsers and syntax trees warm and answer JSON-RPC analysis requests on a local socket
+    Daemon {
+        /// Unix domain socket to listen on
+        #[arg(long, default_value = "dei.sock")]
+        socket: std::path::PathBuf,
+    },
+
+    /// Propose extractions for flagged classes and simulate the split
+    Simulate {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Recompute metrics as if the suggested extractions were performed
+        #[arg(long)]
+        apply_clusters: bool,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Ordered refactoring backlog with estimated effort per step
+    Plan {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Only list the N highest-priority steps
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+
+    /// Scaffold the class a cluster of methods would be extracted into, as a patch (Rust, C#)
+    Refactor {
+        /// Class to split, as path/to/file::Class (the file alone if it declares one class)
+        target: String,
+
+        /// Suggested cluster to scaffold, when several are suggested
+        #[arg(long, value_name = "NAME")]
+        cluster: Option<String>,
+
+        /// Methods to extract instead of a suggested cluster
+        #[arg(long, value_delimiter = ',', value_name = "METHOD")]
+        methods: Vec<String>,
+
+        /// Name of the extracted class
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Print the patch instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Patch file to write (default: <Name>.patch)
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+    },
+
+    /// Step through new findings and accept, suppress or open each one
+    Triage {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Accept every new finding into the baseline without prompting
+        #[arg(long)]
+        accept_all: bool,
+    },
+
+    /// Dump every raw metric, one row per class and per method
+    Metrics {
+        /// Path to analyze
+        path: std::path::PathBuf,
+
+        /// Config file to use instead of the nearest dei.toml
+        #[arg(long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Output format (csv, tsv)
+        #[arg(long, default_value = "csv")]
+        format: String,
+    },
+
+    /// Verify every language parser against the bundled fixtures
+    Selftest {
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
     },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     let cli = Cli::parse();
+    // Before any other thread starts, so the runtime's, the pool's and the exporter's threads
+    // all inherit the lowered priority
+    resources::init(cli.threads.map(usize::from), cli.max_memory, cli.nice)?;
+    telemetry::init(cli.trace, cli.otlp_endpoint.as_deref())?;
 
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the async runtime")?
+        .block_on(run(cli))
+}
+
+async fn run(cli: Cli) -> Result<()> {
     match cli.command {
-        Commands::Check {
+        Commands::Check { path, options } => match path {
+            Some(path) if !options.schema => commands::check::run(path, *options).await?,
+            _ => print!("{}", report::JSON_SCHEMA),
+        },
+        Commands::Arch { path, max_third_party, packages, min_betweenness, load_state } => {
+            commands::arch::run(path, max_third_party, packages, min_betweenness, load_state).await?;
+        }
+        Commands::Api {
+            path,
+            max_public_types,
+            max_public_methods,
+            format,
+            verbose,
+            fail_on,
+            max_issues,
+            no_fail,
+        } => {
+            let limits = commands::api::ApiLimits {
+                max_public_types,
+                max_public_methods,
+            };
+            let gate = commands::api::ApiGate { fail_on, max_issues, no_fail };
+            commands::api::run(path, limits, gate, format, verbose).await?;
+        }
+        Commands::Hotspots {
+            path,
+            since_days,
+            top,
+            format,
+        } => {
+            commands::hotspots::run(path, since_days, top, format).await?;
+        }
+        Commands::DiffFile {
+            path,
+            base,
+            format,
+            verbose,
+        } => {
+            commands::diff_file::run(path, base, format, verbose).await?;
+        }
+        Commands::Compare {
+            before,
+            after,
             path,
-            max_lines,
-            max_methods,
-            max_complexity,
+            config,
             format,
             verbose,
         } => {
-            commands::check::run(
-                path,
-                max_lines,
-                max_methods,
-                max_complexity,
-                format,
-                verbose,
-            )
-            .await?;
-        }
-        Commands::Arch { path } => {
-            commands::arch::run(path).await?;
+            commands::compare::run(before, after, path, config, format, verbose).await?;
+        }
+        Commands::Explain {
+            target,
+            path,
+            config,
+            format,
+        } => {
+            commands::explain::run(target, path, config, format).await?;
+        }
+        Commands::Trend {
+            path,
+            record,
+            db,
+            config,
+            last,
+            format,
+            load_state,
+        } => {
+            commands::trend::run(path, config, db, record, last, format, load_state).await?;
+        }
+        Commands::Serve {
+            path,
+            host,
+            port,
+            interval,
+            config,
+            load_state,
+        } => {
+            commands::serve::run(path, config, host, port, interval, load_state).await?;
+        }
+        Commands::Lsp { config } => {
+            commands::lsp::run(config).await?;
+        }
+        Commands::Daemon { socket } => {
+            #[cfg(unix)]
+            commands::daemon::run(socket).await?;
+            #[cfg(not(unix))]
+            anyhow::bail!("dei daemon listens on a Unix domain socket, which {} can't be on this platform", socket.display());
+        }
+        Commands::Simulate {
+            path,
+            apply_clusters,
+            config,
+            format,
+        } => {
+            commands::simulate::run(path, apply_clusters, config, format).await?;
+        }
+        Commands::Plan {
+            path,
+            config,
+            top,
+            format,
+        } => {
+            commands::plan::run(path, config, top, format).await?;
+        }
+        Commands::Refactor {
+            target,
+            cluster,
+            methods,
+            name,
+            dry_run,
+            output,
+            config,
+        } => {
+            let selection = commands::refactor::Selection { cluster, methods, name };
+            commands::refactor::run(target, selection, config, output, dry_run).await?;
+        }
+        Commands::Triage {
+            path,
+            config,
+            accept_all,
+        } => {
+            commands::triage::run(path, config, accept_all).await?;
+        }
+        Commands::Metrics { path, config, format } => {
+            commands::metrics::run(path, config, format).await?;
+        }
+        Commands::Selftest { format } => {
+            commands::selftest::run(format).await?;
         }
     }
 
+    telemetry::shutdown();
     Ok(())
 }
 
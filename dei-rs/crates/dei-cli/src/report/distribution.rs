@@ -0,0 +1,129 @@
+//! Histograms of class size and method complexity against their thresholds
+//!
+//! Buckets are quarters of the threshold up to twice it, then one open bucket, so the
+//! histogram shows how much of the code sits just under a limit as well as how far past it
+//! the rest has grown. Entry-point profiles may have loosened a class's own limits; the
+//! histograms are drawn against the configured ones.
+
+use colored::{ColoredString, Colorize};
+use dei_core::{models::AnalysisResult, thresholds::Thresholds};
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Widest bar in the text report, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Quarters of the threshold a histogram runs to before its open bucket
+const QUARTERS: usize = 8;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Histogram {
+    /// What was measured, e.g. `Class lines`
+    pub title: &'static str,
+    /// What was counted, e.g. `classes`
+    pub unit: &'static str,
+    pub threshold: usize,
+    pub total: usize,
+    /// Count of the fullest bucket, for scaling bars
+    pub peak: usize,
+    pub buckets: Vec<Bucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bucket {
+    /// `76-150`, or `601+` for the open bucket
+    pub label: String,
+    pub min: usize,
+    /// `None` for the open bucket
+    pub max: Option<usize>,
+    pub count: usize,
+    /// Whether every value in the bucket is over the threshold
+    pub over_threshold: bool,
+}
+
+impl Histogram {
+    fn new(title: &'static str, unit: &'static str, threshold: usize, values: impl IntoIterator<Item = usize>) -> Self {
+        let mut bounds: Vec<usize> = (1..=QUARTERS).map(|k| (threshold.max(1) * k).div_ceil(4)).collect();
+        bounds.dedup();
+
+        let mut buckets: Vec<Bucket> = Vec::with_capacity(bounds.len() + 1);
+        let mut min = 0;
+        for &max in &bounds {
+            buckets.push(Bucket::new(min, Some(max), threshold));
+            min = max + 1;
+        }
+        buckets.push(Bucket::new(min, None, threshold));
+
+        let mut total = 0;
+        for value in values {
+            let index = bounds.partition_point(|&max| max < value);
+            buckets[index].count += 1;
+            total += 1;
+        }
+        let peak = buckets.iter().map(|b| b.count).max().unwrap_or_default();
+
+        Self { title, unit, threshold, total, peak, buckets }
+    }
+}
+
+impl Bucket {
+    fn new(min: usize, max: Option<usize>, threshold: usize) -> Self {
+        let label = match max {
+            Some(max) => format!("{}-{}", min, max),
+            None => format!("{}+", min),
+        };
+        Self { label, min, max, count: 0, over_threshold: min > threshold }
+    }
+}
+
+/// Class lines and method complexity across `results`
+pub fn distribution(results: &[AnalysisResult], thresholds: &Thresholds) -> Vec<Histogram> {
+    let classes = results.iter().map(|r| &r.class_metrics);
+    let methods = classes.clone().flat_map(|c| c.methods.iter());
+    vec![
+        Histogram::new("Class lines", "classes", thresholds.max_class_lines.0, classes.map(|c| c.lines.0)),
+        Histogram::new("Method complexity", "methods", thresholds.max_method_complexity.0, methods.map(|m| m.complexity.0)),
+    ]
+}
+
+/// The histograms as bar charts, one row per bucket
+pub fn write_distribution(out: &mut dyn Write, histograms: &[Histogram]) -> io::Result<()> {
+    if histograms.iter().all(|h| h.total == 0) {
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "📊 DISTRIBUTION:".bright_blue().bold())?;
+    writeln!(out)?;
+    for histogram in histograms.iter().filter(|h| h.total > 0) {
+        writeln!(out, "  {} (max {}, {} {})",
+            histogram.title.bold(),
+            histogram.threshold,
+            histogram.total,
+            histogram.unit
+        )?;
+        let width = histogram.buckets.iter().map(|b| b.label.len()).max().unwrap_or_default();
+        for bucket in &histogram.buckets {
+            // Any bucket with something in it gets at least one block
+            let len = (bucket.count * BAR_WIDTH).div_ceil(histogram.peak.max(1));
+            writeln!(out, "    {:>width$} {:<BAR_WIDTH$} {}",
+                bucket.label,
+                paint(&"█".repeat(len), bucket, histogram.threshold),
+                bucket.count,
+                width = width
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Red past the threshold, yellow in the last quarter before it, green below
+fn paint(bar: &str, bucket: &Bucket, threshold: usize) -> ColoredString {
+    if bucket.over_threshold {
+        bar.red()
+    } else if bucket.min * 4 > threshold * 3 {
+        bar.yellow()
+    } else {
+        bar.green()
+    }
+}
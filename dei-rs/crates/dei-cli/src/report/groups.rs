@@ -0,0 +1,87 @@
+//! Per-group subtotals for `--group-by`
+//!
+//! On a tree with thousands of classes the flat report is hard to navigate; grouping by
+//! directory, language or project gives each group its own counts and its worst classes, so
+//! the places needing work stand out first.
+
+use colored::Colorize;
+use dei_core::models::{AnalysisResult, ClassLocation};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use super::ReportSummary;
+
+/// Worst classes listed per group
+const WORST_PER_GROUP: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportGroup {
+    /// Directory, language or project the classes belong to
+    pub name: String,
+    pub summary: ReportSummary,
+    /// Flagged classes with the highest violation score, worst first
+    pub worst: Vec<ClassLocation>,
+}
+
+impl ReportGroup {
+    /// Group `results` by `key`, the groups with most flagged classes first
+    pub fn collect(results: &[AnalysisResult], mut key: impl FnMut(&AnalysisResult) -> String) -> Vec<Self> {
+        let mut members: BTreeMap<String, Vec<AnalysisResult>> = BTreeMap::new();
+        for result in results {
+            members.entry(key(result)).or_default().push(result.clone());
+        }
+
+        let mut groups: Vec<Self> = members
+            .into_iter()
+            .map(|(name, mut results)| {
+                let summary = ReportSummary::from_results(&results);
+                results.retain(|r| r.has_issues());
+                results.sort_by(|a, b| b.violation_score().total_cmp(&a.violation_score()));
+                let worst = results
+                    .iter()
+                    .take(WORST_PER_GROUP)
+                    .map(|r| ClassLocation {
+                        qualified_name: r.class_metrics.fully_qualified_name.clone(),
+                        file_path: r.class_metrics.file_path.clone(),
+                        span: r.class_metrics.span,
+                    })
+                    .collect();
+                Self { name, summary, worst }
+            })
+            .collect();
+
+        // Stable, so equally flagged groups stay in name order
+        groups.sort_by_key(|g| std::cmp::Reverse((g.flagged(), g.summary.god_classes)));
+        groups
+    }
+
+    fn flagged(&self) -> usize {
+        self.summary.total_classes - self.summary.healthy_classes
+    }
+}
+
+/// A block per group: its counts, then its worst classes
+pub fn write_groups(out: &mut dyn Write, title: &str, groups: &[ReportGroup]) -> io::Result<()> {
+    if groups.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "{}", format!("BY {}:", title.to_uppercase()).bright_green().bold())?;
+    writeln!(out)?;
+    for group in groups {
+        let summary = &group.summary;
+        writeln!(out, "  {} {}", group.name.bold(), format!("({} classes)", summary.total_classes).dimmed())?;
+        writeln!(out, "     Flagged: {} | God Classes: {} | With God Methods: {} | Healthy: {}",
+            group.flagged().to_string().yellow(),
+            summary.god_classes.to_string().red(),
+            summary.classes_with_god_methods.to_string().yellow(),
+            summary.healthy_classes.to_string().green()
+        )?;
+        for location in &group.worst {
+            writeln!(out, "     • {} ({}:{})", location.qualified_name.bright_yellow(), location.file_path, location.span.start)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Versioned JSON envelope for `--format json`
+//!
+//! The document is `{"version", "metadata", "summary", "results", "god_files", "skipped_files", "file_warnings", "groups", "methods"}`, described by the bundled
+//! `schemas/check.schema.json` (`dei check --schema` prints it). Fields may be added
+//! within a version; removing or retyping one bumps [`JSON_VERSION`].
+
+use dei_core::{
+    metrics::MethodMetrics,
+    models::{AnalysisResult, FileError, FileWarning, GodFileResult},
+    thresholds::Thresholds,
+};
+use serde::Serialize;
+
+use super::{ReportGroup, ReportMetadata, ReportSummary};
+
+/// Version of the `--format json` document
+pub const JSON_VERSION: &str = "1";
+
+/// JSON Schema of the `--format json` document
+pub const JSON_SCHEMA: &str = include_str!("../../schemas/check.schema.json");
+
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a> {
+    pub version: &'static str,
+    pub metadata: &'a ReportMetadata,
+    pub summary: ReportSummary,
+    pub results: &'a [AnalysisResult],
+    /// Files over the god-file thresholds
+    pub god_files: &'a [GodFileResult],
+    /// Files that couldn't be read or parsed; omitted when there are none
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub skipped_files: &'a [FileError],
+    /// Files analyzed with a caveat; omitted when there are none
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub file_warnings: &'a [FileWarning],
+    /// Subtotals per `--group-by` group; omitted without it
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub groups: &'a [ReportGroup],
+    /// Every method of every analyzed class with `--include-all-methods`; omitted without it
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub methods: &'a [MethodEntry<'a>],
+}
+
+/// One method and the class it belongs to, flagged or not
+#[derive(Debug, Serialize)]
+pub struct MethodEntry<'a> {
+    pub class_name: &'a str,
+    pub file_path: &'a str,
+    pub metrics: &'a MethodMetrics,
+    /// Whether it is reported among its class's `god_methods`
+    pub is_god_method: bool,
+    /// How far the method overshoots its limits, as ranked for god methods
+    pub violation_score: f64,
+}
+
+impl<'a> MethodEntry<'a> {
+    /// The methods of `results`, class by class
+    pub fn all(results: &'a [AnalysisResult], thresholds: &Thresholds) -> Vec<Self> {
+        results
+            .iter()
+            .flat_map(|result| {
+                let class = &result.class_metrics;
+                let thresholds = thresholds.for_entry_point(class.entry_point);
+                class.methods.iter().map(move |method| MethodEntry {
+                    class_name: &class.fully_qualified_name,
+                    file_path: &class.file_path,
+                    metrics: method,
+                    is_god_method: result
+                        .god_methods
+                        .iter()
+                        .any(|g| g.method_name == method.name && g.metrics.span == method.span),
+                    violation_score: method.violation_score(&thresholds),
+                })
+            })
+            .collect()
+    }
+}
+
+impl<'a> JsonReport<'a> {
+    /// `summary` may cover more classes than `results` (`--top`, `--summary`)
+    pub fn new(metadata: &'a ReportMetadata, summary: ReportSummary, results: &'a [AnalysisResult]) -> Self {
+        Self {
+            version: JSON_VERSION,
+            metadata,
+            summary,
+            results,
+            god_files: &[],
+            skipped_files: &[],
+            file_warnings: &[],
+            groups: &[],
+            methods: &[],
+        }
+    }
+
+    pub fn with_god_files(mut self, god_files: &'a [GodFileResult]) -> Self {
+        self.god_files = god_files;
+        self
+    }
+
+    pub fn with_skipped_files(mut self, skipped_files: &'a [FileError]) -> Self {
+        self.skipped_files = skipped_files;
+        self
+    }
+
+    pub fn with_file_warnings(mut self, file_warnings: &'a [FileWarning]) -> Self {
+        self.file_warnings = file_warnings;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: &'a [ReportGroup]) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    pub fn with_methods(mut self, methods: &'a [MethodEntry<'a>]) -> Self {
+        self.methods = methods;
+        self
+    }
+}
@@ -0,0 +1,56 @@
+//! What produced a report: tool version, when, on what and with which limits
+//!
+//! Enough to rerun the analysis and get the same results, or to tell two reports apart
+//! when auditing them later.
+
+use dei_core::thresholds::Thresholds;
+use dei_git::GitRepo;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportMetadata {
+    pub tool: &'static str,
+    pub version: &'static str,
+    /// Unix seconds
+    pub generated_at: u64,
+    /// The analyzed file or directory, absolute where it could be resolved
+    pub path: String,
+    /// HEAD of the repository the path lives in; omitted outside one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Limits after `dei.toml`, presets and command-line overrides
+    pub thresholds: Thresholds,
+    pub files: FileCounts,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct FileCounts {
+    /// Files parsed and analyzed
+    pub analyzed: usize,
+    /// Files that couldn't be read or parsed
+    pub skipped: usize,
+    /// Analyzed files with a caveat
+    pub with_warnings: usize,
+}
+
+impl ReportMetadata {
+    pub fn new(path: &Path, thresholds: &Thresholds, files: FileCounts) -> Self {
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        Self {
+            tool: "dei",
+            version: env!("CARGO_PKG_VERSION"),
+            generated_at,
+            commit: GitRepo::discover(&path).ok().and_then(|repo| repo.head_commit()),
+            path: path.display().to_string(),
+            thresholds: thresholds.clone(),
+            files,
+        }
+    }
+}
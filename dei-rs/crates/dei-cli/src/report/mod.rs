@@ -1,7 +1,219 @@
 //! Beautiful report generation
 
+mod distribution;
+mod groups;
+mod json;
+mod metadata;
+mod template;
+
+pub use distribution::{distribution, write_distribution, Histogram};
+pub use groups::{write_groups, ReportGroup};
+pub use json::{JsonReport, MethodEntry, JSON_SCHEMA};
+pub use metadata::{FileCounts, ReportMetadata};
+pub use template::TemplateReport;
+
 use colored::Colorize;
 use dei_core::{models::*, thresholds::Thresholds};
+use dei_git::{top_contributors, Attribution};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Coupling line for classes with any dependents or dependencies
+fn write_coupling(out: &mut dyn Write, indent: &str, coupling: Option<ClassCoupling>) -> io::Result<()> {
+    let Some(coupling) = coupling.filter(|c| c.afferent + c.efferent > 0) else {
+        return Ok(());
+    };
+    writeln!(
+        out,
+        "{indent}Coupling: Ca {} | Ce {} | Instability {:.2}",
+        coupling.afferent.to_string().yellow(),
+        coupling.efferent.to_string().yellow(),
+        coupling.instability
+    )
+}
+
+/// Owner line for results annotated by `--owners`
+fn write_owners(out: &mut dyn Write, indent: &str, owners: &[Owner]) -> io::Result<()> {
+    if owners.is_empty() {
+        return Ok(());
+    }
+    let names: Vec<String> = owners
+        .iter()
+        .map(|o| format!("{} ({:.0}%)", o.name, o.share * 100.0))
+        .collect();
+    writeln!(out, "{}Owners: {}", indent, names.join(", ").bright_cyan())?;
+    Ok(())
+}
+
+/// Tags, owner and layer assigned by `[[metadata]]` path rules
+fn write_metadata(out: &mut dyn Write, indent: &str, metadata: &PathMetadata) -> io::Result<()> {
+    let mut parts = Vec::new();
+    if !metadata.tags.is_empty() {
+        parts.push(format!("Tags: {}", metadata.tags.join(", ")));
+    }
+    if let Some(owner) = &metadata.owner {
+        parts.push(format!("Owner: {owner}"));
+    }
+    if let Some(layer) = &metadata.layer {
+        parts.push(format!("Layer: {layer}"));
+    }
+    if !parts.is_empty() {
+        writeln!(out, "{}{}", indent, parts.join(" | ").dimmed())?;
+    }
+    Ok(())
+}
+
+/// How cleanly clustering split the class, so suggestions can be taken with a grain of salt
+fn write_cluster_stats(out: &mut dyn Write, stats: &ClusterStats) -> io::Result<()> {
+    let silhouette = match stats.silhouette {
+        Some(score) => format!("{:.2}", score),
+        None => "n/a".into(),
+    };
+    writeln!(out, "       {}",
+        format!("Clustering: {} cluster(s), {:.0}% noise, silhouette {}",
+            stats.n_clusters, stats.noise_ratio * 100.0, silhouette).dimmed()
+    )?;
+    Ok(())
+}
+
+/// Bullet for a single threshold violation, tagged when not a plain warning
+fn write_violation(out: &mut dyn Write, indent: &str, violation: &Violation) -> io::Result<()> {
+    let severity = match violation.severity {
+        Severity::Warning => String::new(),
+        Severity::Info => format!(" [{}]", violation.severity.as_str().cyan()),
+        Severity::Error => format!(" [{}]", violation.severity.as_str().red().bold()),
+    };
+    if let (Some(rule), Some(message)) = (&violation.rule, &violation.message) {
+        writeln!(out, "{}• {}: {}{}", indent, rule.bold(), message, severity)?;
+        return Ok(());
+    }
+    writeln!(out, "{}• {:?}: {} exceeds {}{}",
+        indent,
+        violation.kind,
+        violation.actual.to_string().red(),
+        violation.threshold.to_string().green(),
+        severity
+    )?;
+    Ok(())
+}
+
+/// [`write_violation`] to stdout, for commands printing as they go
+pub(crate) fn print_violation(indent: &str, violation: &Violation) {
+    let _ = write_violation(&mut io::stdout(), indent, violation);
+}
+
+/// Trend line for god classes in a `--changed-since` run
+fn write_base_comparison(out: &mut dyn Write, result: &AnalysisResult) -> io::Result<()> {
+    let Some(comparison) = result.base_comparison else {
+        return Ok(());
+    };
+    let trend = match comparison.trend {
+        Trend::New => "new".yellow(),
+        Trend::Better => "better".green(),
+        Trend::Worse => "worse".red(),
+        Trend::Unchanged => "unchanged".normal(),
+    };
+
+    writeln!(out, "     Since base: {} (complexity {:+}, methods {:+}, lines {:+})",
+        trend,
+        comparison.complexity_delta,
+        comparison.methods_delta,
+        comparison.lines_delta
+    )?;
+    Ok(())
+}
+
+/// Age line for results annotated by `--age-weighting`
+fn write_code_age(out: &mut dyn Write, result: &AnalysisResult) -> io::Result<()> {
+    let Some(age) = result.code_age else {
+        return Ok(());
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let trend = if age.is_growing() { "growing".red() } else { "stable".green() };
+
+    writeln!(out, "     Age: {} days | Changed in last {} days: {:.0}% ({})",
+        age.age_days(now),
+        age.window_days,
+        age.recent_share * 100.0,
+        trend
+    )?;
+    Ok(())
+}
+
+/// Headline counts shared by the built-in and templated reports
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummary {
+    pub total_classes: usize,
+    pub god_classes: usize,
+    pub classes_with_god_methods: usize,
+    pub low_cohesion_classes: usize,
+    /// Too many fields or dependencies
+    pub oversized_classes: usize,
+    pub healthy_classes: usize,
+    /// Simple names declared by more than one class
+    pub duplicate_class_names: usize,
+}
+
+impl ReportSummary {
+    pub fn from_results(results: &[AnalysisResult]) -> Self {
+        Self {
+            total_classes: results.len(),
+            god_classes: results.iter().filter(|r| r.is_god_class).count(),
+            classes_with_god_methods: results.iter().filter(|r| !r.god_methods.is_empty()).count(),
+            low_cohesion_classes: results.iter().filter(|r| is_low_cohesion(r)).count(),
+            oversized_classes: results.iter().filter(|r| is_oversized(r)).count(),
+            healthy_classes: results.iter().filter(|r| !r.has_issues()).count(),
+            duplicate_class_names: duplicate_class_names(results).len(),
+        }
+    }
+}
+
+/// Every definition of each clashing class name, sorted by name then location
+pub fn duplicate_class_names(results: &[AnalysisResult]) -> BTreeMap<Arc<str>, Vec<ClassLocation>> {
+    let mut groups: BTreeMap<Arc<str>, Vec<ClassLocation>> = BTreeMap::new();
+    for result in results.iter().filter(|r| !r.name_clashes.is_empty()) {
+        let metrics = &result.class_metrics;
+        let own = ClassLocation {
+            qualified_name: metrics.fully_qualified_name.clone(),
+            file_path: metrics.file_path.clone(),
+            span: metrics.span,
+        };
+
+        let group = groups.entry(metrics.name.clone()).or_default();
+        for location in std::iter::once(own).chain(result.name_clashes.iter().cloned()) {
+            if !group.contains(&location) {
+                group.push(location);
+            }
+        }
+    }
+
+    for group in groups.values_mut() {
+        group.sort_by(|a, b| (&a.file_path, a.span.start).cmp(&(&b.file_path, b.span.start)));
+    }
+    groups
+}
+
+fn is_low_cohesion(result: &AnalysisResult) -> bool {
+    result.violations.iter().any(|v| v.kind == ViolationKind::LowCohesion)
+}
+
+fn is_oversize(violation: &Violation) -> bool {
+    matches!(violation.kind, ViolationKind::FieldCount | ViolationKind::DependencyCount)
+}
+
+fn is_oversized(result: &AnalysisResult) -> bool {
+    result.violations.iter().any(is_oversize)
+}
+
+fn breaks_rules(result: &AnalysisResult) -> bool {
+    result.violations.iter().any(|v| v.kind == ViolationKind::Rule)
+}
 
 pub struct ReportGenerator {
     thresholds: Thresholds,
@@ -12,65 +224,110 @@ impl ReportGenerator {
         Self { thresholds }
     }
 
-    pub fn print_text_report(&self, results: &[AnalysisResult], verbose: bool) {
-        let total_classes = results.len();
+    pub fn thresholds(&self) -> &Thresholds {
+        &self.thresholds
+    }
+
+    /// The counts heading every text report
+    pub fn write_summary(&self, out: &mut dyn Write, summary: &ReportSummary) -> io::Result<()> {
+        writeln!(out, "{}", "SUMMARY:".bright_green().bold())?;
+        writeln!(out)?;
+        writeln!(out, "  {} {}", "Total Classes:".bold(), summary.total_classes)?;
+        writeln!(out, "  {} {}", "God Classes:".bold(), summary.god_classes.to_string().red())?;
+        writeln!(out, "  {} {}", "Classes with God Methods:".bold(), summary.classes_with_god_methods.to_string().yellow())?;
+        writeln!(out, "  {} {}", "Low Cohesion Classes:".bold(), summary.low_cohesion_classes.to_string().yellow())?;
+        writeln!(out, "  {} {}", "Too Many Fields/Dependencies:".bold(), summary.oversized_classes.to_string().yellow())?;
+        writeln!(out, "  {} {}", "Healthy Classes:".bold(), summary.healthy_classes.to_string().green())?;
+        writeln!(out, "  {} {}", "Duplicate Class Names:".bold(), summary.duplicate_class_names.to_string().yellow())?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Summary counts, then a section per kind of finding for `results`; `summary` may
+    /// cover more classes than `results` (`--top`, `--summary`)
+    pub fn write_text_report(
+        &self,
+        out: &mut dyn Write,
+        summary: &ReportSummary,
+        results: &[AnalysisResult],
+        verbose: bool,
+    ) -> io::Result<()> {
         let god_classes: Vec<_> = results.iter().filter(|r| r.is_god_class).collect();
         let classes_with_god_methods: Vec<_> = results
             .iter()
             .filter(|r| !r.god_methods.is_empty())
             .collect();
-        let healthy_classes = results.iter().filter(|r| !r.has_issues()).count();
+        let low_cohesion: Vec<_> = results.iter().filter(|r| is_low_cohesion(r)).collect();
+        let oversized: Vec<_> = results.iter().filter(|r| is_oversized(r)).collect();
 
-        // Summary
-        println!("{}", "SUMMARY:".bright_green().bold());
-        println!();
-        println!("  {} {}", "Total Classes:".bold(), total_classes);
-        println!("  {} {}", "God Classes:".bold(), god_classes.len().to_string().red());
-        println!("  {} {}", "Classes with God Methods:".bold(), classes_with_god_methods.len().to_string().yellow());
-        println!("  {} {}", "Healthy Classes:".bold(), healthy_classes.to_string().green());
-        println!();
+        self.write_summary(out, summary)?;
+
+        let flagged = summary.total_classes - summary.healthy_classes;
+        let shown = results.iter().filter(|r| r.has_issues()).count();
+        if shown > 0 && shown < flagged {
+            writeln!(out, "{}", format!("Showing the {} worst of {} flagged classes", shown, flagged).dimmed())?;
+            writeln!(out)?;
+        }
 
         // God classes
         if !god_classes.is_empty() {
-            println!("{}", "⚠️  GOD CLASSES DETECTED:".red().bold());
-            println!();
+            writeln!(out, "{}", "⚠️  GOD CLASSES DETECTED:".red().bold())?;
+            writeln!(out)?;
 
             for result in &god_classes {
                 let metrics = &result.class_metrics;
-                println!("  {} {}", "❌".red(), metrics.name.bright_red().bold());
-                println!("     File: {}", metrics.file_path);
-                println!("     Lines: {} | Methods: {} | Complexity: {}",
+                // Framework profiles may have loosened the limits for this class
+                let role = metrics
+                    .entry_point
+                    .map(|e| format!(" [{:?}]", e).dimmed().to_string())
+                    .unwrap_or_default();
+                writeln!(out, "  {} {}{}", "❌".red(), metrics.name.bright_red().bold(), role)?;
+                writeln!(out, "     File: {}", metrics.file_path)?;
+                write_metadata(out, "     ", &result.metadata)?;
+                writeln!(out, "     Lines: {} | Methods: {} | Complexity: {}",
                     metrics.lines.0.to_string().yellow(),
                     metrics.method_count.0.to_string().yellow(),
                     metrics.complexity.0.to_string().yellow()
-                );
+                )?;
+                write_coupling(out, "     ", result.coupling)?;
+                if verbose {
+                    for violation in result.god_class_violations.iter() {
+                        write_violation(out, "     ", violation)?;
+                    }
+                }
+                write_base_comparison(out, result)?;
+                write_code_age(out, result)?;
+                write_owners(out, "     ", &result.owners)?;
 
                 if !result.suggested_extractions.is_empty() {
-                    println!("     {} {}", "Suggested Extractions:".cyan(), result.suggested_extractions.len());
+                    writeln!(out, "     {} {}", "Suggested Extractions:".cyan(), result.suggested_extractions.len())?;
                     
                     if verbose {
+                        if let Some(stats) = &result.cluster_stats {
+                            write_cluster_stats(out, stats)?;
+                        }
                         for cluster in result.suggested_extractions.iter() {
-                            println!("       → {} (cohesion: {:.2})",
+                            writeln!(out, "       → {} (cohesion: {:.2})",
                                 cluster.suggested_name.bright_cyan(),
                                 cluster.cohesion_score
-                            );
-                            println!("         Methods: {}", cluster.methods.len());
+                            )?;
+                            writeln!(out, "         Methods: {}", cluster.methods.len())?;
                         }
                     }
                 }
 
                 if !result.god_methods.is_empty() {
-                    println!("     {} {}", "God Methods:".yellow(), result.god_methods.len());
+                    writeln!(out, "     {} {}", "God Methods:".yellow(), result.god_methods.len())?;
                 }
 
-                println!();
+                writeln!(out)?;
             }
         }
 
         // Classes with god methods
         if !classes_with_god_methods.is_empty() {
-            println!("{}", "⚠️  GOD METHODS DETECTED:".yellow().bold());
-            println!();
+            writeln!(out, "{}", "⚠️  GOD METHODS DETECTED:".yellow().bold())?;
+            writeln!(out)?;
 
             for result in &classes_with_god_methods {
                 if result.is_god_class {
@@ -78,39 +335,252 @@ impl ReportGenerator {
                 }
 
                 let metrics = &result.class_metrics;
-                println!("  {} {}", "📝".yellow(), metrics.name.bright_yellow());
-                println!("     File: {}", metrics.file_path);
-                println!("     {} {}", "God Methods:".bold(), result.god_methods.len());
-                println!();
+                writeln!(out, "  {} {}", "📝".yellow(), metrics.name.bright_yellow())?;
+                writeln!(out, "     File: {}", metrics.file_path)?;
+                write_metadata(out, "     ", &result.metadata)?;
+                writeln!(out, "     {} {}", "God Methods:".bold(), result.god_methods.len())?;
+                write_coupling(out, "     ", result.coupling)?;
+                write_code_age(out, result)?;
+                write_owners(out, "     ", &result.owners)?;
+                writeln!(out)?;
 
                 if verbose {
                     for god_method in result.god_methods.iter() {
-                        println!("       ⚠️  {}", god_method.method_name.yellow());
-                        println!("          Lines: {} | Complexity: {} | Parameters: {}",
+                        writeln!(out, "       ⚠️  {}", god_method.method_name.yellow())?;
+                        writeln!(out, "          Lines: {} | Complexity: {} | Parameters: {} | Nesting: {} | Chain: {}",
                             god_method.metrics.lines.0,
                             god_method.metrics.complexity.0,
-                            god_method.metrics.parameters.0
-                        );
+                            god_method.metrics.parameters.0,
+                            god_method.metrics.nesting_depth.0,
+                            god_method.metrics.chain_length.0
+                        )?;
+                        write_owners(out, "          ", &god_method.owners)?;
 
                         for violation in god_method.violations.iter() {
-                            println!("          • {:?}: {} exceeds {}",
-                                violation.kind,
-                                violation.actual.to_string().red(),
-                                violation.threshold.to_string().green()
-                            );
+                            write_violation(out, "          ", violation)?;
                         }
-                        println!();
+                        writeln!(out)?;
                     }
                 }
             }
         }
 
+        // Low cohesion classes
+        if !low_cohesion.is_empty() {
+            writeln!(out, "{}", "⚠️  LOW COHESION DETECTED:".yellow().bold())?;
+            writeln!(out)?;
+
+            for result in &low_cohesion {
+                let metrics = &result.class_metrics;
+                writeln!(out, "  {} {}", "🧩".yellow(), metrics.name.bright_yellow())?;
+                writeln!(out, "     File: {}", metrics.file_path)?;
+                write_metadata(out, "     ", &result.metadata)?;
+                writeln!(out, "     LCOM4: {} (max {})",
+                    result.lcom4.unwrap_or_default().to_string().red(),
+                    self.thresholds.max_lcom4.to_string().green()
+                )?;
+                write_coupling(out, "     ", result.coupling)?;
+                writeln!(out)?;
+            }
+        }
+
+        // Data blobs and high fan-out classes
+        if !oversized.is_empty() {
+            writeln!(out, "{}", "⚠️  TOO MANY FIELDS / DEPENDENCIES:".yellow().bold())?;
+            writeln!(out)?;
+
+            for result in &oversized {
+                let metrics = &result.class_metrics;
+                writeln!(out, "  {} {}", "📦".yellow(), metrics.name.bright_yellow())?;
+                writeln!(out, "     File: {}", metrics.file_path)?;
+                write_metadata(out, "     ", &result.metadata)?;
+                for violation in result.violations.iter().filter(|v| is_oversize(v)) {
+                    write_violation(out, "     ", violation)?;
+                }
+                write_coupling(out, "     ", result.coupling)?;
+                writeln!(out)?;
+            }
+        }
+
+        // Findings of the rules in .dei/rules/
+        let rule_breakers: Vec<_> = results.iter().filter(|r| breaks_rules(r)).collect();
+        if !rule_breakers.is_empty() {
+            writeln!(out, "{}", "⚠️  CUSTOM RULES:".yellow().bold())?;
+            writeln!(out)?;
+
+            for result in &rule_breakers {
+                let metrics = &result.class_metrics;
+                writeln!(out, "  {} {}", "📜".yellow(), metrics.name.bright_yellow())?;
+                writeln!(out, "     File: {}", metrics.file_path)?;
+                write_metadata(out, "     ", &result.metadata)?;
+                for violation in result.violations.iter().filter(|v| v.kind == ViolationKind::Rule) {
+                    write_violation(out, "     ", violation)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        // Same simple name declared in several places
+        let duplicates = duplicate_class_names(results);
+        if !duplicates.is_empty() {
+            writeln!(out, "{}", "⚠️  DUPLICATE CLASS NAMES:".yellow().bold())?;
+            writeln!(out)?;
+
+            for (name, locations) in &duplicates {
+                writeln!(out, "  {} {} ({} definitions)", "🪞".yellow(), name.bright_yellow(), locations.len())?;
+                for location in locations {
+                    writeln!(out, "     {}:{} ({})", location.file_path, location.span.start, location.qualified_name)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
         // Success message
-        if god_classes.is_empty() && classes_with_god_methods.is_empty() {
-            println!("{}", "✅ No god classes or methods detected!".green().bold());
-            println!("{}", "   Your code is well-structured.".green());
-            println!();
+        if summary.healthy_classes == summary.total_classes {
+            writeln!(out, "{}", "✅ No god classes or methods detected!".green().bold())?;
+            writeln!(out, "{}", "   Your code is well-structured.".green())?;
+            writeln!(out)?;
         }
+        Ok(())
+    }
+    /// Vendored code analyzed with `--include-vendored`; informational only
+    pub fn write_external_report(&self, out: &mut dyn Write, results: &[AnalysisResult], verbose: bool) -> io::Result<()> {
+        if results.is_empty() {
+            return Ok(());
+        }
+        let summary = ReportSummary::from_results(results);
+
+        writeln!(out, "{}", "📦 EXTERNAL CODE (vendored, not gated):".bright_blue().bold())?;
+        writeln!(out)?;
+        writeln!(out, "  {} {}", "Total Classes:".bold(), summary.total_classes)?;
+        writeln!(out, "  {} {}", "God Classes:".bold(), summary.god_classes)?;
+        writeln!(out, "  {} {}", "Classes with God Methods:".bold(), summary.classes_with_god_methods)?;
+        writeln!(out, "  {} {}", "Healthy Classes:".bold(), summary.healthy_classes)?;
+        writeln!(out)?;
+
+        if verbose {
+            for result in results.iter().filter(|r| r.has_issues()) {
+                writeln!(out, "  {} {}", "•".bright_blue(), result.class_metrics.name.bold())?;
+                writeln!(out, "     File: {}", result.class_metrics.file_path)?;
+                writeln!(out, "     {}", result.summary)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Files left out because they couldn't be read or parsed
+    pub fn write_skipped_files(&self, out: &mut dyn Write, errors: &[FileError]) -> io::Result<()> {
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}", "⏭️  SKIPPED FILES (--strict to fail instead):".yellow().bold())?;
+        writeln!(out)?;
+        for error in errors {
+            writeln!(out, "  {} {}", "•".yellow(), error.file_path)?;
+            writeln!(out, "     {}", error.message.dimmed())?;
+        }
+        writeln!(out)
     }
-}
 
+    /// Files analyzed with a caveat, such as a lossy transcoding
+    pub fn write_file_warnings(&self, out: &mut dyn Write, warnings: &[FileWarning]) -> io::Result<()> {
+        if warnings.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}", "⚠️  FILE WARNINGS:".yellow().bold())?;
+        writeln!(out)?;
+        for warning in warnings {
+            writeln!(out, "  {} {}", "•".yellow(), warning.file_path)?;
+            writeln!(out, "     {}", warning.message.dimmed())?;
+        }
+        writeln!(out)
+    }
+
+    /// Files declaring too many classes or grown past the file-level limits
+    pub fn write_god_files(&self, out: &mut dyn Write, results: &[GodFileResult]) -> io::Result<()> {
+        if results.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}", "⚠️  GOD FILES DETECTED:".red().bold())?;
+        writeln!(out)?;
+
+        for result in results {
+            writeln!(out, "  {} {}", "🗂️".red(), result.file_path.bright_red())?;
+            writeln!(out, "     Classes: {} | Lines: {}",
+                result.class_count.to_string().yellow(),
+                result.total_lines.to_string().yellow()
+            )?;
+            for violation in result.violations.iter() {
+                write_violation(out, "     ", violation)?;
+            }
+            if !result.class_names.is_empty() {
+                let names: Vec<&str> = result.class_names.iter().map(|n| n.as_ref()).collect();
+                writeln!(out, "     Declares: {}", names.join(", "))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Files over the opt-in wildcard import and imported-name limits
+    pub fn write_import_hygiene(&self, out: &mut dyn Write, results: &[ImportHygieneResult]) -> io::Result<()> {
+        if results.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}", "⚠️  NAMESPACE POLLUTION:".yellow().bold())?;
+        writeln!(out)?;
+
+        for result in results {
+            writeln!(out, "  {} {}", "🌐".yellow(), result.file_path.bright_yellow())?;
+            for violation in result.violations.iter() {
+                write_violation(out, "     ", violation)?;
+            }
+            if !result.wildcard_imports.is_empty() {
+                let modules: Vec<&str> = result.wildcard_imports.iter().map(|m| m.as_ref()).collect();
+                writeln!(out, "     Wildcards: {}", modules.join(", "))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Opt-in section naming who shaped the flagged code, for targeted coaching
+    pub fn write_contributors(&self, out: &mut dyn Write, attributions: &[Attribution], verbose: bool) -> io::Result<()> {
+        let contributors = top_contributors(attributions);
+        if contributors.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}", "👥 TOP CONTRIBUTORS TO COMPLEXITY:".bright_cyan().bold())?;
+        writeln!(out)?;
+
+        for contributor in &contributors {
+            writeln!(out, "  {} <{}>", contributor.name.bold(), contributor.email)?;
+            writeln!(out, "     Flagged: {} | Lines in flagged code: {}",
+                contributor.flagged.to_string().yellow(),
+                contributor.lines.to_string().yellow()
+            )?;
+
+            if verbose {
+                for attribution in attributions
+                    .iter()
+                    .filter(|a| a.authors.iter().any(|author| author.email == contributor.email))
+                {
+                    writeln!(out, "       → {} ({}:{}-{})",
+                        attribution.subject,
+                        attribution.file_path,
+                        attribution.span.start,
+                        attribution.span.end
+                    )?;
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
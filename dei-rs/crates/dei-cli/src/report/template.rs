@@ -0,0 +1,92 @@
+//! User-supplied Tera templates for branded text, Markdown or HTML reports
+//!
+//! Templates see `metadata`, `summary`, `results` and `god_files` (the same objects as
+//! `--format json`), `distribution` (histograms of class lines and method complexity across
+//! every class), `thresholds`, `version` and `generated_at` (Unix seconds). Sibling files
+//! with the same extension are registered too, so `{% include "header.md" %}`
+//! and `{% extends "base.html" %}` work. `.html`/`.xml` templates are autoescaped.
+//!
+//! `--format html` uses the same context with the bundled `templates/report.html`.
+
+use anyhow::{Context as _, Result};
+use dei_core::models::{AnalysisResult, GodFileResult};
+use std::path::{Path, PathBuf};
+use tera::{Context, Tera};
+
+use super::{Histogram, ReportGenerator, ReportMetadata, ReportSummary};
+
+/// Self-contained report with sortable tables and per-class drill-down
+const HTML_REPORT: &str = include_str!("../../templates/report.html");
+
+pub struct TemplateReport {
+    tera: Tera,
+    name: String,
+}
+
+impl TemplateReport {
+    /// Load a template file along with its same-extension siblings
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = template_name(path)?;
+        let mut tera = Tera::default();
+
+        let siblings = sibling_templates(path)?;
+        tera.add_template_files(siblings.iter().map(|p| (p.as_path(), template_name(p).ok())))
+            .with_context(|| format!("Failed to load report template {}", path.display()))?;
+
+        Ok(Self { tera, name })
+    }
+
+    /// The bundled interactive HTML report
+    pub fn html() -> Result<Self> {
+        let name = "report.html".to_string();
+        let mut tera = Tera::default();
+        tera.add_raw_template(&name, HTML_REPORT)
+            .context("Failed to load the bundled HTML report template")?;
+
+        Ok(Self { tera, name })
+    }
+
+    pub fn render(
+        &self,
+        generator: &ReportGenerator,
+        metadata: &ReportMetadata,
+        summary: &ReportSummary,
+        results: &[AnalysisResult],
+        god_files: &[GodFileResult],
+        distribution: &[Histogram],
+    ) -> Result<String> {
+        let mut context = Context::new();
+        context.insert("metadata", metadata);
+        context.insert("summary", summary);
+        context.insert("results", results);
+        context.insert("god_files", god_files);
+        context.insert("distribution", distribution);
+        context.insert("thresholds", generator.thresholds());
+        context.insert("version", metadata.version);
+        context.insert("generated_at", &metadata.generated_at);
+
+        self.tera
+            .render(&self.name, &context)
+            .with_context(|| format!("Failed to render report template '{}'", self.name))
+    }
+}
+
+fn template_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .with_context(|| format!("Not a template file: {}", path.display()))
+}
+
+fn sibling_templates(path: &Path) -> Result<Vec<PathBuf>> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut files = vec![path.to_path_buf()];
+
+    for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let sibling = entry.path();
+        if sibling.is_file() && sibling.extension() == path.extension() && sibling.file_name() != path.file_name() {
+            files.push(sibling);
+        }
+    }
+
+    Ok(files)
+}
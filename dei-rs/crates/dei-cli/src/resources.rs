@@ -0,0 +1,132 @@
+//! How much of the machine an analysis may take
+//!
+//! For shared CI runners: `--threads` sizes the pool files are parsed and analyzed on,
+//! `--max-memory` caps the heap so a runaway analysis aborts instead of waking the OOM
+//! killer for its neighbours, and `--nice` lowers CPU priority and, on Linux, moves disk
+//! reads to the idle I/O class.
+//!
+//! Linux keeps CPU and I/O priority per thread, and a thread starts with its creator's. So
+//! [`init`] must run on the main thread before anything else starts a thread: the async
+//! runtime, the thread pool, the trace exporter. Every worker then inherits the priority;
+//! lowering it afterwards would leave them all at the old one.
+
+use crate::commands::check::parse_size;
+use anyhow::{Context, Result};
+
+/// Niceness under `--nice`, as `nice` without `-n`
+#[cfg(unix)]
+const NICENESS: libc::c_int = 10;
+
+/// Smallest `--max-memory`: below it the async runtime and thread pool can't start their threads
+pub const MIN_MEMORY: u64 = 64 << 20;
+
+/// Parse `--max-memory` like any other size, refusing caps too small to start under
+pub fn parse_memory(text: &str) -> std::result::Result<u64, String> {
+    let bytes = parse_size(text)?;
+    if bytes < MIN_MEMORY {
+        return Err(format!("'{}' is below the minimum of {}M", text.trim(), MIN_MEMORY >> 20));
+    }
+    Ok(bytes)
+}
+
+/// Lower priority, cap memory and size the thread pool as asked; nothing when nothing is
+///
+/// Call before any other thread is started, see the module docs.
+pub fn init(threads: Option<usize>, max_memory: Option<u64>, nice: bool) -> Result<()> {
+    if nice {
+        lower_priority().context("Failed to apply --nice")?;
+    }
+    if let Some(bytes) = max_memory {
+        limit_memory(bytes).context("Failed to apply --max-memory")?;
+    }
+    // Spawns the pool's workers, so only once this thread's priority is where they should start
+    if let Some(threads) = threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .context("Failed to size the thread pool")?;
+    }
+    Ok(())
+}
+
+/// Cap the data segment, which covers the heap and thread stacks but not memory-mapped sources
+#[cfg(unix)]
+fn limit_memory(bytes: u64) -> std::io::Result<()> {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: `limit` is a valid rlimit for the duration of each call
+    if unsafe { libc::getrlimit(libc::RLIMIT_DATA, &mut limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // Only root may raise the hard limit, so a lower one already set stays
+    limit.rlim_cur = limit.rlim_max.min(bytes as libc::rlim_t);
+    if unsafe { libc::setrlimit(libc::RLIMIT_DATA, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn limit_memory(_bytes: u64) -> std::io::Result<()> {
+    eprintln!("dei: --max-memory is not supported on this platform");
+    Ok(())
+}
+
+/// Lower the priority of this thread and every thread it starts
+#[cfg(unix)]
+fn lower_priority() -> std::io::Result<()> {
+    // SAFETY: plain syscall; Linux applies it to the calling thread only, and threads it
+    // starts afterwards inherit it
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, NICENESS) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+        const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+        const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+        // SAFETY: ioprio_set takes only integers
+        let set = unsafe {
+            libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT)
+        };
+        if set != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn lower_priority() -> std::io::Result<()> {
+    eprintln!("dei: --nice is not supported on this platform");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size_units() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512B"), Ok(512));
+        assert_eq!(parse_size("4K"), Ok(4 << 10));
+        assert_eq!(parse_size("10MB"), Ok(10 << 20));
+        assert_eq!(parse_size("10mb"), Ok(10 << 20));
+        assert_eq!(parse_size("2GiB"), Ok(2 << 30));
+        assert_eq!(parse_size(" 1 G "), Ok(1 << 30));
+        assert!(parse_size("10TB").is_err());
+        assert!(parse_size("10MBs").is_err());
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("-1M").is_err());
+        assert!(parse_size("99999999999G").is_err());
+    }
+
+    #[test]
+    fn test_parse_memory_minimum() {
+        assert_eq!(parse_memory("64M"), Ok(MIN_MEMORY));
+        assert_eq!(parse_memory("1GiB"), Ok(1 << 30));
+        assert!(parse_memory("1K").unwrap_err().contains("minimum"));
+        assert!(parse_memory("63MB").is_err());
+        assert!(parse_memory("lots").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+//! Tracing of the analysis pipeline
+//!
+//! Off unless asked for. `--trace` prints every span to stderr as it closes, with the time
+//! it took: building the tree, the parse phase, the analysis phase. `--otlp-endpoint` sends
+//! the same spans to an OpenTelemetry collector over OTLP/HTTP, for Jaeger, Tempo or any other
+//! tracing backend. `RUST_LOG` picks which spans are kept; the phases are at `info`, and
+//! `RUST_LOG=debug` adds a span per file, per class and per clustered class.
+
+use anyhow::Result;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use std::sync::OnceLock;
+use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Exporter of the running process, flushed before it exits
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Install the subscriber for `--trace` and `--otlp-endpoint`; nothing when neither is given
+///
+/// `otlp_endpoint` is the collector's base URL, like `OTEL_EXPORTER_OTLP_ENDPOINT`, so spans
+/// are posted to `/v1/traces` under it.
+pub fn init(trace: bool, otlp_endpoint: Option<&str>) -> Result<()> {
+    if !trace && otlp_endpoint.is_none() {
+        return Ok(());
+    }
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt = trace.then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_span_events(FmtSpan::CLOSE)
+    });
+    let otlp = match otlp_endpoint {
+        Some(endpoint) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+                .build()?;
+            let provider = SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(Resource::builder().with_service_name("dei").build())
+                .build();
+            let tracer = provider.tracer("dei");
+            let _ = PROVIDER.set(provider);
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry().with(filter).with(fmt).with(otlp).try_init()?;
+    Ok(())
+}
+
+/// Send the spans still buffered for the collector
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("dei: couldn't export traces: {}", e);
+        }
+    }
+}
+
+/// [`std::process::exit`], after sending the spans still buffered
+pub fn exit(code: i32) -> ! {
+    shutdown();
+    std::process::exit(code)
+}
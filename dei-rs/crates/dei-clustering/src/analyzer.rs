@@ -5,46 +5,112 @@
 use dei_core::{
     error::Result,
     metrics::ClassMetrics,
-    models::ResponsibilityCluster,
-    thresholds::Thresholds,
+    models::{ClusterStats, Language, ResponsibilityCluster},
+    thresholds::{ClusterSeed, ClusterStrategy, Thresholds},
     traits::ClusterAnalyzer,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
-use crate::hdbscan::DbscanClusterer;
+use crate::{
+    community::MethodGraph,
+    distance::{DistanceMetric, FeatureDistance},
+    embeddings::{self, Embedder},
+    hdbscan::{silhouette_score_with, DbscanClusterer},
+};
+
+/// Methods of one class clustered by default; distances grow with the square of this
+pub const MAX_CLUSTERED_METHODS: usize = 256;
 
 pub struct ClusteringAnalyzer {
     clusterer: DbscanClusterer,
+    metric: DistanceMetric,
+    /// Longest methods clustered per class; the rest stay with the class
+    max_methods: usize,
+    /// Semantic vectors used instead of the TF-IDF features when available
+    embedder: Option<Arc<dyn Embedder>>,
 }
 
 impl ClusteringAnalyzer {
+    /// DBSCAN parameters tuned for each class
     pub fn new() -> Self {
         Self {
             clusterer: DbscanClusterer::default(),
+            metric: DistanceMetric::default(),
+            max_methods: MAX_CLUSTERED_METHODS,
+            embedder: None,
         }
     }
 
+    /// Fixed DBSCAN parameters, skipping the tuning
     pub fn with_params(min_points: usize, tolerance: f64) -> Self {
         Self {
             clusterer: DbscanClusterer::new(min_points, tolerance),
+            metric: DistanceMetric::default(),
+            max_methods: MAX_CLUSTERED_METHODS,
+            embedder: None,
         }
     }
 
-    /// Generate cluster name from common tokens
+    /// Cluster on vectors from `embedder`, falling back to TF-IDF features whenever it
+    /// returns none; Jaccard and structural distances always use the TF-IDF features
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// Cap on the methods clustered per class, [`MAX_CLUSTERED_METHODS`] unless set
+    pub fn with_max_methods(mut self, max_methods: usize) -> Self {
+        self.max_methods = max_methods;
+        self
+    }
+
+    /// Distance between method feature vectors; Euclidean unless set
+    pub fn with_metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Cluster of hand-picked (or LCOM4-grouped) methods, named and scored like clustered ones
+    pub fn cluster_for(&self, class: &ClassMetrics, method_names: &[Arc<str>]) -> ResponsibilityCluster {
+        let method_indices: Vec<usize> = class
+            .methods
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| method_names.contains(&m.name))
+            .map(|(idx, _)| idx)
+            .collect();
+        let suggested_name = self.generate_cluster_name(&method_indices, &class.methods, class);
+        let justification = self.generate_justification(&method_indices, &class.methods);
+        self.build_cluster(suggested_name, justification, &method_indices, &class.methods)
+    }
+
+    /// Name a cluster after the state it owns, or else its most common tokens
+    ///
+    /// A field counts as the cluster's own when most of the cluster's methods use it and
+    /// most of its uses in the class are from the cluster. Ties are broken alphabetically so
+    /// the same class always yields the same names.
     fn generate_cluster_name(
         &self,
         method_indices: &[usize],
         methods: &[dei_core::metrics::MethodMetrics],
-        original_class: &str,
+        original_class: &ClassMetrics,
     ) -> String {
-        let mut token_freq: HashMap<String, usize> = HashMap::new();
+        // Logic behind a controller or view belongs in a service named after its resource
+        if let Some(entry_point) = original_class.entry_point.filter(|e| e.is_request_handler()) {
+            return format!("{}Service", entry_point.stem(&original_class.name));
+        }
 
-        for &idx in method_indices {
-            if let Some(method) = methods.get(idx) {
-                for token in method.tokens.iter() {
-                    *token_freq.entry(token.to_string()).or_insert(0) += 1;
-                }
+        let cluster: Vec<&dei_core::metrics::MethodMetrics> =
+            method_indices.iter().filter_map(|&idx| methods.get(idx)).collect();
+        if let Some(field) = owned_field(&cluster, &original_class.methods) {
+            return format!("{}Service", pascal_case(field));
+        }
+
+        let mut token_freq: BTreeMap<&str, usize> = BTreeMap::new();
+        for method in &cluster {
+            for token in method.tokens.iter() {
+                *token_freq.entry(token.as_ref()).or_insert(0) += 1;
             }
         }
 
@@ -56,18 +122,19 @@ impl ClusteringAnalyzer {
 
         let mut filtered: Vec<_> = token_freq
             .into_iter()
-            .filter(|(token, _)| !common_words.contains(&token.as_str()) && token.len() > 2)
+            .filter(|(token, _)| !common_words.contains(token) && token.len() > 2)
             .collect();
+        // Stable sort keeps the alphabetical order among equally common tokens
         filtered.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
-        
+
         let top_tokens: Vec<String> = filtered
             .into_iter()
             .take(2)
-            .map(|(token, _)| capitalize_first(&token))
+            .map(|(token, _)| capitalize_first(token))
             .collect();
 
         if top_tokens.is_empty() {
-            format!("{}Component", original_class)
+            format!("{}Component", original_class.name)
         } else {
             format!("{}Service", top_tokens.join(""))
         }
@@ -110,6 +177,27 @@ impl ClusteringAnalyzer {
         }
     }
 
+    fn build_cluster(
+        &self,
+        suggested_name: String,
+        justification: String,
+        method_indices: &[usize],
+        methods: &[dei_core::metrics::MethodMetrics],
+    ) -> ResponsibilityCluster {
+        let method_names: Arc<[Arc<str>]> = method_indices
+            .iter()
+            .filter_map(|&idx| methods.get(idx).map(|m| m.name.clone()))
+            .collect();
+
+        ResponsibilityCluster {
+            suggested_name: suggested_name.into(),
+            methods: method_names,
+            cohesion_score: self.calculate_cohesion(method_indices, methods),
+            shared_dependencies: Arc::new([]),
+            justification: justification.into(),
+        }
+    }
+
     /// Generate justification text for a cluster
     fn generate_justification(
         &self,
@@ -132,68 +220,121 @@ impl ClusteringAnalyzer {
     }
 }
 
-impl ClusterAnalyzer for ClusteringAnalyzer {
-    fn analyze(
+impl ClusteringAnalyzer {
+    /// Clusters of `class` and how cleanly its free (unseeded) methods split into them;
+    /// no stats when the class has too few methods to cluster
+    ///
+    /// Only the longest `max_methods` free methods are clustered, keeping the feature
+    /// matrix of a huge class small enough to cluster quickly.
+    pub fn analyze_with_stats(
         &self,
         class: &ClassMetrics,
         thresholds: &Thresholds,
-    ) -> Result<Vec<ResponsibilityCluster>> {
-        let methods: Vec<_> = class.methods.iter().cloned().collect();
+    ) -> Result<(Vec<ResponsibilityCluster>, Option<ClusterStats>)> {
+        let _span = tracing::debug_span!("cluster", class = %class.name, methods = class.methods.len()).entered();
+        // Trivial accessors and boilerplate only add noise to clusters
+        let min_lines = thresholds.min_method_lines_for(Language::from_path(class.file_path.as_ref()));
+        let methods: Vec<_> = class
+            .methods
+            .iter()
+            .filter(|m| m.lines >= min_lines)
+            .cloned()
+            .collect();
 
         if methods.len() < thresholds.min_cluster_size {
-            return Ok(Vec::new());
+            return Ok((Vec::new(), None));
         }
 
-        // Build feature matrix
-        // TODO: Implement embeddings module
-        // let (features, _vocab) = embeddings::build_feature_matrix(&methods);
-        let _features = ndarray::Array2::<f64>::zeros((methods.len(), 10)); // Placeholder
+        // User-pinned seeds take precedence; seeded methods never enter automatic clustering,
+        // and a seed matching fewer than `min_cluster_size` of them leaves them with the class
+        let (mut seeded, mut free) = partition_by_seeds(&methods, &thresholds.cluster_seeds);
+        seeded.retain(|(_, indices)| indices.len() >= thresholds.min_cluster_size);
+        if free.len() > self.max_methods {
+            // The longest methods carry most of the responsibilities worth extracting
+            free.sort_by_key(|&idx| std::cmp::Reverse(methods[idx].lines));
+            free.truncate(self.max_methods);
+            free.sort_unstable();
+        }
 
-        // Perform clustering
-        // let labels = self.clusterer.cluster(&features);
-        let labels: Vec<Option<usize>> = vec![Some(0); methods.len()]; // Placeholder - all in one cluster
+        let mut result = Vec::new();
+        for (seed, method_indices) in seeded {
+            let justification = format!(
+                "Pinned by seed '{}': {}",
+                seed.name,
+                self.generate_justification(&method_indices, &methods)
+            );
+            result.push(self.build_cluster(
+                seed.name.to_string(),
+                justification,
+                &method_indices,
+                &methods,
+            ));
+        }
+
+        // Perform clustering; noise methods stay with the class
+        let free_methods: Vec<_> = free.iter().map(|&idx| methods[idx].clone()).collect();
+        let (features, vocab) = embeddings::build_feature_matrix(&free_methods);
+        let distance = FeatureDistance::new(self.metric, &vocab);
+        // Token and structural overlap need the TF-IDF columns; dense vectors have none
+        let features = match (&self.embedder, self.metric) {
+            (Some(embedder), DistanceMetric::Euclidean | DistanceMetric::Cosine) => embedder
+                .embed(&free_methods)
+                .filter(|embedded| embedded.nrows() == free_methods.len())
+                .unwrap_or(features),
+            _ => features,
+        };
+        let mut labels = match thresholds.cluster_strategy {
+            ClusterStrategy::Tokens => self.clusterer.cluster_with(&features, &distance),
+            ClusterStrategy::CallGraph => MethodGraph::build(&free_methods).communities(),
+        };
+
+        // Clusters too small to suggest stay with the class, like noise
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        for label in labels.iter().flatten() {
+            *sizes.entry(*label).or_default() += 1;
+        }
+        for label in labels.iter_mut() {
+            if label.is_some_and(|l| sizes[&l] < thresholds.min_cluster_size) {
+                *label = None;
+            }
+        }
 
         // Group methods by cluster
-        let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
-        for (idx, label) in labels.iter().enumerate() {
+        let mut clusters: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (&idx, label) in free.iter().zip(labels.iter()) {
             if let Some(cluster_id) = label {
                 clusters.entry(*cluster_id).or_default().push(idx);
             }
         }
 
         // Create responsibility clusters
-        let mut result = Vec::new();
         for (_, method_indices) in clusters {
-            if method_indices.len() < thresholds.min_cluster_size {
-                continue;
-            }
 
             let suggested_name = self.generate_cluster_name(
                 &method_indices,
                 &methods,
-                class.name.as_ref(),
+                class,
             );
-
-            let cohesion = self.calculate_cohesion(&method_indices, &methods);
             let justification = self.generate_justification(&method_indices, &methods);
 
-            let method_names: Arc<[Arc<str>]> = method_indices
-                .iter()
-                .filter_map(|&idx| methods.get(idx).map(|m| m.name.clone()))
-                .collect();
-
-            let shared_deps: Arc<[Arc<str>]> = Arc::new([]);
-
-            result.push(ResponsibilityCluster {
-                suggested_name: suggested_name.into(),
-                methods: method_names,
-                cohesion_score: cohesion,
-                shared_dependencies: shared_deps,
-                justification: justification.into(),
-            });
+            result.push(self.build_cluster(suggested_name, justification, &method_indices, &methods));
         }
 
-        Ok(result)
+        let stats = ClusterStats {
+            silhouette: silhouette_score_with(&features, &labels, &distance),
+            ..ClusterStats::from_labels(&labels)
+        };
+        Ok((result, Some(stats)))
+    }
+}
+
+impl ClusterAnalyzer for ClusteringAnalyzer {
+    fn analyze(
+        &self,
+        class: &ClassMetrics,
+        thresholds: &Thresholds,
+    ) -> Result<Vec<ResponsibilityCluster>> {
+        self.analyze_with_stats(class, thresholds).map(|(clusters, _)| clusters)
     }
 }
 
@@ -203,6 +344,75 @@ impl Default for ClusteringAnalyzer {
     }
 }
 
+/// Split method indices into user-seeded groups (first matching seed wins) and the rest
+fn partition_by_seeds<'a>(
+    methods: &[dei_core::metrics::MethodMetrics],
+    seeds: &'a [ClusterSeed],
+) -> (Vec<(&'a ClusterSeed, Vec<usize>)>, Vec<usize>) {
+    let mut seeded: Vec<(&ClusterSeed, Vec<usize>)> = seeds.iter().map(|s| (s, Vec::new())).collect();
+    let mut free = Vec::new();
+
+    for (idx, method) in methods.iter().enumerate() {
+        match seeded.iter_mut().find(|(seed, _)| seed.matches(&method.name)) {
+            Some((_, indices)) => indices.push(idx),
+            None => free.push(idx),
+        }
+    }
+
+    seeded.retain(|(_, indices)| !indices.is_empty());
+    (seeded, free)
+}
+
+/// Field most of the cluster uses and the rest of the class mostly doesn't, if any
+fn owned_field<'a>(
+    cluster: &[&'a dei_core::metrics::MethodMetrics],
+    class_methods: &[dei_core::metrics::MethodMetrics],
+) -> Option<&'a str> {
+    let mut inside: BTreeMap<&str, usize> = BTreeMap::new();
+    for method in cluster {
+        for field in method.accessed_fields.iter().map(|f| f.as_ref()).collect::<BTreeSet<_>>() {
+            *inside.entry(field).or_default() += 1;
+        }
+    }
+    let total = |field: &str| class_methods.iter().filter(|m| m.accessed_fields.iter().any(|f| f.as_ref() == field)).count();
+
+    let mut owned: Vec<(&str, usize)> = inside
+        .into_iter()
+        .filter(|&(field, count)| 2 * count >= cluster.len() && 2 * count > total(field))
+        .collect();
+    // Stable sort keeps the alphabetical order among equally used fields
+    owned.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    owned.first().map(|&(field, _)| field).filter(|field| !pascal_case(field).is_empty())
+}
+
+/// `email_queue`, `_pendingOrders` or `m_sessions` → `EmailQueue`, `PendingOrder`, `Session`
+fn pascal_case(field: &str) -> String {
+    let field = field.trim_start_matches('_');
+    let field = field.strip_prefix("m_").unwrap_or(field);
+
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for c in field.chars() {
+        if (!c.is_alphanumeric() || c.is_uppercase()) && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_alphanumeric() {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    // A collection field names its elements' service
+    if let Some(last) = words.last_mut() {
+        if last.len() > 3 && last.ends_with('s') && !last.ends_with("ss") {
+            last.pop();
+        }
+    }
+    words.iter().map(|w| capitalize_first(&w.to_lowercase())).collect()
+}
+
 fn capitalize_first(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
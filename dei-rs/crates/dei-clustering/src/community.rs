@@ -0,0 +1,144 @@
+//! Community detection over the methods of a class
+//!
+//! Methods are joined by two kinds of edges:
+//! - shared field access, weighted by the cosine overlap of their field sets
+//! - calls between them, weighted down by how many siblings call the same method, so a
+//!   logging or validation helper everybody calls doesn't glue the class back together
+//!
+//! Weighted label propagation then lets each method adopt the label most strongly held by
+//! its neighbours until nothing changes. Methods are visited in declaration order and ties
+//! go to the current label, then the smallest, so results are deterministic.
+
+use dei_core::metrics::MethodMetrics;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Rounds of label propagation before giving up on convergence
+const MAX_ROUNDS: usize = 100;
+
+/// Undirected weighted graph with one node per method
+#[derive(Debug, Clone, Default)]
+pub struct MethodGraph {
+    /// Neighbours and edge weights of each node
+    adjacency: Vec<BTreeMap<usize, f64>>,
+}
+
+impl MethodGraph {
+    /// Graph of `methods` joined by shared fields and calls among themselves
+    pub fn build(methods: &[MethodMetrics]) -> Self {
+        let n = methods.len();
+        let mut graph = Self {
+            adjacency: vec![BTreeMap::new(); n],
+        };
+
+        let fields: Vec<HashSet<&str>> = methods
+            .iter()
+            .map(|m| m.accessed_fields.iter().map(|f| f.as_ref()).collect())
+            .collect();
+        for i in 0..n {
+            for j in i + 1..n {
+                let shared = fields[i].intersection(&fields[j]).count();
+                if shared > 0 {
+                    let overlap = shared as f64 / ((fields[i].len() * fields[j].len()) as f64).sqrt();
+                    graph.connect(i, j, overlap);
+                }
+            }
+        }
+
+        let index: HashMap<&str, usize> = methods.iter().enumerate().map(|(i, m)| (m.name.as_ref(), i)).collect();
+        let callees: Vec<HashSet<usize>> = methods
+            .iter()
+            .enumerate()
+            .map(|(caller, m)| {
+                m.called_methods
+                    .iter()
+                    .filter_map(|call| index.get(own_call(call)).copied())
+                    .filter(|&callee| callee != caller)
+                    .collect()
+            })
+            .collect();
+        let mut callers = vec![0usize; n];
+        for &callee in callees.iter().flatten() {
+            callers[callee] += 1;
+        }
+        for (caller, called) in callees.iter().enumerate() {
+            for &callee in called {
+                graph.connect(caller, callee, 1.0 / callers[callee] as f64);
+            }
+        }
+
+        graph
+    }
+
+    pub fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacency.is_empty()
+    }
+
+    /// Weight of the edge between two methods, 0 when they aren't connected
+    pub fn weight(&self, a: usize, b: usize) -> f64 {
+        self.adjacency.get(a).and_then(|n| n.get(&b)).copied().unwrap_or_default()
+    }
+
+    fn connect(&mut self, a: usize, b: usize, weight: f64) {
+        *self.adjacency[a].entry(b).or_default() += weight;
+        *self.adjacency[b].entry(a).or_default() += weight;
+    }
+
+    /// Community of each method, numbered from 0 in order of first member;
+    /// methods left on their own are `None`, like DBSCAN noise
+    pub fn communities(&self) -> Vec<Option<usize>> {
+        let mut labels: Vec<usize> = (0..self.len()).collect();
+
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for node in 0..self.len() {
+                let mut strength: BTreeMap<usize, f64> = BTreeMap::new();
+                for (&neighbour, &weight) in &self.adjacency[node] {
+                    *strength.entry(labels[neighbour]).or_default() += weight;
+                }
+                let Some(best) = strength.values().copied().max_by(f64::total_cmp) else {
+                    continue;
+                };
+                let current = labels[node];
+                if strength.get(&current).is_some_and(|&s| s >= best) {
+                    continue;
+                }
+                // BTreeMap order makes the first best label the smallest
+                if let Some((&label, _)) = strength.iter().find(|(_, &s)| s >= best) {
+                    labels[node] = label;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        for &label in &labels {
+            *sizes.entry(label).or_default() += 1;
+        }
+        let mut numbering: HashMap<usize, usize> = HashMap::new();
+        labels
+            .iter()
+            .map(|label| {
+                (sizes[label] > 1).then(|| {
+                    let next = numbering.len();
+                    *numbering.entry(*label).or_insert(next)
+                })
+            })
+            .collect()
+    }
+}
+
+/// `self.save`, `this.save` or `save` → `save`; other receivers are left as written
+fn own_call(call: &str) -> &str {
+    call.strip_prefix("self.")
+        .or_else(|| call.strip_prefix("this."))
+        .or_else(|| call.strip_prefix("self::"))
+        .or_else(|| call.strip_prefix("Self::"))
+        .unwrap_or(call)
+}
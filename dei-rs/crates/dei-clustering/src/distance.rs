@@ -0,0 +1,121 @@
+//! Distances between rows of a method feature matrix
+//!
+//! Euclidean distance on sparse, mostly-zero rows is dominated by how many features a
+//! method has rather than which ones it shares, so the alternatives compare what two
+//! methods have in common:
+//! - cosine: the angle between the full rows, ignoring their length
+//! - Jaccard: the share of identifier tokens the two methods have in common
+//! - structural overlap: shared fields and calls, relative to the method using fewer of them
+
+use linfa_nn::distance::Distance;
+use ndarray::{ArrayView, Dimension};
+
+use crate::embeddings::Vocabulary;
+
+/// How far apart two methods are, picked with [`ClusteringAnalyzer::with_metric`]
+///
+/// [`ClusteringAnalyzer::with_metric`]: crate::ClusteringAnalyzer::with_metric
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DistanceMetric {
+    /// Straight-line distance between the weighted feature rows
+    #[default]
+    Euclidean,
+    /// `1 - cos θ` between the feature rows
+    Cosine,
+    /// `1 - |A ∩ B| / |A ∪ B|` over the tokens of each method
+    Jaccard,
+    /// `1 - |A ∩ B| / min(|A|, |B|)` over the fields and calls of each method
+    Structural,
+}
+
+/// A [`DistanceMetric`] bound to the column layout of one feature matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureDistance {
+    metric: DistanceMetric,
+    /// Leading token columns; the field and call columns follow
+    token_columns: usize,
+}
+
+impl FeatureDistance {
+    pub fn new(metric: DistanceMetric, vocabulary: &Vocabulary) -> Self {
+        Self {
+            metric,
+            token_columns: vocabulary.idf.len(),
+        }
+    }
+
+    /// Euclidean distance, which needs no column layout
+    pub fn euclidean() -> Self {
+        Self {
+            metric: DistanceMetric::Euclidean,
+            token_columns: 0,
+        }
+    }
+
+    pub fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    /// Distance between two rows of the same matrix
+    pub fn between<D: Dimension>(&self, a: ArrayView<f64, D>, b: ArrayView<f64, D>) -> f64 {
+        let pairs = a.iter().zip(b.iter());
+        match self.metric {
+            DistanceMetric::Euclidean => pairs.map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt(),
+            DistanceMetric::Cosine => {
+                let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+                for (x, y) in pairs {
+                    dot += x * y;
+                    norm_a += x * x;
+                    norm_b += y * y;
+                }
+                if norm_a == 0.0 && norm_b == 0.0 {
+                    return 0.0;
+                }
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    return 1.0;
+                }
+                (1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())).max(0.0)
+            }
+            DistanceMetric::Jaccard => {
+                let (shared, union) = set_overlap(pairs.take(self.token_columns));
+                if union == 0 {
+                    return 0.0;
+                }
+                1.0 - shared as f64 / union as f64
+            }
+            DistanceMetric::Structural => {
+                let (mut shared, mut in_a, mut in_b) = (0usize, 0usize, 0usize);
+                for (x, y) in pairs.skip(self.token_columns) {
+                    in_a += usize::from(*x != 0.0);
+                    in_b += usize::from(*y != 0.0);
+                    shared += usize::from(*x != 0.0 && *y != 0.0);
+                }
+                match in_a.min(in_b) {
+                    0 if in_a == in_b => 0.0,
+                    0 => 1.0,
+                    smaller => 1.0 - shared as f64 / smaller as f64,
+                }
+            }
+        }
+    }
+}
+
+impl Default for FeatureDistance {
+    fn default() -> Self {
+        Self::euclidean()
+    }
+}
+
+impl Distance<f64> for FeatureDistance {
+    fn distance<D: Dimension>(&self, a: ArrayView<f64, D>, b: ArrayView<f64, D>) -> f64 {
+        self.between(a, b)
+    }
+}
+
+/// Columns set in both rows, and in either
+fn set_overlap<'a>(pairs: impl Iterator<Item = (&'a f64, &'a f64)>) -> (usize, usize) {
+    pairs.fold((0, 0), |(shared, union), (x, y)| {
+        let (x, y) = (*x != 0.0, *y != 0.0);
+        (shared + usize::from(x && y), union + usize::from(x || y))
+    })
+}
@@ -0,0 +1,165 @@
+//! Method feature vectors for clustering
+//!
+//! Each method becomes one row of three blocks, each L2-normalized and then weighted:
+//! - TF-IDF over the identifier tokens of its body, so methods talking about the same
+//!   things end up close
+//! - the fields it accesses, so methods sharing state end up close
+//! - the methods it calls, plus itself, so callers end up close to their callees and to
+//!   other callers of the same methods
+//!
+//! Normalizing per block keeps a method with many tokens from drowning out its field
+//! and call overlap, and keeps Euclidean distances between rows within `[0, 2]`.
+//!
+//! An [`Embedder`] can replace these rows with dense vectors from a semantic model; when
+//! it has nothing to offer, clustering falls back to the features built here.
+
+use dei_core::metrics::MethodMetrics;
+use ndarray::Array2;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Weight of the token block
+pub const TOKEN_WEIGHT: f64 = 0.5;
+/// Weight of the shared-field block
+pub const FIELD_WEIGHT: f64 = 0.3;
+/// Weight of the call-overlap block
+pub const CALL_WEIGHT: f64 = 0.2;
+/// Token columns kept, those used by the most methods; the long tail of tokens only one
+/// or two methods use says little about which methods belong together
+pub const MAX_TOKEN_COLUMNS: usize = 1024;
+
+/// What a column of the feature matrix stands for
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Feature {
+    /// An identifier token, weighted by TF-IDF
+    Token(Arc<str>),
+    /// A field the method reads or writes
+    Field(Arc<str>),
+    /// A method called, or the method itself
+    Call(Arc<str>),
+}
+
+/// Columns of a feature matrix, in order
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    pub features: Vec<Feature>,
+    /// Inverse document frequency of each token column, in column order
+    pub idf: Vec<f64>,
+}
+
+impl Vocabulary {
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Column of `feature`, if any method has it
+    pub fn column(&self, feature: &Feature) -> Option<usize> {
+        self.features.iter().position(|f| f == feature)
+    }
+}
+
+/// Dense method vectors from an external model, e.g. a local sentence-transformer
+///
+/// `None` (no model on disk, a failed load or inference) makes clustering fall back to
+/// [`build_feature_matrix`], as does a matrix without one row per method.
+pub trait Embedder: Send + Sync {
+    fn embed(&self, methods: &[MethodMetrics]) -> Option<Array2<f64>>;
+}
+
+/// Text a sentence model sees for a method: its name split into words, then its tokens
+pub fn method_text(method: &MethodMetrics) -> String {
+    let name = method.name.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty());
+    name.chain(method.tokens.iter().map(|t| t.as_ref())).collect::<Vec<_>>().join(" ")
+}
+
+/// One row per method, in order, and the vocabulary naming the columns
+pub fn build_feature_matrix(methods: &[MethodMetrics]) -> (Array2<f64>, Vocabulary) {
+    // Sorted vocabularies keep columns independent of method order
+    let mut tokens: BTreeSet<Arc<str>> = methods.iter().flat_map(|m| m.tokens.iter().cloned()).collect();
+    if tokens.len() > MAX_TOKEN_COLUMNS {
+        let mut by_use: Vec<(Arc<str>, usize)> = tokens
+            .into_iter()
+            .map(|token| {
+                let containing = methods.iter().filter(|m| m.tokens.contains(&token)).count();
+                (token, containing)
+            })
+            .collect();
+        // Stable sort keeps the alphabetical order among equally used tokens
+        by_use.sort_by_key(|(_, containing)| std::cmp::Reverse(*containing));
+        tokens = by_use.into_iter().take(MAX_TOKEN_COLUMNS).map(|(token, _)| token).collect();
+    }
+    let fields: BTreeSet<Arc<str>> = methods.iter().flat_map(|m| m.accessed_fields.iter().cloned()).collect();
+    let calls: BTreeSet<Arc<str>> = methods
+        .iter()
+        .flat_map(|m| m.called_methods.iter().cloned().chain(std::iter::once(m.name.clone())))
+        .collect();
+
+    let idf: Vec<f64> = tokens
+        .iter()
+        .map(|token| {
+            let containing = methods.iter().filter(|m| m.tokens.contains(token)).count();
+            inverse_document_frequency(methods.len(), containing)
+        })
+        .collect();
+
+    let token_column: HashMap<&str, usize> = tokens.iter().enumerate().map(|(i, t)| (t.as_ref(), i)).collect();
+    let field_offset = tokens.len();
+    let field_column: HashMap<&str, usize> =
+        fields.iter().enumerate().map(|(i, f)| (f.as_ref(), field_offset + i)).collect();
+    let call_offset = field_offset + fields.len();
+    let call_column: HashMap<&str, usize> =
+        calls.iter().enumerate().map(|(i, c)| (c.as_ref(), call_offset + i)).collect();
+
+    let mut matrix = Array2::<f64>::zeros((methods.len(), call_offset + calls.len()));
+    for (row, method) in methods.iter().enumerate() {
+        let mut counts: HashMap<usize, f64> = HashMap::new();
+        for column in method.tokens.iter().filter_map(|token| token_column.get(token.as_ref())) {
+            *counts.entry(*column).or_default() += 1.0;
+        }
+        let total = counts.values().sum::<f64>().max(1.0);
+        let token_block: Vec<(usize, f64)> =
+            counts.into_iter().map(|(column, count)| (column, count / total * idf[column])).collect();
+
+        let field_block: Vec<(usize, f64)> = distinct(&method.accessed_fields)
+            .map(|field| (field_column[field], 1.0))
+            .collect();
+
+        let call_block: Vec<(usize, f64)> = distinct(&method.called_methods)
+            .chain(std::iter::once(method.name.as_ref()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|call| (call_column[call], 1.0))
+            .collect();
+
+        for (block, weight) in [(token_block, TOKEN_WEIGHT), (field_block, FIELD_WEIGHT), (call_block, CALL_WEIGHT)] {
+            let norm = block.iter().map(|(_, v)| v * v).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            for (column, value) in block {
+                matrix[[row, column]] = value / norm * weight;
+            }
+        }
+    }
+
+    let features = tokens
+        .into_iter()
+        .map(Feature::Token)
+        .chain(fields.into_iter().map(Feature::Field))
+        .chain(calls.into_iter().map(Feature::Call))
+        .collect();
+    (matrix, Vocabulary { features, idf })
+}
+
+/// Smoothed IDF, `ln((1 + n) / (1 + df)) + 1`: positive even for tokens every method has
+fn inverse_document_frequency(documents: usize, containing: usize) -> f64 {
+    ((1.0 + documents as f64) / (1.0 + containing as f64)).ln() + 1.0
+}
+
+fn distinct(names: &[Arc<str>]) -> impl Iterator<Item = &str> {
+    names.iter().map(|n| n.as_ref()).collect::<BTreeSet<_>>().into_iter()
+}
@@ -4,36 +4,92 @@
 //! - Automatically determines number of clusters
 //! - Robust to noise
 //! - Finds clusters of arbitrary shape
+//!
+//! Unless pinned, the parameters are tuned per class: `min_points` grows with the number
+//! of methods, and the tolerance starts at the knee of the k-distance graph (the distance
+//! at which points stop having close neighbours). A few tolerances around the knee are
+//! then tried and the labeling with the best silhouette score is kept.
 
-use linfa::prelude::*;
+use linfa::{traits::Transformer, ParamGuard};
 use linfa_clustering::Dbscan;
+use linfa_nn::CommonNearestNeighbour;
 use ndarray::Array2;
 
+use crate::distance::{DistanceMetric, FeatureDistance};
+
+pub use dei_core::models::ClusterStats;
+
+/// Multiples of the knee tolerance tried when tuning
+const TOLERANCE_FACTORS: [f64; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+
 /// DBSCAN-based clustering
 pub struct DbscanClusterer {
-    min_points: usize,
-    tolerance: f64,
+    /// Fixed `min_points`, or `None` to derive it from the number of points
+    min_points: Option<usize>,
+    /// Fixed tolerance, or `None` to tune it
+    tolerance: Option<f64>,
 }
 
 impl DbscanClusterer {
+    /// Fixed parameters, no tuning
     pub fn new(min_points: usize, tolerance: f64) -> Self {
         Self {
-            min_points,
-            tolerance,
+            min_points: Some(min_points),
+            tolerance: Some(tolerance),
         }
     }
 
-    /// Cluster feature vectors
+    /// Parameters tuned for each set of points
+    pub fn auto() -> Self {
+        Self {
+            min_points: None,
+            tolerance: None,
+        }
+    }
+
+    /// Cluster feature vectors by Euclidean distance; `None` marks noise
     pub fn cluster(&self, features: &Array2<f64>) -> Vec<Option<usize>> {
-        // TODO: Fix DBSCAN API usage for current linfa version
-        // Placeholder implementation: return all points in cluster 0
-        vec![Some(0); features.nrows()]
+        self.cluster_with(features, &FeatureDistance::euclidean())
+    }
+
+    /// Cluster feature vectors by `distance`; `None` marks noise
+    pub fn cluster_with(&self, features: &Array2<f64>, distance: &FeatureDistance) -> Vec<Option<usize>> {
+        let (min_points, tolerance) = self.tune_with(features, distance);
+        dbscan(features, min_points, tolerance, distance)
+    }
+
+    /// `min_points` and tolerance [`cluster`](Self::cluster) uses for `features`
+    pub fn tune(&self, features: &Array2<f64>) -> (usize, f64) {
+        self.tune_with(features, &FeatureDistance::euclidean())
     }
 
-    /// Get optimal parameters using elbow method
+    /// `min_points` and tolerance [`cluster_with`](Self::cluster_with) uses for `features`
+    pub fn tune_with(&self, features: &Array2<f64>, distance: &FeatureDistance) -> (usize, f64) {
+        let min_points = self.min_points.unwrap_or_else(|| Self::auto_params(features.nrows()).0);
+        if let Some(tolerance) = self.tolerance {
+            return (min_points, tolerance);
+        }
+
+        let knee = estimate_tolerance_with(features, min_points, distance);
+        let mut best = (knee, f64::NEG_INFINITY);
+        for factor in TOLERANCE_FACTORS {
+            let tolerance = knee * factor;
+            let labels = dbscan(features, min_points, tolerance, distance);
+            // Noise doesn't count against the silhouette, so discount labelings that shed points
+            let clustered = labels.iter().flatten().count() as f64 / labels.len().max(1) as f64;
+            if let Some(score) = silhouette_score_with(features, &labels, distance).map(|s| s * clustered) {
+                if score > best.1 {
+                    best = (tolerance, score);
+                }
+            }
+        }
+        (min_points, best.0)
+    }
+
+    /// `min_points` for `n_samples` points, and the fallback tolerance
     pub fn auto_params(n_samples: usize) -> (usize, f64) {
         let min_points = (n_samples as f64).sqrt().ceil() as usize;
-        let min_points = min_points.max(3).min(10);
+        let min_points = min_points.clamp(3, 10);
         
         // Tolerance is dataset-dependent, start conservative
         let tolerance = 0.5;
@@ -44,45 +100,119 @@ impl DbscanClusterer {
 
 impl Default for DbscanClusterer {
     fn default() -> Self {
-        Self::new(3, 0.5)
+        Self::auto()
     }
 }
 
-/// Cluster statistics
-#[derive(Debug, Clone)]
-pub struct ClusterStats {
-    pub n_clusters: usize,
-    pub n_noise: usize,
-    pub cluster_sizes: Vec<usize>,
-    pub avg_cluster_size: f64,
+fn dbscan(features: &Array2<f64>, min_points: usize, tolerance: f64, distance: &FeatureDistance) -> Vec<Option<usize>> {
+    // Degenerate inputs the linfa implementation rejects or panics on
+    if features.nrows() == 0 || features.ncols() == 0 {
+        return vec![None; features.nrows()];
+    }
+    // Space-partitioning trees rely on the triangle inequality, which only Euclidean keeps
+    let nn = match distance.metric() {
+        DistanceMetric::Euclidean => CommonNearestNeighbour::KdTree,
+        _ => CommonNearestNeighbour::LinearSearch,
+    };
+    match Dbscan::params_with(min_points.max(2), *distance, nn).tolerance(tolerance.max(f64::EPSILON)).check() {
+        Ok(params) => params.transform(features).to_vec(),
+        Err(_) => vec![None; features.nrows()],
+    }
 }
 
-impl ClusterStats {
-    pub fn from_labels(labels: &[Option<usize>]) -> Self {
-        let n_noise = labels.iter().filter(|l| l.is_none()).count();
-        
-        let mut cluster_sizes = vec![0; labels.len()];
-        for label in labels.iter().flatten() {
-            if *label < cluster_sizes.len() {
-                cluster_sizes[*label] += 1;
-            }
-        }
-        
-        cluster_sizes.retain(|&size| size > 0);
-        let n_clusters = cluster_sizes.len();
-        
-        let avg_cluster_size = if n_clusters > 0 {
-            cluster_sizes.iter().sum::<usize>() as f64 / n_clusters as f64
-        } else {
-            0.0
+/// Tolerance at the knee of the k-distance graph, `k = min_points - 1`
+///
+/// Each point's distance to its k-th nearest neighbour, sorted ascending, rises slowly
+/// through dense regions and sharply once it reaches outliers; the knee is the point
+/// furthest below the chord joining both ends. Falls back to the conservative default
+/// when there are too few points to draw a graph.
+pub fn estimate_tolerance(features: &Array2<f64>, min_points: usize) -> f64 {
+    estimate_tolerance_with(features, min_points, &FeatureDistance::euclidean())
+}
+
+/// [`estimate_tolerance`] by `distance`
+pub fn estimate_tolerance_with(features: &Array2<f64>, min_points: usize, distance: &FeatureDistance) -> f64 {
+    let n = features.nrows();
+    let k = min_points.saturating_sub(1).max(1);
+    if n <= k {
+        return DbscanClusterer::auto_params(n).1;
+    }
+
+    let mut k_distances: Vec<f64> = (0..n)
+        .map(|i| {
+            let mut distances: Vec<f64> = (0..n)
+                .filter(|&j| j != i)
+                .map(|j| distance.between(features.row(i), features.row(j)))
+                .collect();
+            distances.sort_by(f64::total_cmp);
+            distances[k - 1]
+        })
+        .collect();
+    k_distances.sort_by(f64::total_cmp);
+
+    let (low, high) = (k_distances[0], k_distances[n - 1]);
+    if high - low <= f64::EPSILON {
+        return high.max(f64::EPSILON);
+    }
+    let knee = (0..n)
+        .map(|i| {
+            let x = i as f64 / (n - 1) as f64;
+            let y = (k_distances[i] - low) / (high - low);
+            (i, x - y)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(n - 1, |(i, _)| i);
+    k_distances[knee].max(f64::EPSILON)
+}
+
+/// Mean silhouette of the clustered points, in `[-1, 1]`; `None` with fewer than two clusters
+///
+/// A point's silhouette compares its mean distance to the rest of its cluster (`a`) with
+/// the mean distance to the nearest other cluster (`b`): `(b - a) / max(a, b)`. Noise is
+/// left out, and points alone in their cluster score 0.
+pub fn silhouette_score(features: &Array2<f64>, labels: &[Option<usize>]) -> Option<f64> {
+    silhouette_score_with(features, labels, &FeatureDistance::euclidean())
+}
+
+/// [`silhouette_score`] by `distance`
+pub fn silhouette_score_with(features: &Array2<f64>, labels: &[Option<usize>], distance: &FeatureDistance) -> Option<f64> {
+    let n_clusters = labels.iter().flatten().max().map_or(0, |&max| max + 1);
+    let mut sizes = vec![0usize; n_clusters];
+    for &label in labels.iter().flatten() {
+        sizes[label] += 1;
+    }
+    if sizes.iter().filter(|&&size| size > 0).count() < 2 {
+        return None;
+    }
+
+    let mut total = 0.0;
+    let mut counted = 0;
+    for (i, own) in labels.iter().enumerate() {
+        let Some(own) = *own else {
+            continue;
         };
+        counted += 1;
+        if sizes[own] < 2 {
+            continue;
+        }
 
-        Self {
-            n_clusters,
-            n_noise,
-            cluster_sizes,
-            avg_cluster_size,
+        let mut sums = vec![0.0; n_clusters];
+        for (j, other) in labels.iter().enumerate() {
+            if let Some(other) = *other {
+                if j != i {
+                    sums[other] += distance.between(features.row(i), features.row(j));
+                }
+            }
+        }
+        let a = sums[own] / (sizes[own] - 1) as f64;
+        let b = (0..n_clusters)
+            .filter(|&c| c != own && sizes[c] > 0)
+            .map(|c| sums[c] / sizes[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+        let scale = a.max(b);
+        if scale > 0.0 {
+            total += (b - a) / scale;
         }
     }
+    Some(total / counted as f64)
 }
-
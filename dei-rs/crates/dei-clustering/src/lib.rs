@@ -6,8 +6,17 @@
 //! - More robust for varying cluster densities
 
 pub mod analyzer;
-// pub mod embeddings; // TODO: Implement embeddings module
+pub mod community;
+pub mod distance;
+pub mod embeddings;
 pub mod hdbscan;
+#[cfg(feature = "candle")]
+pub mod sentence;
 
-pub use analyzer::ClusteringAnalyzer;
+pub use analyzer::{ClusteringAnalyzer, MAX_CLUSTERED_METHODS};
+pub use community::MethodGraph;
+pub use distance::{DistanceMetric, FeatureDistance};
+pub use embeddings::{build_feature_matrix, method_text, Embedder, Feature, Vocabulary};
+#[cfg(feature = "candle")]
+pub use sentence::SentenceEmbedder;
 
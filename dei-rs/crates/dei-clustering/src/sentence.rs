@@ -0,0 +1,119 @@
+//! Sentence-transformer embeddings, run on the CPU with candle
+//!
+//! [`SentenceEmbedder`] reads a BERT-family model laid out as on the Hugging Face hub, e.g. a
+//! local copy of `sentence-transformers/all-MiniLM-L6-v2`:
+//! - `config.json`, the model config
+//! - `tokenizer.json`
+//! - `model.safetensors`
+//!
+//! Each method's [`method_text`] is embedded by mean-pooling the last hidden layer over its
+//! tokens, then L2-normalized, so Euclidean and cosine distances rank pairs alike. The model
+//! is loaded on first use; when it is missing or fails to load or run, the embedder returns
+//! `None` and clustering falls back to the TF-IDF features.
+
+use candle_core::{Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use dei_core::metrics::MethodMetrics;
+use ndarray::Array2;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+use crate::embeddings::{method_text, Embedder};
+
+/// Tokens of a method the model sees; its name and first identifiers say enough
+pub const MAX_TOKENS: usize = 256;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Method vectors from a local sentence-transformer, loaded from a directory on first use
+pub struct SentenceEmbedder {
+    dir: PathBuf,
+    model: OnceLock<Option<Model>>,
+}
+
+struct Model {
+    bert: BertModel,
+    tokenizer: Tokenizer,
+}
+
+impl SentenceEmbedder {
+    /// An embedder for the model in `dir`; nothing is read until the first method is embedded
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), model: OnceLock::new() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether the model loads, loading it if that hasn't been tried yet
+    pub fn is_available(&self) -> bool {
+        self.model().is_some()
+    }
+
+    fn model(&self) -> Option<&Model> {
+        self.model
+            .get_or_init(|| match Model::load(&self.dir) {
+                Ok(model) => Some(model),
+                Err(e) => {
+                    tracing::warn!(dir = %self.dir.display(), error = %e, "embedding model unavailable, clustering on TF-IDF features");
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+impl Embedder for SentenceEmbedder {
+    fn embed(&self, methods: &[MethodMetrics]) -> Option<Array2<f64>> {
+        let model = self.model()?;
+        model
+            .embed(methods)
+            .inspect_err(|e| tracing::warn!(error = %e, "embedding failed, clustering on TF-IDF features"))
+            .ok()
+    }
+}
+
+impl Model {
+    fn load(dir: &Path) -> Result<Self, BoxError> {
+        let config: Config = serde_json::from_str(&std::fs::read_to_string(dir.join("config.json"))?)?;
+        let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer.with_truncation(Some(TruncationParams { max_length: MAX_TOKENS, ..Default::default() }))?;
+
+        // SAFETY: the weights are only read, and a file rewritten while mapped is the same
+        // hazard as for mapped sources; the mapping lives as long as the model
+        let weights = unsafe { VarBuilder::from_mmaped_safetensors(&[dir.join("model.safetensors")], DTYPE, &Device::Cpu)? };
+        Ok(Self { bert: BertModel::load(weights, &config)?, tokenizer })
+    }
+
+    /// One normalized row per method, in order
+    fn embed(&self, methods: &[MethodMetrics]) -> Result<Array2<f64>, BoxError> {
+        if methods.is_empty() {
+            return Ok(Array2::zeros((0, 0)));
+        }
+        let texts: Vec<String> = methods.iter().map(method_text).collect();
+        let encodings = self.tokenizer.encode_batch(texts, true)?;
+        let device = &self.bert.device;
+        let rows = |row: fn(&tokenizers::Encoding) -> &[u32]| -> candle_core::Result<Tensor> {
+            let rows = encodings.iter().map(|e| Tensor::new(row(e), device)).collect::<candle_core::Result<Vec<_>>>()?;
+            Tensor::stack(&rows, 0)
+        };
+        let ids = rows(tokenizers::Encoding::get_ids)?;
+        let mask = rows(tokenizers::Encoding::get_attention_mask)?;
+
+        let hidden = self.bert.forward(&ids, &ids.zeros_like()?, Some(&mask))?;
+        // Mean of the hidden states of real tokens, leaving padding out
+        let mask = mask.to_dtype(DTYPE)?.unsqueeze(2)?;
+        let tokens = mask.sum(1)?.clamp(1.0, f64::MAX)?;
+        let pooled = hidden.broadcast_mul(&mask)?.sum(1)?.broadcast_div(&tokens)?;
+        let norms = pooled.sqr()?.sum_keepdim(1)?.sqrt()?.clamp(1e-12, f64::MAX)?;
+        let rows: Vec<Vec<f32>> = pooled.broadcast_div(&norms)?.to_vec2()?;
+
+        let width = rows.first().map_or(0, Vec::len);
+        let values = rows.into_iter().flatten().map(f64::from).collect();
+        Ok(Array2::from_shape_vec((methods.len(), width), values)?)
+    }
+}
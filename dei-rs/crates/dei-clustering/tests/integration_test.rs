@@ -1,23 +1,644 @@
- use dei_clustering::ClusteringAnalyzer;
+use dei_clustering::ClusteringAnalyzer;
+use dei_core::{
+    metrics::{ClassMetrics, LineSpan, MethodMetrics},
+    models::Language,
+    thresholds::*,
+    traits::ClusterAnalyzer,
+};
+use std::sync::Arc;
 
 #[test]
 fn test_clustering_analyzer_creation() {
-    let analyzer = ClusteringAnalyzer::new();
+    let _analyzer = ClusteringAnalyzer::new();
     // Basic test to ensure analyzer initializes without errors
-    assert!(true);
 }
 
 #[test]
 fn test_clustering_analyzer_with_params() {
-    let analyzer = ClusteringAnalyzer::with_params(3, 0.5);
+    let _analyzer = ClusteringAnalyzer::with_params(3, 0.5);
     // Test custom parameters don't cause issues
-    assert!(true);
 }
 
 #[test]
 fn test_analyzer_default() {
-    let analyzer = ClusteringAnalyzer::default();
+    let _analyzer = ClusteringAnalyzer::default();
     // Test default implementation works
-    assert!(true);
 }
 
+
+fn method(name: &str) -> MethodMetrics {
+    MethodMetrics {
+        name: name.into(),
+        lines: Lines(10),
+        complexity: Complexity(2),
+        parameters: ParamCount(1),
+        params: Arc::new([]),
+        generics: Arc::new([]),
+        nesting_depth: NestingDepth(0),
+        chain_length: ChainLength(0),
+        exit_points: 0,
+        magic_literals: 0,
+        span: LineSpan::default(),
+        called_methods: Arc::new([]),
+        accessed_fields: Arc::new([]),
+        return_type: "void".into(),
+        is_public: true,
+        is_static: false,
+        is_async: false,
+        tokens: Arc::new([]),
+        ast_snippet: None,
+    }
+}
+
+#[test]
+fn test_cluster_seeds_pin_methods() {
+    let methods: Vec<_> = [
+        "email_send", "email_validate", "email_format",
+        "load_user", "save_user", "delete_user",
+    ]
+    .iter()
+    .map(|n| method(n))
+    .collect();
+
+    let class = ClassMetrics {
+        name: "UserManager".into(),
+        fully_qualified_name: "UserManager".into(),
+        file_path: "user_manager.rs".into(),
+        lines: Lines(400),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(12),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    let thresholds = Thresholds {
+        cluster_seeds: vec![ClusterSeed::new("EmailService", ["email_*"])],
+        ..Default::default()
+    };
+
+    let clusters = ClusteringAnalyzer::new().analyze(&class, &thresholds).unwrap();
+
+    let email = clusters
+        .iter()
+        .find(|c| c.suggested_name.as_ref() == "EmailService")
+        .expect("Seeded cluster should be emitted");
+    assert_eq!(email.methods.len(), 3);
+    assert!(email.methods.iter().all(|m| m.starts_with("email_")));
+
+    // Seeded methods never leak into automatic clusters
+    for cluster in clusters.iter().filter(|c| c.suggested_name.as_ref() != "EmailService") {
+        assert!(cluster.methods.iter().all(|m| !m.starts_with("email_")));
+    }
+}
+
+#[test]
+fn test_cluster_seeds_respect_min_cluster_size() {
+    let methods: Vec<_> = ["email_send", "load_user", "save_user", "delete_user"]
+        .iter()
+        .map(|n| method(n))
+        .collect();
+
+    let class = ClassMetrics {
+        name: "UserManager".into(),
+        fully_qualified_name: "UserManager".into(),
+        file_path: "user_manager.rs".into(),
+        lines: Lines(300),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(8),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    let thresholds = Thresholds {
+        min_cluster_size: 2,
+        cluster_seeds: vec![ClusterSeed::new("EmailService", ["email_*"])],
+        ..Default::default()
+    };
+
+    // One matching method is too few to suggest, and it stays with the class
+    let clusters = ClusteringAnalyzer::new().analyze(&class, &thresholds).unwrap();
+    assert!(clusters.iter().all(|c| c.suggested_name.as_ref() != "EmailService"));
+    assert!(clusters.iter().all(|c| c.methods.iter().all(|m| m.as_ref() != "email_send")));
+}
+
+#[test]
+fn test_min_method_lines_floor() {
+    let sized = |name: &str, lines: usize| MethodMetrics {
+        lines: Lines(lines),
+        ..method(name)
+    };
+    let class = |file_path: &str| ClassMetrics {
+        name: "Account".into(),
+        fully_qualified_name: "Account".into(),
+        file_path: file_path.into(),
+        lines: Lines(60),
+        method_count: MethodCount(5),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(8),
+        methods: vec![
+            sized("deposit", 12),
+            sized("withdraw", 14),
+            sized("transfer", 20),
+            sized("get_id", 1),
+            sized("get_owner", 2),
+        ]
+        .into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    let thresholds = Thresholds::default();
+    let clusters = ClusteringAnalyzer::new().analyze(&class("account.rs"), &thresholds).unwrap();
+    let clustered: Vec<&str> = clusters.iter().flat_map(|c| c.methods.iter()).map(|m| m.as_ref()).collect();
+    assert!(clustered.contains(&"deposit"));
+    assert!(!clustered.contains(&"get_id") && !clustered.contains(&"get_owner"));
+
+    // Per-language floors override the default
+    let mut strict_python = Thresholds::default();
+    strict_python.min_method_lines_by_language.insert(Language::Python, Lines(15));
+    assert_eq!(strict_python.min_method_lines_for(Some(Language::Python)), Lines(15));
+    assert_eq!(strict_python.min_method_lines_for(Some(Language::Rust)), Lines(3));
+
+    let clusters = ClusteringAnalyzer::new().analyze(&class("account.py"), &strict_python).unwrap();
+    assert!(clusters.is_empty(), "only one python method clears the floor");
+}
+
+#[test]
+fn test_cluster_for_named_methods() {
+    let tokenized = |name: &str| MethodMetrics {
+        tokens: name.split('_').map(Arc::from).collect(),
+        ..method(name)
+    };
+    let class = ClassMetrics {
+        name: "UserManager".into(),
+        fully_qualified_name: "UserManager".into(),
+        file_path: "user_manager.rs".into(),
+        lines: Lines(120),
+        method_count: MethodCount(4),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(8),
+        methods: vec![tokenized("cache_get"), tokenized("cache_set"), tokenized("cache_clear"), tokenized("load_user")].into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    let picked: Vec<Arc<str>> = vec!["cache_get".into(), "cache_clear".into(), "missing".into()];
+    let cluster = ClusteringAnalyzer::new().cluster_for(&class, &picked);
+    assert!(cluster.suggested_name.starts_with("Cache"), "named after the most shared token");
+    let methods: Vec<&str> = cluster.methods.iter().map(|m| m.as_ref()).collect();
+    assert_eq!(methods, ["cache_get", "cache_clear"]);
+}
+
+#[test]
+fn test_feature_matrix_groups_related_methods() {
+    use dei_clustering::{build_feature_matrix, Feature};
+
+    let build = |name: &str, tokens: &[&str], fields: &[&str], calls: &[&str]| MethodMetrics {
+        tokens: tokens.iter().map(|t| Arc::from(*t)).collect(),
+        accessed_fields: fields.iter().map(|f| Arc::from(*f)).collect(),
+        called_methods: calls.iter().map(|c| Arc::from(*c)).collect(),
+        ..method(name)
+    };
+    let methods = [
+        build("send_email", &["email", "send", "smtp"], &["smtp"], &["format_email"]),
+        build("format_email", &["email", "format", "template"], &["templates"], &[]),
+        build("load_user", &["user", "load", "db"], &["db"], &[]),
+    ];
+
+    let (matrix, vocab) = build_feature_matrix(&methods);
+    assert_eq!(matrix.nrows(), 3);
+    assert_eq!(matrix.ncols(), vocab.len());
+
+    // Tokens every method shares are weighted below rarer ones
+    let email = vocab.column(&Feature::Token("email".into())).unwrap();
+    let smtp = vocab.column(&Feature::Token("smtp".into())).unwrap();
+    assert!(matrix[[0, smtp]] > matrix[[0, email]]);
+    assert!(vocab.column(&Feature::Field("db".into())).is_some());
+    // A callee shares its own call column with its callers
+    let callee = vocab.column(&Feature::Call("format_email".into())).unwrap();
+    assert!(matrix[[0, callee]] > 0.0 && matrix[[1, callee]] > 0.0);
+    assert_eq!(matrix[[2, callee]], 0.0);
+
+    let distance = |a: usize, b: usize| {
+        (&matrix.row(a) - &matrix.row(b)).mapv(|v| v * v).sum().sqrt()
+    };
+    assert!(distance(0, 1) < distance(0, 2));
+    assert!(distance(0, 1) < distance(1, 2));
+
+    let (empty, vocab) = build_feature_matrix(&[]);
+    assert_eq!(empty.nrows(), 0);
+    assert!(vocab.is_empty());
+}
+
+#[test]
+fn test_dbscan_tuning_separates_groups() {
+    use dei_clustering::hdbscan::{estimate_tolerance, silhouette_score, DbscanClusterer};
+    use ndarray::array;
+
+    // Two tight groups far apart, and an outlier
+    let features = array![
+        [0.0, 0.0], [0.1, 0.0], [0.0, 0.1], [0.1, 0.1],
+        [5.0, 5.0], [5.1, 5.0], [5.0, 5.1], [5.1, 5.1],
+        [20.0, -20.0],
+    ];
+
+    let tolerance = estimate_tolerance(&features, 3);
+    assert!(tolerance > 0.0 && tolerance < 5.0, "knee {} sits below the gap between groups", tolerance);
+
+    let labels = DbscanClusterer::auto().cluster(&features);
+    assert!(labels[0].is_some() && labels[4].is_some());
+    assert!(labels[..4].iter().all(|l| *l == labels[0]));
+    assert!(labels[4..8].iter().all(|l| *l == labels[4]));
+    assert_ne!(labels[0], labels[4]);
+    assert_eq!(labels[8], None, "the outlier is noise");
+
+    let score = silhouette_score(&features, &labels).unwrap();
+    assert!(score > 0.9, "well-separated groups score near 1, got {}", score);
+    let merged = vec![Some(0); features.nrows()];
+    assert_eq!(silhouette_score(&features, &merged), None);
+
+    // Pinned parameters skip the tuning
+    let fixed = DbscanClusterer::new(3, 100.0);
+    assert_eq!(fixed.tune(&features), (3, 100.0));
+    assert!(fixed.cluster(&features).iter().all(|l| *l == Some(0)));
+}
+
+#[test]
+fn test_call_graph_strategy_finds_communities() {
+    use dei_clustering::MethodGraph;
+
+    let build = |name: &str, fields: &[&str], calls: &[&str]| MethodMetrics {
+        accessed_fields: fields.iter().map(|f| Arc::from(*f)).collect(),
+        called_methods: calls.iter().map(|c| Arc::from(*c)).collect(),
+        ..method(name)
+    };
+    // Two field-sharing groups, both calling a logging helper, and a loner
+    let methods = vec![
+        build("send_email", &["smtp"], &["self.format_email", "log"]),
+        build("format_email", &["templates"], &["log"]),
+        build("queue_email", &["smtp", "templates"], &["log"]),
+        build("load_user", &["db", "cache"], &["log"]),
+        build("save_user", &["db"], &["self.load_user", "log"]),
+        build("evict_user", &["cache"], &["log"]),
+        build("log", &[], &["println"]),
+        build("version", &[], &[]),
+    ];
+
+    let graph = MethodGraph::build(&methods);
+    assert!(graph.weight(0, 1) > graph.weight(0, 6), "direct calls outweigh a shared helper");
+    assert_eq!(graph.weight(0, 3), 0.0);
+
+    let labels = graph.communities();
+    assert!(labels[..3].iter().all(|l| l.is_some() && *l == labels[0]));
+    assert!(labels[3..6].iter().all(|l| l.is_some() && *l == labels[3]));
+    assert_ne!(labels[0], labels[3]);
+    assert_eq!(labels[7], None, "methods without edges stay with the class");
+    assert_eq!(labels, graph.communities(), "propagation is deterministic");
+
+    let class = ClassMetrics {
+        name: "Notifier".into(),
+        fully_qualified_name: "Notifier".into(),
+        file_path: "notifier.rs".into(),
+        lines: Lines(400),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 4,
+        complexity: Complexity(16),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    let thresholds = Thresholds {
+        cluster_strategy: ClusterStrategy::CallGraph,
+        ..Default::default()
+    };
+    let clusters = ClusteringAnalyzer::new().analyze(&class, &thresholds).unwrap();
+    assert!(clusters.iter().any(|c| c.methods.iter().any(|m| m.as_ref() == "send_email")
+        && c.methods.iter().all(|m| m.as_ref() != "load_user")));
+    assert_eq!("call-graph".parse::<ClusterStrategy>(), Ok(ClusterStrategy::CallGraph));
+}
+
+#[test]
+fn test_cluster_names_are_stable() {
+    let build = |name: &str, tokens: &[&str], fields: &[&str]| MethodMetrics {
+        tokens: tokens.iter().map(|t| Arc::from(*t)).collect(),
+        accessed_fields: fields.iter().map(|f| Arc::from(*f)).collect(),
+        ..method(name)
+    };
+    let class = |methods: Vec<MethodMetrics>| ClassMetrics {
+        name: "Mailer".into(),
+        fully_qualified_name: "Mailer".into(),
+        file_path: "mailer.rs".into(),
+        lines: Lines(120),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 2,
+        complexity: Complexity(8),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    let picked: Vec<Arc<str>> = vec!["a".into(), "b".into(), "c".into()];
+    let analyzer = ClusteringAnalyzer::new();
+
+    // Equally common tokens are taken alphabetically, every time
+    let tied = class(vec![
+        build("a", &["zeta", "alpha"], &[]),
+        build("b", &["mid", "zeta"], &[]),
+        build("c", &["alpha", "mid"], &[]),
+    ]);
+    for _ in 0..10 {
+        assert_eq!(analyzer.cluster_for(&tied, &picked).suggested_name.as_ref(), "AlphaMidService");
+    }
+
+    // A field the cluster owns names it; one the whole class shares doesn't
+    let owning = class(vec![
+        build("a", &["send"], &["_emailQueues", "db"]),
+        build("b", &["send"], &["_emailQueues", "db"]),
+        build("c", &["send"], &["db"]),
+        build("d", &["load"], &["db"]),
+        build("e", &["load"], &["db"]),
+        build("f", &["load"], &["db"]),
+    ]);
+    assert_eq!(analyzer.cluster_for(&owning, &picked).suggested_name.as_ref(), "EmailQueueService");
+}
+
+#[test]
+fn test_distance_metrics() {
+    use dei_clustering::{build_feature_matrix, DistanceMetric, FeatureDistance};
+
+    let build = |name: &str, tokens: &[&str], fields: &[&str]| MethodMetrics {
+        tokens: tokens.iter().map(|t| Arc::from(*t)).collect(),
+        accessed_fields: fields.iter().map(|f| Arc::from(*f)).collect(),
+        ..method(name)
+    };
+    let methods = [
+        build("send", &["email", "smtp", "retry"], &["smtp"]),
+        build("queue", &["email", "smtp"], &["smtp", "queue"]),
+        build("load", &["user", "db"], &["db"]),
+    ];
+    let (matrix, vocab) = build_feature_matrix(&methods);
+    let matrix = &matrix;
+    let distance = |metric| {
+        let d = FeatureDistance::new(metric, &vocab);
+        move |a: usize, b: usize| d.between(matrix.row(a), matrix.row(b))
+    };
+
+    let jaccard = distance(DistanceMetric::Jaccard);
+    assert!((jaccard(0, 1) - (1.0 - 2.0 / 3.0)).abs() < 1e-9, "2 of 3 tokens shared");
+    assert_eq!(jaccard(0, 2), 1.0);
+    assert_eq!(jaccard(0, 0), 0.0);
+
+    // `send` calls nothing; its fields and its own call column are all `queue`'s too
+    let structural = distance(DistanceMetric::Structural);
+    assert!(structural(0, 1) < structural(0, 2));
+    assert_eq!(structural(0, 2), 1.0);
+
+    let cosine = distance(DistanceMetric::Cosine);
+    assert!(cosine(0, 0).abs() < 1e-9);
+    assert!(cosine(0, 1) < cosine(0, 2));
+    assert!((0.0..=1.0).contains(&cosine(0, 2)));
+
+    // Every metric is usable end to end
+    let class = ClassMetrics {
+        name: "Mailer".into(),
+        fully_qualified_name: "Mailer".into(),
+        file_path: "mailer.rs".into(),
+        lines: Lines(120),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 3,
+        complexity: Complexity(8),
+        methods: methods.to_vec().into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    for metric in [DistanceMetric::Euclidean, DistanceMetric::Cosine, DistanceMetric::Jaccard, DistanceMetric::Structural] {
+        let analyzer = ClusteringAnalyzer::new().with_metric(metric);
+        assert!(analyzer.analyze(&class, &Thresholds::default()).is_ok());
+    }
+}
+
+#[test]
+fn test_max_methods_caps_clustering() {
+    let methods: Vec<_> = (0..12)
+        .map(|i| MethodMetrics {
+            lines: Lines(10 + i),
+            tokens: vec![Arc::from(if i % 2 == 0 { "email" } else { "user" })].into(),
+            ..method(&format!("method_{}", i))
+        })
+        .collect();
+    let class = ClassMetrics {
+        name: "Manager".into(),
+        fully_qualified_name: "Manager".into(),
+        file_path: "manager.rs".into(),
+        lines: Lines(400),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(24),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    let (clusters, stats) = ClusteringAnalyzer::new()
+        .with_max_methods(6)
+        .analyze_with_stats(&class, &Thresholds::default())
+        .unwrap();
+    let stats = stats.unwrap();
+    assert_eq!(stats.cluster_sizes.iter().sum::<usize>() + stats.n_noise, 6);
+    // Only the six longest methods are considered
+    for cluster in &clusters {
+        for name in cluster.methods.iter() {
+            let index: usize = name.trim_start_matches("method_").parse().unwrap();
+            assert!(index >= 6, "{} is among the shortest", name);
+        }
+    }
+}
+
+#[test]
+fn test_embedder_replaces_features_with_fallback() {
+    use dei_clustering::{method_text, Embedder};
+    use ndarray::Array2;
+
+    /// Puts `email` methods in one corner and the rest in another
+    struct Corners;
+    impl Embedder for Corners {
+        fn embed(&self, methods: &[MethodMetrics]) -> Option<Array2<f64>> {
+            let rows: Vec<f64> = methods
+                .iter()
+                .flat_map(|m| if method_text(m).contains("email") { [0.0, 0.0] } else { [10.0, 10.0] })
+                .collect();
+            Array2::from_shape_vec((methods.len(), 2), rows).ok()
+        }
+    }
+    struct Missing;
+    impl Embedder for Missing {
+        fn embed(&self, _: &[MethodMetrics]) -> Option<Array2<f64>> {
+            None
+        }
+    }
+
+    assert_eq!(method_text(&method("send_email")), "send email");
+
+    let names = ["send_email", "queue_email", "format_email", "load_user", "save_user", "drop_user"];
+    let class = ClassMetrics {
+        name: "Manager".into(),
+        fully_qualified_name: "Manager".into(),
+        file_path: "manager.rs".into(),
+        lines: Lines(400),
+        method_count: MethodCount(names.len()),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(12),
+        methods: names.iter().map(|n| method(n)).collect::<Vec<_>>().into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    let thresholds = Thresholds::default();
+
+    let clusters = ClusteringAnalyzer::with_params(3, 1.0)
+        .with_embedder(Arc::new(Corners))
+        .analyze(&class, &thresholds)
+        .unwrap();
+    assert_eq!(clusters.len(), 2);
+    for cluster in &clusters {
+        let emails = cluster.methods.iter().filter(|m| m.ends_with("_email")).count();
+        assert!(emails == 0 || emails == cluster.methods.len());
+    }
+
+    let fallback = ClusteringAnalyzer::new().with_embedder(Arc::new(Missing)).analyze(&class, &thresholds).unwrap();
+    let plain = ClusteringAnalyzer::new().analyze(&class, &thresholds).unwrap();
+    assert_eq!(fallback.len(), plain.len());
+}
+
+#[cfg(feature = "candle")]
+mod sentence {
+    use super::*;
+    use candle_core::Device;
+    use candle_nn::{VarBuilder, VarMap};
+    use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+    use dei_clustering::{Embedder, SentenceEmbedder};
+    use std::path::Path;
+    use tokenizers::{models::wordlevel::WordLevel, pre_tokenizers::whitespace::Whitespace, Tokenizer};
+
+    const WORDS: [&str; 6] = ["[PAD]", "[UNK]", "send", "email", "load", "user"];
+
+    /// A tiny BERT with random weights, laid out like a downloaded sentence-transformer
+    fn write_model(dir: &Path) {
+        let config = format!(
+            r#"{{
+                "vocab_size": {}, "hidden_size": 8, "num_hidden_layers": 1, "num_attention_heads": 2,
+                "intermediate_size": 16, "hidden_act": "gelu", "hidden_dropout_prob": 0.0,
+                "max_position_embeddings": 64, "type_vocab_size": 2, "initializer_range": 0.02,
+                "layer_norm_eps": 1e-12, "pad_token_id": 0, "classifier_dropout": null, "model_type": null
+            }}"#,
+            WORDS.len()
+        );
+        std::fs::write(dir.join("config.json"), &config).unwrap();
+
+        let varmap = VarMap::new();
+        let config: Config = serde_json::from_str(&config).unwrap();
+        BertModel::load(VarBuilder::from_varmap(&varmap, DTYPE, &Device::Cpu), &config).unwrap();
+        varmap.save(dir.join("model.safetensors")).unwrap();
+
+        let vocab = WORDS.iter().enumerate().map(|(id, word)| (word.to_string(), id as u32)).collect();
+        let model = WordLevel::builder().vocab(vocab).unk_token("[UNK]".into()).build().unwrap();
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer.save(dir.join("tokenizer.json"), false).unwrap();
+    }
+
+    #[test]
+    fn test_sentence_embedder_falls_back_without_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let embedder = SentenceEmbedder::new(dir.path().join("all-MiniLM-L6-v2"));
+        let methods: Vec<_> = ["send_email", "load_user"].iter().map(|n| method(n)).collect();
+
+        assert!(!embedder.is_available());
+        assert!(embedder.embed(&methods).is_none());
+
+        // Clustering carries on with the TF-IDF features
+        let class = ClassMetrics {
+            name: "Manager".into(),
+            fully_qualified_name: "Manager".into(),
+            file_path: "manager.rs".into(),
+            lines: Lines(400),
+            method_count: MethodCount(methods.len()),
+            property_count: 0,
+            field_count: 0,
+            complexity: Complexity(12),
+            methods: methods.into(),
+            dependencies: Arc::new([]),
+            span: LineSpan::default(),
+            entry_point: None,
+            is_abstract: false,
+            is_public: true,
+        };
+        let thresholds = Thresholds::default();
+        let fallback = ClusteringAnalyzer::new()
+            .with_embedder(Arc::new(embedder))
+            .analyze(&class, &thresholds)
+            .unwrap();
+        assert_eq!(fallback.len(), ClusteringAnalyzer::new().analyze(&class, &thresholds).unwrap().len());
+
+        // A directory missing the weights is no model either
+        std::fs::write(dir.path().join("config.json"), "{}").unwrap();
+        assert!(!SentenceEmbedder::new(dir.path()).is_available());
+    }
+
+    #[test]
+    fn test_sentence_embedder_runs_a_local_model() {
+        let dir = tempfile::tempdir().unwrap();
+        write_model(dir.path());
+        let embedder = SentenceEmbedder::new(dir.path());
+        assert!(embedder.is_available());
+
+        let methods: Vec<_> = ["send_email", "load_user", "send_email", "email"].iter().map(|n| method(n)).collect();
+        let rows = embedder.embed(&methods).unwrap();
+        assert_eq!(rows.dim(), (4, 8));
+        for row in rows.rows() {
+            assert!((row.dot(&row) - 1.0).abs() < 1e-4);
+        }
+        // Same text, same vector; a method of different length pools over its own tokens only
+        assert_eq!(rows.row(0), rows.row(2));
+        assert_ne!(rows.row(0), rows.row(1));
+        assert!(embedder.embed(&[]).is_some_and(|rows| rows.nrows() == 0));
+    }
+}
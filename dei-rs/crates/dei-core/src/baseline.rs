@@ -0,0 +1,290 @@
+//! Accepted and suppressed findings, so checks only report what is new
+//!
+//! `dei-baseline.json` records findings accepted as existing debt; `dei-suppressions.toml`
+//! records findings silenced for good, each with a reason. Both live next to `dei.toml`
+//! (or in the analyzed directory), locate findings by paths relative to it, and are
+//! written by `dei triage`:
+//!
+//! ```toml
+//! [[suppress]]
+//! file = "src/legacy/billing.py"
+//! class = "InvoiceBuilder"
+//! method = "build"
+//! reason = "Generated from the tax tables; rewritten in Q3"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{
+    error::{Error, Result},
+    metrics::LineSpan,
+    models::{AnalysisResult, Violation, ViolationKind},
+};
+
+pub const BASELINE_FILE: &str = "dei-baseline.json";
+pub const SUPPRESSIONS_FILE: &str = "dei-suppressions.toml";
+
+/// A flagged class, or one god method in it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct FindingId {
+    /// Path relative to the baseline root, with `/` separators
+    pub file: Arc<str>,
+    pub class: Arc<str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<Arc<str>>,
+}
+
+impl std::fmt::Display for FindingId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.method {
+            Some(method) => write!(f, "{}::{}", self.class, method),
+            None => write!(f, "{}", self.class),
+        }
+    }
+}
+
+/// One unit of triage: a class's own findings, or a god method's
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub id: FindingId,
+    /// Path as analyzed, for opening in an editor
+    pub path: Arc<str>,
+    pub span: LineSpan,
+    pub is_god_class: bool,
+    pub violations: Vec<Violation>,
+}
+
+impl Finding {
+    /// Findings of a result, class-level first; `file` is the root-relative path
+    pub fn of(result: &AnalysisResult, file: &Arc<str>) -> Vec<Finding> {
+        let class = &result.class_metrics;
+        let id = |method: Option<Arc<str>>| FindingId {
+            file: file.clone(),
+            class: class.name.clone(),
+            method,
+        };
+
+        let mut findings = Vec::new();
+        if result.is_god_class || !result.violations.is_empty() {
+            findings.push(Finding {
+                id: id(None),
+                path: class.file_path.clone(),
+                span: class.span,
+                is_god_class: result.is_god_class,
+                violations: result
+                    .god_class_violations
+                    .iter()
+                    .chain(result.violations.iter())
+                    .cloned()
+                    .collect(),
+            });
+        }
+
+        findings.extend(result.god_methods.iter().map(|m| Finding {
+            id: id(Some(m.method_name.clone())),
+            path: class.file_path.clone(),
+            span: m.metrics.span,
+            is_god_class: false,
+            violations: m.violations.to_vec(),
+        }));
+        findings
+    }
+
+    fn kinds(&self) -> Vec<ViolationKind> {
+        let mut kinds = Vec::new();
+        for violation in &self.violations {
+            if !kinds.contains(&violation.kind) {
+                kinds.push(violation.kind);
+            }
+        }
+        kinds
+    }
+}
+
+/// A finding accepted as existing debt; a kind it did not have at the time reports it again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    #[serde(flatten)]
+    pub id: FindingId,
+    pub kinds: Vec<ViolationKind>,
+}
+
+/// A finding silenced for good
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    #[serde(flatten)]
+    pub id: FindingId,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BaselineFile {
+    accepted: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SuppressionsFile {
+    #[serde(default)]
+    suppress: Vec<Suppression>,
+}
+
+/// Baseline and suppressions of one root directory
+#[derive(Debug, Clone, Default)]
+pub struct Decisions {
+    root: PathBuf,
+    pub accepted: Vec<BaselineEntry>,
+    pub suppressed: Vec<Suppression>,
+}
+
+impl Decisions {
+    /// Read both files from `root`; missing files are empty
+    pub fn load(root: &Path) -> Result<Self> {
+        let baseline_path = root.join(BASELINE_FILE);
+        let accepted = if baseline_path.is_file() {
+            let text = fs::read_to_string(&baseline_path)?;
+            serde_json::from_str::<BaselineFile>(&text)
+                .map_err(|e| Error::Parse { path: baseline_path, message: e.to_string() })?
+                .accepted
+        } else {
+            Vec::new()
+        };
+
+        let suppressions_path = root.join(SUPPRESSIONS_FILE);
+        let suppressed = if suppressions_path.is_file() {
+            let text = fs::read_to_string(&suppressions_path)?;
+            toml::from_str::<SuppressionsFile>(&text)
+                .map_err(|e| Error::Parse { path: suppressions_path, message: e.to_string() })?
+                .suppress
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            accepted,
+            suppressed,
+        })
+    }
+
+    /// Write both files back, sorted so they diff cleanly; an empty list writes no file
+    pub fn save(&mut self) -> Result<()> {
+        self.accepted.sort_by(|a, b| a.id.cmp(&b.id));
+        self.suppressed.sort_by(|a, b| a.id.cmp(&b.id));
+
+        if !self.accepted.is_empty() {
+            let baseline = BaselineFile { accepted: self.accepted.clone() };
+            let json = serde_json::to_string_pretty(&baseline)
+                .map_err(|e| Error::Analysis(e.to_string()))?;
+            fs::write(self.root.join(BASELINE_FILE), json + "\n")?;
+        }
+        if !self.suppressed.is_empty() {
+            let suppressions = SuppressionsFile { suppress: self.suppressed.clone() };
+            let text = toml::to_string(&suppressions).map_err(|e| Error::Analysis(e.to_string()))?;
+            fs::write(self.root.join(SUPPRESSIONS_FILE), text)?;
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of an analyzed file relative to the root, with `/` separators
+    pub fn relative(&self, path: &str) -> Arc<str> {
+        let path = Path::new(path);
+        let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let root = self.root.canonicalize().unwrap_or_else(|_| self.root.clone());
+        let relative = absolute.strip_prefix(&root).unwrap_or(path);
+        relative.to_string_lossy().replace('\\', "/").into()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accepted.is_empty() && self.suppressed.is_empty()
+    }
+
+    pub fn is_suppressed(&self, finding: &Finding) -> bool {
+        self.suppressed.iter().any(|s| s.id == finding.id)
+    }
+
+    /// Accepted, and without kinds it lacked when accepted
+    pub fn is_accepted(&self, finding: &Finding) -> bool {
+        self.accepted
+            .iter()
+            .any(|e| e.id == finding.id && finding.kinds().iter().all(|k| e.kinds.contains(k)))
+    }
+
+    /// Neither accepted nor suppressed
+    pub fn is_new(&self, finding: &Finding) -> bool {
+        !self.is_suppressed(finding) && !self.is_accepted(finding)
+    }
+
+    /// Record a finding as existing debt, replacing an older entry for it
+    pub fn accept(&mut self, finding: &Finding) {
+        self.accepted.retain(|e| e.id != finding.id);
+        self.accepted.push(BaselineEntry {
+            id: finding.id.clone(),
+            kinds: finding.kinds(),
+        });
+    }
+
+    pub fn suppress(&mut self, finding: &Finding, reason: impl Into<String>) {
+        self.suppressed.retain(|s| s.id != finding.id);
+        self.suppressed.push(Suppression {
+            id: finding.id.clone(),
+            reason: reason.into(),
+        });
+    }
+
+    /// Drop accepted and suppressed findings from a result
+    pub fn apply(&self, result: AnalysisResult) -> AnalysisResult {
+        if self.is_empty() {
+            return result;
+        }
+
+        let file = self.relative(&result.class_metrics.file_path);
+        let findings = Finding::of(&result, &file);
+        let class_is_new = findings
+            .iter()
+            .find(|f| f.id.method.is_none())
+            .is_some_and(|f| self.is_new(f));
+        let new_methods: Vec<&Arc<str>> = findings
+            .iter()
+            .filter(|f| self.is_new(f))
+            .filter_map(|f| f.id.method.as_ref())
+            .collect();
+
+        let god_methods = result
+            .god_methods
+            .iter()
+            .filter(|m| new_methods.contains(&&m.method_name))
+            .cloned()
+            .collect();
+
+        let updated = if class_is_new {
+            AnalysisResult { god_methods, ..result }
+        } else {
+            AnalysisResult {
+                is_god_class: false,
+                god_class_violations: Arc::new([]),
+                violations: Arc::new([]),
+                god_methods,
+                ..result
+            }
+        };
+
+        if updated.has_issues() {
+            return updated;
+        }
+        AnalysisResult {
+            lcom4: updated.lcom4,
+            code_age: updated.code_age,
+            base_comparison: updated.base_comparison,
+            owners: updated.owners,
+            name_clashes: updated.name_clashes,
+            ..AnalysisResult::healthy(updated.class_metrics)
+        }
+    }
+}
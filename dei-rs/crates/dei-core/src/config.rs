@@ -0,0 +1,385 @@
+//! Repository-level `dei.toml`
+//!
+//! ```toml
+//! format = "text"
+//! preset = "strict"
+//! ignore = ["generated", "third_party"]
+//! fail_on = "error"
+//! max_issues = 10
+//!
+//! [thresholds]
+//! max_class_lines = 400
+//! max_methods = 25
+//!
+//! [smells]
+//! low_cohesion = false
+//!
+//! [violations]
+//! ParameterCount = "off"
+//! MagicLiterals = "info"
+//! Complexity = "error"
+//! DEI002 = "error"
+//! duplicate-class-name = "off"
+//!
+//! [architecture]
+//! layers = ["ui", "service", "data"]
+//!
+//! [clustering]
+//! model = "models/all-MiniLM-L6-v2"
+//!
+//! [[metadata]]
+//! pattern = "src/ui/*"
+//! tags = ["frontend"]
+//! owner = "web-team"
+//! layer = "ui"
+//! ```
+//!
+//! Every key is optional; command-line flags override file values. `[thresholds]` keys
+//! override the preset's values, whether it is picked here or with `--preset`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::{Error, Result},
+    models::{
+        AnalysisResult, GodFileResult, GodMethodResult, ImportHygieneResult, Rule, Severity, Violation, ViolationKind,
+    },
+    thresholds::{wildcard_match, Preset, Thresholds},
+};
+
+/// File name looked up from the analyzed path towards the filesystem root
+pub const CONFIG_FILE: &str = "dei.toml";
+
+/// Contents of a `dei.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeiConfig {
+    /// Built-in thresholds the `[thresholds]` table is applied on top of
+    pub preset: Option<Preset>,
+    pub thresholds: Thresholds,
+    /// `[thresholds]` keys as written, re-applied when the preset changes
+    #[serde(skip)]
+    threshold_overrides: toml::Table,
+    /// Extra path components to skip, like the built-in `target` or `node_modules`; a
+    /// component is skipped when its name contains one of these
+    pub ignore: Vec<String>,
+    /// Output format (`text`, `json`, `html`)
+    pub format: Option<String>,
+    pub smells: Smells,
+    pub violations: ViolationPolicy,
+    /// Lowest severity that fails a check (`info`, `warning`, `error`)
+    pub fail_on: Option<Severity>,
+    /// Findings at or above `fail_on` tolerated before a check fails
+    pub max_issues: Option<usize>,
+    pub architecture: Architecture,
+    pub clustering: Clustering,
+    /// Tags, owner and layer for the files under matching paths, in `[[metadata]]` tables
+    pub metadata: Vec<PathRule>,
+}
+
+impl DeiConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let parse_error = |e: toml::de::Error| Error::Parse {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        };
+        let table: toml::Table = toml::from_str(&text).map_err(parse_error)?;
+        let mut config: Self = toml::from_str(&text).map_err(parse_error)?;
+        if let Some(toml::Value::Table(overrides)) = table.get("thresholds") {
+            config.threshold_overrides = overrides.clone();
+        }
+        if let Some(preset) = config.preset {
+            config = config.with_preset(preset)?;
+        }
+        // Relative model paths are relative to the config file, wherever dei runs from
+        if let (Some(model), Some(dir)) = (&config.clustering.model, path.parent()) {
+            config.clustering.model = Some(dir.join(model));
+        }
+        config
+            .thresholds
+            .validate()
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        Ok(config)
+    }
+
+    /// Rebase the thresholds on `preset`, keeping the keys set in `[thresholds]`
+    pub fn with_preset(mut self, preset: Preset) -> Result<Self> {
+        let invalid = |e: &dyn std::fmt::Display| Error::Config(format!("preset {}: {}", preset, e));
+        let mut table = toml::Table::try_from(Thresholds::preset(preset)).map_err(|e| invalid(&e))?;
+        table.extend(self.threshold_overrides.clone());
+        self.thresholds = table.try_into().map_err(|e| invalid(&e))?;
+        self.preset = Some(preset);
+        Ok(self)
+    }
+
+    /// Load the nearest `dei.toml` at or above `start`
+    pub fn discover(start: &Path) -> Result<Option<(PathBuf, Self)>> {
+        let start = start.canonicalize().map_err(|_| Error::PathNotFound(start.to_path_buf()))?;
+        let first_dir = if start.is_dir() { start.as_path() } else { start.parent().unwrap_or(&start) };
+
+        for dir in first_dir.ancestors() {
+            let candidate = dir.join(CONFIG_FILE);
+            if candidate.is_file() {
+                let config = Self::load(&candidate)?;
+                return Ok(Some((candidate, config)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Architecture rules `dei arch` enforces
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Architecture {
+    /// Layers from highest to lowest (`ui -> service -> data`), matched against directory
+    /// names and namespace segments; a layer must not depend on the layers above it
+    pub layers: Vec<String>,
+}
+
+/// Metadata for every file and directory whose path matches, from a `[[metadata]]` table
+///
+/// Patterns use `*`/`?` wildcards; relative patterns match anywhere in the tree
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathRule {
+    pub pattern: Arc<str>,
+    #[serde(default)]
+    pub tags: Vec<Arc<str>>,
+    #[serde(default)]
+    pub owner: Option<Arc<str>>,
+    #[serde(default)]
+    pub layer: Option<Arc<str>>,
+}
+
+impl PathRule {
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        wildcard_match(&self.pattern, &path)
+            || (!self.pattern.starts_with('/') && wildcard_match(&format!("*/{}", self.pattern), &path))
+    }
+}
+
+/// How methods are clustered into suggested extractions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Clustering {
+    /// Directory of a sentence-transformer (`config.json`, `tokenizer.json`,
+    /// `model.safetensors`) to cluster on its embeddings instead of TF-IDF features; only
+    /// used by builds with the `embeddings` feature
+    pub model: Option<PathBuf>,
+}
+
+/// Which smells are reported; all enabled by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Smells {
+    pub god_class: bool,
+    pub god_method: bool,
+    pub low_cohesion: bool,
+    pub too_many_fields: bool,
+    pub too_many_dependencies: bool,
+    /// Classes sharing a simple name with classes in other files or namespaces
+    pub duplicate_names: bool,
+    /// Wildcard imports and imported-name counts, under their opt-in limits
+    pub import_hygiene: bool,
+}
+
+impl Default for Smells {
+    fn default() -> Self {
+        Self {
+            god_class: true,
+            god_method: true,
+            low_cohesion: true,
+            too_many_fields: true,
+            too_many_dependencies: true,
+            duplicate_names: true,
+            import_hygiene: true,
+        }
+    }
+}
+
+impl Smells {
+    /// Drop the findings of disabled smells from a result
+    pub fn apply(&self, result: AnalysisResult) -> AnalysisResult {
+        let violations = result
+            .violations
+            .iter()
+            .filter(|v| match v.kind {
+                ViolationKind::LowCohesion => self.low_cohesion,
+                ViolationKind::FieldCount => self.too_many_fields,
+                ViolationKind::DependencyCount => self.too_many_dependencies,
+                _ => true,
+            })
+            .cloned()
+            .collect();
+
+        AnalysisResult {
+            is_god_class: result.is_god_class && self.god_class,
+            god_methods: if self.god_method { result.god_methods.clone() } else { Default::default() },
+            god_class_violations: if self.god_class {
+                result.god_class_violations.clone()
+            } else {
+                Default::default()
+            },
+            violations,
+            name_clashes: if self.duplicate_names { result.name_clashes.clone() } else { Default::default() },
+            ..result
+        }
+    }
+
+    /// Drop the built-in import findings when import hygiene is disabled, keeping those of
+    /// custom rules; `None` once nothing is left
+    pub fn apply_imports(&self, result: ImportHygieneResult) -> Option<ImportHygieneResult> {
+        if self.import_hygiene {
+            return Some(result);
+        }
+        let violations: Arc<[Violation]> = result
+            .violations
+            .iter()
+            .filter(|v| !matches!(v.kind, ViolationKind::WildcardImports | ViolationKind::ImportedSymbols))
+            .cloned()
+            .collect();
+        (!violations.is_empty()).then_some(ImportHygieneResult { violations, ..result })
+    }
+}
+
+/// Severity of a violation kind, or `off` to stop reporting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KindSetting {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl KindSetting {
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            KindSetting::Off => None,
+            KindSetting::Info => Some(Severity::Info),
+            KindSetting::Warning => Some(Severity::Warning),
+            KindSetting::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// A `[violations]` key: a kind name as it appears in JSON output, or a rule by ID or name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PolicyKey {
+    Kind(ViolationKind),
+    Rule(Rule),
+}
+
+impl TryFrom<String> for PolicyKey {
+    type Error = String;
+
+    fn try_from(key: String) -> std::result::Result<Self, Self::Error> {
+        use serde::de::{value::{Error as ValueError, StrDeserializer}, IntoDeserializer};
+        let deserializer: StrDeserializer<ValueError> = key.as_str().into_deserializer();
+        match ViolationKind::deserialize(deserializer) {
+            Ok(kind) => Ok(PolicyKey::Kind(kind)),
+            Err(_) => key
+                .parse()
+                .map(PolicyKey::Rule)
+                .map_err(|_| format!("unknown violation kind or rule '{}'", key)),
+        }
+    }
+}
+
+impl From<PolicyKey> for String {
+    fn from(key: PolicyKey) -> Self {
+        match key {
+            PolicyKey::Kind(kind) => format!("{:?}", kind),
+            PolicyKey::Rule(rule) => rule.id().to_string(),
+        }
+    }
+}
+
+/// `[violations]` table keyed by violation kind or rule; a rule's setting wins over its
+/// violations' kinds, and unlisted ones keep the severity derived from how far they overrun
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ViolationPolicy(pub HashMap<PolicyKey, KindSetting>);
+
+impl ViolationPolicy {
+    /// Severity a violation is reported at, `None` when its rule or kind is turned off
+    pub fn severity(&self, violation: &Violation) -> Option<Severity> {
+        let by_rule = violation.rule_id.and_then(|rule| self.0.get(&PolicyKey::Rule(rule)));
+        by_rule
+            .or_else(|| self.0.get(&PolicyKey::Kind(violation.kind)))
+            .map_or(Some(violation.severity), |s| s.severity())
+    }
+
+    /// Whether `rule` is turned off as a whole
+    pub fn is_off(&self, rule: Rule) -> bool {
+        self.0.get(&PolicyKey::Rule(rule)) == Some(&KindSetting::Off)
+    }
+
+    /// Set severities and drop turned-off kinds, along with god classes and
+    /// god methods that have no remaining reason to be reported
+    pub fn apply(&self, result: AnalysisResult) -> AnalysisResult {
+        if self.0.is_empty() {
+            return result;
+        }
+
+        let god_class_violations = self.retag(&result.god_class_violations);
+        let is_god_class = result.is_god_class
+            && (result.god_class_violations.is_empty() || !god_class_violations.is_empty());
+
+        let god_methods = result
+            .god_methods
+            .iter()
+            .map(|m| GodMethodResult { violations: self.retag(&m.violations), ..m.clone() })
+            .filter(|m| !m.violations.is_empty())
+            .collect();
+
+        let name_clashes = if self.is_off(Rule::DuplicateClassName) { Arc::new([]) } else { result.name_clashes.clone() };
+        let updated = AnalysisResult {
+            is_god_class,
+            god_class_violations,
+            god_methods,
+            violations: self.retag(&result.violations),
+            name_clashes,
+            ..result
+        };
+
+        if updated.has_issues() {
+            return updated;
+        }
+        AnalysisResult {
+            lcom4: updated.lcom4,
+            code_age: updated.code_age,
+            base_comparison: updated.base_comparison,
+            owners: updated.owners,
+            name_clashes: updated.name_clashes,
+            ..AnalysisResult::healthy(updated.class_metrics)
+        }
+    }
+
+    /// Set severities on a file's import findings, `None` once every kind is off
+    pub fn apply_imports(&self, result: ImportHygieneResult) -> Option<ImportHygieneResult> {
+        let violations = self.retag(&result.violations);
+        (!violations.is_empty()).then_some(ImportHygieneResult { violations, ..result })
+    }
+
+    /// Set severities on a god file's signals; `None` once every one is turned off
+    pub fn apply_files(&self, result: GodFileResult) -> Option<GodFileResult> {
+        let violations = self.retag(&result.violations);
+        (!violations.is_empty()).then_some(GodFileResult { violations, ..result })
+    }
+
+    fn retag(&self, violations: &[Violation]) -> Arc<[Violation]> {
+        violations
+            .iter()
+            .filter_map(|v| Some(Violation { severity: self.severity(v)?, ..v.clone() }))
+            .collect()
+    }
+}
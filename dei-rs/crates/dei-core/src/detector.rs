@@ -0,0 +1,372 @@
+//! Pluggable smell detectors
+//!
+//! A [`Detector`] inspects one class, or one file, and reports what it finds as [`Finding`]s.
+//! Analysis runs every detector in a [`DetectorRegistry`] and assembles the findings into
+//! results, so a new smell is a new detector, and a detector can be disabled by name without
+//! touching the traversal. The default registry holds the built-in detectors, in the order
+//! their violations have always been reported.
+
+use std::sync::Arc;
+
+use crate::{
+    metrics::{ClassMetrics, FileMetrics, MethodMetrics},
+    models::{GodMethodResult, PathMetadata, Rule, Violation, ViolationKind},
+    thresholds::Thresholds,
+};
+
+/// What a detector gets to see besides the class or file itself
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    /// Thresholds with the entry-point adjustments for this class or file applied
+    pub thresholds: &'a Thresholds,
+    /// LCOM4 of the class, `None` for files and for classes with too few methods to judge
+    pub lcom4: Option<usize>,
+    /// Responsibility clusters across a file's methods, measured only when
+    /// `max_file_responsibilities` is set since clustering is slow
+    pub responsibilities: Option<usize>,
+    /// Tags, owner and layer the `[[metadata]]` path rules give the file
+    pub metadata: Option<&'a PathMetadata>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(thresholds: &'a Thresholds) -> Self {
+        Self { thresholds, lcom4: None, responsibilities: None, metadata: None }
+    }
+}
+
+/// One problem reported by a detector
+#[derive(Debug, Clone)]
+pub enum Finding {
+    /// A threshold that makes the class a god class
+    GodClass(Violation),
+    /// A method of the class over the god-method thresholds
+    GodMethod(Box<GodMethodResult>),
+    /// A class-level problem that doesn't make it a god class on its own
+    Class(Violation),
+    /// A threshold that makes the file a god file
+    GodFile(Violation),
+    /// A problem with a file's imports, reported under its import hygiene
+    Imports(Violation),
+}
+
+/// A check for one kind of smell
+pub trait Detector: Send + Sync {
+    /// Name the detector is disabled by, in the snake case of `[smells]` keys
+    fn name(&self) -> &str;
+
+    /// Findings for one class
+    fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding>;
+
+    /// Findings for one file; most detectors only look at classes
+    fn inspect_file(&self, _file: &FileMetrics, _ctx: &Context) -> Vec<Finding> {
+        Vec::new()
+    }
+}
+
+/// The detectors an analysis runs, in order
+#[derive(Clone)]
+pub struct DetectorRegistry {
+    detectors: Vec<Arc<dyn Detector>>,
+}
+
+impl Default for DetectorRegistry {
+    /// The built-in detectors
+    fn default() -> Self {
+        Self::empty()
+            .with(GodClassDetector)
+            .with(GodMethodDetector)
+            .with(LowCohesionDetector)
+            .with(FieldCountDetector)
+            .with(DependencyCountDetector)
+            .with(GodFileDetector)
+            .with(ImportHygieneDetector)
+    }
+}
+
+impl DetectorRegistry {
+    /// A registry without any detectors, not even the built-in ones
+    pub fn empty() -> Self {
+        Self { detectors: Vec::new() }
+    }
+
+    /// Run `detector` after the ones already registered
+    pub fn register(&mut self, detector: impl Detector + 'static) {
+        self.detectors.push(Arc::new(detector));
+    }
+
+    pub fn with(mut self, detector: impl Detector + 'static) -> Self {
+        self.register(detector);
+        self
+    }
+
+    /// Stop running the detector called `name`; `false` if none is registered under it
+    pub fn disable(&mut self, name: &str) -> bool {
+        let before = self.detectors.len();
+        self.detectors.retain(|d| d.name() != name);
+        self.detectors.len() != before
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.detectors.iter().map(|d| d.name())
+    }
+
+    /// Findings of every detector for one class
+    pub fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        self.detectors.iter().flat_map(|d| d.inspect(class, ctx)).collect()
+    }
+
+    /// Findings of every detector for one file
+    pub fn inspect_file(&self, file: &FileMetrics, ctx: &Context) -> Vec<Finding> {
+        self.detectors.iter().flat_map(|d| d.inspect_file(file, ctx)).collect()
+    }
+}
+
+impl std::fmt::Debug for DetectorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Classes over the line, method-count or complexity thresholds, as combined by the
+/// `god_class` strategy
+pub struct GodClassDetector;
+
+impl Detector for GodClassDetector {
+    fn name(&self) -> &str {
+        "god_class"
+    }
+
+    fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        let thresholds = ctx.thresholds;
+        if !class.is_god_class(thresholds) {
+            return Vec::new();
+        }
+        let checks = [
+            (ViolationKind::Lines, class.lines.0, thresholds.max_class_lines.0),
+            (ViolationKind::MethodCount, class.method_count.0, thresholds.max_methods.0),
+            (ViolationKind::Complexity, class.complexity.0, thresholds.max_class_complexity.0),
+        ];
+
+        checks
+            .into_iter()
+            .filter(|&(_, actual, threshold)| actual > threshold)
+            .map(|(kind, actual, threshold)| Finding::GodClass(Violation::new(kind, actual, threshold).with_rule(Rule::GodClass)))
+            .collect()
+    }
+}
+
+/// Methods over any of the god-method thresholds
+pub struct GodMethodDetector;
+
+impl Detector for GodMethodDetector {
+    fn name(&self) -> &str {
+        "god_method"
+    }
+
+    fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        class
+            .methods
+            .iter()
+            .filter(|m| m.is_god_method(ctx.thresholds))
+            .map(|m| Finding::GodMethod(Box::new(god_method_result(m, class, ctx.thresholds))))
+            .collect()
+    }
+}
+
+fn god_method_result(method: &MethodMetrics, class: &ClassMetrics, thresholds: &Thresholds) -> GodMethodResult {
+    let mut violations = Vec::new();
+
+    if method.lines > thresholds.max_method_lines {
+        violations.push(Violation::new(ViolationKind::Lines, method.lines.0, thresholds.max_method_lines.0));
+    }
+
+    if method.complexity > thresholds.max_method_complexity {
+        violations.push(Violation::new(
+            ViolationKind::Complexity,
+            method.complexity.0,
+            thresholds.max_method_complexity.0,
+        ));
+    }
+
+    if method.parameters > thresholds.max_parameters {
+        violations.push(Violation::new(
+            ViolationKind::ParameterCount,
+            method.parameters.0,
+            thresholds.max_parameters.0,
+        ));
+    }
+
+    if method.nesting_depth > thresholds.max_nesting_depth {
+        violations.push(Violation::new(
+            ViolationKind::NestingDepth,
+            method.nesting_depth.0,
+            thresholds.max_nesting_depth.0,
+        ));
+    }
+
+    if method.chain_length > thresholds.max_chain_length {
+        violations.push(Violation::new(
+            ViolationKind::MessageChain,
+            method.chain_length.0,
+            thresholds.max_chain_length.0,
+        ));
+    }
+
+    if let Some(max_exits) = thresholds.max_exit_points.filter(|&max| method.exit_points > max) {
+        violations.push(Violation::new(ViolationKind::ExitPoints, method.exit_points, max_exits));
+    }
+
+    if let Some(max_density) = thresholds.max_magic_density {
+        if method.magic_density() > max_density {
+            violations.push(Violation::new(
+                ViolationKind::MagicLiterals,
+                method.magic_literals,
+                (max_density * method.lines.0 as f64) as usize,
+            ));
+        }
+    }
+
+    GodMethodResult {
+        method_name: method.name.clone(),
+        class_name: class.name.clone(),
+        file_path: class.file_path.clone(),
+        metrics: method.clone(),
+        violations: violations.into_iter().map(|v| v.with_rule(Rule::GodMethod)).collect(),
+        violation_score: method.violation_score(thresholds),
+        owners: Arc::new([]),
+    }
+}
+
+/// Classes whose methods split into groups sharing no state; judged independently of size,
+/// since a small class can still mix concerns
+pub struct LowCohesionDetector;
+
+impl Detector for LowCohesionDetector {
+    fn name(&self) -> &str {
+        "low_cohesion"
+    }
+
+    fn inspect(&self, _class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        let max = ctx.thresholds.max_lcom4;
+        ctx.lcom4
+            .filter(|&lcom4| lcom4 > max)
+            .map(|lcom4| Finding::Class(Violation::new(ViolationKind::LowCohesion, lcom4, max).with_rule(Rule::LowCohesion)))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Classes holding more fields than `max_fields`
+pub struct FieldCountDetector;
+
+impl Detector for FieldCountDetector {
+    fn name(&self) -> &str {
+        "too_many_fields"
+    }
+
+    fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        let max = ctx.thresholds.max_fields;
+        (class.field_count > max)
+            .then(|| {
+                let violation = Violation::new(ViolationKind::FieldCount, class.field_count, max);
+                Finding::Class(violation.with_rule(Rule::TooManyFields))
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Classes depending on more imports than `max_dependencies`
+pub struct DependencyCountDetector;
+
+impl Detector for DependencyCountDetector {
+    fn name(&self) -> &str {
+        "too_many_dependencies"
+    }
+
+    fn inspect(&self, class: &ClassMetrics, ctx: &Context) -> Vec<Finding> {
+        let (count, max) = (class.dependencies.len(), ctx.thresholds.max_dependencies);
+        (count > max)
+            .then(|| {
+                let violation = Violation::new(ViolationKind::DependencyCount, count, max);
+                Finding::Class(violation.with_rule(Rule::TooManyDependencies))
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Files with too many classes, lines, top-level functions, imports or responsibilities
+pub struct GodFileDetector;
+
+impl Detector for GodFileDetector {
+    fn name(&self) -> &str {
+        "god_file"
+    }
+
+    fn inspect(&self, _class: &ClassMetrics, _ctx: &Context) -> Vec<Finding> {
+        Vec::new()
+    }
+
+    fn inspect_file(&self, file: &FileMetrics, ctx: &Context) -> Vec<Finding> {
+        let thresholds = ctx.thresholds;
+        let mut violations = Vec::new();
+
+        if file.classes.len() > thresholds.max_classes_per_file {
+            violations.push(Violation::new(
+                ViolationKind::ClassesPerFile,
+                file.classes.len(),
+                thresholds.max_classes_per_file,
+            ));
+        }
+
+        if file.lines > thresholds.max_file_lines {
+            violations.push(Violation::new(ViolationKind::Lines, file.lines.0, thresholds.max_file_lines.0));
+        }
+
+        if let Some(max) = thresholds.max_functions_per_file.filter(|&max| file.top_level_functions > max) {
+            violations.push(Violation::new(ViolationKind::TopLevelFunctions, file.top_level_functions, max));
+        }
+
+        if let Some(max) = thresholds.max_file_imports.filter(|&max| file.imports > max) {
+            violations.push(Violation::new(ViolationKind::DependencyCount, file.imports, max));
+        }
+
+        let responsibilities = thresholds.max_file_responsibilities.zip(ctx.responsibilities);
+        if let Some((max, responsibilities)) = responsibilities.filter(|&(max, actual)| actual > max) {
+            violations.push(Violation::new(ViolationKind::Responsibilities, responsibilities, max));
+        }
+
+        violations.into_iter().map(|v| Finding::GodFile(v.with_rule(Rule::GodFile))).collect()
+    }
+}
+
+/// Files with more wildcard imports or imported names than their opt-in limits
+pub struct ImportHygieneDetector;
+
+impl Detector for ImportHygieneDetector {
+    fn name(&self) -> &str {
+        "import_hygiene"
+    }
+
+    fn inspect(&self, _class: &ClassMetrics, _ctx: &Context) -> Vec<Finding> {
+        Vec::new()
+    }
+
+    fn inspect_file(&self, file: &FileMetrics, ctx: &Context) -> Vec<Finding> {
+        let thresholds = ctx.thresholds;
+        let mut violations = Vec::new();
+
+        let wildcards = file.wildcard_imports.len();
+        if let Some(max) = thresholds.max_wildcard_imports.filter(|&max| wildcards > max) {
+            violations.push(Violation::new(ViolationKind::WildcardImports, wildcards, max).with_rule(Rule::WildcardImports));
+        }
+
+        if let Some(max) = thresholds.max_imported_symbols.filter(|&max| file.imported_symbols > max) {
+            let violation = Violation::new(ViolationKind::ImportedSymbols, file.imported_symbols, max);
+            violations.push(violation.with_rule(Rule::TooManyImportedSymbols));
+        }
+
+        violations.into_iter().map(Finding::Imports).collect()
+    }
+}
@@ -0,0 +1,158 @@
+//! Per-class and per-method metric changes of one file between two revisions
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::metrics::{ClassMetrics, MethodMetrics};
+use crate::models::{BaseComparison, Trend};
+
+/// Whether a class or method exists on each side of the diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+    Unchanged,
+}
+
+impl Change {
+    fn between<T: PartialEq>(before: Option<T>, after: Option<T>) -> Self {
+        match (before, after) {
+            (None, _) => Change::Added,
+            (_, None) => Change::Removed,
+            (Some(b), Some(a)) if b == a => Change::Unchanged,
+            _ => Change::Modified,
+        }
+    }
+}
+
+/// One metric before and after, `0` on the side where the item is absent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricChange {
+    pub before: usize,
+    pub after: usize,
+}
+
+impl MetricChange {
+    fn new(before: Option<usize>, after: Option<usize>) -> Self {
+        Self {
+            before: before.unwrap_or_default(),
+            after: after.unwrap_or_default(),
+        }
+    }
+
+    pub fn delta(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodDiff {
+    pub name: Arc<str>,
+    pub change: Change,
+    pub lines: MetricChange,
+    pub complexity: MetricChange,
+    pub parameters: MetricChange,
+    pub nesting_depth: MetricChange,
+}
+
+impl MethodDiff {
+    fn between(name: Arc<str>, before: Option<&MethodMetrics>, after: Option<&MethodMetrics>) -> Self {
+        let key = |m: &MethodMetrics| (m.lines, m.complexity, m.parameters, m.nesting_depth);
+
+        Self {
+            change: Change::between(before.map(key), after.map(key)),
+            lines: MetricChange::new(before.map(|m| m.lines.0), after.map(|m| m.lines.0)),
+            complexity: MetricChange::new(before.map(|m| m.complexity.0), after.map(|m| m.complexity.0)),
+            parameters: MetricChange::new(before.map(|m| m.parameters.0), after.map(|m| m.parameters.0)),
+            nesting_depth: MetricChange::new(before.map(|m| m.nesting_depth.0), after.map(|m| m.nesting_depth.0)),
+            name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassDiff {
+    pub name: Arc<str>,
+    pub change: Change,
+    /// Better or worse by complexity, then methods, then lines; `None` for removed classes
+    pub trend: Option<Trend>,
+    pub lines: MetricChange,
+    pub methods: MetricChange,
+    pub complexity: MetricChange,
+    pub fields: MetricChange,
+    pub method_diffs: Vec<MethodDiff>,
+}
+
+impl ClassDiff {
+    fn between(name: Arc<str>, before: Option<&ClassMetrics>, after: Option<&ClassMetrics>) -> Self {
+        let mut names: Vec<&Arc<str>> = before
+            .iter()
+            .chain(after.iter())
+            .flat_map(|c| c.methods.iter().map(|m| &m.name))
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let method_diffs: Vec<MethodDiff> = names
+            .into_iter()
+            .map(|n| MethodDiff::between(n.clone(), largest_method(before, n), largest_method(after, n)))
+            .collect();
+
+        let key = |c: &ClassMetrics| (c.lines, c.method_count, c.complexity, c.field_count);
+        let metrics_changed = |b: &ClassMetrics, a: &ClassMetrics| key(b) != key(a);
+        let change = match (before, after) {
+            (None, _) => Change::Added,
+            (_, None) => Change::Removed,
+            (Some(b), Some(a)) if metrics_changed(b, a) => Change::Modified,
+            _ if method_diffs.iter().any(|m| m.change != Change::Unchanged) => Change::Modified,
+            _ => Change::Unchanged,
+        };
+
+        Self {
+            change,
+            trend: after.map(|a| BaseComparison::between(before, a).trend),
+            lines: MetricChange::new(before.map(|c| c.lines.0), after.map(|c| c.lines.0)),
+            methods: MetricChange::new(before.map(|c| c.method_count.0), after.map(|c| c.method_count.0)),
+            complexity: MetricChange::new(before.map(|c| c.complexity.0), after.map(|c| c.complexity.0)),
+            fields: MetricChange::new(before.map(|c| c.field_count), after.map(|c| c.field_count)),
+            method_diffs,
+            name,
+        }
+    }
+}
+
+/// Match classes by name across two parses of the same file
+///
+/// Rust splits a type over several items, so the largest namesake on each side stands for the type.
+pub fn diff_classes(before: &[ClassMetrics], after: &[ClassMetrics]) -> Vec<ClassDiff> {
+    let before = largest_by_name(before);
+    let after = largest_by_name(after);
+
+    let mut names: Vec<&Arc<str>> = before.keys().chain(after.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| ClassDiff::between(name.clone(), before.get(name).copied(), after.get(name).copied()))
+        .collect()
+}
+
+fn largest_by_name(classes: &[ClassMetrics]) -> BTreeMap<Arc<str>, &ClassMetrics> {
+    let mut by_name: BTreeMap<Arc<str>, &ClassMetrics> = BTreeMap::new();
+    for class in classes {
+        let entry = by_name.entry(class.name.clone()).or_insert(class);
+        if class.complexity > entry.complexity {
+            *entry = class;
+        }
+    }
+    by_name
+}
+
+/// Overloads share a name; the most complex one stands for them
+fn largest_method<'a>(class: Option<&'a ClassMetrics>, name: &str) -> Option<&'a MethodMetrics> {
+    class.and_then(|c| c.methods.iter().filter(|m| m.name.as_ref() == name).max_by_key(|m| m.complexity))
+}
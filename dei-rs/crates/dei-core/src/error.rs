@@ -27,5 +27,8 @@ pub enum Error {
 
     #[error("Unsupported language: {0}")]
     UnsupportedLanguage(String),
+
+    #[error("Git error: {0}")]
+    Git(String),
 }
 
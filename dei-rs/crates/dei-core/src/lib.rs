@@ -3,6 +3,10 @@
 //! This crate provides language-agnostic abstractions for code analysis,
 //! emphasizing zero-cost abstractions and strong typing.
 
+pub mod baseline;
+pub mod config;
+pub mod detector;
+pub mod diff;
 pub mod error;
 pub mod metrics;
 pub mod models;
@@ -10,6 +14,7 @@ pub mod thresholds;
 pub mod traits;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
 
 pub use error::{Error, Result};
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use crate::models::EntryPoint;
 use crate::thresholds::*;
 
 /// Method-level metrics with zero-copy strings where possible
@@ -11,6 +12,25 @@ pub struct MethodMetrics {
     pub lines: Lines,
     pub complexity: Complexity,
     pub parameters: ParamCount,
+    /// Declared parameters in order, receivers (`self`/`this`) excluded
+    #[serde(default)]
+    pub params: Arc<[Parameter]>,
+    /// Generic type parameters as written (e.g. `T: Clone`, `'a`)
+    #[serde(default)]
+    pub generics: Arc<[Arc<str>]>,
+    pub nesting_depth: NestingDepth,
+    /// Longest accessor chain in the body, links on `self`/`this` excluded
+    #[serde(default)]
+    pub chain_length: ChainLength,
+    /// `return`, `break`, `continue` and `throw`/`raise` statements, nested functions excluded
+    #[serde(default)]
+    pub exit_points: usize,
+    /// Numeric and string literals other than `0`, `1` and `""`
+    #[serde(default)]
+    pub magic_literals: usize,
+    /// Source lines the method occupies
+    #[serde(default)]
+    pub span: LineSpan,
     pub called_methods: Arc<[Arc<str>]>,
     pub accessed_fields: Arc<[Arc<str>]>,
     pub return_type: Arc<str>,
@@ -18,6 +38,39 @@ pub struct MethodMetrics {
     pub is_static: bool,
     pub is_async: bool,
     pub tokens: Arc<[Arc<str>]>, // For semantic analysis
+    /// Structural s-expression of the method body (opt-in, flagged methods only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ast_snippet: Option<Arc<str>>,
+}
+
+/// A declared method parameter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: Arc<str>,
+    /// Declared type, `None` for untyped languages or omitted annotations
+    pub type_name: Option<Arc<str>>,
+    /// Default value expression as written in source
+    pub default: Option<Arc<str>>,
+}
+
+impl Parameter {
+    pub fn new(name: impl Into<Arc<str>>) -> Self {
+        Self {
+            name: name.into(),
+            type_name: None,
+            default: None,
+        }
+    }
+
+    pub fn with_type(mut self, type_name: impl Into<Arc<str>>) -> Self {
+        self.type_name = Some(type_name.into());
+        self
+    }
+
+    pub fn with_default(mut self, default: impl Into<Arc<str>>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
 }
 
 impl MethodMetrics {
@@ -26,6 +79,14 @@ impl MethodMetrics {
         self.lines > thresholds.max_method_lines
             || self.complexity > thresholds.max_method_complexity
             || self.parameters > thresholds.max_parameters
+            || self.nesting_depth > thresholds.max_nesting_depth
+            || self.chain_length > thresholds.max_chain_length
+            || thresholds.max_exit_points.is_some_and(|max| self.exit_points > max)
+    }
+
+    /// Magic literals per line of code
+    pub fn magic_density(&self) -> f64 {
+        self.magic_literals as f64 / self.lines.0.max(1) as f64
     }
 
     /// Calculate violation score (higher = worse)
@@ -36,6 +97,49 @@ impl MethodMetrics {
         
         (line_ratio + complexity_ratio + param_ratio) / 3.0
     }
+
+    /// Language-neutral rendering, e.g. `get<T>(key: &str, fallback = None) -> Option<T>`
+    pub fn signature(&self) -> String {
+        let generics = if self.generics.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", self.generics.join(", "))
+        };
+
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|p| {
+                let mut rendered = p.name.to_string();
+                if let Some(ty) = &p.type_name {
+                    rendered.push_str(&format!(": {ty}"));
+                }
+                if let Some(default) = &p.default {
+                    rendered.push_str(&format!(" = {default}"));
+                }
+                rendered
+            })
+            .collect();
+
+        format!("{}{}({}) -> {}", self.name, generics, params.join(", "), self.return_type)
+    }
+}
+
+/// 1-based inclusive line range in the source file, `0..=0` when unknown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineSpan {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.start > 0 && self.end >= self.start
+    }
 }
 
 /// Class-level metrics
@@ -51,14 +155,44 @@ pub struct ClassMetrics {
     pub complexity: Complexity,
     pub methods: Arc<[MethodMetrics]>,
     pub dependencies: Arc<[Arc<str>]>,
+    /// Source lines the class occupies
+    #[serde(default)]
+    pub span: LineSpan,
+    /// Framework role, when the class is a controller, view or managed bean
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<EntryPoint>,
+    /// Interface, trait, protocol or abstract class
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_abstract: bool,
+    /// Visible outside its module: `pub` or `public`, or named without a leading underscore
+    #[serde(default = "public")]
+    pub is_public: bool,
+}
+
+fn public() -> bool {
+    true
 }
 
 impl ClassMetrics {
-    /// Check if class exceeds any threshold
+    /// Check if class is a god class under the thresholds' `god_class` strategy
     pub fn is_god_class(&self, thresholds: &Thresholds) -> bool {
-        self.lines > thresholds.max_class_lines
-            || self.method_count > thresholds.max_methods
-            || self.complexity > thresholds.max_class_complexity
+        thresholds.is_god_class(self.lines, self.method_count, self.complexity)
+    }
+
+    /// Copy with per-method AST snippets dropped (they are only reported for flagged methods)
+    pub fn without_ast_snippets(&self) -> ClassMetrics {
+        if self.methods.iter().all(|m| m.ast_snippet.is_none()) {
+            return self.clone();
+        }
+
+        ClassMetrics {
+            methods: self
+                .methods
+                .iter()
+                .map(|m| MethodMetrics { ast_snippet: None, ..m.clone() })
+                .collect(),
+            ..self.clone()
+        }
     }
 
     /// Count god methods in this class
@@ -73,13 +207,44 @@ pub struct FileMetrics {
     pub path: Arc<str>,
     pub lines: Lines,
     pub classes: Arc<[ClassMetrics]>,
+    /// Free functions declared at file scope (module functions, not methods)
+    #[serde(default)]
+    pub top_level_functions: usize,
+    /// Distinct modules the file imports
+    #[serde(default)]
+    pub imports: usize,
+    /// Names the imports bring into scope, a wildcard counting as one
+    #[serde(default)]
+    pub imported_symbols: usize,
+    /// Modules imported wholesale (`*`, `using static`, `export * from`)
+    #[serde(default)]
+    pub wildcard_imports: Arc<[Arc<str>]>,
+    /// Framework entry declared at file scope (`#[tokio::main]`, decorated Django views)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<EntryPoint>,
+    /// Encoding the source was lossily transcoded from, when it wasn't UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<Arc<str>>,
 }
 
 impl FileMetrics {
-    /// Check if file has too many classes or is too long
+    /// Whether `class` is the synthetic module parsers build from the file's
+    /// free functions (named after the file, as in JavaScript, R and Perl)
+    pub fn is_module_class(&self, class: &ClassMetrics) -> bool {
+        self.top_level_functions > 0
+            && std::path::Path::new(self.path.as_ref())
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy() == class.name.as_ref())
+    }
+
+    /// Check if file has too many classes, functions or imports, or is too long
+    ///
+    /// Responsibilities need clustering and are judged separately
     pub fn is_god_file(&self, thresholds: &Thresholds) -> bool {
         self.classes.len() > thresholds.max_classes_per_file
             || self.lines > thresholds.max_file_lines
+            || thresholds.max_functions_per_file.is_some_and(|max| self.top_level_functions > max)
+            || thresholds.max_file_imports.is_some_and(|max| self.imports > max)
     }
 }
 
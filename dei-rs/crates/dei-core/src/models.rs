@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use crate::config::PathRule;
 use crate::metrics::*;
 
 /// Represents a cluster of methods with shared responsibility
@@ -16,6 +17,66 @@ pub struct ResponsibilityCluster {
     pub justification: Arc<str>,
 }
 
+/// Coupling of a class to the rest of the analyzed tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassCoupling {
+    /// Distinct classes depending on this one
+    pub afferent: usize,
+    /// Distinct classes this one depends on
+    pub efferent: usize,
+    /// Efferent / (Afferent + Efferent), 0 for an isolated class
+    pub instability: f64,
+}
+
+/// How cleanly clustering split a class, to judge how far to trust its suggestions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClusterStats {
+    pub n_clusters: usize,
+    /// Methods left out of every cluster
+    pub n_noise: usize,
+    /// Share of clustered methods left out, from 0 to 1
+    pub noise_ratio: f64,
+    pub cluster_sizes: Vec<usize>,
+    pub avg_cluster_size: f64,
+    /// Mean silhouette of the clustered methods, from -1 (misplaced) to 1 (well separated);
+    /// `None` with fewer than two clusters
+    #[serde(default)]
+    pub silhouette: Option<f64>,
+}
+
+impl ClusterStats {
+    /// Counts from cluster labels, `None` marking noise; the silhouette is left unset
+    pub fn from_labels(labels: &[Option<usize>]) -> Self {
+        let n_noise = labels.iter().filter(|l| l.is_none()).count();
+
+        let mut cluster_sizes = vec![0; labels.len()];
+        for label in labels.iter().flatten() {
+            if *label < cluster_sizes.len() {
+                cluster_sizes[*label] += 1;
+            }
+        }
+
+        cluster_sizes.retain(|&size| size > 0);
+        let n_clusters = cluster_sizes.len();
+
+        let avg_cluster_size = if n_clusters > 0 {
+            cluster_sizes.iter().sum::<usize>() as f64 / n_clusters as f64
+        } else {
+            0.0
+        };
+        let noise_ratio = if labels.is_empty() { 0.0 } else { n_noise as f64 / labels.len() as f64 };
+
+        Self {
+            n_clusters,
+            n_noise,
+            noise_ratio,
+            cluster_sizes,
+            avg_cluster_size,
+            silhouette: None,
+        }
+    }
+}
+
 /// Analysis result for a god method
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GodMethodResult {
@@ -25,6 +86,45 @@ pub struct GodMethodResult {
     pub metrics: MethodMetrics,
     pub violations: Arc<[Violation]>,
     pub violation_score: f64,
+    /// Dominant authors of the method, when ownership was requested
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    pub owners: Arc<[Owner]>,
+}
+
+/// An author owning a meaningful share of a class or method, per git blame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Owner {
+    pub name: Arc<str>,
+    pub email: Arc<str>,
+    /// Committed lines written by this author
+    pub lines: usize,
+    /// Fraction of the committed lines
+    pub share: f64,
+}
+
+/// A file left out of the analysis because it couldn't be read or parsed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileError {
+    pub file_path: Arc<str>,
+    pub message: Arc<str>,
+}
+
+/// A file analyzed with a caveat the report should mention
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileWarning {
+    pub file_path: Arc<str>,
+    pub message: Arc<str>,
+}
+
+impl FileWarning {
+    /// Warning for a file that wasn't UTF-8 and was transcoded to be analyzed
+    pub fn transcoded(metrics: &FileMetrics) -> Option<Self> {
+        let encoding = metrics.encoding.as_ref()?;
+        Some(Self {
+            file_path: metrics.path.clone(),
+            message: format!("not valid UTF-8; decoded as {}, replacing undecodable bytes", encoding).into(),
+        })
+    }
 }
 
 /// Analysis result for a god file
@@ -35,6 +135,18 @@ pub struct GodFileResult {
     pub total_lines: usize,
     pub class_names: Arc<[Arc<str>]>,
     pub violations: Arc<[Violation]>,
+    /// Framework entry declared at file scope
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<EntryPoint>,
+}
+
+/// Namespace pollution found in a file's imports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportHygieneResult {
+    pub file_path: Arc<str>,
+    pub imported_symbols: usize,
+    pub wildcard_imports: Arc<[Arc<str>]>,
+    pub violations: Arc<[Violation]>,
 }
 
 /// Specific threshold violation
@@ -43,15 +155,279 @@ pub struct Violation {
     pub kind: ViolationKind,
     pub actual: usize,
     pub threshold: usize,
+    #[serde(default)]
+    pub severity: Severity,
+    /// Custom rule that raised a [`ViolationKind::Rule`] violation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<Arc<str>>,
+    /// What the custom rule found, in its own words
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Arc<str>>,
+    /// Rule the violation is reported under; `None` for violations from other detectors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<Rule>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl Violation {
+    /// Violation with a severity derived from how far `actual` exceeds `threshold`
+    pub fn new(kind: ViolationKind, actual: usize, threshold: usize) -> Self {
+        Self {
+            kind,
+            actual,
+            threshold,
+            severity: Severity::for_overrun(actual, threshold),
+            rule: None,
+            message: None,
+            rule_id: None,
+        }
+    }
+
+    /// The same violation, reported under `rule`
+    pub fn with_rule(self, rule: Rule) -> Self {
+        Self { rule_id: Some(rule), ..self }
+    }
+
+    /// Violation raised by the custom rule called `rule`; a warning unless the rule says otherwise
+    pub fn from_rule(rule: impl Into<Arc<str>>, message: impl Into<Arc<str>>) -> Self {
+        Self {
+            kind: ViolationKind::Rule,
+            actual: 1,
+            threshold: 0,
+            severity: Severity::Warning,
+            rule: Some(rule.into()),
+            message: Some(message.into()),
+            rule_id: Some(Rule::Custom),
+        }
+    }
+}
+
+/// Where the rules are documented, one section per rule
+pub const RULES_DOC_URL: &str = "https://github.com/GriffinCanCode/Dei/blob/main/dei-rs/docs/rules.md";
+
+/// A kind of finding with a stable ID, for SARIF-style output and per-rule configuration
+///
+/// IDs never change meaning once published; serialized as the ID (`DEI001`), parsed from the
+/// ID or the name (`god-class`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "&'static str", try_from = "String")]
+pub enum Rule {
+    GodClass,
+    GodMethod,
+    LowCohesion,
+    TooManyFields,
+    TooManyDependencies,
+    GodFile,
+    WildcardImports,
+    TooManyImportedSymbols,
+    DuplicateClassName,
+    /// Any rule from `.dei/rules/`; the violation's `rule` names which
+    Custom,
+}
+
+/// What a rule is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleCategory {
+    Size,
+    Complexity,
+    Cohesion,
+    Coupling,
+    Imports,
+    Naming,
+    Custom,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 10] = [
+        Rule::GodClass,
+        Rule::GodMethod,
+        Rule::LowCohesion,
+        Rule::TooManyFields,
+        Rule::TooManyDependencies,
+        Rule::GodFile,
+        Rule::WildcardImports,
+        Rule::TooManyImportedSymbols,
+        Rule::DuplicateClassName,
+        Rule::Custom,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::GodClass => "DEI001",
+            Rule::GodMethod => "DEI002",
+            Rule::LowCohesion => "DEI003",
+            Rule::TooManyFields => "DEI004",
+            Rule::TooManyDependencies => "DEI005",
+            Rule::GodFile => "DEI006",
+            Rule::WildcardImports => "DEI007",
+            Rule::TooManyImportedSymbols => "DEI008",
+            Rule::DuplicateClassName => "DEI009",
+            Rule::Custom => "DEI100",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::GodClass => "god-class",
+            Rule::GodMethod => "god-method",
+            Rule::LowCohesion => "low-cohesion",
+            Rule::TooManyFields => "too-many-fields",
+            Rule::TooManyDependencies => "too-many-dependencies",
+            Rule::GodFile => "god-file",
+            Rule::WildcardImports => "wildcard-imports",
+            Rule::TooManyImportedSymbols => "too-many-imported-symbols",
+            Rule::DuplicateClassName => "duplicate-class-name",
+            Rule::Custom => "custom",
+        }
+    }
+
+    pub fn category(self) -> RuleCategory {
+        match self {
+            Rule::GodClass | Rule::GodFile => RuleCategory::Size,
+            Rule::GodMethod => RuleCategory::Complexity,
+            Rule::LowCohesion => RuleCategory::Cohesion,
+            Rule::TooManyFields => RuleCategory::Size,
+            Rule::TooManyDependencies => RuleCategory::Coupling,
+            Rule::WildcardImports | Rule::TooManyImportedSymbols => RuleCategory::Imports,
+            Rule::DuplicateClassName => RuleCategory::Naming,
+            Rule::Custom => RuleCategory::Custom,
+        }
+    }
+
+    /// One line on what the rule flags
+    pub fn description(self) -> &'static str {
+        match self {
+            Rule::GodClass => "Class over the line, method-count or complexity limit",
+            Rule::GodMethod => "Method over a length, complexity, parameter, nesting, chain or exit-point limit",
+            Rule::LowCohesion => "Class whose methods split into groups sharing no state (LCOM4)",
+            Rule::TooManyFields => "Class holding more fields than allowed",
+            Rule::TooManyDependencies => "Class importing from more places than allowed",
+            Rule::GodFile => "File with too many classes, lines, functions, imports or responsibilities",
+            Rule::WildcardImports => "File with more wildcard imports than allowed",
+            Rule::TooManyImportedSymbols => "File pulling more names into its namespace than allowed",
+            Rule::DuplicateClassName => "Class sharing its simple name with classes elsewhere",
+            Rule::Custom => "Finding of a project rule from .dei/rules/",
+        }
+    }
+
+    /// Documentation of the rule
+    pub fn help_url(self) -> String {
+        format!("{}#{}-{}", RULES_DOC_URL, self.id().to_ascii_lowercase(), self.name())
+    }
+}
+
+impl From<Rule> for &'static str {
+    fn from(rule: Rule) -> Self {
+        rule.id()
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.id(), self.name())
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+
+    /// Rule ID (`DEI001`, any case) or name (`god-class`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.id().eq_ignore_ascii_case(s) || rule.name() == s)
+            .ok_or_else(|| format!("unknown rule '{}' (expected an ID like DEI001 or a name like god-class)", s))
+    }
+}
+
+/// Overruns beyond this multiple of the threshold are errors rather than warnings
+pub const ERROR_OVERRUN: usize = 2;
+
+/// How much a finding matters; by default only warnings and errors fail a check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// Severity of a metric at `actual` against its `threshold`: a warning when
+    /// it is over, an error when it is over [`ERROR_OVERRUN`] times the threshold
+    pub fn for_overrun(actual: usize, threshold: usize) -> Self {
+        if actual > threshold.saturating_mul(ERROR_OVERRUN) {
+            Severity::Error
+        } else {
+            Severity::Warning
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            other => Err(format!("unknown severity '{}' (expected info, warning or error)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ViolationKind {
     Lines,
     Complexity,
     MethodCount,
     ParameterCount,
+    NestingDepth,
+    /// Law-of-Demeter train wreck (`a.b().c().d()`)
+    MessageChain,
+    /// More return/break/continue/throw statements than allowed
+    ExitPoints,
+    /// Hard-coded numbers and strings, reported on already flagged methods
+    MagicLiterals,
     ClassesPerFile,
+    /// Free functions declared at file scope
+    TopLevelFunctions,
+    /// Unrelated method groups found by clustering a whole file
+    Responsibilities,
+    LowCohesion,
+    /// Data-blob class holding too much state
+    FieldCount,
+    /// Class or file importing from too many places
+    DependencyCount,
+    /// `import x.*`, `from x import *`, `using static` and the like
+    WildcardImports,
+    /// Names pulled into a file's namespace by its imports
+    ImportedSymbols,
+    /// Public types in one module, counted by `dei api`
+    PublicTypes,
+    /// Public methods on a module's public types, counted by `dei api`
+    PublicMethods,
+    /// Raised by a custom rule script
+    Rule,
 }
 
 /// Complete analysis result for a class
@@ -60,16 +436,181 @@ pub struct AnalysisResult {
     pub class_metrics: ClassMetrics,
     pub is_god_class: bool,
     pub suggested_extractions: Arc<[ResponsibilityCluster]>,
+    /// Quality of the clustering behind `suggested_extractions`, when it ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_stats: Option<ClusterStats>,
     pub god_methods: Arc<[GodMethodResult]>,
+    /// Thresholds that make this a god class (lines, method count, complexity)
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    pub god_class_violations: Arc<[Violation]>,
+    /// LCOM4 score (connected method/field components), `None` if too few methods to judge
+    #[serde(default)]
+    pub lcom4: Option<usize>,
+    /// Afferent/efferent coupling within the analyzed tree, filled in by `dei check`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coupling: Option<ClassCoupling>,
+    /// Class-level violations that don't make it a god class (e.g. low cohesion)
+    #[serde(default = "no_violations")]
+    pub violations: Arc<[Violation]>,
+    /// Age and recent growth from git blame, when age weighting was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_age: Option<CodeAge>,
+    /// Change against the base revision, for god classes in a `--changed-since` run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_comparison: Option<BaseComparison>,
+    /// Dominant authors of the class, when ownership was requested
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    pub owners: Arc<[Owner]>,
+    /// Other classes in the analyzed tree with the same simple name
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    pub name_clashes: Arc<[ClassLocation]>,
+    /// What the `[[metadata]]` path rules matching the class's file say about it
+    #[serde(default, skip_serializing_if = "PathMetadata::is_empty")]
+    pub metadata: PathMetadata,
     #[serde(skip_serializing, default = "default_systemtime")]
     pub analyzed_at: SystemTime,
     pub summary: Arc<str>,
 }
 
+/// Tags, owner and layer given to a file by the path rules matching it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathMetadata {
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    pub tags: Arc<[Arc<str>]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<Arc<str>>,
+}
+
+impl PathMetadata {
+    /// What `rules` say about `path`, in order: tags accumulate, a later owner or layer wins
+    pub fn from_rules(rules: &[PathRule], path: &str) -> Self {
+        let mut tags: Vec<Arc<str>> = Vec::new();
+        let (mut owner, mut layer) = (None, None);
+        for rule in rules.iter().filter(|r| r.matches(path)) {
+            for tag in &rule.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            owner = rule.owner.clone().or(owner);
+            layer = rule.layer.clone().or(layer);
+        }
+        Self { tags: tags.into(), owner, layer }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.owner.is_none() && self.layer.is_none()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.as_ref() == tag)
+    }
+}
+
+/// Where a class is defined
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassLocation {
+    pub qualified_name: Arc<str>,
+    pub file_path: Arc<str>,
+    pub span: LineSpan,
+}
+
+/// Direction a class moved relative to a base revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trend {
+    /// Not present at the base revision
+    New,
+    Better,
+    Worse,
+    Unchanged,
+}
+
+/// Size and complexity change of a class since a base revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseComparison {
+    pub trend: Trend,
+    pub complexity_delta: i64,
+    pub methods_delta: i64,
+    pub lines_delta: i64,
+}
+
+impl BaseComparison {
+    /// Compare by complexity, then method count, then lines
+    pub fn between(base: Option<&ClassMetrics>, current: &ClassMetrics) -> Self {
+        let Some(base) = base else {
+            return Self {
+                trend: Trend::New,
+                complexity_delta: current.complexity.0 as i64,
+                methods_delta: current.method_count.0 as i64,
+                lines_delta: current.lines.0 as i64,
+            };
+        };
+
+        let complexity_delta = current.complexity.0 as i64 - base.complexity.0 as i64;
+        let methods_delta = current.method_count.0 as i64 - base.method_count.0 as i64;
+        let lines_delta = current.lines.0 as i64 - base.lines.0 as i64;
+
+        let trend = match (complexity_delta, methods_delta, lines_delta).cmp(&(0, 0, 0)) {
+            std::cmp::Ordering::Less => Trend::Better,
+            std::cmp::Ordering::Greater => Trend::Worse,
+            std::cmp::Ordering::Equal => Trend::Unchanged,
+        };
+
+        Self {
+            trend,
+            complexity_delta,
+            methods_delta,
+            lines_delta,
+        }
+    }
+}
+
+/// How old a class is and how much of it changed recently, from git blame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CodeAge {
+    /// Commit time of the oldest surviving line, seconds since the epoch
+    pub first_seen: i64,
+    /// Commit time of the newest line, seconds since the epoch
+    pub last_changed: i64,
+    /// Fraction of lines last changed inside the growth window
+    pub recent_share: f64,
+    pub window_days: u64,
+}
+
+impl CodeAge {
+    pub fn age_days(&self, now: i64) -> u64 {
+        ((now - self.first_seen).max(0) / (24 * 60 * 60)) as u64
+    }
+
+    /// Most of the code was written or rewritten inside the window
+    pub fn is_growing(&self) -> bool {
+        self.recent_share >= 0.5
+    }
+
+    /// 1.0 for code untouched during the window, up to 3.0 when all of it is recent
+    pub fn priority_weight(&self) -> f64 {
+        1.0 + 2.0 * self.recent_share
+    }
+}
+
 fn default_systemtime() -> SystemTime {
+    now()
+}
+
+/// The current time, or the Unix epoch in a browser, where std has no clock to read
+fn now() -> SystemTime {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    return SystemTime::UNIX_EPOCH;
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
     SystemTime::now()
 }
 
+fn no_violations() -> Arc<[Violation]> {
+    Arc::new([])
+}
+
 impl AnalysisResult {
     pub fn healthy(metrics: ClassMetrics) -> Self {
         Self {
@@ -77,13 +618,63 @@ impl AnalysisResult {
             class_metrics: metrics,
             is_god_class: false,
             suggested_extractions: Arc::new([]),
+            cluster_stats: None,
             god_methods: Arc::new([]),
-            analyzed_at: SystemTime::now(),
+            god_class_violations: Arc::new([]),
+            lcom4: None,
+            coupling: None,
+            violations: Arc::new([]),
+            code_age: None,
+            base_comparison: None,
+            owners: Arc::new([]),
+            name_clashes: Arc::new([]),
+            metadata: PathMetadata::default(),
+            analyzed_at: now(),
         }
     }
 
     pub fn has_issues(&self) -> bool {
-        self.is_god_class || !self.god_methods.is_empty()
+        self.is_god_class || !self.god_methods.is_empty() || !self.violations.is_empty()
+    }
+
+    /// Highest severity among the findings, `None` for a healthy class
+    pub fn max_severity(&self) -> Option<Severity> {
+        // Results built without reasons still count as a warning-level god class
+        let unexplained = (self.is_god_class && self.god_class_violations.is_empty())
+            .then_some(Severity::Warning);
+
+        self.god_class_violations
+            .iter()
+            .chain(self.violations.iter())
+            .chain(self.god_methods.iter().flat_map(|m| m.violations.iter()))
+            .map(|v| v.severity)
+            .chain(unexplained)
+            .max()
+    }
+
+    /// Whether any finding is at least `fail_on`
+    pub fn fails_check(&self, fail_on: Severity) -> bool {
+        self.max_severity().is_some_and(|s| s >= fail_on)
+    }
+
+    /// How far the class overshoots its limits, for ranking offenders: `actual / threshold`
+    /// summed over class-level findings, plus each god method's score; 0 for healthy classes
+    pub fn violation_score(&self) -> f64 {
+        let overrun = |v: &Violation| v.actual as f64 / v.threshold.max(1) as f64;
+        let unexplained = if self.is_god_class && self.god_class_violations.is_empty() { 1.0 } else { 0.0 };
+
+        self.god_class_violations.iter().chain(self.violations.iter()).map(overrun).sum::<f64>()
+            + self.god_methods.iter().map(|m| m.violation_score).sum::<f64>()
+            + unexplained
+    }
+
+    /// Class complexity weighted towards fast-growing code; 0 for classes without issues
+    pub fn growth_priority(&self) -> f64 {
+        if !self.has_issues() {
+            return 0.0;
+        }
+        let weight = self.code_age.map_or(1.0, |age| age.priority_weight());
+        self.class_metrics.complexity.0 as f64 * weight
     }
 }
 
@@ -117,6 +708,13 @@ impl Language {
         }
     }
 
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+    }
+
     pub fn extensions(&self) -> &[&str] {
         match self {
             Language::Rust => &["rs"],
@@ -132,3 +730,78 @@ impl Language {
     }
 }
 
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    /// Case-insensitive name or extension: `rust`, `csharp`/`c#`, `py`, `ts`, ...
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Ok(Language::Rust),
+            "csharp" | "c#" | "cs" => Ok(Language::CSharp),
+            "python" | "py" => Ok(Language::Python),
+            "javascript" | "js" => Ok(Language::JavaScript),
+            "typescript" | "ts" => Ok(Language::TypeScript),
+            "go" => Ok(Language::Go),
+            "java" => Ok(Language::Java),
+            "perl" | "pl" => Ok(Language::Perl),
+            "r" => Ok(Language::R),
+            other => Err(format!(
+                "unknown language '{}' (expected rust, csharp, python, javascript, typescript, go, java, perl or r)",
+                other
+            )),
+        }
+    }
+}
+
+/// Languages picked by `--lang` and `--exclude-lang`; empty selects every language
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguageFilter {
+    pub include: Vec<Language>,
+    pub exclude: Vec<Language>,
+}
+
+impl LanguageFilter {
+    pub fn allows(&self, language: Language) -> bool {
+        (self.include.is_empty() || self.include.contains(&language)) && !self.exclude.contains(&language)
+    }
+}
+
+
+/// Framework role a class or file plays, recognized from attributes, decorators,
+/// annotations or base classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntryPoint {
+    /// ASP.NET MVC or Web API controller
+    AspNetController,
+    /// Django class-based view, or a module of decorated view functions
+    DjangoView,
+    /// Spring `@Controller` or `@RestController`
+    SpringController,
+    /// Other Spring-managed beans (`@Service`, `@Component`, `@Repository`, `@Configuration`)
+    SpringBean,
+    /// `#[tokio::main]` binary entry
+    TokioMain,
+}
+
+impl EntryPoint {
+    /// Request handlers, whose business logic belongs in a service
+    pub fn is_request_handler(self) -> bool {
+        matches!(
+            self,
+            EntryPoint::AspNetController | EntryPoint::DjangoView | EntryPoint::SpringController
+        )
+    }
+
+    /// Class name without the role suffix (`OrderController` → `Order`)
+    pub fn stem(self, class_name: &str) -> &str {
+        let suffixes: &[&str] = match self {
+            EntryPoint::AspNetController | EntryPoint::SpringController => &["Controller"],
+            EntryPoint::DjangoView => &["ViewSet", "APIView", "View"],
+            EntryPoint::SpringBean | EntryPoint::TokioMain => &[],
+        };
+        suffixes
+            .iter()
+            .find_map(|s| class_name.strip_suffix(s).filter(|stem| !stem.is_empty()))
+            .unwrap_or(class_name)
+    }
+}
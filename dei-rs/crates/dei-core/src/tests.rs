@@ -23,6 +23,13 @@ mod tests {
             lines: Lines(100),
             complexity: Complexity(15),
             parameters: ParamCount(7),
+            params: Arc::new([]),
+            generics: Arc::new([]),
+            nesting_depth: NestingDepth(0),
+            chain_length: ChainLength(0),
+            exit_points: 0,
+            magic_literals: 0,
+            span: LineSpan::default(),
             called_methods: Arc::new([]),
             accessed_fields: Arc::new([]),
             return_type: "void".into(),
@@ -30,6 +37,7 @@ mod tests {
             is_static: false,
             is_async: false,
             tokens: Arc::new([]),
+            ast_snippet: None,
         };
 
         let thresholds = Thresholds::default();
@@ -49,12 +57,54 @@ mod tests {
             complexity: Complexity(80),
             methods: Arc::new([]),
             dependencies: Arc::new([]),
+            span: LineSpan::default(),
+            entry_point: None,
+            is_abstract: false,
+            is_public: true,
         };
 
         let thresholds = Thresholds::default();
         assert!(class.is_god_class(&thresholds));
     }
 
+    #[test]
+    fn test_god_class_strategies() {
+        let defaults = Thresholds::default();
+        // Long but simple: only lines over the limit
+        let long = (Lines(450), MethodCount(10), Complexity(20));
+        // Long and complex, well within the method limit
+        let tangled = (Lines(450), MethodCount(10), Complexity(150));
+        let fires = |strategy: GodClassStrategy, (lines, methods, complexity)| {
+            Thresholds { god_class: strategy, ..defaults.clone() }.is_god_class(lines, methods, complexity)
+        };
+
+        assert!(fires(GodClassStrategy::Any, long));
+        assert!(!fires(GodClassStrategy::AtLeast { count: 2 }, long));
+        assert!(fires(GodClassStrategy::AtLeast { count: 2 }, tangled));
+
+        let score = GodClassStrategy::Score { min_score: 1.0, weights: ScoreWeights::default() };
+        assert!(!fires(score, long));
+        assert!(fires(score, tangled));
+
+        let config: Thresholds = toml::from_str("[god_class]\nstrategy = \"at-least\"\ncount = 2").unwrap();
+        assert_eq!(config.god_class, GodClassStrategy::AtLeast { count: 2 });
+        let config: Thresholds =
+            toml::from_str("[god_class]\nstrategy = \"score\"\nmin_score = 1.5\nweights = { lines = 0.5 }").unwrap();
+        assert_eq!(
+            config.god_class,
+            GodClassStrategy::Score { min_score: 1.5, weights: ScoreWeights { lines: 0.5, ..Default::default() } }
+        );
+        assert!(config.validate().is_ok());
+
+        let invalid = Thresholds { god_class: GodClassStrategy::AtLeast { count: 4 }, ..defaults.clone() };
+        assert!(invalid.validate().is_err());
+        let invalid = Thresholds {
+            god_class: GodClassStrategy::Score { min_score: 0.5, weights: ScoreWeights::default() },
+            ..defaults
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_violation_score() {
         let method = MethodMetrics {
@@ -62,6 +112,13 @@ mod tests {
             lines: Lines(100),
             complexity: Complexity(20),
             parameters: ParamCount(10),
+            params: Arc::new([]),
+            generics: Arc::new([]),
+            nesting_depth: NestingDepth(0),
+            chain_length: ChainLength(0),
+            exit_points: 0,
+            magic_literals: 0,
+            span: LineSpan::default(),
             called_methods: Arc::new([]),
             accessed_fields: Arc::new([]),
             return_type: "void".into(),
@@ -69,11 +126,132 @@ mod tests {
             is_static: false,
             is_async: false,
             tokens: Arc::new([]),
+            ast_snippet: None,
         };
 
         let thresholds = Thresholds::default();
         let score = method.violation_score(&thresholds);
         assert!(score > 1.0); // Exceeds all thresholds
     }
-}
 
+    #[test]
+    fn test_cluster_seed_matching() {
+        let seed = ClusterSeed::new("EmailService", ["email_*", "send_?ail"]);
+
+        assert!(seed.matches("email_validate"));
+        assert!(seed.matches("email_"));
+        assert!(seed.matches("send_mail"));
+        assert!(!seed.matches("validate_email"));
+        assert!(!seed.matches("send_email"));
+
+        assert!(wildcard_match("*_user_*", "load_user_profile"));
+        assert!(!wildcard_match("*_user_*", "load_users"));
+    }
+
+    #[test]
+    fn test_cluster_seed_validation() {
+        let invalid = Thresholds {
+            cluster_seeds: vec![ClusterSeed::new("Empty", Vec::<&str>::new())],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_language_filter() {
+        use crate::models::{Language, LanguageFilter};
+
+        assert_eq!("C#".parse::<Language>(), Ok(Language::CSharp));
+        assert_eq!("py".parse::<Language>(), Ok(Language::Python));
+        assert!("cobol".parse::<Language>().is_err());
+
+        assert!(LanguageFilter::default().allows(Language::Go));
+        let only = LanguageFilter { include: vec![Language::Rust, Language::Python], exclude: vec![] };
+        assert!(only.allows(Language::Rust));
+        assert!(!only.allows(Language::Java));
+        let without = LanguageFilter { include: vec![], exclude: vec![Language::Java] };
+        assert!(!without.allows(Language::Java));
+        assert!(without.allows(Language::Perl));
+    }
+
+    #[test]
+    fn test_severity_from_overrun() {
+        use crate::{config::ViolationPolicy, models::*};
+
+        assert_eq!(Severity::for_overrun(15, 10), Severity::Warning);
+        assert_eq!(Severity::for_overrun(20, 10), Severity::Warning);
+        assert_eq!(Severity::for_overrun(21, 10), Severity::Error);
+        assert_eq!(Severity::for_overrun(1, 0), Severity::Error);
+        assert_eq!("Error".parse::<Severity>(), Ok(Severity::Error));
+        assert!("fatal".parse::<Severity>().is_err());
+
+        // Unlisted kinds keep the derived severity, listed kinds override it
+        let violation = Violation::new(ViolationKind::Complexity, 30, 10);
+        let policy: ViolationPolicy = toml::from_str("ParameterCount = \"off\"").unwrap();
+        assert_eq!(policy.severity(&violation), Some(Severity::Error));
+        let policy: ViolationPolicy = toml::from_str("Complexity = \"info\"").unwrap();
+        assert_eq!(policy.severity(&violation), Some(Severity::Info));
+    }
+
+    #[test]
+    fn test_rule_ids() {
+        use crate::{config::ViolationPolicy, models::*};
+
+        assert_eq!(Rule::GodClass.to_string(), "DEI001 god-class");
+        assert_eq!("dei002".parse::<Rule>(), Ok(Rule::GodMethod));
+        assert_eq!("god-file".parse::<Rule>(), Ok(Rule::GodFile));
+        assert!("DEI999".parse::<Rule>().is_err());
+        assert!(Rule::GodClass.help_url().ends_with("rules.md#dei001-god-class"));
+
+        // IDs are unique and round-trip through JSON
+        let ids: std::collections::HashSet<_> = Rule::ALL.iter().map(|r| r.id()).collect();
+        assert_eq!(ids.len(), Rule::ALL.len());
+        let violation = Violation::new(ViolationKind::Lines, 500, 300).with_rule(Rule::GodClass);
+        let json = serde_json::to_value(&violation).unwrap();
+        assert_eq!(json["rule_id"], "DEI001");
+        assert_eq!(serde_json::from_value::<Violation>(json).unwrap().rule_id, Some(Rule::GodClass));
+
+        // A rule's setting wins over the setting for its violations' kind
+        let policy: ViolationPolicy = toml::from_str("Lines = \"off\"\nDEI001 = \"error\"").unwrap();
+        assert_eq!(policy.severity(&violation), Some(Severity::Error));
+        assert_eq!(policy.severity(&Violation::new(ViolationKind::Lines, 500, 300)), None);
+        let policy: ViolationPolicy = toml::from_str("god-class = \"off\"").unwrap();
+        assert_eq!(policy.severity(&violation), None);
+        assert!(toml::from_str::<ViolationPolicy>("Lenght = \"off\"").is_err());
+    }
+
+    #[test]
+    fn test_violation_score_ranks_overruns() {
+        use crate::models::*;
+
+        let class = ClassMetrics {
+            name: "Orders".into(),
+            fully_qualified_name: "Orders".into(),
+            file_path: "orders.py".into(),
+            lines: Lines(600),
+            method_count: MethodCount(10),
+            property_count: 0,
+            field_count: 0,
+            complexity: Complexity(40),
+            methods: Arc::new([]),
+            dependencies: Arc::new([]),
+            span: LineSpan::default(),
+            entry_point: None,
+            is_abstract: false,
+            is_public: true,
+        };
+        let healthy = AnalysisResult::healthy(class);
+        assert_eq!(healthy.violation_score(), 0.0);
+
+        let flagged = |violations: Vec<Violation>| AnalysisResult {
+            is_god_class: true,
+            god_class_violations: violations.into(),
+            ..healthy.clone()
+        };
+        let twice_over = flagged(vec![Violation::new(ViolationKind::Lines, 600, 300)]);
+        let barely_over = flagged(vec![Violation::new(ViolationKind::Lines, 330, 300)]);
+        assert_eq!(twice_over.violation_score(), 2.0);
+        assert!(twice_over.violation_score() > barely_over.violation_score());
+        assert_eq!(flagged(Vec::new()).violation_score(), 1.0);
+    }
+}
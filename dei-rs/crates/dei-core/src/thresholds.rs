@@ -1,6 +1,11 @@
 //! Detection thresholds with strong typing and validation
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::{EntryPoint, Language};
 
 /// Newtype for lines of code to prevent mixing with other integers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -18,26 +23,84 @@ pub struct MethodCount(pub usize);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ParamCount(pub usize);
 
+/// Newtype for block nesting depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NestingDepth(pub usize);
+
+/// Newtype for accessor chain length (`a.b().c().d()` is 3)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChainLength(pub usize);
+
 /// Configurable detection thresholds with strong typing
+///
+/// Missing fields take their default when deserializing, so configs can be partial.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Thresholds {
     // Class-level
     pub max_class_lines: Lines,
     pub max_methods: MethodCount,
     pub max_class_complexity: Complexity,
+    pub max_fields: usize,
+    pub max_dependencies: usize,
+    /// How lines, method count and complexity over their limits add up to a god class
+    #[serde(default)]
+    pub god_class: GodClassStrategy,
     
     // Method-level
     pub max_method_lines: Lines,
     pub max_method_complexity: Complexity,
     pub max_parameters: ParamCount,
+    pub max_nesting_depth: NestingDepth,
+    pub max_chain_length: ChainLength,
+    /// Opt-in limit on return/break/continue/throw statements (single-exit style rules)
+    #[serde(default)]
+    pub max_exit_points: Option<usize>,
+    /// Magic literals per line; only adds a violation to methods already flagged
+    #[serde(default)]
+    pub max_magic_density: Option<f64>,
     
     // File-level
     pub max_classes_per_file: usize,
     pub max_file_lines: Lines,
+    /// Opt-in limit on free functions declared at file scope
+    #[serde(default)]
+    pub max_functions_per_file: Option<usize>,
+    /// Opt-in limit on distinct modules a file imports
+    #[serde(default)]
+    pub max_file_imports: Option<usize>,
+    /// Opt-in limit on responsibilities found by clustering every method in a file
+    #[serde(default)]
+    pub max_file_responsibilities: Option<usize>,
+    /// Opt-in limit on wildcard imports per file (`0` forbids them)
+    #[serde(default)]
+    pub max_wildcard_imports: Option<usize>,
+    /// Opt-in limit on names a file's imports bring into scope
+    #[serde(default)]
+    pub max_imported_symbols: Option<usize>,
+    
+    // Cohesion
+    pub max_lcom4: usize,
     
     // Clustering
+    /// Methods shorter than this are left out of clustering (trivial accessors, boilerplate)
+    #[serde(default = "default_min_method_lines")]
+    pub min_method_lines: Lines,
+    /// Per-language overrides of `min_method_lines`
+    #[serde(default)]
+    pub min_method_lines_by_language: HashMap<Language, Lines>,
     pub min_cluster_size: usize,
     pub cluster_threshold: f64,
+    #[serde(default)]
+    pub cluster_seeds: Vec<ClusterSeed>,
+    /// How methods are grouped into suggested extractions
+    #[serde(default)]
+    pub cluster_strategy: ClusterStrategy,
+
+    // Framework profiles
+    /// Overrides for controllers, views and other framework entry points
+    #[serde(default)]
+    pub entry_points: HashMap<EntryPoint, ThresholdProfile>,
 }
 
 impl Default for Thresholds {
@@ -46,18 +109,235 @@ impl Default for Thresholds {
             max_class_lines: Lines(300),
             max_methods: MethodCount(20),
             max_class_complexity: Complexity(50),
+            max_fields: 15,
+            max_dependencies: 20,
+            god_class: GodClassStrategy::default(),
             max_method_lines: Lines(50),
             max_method_complexity: Complexity(10),
             max_parameters: ParamCount(5),
+            max_nesting_depth: NestingDepth(4),
+            max_chain_length: ChainLength(4),
+            max_exit_points: None,
+            max_magic_density: None,
             max_classes_per_file: 3,
             max_file_lines: Lines(500),
+            max_functions_per_file: None,
+            max_file_imports: None,
+            max_file_responsibilities: None,
+            max_wildcard_imports: None,
+            max_imported_symbols: None,
+            max_lcom4: 2,
+            min_method_lines: default_min_method_lines(),
+            min_method_lines_by_language: HashMap::new(),
             min_cluster_size: 3,
             cluster_threshold: 0.7,
+            cluster_seeds: Vec::new(),
+            cluster_strategy: ClusterStrategy::default(),
+            entry_points: HashMap::new(),
         }
     }
 }
 
+fn default_min_method_lines() -> Lines {
+    Lines(3)
+}
+
+/// Built-in threshold set, picked with `--preset` or `preset = "..."` in `dei.toml`
+///
+/// `strict`, `default` and `lenient` apply to any language; a language name (`rust`,
+/// `python`, ...) picks limits tuned to that language's idioms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Strict,
+    Default,
+    Lenient,
+    Idiomatic(Language),
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "strict" => Ok(Preset::Strict),
+            "default" => Ok(Preset::Default),
+            "lenient" => Ok(Preset::Lenient),
+            other => other.parse().map(Preset::Idiomatic).map_err(|_| {
+                format!("unknown preset '{}' (expected strict, default, lenient or a language name)", other)
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Preset::Strict => f.write_str("strict"),
+            Preset::Default => f.write_str("default"),
+            Preset::Lenient => f.write_str("lenient"),
+            Preset::Idiomatic(language) => write!(f, "{}", format!("{:?}", language).to_lowercase()),
+        }
+    }
+}
+
+impl Serialize for Preset {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Preset {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl Thresholds {
+    /// Thresholds of a built-in preset
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Strict => Self::strict(),
+            Preset::Default => Self::default(),
+            Preset::Lenient => Self::lenient(),
+            Preset::Idiomatic(language) => Self::idiomatic(language),
+        }
+    }
+
+    /// Tighter limits for new code or teams paying down debt
+    pub fn strict() -> Self {
+        Self {
+            max_class_lines: Lines(200),
+            max_methods: MethodCount(15),
+            max_class_complexity: Complexity(35),
+            max_fields: 10,
+            max_dependencies: 12,
+            max_method_lines: Lines(30),
+            max_method_complexity: Complexity(7),
+            max_parameters: ParamCount(4),
+            max_nesting_depth: NestingDepth(3),
+            max_chain_length: ChainLength(3),
+            max_classes_per_file: 2,
+            max_file_lines: Lines(350),
+            max_lcom4: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Looser limits for legacy code, flagging only the worst offenders
+    pub fn lenient() -> Self {
+        Self {
+            max_class_lines: Lines(500),
+            max_methods: MethodCount(30),
+            max_class_complexity: Complexity(80),
+            max_fields: 25,
+            max_dependencies: 30,
+            max_method_lines: Lines(80),
+            max_method_complexity: Complexity(15),
+            max_parameters: ParamCount(7),
+            max_nesting_depth: NestingDepth(5),
+            max_chain_length: ChainLength(6),
+            max_classes_per_file: 5,
+            max_file_lines: Lines(1000),
+            max_lcom4: 3,
+            ..Self::default()
+        }
+    }
+
+    /// Defaults adjusted to how code is usually laid out in `language`
+    pub fn idiomatic(language: Language) -> Self {
+        let base = Self::default();
+        match language {
+            // Many small types per module, exhaustive `match` arms, inline test modules
+            Language::Rust => Self {
+                max_class_lines: Lines(400),
+                max_methods: MethodCount(25),
+                max_method_complexity: Complexity(12),
+                max_classes_per_file: 6,
+                max_file_lines: Lines(800),
+                ..base
+            },
+            // One type per file, verbose declarations and explicit properties
+            Language::CSharp | Language::Java => Self {
+                max_class_lines: Lines(400),
+                max_method_lines: Lines(60),
+                max_fields: 20,
+                max_classes_per_file: 1,
+                ..base
+            },
+            // Dense code, modules holding several small classes, keyword arguments
+            Language::Python => Self {
+                max_class_lines: Lines(250),
+                max_method_lines: Lines(40),
+                max_parameters: ParamCount(6),
+                max_classes_per_file: 5,
+                max_file_lines: Lines(600),
+                ..base
+            },
+            // Callback nesting and fluent chains (promises, builders)
+            Language::JavaScript | Language::TypeScript => Self {
+                max_method_lines: Lines(40),
+                max_nesting_depth: NestingDepth(5),
+                max_chain_length: ChainLength(6),
+                max_file_lines: Lines(400),
+                ..base
+            },
+            // Explicit error handling lengthens functions and adds branches
+            Language::Go => Self {
+                max_methods: MethodCount(30),
+                max_method_lines: Lines(60),
+                max_method_complexity: Complexity(15),
+                max_classes_per_file: 6,
+                max_file_lines: Lines(800),
+                ..base
+            },
+            // Script-style subs and files of free functions
+            Language::Perl | Language::R => Self {
+                max_method_lines: Lines(60),
+                max_method_complexity: Complexity(12),
+                max_file_lines: Lines(800),
+                ..base
+            },
+        }
+    }
+
+    /// Minimum method size considered for clustering in the given language
+    pub fn min_method_lines_for(&self, language: Option<Language>) -> Lines {
+        language
+            .and_then(|l| self.min_method_lines_by_language.get(&l).copied())
+            .unwrap_or(self.min_method_lines)
+    }
+
+    /// Whether a class of this size is a god class under the `god_class` strategy
+    pub fn is_god_class(&self, lines: Lines, methods: MethodCount, complexity: Complexity) -> bool {
+        let ratios = [
+            (lines.0, self.max_class_lines.0),
+            (methods.0, self.max_methods.0),
+            (complexity.0, self.max_class_complexity.0),
+        ];
+        match self.god_class {
+            GodClassStrategy::Any => ratios.iter().any(|&(actual, max)| actual > max),
+            GodClassStrategy::AtLeast { count } => ratios.iter().filter(|&&(actual, max)| actual > max).count() >= count,
+            GodClassStrategy::Score { min_score, weights } => {
+                let weights = [weights.lines, weights.methods, weights.complexity];
+                let score: f64 = ratios
+                    .iter()
+                    .zip(weights)
+                    .map(|(&(actual, max), weight)| weight * actual as f64 / max.max(1) as f64)
+                    .sum();
+                score / weights.iter().sum::<f64>() > min_score
+            }
+        }
+    }
+
+    /// Thresholds with the profile for `entry_point` applied, if one is configured
+    pub fn for_entry_point(&self, entry_point: Option<EntryPoint>) -> Cow<'_, Thresholds> {
+        match entry_point.and_then(|e| self.entry_points.get(&e)) {
+            Some(profile) => Cow::Owned(profile.apply(self)),
+            None => Cow::Borrowed(self),
+        }
+    }
+
     /// Validate thresholds are sensible
     pub fn validate(&self) -> Result<(), String> {
         if self.max_class_lines.0 < self.max_method_lines.0 {
@@ -66,10 +346,208 @@ impl Thresholds {
         if self.cluster_threshold < 0.0 || self.cluster_threshold > 1.0 {
             return Err("cluster_threshold must be between 0.0 and 1.0".into());
         }
+        if self.max_magic_density.is_some_and(|d| d < 0.0) {
+            return Err("max_magic_density must be >= 0.0".into());
+        }
+        if self.max_lcom4 < 1 {
+            return Err("max_lcom4 must be >= 1".into());
+        }
+        match self.god_class {
+            GodClassStrategy::Any => {}
+            GodClassStrategy::AtLeast { count } => {
+                if !(1..=3).contains(&count) {
+                    return Err("god_class.count must be between 1 and 3".into());
+                }
+            }
+            GodClassStrategy::Score { min_score, weights } => {
+                // Below 1.0 a class within every limit could be a god class with nothing to report
+                if min_score < 1.0 {
+                    return Err("god_class.min_score must be >= 1.0".into());
+                }
+                let weights = [weights.lines, weights.methods, weights.complexity];
+                if weights.iter().any(|&w| w < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+                    return Err("god_class.weights must be >= 0.0 and not all 0.0".into());
+                }
+            }
+        }
         if self.min_cluster_size < 2 {
             return Err("min_cluster_size must be >= 2".into());
         }
+        if let Some(seed) = self.cluster_seeds.iter().find(|s| s.patterns.is_empty()) {
+            return Err(format!("cluster seed '{}' must have at least one pattern", seed.name));
+        }
+        for (entry_point, profile) in &self.entry_points {
+            let applied = Thresholds {
+                entry_points: HashMap::new(),
+                ..profile.apply(self)
+            };
+            applied
+                .validate()
+                .map_err(|e| format!("entry_points.{:?}: {}", entry_point, e))?;
+        }
         Ok(())
     }
 }
 
+/// Class- and file-level limits for one framework role; unset keys keep the base value
+///
+/// ```toml
+/// [thresholds.entry_points.AspNetController]
+/// max_methods = 30
+/// max_dependencies = 25
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdProfile {
+    pub max_class_lines: Option<Lines>,
+    pub max_methods: Option<MethodCount>,
+    pub max_class_complexity: Option<Complexity>,
+    pub max_fields: Option<usize>,
+    pub max_dependencies: Option<usize>,
+    pub max_lcom4: Option<usize>,
+    pub max_file_lines: Option<Lines>,
+    pub max_functions_per_file: Option<usize>,
+}
+
+impl ThresholdProfile {
+    fn apply(&self, base: &Thresholds) -> Thresholds {
+        Thresholds {
+            max_class_lines: self.max_class_lines.unwrap_or(base.max_class_lines),
+            max_methods: self.max_methods.unwrap_or(base.max_methods),
+            max_class_complexity: self.max_class_complexity.unwrap_or(base.max_class_complexity),
+            max_fields: self.max_fields.unwrap_or(base.max_fields),
+            max_dependencies: self.max_dependencies.unwrap_or(base.max_dependencies),
+            max_lcom4: self.max_lcom4.unwrap_or(base.max_lcom4),
+            max_file_lines: self.max_file_lines.unwrap_or(base.max_file_lines),
+            max_functions_per_file: self.max_functions_per_file.or(base.max_functions_per_file),
+            ..base.clone()
+        }
+    }
+}
+
+/// When a class over some of its size limits is a god class
+///
+/// ```toml
+/// [thresholds.god_class]
+/// strategy = "at-least"   # "any" (the default), "at-least" or "score"
+/// count = 2
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum GodClassStrategy {
+    /// Any one of lines, method count and complexity over its limit
+    #[default]
+    Any,
+    /// At least `count` of the three over their limits
+    AtLeast { count: usize },
+    /// The weighted mean of each metric over its limit (`lines / max_class_lines`, ...)
+    /// above `min_score`, so a long but simple class can pass
+    Score {
+        min_score: f64,
+        #[serde(default)]
+        weights: ScoreWeights,
+    },
+}
+
+/// Weights of the metrics in a [`GodClassStrategy::Score`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreWeights {
+    pub lines: f64,
+    pub methods: f64,
+    pub complexity: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self { lines: 1.0, methods: 1.0, complexity: 1.0 }
+    }
+}
+
+/// How clustering groups a class's methods, picked with `cluster_strategy = "..."`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClusterStrategy {
+    /// DBSCAN over token, field and call features
+    #[default]
+    Tokens,
+    /// Community detection on the graph of shared field access and mutual calls
+    CallGraph,
+}
+
+impl std::str::FromStr for ClusterStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(ClusterStrategy::Tokens),
+            "call-graph" => Ok(ClusterStrategy::CallGraph),
+            other => Err(format!("unknown cluster strategy '{}' (expected tokens or call-graph)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for ClusterStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClusterStrategy::Tokens => f.write_str("tokens"),
+            ClusterStrategy::CallGraph => f.write_str("call-graph"),
+        }
+    }
+}
+
+/// User-pinned grouping of methods that must be clustered together
+///
+/// Patterns are matched against method names with `*` (any run) and `?` (any char) wildcards,
+/// e.g. `email_*` pins every email helper into a single extraction. Like any other cluster, a
+/// seed matching fewer than `min_cluster_size` methods isn't suggested.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterSeed {
+    pub name: Arc<str>,
+    pub patterns: Vec<Arc<str>>,
+}
+
+impl ClusterSeed {
+    pub fn new(name: impl Into<Arc<str>>, patterns: impl IntoIterator<Item = impl Into<Arc<str>>>) -> Self {
+        Self {
+            name: name.into(),
+            patterns: patterns.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Check if a method name falls under this seed
+    pub fn matches(&self, method_name: &str) -> bool {
+        self.patterns.iter().any(|p| wildcard_match(p, method_name))
+    }
+}
+
+/// Minimal `*`/`?` wildcard matching (no character classes)
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
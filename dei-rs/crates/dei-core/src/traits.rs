@@ -7,6 +7,7 @@ use crate::{
     thresholds::{Complexity, Lines, Thresholds},
 };
 use std::path::Path;
+use std::sync::Arc;
 
 /// Trait for parsing source files into metrics
 pub trait Parser: Send + Sync {
@@ -15,6 +16,12 @@ pub trait Parser: Send + Sync {
     
     /// Get supported languages
     fn supported_languages(&self) -> &[Language];
+
+    /// Qualified names of the classes in `path` that may have changed since the file was last
+    /// parsed; `None` means any of them may have
+    fn changed_classes(&self, _path: &Path) -> Option<Vec<Arc<str>>> {
+        None
+    }
 }
 
 /// Trait for calculating code complexity
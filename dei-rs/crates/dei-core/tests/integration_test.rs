@@ -1,8 +1,8 @@
 use dei_core::{
+    config::DeiConfig,
     metrics::*, 
     models::*, 
     thresholds::*,
-    Error, Result,
 };
 use std::sync::Arc;
 
@@ -15,13 +15,31 @@ fn test_thresholds_creation() {
         max_class_lines: Lines(300),
         max_method_lines: Lines(40),
         max_class_complexity: Complexity(50),
+        max_fields: 15,
+        max_dependencies: 20,
+        god_class: GodClassStrategy::Any,
         max_method_complexity: Complexity(8),
         max_methods: MethodCount(15),
         max_parameters: ParamCount(4),
+        max_nesting_depth: NestingDepth(4),
+        max_chain_length: ChainLength(4),
+        max_exit_points: None,
+        max_magic_density: None,
         max_classes_per_file: 3,
         max_file_lines: Lines(500),
+        max_functions_per_file: None,
+        max_file_imports: None,
+        max_file_responsibilities: None,
+        max_wildcard_imports: None,
+        max_imported_symbols: None,
+        max_lcom4: 2,
+        min_method_lines: Lines(3),
+        min_method_lines_by_language: Default::default(),
         min_cluster_size: 3,
         cluster_threshold: 0.7,
+        cluster_seeds: Vec::new(),
+        cluster_strategy: ClusterStrategy::Tokens,
+        entry_points: Default::default(),
     };
     assert!(custom.validate().is_ok());
 }
@@ -32,13 +50,31 @@ fn test_invalid_thresholds() {
         max_class_lines: Lines(10),
         max_method_lines: Lines(100), // Invalid: method lines > class lines
         max_class_complexity: Complexity(50),
+        max_fields: 15,
+        max_dependencies: 20,
+        god_class: GodClassStrategy::Any,
         max_method_complexity: Complexity(10),
         max_methods: MethodCount(20),
         max_parameters: ParamCount(5),
+        max_nesting_depth: NestingDepth(4),
+        max_chain_length: ChainLength(4),
+        max_exit_points: None,
+        max_magic_density: None,
         max_classes_per_file: 3,
         max_file_lines: Lines(500),
+        max_functions_per_file: None,
+        max_file_imports: None,
+        max_file_responsibilities: None,
+        max_wildcard_imports: None,
+        max_imported_symbols: None,
+        max_lcom4: 2,
+        min_method_lines: Lines(3),
+        min_method_lines_by_language: Default::default(),
         min_cluster_size: 3,
         cluster_threshold: 0.7,
+        cluster_seeds: Vec::new(),
+        cluster_strategy: ClusterStrategy::Tokens,
+        entry_points: Default::default(),
     };
     assert!(invalid.validate().is_err(), "Should fail validation when method lines > class lines");
 }
@@ -50,6 +86,13 @@ fn test_method_metrics_god_detection() {
         lines: Lines(150),
         complexity: Complexity(25),
         parameters: ParamCount(8),
+        params: Arc::new([]),
+        generics: Arc::new([]),
+        nesting_depth: NestingDepth(0),
+        chain_length: ChainLength(0),
+        exit_points: 0,
+        magic_literals: 0,
+        span: LineSpan::default(),
         called_methods: Arc::new([]),
         accessed_fields: Arc::new([]),
         return_type: "Result<(), Error>".into(),
@@ -57,6 +100,7 @@ fn test_method_metrics_god_detection() {
         is_static: false,
         is_async: false,
         tokens: Arc::new([]),
+        ast_snippet: None,
     };
     
     let thresholds = Thresholds::default();
@@ -76,6 +120,10 @@ fn test_class_metrics_god_detection() {
         complexity: Complexity(120),
         methods: Arc::new([]),
         dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
     };
     
     let thresholds = Thresholds::default();
@@ -95,6 +143,10 @@ fn test_normal_class_metrics() {
         complexity: Complexity(15),
         methods: Arc::new([]),
         dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
     };
     
     let thresholds = Thresholds::default();
@@ -121,3 +173,41 @@ fn test_metrics_display() {
     assert_eq!(params.0, 3);
 }
 
+
+#[test]
+fn test_threshold_presets() {
+    let (strict, default, lenient) = (Thresholds::strict(), Thresholds::default(), Thresholds::lenient());
+    assert!(strict.max_class_lines < default.max_class_lines && default.max_class_lines < lenient.max_class_lines);
+    assert!(strict.max_method_complexity < default.max_method_complexity);
+    assert!(default.max_method_complexity < lenient.max_method_complexity);
+
+    for preset in ["strict", "default", "lenient", "rust", "csharp", "python", "javascript", "typescript", "go", "java", "perl", "r"] {
+        let preset: Preset = preset.parse().unwrap();
+        assert!(Thresholds::preset(preset).validate().is_ok(), "{} should validate", preset);
+        assert_eq!(preset.to_string().parse::<Preset>().unwrap(), preset);
+    }
+
+    assert_eq!("Strict".parse::<Preset>().unwrap(), Preset::Strict);
+    assert_eq!("C#".parse::<Preset>().unwrap(), Preset::Idiomatic(Language::CSharp));
+    assert_eq!(Thresholds::idiomatic(Language::Java).max_classes_per_file, 1);
+    assert!("relaxed".parse::<Preset>().is_err());
+}
+
+#[test]
+fn test_config_preset_keeps_explicit_thresholds() {
+    let path = std::env::temp_dir().join(format!("dei-preset-{}.toml", std::process::id()));
+    std::fs::write(&path, "preset = \"strict\"\n\n[thresholds]\nmax_methods = 40\n").unwrap();
+    let config = DeiConfig::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let config = config.unwrap();
+
+    assert_eq!(config.preset, Some(Preset::Strict));
+    assert_eq!(config.thresholds.max_methods, MethodCount(40));
+    assert_eq!(config.thresholds.max_class_lines, Thresholds::strict().max_class_lines);
+
+    // Switching presets keeps what dei.toml set explicitly
+    let config = config.with_preset(Preset::Lenient).unwrap();
+    assert_eq!(config.thresholds.max_methods, MethodCount(40));
+    assert_eq!(config.thresholds.max_class_lines, Thresholds::lenient().max_class_lines);
+    assert_eq!(config.thresholds.max_exit_points, None);
+}
@@ -3,11 +3,10 @@
 //! These benchmarks measure real-world performance across different scenarios.
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use dei_ast::{AstBuilder, ParallelTraverser};
-use dei_core::thresholds::Thresholds;
+use dei_ast::{analyze_file, AstBuilder, ParallelTraverser};
+use dei_core::{thresholds::Thresholds, traits::Parser};
 use dei_e2e::{FixtureManager, TestHarness};
 use dei_languages::MultiLanguageParser;
-use std::path::PathBuf;
 
 fn bench_single_file_analysis(c: &mut Criterion) {
     let mut group = c.benchmark_group("single_file");
@@ -29,6 +28,17 @@ fn bench_single_file_analysis(c: &mut Criterion) {
         });
     });
     
+    // Editor path: no filesystem AST or traverser, parser reused between calls
+    group.bench_function("fast_path_god_class_rust", |b| {
+        let parser = MultiLanguageParser::new().expect("Failed to create parser");
+        let thresholds = Thresholds::default();
+        let file_path = healthy_path.join("god_class.rs");
+        
+        b.iter(|| {
+            black_box(analyze_file(&parser, &file_path, &thresholds).unwrap())
+        });
+    });
+    
     group.finish();
 }
 
@@ -213,7 +223,6 @@ fn bench_threshold_variations(c: &mut Criterion) {
     let path = fixture.copy_fixture("rust").expect("Failed to copy fixture");
     
     group.bench_function("strict_thresholds", |b| {
-        use dei_core::thresholds::{Complexity, Lines, MethodCount};
         use dei_e2e::ThresholdBuilder;
         
         let thresholds = ThresholdBuilder::new()
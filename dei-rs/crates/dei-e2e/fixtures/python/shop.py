@@ -0,0 +1,37 @@
+class Shop:
+    def __init__(self):
+        self.items = []
+        self.total = 0
+        self.mailer = None
+        self.outbox = []
+
+    def add_item(self, item):
+        self.items.append(item)
+        self.total += item
+        return self.total
+
+    def remove_item(self, item):
+        self.items.remove(item)
+        self.total -= item
+        return self.total
+
+    def checkout(self):
+        total = self.total
+        self.items = []
+        self.notify_send(total)
+        return total
+
+    def notify_send(self, msg):
+        self.outbox.append(msg)
+        self.mailer = msg
+        return msg
+
+    def notify_flush(self):
+        sent = list(self.outbox)
+        self.outbox = []
+        return sent
+
+    def notify_retry(self):
+        for m in self.outbox:
+            self.mailer = m
+        return self.mailer
@@ -1,12 +1,11 @@
 //! Test harness for running full E2E analysis
 
 use anyhow::Result;
-use dei_ast::{AstBuilder, ParallelTraverser};
 use dei_core::{
     models::AnalysisResult,
     thresholds::{Complexity, Lines, MethodCount, Thresholds},
 };
-use dei_languages::MultiLanguageParser;
+use dei_engine::AnalysisEngine;
 use std::path::Path;
 
 /// Simplified test harness for running analysis
@@ -28,14 +27,10 @@ impl TestHarness {
 
     /// Run full analysis on a directory
     pub fn analyze_path(&self, path: impl AsRef<Path>) -> Result<Vec<AnalysisResult>> {
-        let builder = AstBuilder::new();
-        let root_id = builder.build(path.as_ref())?;
-        
-        let parser = MultiLanguageParser::new()?;
-        let traverser = ParallelTraverser::new(parser, builder.arena().clone());
-        traverser.traverse_and_analyze(root_id, &self.thresholds)?;
-        
-        Ok(traverser.all_results())
+        let analysis = AnalysisEngine::builder()
+            .thresholds(self.thresholds.clone())
+            .analyze(path)?;
+        Ok(analysis.results)
     }
 
     /// Quick check if path has any god classes
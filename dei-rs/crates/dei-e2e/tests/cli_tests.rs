@@ -87,7 +87,8 @@ fn test_cli_check_custom_thresholds() -> Result<()> {
     cmd.assert()
         .failure(); // Should fail with strict thresholds
     
-    // Very lenient thresholds
+    // Very lenient thresholds; file-level limits have no flags
+    fixture.create_file("rust/dei.toml", "[thresholds]\nmax_classes_per_file = 100\n")?;
     let mut cmd2 = Command::cargo_bin("dei")?;
     cmd2.arg("check")
         .arg(path.join("god_class.rs"))
@@ -96,6 +97,8 @@ fn test_cli_check_custom_thresholds() -> Result<()> {
         .arg("--max-methods")
         .arg("1000")
         .arg("--max-complexity")
+        .arg("1000")
+        .arg("--max-lcom4")
         .arg("1000");
     
     cmd2.assert()
@@ -145,7 +148,8 @@ fn test_cli_check_directory() -> Result<()> {
     cmd.arg("check")
         .arg(&path);
     
-    let output = cmd.assert().success();
+    // The fixture directory includes god_method.rs, so issues are reported
+    let output = cmd.assert().failure();
     
     // Should analyze multiple files
     let stdout = String::from_utf8(output.get_output().stdout.clone())?;
@@ -170,6 +174,175 @@ fn test_cli_arch_command() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_cli_arch_packages() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/orders/order.rs", "use crate::billing::Invoice;\n\npub struct Order { invoice: Invoice }\n")?;
+    fixture.create_file("src/billing/invoice.rs", "pub struct Invoice { total: u64 }\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path()).arg("--packages");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("PACKAGE COUPLING"))
+        .stdout(predicate::str::contains("src/orders"))
+        .stdout(predicate::str::contains("src/billing"));
+    
+    // Off unless asked for
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("PACKAGE COUPLING").not());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_arch_choke_points() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/api.rs", "use crate::service::Service;\n\npub struct Api { service: Service }\n")?;
+    fixture.create_file("src/cli.rs", "use crate::service::Service;\n\npub struct Cli { service: Service }\n")?;
+    fixture.create_file("src/service.rs", "use crate::store::Store;\n\npub struct Service { store: Store }\n")?;
+    fixture.create_file("src/store.rs", "pub struct Store { rows: Vec<u64> }\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("CHOKE POINTS"))
+        .stdout(predicate::str::contains("Service lies on 33% of dependency paths"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path()).arg("--min-betweenness").arg("0.5");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("CHOKE POINTS").not());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_check_reports_class_coupling() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/api.rs", "use crate::hub::Hub;\n\npub struct Api { hub: Hub }\n")?;
+    fixture.create_file("src/cli.rs", "use crate::hub::Hub;\n\npub struct Cli { hub: Hub }\n")?;
+    fixture.create_file("src/hub.rs", concat!(
+        "use crate::store::Store;\n\n",
+        "pub struct Hub { store: Store }\n\n",
+        "impl Hub {\n    fn load(&self) {}\n    fn save(&self) {}\n}\n",
+    ))?;
+    fixture.create_file("src/store.rs", "pub struct Store { rows: Vec<u64> }\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--max-methods").arg("1").arg("--format").arg("json");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let hub = report["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["class_metrics"]["name"] == "Hub")
+        .unwrap();
+    assert_eq!(hub["coupling"]["afferent"], 2);
+    assert_eq!(hub["coupling"]["efferent"], 1);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--max-methods").arg("1");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Coupling: Ca 2 | Ce 1 | Instability 0.33"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_arch_layers() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/ui/view.rs", "use crate::data::Repo;\n\npub struct View { repo: Repo }\n")?;
+    fixture.create_file("src/data/repo.rs", "pub struct Repo { rows: Vec<u64> }\n")?;
+    fixture.create_file("dei.toml", "[architecture]\nlayers = [\"ui\", \"service\", \"data\"]\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("LAYERING"))
+        .stdout(predicate::str::contains("Violations: 0"));
+    
+    // Data reaching up into the UI fails the run
+    fixture.create_file("src/data/repo.rs", "use crate::ui::View;\n\npub struct Repo { view: View }\n")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path());
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Repo (data) depends on View (ui)"))
+        .stdout(predicate::str::contains("via 1 reference"));
+    
+    // Calls name the methods making them
+    fixture.create_file("src/data/repo.rs", concat!(
+        "use crate::ui::View;\n\n",
+        "pub struct Repo;\n\n",
+        "impl Repo {\n    fn load(&self) { View::refresh(); }\n    fn save(&self) { View::refresh(); }\n}\n",
+    ))?;
+    fixture.create_file("src/ui/view.rs", "pub struct View;\n\nimpl View {\n    fn refresh() {}\n}\n")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("via 2 calls in 2 methods (load, save), 1 reference"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_path_metadata() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/billing/invoice.rs", "use crate::ui::View;\n\npub struct Invoice { view: View }\n")?;
+    fixture.create_file("src/ui/view.rs", "pub struct View { rows: Vec<u64> }\n")?;
+    fixture.create_file("dei.toml", concat!(
+        "[architecture]\nlayers = [\"ui\", \"service\", \"data\"]\n\n",
+        "[[metadata]]\npattern = \"src/billing/*\"\ntags = [\"pci\"]\nowner = \"billing-team\"\nlayer = \"data\"\n",
+    ))?;
+    fixture.create_file(
+        ".dei/rules/pci.rhai",
+        "fn inspect(class) {\n    if class.tags.contains(\"pci\") {\n        return #{ message: `${class.name} is owned by ${class.owner}`, severity: \"info\" };\n    }\n}\n",
+    )?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let result = |name: &str| {
+        report["results"].as_array().unwrap().iter().find(|r| r["class_metrics"]["name"] == name).unwrap().clone()
+    };
+    let invoice = result("Invoice");
+    assert_eq!(invoice["metadata"], serde_json::json!({ "tags": ["pci"], "owner": "billing-team", "layer": "data" }));
+    let rule = invoice["violations"].as_array().unwrap().iter().find(|v| v["kind"] == "Rule").unwrap().clone();
+    assert_eq!(rule["message"], "Invoice is owned by billing-team");
+    
+    // Files no pattern matches carry no metadata and pass the rule
+    let view = result("View");
+    assert!(view.get("metadata").is_none());
+    assert!(view["violations"].as_array().unwrap().is_empty());
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("text");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Tags: pci | Owner: billing-team | Layer: data"));
+    
+    // The assigned layer wins over the directory, so billing code reaching into the UI is flagged
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Invoice (data) depends on View (ui)"));
+    
+    Ok(())
+}
+
 #[test]
 fn test_cli_help_message() -> Result<()> {
     let mut cmd = Command::cargo_bin("dei")?;
@@ -262,7 +435,7 @@ fn test_cli_progress_indicators() -> Result<()> {
         .arg(&path);
     
     cmd.assert()
-        .success()
+        .failure() // god_method.rs is flagged
         .stdout(predicate::str::contains("✓")); // Should show progress checkmarks
     
     Ok(())
@@ -303,3 +476,2294 @@ fn test_cli_output_coloring() -> Result<()> {
     Ok(())
 }
 
+
+#[test]
+fn test_cli_ast_snippets_for_flagged_methods() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(path.join("god_method.rs"))
+        .arg("--format")
+        .arg("json")
+        .arg("--ast-snippets");
+    
+    let output = cmd.assert().failure();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let parsed: serde_json::Value = serde_json::from_str(&stdout)?;
+    
+    for result in parsed["results"].as_array().unwrap() {
+        // Healthy methods never carry snippets
+        for method in result["class_metrics"]["methods"].as_array().unwrap() {
+            assert!(method.get("ast_snippet").is_none());
+        }
+        // Flagged methods do
+        for god_method in result["god_methods"].as_array().unwrap() {
+            let snippet = god_method["metrics"]["ast_snippet"].as_str().unwrap();
+            assert!(snippet.starts_with("(function_item"));
+        }
+    }
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_api_command() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("api")
+        .arg(&path)
+        .arg("--format")
+        .arg("json")
+        .arg("--max-public-methods")
+        .arg("1");
+    
+    // Oversized APIs fail the run as findings fail `dei check`
+    let output = cmd.assert().code(1);
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let modules: serde_json::Value = serde_json::from_str(&stdout)?;
+    let modules = modules.as_array().unwrap();
+    
+    assert!(!modules.is_empty(), "Fixtures expose public methods");
+    for module in modules {
+        let public = module["public_methods"].as_u64().unwrap();
+        let flagged = !module["violations"].as_array().unwrap().is_empty();
+        assert_eq!(flagged, public > 1);
+        for ty in module["types"].as_array().unwrap() {
+            for method in ty["methods"].as_array().unwrap() {
+                assert!(method["signature"].as_str().unwrap().contains('('));
+            }
+        }
+    }
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("api").arg(&path).arg("--max-public-methods").arg("1").arg("--no-fail");
+    cmd.assert().success();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("api").arg(&path).arg("--max-public-methods").arg("1").arg("--fail-on").arg("error");
+    let output = cmd.assert();
+    let errors = modules
+        .iter()
+        .flat_map(|m| m["violations"].as_array().unwrap())
+        .any(|v| v["severity"] == "error");
+    assert_eq!(output.get_output().status.code(), Some(i32::from(errors)));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_api_skips_private_types() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.create_file("lib.rs", concat!(
+        "pub struct Client;\n",
+        "impl Client {\n    pub fn get(&self) {}\n    pub fn put(&self) {}\n}\n",
+        "struct Pool;\n",
+        "impl Pool {\n    pub fn take(&self) {}\n    pub fn give(&self) {}\n}\n",
+    ))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("api").arg(&path).arg("--format").arg("json").arg("--max-public-methods").arg("2");
+    let output = cmd.assert().success();
+    let modules: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    let module = &modules[0];
+    assert_eq!(module["public_methods"], 2);
+    let types: Vec<&str> = module["types"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(types, ["Client"]);
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_check_custom_template() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    
+    fixture.create_file("templates/brand.md", "# ACME Code Health\n")?;
+    let template = fixture.create_file("templates/report.md", concat!(
+        "{% include \"brand.md\" %}",
+        "God classes: {{ summary.god_classes }}\n",
+        "{% for r in results %}{% if r.is_god_class %}- {{ r.class_metrics.name }}\n{% endif %}{% endfor %}",
+    ))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(path.join("god_class.rs"))
+        .arg("--max-lines")
+        .arg("100")
+        .arg("--template")
+        .arg(&template);
+    
+    let output = cmd.assert().failure();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    
+    assert!(stdout.starts_with("# ACME Code Health\n"), "Template replaces the banner");
+    assert!(stdout.contains("God classes: 1"));
+    assert!(stdout.contains("- MegaUserManager"));
+    assert!(!stdout.contains("SUMMARY"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_check_changed_since() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let god_class = include_str!("../fixtures/rust/god_class.rs");
+    fixture.create_file("project/stable.rs", god_class)?;
+    fixture.create_file("project/growing.rs", god_class)?;
+
+    // Commit both files as the base revision
+    let project = fixture.path().join("project");
+    let repo = git2::Repository::init(&project)?;
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("Dev", "dev@example.com")?;
+    repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])?;
+
+    // Grow one god class in the working tree
+    let grown = god_class.replacen(
+        "impl MegaUserManager {",
+        "impl MegaUserManager {\n    pub fn audit(&self, on: bool) -> bool {\n        if on { self.users.is_empty() } else { false }\n    }\n",
+        1,
+    );
+    fixture.create_file("project/growing.rs", &grown)?;
+
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(&project)
+        .arg("--max-lines")
+        .arg("100")
+        .arg("--max-methods")
+        .arg("10")
+        .arg("--changed-since")
+        .arg("HEAD")
+        .arg("--format")
+        .arg("json");
+    
+    let output = cmd.assert().failure();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let results: serde_json::Value = serde_json::from_str(&stdout)?;
+    let results = results["results"].as_array().unwrap();
+    
+    assert!(!results.is_empty());
+    for result in results {
+        let file = result["class_metrics"]["file_path"].as_str().unwrap();
+        assert!(file.ends_with("growing.rs"), "Unchanged files are skipped: {}", file);
+    }
+    
+    let manager = results
+        .iter()
+        .find(|r| r["class_metrics"]["name"] == "MegaUserManager" && r["is_god_class"] == true)
+        .unwrap();
+    assert_eq!(manager["base_comparison"]["trend"], "Worse");
+    assert_eq!(manager["base_comparison"]["methods_delta"], 1);
+    
+    // Nothing changed since the working tree state was committed
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = repo.head()?.peel_to_commit()?;
+    repo.commit(Some("HEAD"), &signature, &signature, "grow", &tree, &[&parent])?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(&project)
+        .arg("--max-lines")
+        .arg("100")
+        .arg("--changed-since")
+        .arg("HEAD");
+    
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0 file(s) changed since HEAD"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_slow_file_guardrails() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    
+    // A zero budget makes every file "slow"; by default it is only reported
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(path.join("god_class.rs"))
+        .arg("--max-lines")
+        .arg("100")
+        .arg("--max-file-seconds")
+        .arg("0");
+    
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("GOD CLASS"))
+        .stderr(predicate::str::contains("Slow file:"))
+        .stderr(predicate::str::contains("god_class.rs (Rust,"))
+        .stderr(predicate::str::contains("skipped").not());
+    
+    // With skipping, the pathological file no longer dominates the report
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(path.join("god_class.rs"))
+        .arg("--max-lines")
+        .arg("100")
+        .arg("--max-file-seconds")
+        .arg("0")
+        .arg("--skip-slow-files")
+        .arg("--format")
+        .arg("json");
+    
+    let output = cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("to parse - skipped"));
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let results: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert!(results["results"].as_array().unwrap().is_empty());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_skips_minified_assets() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let source = "class Widget {\n  render(items) {\n    return items.length;\n  }\n}\n";
+    let bundle: String = (0..100).map(|i| format!("function f{}(a){{return a+{}}}", i, i)).collect();
+    
+    fixture.create_file("web/app.js", source)?;
+    fixture.create_file("web/vendor.min.js", source)?;
+    fixture.create_file("web/bundle.js", &bundle)?;
+    fixture.create_file("web/compiled.js", &format!("{}//# sourceMappingURL=compiled.js.map\n", source))?;
+    fixture.create_file("web/mapped.js", source)?;
+    fixture.create_file("web/mapped.js.map", "{}")?;
+    
+    let analyzed_files = |extra: &[&str]| -> Result<Vec<String>> {
+        let mut cmd = Command::cargo_bin("dei")?;
+        cmd.arg("check")
+            .arg(fixture.path().join("web"))
+            .arg("--format")
+            .arg("json")
+            .args(extra);
+        let output = cmd.output()?;
+        let results: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let mut files: Vec<String> = results["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["class_metrics"]["file_path"].as_str().unwrap().rsplit('/').next().unwrap().to_string())
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    };
+    
+    assert_eq!(analyzed_files(&[])?, ["app.js"]);
+    assert_eq!(
+        analyzed_files(&["--include-minified"])?,
+        ["app.js", "bundle.js", "compiled.js", "mapped.js", "vendor.min.js"]
+    );
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("web"));
+    cmd.assert()
+        .stdout(predicate::str::contains("Skipped 4 minified/compiled file(s)"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_dei_toml_config() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let god_class = include_str!("../fixtures/rust/god_class.rs");
+    fixture.create_file("project/src/manager.rs", god_class)?;
+    fixture.create_file("project/generated/manager.rs", god_class)?;
+    fixture.create_file("project/dei.toml", concat!(
+        "format = \"json\"\n",
+        "ignore = [\"generated\"]\n",
+        "\n",
+        "[thresholds]\n",
+        "max_class_lines = 100\n",
+        "max_methods = 10\n",
+        "\n",
+        "[smells]\n",
+        "god_method = false\n",
+    ))?;
+    
+    // Format, thresholds, ignores and smells all come from the file
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project"));
+    
+    let output = cmd.assert().failure();
+    let results: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let results = results["results"].as_array().unwrap();
+    
+    assert!(results.iter().any(|r| r["class_metrics"]["name"] == "MegaUserManager" && r["is_god_class"] == true));
+    for result in results {
+        assert!(result["god_methods"].as_array().unwrap().is_empty(), "God methods are disabled");
+        let file = result["class_metrics"]["file_path"].as_str().unwrap();
+        assert!(!file.contains("generated"), "Ignored path analyzed: {}", file);
+    }
+    
+    // Found from a subdirectory, and flags win over file values
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(fixture.path().join("project/src"))
+        .arg("--format")
+        .arg("text")
+        .arg("--max-lines")
+        .arg("10000")
+        .arg("--max-methods")
+        .arg("1000")
+        .arg("--max-complexity")
+        .arg("1000");
+    
+    cmd.assert()
+        .stdout(predicate::str::contains("Config:"))
+        .stdout(predicate::str::contains("dei.toml"))
+        .stdout(predicate::str::contains("GOD CLASS").not());
+    
+    // Typos are reported instead of silently ignored
+    fixture.create_file("project/dei.toml", "[thresholds]\nmax_class_line = 100\n")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("max_class_line"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_violation_severity_policy() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/src/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    let policy = concat!(
+        "format = \"json\"\n",
+        "\n",
+        "[violations]\n",
+        "ParameterCount = \"off\"\n",
+        "Lines = \"info\"\n",
+        "MethodCount = \"info\"\n",
+        "NestingDepth = \"info\"\n",
+        "MessageChain = \"info\"\n",
+        "ExitPoints = \"info\"\n",
+        "MagicLiterals = \"info\"\n",
+        "LowCohesion = \"info\"\n",
+        "FieldCount = \"info\"\n",
+        "DependencyCount = \"info\"\n",
+        "ClassesPerFile = \"info\"\n",
+    );
+    
+    // Everything left is informational, so the check passes
+    fixture.create_file("project/dei.toml", &format!("{}Complexity = \"info\"\n", policy))?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project"));
+    
+    let output = cmd.assert().success();
+    let results: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let violations: Vec<&serde_json::Value> = results["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|r| {
+            let class = r["violations"].as_array().unwrap().iter();
+            let god_class = r["god_class_violations"].as_array().into_iter().flatten();
+            let god_methods = r["god_methods"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .flat_map(|m| m["violations"].as_array().unwrap());
+            class.chain(god_class).chain(god_methods)
+        })
+        .collect();
+    
+    assert!(!violations.is_empty());
+    assert!(violations.iter().all(|v| v["severity"] == "info"));
+    assert!(violations.iter().all(|v| v["kind"] != "ParameterCount"), "Disabled kind reported");
+    
+    // Escalating one kind fails the check again
+    fixture.create_file("project/dei.toml", &format!("{}Complexity = \"error\"\n", policy))?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project"));
+    
+    let output = cmd.assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("\"severity\": \"error\""));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_duplicate_class_names() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let user = "pub struct User { id: u64 }\n\nimpl User {\n    pub fn id(&self) -> u64 { self.id }\n}\n";
+    fixture.create_file("project/billing/user.rs", user)?;
+    fixture.create_file("project/auth/user.rs", user)?;
+    // Synthetic module classes named after the file are not declared names
+    fixture.create_file("project/web/index.js", "function render() { return 1; }\n")?;
+    fixture.create_file("project/api/index.js", "function route() { return 2; }\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("text");
+    
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("DUPLICATE CLASS NAMES"), "{}", stdout);
+    assert!(stdout.contains("User (2 definitions)"), "{}", stdout);
+    assert!(!stdout.contains("index ("), "{}", stdout);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("json");
+    
+    let output = cmd.assert().success();
+    let results: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let users: Vec<&serde_json::Value> = results["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["class_metrics"]["name"] == "User")
+        .collect();
+    
+    assert_eq!(users.len(), 2);
+    for user in users {
+        let clashes = user["name_clashes"].as_array().unwrap();
+        assert_eq!(clashes.len(), 1);
+        assert_ne!(clashes[0]["file_path"], user["class_metrics"]["file_path"]);
+    }
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_namespace_pollution() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/app.py", "from os.path import *\nimport json\n")?;
+    fixture.create_file("project/dei.toml", "[thresholds]\nmax_wildcard_imports = 0\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("text");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("NAMESPACE POLLUTION"))
+        .stdout(predicate::str::contains("Wildcards: os.path"));
+    
+    // The violation policy governs import findings like any other kind
+    fixture.create_file(
+        "project/dei.toml",
+        "[thresholds]\nmax_wildcard_imports = 0\n\n[violations]\nWildcardImports = \"info\"\n",
+    )?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("text");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[info]"));
+    
+    // Turned off like any other smell
+    fixture.create_file(
+        "project/dei.toml",
+        "[thresholds]\nmax_wildcard_imports = 0\n\n[smells]\nimport_hygiene = false\n",
+    )?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("text");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("NAMESPACE POLLUTION").not());
+    
+    // And replaced by a rule judging the file instead
+    fixture.create_file(
+        "project/.dei/rules/no_wildcards.rhai",
+        "fn inspect_file(file) {\n    if file.wildcard_imports.len() > 0 {\n        return `wildcards from ${file.wildcard_imports[0]}`;\n    }\n}\n",
+    )?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("text");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("NAMESPACE POLLUTION"))
+        .stdout(predicate::str::contains("wildcards from os.path"))
+        .stdout(predicate::str::contains("WildcardImports").not());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_fail_on_severity() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    
+    // Barely over the limit: a warning, reported but tolerated by --fail-on error
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(path.join("god_class.rs")).arg("--max-methods").arg("20").arg("--fail-on").arg("error");
+    cmd.assert().success().stdout(predicate::str::contains("GOD CLASSES DETECTED"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(path.join("god_class.rs")).arg("--max-methods").arg("20").arg("--format").arg("json");
+    let output = cmd.assert().failure();
+    let results: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let severities: Vec<String> = results["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|r| r["god_class_violations"].as_array().into_iter().flatten())
+        .map(|v| v["severity"].as_str().unwrap().to_string())
+        .collect();
+    assert!(!severities.is_empty());
+    assert!(severities.iter().all(|s| s == "warning"), "{:?}", severities);
+    
+    // Far over the limit: an error fails even with --fail-on error
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(path.join("god_class.rs")).arg("--max-methods").arg("2").arg("--fail-on").arg("error");
+    cmd.assert().failure();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(path.join("god_class.rs")).arg("--fail-on").arg("fatal");
+    cmd.assert().failure().stderr(predicate::str::contains("unknown severity"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_max_issues_and_summary_line() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--max-issues").arg("0");
+    let output = cmd.assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    let line = stdout.lines().last().unwrap_or_default().to_string();
+    assert!(line.starts_with("dei: 1 god classes, "), "{}", line);
+    assert!(line.ends_with(" new"), "{}", line);
+    
+    // Tolerating every finding passes, as does --no-fail
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--max-issues").arg("1000");
+    cmd.assert().success();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--no-fail");
+    cmd.assert().success();
+    
+    // JSON keeps stdout clean; the line goes to stderr
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success().stderr(predicate::str::contains("dei: 1 god classes"));
+    serde_json::from_slice::<serde_json::Value>(&output.get_output().stdout)?;
+    
+    // Accepted findings are no longer new, even when --no-baseline reports them
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("triage").arg(fixture.path()).arg("--accept-all");
+    cmd.assert().success();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--no-baseline").arg("--no-fail");
+    cmd.assert().success().stdout(predicate::str::contains("dei: 1 god classes").and(predicate::str::contains(", 0 new")));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_reports_god_files() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let source: String = ["Alpha", "Beta", "Gamma", "Delta"]
+        .iter()
+        .map(|name| format!("pub struct {name} {{ value: u32 }}\n\nimpl {name} {{\n    pub fn value(&self) -> u32 {{\n        self.value\n    }}\n}}\n\n"))
+        .collect();
+    fixture.create_file("crowded.rs", &source)?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("GOD FILES DETECTED"))
+        .stdout(predicate::str::contains("crowded.rs"))
+        .stdout(predicate::str::contains("dei: 0 god classes, 0 god methods, 1 god files"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let god_files = report["god_files"].as_array().unwrap();
+    assert_eq!(god_files.len(), 1);
+    assert_eq!(god_files[0]["class_count"], 4);
+    assert_eq!(god_files[0]["violations"][0]["kind"], "ClassesPerFile");
+    
+    // Other --only kinds leave files out, and with them their exit-code contribution
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--only").arg("god-class");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("GOD FILES DETECTED").not());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_suggest_extractions() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    
+    let extractions = |extra: &[&str]| -> Result<usize> {
+        let mut cmd = Command::cargo_bin("dei")?;
+        cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").args(extra);
+        let output = cmd.assert().failure();
+        let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+        Ok(report["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["suggested_extractions"].as_array().unwrap().len())
+            .sum())
+    };
+    
+    assert_eq!(extractions(&[])?, 0);
+    assert!(extractions(&["--suggest-extractions"])? > 0);
+    
+    // Clustering quality comes along with the suggestions
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--suggest-extractions");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let stats = report["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["class_metrics"]["name"] == "MegaUserManager")
+        .map(|r| r["cluster_stats"].clone())
+        .unwrap();
+    assert!(stats["n_clusters"].is_u64());
+    let noise = stats["noise_ratio"].as_f64().unwrap();
+    assert!((0.0..=1.0).contains(&noise));
+    assert!(stats["silhouette"].is_null() || stats["silhouette"].as_f64().unwrap().abs() <= 1.0);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--suggest-extractions").arg("--verbose");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Suggested Extractions:"))
+        .stdout(predicate::str::contains("Clustering:"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_vendored_code() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/src/healthy.rs", include_str!("../fixtures/rust/healthy.rs"))?;
+    fixture.create_file("project/vendor/lib/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--format").arg("text");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped 1 vendored"))
+        .stdout(predicate::str::contains("EXTERNAL CODE").not());
+    
+    // Analyzed into its own section, still without failing the check
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("project")).arg("--include-vendored").arg("--verbose");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("EXTERNAL CODE"))
+        .stdout(predicate::str::contains("vendor/lib/manager.rs"))
+        .stdout(predicate::str::contains("GOD CLASSES DETECTED").not());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_include_exclude_globs() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/healthy.rs", include_str!("../fixtures/rust/healthy.rs"))?;
+    fixture.create_file("src/generated/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("tests/shop.py", include_str!("../fixtures/python/shop.py"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path())
+        .arg("--exclude").arg("generated/")
+        .arg("--exclude").arg("tests");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("GOD CLASSES DETECTED").not());
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path())
+        .arg("--include").arg("*.py")
+        .arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    let results = report["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r["class_metrics"]["file_path"].as_str().unwrap().ends_with("shop.py")));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--include").arg("src/**");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("GOD CLASSES DETECTED"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_language_selection() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("GodClass.java", include_str!("../fixtures/java/GodClass.java"))?;
+    fixture.create_file("shop.py", include_str!("../fixtures/python/shop.py"))?;
+    
+    let files = |args: &[&str]| -> Result<Vec<String>> {
+        let mut cmd = Command::cargo_bin("dei")?;
+        cmd.arg("check").arg(fixture.path()).args(args).arg("--format").arg("json");
+        let output = cmd.output()?;
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let mut files: Vec<String> = report["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["class_metrics"]["file_path"].as_str().unwrap().rsplit('/').next().unwrap().to_string())
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    };
+    
+    assert_eq!(files(&["--lang", "rust,python"])?, ["god_class.rs", "shop.py"]);
+    assert_eq!(files(&["--exclude-lang", "rust", "--exclude-lang", "java"])?, ["shop.py"]);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--lang").arg("cobol");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown language 'cobol'"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_html_report() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--format").arg("html");
+    
+    let output = cmd.assert().failure();
+    let html = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(html.starts_with("<!DOCTYPE html>"), "Banner leaked into the report");
+    assert!(html.contains("data-name=\"MegaUserManager\""));
+    assert!(html.contains("badge god"));
+    assert!(html.contains("<h3>God methods</h3>"));
+    assert!(html.contains("<script>"), "Report is not self-contained");
+    assert!(html.contains("<h2>Distribution</h2>"));
+    assert!(html.contains("class=\"histogram\""));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_report_metadata() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("project/helpers.py", "def helper():\n    return 1\n")?;
+    let project = fixture.path().join("project");
+    let repo = git2::Repository::init(&project)?;
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("Dev", "dev@example.com")?;
+    let commit = repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])?.to_string();
+
+    // Enough to rerun the analysis: version, path, commit, the limits after overrides, file counts
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project).arg("--format").arg("json").arg("--max-methods").arg("12").arg("--no-fail");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let metadata = &report["metadata"];
+    assert_eq!(metadata["tool"], "dei");
+    assert_eq!(metadata["version"], env!("CARGO_PKG_VERSION"));
+    assert!(metadata["generated_at"].as_u64().unwrap() > 0);
+    assert_eq!(metadata["path"], project.canonicalize()?.to_str().unwrap());
+    assert_eq!(metadata["commit"], commit.as_str());
+    assert_eq!(metadata["thresholds"]["max_methods"], 12);
+    assert_eq!(metadata["files"]["analyzed"], 2);
+    assert_eq!(metadata["files"]["skipped"], 0);
+
+    // The HTML report shows the same
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project).arg("--format").arg("html").arg("--max-methods").arg("12").arg("--no-fail");
+    let output = cmd.assert().success();
+    let html = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(html.contains(&commit), "commit missing from the HTML report");
+    assert!(html.contains("2 files analyzed"));
+    assert!(html.contains("<td>max_methods</td><td class=\"num\">12</td>"));
+
+    // Outside a repository there is no commit
+    let outside = FixtureManager::new()?;
+    outside.create_file("manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(outside.path().join("manager.rs")).arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert!(report["metadata"].get("commit").is_none());
+    assert_eq!(report["metadata"]["files"]["analyzed"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_resource_limits() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.copy_fixture("rust")?;
+    fixture.copy_fixture("python")?;
+
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success();
+    let unlimited: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+
+    // One thread, a generous memory cap and low priority change nothing in the results
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("--threads").arg("1").arg("--nice").arg("check").arg(fixture.path())
+        .arg("--max-memory").arg("2GiB").arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success();
+    let limited: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert_eq!(limited["summary"], unlimited["summary"]);
+    // Order across files follows which thread finished first
+    let classes = |report: &serde_json::Value| {
+        let mut classes: Vec<String> = report["results"].as_array().unwrap().iter().map(|r| r.to_string()).collect();
+        classes.sort();
+        classes
+    };
+    assert_eq!(classes(&limited), classes(&unlimited));
+
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--threads").arg("0");
+    cmd.assert().failure().stderr(predicate::str::contains("--threads"));
+
+    // A cap too small to start the worker threads under is refused before anything runs
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--max-memory").arg("1K");
+    cmd.assert().code(2).stderr(predicate::str::contains("--max-memory").and(predicate::str::contains("minimum")));
+
+    Ok(())
+}
+
+/// Every thread of a `--nice` run starts at the lowered priority, not just the main thread
+#[cfg(target_os = "linux")]
+#[test]
+fn test_cli_nice_reaches_worker_threads() -> Result<()> {
+    use std::io::BufRead;
+
+    let fixture = FixtureManager::new()?;
+    fixture.copy_fixture("rust")?;
+
+    // Long-running, so its threads can be looked at once the first analysis is done
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin("dei"))
+        .arg("--threads").arg("2").arg("--nice")
+        .arg("serve").arg(fixture.path())
+        .arg("--port").arg("0")
+        .arg("--interval").arg("0")
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    let mut banner = String::new();
+    std::io::BufReader::new(server.stdout.take().unwrap()).read_line(&mut banner)?;
+
+    let checks = (|| -> Result<()> {
+        let mut niceness = Vec::new();
+        for task in std::fs::read_dir(format!("/proc/{}/task", server.id()))? {
+            let stat = std::fs::read_to_string(task?.path().join("stat"))?;
+            // Fields after the command name, which may hold spaces; nice is the 19th overall
+            let fields: Vec<&str> = stat.rsplit_once(')').unwrap().1.split_whitespace().collect();
+            niceness.push(fields[16].parse::<i32>()?);
+        }
+        // The main thread, two pool workers and the runtime's
+        anyhow::ensure!(niceness.len() > 3, "{:?}", niceness);
+        anyhow::ensure!(niceness.iter().all(|&n| n == 10), "{:?}", niceness);
+        Ok(())
+    })();
+
+    server.kill()?;
+    server.wait()?;
+    checks
+}
+
+#[test]
+fn test_cli_group_by() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.copy_fixture("rust")?;
+    fixture.copy_fixture("java")?;
+
+    // Groups replace the per-class sections; the worst classes are named under each
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--group-by").arg("language").arg("--no-fail");
+    let output = cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("SUMMARY:"))
+        .stdout(predicate::str::contains("BY LANGUAGE:"))
+        .stdout(predicate::str::contains("GOD CLASSES DETECTED").not());
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let rust = stdout.find("\n  Rust (").expect("no Rust group");
+    let java = stdout.find("\n  Java (").expect("no Java group");
+    let mega = stdout.find("MegaUserManager (").expect("worst Rust class not listed");
+    assert!(rust < mega && (mega < java || java < rust), "MegaUserManager listed outside its group:\n{}", stdout);
+
+    // Projects are the nearest directories with a build manifest, up to the analyzed path
+    fixture.create_file("rust/Cargo.toml", "[package]\nname = \"fixture\"\n")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--group-by").arg("project").arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let groups = report["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    let names: Vec<&str> = groups.iter().map(|g| g["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&fixture.path().join("rust").to_str().unwrap()), "{:?}", names);
+    assert!(names.contains(&fixture.path().to_str().unwrap()), "{:?}", names);
+    let total: u64 = groups.iter().map(|g| g["summary"]["total_classes"].as_u64().unwrap()).sum();
+    assert_eq!(total, report["summary"]["total_classes"].as_u64().unwrap());
+    assert!(groups[0]["worst"].as_array().unwrap().len() <= 3);
+
+    // Without --group-by the JSON has no groups
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert!(report.get("groups").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_include_all_methods() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.copy_fixture("rust")?;
+
+    // Every method is listed, even with the per-class results trimmed
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--summary").arg("--include-all-methods").arg("--no-fail");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert!(report["results"].as_array().unwrap().is_empty());
+    let methods = report["methods"].as_array().unwrap().clone();
+    assert!(methods.iter().any(|m| m["is_god_method"] == true));
+    assert!(methods.iter().any(|m| m["is_god_method"] == false));
+    let method = &methods[0];
+    assert!(method["class_name"].is_string() && method["file_path"].is_string());
+    assert!(method["metrics"]["complexity"].is_u64() && method["violation_score"].is_number());
+
+    // Without the flag there is no methods array
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--no-fail");
+    let output = cmd.assert().success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert!(report.get("methods").is_none());
+    let method_count: u64 = report["results"].as_array().unwrap().iter()
+        .map(|r| r["class_metrics"]["method_count"].as_u64().unwrap())
+        .sum();
+    assert_eq!(methods.len() as u64, method_count);
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_verbose_distribution_histograms() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--max-lines").arg("100").arg("--verbose");
+
+    // MegaUserManager is past the 100-line limit, so the open-ended buckets aren't all empty
+    let output = cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("DISTRIBUTION:"))
+        .stdout(predicate::str::contains("Class lines (max 100,"))
+        .stdout(predicate::str::contains("Method complexity (max 10,"))
+        .stdout(predicate::str::contains("0-25 "));
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let class_lines: Vec<&str> = stdout
+        .lines()
+        .skip_while(|line| !line.contains("Class lines"))
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .collect();
+    // Quarters of 100 up to 200, then the open bucket
+    assert_eq!(class_lines.len(), 9);
+    let over: usize = class_lines[4..]
+        .iter()
+        .filter_map(|line| line.split_whitespace().last()?.parse::<usize>().ok())
+        .sum();
+    assert!(over >= 1, "No class counted past the threshold:\n{}", stdout);
+
+    // Only the verbose report has them
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--max-lines").arg("100");
+    cmd.assert().stdout(predicate::str::contains("DISTRIBUTION:").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_filter_expression() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.copy_fixture("rust")?;
+    fixture.copy_fixture("java")?;
+    
+    let names = |filter: &str| -> Result<Vec<String>> {
+        let mut cmd = Command::cargo_bin("dei")?;
+        cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--filter").arg(filter);
+        let output = cmd.output()?;
+        let results: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        Ok(results["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["class_metrics"]["name"].as_str().unwrap().to_string())
+            .collect())
+    };
+    
+    assert_eq!(names(r#"lang == "rust" && god_class"#)?, ["MegaUserManager"]);
+    
+    let java = names(r#"path ~ "java/**" && complexity >= 1"#)?;
+    assert!(!java.is_empty());
+    assert!(!java.contains(&"MegaUserManager".to_string()));
+    
+    assert!(names(r#"!(lang == "Rust") && lang != "java""#)?.is_empty());
+    
+    // Filtered-out findings no longer fail the check
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--filter").arg("healthy");
+    cmd.assert().success();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--filter").arg("complexity ~ \"3\"");
+    cmd.assert().failure().stderr(predicate::str::contains("invalid filter"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_diff_file() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let god_class = include_str!("../fixtures/rust/god_class.rs");
+    let path = fixture.create_file("project/manager.rs", god_class)?;
+    
+    let project = fixture.path().join("project");
+    let repo = git2::Repository::init(&project)?;
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("Dev", "dev@example.com")?;
+    repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])?;
+    
+    let grown = god_class.replacen(
+        "impl MegaUserManager {",
+        "impl MegaUserManager {\n    pub fn audit(&self, on: bool) -> bool {\n        if on { self.users.is_empty() } else { false }\n    }\n",
+        1,
+    );
+    fixture.create_file("project/manager.rs", &grown)?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("diff-file").arg(&path).arg("--base").arg("HEAD").arg("--format").arg("json");
+    
+    let output = cmd.assert().success();
+    let diffs: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let manager = diffs
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"] == "MegaUserManager")
+        .unwrap();
+    
+    assert_eq!(manager["change"], "modified");
+    assert_eq!(manager["trend"], "Worse");
+    assert_eq!(manager["methods"]["after"].as_u64().unwrap() - manager["methods"]["before"].as_u64().unwrap(), 1);
+    let audit = manager["method_diffs"].as_array().unwrap().iter().find(|m| m["name"] == "audit").unwrap();
+    assert_eq!(audit["change"], "added");
+    assert!(diffs.as_array().unwrap().iter().any(|d| d["change"] == "unchanged"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("diff-file").arg(&path).arg("--base").arg("HEAD");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("MegaUserManager"))
+        .stdout(predicate::str::contains("audit"));
+    
+    // A deleted file shows every class as removed
+    std::fs::remove_file(&path)?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("diff-file").arg(&path).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let diffs: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert!(diffs.as_array().unwrap().iter().all(|d| d["change"] == "removed"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_explain() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/src/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("project/src/app.rs", r#"
+use crate::manager::MegaUserManager;
+
+pub struct App {
+    manager: MegaUserManager,
+}
+
+impl App {
+    pub fn start(&mut self) {
+        self.manager.list_users();
+    }
+}
+"#)?;
+    let project = fixture.path().join("project");
+    let target = format!("{}::MegaUserManager", project.join("src/manager.rs").display());
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("explain").arg(&target).arg("--path").arg(&project).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let explanation: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    assert_eq!(explanation["class"], "MegaUserManager");
+    assert_eq!(explanation["is_god_class"], true);
+    let method_count = explanation["violations"].as_array().unwrap().iter().find(|v| v["kind"] == "MethodCount").unwrap();
+    assert_eq!(method_count["overrun"], method_count["actual"].as_u64().unwrap() - method_count["threshold"].as_u64().unwrap());
+    assert_eq!(explanation["method_details"].as_array().unwrap().len(), explanation["methods"].as_u64().unwrap() as usize);
+    assert!(explanation["cohesion"]["lcom4"].as_u64().unwrap() > 1);
+    assert!(explanation["coupling"]["fan_in"].as_array().unwrap().iter().any(|c| c == "App"));
+    
+    // A file declaring several classes needs the class named
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("explain").arg(project.join("src/manager.rs")).arg("--path").arg(&project);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("declares several classes"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("explain").arg(project.join("src/app.rs")).arg("--path").arg(&project);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("DEI - EXPLAIN"))
+        .stdout(predicate::str::contains("Fan-out"))
+        .stdout(predicate::str::contains("MegaUserManager"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("explain").arg(format!("{}::Missing", project.join("src/manager.rs").display())).arg("--path").arg(&project);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No class 'Missing'"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_plan() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/src/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("project/src/payments.rs", include_str!("../fixtures/rust/god_method.rs"))?;
+    fixture.create_file("project/src/healthy.rs", include_str!("../fixtures/rust/healthy.rs"))?;
+    let project = fixture.path().join("project");
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("plan").arg(&project).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let plan: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    let steps = plan["steps"].as_array().unwrap();
+    assert!(!steps.is_empty());
+    for (i, pair) in steps.windows(2).enumerate() {
+        assert_eq!(pair[0]["rank"], i + 1);
+        assert!(pair[0]["priority"].as_f64().unwrap() >= pair[1]["priority"].as_f64().unwrap());
+    }
+    // Extractions split the class rather than move all of it
+    assert!(steps.iter().any(|s| s["kind"] == "extract-class"
+        && s["class"] == "MegaUserManager"
+        && (1..36).contains(&s["methods"].as_array().unwrap().len())));
+    assert!(steps.iter().any(|s| s["kind"] == "split-method" && s["target"] == "process_complex_payment"));
+    assert!(steps.iter().all(|s| s["effort"] == "small" || s["effort"] == "medium" || s["effort"] == "large"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("plan").arg(&project).arg("--top").arg("1");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("REFACTORING PLAN"))
+        .stdout(predicate::str::contains(" 1. ["))
+        .stdout(predicate::str::contains(" 2. [").not());
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_refactor_scaffold() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("project/src/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("project/src/shop.py", include_str!("../fixtures/python/shop.py"))?;
+    let project = fixture.path().join("project");
+    let repo = git2::Repository::init(&project)?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.current_dir(&project)
+        .arg("refactor")
+        .arg("src/manager.rs::MegaUserManager")
+        .arg("--methods").arg("cache_get,cache_set,cache_clear")
+        .arg("--name").arg("CacheStore")
+        .arg("--dry-run");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("+++ b/src/cache_store.rs"))
+        .stdout(predicate::str::contains("+pub struct CacheStore {"))
+        .stdout(predicate::str::contains("+    cache: HashMap<String, String>,"))
+        .stdout(predicate::str::contains("+    pub fn cache_get(&self, key: &str) -> Option<&String> {"))
+        .stdout(predicate::str::contains("todo!(\"move the body of MegaUserManager::cache_clear\")"))
+        .stdout(predicate::str::contains("users:").not());
+    assert!(!project.join("src/cache_store.rs").exists());
+    
+    // The written patch applies cleanly
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.current_dir(&project)
+        .arg("refactor")
+        .arg("src/manager.rs::MegaUserManager")
+        .arg("--methods").arg("cache_get,cache_set,cache_clear")
+        .arg("--name").arg("CacheStore")
+        .arg("--output").arg(fixture.path().join("cache.patch"));
+    cmd.assert().success().stdout(predicate::str::contains("Scaffolded CacheStore"));
+    let diff = git2::Diff::from_buffer(&std::fs::read(fixture.path().join("cache.patch"))?)?;
+    repo.apply(&diff, git2::ApplyLocation::WorkDir, None)?;
+    let skeleton = std::fs::read_to_string(project.join("src/cache_store.rs"))?;
+    assert!(skeleton.contains("impl CacheStore {"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.current_dir(&project).arg("refactor").arg("src/manager.rs::MegaUserManager").arg("--methods").arg("nope").arg("--dry-run");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("has no method nope"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.current_dir(&project).arg("refactor").arg("src/shop.py").arg("--dry-run");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("supports Rust and C#"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_compare() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("before/src/manager.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("before/src/healthy.rs", include_str!("../fixtures/rust/healthy.rs"))?;
+    fixture.create_file("after/src/healthy.rs", include_str!("../fixtures/rust/healthy.rs"))?;
+    fixture.create_file("after/src/Orders.cs", include_str!("../fixtures/csharp/GodClass.cs"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("compare").arg(fixture.path().join("before")).arg(fixture.path().join("after")).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let comparison: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    assert_eq!(comparison["new_god_classes"], 1);
+    assert_eq!(comparison["resolved_god_classes"], 1);
+    let classes = comparison["classes"].as_array().unwrap();
+    let manager = classes.iter().find(|c| c["name"] == "MegaUserManager").unwrap();
+    assert_eq!(manager["file"], "src/manager.rs");
+    assert_eq!(manager["change"], "removed");
+    assert_eq!(manager["was_god_class"], true);
+    assert!(classes.iter().any(|c| c["file"] == "src/Orders.cs" && c["is_god_class"] == true && c["change"] == "added"));
+    assert!(classes.iter().filter(|c| c["file"] == "src/healthy.rs").all(|c| c["change"] == "unchanged"));
+    
+    // Git refs are checked out on the side, leaving the working tree alone
+    let project = fixture.path().join("before");
+    let repo = git2::Repository::init(&project)?;
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("Dev", "dev@example.com")?;
+    repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])?;
+    std::fs::remove_file(project.join("src/manager.rs"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("compare").arg("HEAD").arg(&project).arg("--path").arg(&project);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("RESOLVED GOD CLASSES"))
+        .stdout(predicate::str::contains("MegaUserManager"));
+    assert!(!project.join("src/manager.rs").exists());
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("compare").arg("no-such-ref").arg(&project).arg("--path").arg(&project);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("neither a path nor a git ref"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_simulate_split() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("shop.py", include_str!("../fixtures/python/shop.py"))?;
+    fixture.create_file("dei.toml", concat!(
+        "[thresholds]\n",
+        "max_methods = 4\n",
+        "\n",
+        "[[thresholds.cluster_seeds]]\n",
+        "name = \"Notifier\"\n",
+        "patterns = [\"notify_*\"]\n",
+    ))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("simulate").arg(fixture.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Notifier"))
+        .stdout(predicate::str::contains("--apply-clusters"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("simulate").arg(fixture.path()).arg("--apply-clusters").arg("--format").arg("json");
+    
+    let output = cmd.assert().success();
+    let proposals: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let shop = &proposals.as_array().unwrap()[0];
+    let simulation = &shop["simulation"];
+    
+    assert_eq!(shop["class"], "Shop");
+    assert_eq!(shop["resolved"], true);
+    assert_eq!(simulation["before"]["class_metrics"]["method_count"], 7);
+    let notifier = simulation["extracted"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["class_metrics"]["name"] == "Notifier")
+        .unwrap();
+    assert_eq!(notifier["class_metrics"]["method_count"], 3);
+    assert_eq!(notifier["class_metrics"]["field_count"], 2);
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_triage() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    // Triage covers classes and methods; keep the fixture from being a god file
+    fixture.create_file("dei.toml", "[thresholds]\nmax_classes_per_file = 10\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("triage").arg(fixture.path()).write_stdin("d\ns\nGenerated code\n");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("MegaUserManager"))
+        .stdout(predicate::str::contains("1 suppressed"));
+    
+    let suppressions = std::fs::read_to_string(fixture.path().join("dei-suppressions.toml"))?;
+    assert!(suppressions.contains("class = \"MegaUserManager\""));
+    assert!(suppressions.contains("reason = \"Generated code\""));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path());
+    cmd.assert().success();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--no-baseline");
+    cmd.assert().failure();
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_triage_accept_all() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    // Triage covers classes and methods; keep the fixture from being a god file
+    fixture.create_file("dei.toml", "[thresholds]\nmax_classes_per_file = 10\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("triage").arg(fixture.path()).arg("--accept-all");
+    cmd.assert().success();
+    
+    let baseline: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(fixture.path().join("dei-baseline.json"))?)?;
+    assert_eq!(baseline["accepted"][0]["class"], "MegaUserManager");
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("hidden"));
+    
+    // Nothing new is left to triage
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("triage").arg(fixture.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No new findings"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_metrics_csv() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("shop.py", include_str!("../fixtures/python/shop.py"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("metrics").arg(fixture.path());
+    
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    let rows: Vec<Vec<&str>> = stdout.lines().map(|l| l.split(',').collect()).collect();
+    let header = &rows[0];
+    let column = |name: &str| header.iter().position(|c| *c == name).unwrap();
+    
+    // One class row plus one row per method, all as wide as the header
+    assert_eq!(rows.len(), 1 + 1 + 7);
+    assert!(rows.iter().all(|r| r.len() == header.len()));
+    
+    let class = rows.iter().find(|r| r[column("kind")] == "class").unwrap();
+    assert_eq!(class[column("class")], "Shop");
+    assert_eq!(class[column("language")], "python");
+    assert_eq!(class[column("methods")], "7");
+    assert_eq!(class[column("parameters")], "");
+    
+    let method = rows.iter().find(|r| r[column("method")] == "add_item").unwrap();
+    assert_eq!(method[column("kind")], "method");
+    assert_eq!(method[column("methods")], "");
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("metrics").arg(fixture.path()).arg("--format").arg("tsv");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("kind\tlanguage\tfile"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_json_envelope_matches_schema() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("shop.py", include_str!("../fixtures/python/shop.py"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg("--schema");
+    let output = cmd.assert().success();
+    let schema: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    // Grouped, so the optional groups are checked against the schema too
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--format").arg("json").arg("--group-by").arg("dir").arg("--include-all-methods");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    assert_eq!(report["version"], "1");
+    assert_eq!(report["groups"].as_array().unwrap().len(), 1);
+    assert_eq!(report["summary"]["total_classes"], report["results"].as_array().unwrap().len());
+    assert!(report["summary"]["god_classes"].as_u64().unwrap() >= 1);
+    assert_matches_schema(&report, &schema, &schema, "$");
+    
+    Ok(())
+}
+
+/// Structural check of `value` against the subset of JSON Schema used by `check.schema.json`
+fn assert_matches_schema(value: &serde_json::Value, schema: &serde_json::Value, root: &serde_json::Value, at: &str) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/$defs/");
+        return assert_matches_schema(value, &root["$defs"][name], root, at);
+    }
+    if let Some(expected) = schema.get("const") {
+        assert_eq!(value, expected, "{}", at);
+    }
+    if let Some(allowed) = schema["enum"].as_array() {
+        assert!(allowed.contains(value), "{}: {} is not one of {:?}", at, value, allowed);
+    }
+    if let Some(ty) = schema.get("type") {
+        let types: Vec<&str> = match ty {
+            serde_json::Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+            other => vec![other.as_str().unwrap()],
+        };
+        let matches = types.iter().any(|t| match *t {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            other => panic!("unsupported schema type {}", other),
+        });
+        assert!(matches, "{}: expected {:?}, got {}", at, types, value);
+    }
+    for field in schema["required"].as_array().into_iter().flatten() {
+        let field = field.as_str().unwrap();
+        assert!(value.get(field).is_some(), "{}: missing required field '{}'", at, field);
+    }
+    if let (Some(properties), Some(object)) = (schema["properties"].as_object(), value.as_object()) {
+        for (field, field_value) in object {
+            let field_schema = properties
+                .get(field)
+                .unwrap_or_else(|| panic!("{}: field '{}' is not in the schema", at, field));
+            assert_matches_schema(field_value, field_schema, root, &format!("{}.{}", at, field));
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            assert_matches_schema(item, items, root, &format!("{}[{}]", at, i));
+        }
+    }
+}
+
+#[test]
+fn test_cli_output_files() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("src/god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    let json_path = fixture.path().join("dei.json");
+    let html_path = fixture.path().join("dei.html");
+    let text_path = fixture.path().join("dei.txt");
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check")
+        .arg(fixture.path().join("src"))
+        .arg("--format")
+        .arg(format!("json:{}", json_path.display()))
+        .arg("--format")
+        .arg(format!("html:{}", html_path.display()))
+        .arg("--output")
+        .arg(&text_path);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Analysis complete"))
+        .stdout(predicate::str::contains("JSON report written to"))
+        .stdout(predicate::str::contains("MegaUserManager").not());
+    
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    assert_eq!(report["version"], "1");
+    assert!(std::fs::read_to_string(&html_path)?.contains("<html"));
+    let text = std::fs::read_to_string(&text_path)?;
+    assert!(text.contains("GOD CLASSES DETECTED"));
+    assert!(!text.contains('\x1b'), "Report files are written without colors");
+    
+    // Two reports can't share stdout
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path().join("src")).arg("--format").arg("json").arg("--format").arg("html");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Only one report can go to stdout"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_summary_and_top() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("GodClass.cs", include_str!("../fixtures/csharp/GodClass.cs"))?;
+    fixture.create_file("shop.py", include_str!("../fixtures/python/shop.py"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--summary");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("God Classes: 2"))
+        .stdout(predicate::str::contains("GOD CLASSES DETECTED").not());
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--top").arg("1").arg("--format").arg("json");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    // Counts still cover the whole run
+    assert_eq!(report["summary"]["god_classes"], 2);
+    let results = report["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["is_god_class"], true);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--top").arg("1");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Showing the 1 worst of"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_threshold_presets() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    // A 40-line method: within the default 50 lines, over strict's 30
+    let body: String = (0..36).map(|i| format!("        let v{i} = input + {i};\n")).collect();
+    fixture.create_file(
+        "project/src/report.rs",
+        &format!("pub struct Report {{\n    total: u64,\n}}\n\nimpl Report {{\n    pub fn build(&self, input: u64) -> u64 {{\n{body}        self.total + input\n    }}\n}}\n"),
+    )?;
+    let project = fixture.path().join("project");
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project);
+    cmd.assert().success();
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project).arg("--preset").arg("strict");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Preset: strict"))
+        .stdout(predicate::str::contains("GOD METHODS DETECTED"));
+    
+    // dei.toml keys win over the preset, wherever it is picked
+    fixture.create_file("project/dei.toml", "preset = \"strict\"\n\n[thresholds]\nmax_method_lines = 45\n")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project);
+    cmd.assert().success().stdout(predicate::str::contains("Preset: strict"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project).arg("--preset").arg("lenient").arg("--max-lines").arg("5");
+    cmd.assert().failure().stdout(predicate::str::contains("Preset: lenient"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&project).arg("--preset").arg("relaxed");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown preset 'relaxed'"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_sort_and_only() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    fixture.create_file("god_method.rs", include_str!("../fixtures/rust/god_method.rs"))?;
+    fixture.create_file("shop.py", include_str!("../fixtures/python/shop.py"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path())
+        .arg("--only").arg("god-class")
+        .arg("--sort").arg("lines")
+        .arg("--format").arg("json");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    let results = report["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r["is_god_class"] == true));
+    let lines: Vec<u64> = results.iter().map(|r| r["class_metrics"]["lines"].as_u64().unwrap()).collect();
+    assert!(lines.windows(2).all(|w| w[0] >= w[1]), "not sorted by lines: {:?}", lines);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path())
+        .arg("--only").arg("god-method")
+        .arg("--format").arg("json");
+    let output = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    
+    let results = report["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| !r["god_methods"].as_array().unwrap().is_empty()));
+    
+    // God classes are warnings; the god method's findings are errors
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--min-severity").arg("error");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("GOD METHODS DETECTED"))
+        .stdout(predicate::str::contains("GOD CLASSES DETECTED").not());
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(fixture.path()).arg("--only").arg("god-module");
+    cmd.assert().failure();
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_trend() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("healthy.rs", include_str!("../fixtures/rust/healthy.rs"))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("trend").arg(fixture.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No runs recorded"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("trend").arg(fixture.path()).arg("--record");
+    cmd.assert().success();
+    assert!(fixture.path().join("dei-trend.db").is_file());
+    
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("trend").arg(fixture.path()).arg("--record");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("DEI - TREND"))
+        .stdout(predicate::str::contains("0 → 1 (+1)"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("trend").arg(fixture.path()).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let points: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let points = points.as_array().unwrap();
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0]["god_classes"], 0);
+    assert_eq!(points[1]["god_classes"], 1);
+    assert!(points[1]["maintainability_index"].as_f64().unwrap() <= 1.0);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("trend").arg(fixture.path()).arg("--last").arg("1").arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let points: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert_eq!(points.as_array().unwrap().len(), 1);
+    assert_eq!(points[0]["god_classes"], 1);
+    
+    Ok(())
+}
+
+/// Minimal HTTP/1.1 request, returning the whole response
+fn http(addr: &str, method: &str, path: &str) -> Result<String> {
+    use std::io::{Read, Write};
+    
+    let mut stream = std::net::TcpStream::connect(addr)?;
+    write!(stream, "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", method, path, addr)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+#[test]
+fn test_cli_serve() -> Result<()> {
+    use std::io::BufRead;
+    
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("god_class.rs", include_str!("../fixtures/rust/god_class.rs"))?;
+    
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin("dei"))
+        .arg("serve").arg(fixture.path())
+        .arg("--port").arg("0")
+        .arg("--interval").arg("0")
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    let mut banner = String::new();
+    std::io::BufReader::new(server.stdout.take().unwrap()).read_line(&mut banner)?;
+    let addr = banner.rsplit("http://").next().unwrap().trim().to_string();
+    
+    let checks = (|| -> Result<()> {
+        let page = http(&addr, "GET", "/")?;
+        assert!(page.starts_with("HTTP/1.1 200"));
+        assert!(page.contains("<html"));
+        
+        let report = http(&addr, "GET", "/api/report")?;
+        let body: serde_json::Value = serde_json::from_str(report.split("\r\n\r\n").nth(1).unwrap())?;
+        assert_eq!(body["version"], "1");
+        assert_eq!(body["summary"]["god_classes"], 1);
+        
+        // A refresh picks up new files
+        fixture.create_file("GodClass.cs", include_str!("../fixtures/csharp/GodClass.cs"))?;
+        let refreshed = http(&addr, "POST", "/api/refresh")?;
+        let body: serde_json::Value = serde_json::from_str(refreshed.split("\r\n\r\n").nth(1).unwrap())?;
+        assert_eq!(body["summary"]["god_classes"], 2);
+        
+        assert!(http(&addr, "GET", "/api/schema")?.contains("application/schema+json"));
+        Ok(())
+    })();
+    
+    server.kill()?;
+    server.wait()?;
+    checks
+}
+
+#[test]
+fn test_cli_selftest() -> Result<()> {
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("selftest");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("8/8 languages"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("selftest").arg("--format").arg("json");
+    
+    let output = cmd.assert().success();
+    let checks: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let checks = checks.as_array().unwrap();
+    
+    assert!(checks.iter().any(|c| c["language"] == "Perl"));
+    for check in checks {
+        assert_eq!(check["god_class"], true, "{}", check);
+        assert_eq!(check["god_method"], true, "{}", check);
+        assert_eq!(check["healthy_class"], true, "{}", check);
+    }
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_skips_unreadable_files() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    std::fs::write(path.join("broken.rs"), b"struct Broken;\n\0\xff\xfe\n")?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--format").arg("text");
+    cmd.assert()
+        .stdout(predicate::str::contains("SKIPPED FILES"))
+        .stdout(predicate::str::contains("broken.rs"))
+        .stdout(predicate::str::contains("GOD CLASSES"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--format").arg("json");
+    let output = cmd.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let skipped = report["skipped_files"].as_array().unwrap();
+    assert_eq!(skipped.len(), 1);
+    assert!(skipped[0]["file_path"].as_str().unwrap().ends_with("broken.rs"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--strict");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("binary content"))
+        .stdout(predicate::str::contains("SKIPPED FILES").not());
+    
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_follows_symlinks_once() -> Result<()> {
+    use std::os::unix::fs::symlink;
+    
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    let outside = fixture.create_file("outside/extra.rs", include_str!("../fixtures/rust/god_method.rs"))?;
+    symlink("healthy.rs", path.join("alias.rs"))?;
+    std::fs::hard_link(path.join("healthy.rs"), path.join("copy.rs"))?;
+    std::fs::create_dir(path.join("nested"))?;
+    symlink("..", path.join("nested/loop"))?;
+    symlink(outside.parent().unwrap(), path.join("linked"))?;
+    
+    let analyzed_files = |args: &[&str]| -> Result<Vec<String>> {
+        let mut cmd = Command::cargo_bin("dei")?;
+        cmd.arg("check").arg(&path).arg("--format").arg("json").args(args);
+        let output = cmd.assert();
+        let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+        let mut files: Vec<String> = report["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["class_metrics"]["file_path"].as_str().unwrap().rsplit('/').next().unwrap().to_string())
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    };
+    
+    // Hard links are one file either way; symlinks are skipped unless followed
+    assert_eq!(analyzed_files(&[])?, ["copy.rs", "god_class.rs", "god_method.rs"]);
+    assert_eq!(analyzed_files(&["--follow-symlinks"])?, ["copy.rs", "extra.rs", "god_class.rs", "god_method.rs"]);
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--follow-symlinks");
+    cmd.assert()
+        .stdout(predicate::str::contains("Skipped 2 duplicate path(s)"))
+        .stdout(predicate::str::contains("Skipped symlink loop"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_runs_custom_rules() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    fixture.create_file(
+        "rust/.dei/rules/no_managers.rhai",
+        r#"
+            fn inspect(class) {
+                if class.name.ends_with("Manager") {
+                    return #{ message: `${class.name} has ${class.methods.len()} methods`, severity: "info" };
+                }
+            }
+        "#,
+    )?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--format").arg("json");
+    let output = cmd.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let manager = report["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["class_metrics"]["name"] == "MegaUserManager")
+        .unwrap();
+    let rule = &manager["violations"].as_array().unwrap().iter().find(|v| v["kind"] == "Rule").unwrap();
+    assert_eq!(rule["rule"], "no_managers");
+    assert_eq!(rule["severity"], "info");
+    assert!(rule["message"].as_str().unwrap().starts_with("MegaUserManager has "));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path);
+    cmd.assert()
+        .stdout(predicate::str::contains("Rules: 1"))
+        .stdout(predicate::str::contains("CUSTOM RULES"));
+    
+    // A rule that doesn't compile stops the check before anything is analyzed
+    fixture.create_file("rust/.dei/rules/broken.rhai", "fn inspect(class) {")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path);
+    cmd.assert().failure().stderr(predicate::str::contains("broken.rhai"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_commands_share_the_check_pipeline() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("shop/shop.py", include_str!("../fixtures/python/shop.py"))?;
+    fixture.create_file("shop/vendor.min.js", "class Widget {\n  render(items) {\n    return items.length;\n  }\n}\n")?;
+    fixture.create_file(
+        "shop/.dei/rules/no_shops.rhai",
+        r#"
+            fn inspect(class) {
+                if class.name == "Shop" {
+                    return #{ message: "shops belong in their own service", severity: "info" };
+                }
+            }
+        "#,
+    )?;
+    let path = fixture.path().join("shop");
+    
+    // Custom rules judge classes outside `dei check` too
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("explain").arg(path.join("shop.py")).arg("--path").arg(&path).arg("--format").arg("json");
+    let output = cmd.assert().success();
+    let explanation: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let rule = explanation["violations"].as_array().unwrap().iter().find(|v| v["kind"] == "Rule").unwrap();
+    assert_eq!(rule["rule"], "no_shops");
+    
+    // Minified bundles stay out, as they do for `dei check`
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("metrics").arg(&path);
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone())?;
+    assert!(stdout.contains("Shop"));
+    assert!(!stdout.contains("Widget"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_reuses_saved_state() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    let state = fixture.path().join("state.bin");
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--save-state").arg(&state);
+    cmd.assert().stdout(predicate::str::contains("Saved state"));
+    
+    let arch = |args: &[&std::ffi::OsStr]| -> Result<Vec<u8>> {
+        let mut cmd = Command::cargo_bin("dei")?;
+        cmd.arg("arch").arg(&path).args(args);
+        Ok(cmd.assert().success().get_output().stdout.clone())
+    };
+    assert_eq!(arch(&["--load-state".as_ref(), state.as_os_str()])?, arch(&[])?);
+    
+    std::fs::write(path.join("healthy.rs"), "pub struct Replaced;\n")?;
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(&path).arg("--load-state").arg(&state);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("1 file(s) changed since"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("arch").arg(fixture.path()).arg("--load-state").arg(&state);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("was saved for"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_transcodes_legacy_encodings() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    // Latin-1 and Shift-JIS, neither of which is valid UTF-8
+    std::fs::write(path.join("legacy.rs"), b"// Caf\xe9 cr\xe8me br\xfbl\xe9e, na\xefve fa\xe7ade\npub struct L\xe9gume;\n")?;
+    std::fs::write(
+        path.join("shop.rs"),
+        b"// \x93\xfa\x96\x7b\x8c\xea\x82\xcc\x83\x52\x83\x81\x83\x93\x83\x67\x82\xc5\x82\xb7\x81\x42\npub struct Shop;\n",
+    )?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--format").arg("json");
+    let output = cmd.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    assert!(report.get("skipped_files").is_none());
+    let warnings = report["file_warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0]["message"].as_str().unwrap().contains("windows-1252"));
+    assert!(warnings[1]["message"].as_str().unwrap().contains("Shift_JIS"));
+    let analyzed = |name: &str| report["results"].as_array().unwrap().iter().any(|r| r["class_metrics"]["name"] == name);
+    assert!(analyzed("Légume"));
+    assert!(analyzed("Shop"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(path.join("legacy.rs"));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("FILE WARNINGS"))
+        .stdout(predicate::str::contains("decoded as windows-1252"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_caps_file_size_and_sniffs_binary() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    std::fs::write(path.join("blob.rs"), b"struct Blob;\n\0\0\0\n")?;
+    // Over the mmap threshold, so it's mapped rather than read
+    let padding = "// generated\n".repeat(100_000);
+    std::fs::write(path.join("large.rs"), format!("pub struct Large;\n{}", padding))?;
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--format").arg("json");
+    let output = cmd.assert();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout)?;
+    let skipped = report["skipped_files"].as_array().unwrap();
+    assert_eq!(skipped.len(), 1);
+    assert!(skipped[0]["file_path"].as_str().unwrap().ends_with("blob.rs"));
+    assert!(skipped[0]["message"].as_str().unwrap().contains("binary content"));
+    let analyzed = |name: &str| report["results"].as_array().unwrap().iter().any(|r| r["class_metrics"]["name"] == name);
+    assert!(analyzed("Large"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--max-file-size").arg("1M");
+    cmd.assert()
+        .stdout(predicate::str::contains("Skipped 1 file(s) over --max-file-size"));
+    
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--max-file-size").arg("10Q");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown size unit"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_cli_lsp_publishes_diagnostics_and_code_lenses() -> Result<()> {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::Stdio;
+
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    fixture.create_file("rust/dei.toml", "[thresholds]\nmax_class_lines = 100\nmax_methods = 10\n")?;
+    let file = path.join("god_class.rs");
+    let uri = format!("file://{}", file.display());
+
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin("dei"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = server.stdin.take().unwrap();
+    let mut stdout = BufReader::new(server.stdout.take().unwrap());
+    let mut send = move |message: serde_json::Value| -> Result<()> {
+        let body = message.to_string();
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        Ok(stdin.flush()?)
+    };
+    let mut receive = || -> Result<serde_json::Value> {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            stdout.read_line(&mut header)?;
+            match header.trim().strip_prefix("Content-Length: ") {
+                Some(n) => length = n.parse()?,
+                None if header.trim().is_empty() => break,
+                None => {}
+            }
+        }
+        let mut body = vec![0; length];
+        stdout.read_exact(&mut body)?;
+        Ok(serde_json::from_slice(&body)?)
+    };
+
+    send(serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}))?;
+    let initialized = receive()?;
+    assert_eq!(initialized["result"]["capabilities"]["codeLensProvider"]["resolveProvider"], false);
+    send(serde_json::json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}))?;
+    send(serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {"textDocument": {"uri": uri, "languageId": "rust", "version": 1, "text": std::fs::read_to_string(&file)?}}
+    }))?;
+
+    let published = receive()?;
+    assert_eq!(published["method"], "textDocument/publishDiagnostics");
+    let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+    let god_class = diagnostics.iter().find(|d| d["code"] == "DEI001").unwrap();
+    assert!(god_class["message"].as_str().unwrap().starts_with("God class 'MegaUserManager'"));
+    assert_eq!(god_class["source"], "dei");
+    assert!(god_class["codeDescription"]["href"].as_str().unwrap().ends_with("#dei001-god-class"));
+
+    send(serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/codeLens", "params": {"textDocument": {"uri": uri}}}))?;
+    let lenses = receive()?;
+    let lens = lenses["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["range"] == god_class["range"])
+        .unwrap();
+    assert!(lens["command"]["title"].as_str().unwrap().contains("36 methods"));
+
+    send(serde_json::json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}))?;
+    receive()?;
+    send(serde_json::json!({"jsonrpc": "2.0", "method": "exit"}))?;
+    // The server stops once its stdin closes
+    drop(send);
+    assert!(server.wait()?.success());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_daemon_answers_json_rpc() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::{Duration, Instant};
+
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    let socket = fixture.path().join("dei.sock");
+    let mut daemon = std::process::Command::new(assert_cmd::cargo::cargo_bin("dei"))
+        .arg("daemon")
+        .arg("--socket")
+        .arg(&socket)
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+
+    let started = Instant::now();
+    let stream = loop {
+        match UnixStream::connect(&socket) {
+            Ok(stream) => break stream,
+            Err(_) if started.elapsed() < Duration::from_secs(30) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(e.into()),
+        }
+    };
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut call = |request: serde_json::Value| -> Result<serde_json::Value> {
+        writeln!(writer, "{}", request)?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        Ok(serde_json::from_str(&line)?)
+    };
+
+    let analyze = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "analyze", "params": {"path": path}});
+    let first = call(analyze.clone())?;
+    assert_eq!(first["id"], 1);
+    let classes = |response: &serde_json::Value| response["result"]["results"].as_array().map(Vec::len);
+    assert!(classes(&first).unwrap() > 0);
+    // The second analysis reuses the warm state and finds the same
+    assert_eq!(classes(&call(analyze)?), classes(&first));
+
+    let status = call(serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "status"}))?;
+    assert_eq!(status["result"]["paths"].as_array().unwrap().len(), 1);
+
+    let text = "pub struct Tiny;\nimpl Tiny {\n    pub fn one(&self) {}\n}\n";
+    let file = serde_json::json!({"path": path.join("healthy.rs"), "text": text});
+    let analyzed = call(serde_json::json!({"jsonrpc": "2.0", "id": 3, "method": "analyze_file", "params": file}))?;
+    assert_eq!(analyzed["result"]["results"][0]["class_metrics"]["name"], "Tiny");
+
+    let unknown = call(serde_json::json!({"jsonrpc": "2.0", "id": 4, "method": "lint"}))?;
+    assert_eq!(unknown["error"]["code"], -32601);
+    let missing = serde_json::json!({"path": fixture.path().join("missing")});
+    let failed = call(serde_json::json!({"jsonrpc": "2.0", "id": 5, "method": "analyze", "params": missing}))?;
+    assert_eq!(failed["error"]["code"], -32000);
+
+    call(serde_json::json!({"jsonrpc": "2.0", "id": 6, "method": "shutdown"}))?;
+    assert!(daemon.wait()?.success());
+    assert!(!socket.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_trace_prints_phase_timings() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.env_remove("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .env_remove("RUST_LOG")
+        .arg("check")
+        .arg(&path)
+        .arg("--trace");
+
+    cmd.assert()
+        .stderr(predicate::str::contains("build{root="))
+        .stderr(predicate::str::contains("parse{files="))
+        .stderr(predicate::str::contains("analyze{files="))
+        .stderr(predicate::str::contains("time.busy="))
+        // Per-file spans only come with RUST_LOG=debug
+        .stderr(predicate::str::contains("parse_file").not());
+
+    // Without --trace nothing is traced
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.env_remove("OTEL_EXPORTER_OTLP_ENDPOINT").arg("check").arg(&path);
+    cmd.assert().stderr(predicate::str::contains("time.busy").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_exports_spans_over_otlp() -> Result<()> {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+
+    // A collector that takes one export and accepts it
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let endpoint = format!("http://{}", listener.local_addr()?);
+    let collector = std::thread::spawn(move || -> Result<Vec<u8>> {
+        let (mut stream, _) = listener.accept()?;
+        let mut request = Vec::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let read = stream.read(&mut buffer)?;
+            request.extend_from_slice(&buffer[..read]);
+            if read == 0 || request.windows(7).any(|w| w == b"analyze") {
+                break;
+            }
+        }
+        stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")?;
+        Ok(request)
+    });
+
+    let mut cmd = Command::cargo_bin("dei")?;
+    cmd.arg("check").arg(&path).arg("--otlp-endpoint").arg(&endpoint);
+    cmd.assert().stderr(predicate::str::contains("couldn't export").not());
+
+    let request = collector.join().unwrap()?;
+    assert!(request.starts_with(b"POST /v1/traces "));
+    for span in [&b"build"[..], b"parse", b"analyze", b"dei"] {
+        assert!(request.windows(span.len()).any(|w| w == span));
+    }
+
+    Ok(())
+}
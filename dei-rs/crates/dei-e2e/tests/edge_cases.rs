@@ -91,6 +91,10 @@ impl 用户 {
     // Should handle Unicode identifiers
     assert!(!results.is_empty());
     
+    let method = &results[0].class_metrics.methods[0];
+    assert!(method.tokens.iter().any(|t| t.as_ref() == "创建"));
+    assert!(method.tokens.iter().any(|t| t.as_ref() == "名称"));
+    
     Ok(())
 }
 
@@ -149,10 +153,9 @@ impl Broken {
     // Should handle parsing errors gracefully
     let result = harness.analyze_path(fixture.path().join("broken.rs"));
     
-    // May succeed with empty results or return error - both acceptable
-    match result {
-        Ok(results) => assert!(results.is_empty() || !results.is_empty()),
-        Err(_) => {} // Error is also acceptable for invalid syntax
+    // May return an error for invalid syntax; what it does find is the partial class, unflagged
+    if let Ok(results) = result {
+        assert!(results.iter().all(|r| r.class_metrics.name.as_ref() == "Broken" && !r.is_god_class));
     }
     
     Ok(())
@@ -308,8 +311,6 @@ async fn test_concurrent_analysis() -> Result<()> {
         )?;
     }
     
-    let harness = TestHarness::new()?;
-    
     // Run multiple analyses concurrently
     let mut handles = vec![];
     for i in 0..10 {
@@ -368,3 +369,31 @@ async fn test_mixed_line_endings() -> Result<()> {
     Ok(())
 }
 
+
+#[tokio::test]
+async fn test_mapped_file_rewritten_after_mapping() -> Result<()> {
+    use dei_languages::{read_source, source::MMAP_THRESHOLD};
+    use std::io::{Seek, SeekFrom, Write};
+
+    let fixture = FixtureManager::new()?;
+    let line = "pub fn f() -> u32 { 1 }\n";
+    let body = line.repeat(MMAP_THRESHOLD as usize / line.len() + 1);
+    let path = fixture.create_file("large.rs", &body)?;
+
+    let text = read_source(&path)?;
+    assert_eq!(text.len(), body.len());
+
+    // Rewritten in place, same length, with bytes that aren't UTF-8
+    let offset = body.len() / 2;
+    let mut file = std::fs::OpenOptions::new().write(true).open(&path)?;
+    file.seek(SeekFrom::Start(offset as u64))?;
+    file.write_all(&[0xFF; 16])?;
+    file.sync_all()?;
+
+    // Still text, cut short where the rewrite broke it if the rewrite shows through
+    assert!(std::str::from_utf8(text.as_bytes()).is_ok());
+    assert!(text.len() == body.len() || text.len() <= offset);
+    assert!(text.starts_with(line));
+
+    Ok(())
+}
@@ -353,3 +353,1182 @@ async fn test_has_god_classes_helper() -> Result<()> {
     Ok(())
 }
 
+
+#[tokio::test]
+async fn test_rust_receiver_and_associated_functions() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    
+    let code = r#"
+pub struct Account {
+    balance: u64,
+}
+
+impl Account {
+    pub fn new(balance: u64) -> Self { Self { balance } }
+    pub fn deposit(&mut self, amount: u64) { self.balance += amount; }
+    pub fn boxed(self: Box<Self>, extra: u64) -> u64 { self.balance + extra }
+}
+"#;
+    
+    fixture.create_file("account.rs", code)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path().join("account.rs"))?;
+    
+    let account = results.iter()
+        .find(|r| r.class_metrics.name.as_ref() == "Account")
+        .expect("Should find Account");
+    let method = |name: &str| account.class_metrics.methods.iter()
+        .find(|m| m.name.as_ref() == name)
+        .cloned()
+        .expect("Method should exist");
+    
+    // Associated functions have no receiver
+    assert!(method("new").is_static);
+    assert!(!method("deposit").is_static);
+    assert!(!method("boxed").is_static);
+    
+    // Receivers are not counted as parameters
+    assert_eq!(method("new").parameters.0, 1);
+    assert_eq!(method("deposit").parameters.0, 1);
+    assert_eq!(method("boxed").parameters.0, 1);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nesting_depth_violation() -> Result<()> {
+    use dei_core::models::ViolationKind;
+
+    let fixture = FixtureManager::new()?;
+    
+    let code = r#"
+pub struct Nested;
+
+impl Nested {
+    pub fn deep(&self, items: &[i32]) -> i32 {
+        let mut total = 0;
+        for item in items {
+            if *item > 0 {
+                while total < 100 {
+                    match item {
+                        1 => {
+                            if total % 2 == 0 {
+                                total += 1;
+                            }
+                        }
+                        _ => total += 2,
+                    }
+                }
+            }
+        }
+        total
+    }
+
+    pub fn flat(&self, x: i32) -> i32 {
+        if x > 10 {
+            1
+        } else if x > 5 {
+            2
+        } else if x > 0 {
+            3
+        } else {
+            4
+        }
+    }
+}
+"#;
+    
+    fixture.create_file("nested.rs", code)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path().join("nested.rs"))?;
+    
+    let nested = results.iter()
+        .find(|r| r.class_metrics.name.as_ref() == "Nested")
+        .expect("Should find Nested");
+    
+    let depth = |name: &str| nested.class_metrics.methods.iter()
+        .find(|m| m.name.as_ref() == name)
+        .map(|m| m.nesting_depth.0)
+        .unwrap();
+    assert_eq!(depth("deep"), 5);
+    assert_eq!(depth("flat"), 1, "else-if chains should not deepen nesting");
+    
+    let deep = nested.god_methods.iter()
+        .find(|m| m.method_name.as_ref() == "deep")
+        .expect("Deeply nested method should be flagged");
+    assert!(deep.violations.iter().any(|v| v.kind == ViolationKind::NestingDepth));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_two_phase_traversal() -> Result<()> {
+    use dei_ast::{AstBuilder, ParallelTraverser};
+    use dei_core::thresholds::Thresholds;
+    use dei_languages::MultiLanguageParser;
+
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("two_phase/a.rs", "pub struct Alpha;\nimpl Alpha { pub fn run(&self) {} }\n")?;
+    fixture.create_file("two_phase/b.rs", "pub struct Beta;\nimpl Beta { pub fn go(&self) {} }\n")?;
+
+    let builder = AstBuilder::new();
+    let root_id = builder.build(&fixture.path().join("two_phase"))?;
+    let traverser = ParallelTraverser::new(MultiLanguageParser::new()?, builder.arena().clone());
+
+    // After parsing, every file is known but nothing has been judged yet
+    traverser.parse_phase(root_id)?;
+    assert_eq!(traverser.parsed_files().len(), 2);
+    assert!(builder.arena().symbols().resolve_type("Alpha").is_some());
+    assert!(builder.arena().symbols().resolve_type("Beta").is_some());
+    assert!(traverser.all_results().is_empty());
+
+    traverser.analysis_phase(&Thresholds::default())?;
+    assert_eq!(traverser.all_results().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_results_counted_once_per_class() -> Result<()> {
+    use dei_ast::{AstBuilder, ParallelTraverser};
+    use dei_core::thresholds::Thresholds;
+    use dei_languages::MultiLanguageParser;
+
+    let fixture = FixtureManager::new()?;
+    fixture.create_file(
+        "dedup/wrapper.rs",
+        r#"
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+impl<T> Wrapper<T> {
+    pub fn get(&self) -> &T { &self.inner }
+}
+
+impl std::fmt::Display for Wrapper<u8> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.inner) }
+}
+
+impl<'a> From<&'a str> for &'a Wrapper<String> {
+    fn from(_: &'a str) -> Self { unimplemented!() }
+}
+"#,
+    )?;
+
+    // The same directory reached by two paths
+    let builder = AstBuilder::new();
+    let direct = builder.build(&fixture.path().join("dedup"))?;
+    let roundabout = builder.build(&fixture.path().join("dedup/../dedup"))?;
+    let traverser = ParallelTraverser::new(MultiLanguageParser::new()?, builder.arena().clone());
+    traverser.parse_phase(direct)?;
+    traverser.parse_phase(roundabout)?;
+    traverser.analysis_phase(&Thresholds::default())?;
+
+    // Generic, trait and reference impls all merge into the struct, which is reported once
+    let results = traverser.all_results();
+    assert_eq!(results.len(), 1, "{:?}", results.iter().map(|r| &r.class_metrics.name).collect::<Vec<_>>());
+    let wrapper = &results[0].class_metrics;
+    assert_eq!(wrapper.name.as_ref(), "Wrapper");
+    let mut methods: Vec<&str> = wrapper.methods.iter().map(|m| m.name.as_ref()).collect();
+    methods.sort_unstable();
+    assert_eq!(methods, ["fmt", "from", "get"]);
+
+    // Of the two copies, the one under the first path as given is kept, however threads finish
+    let kept = fixture.path().join("dedup/../dedup/wrapper.rs");
+    assert_eq!(wrapper.file_path.as_ref(), kept.to_str().unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_results_ordered_by_file_and_position() -> Result<()> {
+    use dei_ast::{AstBuilder, ParallelTraverser};
+    use dei_core::thresholds::Thresholds;
+    use dei_languages::MultiLanguageParser;
+
+    let fixture = FixtureManager::new()?;
+    for file in ["c.rs", "a.rs", "b.rs"] {
+        fixture.create_file(&format!("ordered/{}", file), "pub struct First;\n\npub struct Second;\n\npub struct Third;\n")?;
+    }
+
+    let builder = AstBuilder::new();
+    let root = builder.build(&fixture.path().join("ordered"))?;
+    let traverser = ParallelTraverser::new(MultiLanguageParser::new()?, builder.arena().clone());
+    traverser.traverse_and_analyze(root, &Thresholds::default())?;
+
+    let order: Vec<(String, usize)> = traverser
+        .all_results()
+        .iter()
+        .map(|r| (r.class_metrics.file_path.to_string(), r.class_metrics.span.start))
+        .collect();
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_eq!(order.len(), 9);
+    assert_eq!(order, sorted);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pooled_parsers_keep_options_apart() -> Result<()> {
+    use dei_core::traits::Parser;
+    use dei_languages::{MultiLanguageParser, ParseOptions};
+
+    let fixture = FixtureManager::new()?;
+    let path = fixture.create_file("pooled.py", "class Job:\n    def run(self):\n        return 1\n")?;
+    let snippets = MultiLanguageParser::new()?.with_options(ParseOptions::default().with_ast_snippets(true));
+    let plain = MultiLanguageParser::new()?;
+
+    // Both parsers reuse this thread's Python parser, but each keeps its own options
+    let method = |metrics: &dei_core::metrics::FileMetrics| metrics.classes[0].methods[0].clone();
+    assert!(method(&snippets.parse_file(&path)?).ast_snippet.is_some());
+    assert!(method(&plain.parse_file(&path)?).ast_snippet.is_none());
+    assert!(method(&snippets.parse_file(&path)?).ast_snippet.is_some());
+
+    // Every thread gets the same answer from its own parser
+    let parsed: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| plain.parse_file(&path))).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<dei_core::Result<_>>()
+    })?;
+    assert!(parsed.iter().all(|m| m.classes[0].name.as_ref() == "Job"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_incremental_reparse() -> Result<()> {
+    use dei_ast::{AstBuilder, ParallelTraverser};
+    use dei_core::{thresholds::Thresholds, traits::Parser};
+    use dei_languages::{IncrementalParser, MultiLanguageParser};
+
+    let fixture = FixtureManager::new()?;
+    let before = "class Alpha:\n    def run(self, x):\n        return x\n\nclass Beta:\n    def go(self, x):\n        return x\n";
+    let path = fixture.create_file("incremental/jobs.py", before)?;
+    let parser = IncrementalParser::new();
+    let analyze = |previous: Vec<dei_core::models::AnalysisResult>| -> Result<_> {
+        let builder = AstBuilder::new();
+        let root = builder.build(&fixture.path().join("incremental"))?;
+        let traverser = ParallelTraverser::new(parser.clone(), builder.arena().clone()).with_previous_results(previous);
+        traverser.traverse_and_analyze(root, &Thresholds::default())?;
+        Ok(traverser.all_results())
+    };
+
+    let mut first = analyze(Vec::new())?;
+    assert_eq!(parser.changed_classes(&path), None);
+
+    // Only Beta's body changes, so only Beta is re-judged
+    std::fs::write(&path, before.replace("go(self, x):\n        return x", "go(self, x):\n        return [i for i in x]"))?;
+    for result in &mut first {
+        result.summary = "from the previous run".into();
+    }
+    let second = analyze(first)?;
+    assert_eq!(parser.changed_classes(&path), Some(vec!["Beta".into()]));
+    let summary = |name: &str| second.iter().find(|r| r.class_metrics.name.as_ref() == name).unwrap().summary.clone();
+    assert_eq!(summary("Alpha").as_ref(), "from the previous run");
+    assert_ne!(summary("Beta").as_ref(), "from the previous run");
+
+    // The incremental parse agrees with a fresh one
+    let fresh = MultiLanguageParser::new()?.parse_file(&path)?;
+    assert_eq!(format!("{:?}", parser.parse_file(&path)?), format!("{:?}", fresh));
+    assert_eq!(parser.changed_classes(&path), Some(Vec::new()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_incremental_reanalyzes_after_import_edit() -> Result<()> {
+    use dei_ast::{AstBuilder, ParallelTraverser};
+    use dei_core::{thresholds::Thresholds, traits::Parser};
+    use dei_languages::IncrementalParser;
+
+    let fixture = FixtureManager::new()?;
+    let before = "import os\n\nclass Alpha:\n    def run(self, x):\n        return os.sep + re.escape(x)\n";
+    let path = fixture.create_file("imports/jobs.py", before)?;
+    let parser = IncrementalParser::new();
+    let analyze = |previous: Vec<dei_core::models::AnalysisResult>| -> Result<_> {
+        let builder = AstBuilder::new();
+        let root = builder.build(&fixture.path().join("imports"))?;
+        let traverser = ParallelTraverser::new(parser.clone(), builder.arena().clone()).with_previous_results(previous);
+        traverser.traverse_and_analyze(root, &Thresholds::default())?;
+        Ok(traverser.all_results())
+    };
+
+    let first = analyze(Vec::new())?;
+    assert_eq!(first[0].class_metrics.dependencies.as_ref(), [std::sync::Arc::from("os")]);
+
+    // Same lines, same class body: only the import differs, and the result has to follow it
+    std::fs::write(&path, before.replace("import os", "import re"))?;
+    let second = analyze(first)?;
+    assert_eq!(parser.changed_classes(&path), Some(Vec::new()));
+    assert_eq!(second[0].class_metrics.dependencies.as_ref(), [std::sync::Arc::from("re")]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parse_from_memory_sources() -> Result<()> {
+    use dei_core::{detector::{Context, DetectorRegistry}, thresholds::Thresholds, traits::Parser};
+    use dei_languages::{MemorySources, MultiLanguageParser};
+    use std::path::Path;
+
+    // Nothing on disk: the parser only sees what it is handed, as in a browser
+    let methods: String = (0..25).map(|i| format!("    def step_{}(self):\n        return {}\n", i, i)).collect();
+    let sources = MemorySources::new().with_file("playground/pasted.py", format!("class Pasted:\n{}", methods));
+    let parser = MultiLanguageParser::new()?.with_sources(sources);
+
+    let metrics = parser.parse_file(Path::new("playground/pasted.py"))?;
+    let class = &metrics.classes[0];
+    assert_eq!(class.method_count, MethodCount(25));
+    let thresholds = Thresholds::default();
+    assert!(class.is_god_class(&thresholds));
+    assert!(!DetectorRegistry::default().inspect(class, &Context::new(&thresholds)).is_empty());
+
+    assert!(matches!(
+        parser.parse_file(Path::new("playground/missing.py")),
+        Err(dei_core::Error::PathNotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parallel_build_links_tree() -> Result<()> {
+    use dei_ast::{AstBuilder, NodeId};
+
+    let fixture = FixtureManager::new()?;
+    for dir in ["a", "a/b", "a/b/c", "d"] {
+        for file in ["z.rs", "m.py", "a.rs"] {
+            fixture.create_file(&format!("tree/{}/{}", dir, file), "class X: pass\n")?;
+        }
+    }
+    fixture.create_file("tree/notes.txt", "not code")?;
+
+    let builder = AstBuilder::new();
+    let root = builder.build(&fixture.path().join("tree"))?;
+    let arena = builder.arena();
+
+    // Every node hangs off its parent, one level deeper, with siblings in path order
+    fn walk(arena: &dei_ast::arena::SharedArena, id: NodeId, files: &mut usize) {
+        let node = arena.get(id).unwrap();
+        *files += usize::from(node.is_file());
+        let children = arena.children(id);
+        let paths: Vec<_> = children.iter().map(|&c| arena.get(c).unwrap().path.clone()).collect();
+        assert!(paths.windows(2).all(|w| w[0] < w[1]), "{:?}", paths);
+        for &child in children.iter() {
+            let child = arena.get(child).unwrap();
+            assert_eq!(child.parent, Some(id));
+            assert_eq!(child.depth, node.depth + 1);
+        }
+        children.iter().for_each(|&c| walk(arena, c, files));
+    }
+    let mut files = 0;
+    walk(arena, root, &mut files);
+    assert_eq!(files, 13);
+    assert_eq!(arena.len(), 13 + 5);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rebuild_subtree() -> Result<()> {
+    use dei_ast::AstBuilder;
+
+    let fixture = FixtureManager::new()?;
+    fixture.create_file("watched/src/a.rs", "pub struct Alpha;\n")?;
+    fixture.create_file("watched/lib.rs", "pub struct Lib;\n")?;
+
+    let builder = AstBuilder::new();
+    builder.build(&fixture.path().join("watched"))?;
+    let arena = builder.arena();
+    let (src, _) = arena.iter().find(|(_, n)| n.path.ends_with("src")).unwrap();
+    let (old_file, _) = arena.iter().find(|(_, n)| n.path.ends_with("a.rs")).unwrap();
+    let before = arena.len();
+
+    fixture.create_file("watched/src/b.rs", "pub struct Beta;\n")?;
+    let new_src = builder.rebuild(src)?.unwrap();
+
+    // The old IDs are dead even though their slots were reused
+    assert!(arena.get(src).is_none());
+    assert!(arena.get(old_file).is_none());
+    assert_eq!(arena.len(), before + 1);
+    assert_eq!(arena.children(new_src).len(), 2);
+    let root = arena.get(new_src).unwrap().parent.unwrap();
+    assert!(arena.children(root).contains(&new_src));
+    assert!(!arena.children(root).contains(&src));
+
+    std::fs::remove_dir_all(fixture.path().join("watched/src"))?;
+    assert_eq!(builder.rebuild(new_src)?, None);
+    assert_eq!(arena.children(root).len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_stamps() -> Result<()> {
+    use dei_ast::AstBuilder;
+
+    let fixture = FixtureManager::new()?;
+    let source = fixture.create_file("stamped/lib.rs", "pub struct Lib;\n")?;
+    fixture.create_file("stamped/notes.txt", "not source\n")?;
+
+    let builder = AstBuilder::new();
+    builder.build(&fixture.path().join("stamped"))?;
+    let node = |name: &str| builder.arena().iter().map(|(_, n)| n).find(|n| n.path.ends_with(name)).unwrap();
+    let stamp = node("lib.rs").stamp.unwrap();
+    assert_eq!(stamp.size, 16);
+    assert!(node("notes.txt").stamp.is_none());
+    assert!(!stamp.is_stale(&source));
+
+    // Rewriting the same contents isn't a change; different contents of the same size are
+    std::fs::write(&source, "pub struct Lib;\n")?;
+    assert!(!stamp.is_stale(&source));
+    std::fs::write(&source, "pub struct Lob;\n")?;
+    assert!(stamp.is_stale(&source));
+    std::fs::remove_file(&source)?;
+    assert!(stamp.is_stale(&source));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_saved_state_restores_without_parsing() -> Result<()> {
+    use dei_ast::{AstBuilder, ParallelTraverser, SavedState};
+    use dei_core::thresholds::Thresholds;
+    use dei_languages::MultiLanguageParser;
+
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?;
+    let thresholds = Thresholds::default();
+    let names = |traverser: &ParallelTraverser<MultiLanguageParser>| {
+        let mut names: Vec<_> = traverser.all_results().into_iter().map(|r| r.class_metrics.fully_qualified_name).collect();
+        names.sort();
+        names
+    };
+
+    let builder = AstBuilder::new();
+    let root = builder.build(&path)?;
+    let traverser = ParallelTraverser::new(MultiLanguageParser::new()?, builder.arena().clone());
+    traverser.traverse_and_analyze(root, &thresholds)?;
+    let state_file = fixture.path().join("state.json");
+    SavedState::capture(builder.arena(), root, &path).save(&state_file)?;
+
+    // With the sources gone, only the saved metrics can produce results
+    std::fs::remove_dir_all(&path)?;
+    let (arena, restored_root) = SavedState::load(&state_file)?.restore();
+    assert_eq!(arena.len(), builder.arena().len());
+    let restored = ParallelTraverser::new(MultiLanguageParser::new()?, arena.clone());
+    restored.traverse_and_analyze(restored_root, &thresholds)?;
+    assert!(restored.file_errors().is_empty());
+    assert_eq!(names(&restored), names(&traverser));
+    assert!(!arena.symbols().types_named("UserRepository").is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_low_cohesion_small_class() -> Result<()> {
+    use dei_core::models::ViolationKind;
+
+    let fixture = FixtureManager::new()?;
+    
+    let code = r#"
+class Grabbag:
+    def deposit(self, amount):
+        self.total += amount
+
+    def balance(self):
+        return self.total
+
+    def send(self, msg):
+        self.socket.write(msg)
+
+    def resend(self, msg):
+        self.send(msg)
+
+    def render(self):
+        return self.template.format(self.title)
+
+    def title_case(self):
+        return self.title.upper()
+"#;
+    
+    fixture.create_file("grabbag.py", code)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path().join("grabbag.py"))?;
+    
+    let grabbag = results.iter()
+        .find(|r| r.class_metrics.name.as_ref() == "Grabbag")
+        .expect("Should find Grabbag");
+    
+    let resend = grabbag.class_metrics.methods.iter()
+        .find(|m| m.name.as_ref() == "resend")
+        .unwrap();
+    assert!(resend.called_methods.iter().any(|c| c.as_ref() == "send"));
+    
+    assert!(!grabbag.is_god_class, "Small class should not be a god class");
+    assert_eq!(grabbag.lcom4, Some(3));
+    assert!(grabbag.violations.iter().any(|v| v.kind == ViolationKind::LowCohesion));
+    assert!(grabbag.has_issues());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_structured_method_signatures() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    
+    fixture.create_file("sig.rs", r#"
+pub struct Store;
+
+impl Store {
+    pub fn put<'a, T: Clone>(&mut self, key: &'a str, value: T) {}
+}
+"#)?;
+    fixture.create_file("sig.py", r#"
+class Mailer:
+    def send(self, to: str, retries=3, *args, **kwargs):
+        pass
+"#)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path())?;
+    
+    let method = |class: &str, name: &str| results.iter()
+        .find(|r| r.class_metrics.name.as_ref() == class)
+        .and_then(|r| r.class_metrics.methods.iter().find(|m| m.name.as_ref() == name).cloned())
+        .expect("method should be parsed");
+    
+    let put = method("Store", "put");
+    let names: Vec<&str> = put.params.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["key", "value"], "receiver should be excluded");
+    assert_eq!(put.params[1].type_name.as_deref(), Some("T"));
+    assert_eq!(put.generics.len(), 2);
+    
+    let send = method("Mailer", "send");
+    let names: Vec<&str> = send.params.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["to", "retries", "args", "kwargs"]);
+    assert_eq!(send.params[0].type_name.as_deref(), Some("str"));
+    assert_eq!(send.params[1].default.as_deref(), Some("3"));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_message_chain_violation() -> Result<()> {
+    use dei_core::models::ViolationKind;
+
+    let fixture = FixtureManager::new()?;
+    
+    fixture.create_file("chain.rs", r#"
+pub struct Checkout;
+
+impl Checkout {
+    pub fn city(&self, order: &Order) -> String {
+        order.customer().address().city().name().to_string()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.cart.items().len() as u32
+    }
+}
+"#)?;
+    fixture.create_file("Chain.java", r#"
+public class Report {
+    public String owner(Repo repo) {
+        return repo.getProject().getOwner().getName();
+    }
+}
+"#)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path())?;
+    
+    let method = |class: &str, name: &str| results.iter()
+        .find(|r| r.class_metrics.name.as_ref() == class)
+        .and_then(|r| r.class_metrics.methods.iter().find(|m| m.name.as_ref() == name).cloned())
+        .expect("method should be parsed");
+    
+    assert_eq!(method("Checkout", "city").chain_length.0, 5);
+    assert_eq!(method("Checkout", "total").chain_length.0, 2, "self links are not counted");
+    assert_eq!(method("Report", "owner").chain_length.0, 3);
+    
+    let checkout = results.iter()
+        .find(|r| r.class_metrics.name.as_ref() == "Checkout")
+        .unwrap();
+    let city = checkout.god_methods.iter()
+        .find(|m| m.method_name.as_ref() == "city")
+        .expect("Train wreck should be flagged");
+    assert!(city.violations.iter().any(|v| v.kind == ViolationKind::MessageChain));
+    assert!(checkout.god_methods.iter().all(|m| m.method_name.as_ref() != "total"));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_magic_literal_density() -> Result<()> {
+    use dei_core::{models::ViolationKind, thresholds::{Lines, Thresholds}};
+
+    let fixture = FixtureManager::new()?;
+    
+    fixture.create_file("config.py", r#"
+class Billing:
+    def fee(self, amount):
+        """Docstrings are not magic"""
+        if amount > 2500:
+            return amount * 0.035 + 30
+        return amount * 0.029 + 1 + 0
+
+    def label(self):
+        return "" + "EUR"
+"#)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path())?;
+    let billing = &results[0];
+    
+    let method = |name: &str| billing.class_metrics.methods.iter()
+        .find(|m| m.name.as_ref() == name)
+        .unwrap();
+    assert_eq!(method("fee").magic_literals, 4, "0, 1 and docstrings are excluded");
+    assert_eq!(method("label").magic_literals, 1);
+    assert!(method("fee").magic_density() > 0.5);
+    
+    // Only methods flagged for other reasons get the extra signal
+    let thresholds = Thresholds {
+        max_method_lines: Lines(3),
+        max_magic_density: Some(0.5),
+        ..Default::default()
+    };
+    let results = harness.with_thresholds(thresholds).analyze_path(fixture.path())?;
+    let fee = results[0].god_methods.iter()
+        .find(|m| m.method_name.as_ref() == "fee")
+        .expect("fee exceeds the line limit");
+    assert!(fee.violations.iter().any(|v| v.kind == ViolationKind::MagicLiterals));
+    assert!(results[0].god_methods.iter().all(|m| m.method_name.as_ref() != "label"));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_identifier_tokenization() -> Result<()> {
+    let fixture = FixtureManager::new()?;
+    
+    fixture.create_file("tokens.rs", r#"
+pub struct Gateway;
+
+impl Gateway {
+    pub fn parseHTTPServerConfig(&self) -> u32 {
+        let retry_budget = MAX_RETRY_COUNT;
+        utf8Decode(retry_budget);
+        ΑριθμόςΠελάτη(用户Id)
+    }
+}
+"#)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path())?;
+    let tokens: Vec<&str> = results[0].class_metrics.methods[0]
+        .tokens
+        .iter()
+        .map(|t| t.as_ref())
+        .collect();
+    
+    for expected in [
+        "parse", "http", "server", "config", // acronym run
+        "retry", "budget", "max", "count",   // snake_case and SCREAMING_CASE
+        "utf8", "decode",                    // digits stay with their word
+        "αριθμός", "πελάτη", "用户",          // non-ASCII scripts
+    ] {
+        assert!(tokens.contains(&expected), "missing token {expected:?} in {tokens:?}");
+    }
+    assert!(!tokens.contains(&"id"), "short cased fragments are dropped");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_field_and_dependency_count_violations() -> Result<()> {
+    use dei_core::{models::ViolationKind, thresholds::Thresholds};
+
+    let fixture = FixtureManager::new()?;
+    
+    fixture.create_file("blob.py", r#"
+import os
+import json
+from billing import Invoice
+
+class Customer:
+    def __init__(self):
+        self.name = os.getenv("NAME")
+        self.email = json.loads("{}")
+        self.phone = None
+        self.street = Invoice()
+
+class Tag:
+    def __init__(self):
+        self.label = None
+"#)?;
+    
+    let thresholds = Thresholds {
+        max_fields: 3,
+        max_dependencies: 2,
+        ..Default::default()
+    };
+    let harness = TestHarness::new()?.with_thresholds(thresholds);
+    let results = harness.analyze_path(fixture.path())?;
+    
+    let kinds = |class: &str| -> Vec<ViolationKind> {
+        let result = results.iter()
+            .find(|r| r.class_metrics.name.as_ref() == class)
+            .expect("class should be analyzed");
+        assert!(!result.is_god_class);
+        result.violations.iter().map(|v| v.kind).collect()
+    };
+    
+    assert_eq!(kinds("Customer"), [ViolationKind::FieldCount, ViolationKind::DependencyCount]);
+    assert_eq!(kinds("Tag"), [], "imports count for the classes that use them");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_imports_attributed_to_the_classes_using_them() -> Result<()> {
+    use dei_core::traits::Parser;
+    use dei_languages::MultiLanguageParser;
+
+    let fixture = FixtureManager::new()?;
+    let path = fixture.create_file("report.rs", r#"
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use serde::Serialize as Ser;
+use anyhow::Context as _;
+use std::io::*;
+
+#[derive(Args)]
+pub struct Options {
+    format: Format,
+}
+
+#[derive(Ser)]
+pub struct Summary {
+    total: usize,
+}
+
+impl Summary {
+    pub fn render(&self) -> String {
+        format!("{}", self.total).green().to_string()
+    }
+}
+
+#[derive(ValueEnum)]
+pub enum Format {
+    Text,
+}
+"#)?;
+
+    let metrics = MultiLanguageParser::new()?.parse_file(&path)?;
+    let deps = |name: &str| -> Vec<String> {
+        let class = metrics.classes.iter().find(|c| c.name.as_ref() == name).expect("class should be parsed");
+        let mut deps: Vec<String> = class.dependencies.iter().map(|d| d.to_string()).collect();
+        deps.sort();
+        deps
+    };
+
+    // Imports never named outside the imports (traits used for their methods, `as _`) or
+    // taken wholesale (`*`) can't be pinned on one class, so every class gets them
+    let shared = ["anyhow::Context", "colored::Colorize", "std::io::*"];
+    let with = |names: &[&str]| -> Vec<String> {
+        let mut all: Vec<String> = shared.iter().chain(names).map(|n| n.to_string()).collect();
+        all.sort();
+        all
+    };
+    assert_eq!(deps("Options"), with(&["clap"]));
+    assert_eq!(deps("Summary"), with(&["serde::Serialize"]));
+    assert_eq!(deps("Format"), with(&["clap"]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_exit_points_violation() -> Result<()> {
+    use dei_core::{models::ViolationKind, thresholds::Thresholds};
+
+    let fixture = FixtureManager::new()?;
+    
+    fixture.create_file("Exits.java", r#"
+public class Validator {
+    public int check(int[] values) {
+        for (int v : values) {
+            if (v < 0) {
+                throw new IllegalArgumentException();
+            }
+            if (v == 0) {
+                continue;
+            }
+            if (v > 100) {
+                return -1;
+            }
+        }
+        Runnable r = () -> { return; };
+        return 0;
+    }
+
+    public int single(int v) {
+        return v * 2;
+    }
+}
+"#)?;
+    
+    let harness = TestHarness::new()?;
+    let results = harness.analyze_path(fixture.path())?;
+    let method = |name: &str| results[0].class_metrics.methods.iter()
+        .find(|m| m.name.as_ref() == name)
+        .unwrap()
+        .clone();
+    assert_eq!(method("check").exit_points, 4, "the lambda's return is its own");
+    assert_eq!(method("single").exit_points, 1);
+    assert!(results[0].god_methods.is_empty(), "exit points are opt-in");
+    
+    let thresholds = Thresholds {
+        max_exit_points: Some(1),
+        ..Default::default()
+    };
+    let results = harness.with_thresholds(thresholds).analyze_path(fixture.path())?;
+    let flagged: Vec<_> = results[0].god_methods.iter().map(|m| m.method_name.as_ref()).collect();
+    assert_eq!(flagged, ["check"]);
+    assert!(results[0].god_methods[0].violations.iter().any(|v| v.kind == ViolationKind::ExitPoints));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_file_fast_path_matches_traversal() -> Result<()> {
+    use dei_ast::analyze_file;
+    use dei_core::thresholds::Thresholds;
+    use dei_languages::MultiLanguageParser;
+    
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?.join("god_class.rs");
+    
+    let thresholds = Thresholds::default();
+    let traversed = TestHarness::new()?.analyze_path(&path)?;
+    let fast = analyze_file(&MultiLanguageParser::new()?, &path, &thresholds)?;
+    
+    let summarize = |results: &[dei_core::models::AnalysisResult]| {
+        let mut classes: Vec<_> = results
+            .iter()
+            .map(|r| (r.class_metrics.name.clone(), r.is_god_class, r.god_methods.len(), r.violations.len()))
+            .collect();
+        classes.sort();
+        classes
+    };
+    
+    assert_eq!(summarize(&fast.results), summarize(&traversed));
+    assert!(fast.results.iter().any(|r| r.is_god_class));
+    assert_eq!(fast.metrics.classes.len(), fast.results.len());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_god_file_signals() -> Result<()> {
+    use dei_ast::analyze_file;
+    use dei_core::{models::ViolationKind, thresholds::{ClusterSeed, Thresholds}};
+    use dei_languages::MultiLanguageParser;
+    
+    let fixture = FixtureManager::new()?;
+    let path = fixture.create_file("utils.js", r#"
+import fs from "fs";
+import path from "path";
+import yaml from "yaml";
+
+export function parseConfig(text) {
+    const lines = text.split("\n");
+    return lines.map((line) => line.trim());
+}
+
+export function parseArgs(argv) {
+    const args = argv.slice(2);
+    return args.filter((arg) => arg.startsWith("--"));
+}
+
+export function renderTable(rows) {
+    const header = rows[0].join(" | ");
+    return [header, ...rows.slice(1).map((row) => row.join(" | "))].join("\n");
+}
+
+export function renderBanner(title) {
+    const line = "=".repeat(title.length);
+    return [line, title, line].join("\n");
+}
+
+function readFile(name) {
+    const full = path.join(process.cwd(), name);
+    return fs.readFileSync(full, "utf8");
+}
+"#)?;
+    
+    let parser = MultiLanguageParser::new()?;
+    
+    // Defaults only look at class count and length
+    let analysis = analyze_file(&parser, &path, &Thresholds::default())?;
+    assert_eq!(analysis.metrics.top_level_functions, 5);
+    assert_eq!(analysis.metrics.imports, 3);
+    assert!(analysis.god_file.is_none());
+    
+    let thresholds = Thresholds {
+        max_functions_per_file: Some(4),
+        max_file_imports: Some(2),
+        max_file_responsibilities: Some(1),
+        // Each seed matches two functions
+        min_cluster_size: 2,
+        cluster_seeds: vec![
+            ClusterSeed::new("Parsing", ["parse*"]),
+            ClusterSeed::new("Rendering", ["render*"]),
+        ],
+        ..Thresholds::default()
+    };
+    let god_file = analyze_file(&parser, &path, &thresholds)?
+        .god_file
+        .expect("every opt-in signal trips");
+    
+    let signal = |kind| god_file.violations.iter().find(|v| v.kind == kind).map(|v| v.actual);
+    assert_eq!(signal(ViolationKind::TopLevelFunctions), Some(5));
+    assert_eq!(signal(ViolationKind::DependencyCount), Some(3));
+    assert!(signal(ViolationKind::Responsibilities).is_some_and(|n| n >= 2));
+    assert_eq!(signal(ViolationKind::ClassesPerFile), None);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_wildcard_import_detection() -> Result<()> {
+    use dei_ast::analyze_file;
+    use dei_core::{models::ViolationKind, thresholds::Thresholds};
+    use dei_languages::MultiLanguageParser;
+    
+    let fixture = FixtureManager::new()?;
+    let files = [
+        ("models.py", "from os.path import *\nfrom typing import List, Dict as D\nimport json, re\n", 5, vec!["os.path"]),
+        ("Models.java", "import java.util.*;\nimport static java.lang.Math.*;\nimport java.io.File;\n", 3, vec!["java.util", "java.lang.Math"]),
+        ("models.rs", "use std::collections::*;\nuse std::io::{Read, Write as W, self};\n", 4, vec!["std::collections"]),
+        ("Models.cs", "using static System.Math;\nusing System;\n", 2, vec!["System.Math"]),
+        ("models.ts", "import * as fs from 'fs';\nimport a, { b, c as d } from './x';\nexport * from './y';\n", 5, vec!["./y"]),
+    ];
+    
+    let parser = MultiLanguageParser::new()?;
+    let thresholds = Thresholds {
+        max_wildcard_imports: Some(0),
+        max_imported_symbols: Some(4),
+        ..Thresholds::default()
+    };
+    
+    for (name, source, symbols, wildcards) in files {
+        let path = fixture.create_file(name, source)?;
+        
+        // Nothing is reported unless a limit is configured
+        assert!(analyze_file(&parser, &path, &Thresholds::default())?.import_hygiene.is_none());
+        
+        let analysis = analyze_file(&parser, &path, &thresholds)?;
+        let found: Vec<&str> = analysis.metrics.wildcard_imports.iter().map(|w| w.as_ref()).collect();
+        assert_eq!(analysis.metrics.imported_symbols, symbols, "{}", name);
+        assert_eq!(found, wildcards, "{}", name);
+        
+        let hygiene = analysis.import_hygiene.expect(name);
+        let kinds: Vec<ViolationKind> = hygiene.violations.iter().map(|v| v.kind).collect();
+        assert!(kinds.contains(&ViolationKind::WildcardImports), "{}", name);
+        assert_eq!(kinds.contains(&ViolationKind::ImportedSymbols), symbols > 4, "{}", name);
+    }
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_framework_entry_points() -> Result<()> {
+    use dei_ast::analyze_file;
+    use dei_clustering::ClusteringAnalyzer;
+    use dei_core::{
+        models::EntryPoint,
+        thresholds::{ThresholdProfile, Thresholds},
+        traits::ClusterAnalyzer,
+    };
+    use dei_languages::MultiLanguageParser;
+    
+    let fixture = FixtureManager::new()?;
+    let controller = fixture.create_file("OrdersController.cs", concat!(
+        "[ApiController]\n",
+        "public class OrdersController : ControllerBase\n{\n",
+        "    public int List(int a) {\n        if (a > 1) { return a; }\n        return 0;\n    }\n",
+        "    public int Show(int a) {\n        if (a > 2) { return a; }\n        return 0;\n    }\n",
+        "    public int Create(int a) {\n        if (a > 3) { return a; }\n        return 0;\n    }\n",
+        "}\n",
+        "public class Money { public int Cents() { return 1; } }\n",
+    ))?;
+    let java = fixture.create_file("Billing.java", concat!(
+        "@RestController\npublic class BillingController { public int a() { return 1; } }\n",
+        "@org.springframework.stereotype.Service\nclass BillingService { public int b() { return 1; } }\n",
+    ))?;
+    let python = fixture.create_file("views.py", concat!(
+        "class OrderView(generic.ListView):\n    def get(self, request):\n        return 1\n\n",
+        "@api_view([\"GET\"])\ndef orders(request):\n    return 1\n",
+    ))?;
+    let rust = fixture.create_file("main.rs", "#[tokio::main]\nasync fn main() {}\n")?;
+    
+    let parser = MultiLanguageParser::new()?;
+    let thresholds = Thresholds {
+        max_methods: MethodCount(2),
+        ..Thresholds::default()
+    };
+    
+    let entry_points = |path| -> Result<Vec<(String, Option<EntryPoint>)>> {
+        let analysis = analyze_file(&parser, path, &thresholds)?;
+        Ok(analysis.metrics.classes.iter().map(|c| (c.name.to_string(), c.entry_point)).collect())
+    };
+    assert_eq!(entry_points(&controller)?, [
+        ("OrdersController".into(), Some(EntryPoint::AspNetController)),
+        ("Money".into(), None),
+    ]);
+    assert_eq!(entry_points(&java)?, [
+        ("BillingController".into(), Some(EntryPoint::SpringController)),
+        ("BillingService".into(), Some(EntryPoint::SpringBean)),
+    ]);
+    assert_eq!(entry_points(&python)?, [("OrderView".into(), Some(EntryPoint::DjangoView))]);
+    assert_eq!(analyze_file(&parser, &python, &thresholds)?.metrics.entry_point, Some(EntryPoint::DjangoView));
+    assert_eq!(analyze_file(&parser, &rust, &thresholds)?.metrics.entry_point, Some(EntryPoint::TokioMain));
+    
+    // A controller profile loosens the limits for controllers only
+    let analysis = analyze_file(&parser, &controller, &thresholds)?;
+    assert!(analysis.results.iter().any(|r| r.class_metrics.name.as_ref() == "OrdersController" && r.is_god_class));
+    
+    let profiled = Thresholds {
+        entry_points: [(EntryPoint::AspNetController, ThresholdProfile {
+            max_methods: Some(MethodCount(5)),
+            ..ThresholdProfile::default()
+        })].into(),
+        ..thresholds.clone()
+    };
+    profiled.validate().map_err(anyhow::Error::msg)?;
+    let analysis = analyze_file(&parser, &controller, &profiled)?;
+    assert!(analysis.results.iter().all(|r| !r.is_god_class));
+    
+    // Logic pulled out of a controller is named as a service
+    let class = analysis.metrics.classes.iter().find(|c| c.name.as_ref() == "OrdersController").unwrap();
+    let clusters = ClusteringAnalyzer::new().analyze(class, &thresholds)?;
+    assert_eq!(clusters[0].suggested_name.as_ref(), "OrdersService");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_suggested_extractions_are_opt_in() -> Result<()> {
+    use dei_ast::{analyze_file, AstBuilder, ParallelTraverser};
+    use dei_core::thresholds::Thresholds;
+    use dei_languages::MultiLanguageParser;
+    
+    let fixture = FixtureManager::new()?;
+    let path = fixture.copy_fixture("rust")?.join("god_class.rs");
+    let thresholds = Thresholds::default();
+    
+    let plain = TestHarness::new()?.analyze_path(&path)?;
+    assert!(plain.iter().all(|r| r.suggested_extractions.is_empty()));
+    
+    let builder = AstBuilder::new();
+    let root_id = builder.build(&path)?;
+    let traverser = ParallelTraverser::new(MultiLanguageParser::new()?, builder.arena().clone())
+        .with_suggested_extractions(true);
+    traverser.traverse_and_analyze(root_id, &thresholds)?;
+    let results = traverser.all_results();
+    
+    let manager = results
+        .iter()
+        .find(|r| r.class_metrics.name.as_ref() == "MegaUserManager")
+        .expect("MegaUserManager is analyzed");
+    assert!(manager.is_god_class);
+    assert!(!manager.suggested_extractions.is_empty());
+    for cluster in manager.suggested_extractions.iter() {
+        assert!(cluster.methods.len() < manager.class_metrics.methods.len());
+    }
+    // Only god classes get suggestions
+    assert!(results.iter().filter(|r| !r.is_god_class).all(|r| r.suggested_extractions.is_empty()));
+    
+    // The single-file path suggests the same extractions
+    let fast = analyze_file(&MultiLanguageParser::new()?, &path, &thresholds)?.with_suggested_extractions(&thresholds);
+    let methods = |results: &[dei_core::models::AnalysisResult]| -> Vec<Vec<String>> {
+        let mut groups: Vec<Vec<String>> = results
+            .iter()
+            .flat_map(|r| r.suggested_extractions.iter())
+            .map(|c| {
+                let mut names: Vec<String> = c.methods.iter().map(|m| m.to_string()).collect();
+                names.sort();
+                names
+            })
+            .collect();
+        groups.sort();
+        groups
+    };
+    assert_eq!(methods(&fast.results), methods(&results));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_abstract_types_are_flagged() -> Result<()> {
+    use dei_core::traits::Parser;
+    use dei_languages::MultiLanguageParser;
+    
+    let fixture = FixtureManager::new()?;
+    let csharp = fixture.create_file("Shapes.cs", concat!(
+        "public interface IShape { int Area(); }\n",
+        "public abstract class Shape { public abstract int Area(); }\n",
+        "public class Square : Shape { public override int Area() { return 4; } }\n",
+    ))?;
+    let java = fixture.create_file("Repo.java", concat!(
+        "interface Repo { int find(); }\n",
+        "abstract class BaseRepo implements Repo { }\n",
+        "class SqlRepo extends BaseRepo { public int find() { return 1; } }\n",
+    ))?;
+    let python = fixture.create_file("repo.py", concat!(
+        "class Repo(abc.ABC):\n    def find(self):\n        return 1\n\n",
+        "class Port(metaclass=ABCMeta):\n    def send(self):\n        return 1\n\n",
+        "class SqlRepo(Repo):\n    def find(self):\n        return 2\n",
+    ))?;
+    let rust = fixture.create_file("repo.rs", concat!(
+        "pub trait Repo { fn find(&self) -> i32; }\n",
+        "pub struct SqlRepo;\n",
+        "impl Repo for SqlRepo { fn find(&self) -> i32 { 1 } }\n",
+    ))?;
+    
+    let parser = MultiLanguageParser::new()?;
+    let abstract_types = |path: &std::path::Path| -> Result<Vec<(String, bool)>> {
+        let mut classes: Vec<_> = parser
+            .parse_file(path)?
+            .classes
+            .iter()
+            .map(|c| (c.name.to_string(), c.is_abstract))
+            .collect();
+        classes.sort();
+        Ok(classes)
+    };
+    assert_eq!(abstract_types(&csharp)?, [
+        ("IShape".into(), true),
+        ("Shape".into(), true),
+        ("Square".into(), false),
+    ]);
+    assert_eq!(abstract_types(&java)?, [
+        ("BaseRepo".into(), true),
+        ("Repo".into(), true),
+        ("SqlRepo".into(), false),
+    ]);
+    assert_eq!(abstract_types(&python)?, [
+        ("Port".into(), true),
+        ("Repo".into(), true),
+        ("SqlRepo".into(), false),
+    ]);
+    assert_eq!(abstract_types(&rust)?, [("Repo".into(), true), ("SqlRepo".into(), false)]);
+    
+    Ok(())
+}
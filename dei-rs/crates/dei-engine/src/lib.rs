@@ -0,0 +1,519 @@
+//! Embeddable analysis engine
+//!
+//! The stable entry point for running DEI from other Rust tools without shelling out to
+//! the `dei` binary: configure an [`AnalysisEngine`] and get typed results back.
+//!
+//! ```no_run
+//! use dei_engine::{AnalysisEngine, Language, Thresholds};
+//!
+//! let analysis = AnalysisEngine::builder()
+//!     .thresholds(Thresholds::default())
+//!     .languages([Language::Rust, Language::Python])
+//!     .analyze("src")?;
+//!
+//! for class in analysis.god_classes() {
+//!     println!("{}: {}", class.class_metrics.file_path, class.summary);
+//! }
+//! # Ok::<(), dei_engine::Error>(())
+//! ```
+//!
+//! Long runs can hand results over as each file finishes instead, through
+//! [`AnalysisEngine::stream`], and report progress with a [`ProgressObserver`].
+//!
+//! [`project`] sets an engine up from a project's `dei.toml` and custom rules and judges its
+//! findings as `dei check` does.
+
+use dei_ast::{analyze_file_with, AstBuilder, ParallelTraverser};
+use dei_core::traits::Parser;
+use dei_languages::MultiLanguageParser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+
+pub mod project;
+
+pub use dei_ast::{
+    arena::SharedArena, rules::RULES_DIR, NodeId, Phase, ProgressObserver, SavedState, ScriptRule, SlowFile,
+    SymbolTable, TimeBudget,
+};
+pub use dei_core::{
+    config::PathRule,
+    detector::{Context, Detector, DetectorRegistry, Finding},
+    error::{Error, Result},
+    metrics::{ClassMetrics, FileMetrics, MethodMetrics},
+    models::{
+        AnalysisResult, FileError, FileWarning, GodFileResult, ImportHygieneResult, Language, LanguageFilter, PathMetadata,
+        Violation, ViolationKind,
+    },
+    thresholds::{Complexity, Lines, MethodCount, ParamCount, Preset, Thresholds},
+};
+pub use dei_languages::{IncrementalParser, ParseOptions};
+
+/// Analyzes files and directories with one set of options
+#[derive(Clone, Default)]
+pub struct AnalysisEngine {
+    thresholds: Thresholds,
+    languages: LanguageFilter,
+    suggest_extractions: bool,
+    budget: TimeBudget,
+    ignore: Vec<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_minified: bool,
+    max_file_size: Option<u64>,
+    include_vendored: bool,
+    follow_symlinks: bool,
+    strict: bool,
+    detectors: DetectorRegistry,
+    observers: Vec<Arc<dyn ProgressObserver>>,
+    parse_options: ParseOptions,
+    incremental: Option<IncrementalParser>,
+    previous: Arc<[AnalysisResult]>,
+    restored: Option<(SharedArena, NodeId)>,
+    only_files: Option<Vec<PathBuf>>,
+    keep_tree: bool,
+    path_rules: Vec<PathRule>,
+}
+
+impl AnalysisEngine {
+    pub fn builder() -> AnalysisEngineBuilder {
+        AnalysisEngineBuilder::default()
+    }
+
+    pub fn thresholds(&self) -> &Thresholds {
+        &self.thresholds
+    }
+
+    /// Analyze a file or a directory tree
+    pub fn analyze(&self, path: impl AsRef<Path>) -> Result<Analysis> {
+        let path = path.as_ref();
+        let mut analysis = match &self.incremental {
+            Some(parser) => {
+                parser.prune();
+                self.analyze_with(parser.clone(), path)?
+            }
+            None => self.analyze_with(MultiLanguageParser::new()?.with_options(self.parse_options), path)?,
+        };
+
+        let position = |r: &AnalysisResult| (r.class_metrics.file_path.clone(), r.class_metrics.span.start);
+        analysis.results.sort_by_key(position);
+        analysis.vendored.sort_by_key(position);
+        analysis.files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(analysis)
+    }
+
+    /// Analyze in the background, handing over each class result as its file is analyzed
+    ///
+    /// Results arrive in completion order and, with `include_vendored`, include vendored
+    /// classes; [`AnalysisStream::finish`] returns the complete, ordered [`Analysis`].
+    pub fn stream(&self, path: impl Into<PathBuf>) -> AnalysisStream {
+        let (sender, receiver) = mpsc::channel();
+        let mut engine = self.clone();
+        engine.observers.push(Arc::new(ResultSender(sender)));
+
+        let path = path.into();
+        let handle = thread::spawn(move || engine.analyze(path));
+        AnalysisStream { receiver, handle }
+    }
+
+    fn analyze_with<P: Parser>(&self, parser: P, path: &Path) -> Result<Analysis> {
+        // A lone file skips the filesystem tree, directory walk and parallel traversal
+        let lone_file = path.is_file() && self.restored.is_none() && self.only_files.is_none() && !self.keep_tree;
+        if lone_file {
+            self.analyze_single_file(&parser, path)
+        } else {
+            self.analyze_tree(parser, path)
+        }
+    }
+
+    fn analyze_single_file<P: Parser>(&self, parser: &P, path: &Path) -> Result<Analysis> {
+        let mut analysis = Analysis::default();
+        let selected = Language::from_path(path).is_some_and(|l| self.languages.allows(l));
+        self.observers.files_discovered(usize::from(selected));
+        if !selected {
+            return Ok(analysis);
+        }
+
+        let metadata = PathMetadata::from_rules(&self.path_rules, &path.to_string_lossy());
+        let analyzed = analyze_file_with(parser, path, &self.thresholds, &self.detectors, &metadata);
+        self.observers.file_parsed(path);
+        let mut file = match analyzed {
+            Ok(file) => file,
+            Err(e) if self.strict => return Err(e),
+            Err(e) => {
+                let file_path = path.to_string_lossy().into();
+                analysis.file_errors.push(FileError { file_path, message: e.to_string().into() });
+                return Ok(analysis);
+            }
+        };
+        if self.suggest_extractions {
+            file = file.with_suggested_extractions(&self.thresholds);
+        }
+        let slow = [(Phase::Parse, file.parse_time), (Phase::Analyze, file.analyze_time)]
+            .into_iter()
+            .find(|&(_, elapsed)| self.budget.is_exceeded(elapsed));
+        if let Some((phase, elapsed)) = slow {
+            analysis.slow_files.push(SlowFile {
+                path: path.to_path_buf(),
+                language: Language::from_path(path),
+                bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+                phase,
+                elapsed,
+                skipped: self.budget.skip_slow,
+            });
+            if self.budget.skip_slow {
+                self.observers.file_analyzed(path, &[]);
+                return Ok(analysis);
+            }
+        }
+
+        self.observers.file_analyzed(path, &file.results);
+        analysis.file_warnings.extend(FileWarning::transcoded(&file.metrics));
+        analysis.results = file.results;
+        analysis.god_files.extend(file.god_file);
+        analysis.import_hygiene.extend(file.import_hygiene);
+        analysis.files.push(Arc::new(file.metrics));
+        Ok(analysis)
+    }
+
+    fn analyze_tree<P: Parser>(&self, parser: P, path: &Path) -> Result<Analysis> {
+        let (mut builder, restored_root) = match &self.restored {
+            Some((arena, root)) => (AstBuilder::with_arena(arena.clone()), Some(*root)),
+            None => (AstBuilder::new(), None),
+        };
+        builder.include_minified(self.include_minified);
+        builder.max_file_size(self.max_file_size);
+        builder.include_vendored(self.include_vendored);
+        builder.follow_symlinks(self.follow_symlinks);
+        for pattern in &self.ignore {
+            builder.add_ignore_pattern(pattern.clone());
+        }
+        for glob in &self.include {
+            builder.add_include_glob(glob.clone());
+        }
+        for glob in &self.exclude {
+            builder.add_exclude_glob(glob.clone());
+        }
+        if let Some(files) = &self.only_files {
+            builder.restrict_to_files(files.iter().cloned());
+        }
+        for rule in &self.path_rules {
+            builder.add_path_rule(rule.clone());
+        }
+        let root_id = match restored_root {
+            Some(root) => {
+                builder.arena().apply_path_rules(&self.path_rules);
+                root
+            }
+            None => builder.build(path)?,
+        };
+
+        let mut traverser = ParallelTraverser::new(parser, builder.arena().clone())
+            .with_time_budget(self.budget)
+            .with_languages(self.languages.clone())
+            .with_suggested_extractions(self.suggest_extractions)
+            .with_strict(self.strict)
+            .with_previous_results(self.previous.iter().cloned())
+            .with_detectors(self.detectors.clone());
+        if !self.observers.is_empty() {
+            traverser = traverser.with_observer(Arc::new(self.observers.clone()));
+        }
+        traverser.traverse_and_analyze(root_id, &self.thresholds)?;
+
+        // Vendored code is kept apart so it never counts against the project
+        let is_vendored = |file: &str| builder.is_vendored(Path::new(file));
+        let (vendored, results) = traverser
+            .all_results()
+            .into_iter()
+            .partition(|r| is_vendored(&r.class_metrics.file_path));
+
+        Ok(Analysis {
+            results,
+            vendored,
+            god_files: traverser
+                .god_file_results()
+                .into_iter()
+                .filter(|r| !is_vendored(&r.file_path))
+                .collect(),
+            import_hygiene: traverser
+                .import_hygiene_results()
+                .into_iter()
+                .filter(|r| !is_vendored(&r.file_path))
+                .collect(),
+            slow_files: traverser.slow_files(),
+            file_errors: traverser.file_errors(),
+            file_warnings: traverser.file_warnings(),
+            files: traverser.parsed_files(),
+            skipped_minified: builder.skipped_minified().len(),
+            skipped_large: builder.skipped_large().len(),
+            skipped_duplicates: builder.skipped_duplicates().len(),
+            symlink_loops: builder.symlink_loops(),
+            vendored_dirs: builder.vendored_dirs().len(),
+            tree: Some(AnalyzedTree { arena: builder.arena().clone(), root: root_id }),
+        })
+    }
+}
+
+/// Options for an [`AnalysisEngine`], all optional
+#[derive(Clone, Default)]
+pub struct AnalysisEngineBuilder {
+    engine: AnalysisEngine,
+}
+
+impl AnalysisEngineBuilder {
+    pub fn thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.engine.thresholds = thresholds;
+        self
+    }
+
+    /// Only analyze these languages; every supported language by default
+    pub fn languages(mut self, languages: impl IntoIterator<Item = Language>) -> Self {
+        self.engine.languages.include = languages.into_iter().collect();
+        self
+    }
+
+    /// Never analyze these languages
+    pub fn exclude_languages(mut self, languages: impl IntoIterator<Item = Language>) -> Self {
+        self.engine.languages.exclude = languages.into_iter().collect();
+        self
+    }
+
+    /// Cluster the methods of every god class into suggested extractions (slow)
+    pub fn suggest_extractions(mut self, suggest: bool) -> Self {
+        self.engine.suggest_extractions = suggest;
+        self
+    }
+
+    /// Time allowed per file in each phase
+    pub fn time_budget(mut self, budget: TimeBudget) -> Self {
+        self.engine.budget = budget;
+        self
+    }
+
+    /// Skip every file and directory with a path component containing `pattern`, as
+    /// `node_modules` and `target` are skipped; [`exclude`](Self::exclude) takes globs
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.engine.ignore.push(pattern.into());
+        self
+    }
+
+    /// Only analyze files matching one of the include globs
+    pub fn include(mut self, glob: impl Into<String>) -> Self {
+        self.engine.include.push(glob.into());
+        self
+    }
+
+    /// Skip files matching the glob
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.engine.exclude.push(glob.into());
+        self
+    }
+
+    /// Analyze minified and compiled files instead of skipping them
+    pub fn include_minified(mut self, include: bool) -> Self {
+        self.engine.include_minified = include;
+        self
+    }
+
+    /// Skip files in a directory tree larger than `bytes`; 0 for no limit, the default
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.engine.max_file_size = (bytes > 0).then_some(bytes);
+        self
+    }
+
+    /// Analyze vendored directories; their results land in [`Analysis::vendored`]
+    pub fn include_vendored(mut self, include: bool) -> Self {
+        self.engine.include_vendored = include;
+        self
+    }
+
+    /// Follow symbolic links; each file is still analyzed once, however many paths reach it
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.engine.follow_symlinks = follow;
+        self
+    }
+
+    /// Fail on the first file that can't be read or parsed; by default such files are
+    /// collected in [`Analysis::file_errors`] and the rest is analyzed
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.engine.strict = strict;
+        self
+    }
+
+    /// Run these detectors instead of the built-in ones, to add smells or disable some
+    pub fn detectors(mut self, detectors: DetectorRegistry) -> Self {
+        self.engine.detectors = detectors;
+        self
+    }
+
+    /// Extras to compute while parsing, such as AST snippets of each method
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.engine.parse_options = options;
+        self
+    }
+
+    /// Parse with `parser`, which keeps each file's tree between runs, instead of a fresh
+    /// parser with the [`parse_options`](Self::parse_options); for engines run again and again
+    /// over the same tree, together with [`previous_results`](Self::previous_results)
+    pub fn incremental(mut self, parser: IncrementalParser) -> Self {
+        self.engine.incremental = Some(parser);
+        self
+    }
+
+    /// Results of an earlier run with the same settings, reused for classes the incremental
+    /// parser reports unchanged instead of analyzing them again
+    pub fn previous_results(mut self, results: impl Into<Arc<[AnalysisResult]>>) -> Self {
+        self.engine.previous = results.into();
+        self
+    }
+
+    /// Analyze this tree, restored from a [`SavedState`], instead of building one from the
+    /// analyzed path
+    pub fn restored(mut self, arena: SharedArena, root: NodeId) -> Self {
+        self.engine.restored = Some((arena, root));
+        self
+    }
+
+    /// Only analyze these files of the tree, e.g. the ones changed since a base revision
+    pub fn only_files(mut self, files: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.engine.only_files = Some(files.into_iter().collect());
+        self
+    }
+
+    /// Build the filesystem tree even for a lone file, so [`Analysis::tree`] is always there
+    pub fn keep_tree(mut self, keep: bool) -> Self {
+        self.engine.keep_tree = keep;
+        self
+    }
+
+    /// Give the files under paths matching `rule` its tags, owner and layer, which detectors
+    /// see in their [`Context`] and results carry; may be called more than once
+    pub fn path_rule(mut self, rule: PathRule) -> Self {
+        self.engine.path_rules.push(rule);
+        self
+    }
+
+    /// Report progress to `observer`; may be called more than once
+    pub fn observer(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.engine.observers.push(observer);
+        self
+    }
+
+    pub fn build(self) -> AnalysisEngine {
+        self.engine
+    }
+
+    /// Build the engine and analyze `path` with it
+    pub fn analyze(self, path: impl AsRef<Path>) -> Result<Analysis> {
+        self.build().analyze(path)
+    }
+}
+
+/// Findings for one analyzed path, ordered by file and line
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Analysis {
+    /// One result per class, healthy or not
+    pub results: Vec<AnalysisResult>,
+    /// Results for classes in vendored directories, with `include_vendored`
+    pub vendored: Vec<AnalysisResult>,
+    pub god_files: Vec<GodFileResult>,
+    pub import_hygiene: Vec<ImportHygieneResult>,
+    /// Files that overran the time budget, slowest first
+    pub slow_files: Vec<SlowFile>,
+    /// Files that couldn't be read or parsed, by path
+    pub file_errors: Vec<FileError>,
+    /// Files analyzed with a caveat, such as being transcoded from a legacy encoding
+    pub file_warnings: Vec<FileWarning>,
+    /// Minified files left out
+    pub skipped_minified: usize,
+    /// Files over the size limit left out
+    pub skipped_large: usize,
+    /// Paths left out because they lead to a file already analyzed through another
+    pub skipped_duplicates: usize,
+    /// Symbolic links back into a directory above them, left out
+    pub symlink_loops: Vec<PathBuf>,
+    /// Vendored directories found, whether or not they were analyzed
+    pub vendored_dirs: usize,
+    /// Metrics of every file parsed, by path
+    #[serde(skip)]
+    pub files: Vec<Arc<FileMetrics>>,
+    /// The filesystem tree analyzed; `None` for a lone file unless kept with `keep_tree`
+    #[serde(skip)]
+    pub tree: Option<AnalyzedTree>,
+}
+
+/// The filesystem tree an [`Analysis`] walked, with every file's metrics on its node and the
+/// symbol table built while parsing
+#[derive(Debug, Clone)]
+pub struct AnalyzedTree {
+    pub arena: SharedArena,
+    pub root: NodeId,
+}
+
+impl AnalyzedTree {
+    /// Types and functions declared across the tree, to resolve references by name
+    pub fn symbols(&self) -> &SymbolTable {
+        self.arena.symbols()
+    }
+
+    /// Snapshot of the tree that a later run can restore instead of parsing it again
+    pub fn save_state(&self, path: &Path) -> SavedState {
+        SavedState::capture(&self.arena, self.root, path)
+    }
+}
+
+impl Analysis {
+    pub fn god_classes(&self) -> impl Iterator<Item = &AnalysisResult> {
+        self.results.iter().filter(|r| r.is_god_class)
+    }
+
+    /// Classes with at least one god method
+    pub fn god_methods(&self) -> impl Iterator<Item = &AnalysisResult> {
+        self.results.iter().filter(|r| !r.god_methods.is_empty())
+    }
+
+    /// Classes with any finding
+    pub fn flagged(&self) -> impl Iterator<Item = &AnalysisResult> {
+        self.results.iter().filter(|r| r.has_issues())
+    }
+
+    pub fn has_issues(&self) -> bool {
+        self.flagged().next().is_some() || !self.god_files.is_empty() || !self.import_hygiene.is_empty()
+    }
+}
+
+/// Results of an [`AnalysisEngine::stream`] run, as files finish
+pub struct AnalysisStream {
+    receiver: mpsc::Receiver<AnalysisResult>,
+    handle: JoinHandle<Result<Analysis>>,
+}
+
+impl AnalysisStream {
+    /// Wait for the run to end and return everything it found
+    pub fn finish(self) -> Result<Analysis> {
+        drop(self.receiver);
+        self.handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+/// Blocks until the next result is ready; ends when the run does
+impl Iterator for AnalysisStream {
+    type Item = AnalysisResult;
+
+    fn next(&mut self) -> Option<AnalysisResult> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Forwards analyzed results to an [`AnalysisStream`]
+struct ResultSender(mpsc::Sender<AnalysisResult>);
+
+impl ProgressObserver for ResultSender {
+    fn file_analyzed(&self, _path: &Path, results: &[AnalysisResult]) {
+        // A dropped stream just stops listening
+        for result in results {
+            let _ = self.0.send(result.clone());
+        }
+    }
+}
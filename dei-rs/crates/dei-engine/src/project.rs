@@ -0,0 +1,102 @@
+//! Analyzing a project the way `dei check` does
+//!
+//! A project is the nearest `dei.toml` to the analyzed path, with the custom rules in
+//! `.dei/rules/` and the triage decisions next to it. The `dei` binary and the C API both set
+//! up their engines and judge the findings through here, so a project reads the same from
+//! either.
+//!
+//! ```no_run
+//! let analysis = dei_engine::project::check("src".as_ref())?;
+//! println!("{} god classes", analysis.god_classes().count());
+//! # Ok::<(), dei_engine::Error>(())
+//! ```
+
+use crate::{Analysis, AnalysisEngine, AnalysisEngineBuilder, DetectorRegistry, Result, ScriptRule, RULES_DIR};
+use dei_core::{baseline::Decisions, config::DeiConfig};
+use std::path::{Path, PathBuf};
+
+/// Files larger than this are skipped unless the engine is told otherwise
+pub const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// `file` if given, or else the nearest `dei.toml` to `path`, or else the defaults; with the
+/// path it was loaded from
+pub fn load_config(file: Option<PathBuf>, path: &Path) -> Result<(Option<PathBuf>, DeiConfig)> {
+    Ok(match file {
+        Some(file) => {
+            let config = DeiConfig::load(&file)?;
+            (Some(file), config)
+        }
+        None => match DeiConfig::discover(path)? {
+            Some((file, config)) => (Some(file), config),
+            None => (None, DeiConfig::default()),
+        },
+    })
+}
+
+/// Baseline, suppressions and rules live next to the config file, or in the analyzed directory
+pub fn decisions_root(config_path: Option<&Path>, path: &Path) -> PathBuf {
+    let dir = match config_path {
+        Some(config) => config.parent(),
+        None if path.is_file() => path.parent(),
+        None => Some(path),
+    };
+    dir.filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf()
+}
+
+/// The built-in detectors followed by the custom rules in `.dei/rules/` under `root`, and how
+/// many rules there were
+pub fn load_detectors(root: &Path) -> Result<(DetectorRegistry, usize)> {
+    let rules = ScriptRule::load_dir(&root.join(RULES_DIR))?;
+    let count = rules.len();
+    Ok((rules.into_iter().fold(DetectorRegistry::default(), DetectorRegistry::with), count))
+}
+
+/// An engine set up from `config`, running `detectors`: its thresholds, ignores and
+/// `[[metadata]]` path rules, with files over [`MAX_FILE_SIZE`] skipped
+pub fn engine(config: &DeiConfig, detectors: DetectorRegistry) -> AnalysisEngineBuilder {
+    let builder = AnalysisEngine::builder()
+        .thresholds(config.thresholds.clone())
+        .detectors(detectors)
+        .max_file_size(MAX_FILE_SIZE);
+    let builder = config.ignore.iter().fold(builder, |builder, pattern| builder.ignore(pattern.clone()));
+    config.metadata.iter().fold(builder, |builder, rule| builder.path_rule(rule.clone()))
+}
+
+/// `analysis` under the `[smells]` and `[violations]` policy of `config`: disabled smells and
+/// kinds turned off dropped, severities overridden
+pub fn apply_config(config: &DeiConfig, mut analysis: Analysis) -> Analysis {
+    let judge = |results: Vec<_>| {
+        results
+            .into_iter()
+            .map(|result| config.violations.apply(config.smells.apply(result)))
+            .collect()
+    };
+    analysis.results = judge(analysis.results);
+    analysis.vendored = judge(analysis.vendored);
+    analysis.god_files = analysis
+        .god_files
+        .into_iter()
+        .filter_map(|result| config.violations.apply_files(result))
+        .collect();
+    analysis.import_hygiene = analysis
+        .import_hygiene
+        .into_iter()
+        .filter_map(|result| config.smells.apply_imports(result))
+        .filter_map(|result| config.violations.apply_imports(result))
+        .collect();
+    analysis
+}
+
+/// What `dei check` finds at `path` without flags: the project's config and rules, with
+/// accepted and suppressed findings left out
+pub fn check(path: &Path) -> Result<Analysis> {
+    let (config_path, config) = load_config(None, path)?;
+    let root = decisions_root(config_path.as_deref(), path);
+    let (detectors, _) = load_detectors(&root)?;
+    let mut analysis = apply_config(&config, engine(&config, detectors).analyze(path)?);
+    let decisions = Decisions::load(&root)?;
+    analysis.results = analysis.results.into_iter().map(|result| decisions.apply(result)).collect();
+    Ok(analysis)
+}
@@ -0,0 +1,171 @@
+use dei_engine::{AnalysisEngine, Language, MethodCount, Thresholds};
+use std::fs;
+
+fn write(dir: &std::path::Path, file: &str, content: &str) -> std::path::PathBuf {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    path
+}
+
+const BUSY: &str = "pub struct Busy;\n\nimpl Busy {\n    fn a(&self) {}\n    fn b(&self) {}\n    fn c(&self) {}\n}\n";
+
+#[test]
+fn test_analyze_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "src/busy.rs", BUSY);
+    write(dir.path(), "src/calm.rs", "pub struct Calm;\n\nimpl Calm {\n    fn a(&self) {}\n}\n");
+    write(dir.path(), "scripts/busy.py", "class Busy:\n    def a(self):\n        pass\n    def b(self):\n        pass\n    def c(self):\n        pass\n");
+
+    let thresholds = Thresholds { max_methods: MethodCount(2), ..Thresholds::default() };
+    let analysis = AnalysisEngine::builder().thresholds(thresholds.clone()).analyze(dir.path()).unwrap();
+    let files: Vec<&str> = analysis.results.iter().map(|r| r.class_metrics.file_path.as_ref()).collect();
+    assert_eq!(files.len(), 3);
+    assert!(files.windows(2).all(|w| w[0] <= w[1]), "results are ordered by file");
+    assert_eq!(analysis.god_classes().count(), 2);
+    assert!(analysis.has_issues());
+
+    // Language selection and exclusion globs narrow what is analyzed
+    let rust_only = AnalysisEngine::builder()
+        .thresholds(thresholds.clone())
+        .languages([Language::Rust])
+        .analyze(dir.path())
+        .unwrap();
+    assert_eq!(rust_only.results.len(), 2);
+    assert!(rust_only.results.iter().all(|r| r.class_metrics.file_path.ends_with(".rs")));
+
+    // Ignore patterns skip every path with a component containing them
+    let ignored = AnalysisEngine::builder().ignore("script").analyze(dir.path()).unwrap();
+    assert_eq!(ignored.results.len(), 2);
+    assert!(ignored.results.iter().all(|r| r.class_metrics.file_path.contains("/src/")));
+
+    let engine = AnalysisEngine::builder().thresholds(thresholds).exclude("**/busy.*").build();
+    let calm = engine.analyze(dir.path()).unwrap();
+    assert_eq!(calm.results.len(), 1);
+    assert!(!calm.has_issues());
+}
+
+#[test]
+fn test_analyze_single_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "busy.rs", BUSY);
+
+    let engine = AnalysisEngine::builder()
+        .thresholds(Thresholds { max_methods: MethodCount(2), ..Thresholds::default() })
+        .build();
+    let analysis = engine.analyze(&file).unwrap();
+    assert_eq!(analysis.results.len(), 1);
+    assert!(analysis.results[0].is_god_class);
+    let summaries = |results: &[dei_engine::AnalysisResult]| -> Vec<String> {
+        results.iter().map(|r| r.summary.to_string()).collect()
+    };
+    assert_eq!(summaries(&engine.analyze(dir.path()).unwrap().results), summaries(&analysis.results));
+
+    let excluded = AnalysisEngine::builder().exclude_languages([Language::Rust]).analyze(&file).unwrap();
+    assert!(excluded.results.is_empty());
+
+    assert!(AnalysisEngine::builder().analyze(dir.path().join("missing")).is_err());
+}
+
+#[test]
+fn test_stream_with_progress() {
+    use dei_engine::{AnalysisResult, ProgressObserver};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counts {
+        discovered: AtomicUsize,
+        parsed: AtomicUsize,
+        analyzed: AtomicUsize,
+        results: AtomicUsize,
+    }
+
+    impl ProgressObserver for Counts {
+        fn files_discovered(&self, count: usize) {
+            self.discovered.store(count, Ordering::Relaxed);
+        }
+
+        fn file_parsed(&self, _path: &Path) {
+            self.parsed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn file_analyzed(&self, _path: &Path, results: &[AnalysisResult]) {
+            self.analyzed.fetch_add(1, Ordering::Relaxed);
+            self.results.fetch_add(results.len(), Ordering::Relaxed);
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..5 {
+        write(dir.path(), &format!("src/busy_{i}.rs"), BUSY);
+    }
+    write(dir.path(), "README.md", "not code\n");
+
+    let counts = Arc::new(Counts::default());
+    let engine = AnalysisEngine::builder()
+        .thresholds(Thresholds { max_methods: MethodCount(2), ..Thresholds::default() })
+        .observer(counts.clone())
+        .build();
+
+    let mut stream = engine.stream(dir.path());
+    let streamed: Vec<_> = stream.by_ref().collect();
+    let analysis = stream.finish().unwrap();
+    assert_eq!(streamed.len(), 5);
+    assert!(streamed.iter().all(|r| r.is_god_class));
+    assert_eq!(analysis.results.len(), 5);
+
+    assert_eq!(counts.discovered.load(Ordering::Relaxed), 5, "only supported files count");
+    assert_eq!(counts.parsed.load(Ordering::Relaxed), 5);
+    assert_eq!(counts.analyzed.load(Ordering::Relaxed), 5);
+    assert_eq!(counts.results.load(Ordering::Relaxed), 5);
+
+    // Finishing without reading still returns everything
+    let analysis = engine.stream(dir.path()).finish().unwrap();
+    assert_eq!(analysis.god_classes().count(), 5);
+}
+
+#[test]
+fn test_custom_and_disabled_detectors() {
+    use dei_engine::{ClassMetrics, Context, Detector, DetectorRegistry, Finding, Violation, ViolationKind};
+
+    /// Flags classes with a single method
+    struct Lonely;
+
+    impl Detector for Lonely {
+        fn name(&self) -> &str {
+            "lonely"
+        }
+
+        fn inspect(&self, class: &ClassMetrics, _ctx: &Context) -> Vec<Finding> {
+            (class.method_count.0 == 1)
+                .then(|| Finding::Class(Violation::new(ViolationKind::MethodCount, 1, 0)))
+                .into_iter()
+                .collect()
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "busy.rs", BUSY);
+    write(dir.path(), "pair.rs", "pub struct Pair {\n    a: u8,\n    b: u8,\n}\n\nimpl Pair {\n    fn a(&self) -> u8 {\n        self.a\n    }\n}\n");
+    let thresholds = Thresholds { max_methods: MethodCount(2), ..Thresholds::default() };
+
+    let builtin = AnalysisEngine::builder().thresholds(thresholds.clone()).analyze(dir.path()).unwrap();
+    assert_eq!(builtin.god_classes().count(), 1);
+    assert_eq!(builtin.results.iter().filter(|r| r.has_issues()).count(), 1);
+
+    let mut detectors = DetectorRegistry::default().with(Lonely);
+    assert!(detectors.disable("god_class"));
+    assert!(!detectors.disable("god_class"));
+    assert!(!detectors.names().any(|name| name == "god_class"));
+    let custom = AnalysisEngine::builder()
+        .thresholds(thresholds)
+        .detectors(detectors)
+        .analyze(dir.path())
+        .unwrap();
+    assert_eq!(custom.god_classes().count(), 0);
+    let flagged: Vec<&str> = custom.results.iter().filter(|r| r.has_issues()).map(|r| r.class_metrics.name.as_ref()).collect();
+    assert_eq!(flagged, ["Pair"]);
+    assert_eq!(custom.results[1].violations[0].kind, ViolationKind::MethodCount);
+}
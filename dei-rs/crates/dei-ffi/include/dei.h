@@ -0,0 +1,37 @@
+/* C API of DEI, a detector of god classes and god methods
+ *
+ * Link against the dei library built by the dei-ffi crate (libdei.so, libdei.dylib,
+ * dei.dll or libdei.a). Strings returned by dei_analyze are owned by the caller and must be
+ * released with dei_string_free; the others are owned by DEI.
+ *
+ * Build it with `cargo build -p dei-ffi --profile ffi`. A panic inside DEI is then reported
+ * as a failure; in a release build it aborts the process instead.
+ */
+
+#ifndef DEI_H
+#define DEI_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Analyze a file or directory with the nearest dei.toml and the custom rules in .dei/rules
+ * next to it, and return the findings as JSON, or NULL on failure, when dei_last_error
+ * says why. */
+char *dei_analyze(const char *path);
+
+/* Free a string returned by dei_analyze; NULL is ignored. */
+void dei_string_free(char *string);
+
+/* Why the last call on this thread failed, or NULL if it succeeded. Valid until the next
+ * call on the same thread. */
+const char *dei_last_error(void);
+
+/* Version of the library, e.g. "0.1.1". */
+const char *dei_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DEI_H */
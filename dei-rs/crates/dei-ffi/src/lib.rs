@@ -0,0 +1,122 @@
+//! C API for embedding DEI
+//!
+//! For IDE plugins and tools in other languages that link against DEI instead of running
+//! the `dei` binary. Build the `cdylib` or `staticlib` with the `ffi` profile and include
+//! `include/dei.h`:
+//!
+//! ```c
+//! char *json = dei_analyze("src");
+//! if (json == NULL) {
+//!     fprintf(stderr, "dei: %s\n", dei_last_error());
+//! } else {
+//!     puts(json);
+//!     dei_string_free(json);
+//! }
+//! ```
+//!
+//! Analyses go through [`dei_engine::project::check`], as `dei check` does: the `dei.toml`
+//! nearest to the analyzed path, the custom rules in `.dei/rules/` next to it, and its
+//! baseline and suppressions. Results are the engine's [`Analysis`](dei_engine::Analysis) as
+//! JSON; strings handed out are owned by the caller and go back through [`dei_string_free`].
+//!
+//! A panic inside DEI is caught at the boundary and reported as an error, since unwinding into
+//! C is undefined behavior. That takes a build whose panics unwind: the `ffi` profile
+//! (`cargo build -p dei-ffi --profile ffi`), or `dev`. Under `release`, which aborts on
+//! panic to keep the `dei` binary small, a panic takes the host process down with it.
+
+use dei_engine::{project, Error, Result};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Analyze the file or directory at `path` and return the findings as JSON
+///
+/// Returns null when `path` is null or not UTF-8, or the analysis fails; [`dei_last_error`]
+/// then says why. Free the result with [`dei_string_free`].
+///
+/// # Safety
+///
+/// `path` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dei_analyze(path: *const c_char) -> *mut c_char {
+    clear_error();
+    if path.is_null() {
+        set_error("path is null");
+        return ptr::null_mut();
+    }
+    // SAFETY: the caller passes a valid NUL-terminated string
+    let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+        set_error("path is not valid UTF-8");
+        return ptr::null_mut();
+    };
+
+    // Unwinding into C is undefined behavior; only catches anything in an unwinding build
+    let analyzed = panic::catch_unwind(AssertUnwindSafe(|| analyze(Path::new(path))));
+    let json = match analyzed {
+        Ok(Ok(json)) => json,
+        Ok(Err(e)) => {
+            set_error(&e.to_string());
+            return ptr::null_mut();
+        }
+        Err(_) => {
+            set_error("analysis panicked");
+            return ptr::null_mut();
+        }
+    };
+    match CString::new(json) {
+        Ok(json) => json.into_raw(),
+        Err(_) => {
+            set_error("results contain a NUL byte");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a string returned by [`dei_analyze`]; null is ignored
+///
+/// # Safety
+///
+/// `string` must be null or a pointer returned by [`dei_analyze`] that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dei_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: the string came from `CString::into_raw` and is freed once
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Why the last call on this thread failed, or null if it succeeded
+///
+/// The message is owned by DEI and valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn dei_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Version of the library, e.g. `0.1.1`; a static string not to be freed
+#[no_mangle]
+pub extern "C" fn dei_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+fn analyze(path: &Path) -> Result<String> {
+    let analysis = project::check(path)?;
+    serde_json::to_string(&analysis).map_err(|e| Error::Analysis(e.to_string()))
+}
+
+fn set_error(message: &str) {
+    // Messages with a NUL are cut short rather than lost
+    let message = message.split('\0').next().unwrap_or_default();
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+fn clear_error() {
+    LAST_ERROR.with(|error| *error.borrow_mut() = None);
+}
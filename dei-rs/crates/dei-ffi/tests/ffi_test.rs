@@ -0,0 +1,103 @@
+use assert_cmd::Command;
+use dei::{dei_analyze, dei_last_error, dei_string_free, dei_version};
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::path::Path;
+
+const BUSY: &str = "pub struct Busy;\n\nimpl Busy {\n    fn a(&self) {}\n    fn b(&self) {}\n    fn c(&self) {}\n}\n";
+
+fn analyze(path: &Path) -> serde_json::Value {
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let json = unsafe { dei_analyze(path.as_ptr()) };
+    assert!(!json.is_null(), "{:?}", unsafe { CStr::from_ptr(dei_last_error()) });
+    let analysis = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+    unsafe { dei_string_free(json) };
+    analysis
+}
+
+#[test]
+fn test_analyze_returns_json() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("busy.rs"), BUSY).unwrap();
+    fs::write(dir.path().join("dei.toml"), "[thresholds]\nmax_methods = 2\n").unwrap();
+
+    let path = CString::new(dir.path().to_str().unwrap()).unwrap();
+    let json = unsafe { dei_analyze(path.as_ptr()) };
+    assert!(!json.is_null());
+    assert!(dei_last_error().is_null());
+
+    let analysis: serde_json::Value = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+    unsafe { dei_string_free(json) };
+    let busy = &analysis["results"][0];
+    assert_eq!(busy["class_metrics"]["name"], "Busy");
+    assert_eq!(busy["is_god_class"], true, "dei.toml next to the path is used");
+}
+
+#[test]
+fn test_failures_set_last_error() {
+    let missing = CString::new("/no/such/dir").unwrap();
+    assert!(unsafe { dei_analyze(missing.as_ptr()) }.is_null());
+    let error = unsafe { CStr::from_ptr(dei_last_error()) }.to_str().unwrap();
+    assert!(error.contains("/no/such/dir"), "{}", error);
+
+    assert!(unsafe { dei_analyze(std::ptr::null()) }.is_null());
+    assert!(!dei_last_error().is_null());
+    unsafe { dei_string_free(std::ptr::null_mut()) };
+
+    let version = unsafe { CStr::from_ptr(dei_version()) }.to_str().unwrap();
+    assert_eq!(version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn test_analyze_runs_custom_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("busy.rs"), BUSY).unwrap();
+    fs::create_dir_all(dir.path().join(".dei/rules")).unwrap();
+    fs::write(
+        dir.path().join(".dei/rules/no_busy.rhai"),
+        r#"fn inspect(class) { if class.name == "Busy" { return #{ message: "too busy" }; } }"#,
+    )
+    .unwrap();
+
+    let path = CString::new(dir.path().to_str().unwrap()).unwrap();
+    let json = unsafe { dei_analyze(path.as_ptr()) };
+    assert!(!json.is_null());
+    let analysis: serde_json::Value = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+    unsafe { dei_string_free(json) };
+    let violations = analysis["results"][0]["violations"].as_array().unwrap();
+    assert!(violations.iter().any(|v| v["rule"] == "no_busy" && v["message"] == "too busy"), "{:?}", violations);
+}
+
+#[test]
+fn test_analyze_matches_dei_check() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("busy.rs"), BUSY).unwrap();
+    fs::write(dir.path().join("busier.rs"), BUSY.replace("Busy", "Busier")).unwrap();
+    // Over the size limit, so skipped by both
+    let padding = "// padding\n".repeat(1024 * 1024);
+    fs::write(dir.path().join("huge.rs"), format!("{}{}", BUSY.replace("Busy", "Huge"), padding)).unwrap();
+    fs::write(dir.path().join("dei.toml"), "[thresholds]\nmax_methods = 2\n\n[violations]\nMethodCount = \"error\"\n").unwrap();
+    fs::write(
+        dir.path().join("dei-suppressions.toml"),
+        "[[suppress]]\nfile = \"busy.rs\"\nclass = \"Busy\"\nreason = \"legacy\"\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("dei").unwrap().arg("check").arg(dir.path()).args(["--format", "json"]).output().unwrap();
+    let mut report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    // Coupling is an annotation of the check report, not part of the analysis
+    for result in report["results"].as_array_mut().unwrap() {
+        result.as_object_mut().unwrap().remove("coupling");
+    }
+
+    let analysis = analyze(dir.path());
+    assert_eq!(analysis["results"], report["results"]);
+    assert_eq!(analysis["god_files"], report["god_files"]);
+
+    let mut names: Vec<_> = analysis["results"].as_array().unwrap().iter().map(|r| r["class_metrics"]["name"].to_string()).collect();
+    names.sort();
+    assert_eq!(names, ["\"Busier\"", "\"Busy\""]);
+    let flagged = |name: &str| analysis["results"].as_array().unwrap().iter().find(|r| r["class_metrics"]["name"] == name).unwrap()["is_god_class"].clone();
+    assert_eq!(flagged("Busier"), true);
+    assert_eq!(flagged("Busy"), false, "suppressed findings are left out");
+}
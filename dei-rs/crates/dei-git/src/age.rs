@@ -0,0 +1,34 @@
+//! Age-of-code weighting
+//!
+//! An old god class that nobody touches is stable; a young one that keeps
+//! growing is where a refactoring still pays off before it sets.
+
+use dei_core::models::AnalysisResult;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::blame::BlameCache;
+use crate::repo::{unix_seconds, GitRepo};
+
+/// Attach [`CodeAge`](dei_core::models::CodeAge) to every result with issues
+///
+/// `window` is the growth window ending at `now`; classes outside the
+/// repository or without a known span are left without an age.
+pub fn annotate_code_age(repo: &GitRepo, results: &mut [AnalysisResult], now: SystemTime, window: Duration) {
+    let window_start = unix_seconds(now.checked_sub(window).unwrap_or(UNIX_EPOCH));
+    let window_days = window.as_secs() / (24 * 60 * 60);
+    let mut blames = BlameCache::new(repo);
+
+    for result in results.iter_mut().filter(|r| r.has_issues()) {
+        let class = &result.class_metrics;
+        result.code_age = blames
+            .get(&class.file_path)
+            .and_then(|blame| blame.code_age(class.span, window_start, window_days));
+    }
+}
+
+/// Order results by [`AnalysisResult::growth_priority`], highest first
+///
+/// Healthy classes keep their relative order at the end.
+pub fn prioritize_by_growth(results: &mut [AnalysisResult]) {
+    results.sort_by(|a, b| b.growth_priority().total_cmp(&a.growth_priority()));
+}
@@ -0,0 +1,241 @@
+//! Blame attribution of flagged code
+//!
+//! Meant for targeted coaching: who last shaped a flagged class or method, and
+//! therefore who is best placed to refactor it, not who is "at fault".
+
+use dei_core::{
+    metrics::LineSpan,
+    models::{AnalysisResult, CodeAge},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::repo::GitRepo;
+
+/// Share of a span an author must own to be attributed
+pub const DEFAULT_MIN_SHARE: f64 = 0.25;
+
+/// Author of a single committed line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineAuthor {
+    name: Arc<str>,
+    email: Arc<str>,
+    /// Commit time, seconds since the epoch
+    time: i64,
+}
+
+/// Per-line authorship of a file as of HEAD
+#[derive(Debug, Clone, Default)]
+pub struct FileBlame {
+    /// Indexed by 0-based line number
+    lines: Vec<LineAuthor>,
+}
+
+impl FileBlame {
+    pub(crate) fn from_git(blame: &git2::Blame<'_>) -> Self {
+        let mut lines = Vec::new();
+        for hunk in blame.iter() {
+            let signature = hunk.final_signature();
+            let author = LineAuthor {
+                name: signature.name().unwrap_or("unknown").into(),
+                email: signature.email().unwrap_or_default().to_lowercase().into(),
+                time: signature.when().seconds(),
+            };
+            lines.extend(std::iter::repeat_n(author, hunk.lines_in_hunk()));
+        }
+        Self { lines }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Authors of the committed lines in `span`, most lines first
+    pub fn authors(&self, span: LineSpan) -> Vec<AuthorShare> {
+        if !span.is_known() {
+            return Vec::new();
+        }
+
+        let end = span.end.min(self.lines.len());
+        let mut shares: Vec<AuthorShare> = Vec::new();
+        for line in self.lines.get(span.start - 1..end).unwrap_or_default() {
+            match shares.iter_mut().find(|s| s.email == line.email) {
+                Some(share) => {
+                    share.lines += 1;
+                    share.last_touched = share.last_touched.max(line.time);
+                }
+                None => shares.push(AuthorShare {
+                    name: line.name.clone(),
+                    email: line.email.clone(),
+                    lines: 1,
+                    last_touched: line.time,
+                }),
+            }
+        }
+
+        shares.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| b.last_touched.cmp(&a.last_touched)));
+        shares
+    }
+
+    /// Oldest and newest commit times in `span` and the share changed since `window_start`
+    pub fn code_age(&self, span: LineSpan, window_start: i64, window_days: u64) -> Option<CodeAge> {
+        if !span.is_known() {
+            return None;
+        }
+        let end = span.end.min(self.lines.len());
+        let lines = self.lines.get(span.start - 1..end)?;
+        if lines.is_empty() {
+            return None;
+        }
+
+        let recent = lines.iter().filter(|l| l.time >= window_start).count();
+        Some(CodeAge {
+            first_seen: lines.iter().map(|l| l.time).min()?,
+            last_changed: lines.iter().map(|l| l.time).max()?,
+            recent_share: recent as f64 / lines.len() as f64,
+            window_days,
+        })
+    }
+
+    /// Authors owning at least `min_share` of `span`, most recently active first
+    ///
+    /// Falls back to the largest owner so a span with many small contributors
+    /// is still attributed to someone.
+    pub fn significant_authors(&self, span: LineSpan, min_share: f64) -> Vec<AuthorShare> {
+        let authors = self.authors(span);
+        let total: usize = authors.iter().map(|a| a.lines).sum();
+        let mut significant: Vec<AuthorShare> = authors
+            .iter()
+            .filter(|a| a.lines as f64 >= total as f64 * min_share)
+            .cloned()
+            .collect();
+
+        if significant.is_empty() {
+            significant.extend(authors.into_iter().take(1));
+        }
+        significant.sort_by_key(|a| std::cmp::Reverse(a.last_touched));
+        significant
+    }
+}
+
+/// Files blamed on first use, keyed by repository-relative path
+pub(crate) struct BlameCache<'r> {
+    repo: &'r GitRepo,
+    files: HashMap<PathBuf, Option<FileBlame>>,
+}
+
+impl<'r> BlameCache<'r> {
+    pub(crate) fn new(repo: &'r GitRepo) -> Self {
+        Self {
+            repo,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Blame for an analyzed file, `None` if it is outside the repository or uncommitted
+    pub(crate) fn get(&mut self, file_path: &str) -> Option<&FileBlame> {
+        let relative = self.repo.relative(Path::new(file_path))?;
+        let repo = self.repo;
+        self.files
+            .entry(relative)
+            .or_insert_with_key(|relative| repo.blame(relative).ok())
+            .as_ref()
+    }
+}
+
+/// How much of a span one author wrote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuthorShare {
+    pub name: Arc<str>,
+    pub email: Arc<str>,
+    pub lines: usize,
+    /// Most recent commit time on these lines, seconds since the epoch
+    pub last_touched: i64,
+}
+
+/// A flagged class or method and the authors it is attributed to
+#[derive(Debug, Clone, Serialize)]
+pub struct Attribution {
+    /// `Class` or `Class::method`
+    pub subject: Arc<str>,
+    pub file_path: Arc<str>,
+    pub span: LineSpan,
+    pub authors: Vec<AuthorShare>,
+}
+
+/// Aggregate over all attributions for one author
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Contributor {
+    pub name: Arc<str>,
+    pub email: Arc<str>,
+    /// Flagged classes and methods attributed to this author
+    pub flagged: usize,
+    /// Lines this author owns inside flagged spans
+    pub lines: usize,
+}
+
+/// Attribute every flagged class and god method to its significant authors
+///
+/// Files outside the repository or without committed history are skipped.
+pub fn attribute_violations(repo: &GitRepo, results: &[AnalysisResult], min_share: f64) -> Vec<Attribution> {
+    let mut blames = BlameCache::new(repo);
+    let mut attributions = Vec::new();
+    for result in results {
+        let class = &result.class_metrics;
+        let class_flagged = result.is_god_class || !result.violations.is_empty();
+        if !class_flagged && result.god_methods.is_empty() {
+            continue;
+        }
+        let Some(blame) = blames.get(&class.file_path) else {
+            continue;
+        };
+
+        let mut attribute = |subject: Arc<str>, span: LineSpan| {
+            let authors = blame.significant_authors(span, min_share);
+            if !authors.is_empty() {
+                attributions.push(Attribution {
+                    subject,
+                    file_path: class.file_path.clone(),
+                    span,
+                    authors,
+                });
+            }
+        };
+
+        if class_flagged {
+            attribute(class.name.clone(), class.span);
+        }
+        for god_method in result.god_methods.iter() {
+            let subject = format!("{}::{}", class.name, god_method.method_name);
+            attribute(subject.into(), god_method.metrics.span);
+        }
+    }
+
+    attributions
+}
+
+/// Authors ranked by how many flagged items they are attributed to, then by lines
+pub fn top_contributors(attributions: &[Attribution]) -> Vec<Contributor> {
+    let mut by_email: HashMap<Arc<str>, Contributor> = HashMap::new();
+    for author in attributions.iter().flat_map(|a| a.authors.iter()) {
+        let contributor = by_email.entry(author.email.clone()).or_insert_with(|| Contributor {
+            name: author.name.clone(),
+            email: author.email.clone(),
+            flagged: 0,
+            lines: 0,
+        });
+        contributor.flagged += 1;
+        contributor.lines += author.lines;
+    }
+
+    let mut contributors: Vec<Contributor> = by_email.into_values().collect();
+    contributors.sort_by(|a, b| {
+        b.flagged
+            .cmp(&a.flagged)
+            .then_with(|| b.lines.cmp(&a.lines))
+            .then_with(|| a.email.cmp(&b.email))
+    });
+    contributors
+}
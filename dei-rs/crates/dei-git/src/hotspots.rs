@@ -0,0 +1,54 @@
+//! Churn × complexity hotspots
+//!
+//! Complex code that rarely changes is cheap to leave alone; complex code that
+//! changes every week is where refactoring pays off first.
+
+use dei_core::metrics::FileMetrics;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::repo::{FileChurn, GitRepo};
+
+/// A file ranked by how much its complexity is exercised by change
+#[derive(Debug, Clone, Serialize)]
+pub struct Hotspot {
+    /// Repository-relative path
+    pub path: PathBuf,
+    pub churn: FileChurn,
+    /// Summed cyclomatic complexity of the file's classes
+    pub complexity: usize,
+    pub lines: usize,
+    /// `churn.commits × complexity`
+    pub score: usize,
+}
+
+/// Rank analyzed files by hotspot score, highest first
+///
+/// Files outside the repository, without history in the window or without
+/// measured complexity are left out.
+pub fn rank_hotspots<'a>(
+    repo: &GitRepo,
+    files: impl IntoIterator<Item = &'a FileMetrics>,
+    churn: &HashMap<PathBuf, FileChurn>,
+) -> Vec<Hotspot> {
+    let mut hotspots: Vec<Hotspot> = files
+        .into_iter()
+        .filter_map(|file| {
+            let path = repo.relative(Path::new(file.path.as_ref()))?;
+            let churn = *churn.get(&path)?;
+            let complexity: usize = file.classes.iter().map(|c| c.complexity.0).sum();
+
+            (complexity > 0).then(|| Hotspot {
+                path,
+                churn,
+                complexity,
+                lines: file.lines.0,
+                score: churn.commits * complexity,
+            })
+        })
+        .collect();
+
+    hotspots.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    hotspots
+}
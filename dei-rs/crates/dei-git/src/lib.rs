@@ -0,0 +1,15 @@
+//! Git history signals for prioritising refactoring work
+//!
+//! History is read through libgit2, so no `git` binary is needed at runtime.
+
+pub mod age;
+pub mod blame;
+pub mod hotspots;
+pub mod ownership;
+pub mod repo;
+
+pub use age::{annotate_code_age, prioritize_by_growth};
+pub use blame::{attribute_violations, top_contributors, Attribution, AuthorShare, Contributor, FileBlame};
+pub use hotspots::{rank_hotspots, Hotspot};
+pub use ownership::{annotate_ownership, dominant_owners};
+pub use repo::{FileChurn, GitRepo};
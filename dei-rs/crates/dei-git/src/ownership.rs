@@ -0,0 +1,55 @@
+//! Code ownership of flagged classes and methods
+//!
+//! Routes refactoring work: each flagged item lists the authors who wrote a
+//! meaningful share of its committed lines, largest share first.
+
+use dei_core::{
+    metrics::LineSpan,
+    models::{AnalysisResult, GodMethodResult, Owner},
+};
+use std::sync::Arc;
+
+use crate::blame::{BlameCache, FileBlame};
+use crate::repo::GitRepo;
+
+/// Fill `owners` on every result with issues and on each of its god methods
+///
+/// Authors below `min_share` of an item's lines are left out, except that the
+/// largest owner is always kept.
+pub fn annotate_ownership(repo: &GitRepo, results: &mut [AnalysisResult], min_share: f64) {
+    let mut blames = BlameCache::new(repo);
+
+    for result in results.iter_mut().filter(|r| r.has_issues()) {
+        let Some(blame) = blames.get(&result.class_metrics.file_path) else {
+            continue;
+        };
+
+        result.owners = dominant_owners(blame, result.class_metrics.span, min_share);
+        result.god_methods = result
+            .god_methods
+            .iter()
+            .map(|god_method| GodMethodResult {
+                owners: dominant_owners(blame, god_method.metrics.span, min_share),
+                ..god_method.clone()
+            })
+            .collect();
+    }
+}
+
+/// Authors of `span` holding at least `min_share` of it, largest share first
+pub fn dominant_owners(blame: &FileBlame, span: LineSpan, min_share: f64) -> Arc<[Owner]> {
+    let authors = blame.authors(span);
+    let total: usize = authors.iter().map(|a| a.lines).sum();
+
+    authors
+        .iter()
+        .enumerate()
+        .filter(|(rank, a)| *rank == 0 || a.lines as f64 >= total as f64 * min_share)
+        .map(|(_, a)| Owner {
+            name: a.name.clone(),
+            email: a.email.clone(),
+            lines: a.lines,
+            share: a.lines as f64 / total as f64,
+        })
+        .collect()
+}
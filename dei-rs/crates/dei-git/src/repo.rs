@@ -0,0 +1,178 @@
+//! Repository access and per-file churn
+
+use dei_core::{error::Result, Error};
+use git2::build::CheckoutBuilder;
+use git2::{Delta, DiffOptions, Patch, Repository, Sort, Tree};
+
+use crate::blame::FileBlame;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How much a file changed over the walked history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FileChurn {
+    /// Non-merge commits touching the file
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_deleted: usize,
+}
+
+impl FileChurn {
+    pub fn lines_changed(&self) -> usize {
+        self.lines_added + self.lines_deleted
+    }
+}
+
+/// A non-bare repository and its canonical working directory
+pub struct GitRepo {
+    repo: Repository,
+    workdir: PathBuf,
+}
+
+impl GitRepo {
+    /// Open the repository containing `path`
+    pub fn discover(path: &Path) -> Result<Self> {
+        let repo = Repository::discover(path).map_err(git_error)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| Error::Git("bare repositories are not supported".into()))?
+            .canonicalize()?;
+
+        Ok(Self { repo, workdir })
+    }
+
+    pub fn workdir(&self) -> &Path {
+        &self.workdir
+    }
+
+    /// Id of the commit HEAD points at, `None` before the first commit
+    pub fn head_commit(&self) -> Option<String> {
+        let commit = self.repo.head().ok()?.peel_to_commit().ok()?;
+        Some(commit.id().to_string())
+    }
+
+    /// Repository-relative form of a path on disk, if it lives in this repository
+    ///
+    /// Deleted files are resolved through their parent directory.
+    pub fn relative(&self, path: &Path) -> Option<PathBuf> {
+        let path = path.canonicalize().ok().or_else(|| {
+            let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            Some(parent.canonicalize().ok()?.join(path.file_name()?))
+        })?;
+        path.strip_prefix(&self.workdir).ok().map(Path::to_path_buf)
+    }
+
+    /// Churn per repository-relative path over non-merge commits reachable from HEAD
+    ///
+    /// With `since`, only commits authored at or after that time are counted.
+    pub fn churn(&self, since: Option<SystemTime>) -> Result<HashMap<PathBuf, FileChurn>> {
+        let since = since.map(unix_seconds);
+        let mut churn: HashMap<PathBuf, FileChurn> = HashMap::new();
+
+        let mut walk = self.repo.revwalk().map_err(git_error)?;
+        walk.set_sorting(Sort::TIME).map_err(git_error)?;
+        walk.push_head().map_err(git_error)?;
+
+        for oid in walk {
+            let commit = self.repo.find_commit(oid.map_err(git_error)?).map_err(git_error)?;
+            if commit.parent_count() > 1 || since.is_some_and(|s| commit.time().seconds() < s) {
+                continue;
+            }
+
+            let tree = commit.tree().map_err(git_error)?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree().map_err(git_error)?),
+                Err(_) => None,
+            };
+            let diff = self
+                .repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+                .map_err(git_error)?;
+
+            for idx in 0..diff.deltas().len() {
+                let Some(patch) = Patch::from_diff(&diff, idx).map_err(git_error)? else {
+                    continue;
+                };
+                let delta = patch.delta();
+                let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                    continue;
+                };
+                let (_, added, deleted) = patch.line_stats().map_err(git_error)?;
+
+                let entry = churn.entry(path.to_path_buf()).or_default();
+                entry.commits += 1;
+                entry.lines_added += added;
+                entry.lines_deleted += deleted;
+            }
+        }
+
+        Ok(churn)
+    }
+
+    /// Repository-relative files added or modified since `base`, working tree changes included
+    ///
+    /// Deleted files are left out; untracked files count as added.
+    pub fn changed_files(&self, base: &str) -> Result<Vec<PathBuf>> {
+        let base_tree = self.tree_at(base)?;
+        let mut options = DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+
+        let diff = self
+            .repo
+            .diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut options))
+            .map_err(git_error)?;
+
+        let mut files: Vec<PathBuf> = diff
+            .deltas()
+            .filter(|delta| delta.status() != Delta::Deleted)
+            .filter_map(|delta| delta.new_file().path().map(Path::to_path_buf))
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Contents of a repository-relative file at `base`, `None` if it did not exist there
+    pub fn file_at(&self, base: &str, relative: &Path) -> Result<Option<Vec<u8>>> {
+        let tree = self.tree_at(base)?;
+        let Ok(entry) = tree.get_path(relative) else {
+            return Ok(None);
+        };
+        let blob = entry.to_object(&self.repo).and_then(|o| o.peel_to_blob()).map_err(git_error)?;
+        Ok(Some(blob.content().to_vec()))
+    }
+
+    /// Write every file of the tree at `rev` into `dir`, leaving HEAD, the index and the
+    /// working tree alone
+    pub fn checkout_to(&self, rev: &str, dir: &Path) -> Result<()> {
+        let tree = self.tree_at(rev)?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.target_dir(dir).update_index(false).force();
+        self.repo
+            .checkout_tree(tree.as_object(), Some(&mut checkout))
+            .map_err(git_error)
+    }
+
+    fn tree_at(&self, base: &str) -> Result<Tree<'_>> {
+        self.repo
+            .revparse_single(base)
+            .and_then(|object| object.peel_to_tree())
+            .map_err(git_error)
+    }
+
+    /// Per-line authorship of a repository-relative file as committed at HEAD
+    pub fn blame(&self, relative: &Path) -> Result<FileBlame> {
+        let blame = self.repo.blame_file(relative, None).map_err(git_error)?;
+        Ok(FileBlame::from_git(&blame))
+    }
+}
+
+pub(crate) fn git_error(err: git2::Error) -> Error {
+    Error::Git(err.message().to_string())
+}
+
+pub(crate) fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default()
+}
@@ -0,0 +1,288 @@
+use dei_core::{metrics::*, models::*, thresholds::*};
+use dei_git::{
+    annotate_code_age, annotate_ownership, attribute_violations, prioritize_by_growth, rank_hotspots, top_contributors, GitRepo,
+};
+use git2::{Repository, Signature, Time};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Commit the given files (path, content) at `secs` since the epoch
+fn commit(repo: &Repository, secs: i64, files: &[(&str, &str)]) {
+    commit_as(repo, "Dev", secs, files);
+}
+
+fn commit_as(repo: &Repository, author: &str, secs: i64, files: &[(&str, &str)]) {
+    let workdir = repo.workdir().unwrap();
+    let mut index = repo.index().unwrap();
+    for (path, content) in files {
+        std::fs::write(workdir.join(path), content).unwrap();
+        index.add_path(Path::new(path)).unwrap();
+    }
+    index.write().unwrap();
+
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::new(author, &format!("{}@example.com", author.to_lowercase()), &Time::new(secs, 0)).unwrap();
+    let parents: Vec<_> = repo.head().ok().and_then(|h| h.peel_to_commit().ok()).into_iter().collect();
+    let parents: Vec<_> = parents.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, "change", &tree, &parents).unwrap();
+}
+
+fn class(path: &Path, complexity: usize, span: LineSpan) -> ClassMetrics {
+    ClassMetrics {
+        name: "Widget".into(),
+        fully_qualified_name: "Widget".into(),
+        file_path: path.to_string_lossy().as_ref().into(),
+        lines: Lines(10),
+        method_count: MethodCount(0),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(complexity),
+        methods: Arc::new([]),
+        dependencies: Arc::new([]),
+        span,
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    }
+}
+
+fn file(path: &Path, complexity: usize) -> FileMetrics {
+    FileMetrics {
+        path: path.to_string_lossy().as_ref().into(),
+        lines: Lines(10),
+        classes: Arc::new([class(path, complexity, LineSpan::default())]),
+        top_level_functions: 0,
+        imports: 0,
+        imported_symbols: 0,
+        wildcard_imports: Arc::new([]),
+        entry_point: None,
+        encoding: None,
+    }
+}
+
+#[test]
+fn test_churn_and_hotspot_ranking() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let long_ago = now - 400 * 24 * 60 * 60;
+    commit(&repo, long_ago, &[("hot.rs", "a\n"), ("calm.rs", "a\nb\nc\n")]);
+    commit(&repo, now - 60, &[("hot.rs", "a\nb\n")]);
+    commit(&repo, now, &[("hot.rs", "c\n")]);
+
+    let git = GitRepo::discover(dir.path()).unwrap();
+    let churn = git.churn(None).unwrap();
+    let hot = churn[Path::new("hot.rs")];
+    assert_eq!(hot.commits, 3);
+    assert_eq!((hot.lines_added, hot.lines_deleted), (3, 2));
+    assert_eq!(churn[Path::new("calm.rs")].commits, 1);
+
+    // A time window drops old commits entirely
+    let recent = git.churn(Some(SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60))).unwrap();
+    assert_eq!(recent[Path::new("hot.rs")].commits, 2);
+    assert!(!recent.contains_key(Path::new("calm.rs")));
+
+    // Simple but busy beats complex but stable
+    let files = [
+        file(&dir.path().join("hot.rs"), 10),
+        file(&dir.path().join("calm.rs"), 25),
+        file(&dir.path().join("untracked.rs"), 99),
+    ];
+    let hotspots = rank_hotspots(&git, &files, &churn);
+    let ranked: Vec<(&Path, usize)> = hotspots.iter().map(|h| (h.path.as_path(), h.score)).collect();
+    assert_eq!(ranked, [(Path::new("hot.rs"), 30), (Path::new("calm.rs"), 25)]);
+}
+
+#[test]
+fn test_blame_attribution_of_flagged_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let original: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    let rewritten: String = (1..=10)
+        .map(|i| if i > 6 { format!("bob {}\n", i) } else { format!("line {}\n", i) })
+        .collect();
+    commit_as(&repo, "Alice", 1_000, &[("widget.rs", &original)]);
+    commit_as(&repo, "Bob", 2_000, &[("widget.rs", &rewritten)]);
+
+    let git = GitRepo::discover(dir.path()).unwrap();
+    let blame = git.blame(Path::new("widget.rs")).unwrap();
+    assert_eq!(blame.line_count(), 10);
+
+    let authors = blame.authors(LineSpan::new(1, 10));
+    let shares: Vec<(&str, usize)> = authors
+        .iter()
+        .map(|a| (a.name.as_ref(), a.lines))
+        .collect();
+    assert_eq!(shares, [("Alice", 6), ("Bob", 4)]);
+
+    // Significant authors are listed most recent first
+    let significant = blame.significant_authors(LineSpan::new(1, 10), 0.25);
+    let names: Vec<&str> = significant.iter().map(|a| a.name.as_ref()).collect();
+    assert_eq!(names, ["Bob", "Alice"]);
+    assert_eq!(significant[0].last_touched, 2_000);
+
+    // A minor share drops out, unknown spans attribute to nobody
+    let names: Vec<String> = blame
+        .significant_authors(LineSpan::new(1, 8), 0.5)
+        .into_iter()
+        .map(|a| a.name.to_string())
+        .collect();
+    assert_eq!(names, ["Alice"]);
+    assert!(blame.authors(LineSpan::default()).is_empty());
+
+    let path = dir.path().join("widget.rs");
+    let results = [
+        AnalysisResult {
+            is_god_class: true,
+            ..AnalysisResult::healthy(class(&path, 60, LineSpan::new(7, 10)))
+        },
+        AnalysisResult::healthy(class(&path, 1, LineSpan::new(1, 6))),
+        AnalysisResult {
+            is_god_class: true,
+            ..AnalysisResult::healthy(class(&dir.path().join("untracked.rs"), 60, LineSpan::new(1, 3)))
+        },
+    ];
+    let attributions = attribute_violations(&git, &results, 0.25);
+    assert_eq!(attributions.len(), 1, "Healthy and untracked classes are not attributed");
+    assert_eq!(attributions[0].subject.as_ref(), "Widget");
+
+    let contributors = top_contributors(&attributions);
+    let tally: Vec<(&str, usize, usize)> = contributors
+        .iter()
+        .map(|c| (c.name.as_ref(), c.flagged, c.lines))
+        .collect();
+    assert_eq!(tally, [("Bob", 1, 4)]);
+}
+
+#[test]
+fn test_age_weighting_ranks_growing_code_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let day = 24 * 60 * 60;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let body: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    commit(&repo, now - 400 * day, &[("old.rs", &body)]);
+    commit(&repo, now - 10 * day, &[("young.rs", &body)]);
+
+    let flagged = |file: &str, complexity: usize| AnalysisResult {
+        is_god_class: true,
+        ..AnalysisResult::healthy(class(&dir.path().join(file), complexity, LineSpan::new(1, 10)))
+    };
+    let mut results = vec![
+        AnalysisResult::healthy(class(&dir.path().join("old.rs"), 5, LineSpan::new(1, 10))),
+        flagged("old.rs", 60),
+        flagged("young.rs", 40),
+    ];
+
+    let git = GitRepo::discover(dir.path()).unwrap();
+    annotate_code_age(&git, &mut results, SystemTime::now(), Duration::from_secs(90 * day as u64));
+
+    let old = results[1].code_age.unwrap();
+    assert_eq!(old.age_days(now), 400);
+    assert_eq!(old.recent_share, 0.0);
+    assert!(!old.is_growing());
+    let young = results[2].code_age.unwrap();
+    assert_eq!(young.window_days, 90);
+    assert!(young.is_growing());
+    assert!(results[0].code_age.is_none(), "Healthy classes are not blamed");
+
+    // Young but less complex outranks old and stable
+    prioritize_by_growth(&mut results);
+    let order: Vec<(&str, usize)> = results
+        .iter()
+        .map(|r| (r.class_metrics.file_path.rsplit('/').next().unwrap(), r.class_metrics.complexity.0))
+        .collect();
+    assert_eq!(order, [("young.rs", 40), ("old.rs", 60), ("old.rs", 5)]);
+}
+
+#[test]
+fn test_ownership_of_flagged_classes_and_methods() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let original: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    let rewritten: String = (1..=10)
+        .map(|i| if i > 8 { format!("carol {}\n", i) } else { format!("line {}\n", i) })
+        .collect();
+    commit_as(&repo, "Alice", 1_000, &[("widget.rs", &original)]);
+    commit_as(&repo, "Carol", 2_000, &[("widget.rs", &rewritten)]);
+
+    let path = dir.path().join("widget.rs");
+    let method = MethodMetrics {
+        name: "render".into(),
+        lines: Lines(2),
+        complexity: Complexity(1),
+        parameters: ParamCount(0),
+        params: Arc::new([]),
+        generics: Arc::new([]),
+        nesting_depth: NestingDepth(0),
+        chain_length: ChainLength(0),
+        exit_points: 0,
+        magic_literals: 0,
+        span: LineSpan::new(9, 10),
+        called_methods: Arc::new([]),
+        accessed_fields: Arc::new([]),
+        return_type: "()".into(),
+        is_public: true,
+        is_static: false,
+        is_async: false,
+        tokens: Arc::new([]),
+        ast_snippet: None,
+    };
+    let god_method = GodMethodResult {
+        method_name: "render".into(),
+        class_name: "Widget".into(),
+        file_path: path.to_string_lossy().as_ref().into(),
+        metrics: method,
+        violations: Arc::new([]),
+        violation_score: 1.0,
+        owners: Arc::new([]),
+    };
+    let mut results = [
+        AnalysisResult {
+            god_methods: Arc::new([god_method]),
+            ..AnalysisResult::healthy(class(&path, 10, LineSpan::new(1, 10)))
+        },
+        AnalysisResult::healthy(class(&path, 1, LineSpan::new(1, 10))),
+    ];
+
+    let git = GitRepo::discover(dir.path()).unwrap();
+    annotate_ownership(&git, &mut results, 0.25);
+
+    // Carol's 20% share of the class is below the bar, Alice's 80% dominates
+    let owners: Vec<(&str, usize)> = results[0].owners.iter().map(|o| (o.name.as_ref(), o.lines)).collect();
+    assert_eq!(owners, [("Alice", 8)]);
+    assert_eq!(results[0].owners[0].share, 0.8);
+
+    let method_owners = &results[0].god_methods[0].owners;
+    assert_eq!(method_owners.len(), 1);
+    assert_eq!(method_owners[0].name.as_ref(), "Carol");
+    assert_eq!(method_owners[0].email.as_ref(), "carol@example.com");
+
+    assert!(results[1].owners.is_empty(), "Healthy classes are not blamed");
+}
+
+#[test]
+fn test_checkout_to_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit(&repo, 1_000, &[("lib.rs", "old\n")]);
+    commit(&repo, 2_000, &[("lib.rs", "new\n"), ("added.rs", "a\n")]);
+
+    let git = GitRepo::discover(dir.path()).unwrap();
+    assert_eq!(git.head_commit(), Some(repo.head().unwrap().target().unwrap().to_string()));
+    let target = tempfile::tempdir().unwrap();
+    git.checkout_to("HEAD~1", target.path()).unwrap();
+
+    assert_eq!(std::fs::read_to_string(target.path().join("lib.rs")).unwrap(), "old\n");
+    assert!(!target.path().join("added.rs").exists());
+    // The repository itself is untouched
+    assert_eq!(std::fs::read_to_string(dir.path().join("lib.rs")).unwrap(), "new\n");
+    assert!(git.changed_files("HEAD").unwrap().is_empty());
+
+    assert!(git.checkout_to("no-such-ref", target.path()).is_err());
+}
@@ -3,8 +3,17 @@
 //! Improved algorithm using tree-sitter for accurate AST-based analysis
 
 use dei_core::{metrics::*, thresholds::*};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::Arc;
 use tree_sitter::Node;
 
+use crate::usage::{is_member_access, is_self_receiver, member_parts};
+
+/// Type suffixes on numeric literals (`1u32`, `1.0f`, `1L`, `1m`)
+static NUMERIC_SUFFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(?:[iu](?:8|16|32|64|128|size)|f(?:32|64)?|d|m|ul?|lu?)$").unwrap());
+
 /// Calculate complexity from tree-sitter AST
 pub struct ComplexityCalculator;
 
@@ -14,7 +23,7 @@ impl ComplexityCalculator {
     pub fn calculate_from_tree(node: &Node, source: &[u8]) -> Complexity {
         let mut complexity = 1; // Base complexity
 
-        let mut visit_stack = vec![node.clone()];
+        let mut visit_stack = vec![*node];
 
         while let Some(current) = visit_stack.pop() {
             match current.kind() {
@@ -56,6 +65,107 @@ impl ComplexityCalculator {
         Complexity(complexity)
     }
 
+    /// Deepest nesting of control-flow blocks within a function node
+    /// `else if` chains count as a single level, matching how they read
+    pub fn max_nesting_depth(node: &Node) -> NestingDepth {
+        fn walk(node: &Node, depth: usize) -> usize {
+            let depth = if is_nesting_block(node) && !is_else_if(node) {
+                depth + 1
+            } else {
+                depth
+            };
+
+            let mut max = depth;
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                max = max.max(walk(&child, depth));
+            }
+            max
+        }
+
+        NestingDepth(walk(node, 0))
+    }
+
+    /// Longest accessor chain within a function node (`a.b().c().d()` → 3)
+    /// Links on `self`/`this` are not counted, so `self.repo.find()` is 1
+    pub fn max_chain_length(node: &Node, source: &[u8]) -> ChainLength {
+        fn walk(node: &Node, source: &[u8]) -> usize {
+            let own = if chain_receiver(node).is_some() {
+                chain_length(node, source)
+            } else {
+                0
+            };
+
+            let mut max = own;
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                max = max.max(walk(&child, source));
+            }
+            max
+        }
+
+        ChainLength(walk(node, source))
+    }
+
+    /// Count `return`, `break`, `continue` and `throw`/`raise` statements
+    /// Exits inside nested closures and local functions belong to them and are skipped
+    pub fn count_exit_points(node: &Node) -> usize {
+        fn walk(node: &Node, is_root: bool) -> usize {
+            if !is_root && is_nested_function(node) {
+                return 0;
+            }
+
+            let own = usize::from(matches!(
+                node.kind(),
+                "return_expression" | "return_statement"
+                    | "break_expression" | "break_statement"
+                    | "continue_expression" | "continue_statement"
+                    | "throw_statement" | "throw_expression" | "raise_statement"
+            ));
+
+            let mut cursor = node.walk();
+            own + node.children(&mut cursor).map(|child| walk(&child, false)).sum::<usize>()
+        }
+
+        walk(node, true)
+    }
+
+    /// Count numeric and string literals other than `0`, `1` and `""`
+    /// Bare string statements (docstrings) are not counted
+    pub fn count_magic_literals(node: &Node, source: &[u8]) -> usize {
+        if is_literal(node) {
+            let is_docstring = node.parent().is_some_and(|p| p.kind() == "expression_statement")
+                && is_string_literal(node);
+            let trivial = node.utf8_text(source).map(is_trivial_literal).unwrap_or(true);
+            return usize::from(!is_docstring && !trivial);
+        }
+
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .map(|child| Self::count_magic_literals(&child, source))
+            .sum()
+    }
+
+    /// 1-based line range a node covers
+    pub fn span(node: &Node) -> LineSpan {
+        LineSpan::new(node.start_position().row + 1, node.end_position().row + 1)
+    }
+
+    /// Free functions directly under `root`, looking through decorators and `export`
+    pub fn count_top_level_functions(root: &Node, kinds: &[&str]) -> usize {
+        let mut cursor = root.walk();
+        root.children(&mut cursor)
+            .filter(|child| {
+                let inner = match child.kind() {
+                    "decorated_definition" => child.child_by_field_name("definition"),
+                    "export_statement" => child.child_by_field_name("declaration"),
+                    _ => Some(*child),
+                };
+                inner.is_some_and(|n| kinds.contains(&n.kind()))
+            })
+            .count()
+    }
+
     /// Count non-blank, non-comment lines
     pub fn count_lines(source: &str) -> Lines {
         let count = source
@@ -74,6 +184,7 @@ impl ComplexityCalculator {
     }
 
     /// Extract parameter count from function node
+    /// Receivers (`self`, `&mut self`, `self: Box<Self>`) are not counted
     pub fn count_parameters(node: &Node, _source: &[u8]) -> ParamCount {
         let mut count = 0;
 
@@ -82,7 +193,11 @@ impl ComplexityCalculator {
             let mut cursor = params.walk();
             for child in params.children(&mut cursor) {
                 match child.kind() {
-                    "parameter" | "parameter_declaration" | "identifier" => count += 1,
+                    "parameter" | "parameter_declaration" | "identifier"
+                        if !Self::is_self_receiver(&child) =>
+                    {
+                        count += 1;
+                    }
                     _ => {}
                 }
             }
@@ -91,6 +206,126 @@ impl ComplexityCalculator {
         ParamCount(count)
     }
 
+    /// Check whether a function node takes a `self` receiver
+    pub fn has_self_receiver(node: &Node) -> bool {
+        node.child_by_field_name("parameters")
+            .map(|params| {
+                let mut cursor = params.walk();
+                let found = params.children(&mut cursor).any(|c| Self::is_self_receiver(&c));
+                found
+            })
+            .unwrap_or(false)
+    }
+
+    /// Receiver parameters: `self_parameter`, or a typed `self: Type` parameter
+    fn is_self_receiver(param: &Node) -> bool {
+        match param.kind() {
+            "self_parameter" | "self" => true,
+            "parameter" => param
+                .child_by_field_name("pattern")
+                .is_some_and(|p| p.kind() == "self"),
+            _ => false,
+        }
+    }
+
+    /// Structured parameter list (name, type, default), receivers excluded
+    pub fn extract_parameters(node: &Node, source: &[u8]) -> Vec<Parameter> {
+        let text = |n: Node| n.utf8_text(source).ok().map(|t| t.trim().to_string());
+        // Type annotations include their `:` in some grammars (TS `type_annotation`)
+        let type_of = |n: &Node| {
+            n.child_by_field_name("type")
+                .and_then(text)
+                .map(|t| t.trim_start_matches(':').trim().to_string())
+                .filter(|t| !t.is_empty())
+        };
+
+        let Some(params) = node
+            .child_by_field_name("parameters")
+            .or_else(|| node.child_by_field_name("parameter"))
+        else {
+            return Vec::new();
+        };
+
+        // Arrow function shorthand: `x => ...`
+        if params.kind() == "identifier" {
+            return text(params).map(Parameter::new).into_iter().collect();
+        }
+
+        let mut result = Vec::new();
+        let mut cursor = params.walk();
+        for (position, child) in params.named_children(&mut cursor).enumerate() {
+            if Self::is_self_receiver(&child) {
+                continue;
+            }
+
+            let name = match child.kind() {
+                "identifier" => text(child),
+                // Rust/TS patterns, Python/C#/Java/R names
+                _ => child
+                    .child_by_field_name("pattern")
+                    .or_else(|| child.child_by_field_name("name"))
+                    .or_else(|| child.child_by_field_name("left"))
+                    .and_then(text)
+                    .or_else(|| match child.kind() {
+                        "typed_parameter" | "list_splat_pattern" | "dictionary_splat_pattern"
+                        | "rest_pattern" | "spread_parameter" => {
+                            let mut inner = child.walk();
+                            let first = child.named_children(&mut inner).next();
+                            first.and_then(text)
+                        }
+                        _ => None,
+                    }),
+            };
+            let Some(name) = name else {
+                continue;
+            };
+
+            // Python/JS have no receiver node; `self`/`cls` is just the first identifier
+            if position == 0 && matches!(name.as_str(), "self" | "cls") {
+                continue;
+            }
+
+            let mut param = Parameter::new(name);
+            if let Some(type_name) = type_of(&child) {
+                param = param.with_type(type_name);
+            }
+            let default = child
+                .child_by_field_name("value")
+                .or_else(|| child.child_by_field_name("default"))
+                .or_else(|| child.child_by_field_name("right"))
+                .and_then(text)
+                .or_else(|| {
+                    // C# `= value` lives in an equals_value_clause
+                    let mut inner = child.walk();
+                    let clause = child
+                        .named_children(&mut inner)
+                        .find(|c| c.kind() == "equals_value_clause");
+                    clause.and_then(|c| c.named_child(0)).and_then(text)
+                });
+            if let Some(default) = default {
+                param = param.with_default(default);
+            }
+            result.push(param);
+        }
+
+        result
+    }
+
+    /// Generic type parameters declared on a function or method
+    pub fn extract_generics(node: &Node, source: &[u8]) -> Vec<Arc<str>> {
+        let Some(generics) = node.child_by_field_name("type_parameters") else {
+            return Vec::new();
+        };
+
+        let mut cursor = generics.walk();
+        let names = generics
+            .named_children(&mut cursor)
+            .filter_map(|c| c.utf8_text(source).ok())
+            .map(Arc::from)
+            .collect();
+        names
+    }
+
     /// Extract tokens for semantic analysis
     /// Uses tree-sitter to get accurate identifiers
     pub fn extract_tokens(node: &Node, source: &[u8]) -> Vec<String> {
@@ -116,18 +351,168 @@ impl ComplexityCalculator {
     }
 }
 
-/// Split camelCase and PascalCase identifiers
+/// Node kinds that open a nested control-flow block across supported grammars
+fn is_nesting_block(node: &Node) -> bool {
+    matches!(
+        node.kind(),
+        "if_expression" | "if_statement" | "unless_statement"
+            | "while_statement" | "while_expression" | "until_statement" | "do_statement"
+            | "for_statement" | "for_expression" | "for_in_statement" | "foreach_statement"
+            | "enhanced_for_statement" | "loop_expression" | "repeat_statement"
+            | "match_expression" | "match_statement" | "switch_statement" | "switch_expression"
+            | "try_statement" | "with_statement"
+    )
+}
+
+/// An `if` that is the `else` branch of another conditional
+fn is_else_if(node: &Node) -> bool {
+    if !matches!(node.kind(), "if_expression" | "if_statement") {
+        return false;
+    }
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    matches!(parent.kind(), "else_clause" | "else")
+        || parent
+            .child_by_field_name("alternative")
+            .is_some_and(|alt| alt.id() == node.id())
+}
+
+fn is_nested_function(node: &Node) -> bool {
+    matches!(
+        node.kind(),
+        "closure_expression" | "function_item" | "lambda" | "lambda_expression"
+            | "arrow_function" | "function_expression" | "function_declaration" | "function_definition"
+            | "local_function_statement" | "anonymous_method_expression" | "method_declaration"
+    )
+}
+
+/// Receiver of a single accessor link (`x` in `x.y` or Java's `x.y()`)
+fn chain_receiver<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+    match node.kind() {
+        "method_invocation" => node.child_by_field_name("object"),
+        kind if is_member_access(kind) => member_parts(node).map(|(receiver, _)| receiver),
+        _ => None,
+    }
+}
+
+fn chain_length(node: &Node, source: &[u8]) -> usize {
+    let mut length = 0;
+    let mut current = *node;
+    while let Some(receiver) = chain_receiver(&current) {
+        if is_self_receiver(&receiver, source) {
+            break;
+        }
+        length += 1;
+        current = unwrap_chain_link(receiver);
+    }
+    length
+}
+
+/// Look through calls, `?`, `.await` and parentheses to the next link
+fn unwrap_chain_link(node: Node) -> Node {
+    let mut current = node;
+    loop {
+        let next = match current.kind() {
+            "call_expression" | "call" | "invocation_expression" => current.child_by_field_name("function"),
+            "try_expression" | "await_expression" | "parenthesized_expression" | "non_null_expression" => {
+                current.named_child(0)
+            }
+            _ => None,
+        };
+        match next {
+            Some(inner) => current = inner,
+            None => return current,
+        }
+    }
+}
+
+fn is_literal(node: &Node) -> bool {
+    is_string_literal(node)
+        || matches!(
+            node.kind(),
+            "integer_literal" | "float_literal" | "real_literal" | "integer" | "float" | "number"
+                | "decimal_integer_literal" | "hex_integer_literal" | "octal_integer_literal"
+                | "binary_integer_literal" | "decimal_floating_point_literal"
+        )
+}
+
+fn is_string_literal(node: &Node) -> bool {
+    matches!(
+        node.kind(),
+        "string_literal" | "raw_string_literal" | "verbatim_string_literal" | "string" | "template_string"
+    )
+}
+
+/// `0`, `1`, `1.0`, `0usize`, `""`, `r""`, `@""` and friends
+fn is_trivial_literal(text: &str) -> bool {
+    let first = text.chars().next().unwrap_or('"');
+    if !first.is_ascii_digit() && first != '.' {
+        return text
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic() || c == '@' || c == '$')
+            .chars()
+            .all(|c| matches!(c, '"' | '\'' | '`' | '#'));
+    }
+
+    let is_radix = matches!(text.get(..2), Some("0x" | "0X" | "0b" | "0B" | "0o" | "0O"));
+    if is_radix {
+        return false;
+    }
+    let digits: String = text.chars().filter(|&c| c != '_').collect();
+    NUMERIC_SUFFIX_RE
+        .replace(&digits, "")
+        .parse::<f64>()
+        .is_ok_and(|v| v == 0.0 || v == 1.0)
+}
+
+/// Split an identifier into lowercase words
+///
+/// Handles snake_case, SCREAMING_CASE, camelCase, acronym runs (`HTTPServer` →
+/// `http`, `server`), trailing digits (`utf8Decode` → `utf8`, `decode`) and any
+/// Unicode script; runs of a caseless script (CJK, Thai, ...) stay one word.
 fn split_identifier(s: &str) -> Vec<String> {
-    let re = regex::Regex::new(r"([a-z0-9])([A-Z])").unwrap();
-    let with_spaces = re.replace_all(s, "$1 $2");
-    
-    with_spaces
-        .split(|c: char| !c.is_alphanumeric())
-        .filter(|s| s.len() > 2)
-        .map(|s| s.to_lowercase())
+    let mut words = Vec::new();
+
+    for part in s.split(|c: char| !c.is_alphanumeric()).filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+
+        for i in 1..chars.len() {
+            let (prev, cur) = (chars[i - 1], chars[i]);
+            let next = chars.get(i + 1);
+
+            let boundary = (cur.is_uppercase() && (prev.is_lowercase() || prev.is_numeric()))
+                || (cur.is_uppercase() && prev.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+                || is_caseless(prev) != is_caseless(cur);
+
+            if boundary {
+                words.push(chars[start..i].iter().collect::<String>());
+                start = i;
+            }
+        }
+        words.push(chars[start..].iter().collect::<String>());
+    }
+
+    words
+        .into_iter()
+        .filter(|w| is_meaningful_word(w))
+        .map(|w| w.to_lowercase())
         .collect()
 }
 
+/// Letters from scripts without case, e.g. Han or Thai
+fn is_caseless(c: char) -> bool {
+    c.is_alphabetic() && !c.is_uppercase() && !c.is_lowercase()
+}
+
+/// Drops numbers and short cased fragments (`a`, `to`); caseless words are kept at any length
+fn is_meaningful_word(word: &str) -> bool {
+    if word.chars().all(|c| c.is_numeric()) {
+        return false;
+    }
+    word.chars().any(is_caseless) || word.chars().count() > 2
+}
+
 impl dei_core::traits::ComplexityCalculator for ComplexityCalculator {
     fn calculate_complexity(&self, source: &str) -> Complexity {
         // Fallback for when we don't have parsed tree - estimate from lines
@@ -6,34 +6,48 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::frameworks;
+use crate::options::ParseOptions;
+use crate::usage::{attach_imports, declared_fields, file_imports, imported_names, MemberUsage};
 
 static CSHARP_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_c_sharp::LANGUAGE.into());
 
 /// C#-specific parser
 pub struct CSharpParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl CSharpParser {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         parser
-            .set_language(&*CSHARP_LANGUAGE)
+            .set_language(&CSHARP_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set C# language: {}", e)))?;
         
-        Ok(Self { parser })
+        Ok(Self { parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
         let tree = self
             .parser
-            .parse(&source, None)
+            .parse(source, old_tree)
             .ok_or_else(|| Error::Parse {
                 path: path.to_path_buf(),
                 message: "Failed to parse C# file".into(),
@@ -45,13 +59,22 @@ impl CSharpParser {
         // Recursively find all class declarations
         self.find_classes(&root, source_bytes, path, &mut classes);
 
-        let lines = ComplexityCalculator::count_lines(&source);
+        let lines = ComplexityCalculator::count_lines(source);
 
-        Ok(FileMetrics {
+        let imports = file_imports(&root, source_bytes);
+        let names = imported_names(&root, source_bytes);
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
             lines,
-            classes: classes.into(),
-        })
+            classes: attach_imports(classes, &imports, source),
+            top_level_functions: 0,
+            imports: imports.len(),
+            imported_symbols: names.symbols,
+            wildcard_imports: names.wildcards.into(),
+            entry_point: None,
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn find_classes(
@@ -63,7 +86,7 @@ impl CSharpParser {
     ) {
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if child.kind() == "class_declaration" {
+            if matches!(child.kind(), "class_declaration" | "interface_declaration") {
                 if let Some(class_metrics) = self.parse_class(&child, source, path) {
                     classes.push(class_metrics);
                 }
@@ -94,12 +117,18 @@ impl CSharpParser {
         let mut methods = Vec::new();
         let mut property_count = 0;
         let mut field_count = 0;
-
-        let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
+        let fields = node
+            .child_by_field_name("body")
+            .map(|body| declared_fields(&body, source))
+            .unwrap_or_default();
+
+        // Members live in the class body, not directly under the declaration
+        let body = node.child_by_field_name("body").unwrap_or(*node);
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
             match child.kind() {
                 "method_declaration" => {
-                    if let Some(method) = self.parse_method(&child, source) {
+                    if let Some(method) = self.parse_method(&child, source, &fields) {
                         methods.push(method);
                     }
                 }
@@ -114,6 +143,11 @@ impl CSharpParser {
             .map(|m| m.complexity.0)
             .sum::<usize>();
 
+        let is_abstract = node.kind() == "interface_declaration"
+            || node
+                .children(&mut node.walk())
+                .any(|c| c.utf8_text(source) == Ok("abstract"));
+
         Some(ClassMetrics {
             name: name.into(),
             fully_qualified_name: name.into(),
@@ -125,6 +159,12 @@ impl CSharpParser {
             complexity: Complexity(total_complexity),
             methods: methods.into(),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: frameworks::csharp_class(node, source),
+            is_abstract,
+            is_public: node
+                .children(&mut node.walk())
+                .any(|c| c.utf8_text(source) == Ok("public")),
         })
     }
 
@@ -132,6 +172,7 @@ impl CSharpParser {
         &self,
         node: &tree_sitter::Node,
         source: &[u8],
+        fields: &[&str],
     ) -> Option<MethodMetrics> {
         let name = node
             .child_by_field_name("name")?
@@ -161,19 +202,28 @@ impl CSharpParser {
             .any(|c| c.kind() == "async");
 
         let tokens = ComplexityCalculator::extract_tokens(node, source);
+        let usage = MemberUsage::extract(node, source, fields);
 
         Some(MethodMetrics {
             name: name.into(),
             lines,
             complexity,
             parameters,
-            called_methods: Arc::new([]),
-            accessed_fields: Arc::new([]),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
+            called_methods: usage.calls.into(),
+            accessed_fields: usage.fields.into(),
             return_type: return_type.into(),
             is_public,
             is_static,
             is_async,
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 }
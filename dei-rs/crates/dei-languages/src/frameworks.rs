@@ -0,0 +1,112 @@
+//! Framework entry points recognized from attributes, decorators, annotations and base classes
+
+use dei_core::models::EntryPoint;
+
+/// Decorators that turn a Django function into a view
+const DJANGO_VIEW_DECORATORS: &[&str] = &[
+    "api_view",
+    "require_http_methods",
+    "require_GET",
+    "require_POST",
+    "require_safe",
+];
+
+/// Spring stereotypes other than controllers
+const SPRING_BEAN_ANNOTATIONS: &[&str] = &["Service", "Component", "Repository", "Configuration"];
+
+/// ASP.NET controllers carry `[ApiController]` or derive from `Controller`/`ControllerBase`
+pub(crate) fn csharp_class(node: &tree_sitter::Node, source: &[u8]) -> Option<EntryPoint> {
+    let mut cursor = node.walk();
+    let attributes: Vec<&str> = node
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "attribute_list")
+        .flat_map(|list| named_children(&list))
+        .filter_map(|attr| attr.child_by_field_name("name")?.utf8_text(source).ok())
+        .map(last_segment)
+        .collect();
+
+    let mut cursor = node.walk();
+    let bases: Vec<&str> = node
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "base_list")
+        .flat_map(|list| named_children(&list))
+        .filter_map(|base| base.utf8_text(source).ok())
+        .map(last_segment)
+        .collect();
+
+    let is_controller = attributes.iter().any(|a| matches!(*a, "ApiController" | "ApiControllerAttribute"))
+        || bases.iter().any(|b| b.ends_with("Controller") || b.ends_with("ControllerBase"));
+    is_controller.then_some(EntryPoint::AspNetController)
+}
+
+/// Spring stereotype annotations on the class
+pub(crate) fn java_class(node: &tree_sitter::Node, source: &[u8]) -> Option<EntryPoint> {
+    let mut cursor = node.walk();
+    let modifiers = node.children(&mut cursor).find(|c| c.kind() == "modifiers")?;
+    let annotations: Vec<&str> = named_children(&modifiers)
+        .into_iter()
+        .filter(|c| matches!(c.kind(), "annotation" | "marker_annotation"))
+        .filter_map(|a| a.child_by_field_name("name")?.utf8_text(source).ok())
+        .map(last_segment)
+        .collect();
+
+    if annotations.iter().any(|a| matches!(*a, "Controller" | "RestController")) {
+        Some(EntryPoint::SpringController)
+    } else if annotations.iter().any(|a| SPRING_BEAN_ANNOTATIONS.contains(a)) {
+        Some(EntryPoint::SpringBean)
+    } else {
+        None
+    }
+}
+
+/// Django class-based views derive from `View`, `APIView`, `TemplateView`, `ModelViewSet`, ...
+pub(crate) fn python_class(node: &tree_sitter::Node, source: &[u8]) -> Option<EntryPoint> {
+    let superclasses = node.child_by_field_name("superclasses")?;
+    named_children(&superclasses)
+        .into_iter()
+        .filter(|base| matches!(base.kind(), "identifier" | "attribute"))
+        .filter_map(|base| base.utf8_text(source).ok())
+        .map(last_segment)
+        .any(|base| base.ends_with("View") || base.ends_with("ViewSet"))
+        .then_some(EntryPoint::DjangoView)
+}
+
+/// A module with `@api_view` or `@require_*` functions is a Django view module
+pub(crate) fn python_module(root: &tree_sitter::Node, source: &[u8]) -> Option<EntryPoint> {
+    named_children(root)
+        .into_iter()
+        .filter(|c| c.kind() == "decorated_definition")
+        .filter(|c| c.child_by_field_name("definition").is_some_and(|d| d.kind() == "function_definition"))
+        .flat_map(|c| named_children(&c))
+        .filter(|d| d.kind() == "decorator")
+        .filter_map(|d| d.utf8_text(source).ok())
+        .any(|d| DJANGO_VIEW_DECORATORS.contains(&marker_name(d)))
+        .then_some(EntryPoint::DjangoView)
+}
+
+/// `#[tokio::main]` on a free function
+pub(crate) fn rust_module(root: &tree_sitter::Node, source: &[u8]) -> Option<EntryPoint> {
+    named_children(root)
+        .into_iter()
+        .filter(|c| c.kind() == "attribute_item")
+        .filter_map(|c| c.utf8_text(source).ok())
+        .any(|attr| marker_name(attr) == "tokio::main")
+        .then_some(EntryPoint::TokioMain)
+}
+
+fn named_children<'a>(node: &tree_sitter::Node<'a>) -> Vec<tree_sitter::Node<'a>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).collect()
+}
+
+/// Marker without sigils or arguments (`@api_view(["GET"])` → `api_view`, `#[tokio::main]` → `tokio::main`)
+fn marker_name(text: &str) -> &str {
+    let text = text.trim_start_matches(['@', '#', '[']).trim_end_matches(']');
+    text.split('(').next().unwrap_or(text).trim()
+}
+
+/// Last segment of a dotted or `::` path, without generic arguments
+fn last_segment(path: &str) -> &str {
+    let path = path.split('<').next().unwrap_or(path).trim();
+    path.rsplit(['.', ':']).next().unwrap_or(path)
+}
@@ -0,0 +1,149 @@
+//! Incremental re-parsing for long-running sessions
+//!
+//! [`IncrementalParser`] remembers the source and syntax tree of every file it parses. An
+//! unchanged file isn't parsed again; a changed one is diffed against its last version, the
+//! old tree is edited to match, and tree-sitter re-parses only what the edit touched.
+
+use dei_core::{
+    error::Result,
+    metrics::{FileMetrics, LineSpan},
+    models::Language,
+    traits::Parser,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tree_sitter::{InputEdit, Point, Tree};
+
+use crate::{multi_parser::MultiLanguageParser, options::ParseOptions, source::SourceProvider};
+
+/// Parser that keeps each file's last tree around for incremental re-parsing
+///
+/// Clones share one cache, so a clone handed to each run of a long-lived session still
+/// re-parses incrementally.
+#[derive(Clone, Default)]
+pub struct IncrementalParser {
+    parser: Arc<MultiLanguageParser>,
+    files: Arc<Mutex<HashMap<PathBuf, ParsedFile>>>,
+}
+
+/// A file as of its last parse
+struct ParsedFile {
+    source: Arc<str>,
+    tree: Tree,
+    metrics: FileMetrics,
+    /// Classes the last parse may have changed; `None` for a first parse
+    changed: Option<Vec<Arc<str>>>,
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            parser: Arc::new(MultiLanguageParser::default().with_options(options)),
+            ..Self::default()
+        }
+    }
+
+    /// Read files from `sources` instead of the filesystem
+    pub fn with_sources(options: ParseOptions, sources: impl SourceProvider + 'static) -> Self {
+        Self {
+            parser: Arc::new(MultiLanguageParser::default().with_options(options).with_sources(sources)),
+            ..Self::default()
+        }
+    }
+
+    /// Drop cached files that no longer exist
+    pub fn prune(&self) {
+        let sources = self.parser.sources();
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).retain(|path, _| sources.exists(path));
+    }
+
+    fn reparse(&self, path: &Path, source: Arc<str>, previous: Option<(Arc<str>, Tree, FileMetrics)>) -> Result<ParsedFile> {
+        let Some((old_source, mut tree, metrics)) = previous else {
+            let (metrics, tree) = self.parser.parse_source(path, &source, None)?;
+            return Ok(ParsedFile { source, tree, metrics, changed: None });
+        };
+        if old_source == source {
+            return Ok(ParsedFile { source, tree, metrics, changed: Some(Vec::new()) });
+        }
+
+        let edit = edit_between(&old_source, &source);
+        tree.edit(&edit);
+        let (metrics, new_tree) = self.parser.parse_source(path, &source, Some(&tree))?;
+
+        // Structural changes plus the edited text itself, which may not change the structure
+        let mut touched = vec![edit.start_position.row + 1..=edit.new_end_position.row + 1];
+        touched.extend(tree.changed_ranges(&new_tree).map(|r| r.start_point.row + 1..=r.end_point.row + 1));
+        let overlaps = |span: LineSpan| touched.iter().any(|t| span.start <= *t.end() && *t.start() <= span.end);
+        let changed = metrics
+            .classes
+            .iter()
+            .filter(|class| overlaps(class.span) || class.methods.iter().any(|m| overlaps(m.span)))
+            .map(|class| class.fully_qualified_name.clone())
+            .collect();
+
+        Ok(ParsedFile { source, tree: new_tree, metrics, changed: Some(changed) })
+    }
+}
+
+impl Parser for IncrementalParser {
+    fn parse_file(&self, path: &Path) -> Result<FileMetrics> {
+        let text = self.parser.sources().read(path)?;
+        let source: Arc<str> = Arc::from(&*text);
+        let previous = self
+            .files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(path)
+            .map(|file| (file.source.clone(), file.tree.clone(), file.metrics.clone()));
+
+        let parsed = self.reparse(path, source, previous)?;
+        let metrics = text.tag(parsed.metrics.clone());
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).insert(path.to_path_buf(), parsed);
+        Ok(metrics)
+    }
+
+    fn supported_languages(&self) -> &[Language] {
+        self.parser.supported_languages()
+    }
+
+    fn changed_classes(&self, path: &Path) -> Option<Vec<Arc<str>>> {
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).get(path)?.changed.clone()
+    }
+}
+
+/// The single edit turning `old` into `new`: everything between their common prefix and suffix
+fn edit_between(old: &str, new: &str) -> InputEdit {
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+    let prefix = old_bytes.iter().zip(new_bytes).take_while(|(a, b)| a == b).count();
+    let max_suffix = old_bytes.len().min(new_bytes.len()) - prefix;
+    let suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let (old_end, new_end) = (old_bytes.len() - suffix, new_bytes.len() - suffix);
+    InputEdit {
+        start_byte: prefix,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point_at(old_bytes, prefix),
+        old_end_position: point_at(old_bytes, old_end),
+        new_end_position: point_at(new_bytes, new_end),
+    }
+}
+
+/// Row and byte column of `offset`
+fn point_at(source: &[u8], offset: usize) -> Point {
+    let before = &source[..offset];
+    let row = before.iter().filter(|&&b| b == b'\n').count();
+    let column = before.iter().rev().take_while(|&&b| b != b'\n').count();
+    Point { row, column }
+}
@@ -4,31 +4,45 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::frameworks;
+use crate::options::ParseOptions;
+use crate::usage::{attach_imports, declared_fields, file_imports, imported_names, MemberUsage};
 
 static JAVA_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_java::LANGUAGE.into());
 
 /// Java parser
 pub struct JavaParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl JavaParser {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         parser
-            .set_language(&*JAVA_LANGUAGE)
+            .set_language(&JAVA_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set Java language: {e}")))?;
-        Ok(Self { parser })
+        Ok(Self { parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
-        let tree = self.parser.parse(&source, None).ok_or_else(|| Error::Parse {
+        let tree = self.parser.parse(source, old_tree).ok_or_else(|| Error::Parse {
             path: path.to_path_buf(),
             message: "Failed to parse Java file".into(),
         })?;
@@ -38,11 +52,20 @@ impl JavaParser {
 
         self.collect_classes(&root, source_bytes, path, &mut classes);
 
-        Ok(FileMetrics {
+        let imports = file_imports(&root, source_bytes);
+        let names = imported_names(&root, source_bytes);
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
-            lines: ComplexityCalculator::count_lines(&source),
-            classes: classes.into(),
-        })
+            lines: ComplexityCalculator::count_lines(source),
+            classes: attach_imports(classes, &imports, source),
+            top_level_functions: 0,
+            imports: imports.len(),
+            imported_symbols: names.symbols,
+            wildcard_imports: names.wildcards.into(),
+            entry_point: None,
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn collect_classes(
@@ -74,11 +97,12 @@ impl JavaParser {
         let mut field_count = 0;
 
         if let Some(body) = node.child_by_field_name("body") {
+            let fields = declared_fields(&body, source);
             let mut cursor = body.walk();
             for child in body.children(&mut cursor) {
                 match child.kind() {
                     "method_declaration" | "constructor_declaration" => {
-                        if let Some(m) = self.parse_method(&child, source) {
+                        if let Some(m) = self.parse_method(&child, source, &fields) {
                             methods.push(m);
                         }
                     }
@@ -108,16 +132,22 @@ impl JavaParser {
             complexity: Complexity(total_complexity.max(1)),
             methods: methods.into(),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: frameworks::java_class(node, source),
+            is_abstract: node.kind() == "interface_declaration"
+                || self.get_modifiers(node, source).contains(&"abstract"),
+            is_public: self.get_modifiers(node, source).contains(&"public"),
         })
     }
 
-    fn parse_method(&self, node: &tree_sitter::Node, source: &[u8]) -> Option<MethodMetrics> {
+    fn parse_method(&self, node: &tree_sitter::Node, source: &[u8], fields: &[&str]) -> Option<MethodMetrics> {
         let name = node.child_by_field_name("name")?.utf8_text(source).ok()?;
         let text = node.utf8_text(source).ok()?;
         let lines = ComplexityCalculator::count_lines(text);
         let complexity = ComplexityCalculator::calculate_from_tree(node, source);
         let parameters = ComplexityCalculator::count_parameters(node, source);
         let tokens = ComplexityCalculator::extract_tokens(node, source);
+        let usage = MemberUsage::extract(node, source, fields);
 
         let return_type = node
             .child_by_field_name("type")
@@ -133,13 +163,21 @@ impl JavaParser {
             lines,
             complexity,
             parameters,
-            called_methods: Arc::new([]),
-            accessed_fields: Arc::new([]),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
+            called_methods: usage.calls.into(),
+            accessed_fields: usage.fields.into(),
             return_type: return_type.into(),
             is_public,
             is_static,
             is_async: false, // Java doesn't have async keyword
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
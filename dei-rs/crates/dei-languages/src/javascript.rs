@@ -4,9 +4,11 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::options::ParseOptions;
+use crate::usage::{attach_imports, file_imports, imported_names, MemberUsage};
 
 static JS_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_javascript::LANGUAGE.into());
 static TS_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into());
@@ -17,30 +19,41 @@ pub struct JsParser {
     js_parser: Parser,
     ts_parser: Parser,
     tsx_parser: Parser,
+    options: ParseOptions,
 }
 
 impl JsParser {
     pub fn new() -> Result<Self> {
         let mut js_parser = Parser::new();
         js_parser
-            .set_language(&*JS_LANGUAGE)
+            .set_language(&JS_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set JS language: {e}")))?;
 
         let mut ts_parser = Parser::new();
         ts_parser
-            .set_language(&*TS_LANGUAGE)
+            .set_language(&TS_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set TS language: {e}")))?;
 
         let mut tsx_parser = Parser::new();
         tsx_parser
-            .set_language(&*TSX_LANGUAGE)
+            .set_language(&TSX_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set TSX language: {e}")))?;
 
-        Ok(Self { js_parser, ts_parser, tsx_parser })
+        Ok(Self { js_parser, ts_parser, tsx_parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
         let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -51,7 +64,7 @@ impl JsParser {
             _ => &mut self.js_parser,
         };
 
-        let tree = parser.parse(&source, None).ok_or_else(|| Error::Parse {
+        let tree = parser.parse(source, old_tree).ok_or_else(|| Error::Parse {
             path: path.to_path_buf(),
             message: "Failed to parse JS/TS file".into(),
         })?;
@@ -61,6 +74,7 @@ impl JsParser {
         let mut loose_functions: Vec<MethodMetrics> = Vec::new();
 
         self.collect_definitions(&root, source_bytes, path, &mut classes, &mut loose_functions);
+        let top_level_functions = loose_functions.len();
 
         // Group loose functions into a synthetic "module" class if present
         if !loose_functions.is_empty() {
@@ -83,14 +97,27 @@ impl JsParser {
                 complexity: Complexity(total_complexity),
                 methods: loose_functions.into(),
                 dependencies: Arc::new([]),
+                span: ComplexityCalculator::span(&root),
+                entry_point: None,
+                is_abstract: false,
+                is_public: true,
             });
         }
 
-        Ok(FileMetrics {
+        let imports = file_imports(&root, source_bytes);
+        let names = imported_names(&root, source_bytes);
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
-            lines: ComplexityCalculator::count_lines(&source),
-            classes: classes.into(),
-        })
+            lines: ComplexityCalculator::count_lines(source),
+            classes: attach_imports(classes, &imports, source),
+            top_level_functions,
+            imports: imports.len(),
+            imported_symbols: names.symbols,
+            wildcard_imports: names.wildcards.into(),
+            entry_point: None,
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn collect_definitions(
@@ -166,6 +193,10 @@ impl JsParser {
             complexity: Complexity(total_complexity.max(1)),
             methods: methods.into(),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: None,
+            is_abstract: false,
+            is_public: true,
         })
     }
 
@@ -176,6 +207,7 @@ impl JsParser {
         let complexity = ComplexityCalculator::calculate_from_tree(node, source);
         let parameters = ComplexityCalculator::count_parameters(node, source);
         let tokens = ComplexityCalculator::extract_tokens(node, source);
+        let usage = MemberUsage::extract(node, source, &[]);
 
         let is_async = node.children(&mut node.walk()).any(|c| c.kind() == "async");
         let is_static = node.children(&mut node.walk()).any(|c| c.kind() == "static");
@@ -185,13 +217,21 @@ impl JsParser {
             lines,
             complexity,
             parameters,
-            called_methods: Arc::new([]),
-            accessed_fields: Arc::new([]),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
+            called_methods: usage.calls.into(),
+            accessed_fields: usage.fields.into(),
             return_type: "unknown".into(),
             is_public: true,
             is_static,
             is_async,
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
@@ -202,6 +242,7 @@ impl JsParser {
         let complexity = ComplexityCalculator::calculate_from_tree(node, source);
         let parameters = ComplexityCalculator::count_parameters(node, source);
         let tokens = ComplexityCalculator::extract_tokens(node, source);
+        let usage = MemberUsage::extract(node, source, &[]);
 
         let is_async = node.children(&mut node.walk()).any(|c| c.kind() == "async");
 
@@ -210,13 +251,21 @@ impl JsParser {
             lines,
             complexity,
             parameters,
-            called_methods: Arc::new([]),
-            accessed_fields: Arc::new([]),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
+            called_methods: usage.calls.into(),
+            accessed_fields: usage.fields.into(),
             return_type: "unknown".into(),
             is_public: true,
             is_static: false,
             is_async,
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
@@ -234,6 +283,7 @@ impl JsParser {
                         let complexity = ComplexityCalculator::calculate_from_tree(&value, source);
                         let parameters = ComplexityCalculator::count_parameters(&value, source);
                         let tokens = ComplexityCalculator::extract_tokens(&value, source);
+                        let usage = MemberUsage::extract(&value, source, &[]);
                         let is_async = value.children(&mut value.walk()).any(|c| c.kind() == "async");
 
                         functions.push(MethodMetrics {
@@ -241,13 +291,21 @@ impl JsParser {
                             lines,
                             complexity,
                             parameters,
-                            called_methods: Arc::new([]),
-                            accessed_fields: Arc::new([]),
+                            params: ComplexityCalculator::extract_parameters(&value, source).into(),
+                            generics: ComplexityCalculator::extract_generics(&value, source).into(),
+                            nesting_depth: ComplexityCalculator::max_nesting_depth(&value),
+                            chain_length: ComplexityCalculator::max_chain_length(&value, source),
+                            exit_points: ComplexityCalculator::count_exit_points(&value),
+                            magic_literals: ComplexityCalculator::count_magic_literals(&value, source),
+                            span: ComplexityCalculator::span(&value),
+                            called_methods: usage.calls.into(),
+                            accessed_fields: usage.fields.into(),
                             return_type: "unknown".into(),
                             is_public: true,
                             is_static: false,
                             is_async,
                             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+                            ast_snippet: self.options.snippet(&value),
                         });
                     }
                 }
@@ -10,9 +10,19 @@ pub mod java;
 pub mod perl;
 pub mod r;
 pub mod complexity;
+mod frameworks;
+pub mod incremental;
 pub mod multi_parser;
+pub mod options;
+pub mod scaffold;
+pub mod source;
+pub mod usage;
 
 pub use complexity::ComplexityCalculator;
+pub use incremental::IncrementalParser;
 pub use multi_parser::MultiLanguageParser;
+pub use options::ParseOptions;
+pub use source::{read_source, FileSystem, MemorySources, SourceProvider};
+pub use usage::MemberUsage;
 
 
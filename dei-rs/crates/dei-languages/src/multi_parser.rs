@@ -3,17 +3,66 @@
 //! Routes to appropriate language-specific parser
 
 use dei_core::{error::Result, metrics::FileMetrics, models::Language, traits::Parser, Error};
+use std::cell::RefCell;
 use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::Tree;
 
-use crate::{csharp::CSharpParser, java::JavaParser, javascript::JsParser, perl::PerlParser, python::PythonParser, r::RParser, rust::RustParser};
+use crate::{csharp::CSharpParser, java::JavaParser, javascript::JsParser, options::ParseOptions, perl::PerlParser, python::PythonParser, r::RParser, rust::RustParser};
+use crate::source::{FileSystem, SourceProvider};
 
-/// Parser that supports multiple languages (creates parsers on-demand for thread safety)
-#[derive(Default)]
-pub struct MultiLanguageParser;
+/// Parser that supports multiple languages
+///
+/// Shared freely across threads: each thread parses with its own tree-sitter parsers, created
+/// on first use of a language and reused for every later file on that thread.
+pub struct MultiLanguageParser {
+    options: ParseOptions,
+    sources: Arc<dyn SourceProvider>,
+}
+
+impl Default for MultiLanguageParser {
+    fn default() -> Self {
+        Self { options: ParseOptions::default(), sources: Arc::new(FileSystem) }
+    }
+}
 
 impl MultiLanguageParser {
     pub fn new() -> Result<Self> {
-        Ok(Self)
+        Ok(Self::default())
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Read files from `sources` instead of the filesystem
+    pub fn with_sources(mut self, sources: impl SourceProvider + 'static) -> Self {
+        self.sources = Arc::new(sources);
+        self
+    }
+
+    pub fn sources(&self) -> &dyn SourceProvider {
+        &*self.sources
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("unknown");
+        let language = Self::detect_language(path)
+            .ok_or_else(|| Error::UnsupportedLanguage(ext.to_string()))?;
+
+        let parse = Parse { options: self.options, path, source, old_tree };
+        match language {
+            Language::Rust => parse.pooled(|pool| &mut pool.rust),
+            Language::CSharp => parse.pooled(|pool| &mut pool.csharp),
+            Language::Python => parse.pooled(|pool| &mut pool.python),
+            Language::JavaScript | Language::TypeScript => parse.pooled(|pool| &mut pool.js),
+            Language::Java => parse.pooled(|pool| &mut pool.java),
+            Language::Perl => parse.pooled(|pool| &mut pool.perl),
+            Language::R => parse.pooled(|pool| &mut pool.r),
+            _ => Err(Error::UnsupportedLanguage(format!("{language:?}"))),
+        }
     }
 
     fn detect_language(path: &Path) -> Option<Language> {
@@ -28,20 +77,13 @@ impl MultiLanguageParser {
 
 impl Parser for MultiLanguageParser {
     fn parse_file(&self, path: &Path) -> Result<FileMetrics> {
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("unknown");
-        let language = Self::detect_language(path)
-            .ok_or_else(|| Error::UnsupportedLanguage(ext.to_string()))?;
-
-        match language {
-            Language::Rust => RustParser::new()?.parse_file(path),
-            Language::CSharp => CSharpParser::new()?.parse_file(path),
-            Language::Python => PythonParser::new()?.parse_file(path),
-            Language::JavaScript | Language::TypeScript => JsParser::new()?.parse_file(path),
-            Language::Java => JavaParser::new()?.parse_file(path),
-            Language::Perl => PerlParser::new()?.parse_file(path),
-            Language::R => RParser::new()?.parse_file(path),
-            _ => Err(Error::UnsupportedLanguage(format!("{language:?}"))),
-        }
+        // Unsupported files fail before being read
+        Self::detect_language(path).ok_or_else(|| {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("unknown");
+            Error::UnsupportedLanguage(ext.to_string())
+        })?;
+        let source = self.sources.read(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
     }
 
     fn supported_languages(&self) -> &[Language] {
@@ -49,3 +91,72 @@ impl Parser for MultiLanguageParser {
     }
 }
 
+
+/// This thread's language parsers, each created the first time its language comes up
+#[derive(Default)]
+struct ParserPool {
+    rust: Option<RustParser>,
+    csharp: Option<CSharpParser>,
+    python: Option<PythonParser>,
+    js: Option<JsParser>,
+    java: Option<JavaParser>,
+    perl: Option<PerlParser>,
+    r: Option<RParser>,
+}
+
+thread_local! {
+    static PARSERS: RefCell<ParserPool> = RefCell::default();
+}
+
+/// A language parser that can be kept in a [`ParserPool`]
+trait PooledParser: Sized {
+    fn create() -> Result<Self>;
+    fn configure(self, options: ParseOptions) -> Self;
+    fn parse(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)>;
+}
+
+macro_rules! pooled_parsers {
+    ($($parser:ty),* $(,)?) => {$(
+        impl PooledParser for $parser {
+            fn create() -> Result<Self> {
+                <$parser>::new()
+            }
+
+            fn configure(self, options: ParseOptions) -> Self {
+                self.with_options(options)
+            }
+
+            fn parse(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
+                self.parse_source(path, source, old_tree)
+            }
+        }
+    )*};
+}
+
+pooled_parsers!(RustParser, CSharpParser, PythonParser, JsParser, JavaParser, PerlParser, RParser);
+
+/// One parse request, run with whichever pooled parser fits the language
+struct Parse<'a> {
+    options: ParseOptions,
+    path: &'a Path,
+    source: &'a str,
+    old_tree: Option<&'a Tree>,
+}
+
+impl Parse<'_> {
+    /// Parse with this thread's parser for the language, creating it if needed
+    ///
+    /// The parser is taken out of the pool while in use, so the pool is never borrowed across a
+    /// parse; options are applied per call since parsers outlive any one `MultiLanguageParser`.
+    fn pooled<T: PooledParser>(self, slot: fn(&mut ParserPool) -> &mut Option<T>) -> Result<(FileMetrics, Tree)> {
+        let pooled = PARSERS.with_borrow_mut(|pool| slot(pool).take());
+        let mut parser = match pooled {
+            Some(parser) => parser,
+            None => T::create()?,
+        }
+        .configure(self.options);
+        let parsed = parser.parse(self.path, self.source, self.old_tree);
+        PARSERS.with_borrow_mut(|pool| *slot(pool) = Some(parser));
+        parsed
+    }
+}
@@ -0,0 +1,64 @@
+//! Parser options shared by all language parsers
+
+use std::sync::Arc;
+use tree_sitter::Node;
+
+/// Opt-in extras computed while parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Attach a structural s-expression of each method to its metrics
+    pub ast_snippets: bool,
+    /// Depth below which snippet subtrees are elided as `…`
+    pub snippet_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            ast_snippets: false,
+            snippet_depth: 6,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn with_ast_snippets(mut self, enabled: bool) -> Self {
+        self.ast_snippets = enabled;
+        self
+    }
+
+    /// Snippet for a method node, if snippets are enabled
+    pub fn snippet(&self, node: &Node) -> Option<Arc<str>> {
+        self.ast_snippets
+            .then(|| structural_sexp(node, self.snippet_depth).into())
+    }
+}
+
+/// Named-node s-expression trimmed to `max_depth` levels
+///
+/// Unlike `Node::to_sexp`, deep subtrees collapse to `…` so generated
+/// snippets stay small enough to embed in JSON output
+pub fn structural_sexp(node: &Node, max_depth: usize) -> String {
+    let mut out = String::new();
+    write_sexp(node, max_depth, &mut out);
+    out
+}
+
+fn write_sexp(node: &Node, remaining: usize, out: &mut String) {
+    out.push('(');
+    out.push_str(node.kind());
+
+    if node.named_child_count() > 0 {
+        if remaining == 0 {
+            out.push_str(" …");
+        } else {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                out.push(' ');
+                write_sexp(&child, remaining - 1, out);
+            }
+        }
+    }
+
+    out.push(')');
+}
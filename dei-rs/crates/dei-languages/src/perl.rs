@@ -4,33 +4,45 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::options::ParseOptions;
 
 static PERL_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_perl::LANGUAGE.into());
 
 /// Perl-specific parser
 pub struct PerlParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl PerlParser {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         parser
-            .set_language(&*PERL_LANGUAGE)
+            .set_language(&PERL_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set Perl language: {}", e)))?;
-        Ok(Self { parser })
+        Ok(Self { parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
         let tree = self
             .parser
-            .parse(&source, None)
+            .parse(source, old_tree)
             .ok_or_else(|| Error::Parse {
                 path: path.to_path_buf(),
                 message: "Failed to parse Perl file".into(),
@@ -42,6 +54,8 @@ impl PerlParser {
 
         self.traverse_nodes(&root, source_bytes, path, &mut packages, &mut standalone_subs);
 
+        let top_level_functions = standalone_subs.len();
+
         // If no packages found but subs exist, create synthetic class from file
         let classes: Vec<ClassMetrics> = if packages.is_empty() && !standalone_subs.is_empty() {
             let total_complexity = standalone_subs.iter().map(|m| m.complexity.0).sum::<usize>();
@@ -49,23 +63,34 @@ impl PerlParser {
                 name: path.file_stem().map(|s| s.to_string_lossy().into()).unwrap_or("main".into()),
                 fully_qualified_name: "main".into(),
                 file_path: path.to_string_lossy().to_string().into(),
-                lines: ComplexityCalculator::count_lines(&source),
+                lines: ComplexityCalculator::count_lines(source),
                 method_count: MethodCount(standalone_subs.len()),
                 property_count: 0,
                 field_count: 0,
                 complexity: Complexity(total_complexity.max(1)),
                 methods: standalone_subs.into(),
                 dependencies: Arc::new([]),
+                span: ComplexityCalculator::span(&root),
+                entry_point: None,
+                is_abstract: false,
+                is_public: true,
             }]
         } else {
             packages.into_values().collect()
         };
 
-        Ok(FileMetrics {
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
-            lines: ComplexityCalculator::count_lines(&source),
+            lines: ComplexityCalculator::count_lines(source),
             classes: classes.into(),
-        })
+            top_level_functions,
+            imports: 0,
+            imported_symbols: 0,
+            wildcard_imports: Arc::new([]),
+            entry_point: None,
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn traverse_nodes(
@@ -76,17 +101,25 @@ impl PerlParser {
         packages: &mut std::collections::HashMap<String, ClassMetrics>,
         standalone_subs: &mut Vec<MethodMetrics>,
     ) {
+        // `package Name;` owns every sub up to the next package statement in the same scope
+        let mut current_package: Option<String> = None;
+
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             match child.kind() {
                 "package_statement" => {
                     if let Some(pkg) = self.parse_package(&child, source, path) {
+                        let has_block = child.children(&mut child.walk()).any(|c| c.kind() == "block");
+                        current_package = (!has_block).then(|| pkg.name.to_string());
                         packages.insert(pkg.name.to_string(), pkg);
                     }
                 }
-                "subroutine_declaration_statement" | "anonymous_subroutine_expression" => {
+                "subroutine_declaration_statement" | "function_definition" | "anonymous_subroutine_expression" => {
                     if let Some(method) = self.parse_subroutine(&child, source) {
-                        standalone_subs.push(method);
+                        match current_package.as_ref().and_then(|name| packages.get_mut(name)) {
+                            Some(pkg) => Self::add_method(pkg, method),
+                            None => standalone_subs.push(method),
+                        }
                     }
                 }
                 _ => self.traverse_nodes(&child, source, path, packages, standalone_subs),
@@ -94,6 +127,15 @@ impl PerlParser {
         }
     }
 
+    /// Grow a statement-form package by a sub declared after it
+    fn add_method(pkg: &mut ClassMetrics, method: MethodMetrics) {
+        pkg.lines = Lines(pkg.lines.0 + method.lines.0);
+        pkg.span = LineSpan::new(pkg.span.start, pkg.span.end.max(method.span.end));
+        pkg.complexity = Complexity(pkg.methods.iter().map(|m| m.complexity.0).sum::<usize>() + method.complexity.0);
+        pkg.methods = pkg.methods.iter().cloned().chain(std::iter::once(method)).collect();
+        pkg.method_count = MethodCount(pkg.methods.len());
+    }
+
     fn parse_package(
         &self,
         node: &tree_sitter::Node,
@@ -107,7 +149,7 @@ impl PerlParser {
         let mut methods = Vec::new();
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            if child.kind() == "subroutine_declaration_statement" {
+            if matches!(child.kind(), "subroutine_declaration_statement" | "function_definition") {
                 if let Some(m) = self.parse_subroutine(&child, source) {
                     methods.push(m);
                 }
@@ -127,6 +169,10 @@ impl PerlParser {
             complexity: Complexity(total_complexity.max(1)),
             methods: methods.into(),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: None,
+            is_abstract: false,
+            is_public: true,
         })
     }
 
@@ -143,6 +189,13 @@ impl PerlParser {
             lines,
             complexity,
             parameters: ParamCount(parameters),
+            params: Arc::new([]),
+            generics: Arc::new([]),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
             called_methods: Arc::new([]),
             accessed_fields: Arc::new([]),
             return_type: "scalar".into(),
@@ -150,6 +203,7 @@ impl PerlParser {
             is_static: false,
             is_async: false,
             tokens: ComplexityCalculator::extract_tokens(node, source).into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
@@ -159,11 +213,12 @@ impl PerlParser {
 
     fn count_complexity_nodes(&self, node: &tree_sitter::Node) -> usize {
         let complexity = match node.kind() {
-            "subroutine_declaration_statement" | "anonymous_subroutine_expression" => 1,
+            "subroutine_declaration_statement" | "function_definition" | "anonymous_subroutine_expression" => 1,
             "if_statement" | "elsif_clause" | "unless_statement" => 1,
-            "for_statement" | "foreach_statement" | "while_statement" | "until_statement" => 1,
+            "for_statement" | "for_statement_1" | "for_statement_2" | "foreach_statement" => 1,
+            "while_statement" | "until_statement" => 1,
             "conditional_expression" => 1,
-            "and" | "or" => 1,
+            "and" | "or" | "&&" | "||" => 1,
             "given_statement" | "when_clause" => 1,
             _ => 0,
         };
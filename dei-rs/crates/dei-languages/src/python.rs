@@ -4,34 +4,48 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::frameworks;
+use crate::options::ParseOptions;
+use crate::usage::{attach_imports, file_imports, imported_names, MemberUsage};
 
 static PYTHON_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_python::LANGUAGE.into());
 
 /// Python-specific parser
 pub struct PythonParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl PythonParser {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         parser
-            .set_language(&*PYTHON_LANGUAGE)
+            .set_language(&PYTHON_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set Python language: {}", e)))?;
         
-        Ok(Self { parser })
+        Ok(Self { parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
         let tree = self
             .parser
-            .parse(&source, None)
+            .parse(source, old_tree)
             .ok_or_else(|| Error::Parse {
                 path: path.to_path_buf(),
                 message: "Failed to parse Python file".into(),
@@ -43,13 +57,22 @@ impl PythonParser {
         // Find all class definitions
         self.find_classes(&root, source_bytes, path, &mut classes);
 
-        let lines = ComplexityCalculator::count_lines(&source);
+        let lines = ComplexityCalculator::count_lines(source);
 
-        Ok(FileMetrics {
+        let imports = file_imports(&root, source_bytes);
+        let names = imported_names(&root, source_bytes);
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
             lines,
-            classes: classes.into(),
-        })
+            classes: attach_imports(classes, &imports, source),
+            top_level_functions: ComplexityCalculator::count_top_level_functions(&root, &["function_definition"]),
+            imports: imports.len(),
+            imported_symbols: names.symbols,
+            wildcard_imports: names.wildcards.into(),
+            entry_point: frameworks::python_module(&root, source_bytes),
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn find_classes(
@@ -102,11 +125,9 @@ impl PythonParser {
                             methods.push(method);
                         }
                     }
-                    "expression_statement" => {
-                        // Class-level attribute assignments
-                        if self.is_class_attribute(&child) {
-                            field_count += 1;
-                        }
+                    // Class-level attribute assignments
+                    "expression_statement" if self.is_class_attribute(&child) => {
+                        field_count += 1;
                     }
                     _ => {}
                 }
@@ -126,6 +147,10 @@ impl PythonParser {
             complexity: Complexity(total_complexity.max(1)),
             methods: methods.into(),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: frameworks::python_class(node, source),
+            is_abstract: self.is_abstract_base(node, source),
+            is_public: !name.starts_with('_'),
         })
     }
 
@@ -150,18 +175,28 @@ impl PythonParser {
 
         let tokens = ComplexityCalculator::extract_tokens(node, source);
 
+        let usage = MemberUsage::extract(node, source, &[]);
+
         Some(MethodMetrics {
             name: name.into(),
             lines,
             complexity,
             parameters: ParamCount(parameters),
-            called_methods: Arc::new([]),
-            accessed_fields: Arc::new([]),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
+            called_methods: usage.calls.into(),
+            accessed_fields: usage.fields.into(),
             return_type: return_type.into(),
             is_public,
             is_static,
             is_async,
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
@@ -250,6 +285,24 @@ impl PythonParser {
         }
     }
 
+    /// Derives from `ABC` or `Protocol`, or declares `metaclass=ABCMeta`
+    fn is_abstract_base(&self, node: &tree_sitter::Node, source: &[u8]) -> bool {
+        let Some(superclasses) = node.child_by_field_name("superclasses") else {
+            return false;
+        };
+        let mut cursor = superclasses.walk();
+        let is_abstract = superclasses.named_children(&mut cursor).any(|base| {
+            let base = match base.kind() {
+                "keyword_argument" => base.child_by_field_name("value"),
+                _ => Some(base),
+            };
+            base.and_then(|b| b.utf8_text(source).ok())
+                .and_then(|text| text.rsplit('.').next())
+                .is_some_and(|name| matches!(name, "ABC" | "ABCMeta" | "Protocol"))
+        });
+        is_abstract
+    }
+
     fn has_decorator(&self, node: &tree_sitter::Node, source: &[u8], decorator_name: &str) -> bool {
         let parent = node.parent();
         if let Some(p) = parent {
@@ -4,31 +4,43 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::options::ParseOptions;
 
 static R_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_r::LANGUAGE.into());
 
 /// R language parser
 pub struct RParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl RParser {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         parser
-            .set_language(&*R_LANGUAGE)
+            .set_language(&R_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set R language: {e}")))?;
-        Ok(Self { parser })
+        Ok(Self { parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
-        let tree = self.parser.parse(&source, None).ok_or_else(|| Error::Parse {
+        let tree = self.parser.parse(source, old_tree).ok_or_else(|| Error::Parse {
             path: path.to_path_buf(),
             message: "Failed to parse R file".into(),
         })?;
@@ -39,32 +51,43 @@ impl RParser {
         // R uses R6, S4, or Reference Classes for OOP - collect functions and class definitions
         self.collect_classes(&root, source_bytes, path, &mut classes);
 
+        let methods = self.collect_top_level_functions(&root, source_bytes);
+        let top_level_functions = methods.len();
+
         // If no classes found, treat top-level functions as a "module"
-        if classes.is_empty() {
-            let methods = self.collect_top_level_functions(&root, source_bytes);
-            if !methods.is_empty() {
-                let total_complexity: usize = methods.iter().map(|m| m.complexity.0).sum();
-                let file_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("module");
-                classes.push(ClassMetrics {
-                    name: file_name.into(),
-                    fully_qualified_name: file_name.into(),
-                    file_path: path.to_string_lossy().to_string().into(),
-                    lines: ComplexityCalculator::count_lines(&source),
-                    method_count: MethodCount(methods.len()),
-                    property_count: 0,
-                    field_count: 0,
-                    complexity: Complexity(total_complexity.max(1)),
-                    methods: methods.into(),
-                    dependencies: Arc::new([]),
-                });
-            }
+        if classes.is_empty() && !methods.is_empty() {
+            let total_complexity: usize = methods.iter().map(|m| m.complexity.0).sum();
+            let file_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("module");
+            classes.push(ClassMetrics {
+                name: file_name.into(),
+                fully_qualified_name: file_name.into(),
+                file_path: path.to_string_lossy().to_string().into(),
+                lines: ComplexityCalculator::count_lines(source),
+                method_count: MethodCount(methods.len()),
+                property_count: 0,
+                field_count: 0,
+                complexity: Complexity(total_complexity.max(1)),
+                methods: methods.into(),
+                dependencies: Arc::new([]),
+                span: ComplexityCalculator::span(&root),
+                entry_point: None,
+                is_abstract: false,
+                is_public: true,
+            });
         }
 
-        Ok(FileMetrics {
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
-            lines: ComplexityCalculator::count_lines(&source),
+            lines: ComplexityCalculator::count_lines(source),
             classes: classes.into(),
-        })
+            top_level_functions,
+            imports: 0,
+            imported_symbols: 0,
+            wildcard_imports: Arc::new([]),
+            entry_point: None,
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn collect_classes(
@@ -120,6 +143,10 @@ impl RParser {
                     complexity: Complexity(total_complexity.max(1)),
                     methods: methods.into(),
                     dependencies: Arc::new([]),
+                    span: ComplexityCalculator::span(node),
+                    entry_point: None,
+                    is_abstract: false,
+                    is_public: !name.starts_with('.'),
                 });
             }
         }
@@ -253,7 +280,7 @@ impl RParser {
 
         let text = node.utf8_text(source).ok()?;
         let lines = ComplexityCalculator::count_lines(text);
-        let complexity = self.calculate_r_complexity(node);
+        let complexity = self.calculate_r_complexity(node, source);
         let parameters = self.count_r_parameters(node, source);
         let tokens = ComplexityCalculator::extract_tokens(node, source);
 
@@ -262,6 +289,13 @@ impl RParser {
             lines,
             complexity: Complexity(complexity),
             parameters: ParamCount(parameters),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
             called_methods: Arc::new([]),
             accessed_fields: Arc::new([]),
             return_type: "unknown".into(),
@@ -269,14 +303,15 @@ impl RParser {
             is_static: false,
             is_async: false,
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
-    fn calculate_r_complexity(&self, node: &tree_sitter::Node) -> usize {
-        self.count_complexity_nodes(node)
+    fn calculate_r_complexity(&self, node: &tree_sitter::Node, source: &[u8]) -> usize {
+        self.count_complexity_nodes(node, source)
     }
 
-    fn count_complexity_nodes(&self, node: &tree_sitter::Node) -> usize {
+    fn count_complexity_nodes(&self, node: &tree_sitter::Node, source: &[u8]) -> usize {
         let mut complexity = match node.kind() {
             "function_definition" => 1,
             "if_statement" | "else_clause" => 1,
@@ -289,7 +324,7 @@ impl RParser {
             }
             "call" => {
                 node.child(0)
-                    .and_then(|f| f.utf8_text(&[]).ok())
+                    .and_then(|f| f.utf8_text(source).ok())
                     .filter(|&n| n == "tryCatch")
                     .map_or(0, |_| 1)
             }
@@ -298,7 +333,7 @@ impl RParser {
 
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            complexity += self.count_complexity_nodes(&child);
+            complexity += self.count_complexity_nodes(&child, source);
         }
         complexity
     }
@@ -6,34 +6,48 @@ use dei_core::{error::Result, metrics::*, thresholds::*, Error};
 use once_cell::sync::Lazy;
 use std::path::Path;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 use crate::complexity::ComplexityCalculator;
+use crate::frameworks;
+use crate::options::ParseOptions;
+use crate::usage::{attach_imports, file_imports, imported_names, MemberUsage};
 
 static RUST_LANGUAGE: Lazy<tree_sitter::Language> = Lazy::new(|| tree_sitter_rust::LANGUAGE.into());
 
 /// Rust-specific parser
 pub struct RustParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl RustParser {
     pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         parser
-            .set_language(&*RUST_LANGUAGE)
+            .set_language(&RUST_LANGUAGE)
             .map_err(|e| Error::Analysis(format!("Failed to set Rust language: {}", e)))?;
         
-        Ok(Self { parser })
+        Ok(Self { parser, options: ParseOptions::default() })
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub fn parse_file(&mut self, path: &Path) -> Result<FileMetrics> {
-        let source = std::fs::read_to_string(path)?;
+        let source = crate::source::read_source(path)?;
+        self.parse_source(path, &source, None).map(|(metrics, _)| source.tag(metrics))
+    }
+
+    /// Parse source already in memory; an `old_tree` edited to match makes the parse incremental
+    pub fn parse_source(&mut self, path: &Path, source: &str, old_tree: Option<&Tree>) -> Result<(FileMetrics, Tree)> {
         let source_bytes = source.as_bytes();
 
         let tree = self
             .parser
-            .parse(&source, None)
+            .parse(source, old_tree)
             .ok_or_else(|| Error::Parse {
                 path: path.to_path_buf(),
                 message: "Failed to parse Rust file".into(),
@@ -44,10 +58,9 @@ impl RustParser {
         let mut impls = Vec::new();
 
         // First pass: collect type definitions and impl blocks
-        let mut cursor = root.walk();
-        for node in root.children(&mut cursor) {
+        for node in root.children(&mut root.walk()) {
             match node.kind() {
-                "struct_item" | "enum_item" => {
+                "struct_item" | "enum_item" | "trait_item" => {
                     if let Some(class_metrics) = self.parse_type(&node, source_bytes, path) {
                         let name = class_metrics.name.to_string();
                         type_defs.insert(name, class_metrics);
@@ -81,13 +94,22 @@ impl RustParser {
             }
         }
 
-        let lines = ComplexityCalculator::count_lines(&source);
+        let lines = ComplexityCalculator::count_lines(source);
 
-        Ok(FileMetrics {
+        let imports = file_imports(&root, source_bytes);
+        let names = imported_names(&root, source_bytes);
+        let metrics = FileMetrics {
             path: path.to_string_lossy().to_string().into(),
             lines,
-            classes: type_defs.into_values().collect::<Vec<_>>().into(),
-        })
+            classes: attach_imports(type_defs.into_values().collect(), &imports, source),
+            top_level_functions: ComplexityCalculator::count_top_level_functions(&root, &["function_item"]),
+            imports: imports.len(),
+            imported_symbols: names.symbols,
+            wildcard_imports: names.wildcards.into(),
+            entry_point: frameworks::rust_module(&root, source_bytes),
+            encoding: None,
+        };
+        Ok((metrics, tree))
     }
 
     fn parse_type(
@@ -104,7 +126,7 @@ impl RustParser {
         let text = node.utf8_text(source).ok()?;
         let lines = ComplexityCalculator::count_lines(text);
         
-        // For structs/enums, methods are in separate impl blocks
+        // For structs/enums/traits, methods are in separate impl blocks
         // This is a simplified version - in practice, we'd need to correlate impls
         
         Some(ClassMetrics {
@@ -118,6 +140,12 @@ impl RustParser {
             complexity: Complexity(1),
             methods: Arc::new([]),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: None,
+            is_abstract: node.kind() == "trait_item",
+            is_public: node
+                .children(&mut node.walk())
+                .any(|c| c.kind() == "visibility_modifier" && c.utf8_text(source).ok() == Some("pub")),
         })
     }
 
@@ -127,10 +155,7 @@ impl RustParser {
         source: &[u8],
         path: &Path,
     ) -> Option<ClassMetrics> {
-        let type_name = node
-            .child_by_field_name("type")?
-            .utf8_text(source)
-            .ok()?;
+        let type_name = impl_type_name(node.child_by_field_name("type")?, source)?;
 
         let text = node.utf8_text(source).ok()?;
         let lines = ComplexityCalculator::count_lines(text);
@@ -168,6 +193,10 @@ impl RustParser {
             complexity: Complexity(total_complexity),
             methods: methods.into(),
             dependencies: Arc::new([]),
+            span: ComplexityCalculator::span(node),
+            entry_point: None,
+            is_abstract: false,
+            is_public: true,
         })
     }
 
@@ -197,18 +226,28 @@ impl RustParser {
 
         let tokens = ComplexityCalculator::extract_tokens(node, source);
 
+        let usage = MemberUsage::extract(node, source, &[]);
+
         Some(MethodMetrics {
             name: name.into(),
             lines,
             complexity,
             parameters,
-            called_methods: Arc::new([]),
-            accessed_fields: Arc::new([]),
+            params: ComplexityCalculator::extract_parameters(node, source).into(),
+            generics: ComplexityCalculator::extract_generics(node, source).into(),
+            nesting_depth: ComplexityCalculator::max_nesting_depth(node),
+            chain_length: ComplexityCalculator::max_chain_length(node, source),
+            exit_points: ComplexityCalculator::count_exit_points(node),
+            magic_literals: ComplexityCalculator::count_magic_literals(node, source),
+            span: ComplexityCalculator::span(node),
+            called_methods: usage.calls.into(),
+            accessed_fields: usage.fields.into(),
             return_type: return_type.into(),
             is_public,
-            is_static: false, // Rust doesn't have static methods in the same way
+            is_static: !ComplexityCalculator::has_self_receiver(node), // Associated function
             is_async: self.is_async_fn(node),
             tokens: tokens.into_iter().map(|s| s.into()).collect(),
+            ast_snippet: self.options.snippet(node),
         })
     }
 
@@ -232,3 +271,13 @@ impl Default for RustParser {
     }
 }
 
+
+/// The type an `impl` block is for, as its definition names it: `JsonReport` for
+/// `impl<'a> JsonReport<'a>`, `Bar` for `impl Display for foo::Bar`
+fn impl_type_name<'a>(node: tree_sitter::Node, source: &'a [u8]) -> Option<&'a str> {
+    match node.kind() {
+        "generic_type" | "reference_type" => impl_type_name(node.child_by_field_name("type")?, source),
+        "scoped_type_identifier" => impl_type_name(node.child_by_field_name("name")?, source),
+        _ => node.utf8_text(source).ok(),
+    }
+}
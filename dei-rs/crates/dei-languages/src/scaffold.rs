@@ -0,0 +1,261 @@
+//! Skeletons of classes extracted from existing ones (Rust and C#)
+//!
+//! Only declarations are carried over: the imports of the source file, the fields the
+//! extracted methods touch and the method signatures as written. Bodies are left as
+//! `todo!()` or `throw new NotImplementedException()` for the developer to move across.
+
+use dei_core::{error::Result, models::Language, Error};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Node, Parser};
+
+/// Methods to move out of a class, and the class to move them into
+#[derive(Debug, Clone)]
+pub struct Extraction<'a> {
+    /// Class the methods are declared in
+    pub class: &'a str,
+    /// Name of the new class
+    pub name: &'a str,
+    pub methods: &'a [Arc<str>],
+    /// Fields the methods access; declared ones are copied into the new class
+    pub fields: &'a [Arc<str>],
+}
+
+/// Languages [`scaffold`] can generate
+pub fn supports(language: Language) -> bool {
+    matches!(language, Language::Rust | Language::CSharp)
+}
+
+/// File the new class goes into, next to `path`: `snake_case.rs` for Rust, `Name.cs` for C#
+pub fn scaffold_path(path: &Path, name: &str) -> Option<std::path::PathBuf> {
+    let file = match Language::from_path(path)? {
+        Language::Rust => format!("{}.rs", snake_case(name)),
+        Language::CSharp => format!("{}.cs", name),
+        _ => return None,
+    };
+    Some(path.with_file_name(file))
+}
+
+/// Source of the new class, built from the declarations in `path`
+pub fn scaffold(path: &Path, extraction: &Extraction) -> Result<String> {
+    let language = Language::from_path(path)
+        .filter(|l| supports(*l))
+        .ok_or_else(|| Error::UnsupportedLanguage(path.display().to_string()))?;
+    let source = std::fs::read_to_string(path)?;
+
+    let mut parser = Parser::new();
+    let grammar: tree_sitter::Language = match language {
+        Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+        _ => tree_sitter_c_sharp::LANGUAGE.into(),
+    };
+    parser
+        .set_language(&grammar)
+        .map_err(|e| Error::Analysis(format!("Failed to set {:?} language: {}", language, e)))?;
+    let tree = parser.parse(&source, None).ok_or_else(|| Error::Parse {
+        path: path.to_path_buf(),
+        message: "Failed to parse file".into(),
+    })?;
+
+    let origin = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    let declarations = Declarations::collect(&tree.root_node(), source.as_bytes(), extraction);
+    let missing: Vec<&str> = extraction
+        .methods
+        .iter()
+        .map(|m| m.as_ref())
+        .filter(|m| !declarations.methods.iter().any(|(name, _)| name == m))
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::Analysis(format!(
+            "{} declares no method {} on {}",
+            path.display(),
+            missing.join(", "),
+            extraction.class
+        )));
+    }
+
+    Ok(match language {
+        Language::Rust => render_rust(extraction, &origin, &declarations),
+        _ => render_csharp(extraction, &origin, &declarations),
+    })
+}
+
+/// Declarations found in the source, in source order
+#[derive(Default)]
+struct Declarations {
+    imports: Vec<String>,
+    /// C# namespace of the class
+    namespace: Option<String>,
+    fields: Vec<String>,
+    /// Method name and signature up to the body
+    methods: Vec<(String, String)>,
+}
+
+impl Declarations {
+    fn collect(root: &Node, source: &[u8], extraction: &Extraction) -> Self {
+        let mut declarations = Self::default();
+        let methods: HashSet<&str> = extraction.methods.iter().map(|m| m.as_ref()).collect();
+        let fields: HashSet<&str> = extraction.fields.iter().map(|f| f.as_ref()).collect();
+        declarations.visit(root, source, extraction.class, &methods, &fields, false);
+        declarations
+    }
+
+    fn visit(&mut self, node: &Node, source: &[u8], class: &str, methods: &HashSet<&str>, fields: &HashSet<&str>, in_class: bool) {
+        let text = |n: &Node| n.utf8_text(source).unwrap_or_default().to_string();
+        let name = |n: &Node| n.child_by_field_name("name").map(|n| text(&n)).unwrap_or_default();
+
+        match node.kind() {
+            "use_declaration" | "using_directive" => {
+                self.imports.push(text(node));
+                return;
+            }
+            "namespace_declaration" | "file_scoped_namespace_declaration"
+                if self.namespace.is_none() && contains_class(node, source, class) =>
+            {
+                self.namespace = node.child_by_field_name("name").map(|n| text(&n));
+            }
+            // Rust spreads a type over its struct and impl blocks
+            "struct_item" | "impl_item" | "class_declaration" => {
+                let own = match node.kind() {
+                    "impl_item" => node.child_by_field_name("type").map(|n| text(&n)),
+                    _ => Some(name(node)),
+                };
+                let own = own.is_some_and(|n| n == class);
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    self.visit(&child, source, class, methods, fields, own);
+                }
+                return;
+            }
+            "field_declaration" if in_class => {
+                // Rust: `name: Type`; C#: `Type a, b;`
+                let declared = match node.child_by_field_name("name") {
+                    Some(n) => vec![text(&n)],
+                    None => declarator_names(node, source),
+                };
+                if declared.iter().any(|d| fields.contains(d.as_str())) {
+                    self.fields.push(text(node));
+                }
+                return;
+            }
+            "property_declaration" if in_class => {
+                if fields.contains(name(node).as_str()) {
+                    self.fields.push(text(node));
+                }
+                return;
+            }
+            "function_item" | "method_declaration" if in_class => {
+                let method = name(node);
+                if methods.contains(method.as_str()) {
+                    let end = node.child_by_field_name("body").map_or(node.end_byte(), |b| b.start_byte());
+                    let signature = String::from_utf8_lossy(&source[node.start_byte()..end]).trim_end().to_string();
+                    self.methods.push((method, signature));
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(&child, source, class, methods, fields, in_class);
+        }
+    }
+}
+
+fn contains_class(node: &Node, source: &[u8], class: &str) -> bool {
+    if node.kind() == "class_declaration"
+        && node.child_by_field_name("name").and_then(|n| n.utf8_text(source).ok()) == Some(class)
+    {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|c| contains_class(&c, source, class));
+    found
+}
+
+fn declarator_names(node: &Node, source: &[u8]) -> Vec<String> {
+    if node.kind() == "variable_declarator" {
+        let name = node.child_by_field_name("name").or_else(|| node.named_child(0));
+        return name.and_then(|n| n.utf8_text(source).ok()).map(str::to_string).into_iter().collect();
+    }
+    let mut cursor = node.walk();
+    let names = node.children(&mut cursor).flat_map(|c| declarator_names(&c, source)).collect();
+    names
+}
+
+fn render_rust(extraction: &Extraction, origin: &str, declarations: &Declarations) -> String {
+    let mut out = format!("//! {}, extracted from `{}` in {}\n\n", extraction.name, extraction.class, origin);
+    for import in &declarations.imports {
+        out.push_str(import);
+        out.push('\n');
+    }
+    if !declarations.imports.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str(&format!("pub struct {} {{\n", extraction.name));
+    for field in &declarations.fields {
+        out.push_str(&format!("    {},\n", field));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!("impl {} {{\n", extraction.name));
+    for (i, (name, signature)) in declarations.methods.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("    {} {{\n", signature));
+        out.push_str(&format!("        todo!(\"move the body of {}::{}\")\n", extraction.class, name));
+        out.push_str("    }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_csharp(extraction: &Extraction, origin: &str, declarations: &Declarations) -> String {
+    let mut out = String::new();
+    for import in &declarations.imports {
+        out.push_str(import);
+        out.push('\n');
+    }
+    if !declarations.imports.is_empty() {
+        out.push('\n');
+    }
+    if let Some(namespace) = &declarations.namespace {
+        out.push_str(&format!("namespace {};\n\n", namespace));
+    }
+
+    out.push_str(&format!("// {}, extracted from {} in {}\n", extraction.name, extraction.class, origin));
+    out.push_str(&format!("public class {}\n{{\n", extraction.name));
+    for field in &declarations.fields {
+        out.push_str(&format!("    {}\n", field));
+    }
+    for (name, signature) in &declarations.methods {
+        out.push('\n');
+        out.push_str(&format!("    {}\n    {{\n", signature));
+        out.push_str(&format!(
+            "        throw new NotImplementedException(\"Move the body of {}.{}\");\n",
+            extraction.class, name
+        ));
+        out.push_str("    }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// `CacheService` → `cache_service`
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,200 @@
+//! Reading source files
+//!
+//! Large files are memory-mapped rather than copied onto the heap, except on wasm32, which
+//! has no mmap. Files whose first bytes contain a NUL are rejected as binary before any
+//! decoding is attempted. Sources that aren't UTF-8 (Latin-1, Shift-JIS and the like in
+//! legacy code) are transcoded from their byte-order mark or a detected encoding, replacing
+//! anything that doesn't decode.
+//!
+//! A mapping is only as stable as the file under it. A rewrite while the file is mapped
+//! shows through, so mapped text is checked as UTF-8 on every access rather than once when
+//! mapped, and is cut short at the first byte that no longer decodes. Truncating the file
+//! while it is mapped is worse: touching a page past the new end raises `SIGBUS` and kills
+//! the process. Mappings live only as long as one parse, which keeps that window small, but
+//! it can't be closed; analyzing a tree whose large files are being rewritten is at risk.
+//!
+//! Parsers read through a [`SourceProvider`]: the [`FileSystem`] by default, or
+//! [`MemorySources`] where there is no filesystem to read, as in a browser (wasm32).
+
+use chardetng::EncodingDetector;
+use dei_core::{error::Result, metrics::FileMetrics, Error};
+use encoding_rs::Encoding;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Files at least this large are memory-mapped, except on wasm32
+pub const MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// Bytes checked for NUL when sniffing for binary content
+const SNIFF_BYTES: usize = 8 * 1024;
+
+/// The text of a source file, owned, mapped or transcoded
+pub enum SourceText {
+    Owned(String),
+    /// Mapped rather than read; see the module docs for what that risks
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(memmap2::Mmap),
+    /// Decoded from a legacy encoding, lossily
+    Transcoded { text: String, encoding: &'static str },
+}
+
+impl SourceText {
+    /// Encoding the text was transcoded from, if it wasn't UTF-8
+    pub fn encoding(&self) -> Option<&'static str> {
+        match self {
+            Self::Transcoded { encoding, .. } => Some(encoding),
+            _ => None,
+        }
+    }
+
+    /// Note on `metrics` parsed from this text the encoding it was transcoded from
+    pub fn tag(&self, mut metrics: FileMetrics) -> FileMetrics {
+        metrics.encoding = self.encoding().map(Into::into);
+        metrics
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            Self::Owned(text) | Self::Transcoded { text, .. } => text,
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Mapped(mapping) => valid_prefix(mapping),
+        }
+    }
+}
+
+/// `bytes` as text, up to the first byte that isn't UTF-8
+#[cfg(not(target_arch = "wasm32"))]
+fn valid_prefix(bytes: &[u8]) -> &str {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+/// Where parsers get the text of the files they are asked to parse
+pub trait SourceProvider: Send + Sync {
+    /// The text of the file at `path`
+    fn read(&self, path: &Path) -> Result<SourceText>;
+
+    /// Whether `path` can still be read, so caches can drop files that are gone
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// Source files on disk, read with [`read_source`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileSystem;
+
+impl SourceProvider for FileSystem {
+    fn read(&self, path: &Path) -> Result<SourceText> {
+        read_source(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// Source files held in memory, by path, e.g. code pasted into a playground
+#[derive(Debug, Clone, Default)]
+pub struct MemorySources {
+    files: HashMap<PathBuf, Arc<str>>,
+}
+
+impl MemorySources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `text` as the file at `path`, replacing what was there
+    pub fn insert(&mut self, path: impl Into<PathBuf>, text: impl Into<Arc<str>>) {
+        self.files.insert(path.into(), text.into());
+    }
+
+    pub fn with_file(mut self, path: impl Into<PathBuf>, text: impl Into<Arc<str>>) -> Self {
+        self.insert(path, text);
+        self
+    }
+
+    pub fn remove(&mut self, path: &Path) -> Option<Arc<str>> {
+        self.files.remove(path)
+    }
+}
+
+impl SourceProvider for MemorySources {
+    fn read(&self, path: &Path) -> Result<SourceText> {
+        let text = self.files.get(path).ok_or_else(|| Error::PathNotFound(path.to_path_buf()))?;
+        check_binary(path, text.as_bytes())?;
+        Ok(SourceText::Owned(text.to_string()))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+}
+
+/// Read a source file as text, mapping it if it is large and transcoding it if it isn't UTF-8
+pub fn read_source(path: &Path) -> Result<SourceText> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if len >= MMAP_THRESHOLD {
+        // SAFETY: the file may change under the mapping; `SourceText` re-checks it as UTF-8
+        // on every access, and truncation is the documented SIGBUS hazard
+        let mapping = unsafe { memmap2::Mmap::map(&file)? };
+        if std::str::from_utf8(&mapping).is_err() {
+            return transcode(path, &mapping);
+        }
+        check_binary(path, &mapping)?;
+        return Ok(SourceText::Mapped(mapping));
+    }
+
+    let mut bytes = Vec::with_capacity(len as usize);
+    file.read_to_end(&mut bytes)?;
+    match String::from_utf8(bytes) {
+        Ok(text) => {
+            check_binary(path, text.as_bytes())?;
+            Ok(SourceText::Owned(text))
+        }
+        Err(e) => transcode(path, e.as_bytes()),
+    }
+}
+
+/// Whether `bytes` look like binary content rather than text
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(SNIFF_BYTES)].contains(&0)
+}
+
+fn check_binary(path: &Path, bytes: &[u8]) -> Result<()> {
+    if is_binary(bytes) {
+        return Err(Error::Parse {
+            path: path.to_path_buf(),
+            message: "binary content".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Decode non-UTF-8 bytes by their byte-order mark or, failing that, a detected encoding
+fn transcode(path: &Path, bytes: &[u8]) -> Result<SourceText> {
+    // UTF-16 is full of NULs, so a byte-order mark wins over sniffing
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None => {
+            check_binary(path, bytes)?;
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, false)
+        }
+    };
+    let (text, _, _) = encoding.decode(bytes);
+    Ok(SourceText::Transcoded { text: text.into_owned(), encoding: encoding.name() })
+}
@@ -0,0 +1,608 @@
+//! Field access and call extraction from method bodies, and file imports
+//!
+//! Grammar-agnostic: recognises the member/call node shapes shared by the
+//! tree-sitter grammars we ship (Rust, C#, Python, JS/TS, Java)
+
+use dei_core::metrics::{ClassMetrics, LineSpan};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+use tree_sitter::Node;
+
+/// Fields a method touches on its own instance and the functions it calls
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemberUsage {
+    pub fields: Vec<Arc<str>>,
+    pub calls: Vec<Arc<str>>,
+}
+
+impl MemberUsage {
+    /// Walk a method node collecting `self.x`/`this.x` accesses and call targets
+    ///
+    /// Calls through `self`/`this` and unqualified calls are recorded by bare name,
+    /// calls on another identifier as `Receiver.name`. `known_fields` lets languages
+    /// with implicit member access (C#, Java) count bare identifiers as field reads.
+    pub fn extract(node: &Node, source: &[u8], known_fields: &[&str]) -> Self {
+        let mut usage = Collector {
+            source,
+            known_fields,
+            fields: Vec::new(),
+            calls: Vec::new(),
+            seen_fields: HashSet::new(),
+            seen_calls: HashSet::new(),
+            callees: HashSet::new(),
+        };
+        usage.visit(node);
+
+        Self {
+            fields: usage.fields.into_iter().map(Into::into).collect(),
+            calls: usage.calls.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+struct Collector<'a> {
+    source: &'a [u8],
+    known_fields: &'a [&'a str],
+    fields: Vec<String>,
+    calls: Vec<String>,
+    seen_fields: HashSet<String>,
+    seen_calls: HashSet<String>,
+    /// Member nodes in callee position, so they aren't also counted as field reads
+    callees: HashSet<usize>,
+}
+
+impl Collector<'_> {
+    fn visit(&mut self, node: &Node) {
+        match node.kind() {
+            "call_expression" | "call" | "invocation_expression" => {
+                if let Some(callee) = node.child_by_field_name("function") {
+                    self.record_callee(&callee);
+                }
+            }
+            "method_invocation" => {
+                // Java: object.name(...) or name(...)
+                if let Some(name) = node.child_by_field_name("name").and_then(|n| self.text(&n)) {
+                    match node.child_by_field_name("object") {
+                        Some(obj) if is_self_receiver(&obj, self.source) => self.push_call(name),
+                        Some(obj) if is_plain_identifier(&obj) => {
+                            let receiver = self.text(&obj).unwrap_or_default();
+                            self.push_call(format!("{receiver}.{name}"));
+                        }
+                        Some(_) => self.push_call(name),
+                        None => self.push_call(name),
+                    }
+                }
+            }
+            kind if is_member_access(kind) && !self.callees.contains(&node.id()) => {
+                if let Some((receiver, member)) = member_parts(node) {
+                    if is_self_receiver(&receiver, self.source) {
+                        if let Some(name) = self.text(&member) {
+                            self.push_field(name);
+                        }
+                    }
+                }
+            }
+            "identifier" if !self.known_fields.is_empty() => {
+                if let Some(name) = self.text(node) {
+                    if self.known_fields.contains(&name.as_str()) && !self.is_declaration_name(node) {
+                        self.push_field(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(&child);
+        }
+    }
+
+    fn record_callee(&mut self, callee: &Node) {
+        if is_member_access(callee.kind()) {
+            self.callees.insert(callee.id());
+            let Some((receiver, member)) = member_parts(callee) else {
+                return;
+            };
+            let Some(name) = self.text(&member) else {
+                return;
+            };
+
+            if is_self_receiver(&receiver, self.source) {
+                self.push_call(name);
+            } else if is_plain_identifier(&receiver) {
+                let receiver = self.text(&receiver).unwrap_or_default();
+                self.push_call(format!("{receiver}.{name}"));
+            } else {
+                self.push_call(name);
+            }
+        } else if let Some(text) = self.text(callee) {
+            // identifier, scoped_identifier (`Type::new`), generic names
+            self.push_call(text);
+        }
+    }
+
+    /// Skip the identifier being declared (parameter or local named like a field)
+    fn is_declaration_name(&self, node: &Node) -> bool {
+        node.parent().is_some_and(|p| {
+            matches!(p.kind(), "parameter" | "formal_parameter" | "variable_declarator")
+                && p.child_by_field_name("name").is_some_and(|n| n.id() == node.id())
+        })
+    }
+
+    fn push_field(&mut self, name: String) {
+        if self.seen_fields.insert(name.clone()) {
+            self.fields.push(name);
+        }
+    }
+
+    fn push_call(&mut self, name: String) {
+        if self.seen_calls.insert(name.clone()) {
+            self.calls.push(name);
+        }
+    }
+
+    fn text(&self, node: &Node) -> Option<String> {
+        node.utf8_text(self.source).ok().map(str::to_string)
+    }
+}
+
+pub(crate) fn is_member_access(kind: &str) -> bool {
+    matches!(
+        kind,
+        "field_expression" | "member_expression" | "attribute" | "member_access_expression" | "field_access"
+    )
+}
+
+/// `(receiver, member)` children of a member access node
+pub(crate) fn member_parts<'t>(node: &Node<'t>) -> Option<(Node<'t>, Node<'t>)> {
+    let (receiver, member) = match node.kind() {
+        "field_expression" => ("value", "field"),
+        "member_expression" => ("object", "property"),
+        "attribute" => ("object", "attribute"),
+        "member_access_expression" => ("expression", "name"),
+        "field_access" => ("object", "field"),
+        _ => return None,
+    };
+    Some((node.child_by_field_name(receiver)?, node.child_by_field_name(member)?))
+}
+
+pub(crate) fn is_self_receiver(node: &Node, source: &[u8]) -> bool {
+    match node.kind() {
+        "self" | "this" | "this_expression" => true,
+        "identifier" => matches!(node.utf8_text(source), Ok("self" | "this")),
+        _ => false,
+    }
+}
+
+fn is_plain_identifier(node: &Node) -> bool {
+    matches!(node.kind(), "identifier" | "type_identifier")
+}
+
+/// Names of fields and properties declared directly in a class body
+///
+/// Used as `known_fields` for languages where members are accessed without `this.`
+pub fn declared_fields<'s>(body: &Node, source: &'s [u8]) -> Vec<&'s str> {
+    let mut names = Vec::new();
+    let mut cursor = body.walk();
+    for member in body.children(&mut cursor) {
+        match member.kind() {
+            "field_declaration" => collect_declarator_names(&member, source, &mut names),
+            "property_declaration" => {
+                if let Some(name) = member.child_by_field_name("name").and_then(|n| n.utf8_text(source).ok()) {
+                    names.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn collect_declarator_names<'s>(node: &Node, source: &'s [u8], names: &mut Vec<&'s str>) {
+    if node.kind() == "variable_declarator" {
+        let name = node.child_by_field_name("name").or_else(|| {
+            let mut cursor = node.walk();
+            let first = node.named_children(&mut cursor).find(|c| c.kind() == "identifier");
+            first
+        });
+        if let Some(name) = name.and_then(|n| n.utf8_text(source).ok()) {
+            names.push(name);
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_declarator_names(&child, source, names);
+    }
+}
+
+/// A module a file imports, as written, and the names code refers to it by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub module: Arc<str>,
+    /// Names the import binds; empty when code can't be told to use it by name, as for
+    /// wildcards, C# namespaces, side-effect imports and traits imported for their methods
+    pub names: Vec<Arc<str>>,
+}
+
+/// Modules a file imports (`use`, `using`, `import`), as written
+///
+/// Grouped Rust imports keep only their common prefix (`serde::{A, B}` → `serde`, binding
+/// `A` and `B`); JS/TS import sources are unquoted (`'lodash/fp'` → `lodash/fp`).
+pub fn file_imports(root: &Node, source: &[u8]) -> Vec<Import> {
+    let mut imports: Vec<(Import, Range<usize>)> = Vec::new();
+    collect_imports(root, source, &mut imports);
+
+    // Names never written outside the imports are used implicitly, like trait methods
+    let mut rest = source.to_vec();
+    for (_, range) in &imports {
+        rest[range.clone()].fill(b' ');
+    }
+    let rest = String::from_utf8_lossy(&rest);
+    let written: HashSet<&str> = identifiers(&rest).collect();
+
+    // One entry per module, binding every name any of its imports bound
+    let mut merged: Vec<Import> = Vec::new();
+    let mut index: HashMap<Arc<str>, usize> = HashMap::new();
+    for (mut import, _) in imports {
+        if !import.names.iter().any(|n| written.contains(n.as_ref())) {
+            import.names.clear();
+        }
+        match index.get(&import.module) {
+            Some(&i) if merged[i].names.is_empty() => {}
+            Some(&i) if import.names.is_empty() => merged[i].names.clear(),
+            Some(&i) => merged[i].names.extend(import.names),
+            None => {
+                index.insert(import.module.clone(), merged.len());
+                merged.push(import);
+            }
+        }
+    }
+    merged
+}
+
+/// Attach to each class the imports (from [`file_imports`]) its lines refer to by name, plus
+/// those whose names can't be told; a class without a known span gets every import
+pub fn attach_imports(classes: Vec<ClassMetrics>, imports: &[Import], source: &str) -> Arc<[ClassMetrics]> {
+    let lines: Vec<&str> = source.lines().collect();
+    classes
+        .into_iter()
+        .map(|class| {
+            let dependencies = match class_identifiers(&class, &lines) {
+                Some(used) => imports
+                    .iter()
+                    .filter(|i| i.names.is_empty() || i.names.iter().any(|n| used.contains(n.as_ref())))
+                    .map(|i| i.module.clone())
+                    .collect(),
+                None => imports.iter().map(|i| i.module.clone()).collect(),
+            };
+            ClassMetrics { dependencies, ..class }
+        })
+        .collect()
+}
+
+/// Identifiers on the lines of a class and of its methods, which in Rust live in `impl` blocks
+/// apart from the type, including the attributes and decorators just above each
+fn class_identifiers<'s>(class: &ClassMetrics, lines: &[&'s str]) -> Option<HashSet<&'s str>> {
+    if !class.span.is_known() {
+        return None;
+    }
+    let spans = std::iter::once(class.span).chain(class.methods.iter().map(|m| m.span).filter(LineSpan::is_known));
+    let mut used = HashSet::new();
+    for span in spans {
+        let mut start = span.start - 1;
+        while start > 0 && lines.get(start - 1).is_some_and(|l| is_annotation(l)) {
+            start -= 1;
+        }
+        for line in lines.get(start..span.end.min(lines.len())).unwrap_or_default() {
+            used.extend(identifiers(line));
+        }
+    }
+    Some(used)
+}
+
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).filter(|t| !t.is_empty())
+}
+
+fn collect_imports(node: &Node, source: &[u8], imports: &mut Vec<(Import, Range<usize>)>) {
+    let text = |n: Node| n.utf8_text(source).ok().map(str::trim);
+    let range = node.byte_range();
+    let mut push = |module: &str, names: Vec<Arc<str>>| imports.push((Import { module: module.into(), names }, range.clone()));
+
+    match node.kind() {
+        "use_declaration" => {
+            if let Some(tree) = node.child_by_field_name("argument").and_then(text) {
+                let path = tree.split("::{").next().unwrap_or(tree);
+                let path = path.split(" as ").next().unwrap_or(path);
+                push(path, use_tree_names(tree).unwrap_or_default());
+            }
+            return;
+        }
+        "using_directive" => {
+            // `using Alias = Name;` and `using static Name;` both end with the name; only an
+            // alias says what code calls it
+            let mut cursor = node.walk();
+            let last = node.named_children(&mut cursor).last();
+            if let Some(name) = last.and_then(text) {
+                let alias = text(*node)
+                    .and_then(|t| t.split_once('='))
+                    .and_then(|(head, _)| head.split_whitespace().last())
+                    .map(Arc::from);
+                push(name, alias.into_iter().collect());
+            }
+            return;
+        }
+        "import_declaration" => {
+            let mut cursor = node.walk();
+            let name = node
+                .named_children(&mut cursor)
+                .find(|c| matches!(c.kind(), "scoped_identifier" | "identifier"));
+            let mut cursor = node.walk();
+            let wildcard = node.children(&mut cursor).any(|c| c.kind() == "asterisk");
+            if let Some(name) = name.and_then(text) {
+                let bound = (!wildcard).then(|| last_segment(name, '.').into());
+                push(name, bound.into_iter().collect());
+            }
+            return;
+        }
+        "import_statement" if node.child_by_field_name("source").is_some() => {
+            if let Some(src) = node.child_by_field_name("source").and_then(text) {
+                push(src.trim_matches(|c| c == '"' || c == '\'' || c == '`'), js_bound_names(node, source));
+            }
+            return;
+        }
+        "import_statement" => {
+            // Python `import a.b, c as d` binds `a` and `d`
+            let mut cursor = node.walk();
+            for name in node.children_by_field_name("name", &mut cursor) {
+                let (module, bound) = match name.kind() {
+                    "aliased_import" => (name.child_by_field_name("name").and_then(text), name.child_by_field_name("alias").and_then(text)),
+                    _ => (text(name), text(name).and_then(|m| m.split('.').next())),
+                };
+                if let Some(module) = module {
+                    push(module, bound.map(Arc::from).into_iter().collect());
+                }
+            }
+            return;
+        }
+        "import_from_statement" => {
+            if let Some(module) = node.child_by_field_name("module_name").and_then(text) {
+                let mut cursor = node.walk();
+                let bound = node
+                    .children_by_field_name("name", &mut cursor)
+                    .filter_map(|name| match name.kind() {
+                        "aliased_import" => name.child_by_field_name("alias").and_then(text),
+                        _ => text(name).map(|n| last_segment(n, '.')),
+                    })
+                    .map(Arc::from)
+                    .collect();
+                push(module, bound);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_imports(&child, source, imports);
+    }
+}
+
+/// An attribute or decorator line: `#[derive(..)]`, `@dataclass`, `[ApiController]`
+fn is_annotation(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("#[") || line.starts_with('@') || (line.starts_with('[') && line.trim_end().ends_with(']'))
+}
+
+/// Names a Rust use tree binds, or `None` when it can't be told: a wildcard, or a trait
+/// imported `as _` for its methods
+fn use_tree_names(tree: &str) -> Option<Vec<Arc<str>>> {
+    let tree = tree.trim();
+    if let Some((prefix, rest)) = tree.split_once('{') {
+        let prefix = prefix.trim_end_matches("::");
+        let mut names = Vec::new();
+        for item in split_top_level(rest.strip_suffix('}')?) {
+            match item.trim() {
+                "" => {}
+                "self" => names.push(last_segment(prefix, ':').into()),
+                item => names.extend(use_tree_names(item)?),
+            }
+        }
+        return Some(names);
+    }
+    let name = match tree.split_once(" as ") {
+        Some((_, alias)) => alias.trim(),
+        None => last_segment(tree, ':'),
+    };
+    (!matches!(name, "*" | "_")).then(|| vec![name.into()])
+}
+
+/// `items` split at the commas outside nested braces
+fn split_top_level(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in items.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&items[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&items[start..]);
+    parts
+}
+
+fn last_segment(path: &str, separator: char) -> &str {
+    path.rsplit(separator).next().unwrap_or(path)
+}
+
+/// Local names a JS/TS import binds: its default, namespace (`* as ns`) and named specifiers
+fn js_bound_names(node: &Node, source: &[u8]) -> Vec<Arc<str>> {
+    let text = |n: Node| n.utf8_text(source).ok().map(str::trim);
+    let mut names: Vec<Arc<str>> = Vec::new();
+    let mut cursor = node.walk();
+    for clause in node.named_children(&mut cursor).filter(|c| c.kind() == "import_clause") {
+        let mut cursor = clause.walk();
+        for binding in clause.named_children(&mut cursor) {
+            match binding.kind() {
+                "named_imports" => {
+                    let mut cursor = binding.walk();
+                    for specifier in binding.named_children(&mut cursor).filter(|s| s.kind() == "import_specifier") {
+                        let local = specifier.child_by_field_name("alias").or_else(|| specifier.child_by_field_name("name"));
+                        names.extend(local.and_then(text).map(Arc::from));
+                    }
+                }
+                "namespace_import" => {
+                    let mut cursor = binding.walk();
+                    let local = binding.named_children(&mut cursor).find(|c| c.kind() == "identifier");
+                    names.extend(local.and_then(text).map(Arc::from));
+                }
+                _ => names.extend(text(binding).map(Arc::from)),
+            }
+        }
+    }
+    names
+}
+
+/// Names a file pulls into its namespace, and the modules it imports wholesale
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedNames {
+    /// Names bound by import statements, a wildcard counting as one
+    pub symbols: usize,
+    /// Modules imported with `*`, `using static` or `export * from`
+    pub wildcards: Vec<Arc<str>>,
+}
+
+/// Count imported names and collect wildcard imports
+pub fn imported_names(root: &Node, source: &[u8]) -> ImportedNames {
+    let mut names = ImportedNames::default();
+    collect_imported_names(root, source, &mut names);
+    names
+}
+
+fn collect_imported_names(node: &Node, source: &[u8], names: &mut ImportedNames) {
+    let text = |n: Node| n.utf8_text(source).ok().map(str::trim);
+    let has_child = |kind: &str| {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|c| c.kind() == kind);
+        found
+    };
+
+    match node.kind() {
+        "use_declaration" => {
+            if let Some(argument) = node.child_by_field_name("argument") {
+                count_use_tree(&argument, source, names);
+            }
+            return;
+        }
+        "using_directive" => {
+            names.symbols += 1;
+            if has_child("static") {
+                let mut cursor = node.walk();
+                let last = node.named_children(&mut cursor).last();
+                if let Some(name) = last.and_then(text) {
+                    names.wildcards.push(name.into());
+                }
+            }
+            return;
+        }
+        "import_declaration" => {
+            names.symbols += 1;
+            if has_child("asterisk") {
+                let mut cursor = node.walk();
+                let name = node
+                    .named_children(&mut cursor)
+                    .find(|c| matches!(c.kind(), "scoped_identifier" | "identifier"));
+                if let Some(name) = name.and_then(text) {
+                    names.wildcards.push(name.into());
+                }
+            }
+            return;
+        }
+        "import_statement" if node.child_by_field_name("source").is_some() => {
+            // JS/TS: default, namespace (`* as ns`) and named specifiers each bind one name
+            let mut cursor = node.walk();
+            for clause in node.named_children(&mut cursor).filter(|c| c.kind() == "import_clause") {
+                let mut cursor = clause.walk();
+                for binding in clause.named_children(&mut cursor) {
+                    names.symbols += match binding.kind() {
+                        "named_imports" => {
+                            let mut cursor = binding.walk();
+                            let specifiers = binding
+                                .named_children(&mut cursor)
+                                .filter(|s| s.kind() == "import_specifier")
+                                .count();
+                            specifiers
+                        }
+                        _ => 1,
+                    };
+                }
+            }
+            return;
+        }
+        "export_statement" if node.child_by_field_name("source").is_some() => {
+            if has_child("*") && !has_child("namespace_export") {
+                names.symbols += 1;
+                if let Some(src) = node.child_by_field_name("source").and_then(text) {
+                    names.wildcards.push(src.trim_matches(|c| c == '"' || c == '\'' || c == '`').into());
+                }
+            }
+            return;
+        }
+        "import_statement" => {
+            let mut cursor = node.walk();
+            names.symbols += node.children_by_field_name("name", &mut cursor).count();
+            return;
+        }
+        "import_from_statement" => {
+            if has_child("wildcard_import") {
+                names.symbols += 1;
+                if let Some(module) = node.child_by_field_name("module_name").and_then(text) {
+                    names.wildcards.push(module.into());
+                }
+            } else {
+                let mut cursor = node.walk();
+                names.symbols += node.children_by_field_name("name", &mut cursor).count();
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_imported_names(&child, source, names);
+    }
+}
+
+/// Rust `use` trees: every leaf binds a name, `path::*` imports the whole module
+fn count_use_tree(node: &Node, source: &[u8], names: &mut ImportedNames) {
+    match node.kind() {
+        "use_wildcard" => {
+            names.symbols += 1;
+            let text = node.utf8_text(source).unwrap_or_default();
+            names.wildcards.push(text.trim_end_matches('*').trim_end_matches("::").into());
+        }
+        "scoped_use_list" => {
+            if let Some(list) = node.child_by_field_name("list") {
+                count_use_tree(&list, source, names);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                count_use_tree(&child, source, names);
+            }
+        }
+        _ => names.symbols += 1,
+    }
+}
@@ -0,0 +1,115 @@
+//! Class cohesion via LCOM4 (Hitz & Montazeri)
+//!
+//! Methods are linked when they share an instance field or one calls the other;
+//! LCOM4 is the number of connected components. A score above 1 means the class
+//! holds unrelated groups of methods that could live in separate types.
+
+use dei_core::{metrics::ClassMetrics, thresholds::Thresholds};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// LCOM4 result for a single class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cohesion {
+    pub lcom4: usize,
+    /// Method names grouped by connected component
+    pub components: Vec<Vec<Arc<str>>>,
+}
+
+impl Cohesion {
+    pub fn is_low(&self, thresholds: &Thresholds) -> bool {
+        self.lcom4 > thresholds.max_lcom4
+    }
+}
+
+/// Computes LCOM4 from the field accesses and calls recorded on each method
+pub struct CohesionAnalyzer;
+
+impl CohesionAnalyzer {
+    /// LCOM4 for a class, `None` when fewer than two methods take part
+    ///
+    /// Static methods and methods that neither touch a field nor call a sibling
+    /// are left out: with no instance state to share they say nothing about cohesion.
+    pub fn lcom4(class: &ClassMetrics) -> Option<Cohesion> {
+        let names: Vec<&str> = class.methods.iter().map(|m| m.name.as_ref()).collect();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        let mut sets = DisjointSet::new(names.len());
+        let mut participates = vec![false; names.len()];
+        let mut field_owner: HashMap<&str, usize> = HashMap::new();
+
+        for (i, method) in class.methods.iter().enumerate() {
+            if method.is_static {
+                continue;
+            }
+
+            for field in method.accessed_fields.iter() {
+                participates[i] = true;
+                match field_owner.get(field.as_ref()) {
+                    Some(&first) => sets.union(first, i),
+                    None => {
+                        field_owner.insert(field.as_ref(), i);
+                    }
+                }
+            }
+
+            for called in method.called_methods.iter() {
+                let Some(&callee) = index.get(called.as_ref()) else {
+                    continue;
+                };
+                if callee != i && !class.methods[callee].is_static {
+                    participates[i] = true;
+                    participates[callee] = true;
+                    sets.union(i, callee);
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<Arc<str>>> = HashMap::new();
+        for (i, method) in class.methods.iter().enumerate() {
+            if participates[i] {
+                groups.entry(sets.find(i)).or_default().push(method.name.clone());
+            }
+        }
+
+        if participates.iter().filter(|p| **p).count() < 2 {
+            return None;
+        }
+
+        let mut components: Vec<Vec<Arc<str>>> = groups.into_values().collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        Some(Cohesion {
+            lcom4: components.len(),
+            components,
+        })
+    }
+}
+
+/// Union-find with path compression
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            let root = self.find(self.parent[x]);
+            self.parent[x] = root;
+        }
+        self.parent[x]
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[rb] = ra;
+        }
+    }
+}
@@ -2,22 +2,73 @@
 //! 
 //! New capability not in C# version - analyzes inter-class dependencies
 
-use dei_core::metrics::ClassMetrics;
-use std::collections::HashMap;
+use dei_core::{metrics::ClassMetrics, models::ClassCoupling};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
-use crate::graph::{DependencyGraph, EdgeKind};
+use crate::graph::{Centrality, DependencyGraph, EdgeKind, EdgeSite};
+use crate::manifest::{DependencyOrigin, PackageManifests};
+use crate::packages::{PackageAnalysis, PackageCoupling};
+
+/// Resolves a referenced type or function name to the class that defines it
+pub type SymbolResolver = Box<dyn Fn(&str) -> Option<Arc<str>> + Send + Sync>;
 
 /// Analyzes coupling between classes
 pub struct CouplingAnalyzer {
     graph: DependencyGraph,
+    resolver: Option<SymbolResolver>,
+    manifests: PackageManifests,
+    /// Third-party packages each class imports, kept out of the graph
+    external: HashMap<Arc<str>, BTreeSet<Arc<str>>>,
 }
 
 impl CouplingAnalyzer {
     pub fn new() -> Self {
         Self {
             graph: DependencyGraph::new(),
+            resolver: None,
+            manifests: PackageManifests::new(),
+            external: HashMap::new(),
+        }
+    }
+
+    /// Resolve references through a project symbol table instead of name heuristics
+    pub fn with_resolver(
+        mut self,
+        resolver: impl Fn(&str) -> Option<Arc<str>> + Send + Sync + 'static,
+    ) -> Self {
+        self.resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Resolve references against `classes` alone, for when no project symbol table is at hand
+    ///
+    /// Type names resolve to the class of that name; method names resolve to their class only
+    /// when a single class declares them.
+    pub fn with_class_resolver(self, classes: &[ClassMetrics]) -> Self {
+        let mut owners: HashMap<Arc<str>, Option<Arc<str>>> = HashMap::new();
+        for class in classes {
+            for method in class.methods.iter() {
+                owners
+                    .entry(method.name.clone())
+                    .and_modify(|owner| {
+                        if owner.as_ref() != Some(&class.name) {
+                            *owner = None;
+                        }
+                    })
+                    .or_insert_with(|| Some(class.name.clone()));
+            }
+        }
+        for class in classes {
+            owners.insert(class.name.clone(), Some(class.name.clone()));
         }
+        self.with_resolver(move |name| owners.get(name).cloned().flatten())
+    }
+
+    /// Tell third-party imports apart using the project's package manifests
+    pub fn with_manifests(mut self, manifests: PackageManifests) -> Self {
+        self.manifests = manifests;
+        self
     }
 
     /// Build dependency graph from class metrics
@@ -26,28 +77,129 @@ impl CouplingAnalyzer {
             let class_name = class.name.clone();
             self.graph.add_node(class_name.clone());
 
-            // Add dependencies
+            // Add dependencies; only internal ones become graph edges
             for dep in class.dependencies.iter() {
-                self.graph.add_edge(class_name.clone(), dep.clone(), EdgeKind::Uses);
+                match self.manifests.classify(dep) {
+                    DependencyOrigin::Standard => {}
+                    DependencyOrigin::ThirdParty(package) => {
+                        self.external.entry(class_name.clone()).or_default().insert(package);
+                    }
+                    DependencyOrigin::Internal => {
+                        if let Some(target) = self.dependency_target(dep) {
+                            let site = EdgeSite { file: class.file_path.clone(), method: None, span: class.span };
+                            self.graph.add_edge_at(class_name.clone(), target, EdgeKind::Uses, site);
+                        }
+                    }
+                }
             }
 
             // Add method calls as edges
             for method in class.methods.iter() {
                 for called in method.called_methods.iter() {
-                    // Heuristic: if called method looks like external class
-                    if called.contains('.') || called.chars().next().map_or(false, |c| c.is_uppercase()) {
-                        self.graph.add_edge(class_name.clone(), called.clone(), EdgeKind::Calls);
+                    if let Some(target) = self.call_target(&class_name, called) {
+                        let site = EdgeSite {
+                            file: class.file_path.clone(),
+                            method: Some(method.name.clone()),
+                            span: method.span,
+                        };
+                        self.graph.add_edge_at(class_name.clone(), target, EdgeKind::Calls, site);
                     }
                 }
             }
         }
     }
 
+    /// Class an internal import points at; module-only imports resolve to nothing
+    fn dependency_target(&self, dep: &Arc<str>) -> Option<Arc<str>> {
+        let Some(resolve) = &self.resolver else {
+            return Some(dep.clone());
+        };
+        let last_segment = dep.rsplit([':', '.', '/']).next().unwrap_or(dep);
+        resolve(dep).or_else(|| resolve(last_segment))
+    }
+
+    /// Class a call depends on, if it crosses a class boundary
+    fn call_target(&self, caller: &Arc<str>, called: &Arc<str>) -> Option<Arc<str>> {
+        if let Some(resolve) = &self.resolver {
+            return resolve(called).filter(|owner| owner != caller);
+        }
+
+        // Heuristic: if called method looks like external class
+        if called.contains('.') || called.chars().next().is_some_and(|c| c.is_uppercase()) {
+            Some(called.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Class dependency graph built so far
+    pub fn graph(&self) -> &DependencyGraph {
+        &self.graph
+    }
+
     /// Get coupling metrics for a class
     pub fn get_coupling(&self, class_name: &Arc<str>) -> Option<crate::graph::CouplingMetrics> {
         self.graph.coupling_metrics(class_name)
     }
 
+    /// Classes depending on a class
+    pub fn dependents(&self, class_name: &Arc<str>) -> Vec<Arc<str>> {
+        self.graph.dependents(class_name)
+    }
+
+    /// Classes a class depends on
+    pub fn dependencies(&self, class_name: &Arc<str>) -> Vec<Arc<str>> {
+        self.graph.dependencies(class_name)
+    }
+
+    /// Distinct third-party packages a class imports
+    pub fn third_party_dependencies(&self, class_name: &str) -> Vec<Arc<str>> {
+        self.external
+            .get(class_name)
+            .map(|packages| packages.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Classes importing more than `limit` third-party packages, worst first
+    pub fn excessive_third_party(&self, limit: usize) -> Vec<(Arc<str>, usize)> {
+        let mut offenders: Vec<(Arc<str>, usize)> = self
+            .external
+            .iter()
+            .filter(|(_, packages)| packages.len() > limit)
+            .map(|(class, packages)| (class.clone(), packages.len()))
+            .collect();
+        offenders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        offenders
+    }
+
+    /// Distinct classes depending on `class` and depended on by it, once the graph is built
+    pub fn class_coupling(&self, class: &Arc<str>) -> Option<ClassCoupling> {
+        if !self.graph.contains(class) {
+            return None;
+        }
+        let others = |names: Vec<Arc<str>>| names.iter().filter(|n| *n != class).count();
+        let afferent = others(self.graph.dependents(class));
+        let efferent = others(self.graph.dependencies(class));
+        let instability = if afferent + efferent > 0 {
+            efferent as f64 / (afferent + efferent) as f64
+        } else {
+            0.0
+        };
+        Some(ClassCoupling { afferent, efferent, instability })
+    }
+
+    /// Classes that many dependency paths flow through, most central first
+    ///
+    /// Changing one of these ripples across the graph even when the class itself is small,
+    /// so they are risky to refactor regardless of their size.
+    pub fn choke_points(&self, min_betweenness: f64) -> Vec<Centrality> {
+        self.graph
+            .centrality()
+            .into_iter()
+            .filter(|c| c.betweenness > 0.0 && c.betweenness >= min_betweenness)
+            .collect()
+    }
+
     /// Find tightly coupled classes (circular dependencies)
     pub fn find_tight_coupling(&self) -> Vec<Vec<Arc<str>>> {
         self.graph.find_cycles()
@@ -64,6 +216,7 @@ impl CouplingAnalyzer {
             n_cycles: cycles.len(),
             cyclomatic_quality,
             maintainability_index: (1.0 - density) * cyclomatic_quality,
+            packages: Vec::new(),
         }
     }
 }
@@ -81,5 +234,23 @@ pub struct ArchitectureMetrics {
     pub n_cycles: usize,
     pub cyclomatic_quality: f64,
     pub maintainability_index: f64,
+    /// Per-package coupling and abstractness, empty unless added with [`Self::with_packages`]
+    pub packages: Vec<PackageCoupling>,
+}
+
+impl ArchitectureMetrics {
+    /// Report the packages of `analysis` alongside the class-level metrics
+    pub fn with_packages(mut self, analysis: &PackageAnalysis) -> Self {
+        self.packages = analysis.packages.clone();
+        self
+    }
+
+    /// Average distance from the main sequence over all packages
+    pub fn mean_distance(&self) -> Option<f64> {
+        if self.packages.is_empty() {
+            return None;
+        }
+        Some(self.packages.iter().map(|p| p.distance).sum::<f64>() / self.packages.len() as f64)
+    }
 }
 
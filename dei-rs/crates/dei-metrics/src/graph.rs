@@ -2,15 +2,19 @@
 //! 
 //! Extension beyond the C# version - provides graph-based insights
 
+use dei_core::metrics::LineSpan;
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 /// Represents a dependency graph between classes/methods
+///
+/// Repeated dependencies of one kind between the same two nodes share a single edge,
+/// whose weight counts them
 #[derive(Debug, Clone)]
 pub struct DependencyGraph {
-    graph: DiGraph<Arc<str>, EdgeKind>,
+    graph: DiGraph<Arc<str>, Edge>,
     node_map: HashMap<Arc<str>, NodeIndex>,
 }
 
@@ -22,6 +26,26 @@ pub enum EdgeKind {
     Implements,
 }
 
+/// One kind of dependency between two nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub kind: EdgeKind,
+    /// Times the dependency was added: for `Calls`, one per callee per calling method; for
+    /// `Uses`, one per reference
+    pub weight: usize,
+    /// Distinct places the dependency comes from, in the order they were added
+    pub sites: Vec<EdgeSite>,
+}
+
+/// Where in the source a dependency comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeSite {
+    pub file: Arc<str>,
+    /// Method making the call, `None` for class-level references such as imports
+    pub method: Option<Arc<str>>,
+    pub span: LineSpan,
+}
+
 impl DependencyGraph {
     pub fn new() -> Self {
         Self {
@@ -41,11 +65,45 @@ impl DependencyGraph {
         idx
     }
 
-    /// Add an edge between nodes
+    /// Add an edge between nodes, or add one to the weight of the existing edge of that kind
     pub fn add_edge(&mut self, from: Arc<str>, to: Arc<str>, kind: EdgeKind) {
+        self.edge_mut(from, to, kind).weight += 1;
+    }
+
+    /// Add an edge like [`Self::add_edge`], recording where it comes from
+    pub fn add_edge_at(&mut self, from: Arc<str>, to: Arc<str>, kind: EdgeKind, site: EdgeSite) {
+        let edge = self.edge_mut(from, to, kind);
+        edge.weight += 1;
+        if !edge.sites.contains(&site) {
+            edge.sites.push(site);
+        }
+    }
+
+    fn edge_mut(&mut self, from: Arc<str>, to: Arc<str>, kind: EdgeKind) -> &mut Edge {
         let from_idx = self.add_node(from);
         let to_idx = self.add_node(to);
-        self.graph.add_edge(from_idx, to_idx, kind);
+        let existing = self
+            .graph
+            .edges_connecting(from_idx, to_idx)
+            .find(|e| e.weight().kind == kind)
+            .map(|e| e.id());
+        let id = existing.unwrap_or_else(|| {
+            self.graph.add_edge(from_idx, to_idx, Edge { kind, weight: 0, sites: Vec::new() })
+        });
+        &mut self.graph[id]
+    }
+
+    /// Every kind of dependency `from` has on `to`
+    pub fn edges_between(&self, from: &str, to: &str) -> Vec<&Edge> {
+        let (Some(&from_idx), Some(&to_idx)) = (self.node_map.get(from), self.node_map.get(to)) else {
+            return Vec::new();
+        };
+        self.graph.edges_connecting(from_idx, to_idx).map(|e| e.weight()).collect()
+    }
+
+    /// Total weight of the dependencies `from` has on `to`, 0 if there are none
+    pub fn weight(&self, from: &str, to: &str) -> usize {
+        self.edges_between(from, to).iter().map(|e| e.weight).sum()
     }
 
     /// Calculate coupling metrics
@@ -73,6 +131,42 @@ impl DependencyGraph {
         })
     }
 
+    pub fn contains(&self, node: &str) -> bool {
+        self.node_map.contains_key(node)
+    }
+
+    /// Distinct nodes depending on `node` (fan-in), sorted
+    pub fn dependents(&self, node: &Arc<str>) -> Vec<Arc<str>> {
+        self.neighbors(node, petgraph::Direction::Incoming)
+    }
+
+    /// Distinct nodes `node` depends on (fan-out), sorted
+    pub fn dependencies(&self, node: &Arc<str>) -> Vec<Arc<str>> {
+        self.neighbors(node, petgraph::Direction::Outgoing)
+    }
+
+    fn neighbors(&self, node: &Arc<str>, direction: petgraph::Direction) -> Vec<Arc<str>> {
+        let Some(&idx) = self.node_map.get(node) else {
+            return Vec::new();
+        };
+        let mut names: Vec<Arc<str>> = self
+            .graph
+            .neighbors_directed(idx, direction)
+            .map(|n| self.graph[n].clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Every edge as `(from, to, kind)`
+    pub fn edges(&self) -> impl Iterator<Item = (&Arc<str>, &Arc<str>, EdgeKind)> + '_ {
+        self.graph.edge_indices().filter_map(|e| {
+            let (from, to) = self.graph.edge_endpoints(e)?;
+            Some((&self.graph[from], &self.graph[to], self.graph[e].kind))
+        })
+    }
+
     /// Find strongly connected components (circular dependencies)
     pub fn find_cycles(&self) -> Vec<Vec<Arc<str>>> {
         let sccs = petgraph::algo::kosaraju_scc(&self.graph);
@@ -87,6 +181,96 @@ impl DependencyGraph {
             .collect()
     }
 
+    /// Degree and betweenness centrality of every node, most central first
+    ///
+    /// Betweenness is Brandes' count of shortest dependency paths passing through a node,
+    /// normalized by the `(n - 1)(n - 2)` ordered pairs it could sit between; parallel
+    /// edges count once.
+    pub fn centrality(&self) -> Vec<Centrality> {
+        let n = self.graph.node_count();
+        let successors: Vec<Vec<usize>> = self
+            .graph
+            .node_indices()
+            .map(|idx| {
+                let mut next: Vec<usize> = self
+                    .graph
+                    .neighbors(idx)
+                    .map(|n| n.index())
+                    .filter(|&n| n != idx.index())
+                    .collect();
+                next.sort_unstable();
+                next.dedup();
+                next
+            })
+            .collect();
+        let mut fan_in = vec![0usize; n];
+        for next in &successors {
+            for &w in next {
+                fan_in[w] += 1;
+            }
+        }
+
+        let mut betweenness = vec![0.0; n];
+        for source in 0..n {
+            let mut order = Vec::with_capacity(n);
+            let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+            let mut paths = vec![0.0; n];
+            let mut distance: Vec<Option<usize>> = vec![None; n];
+            paths[source] = 1.0;
+            distance[source] = Some(0);
+
+            let mut queue = VecDeque::from([source]);
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+                let next_distance = distance[v].map(|d| d + 1);
+                for &w in &successors[v] {
+                    if distance[w].is_none() {
+                        distance[w] = next_distance;
+                        queue.push_back(w);
+                    }
+                    if distance[w] == next_distance {
+                        paths[w] += paths[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            let mut dependency = vec![0.0; n];
+            for &w in order.iter().rev() {
+                for &v in &predecessors[w] {
+                    dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+                }
+                if w != source {
+                    betweenness[w] += dependency[w];
+                }
+            }
+        }
+
+        let pairs = if n > 2 { ((n - 1) * (n - 2)) as f64 } else { 1.0 };
+        let mut centrality: Vec<Centrality> = self
+            .graph
+            .node_indices()
+            .map(|idx| {
+                let i = idx.index();
+                let fan_out = successors[i].len();
+                Centrality {
+                    node: self.graph[idx].clone(),
+                    fan_in: fan_in[i],
+                    fan_out,
+                    degree: if n > 1 { (fan_in[i] + fan_out) as f64 / (n - 1) as f64 } else { 0.0 },
+                    betweenness: betweenness[i] / pairs,
+                }
+            })
+            .collect();
+        centrality.sort_by(|a, b| {
+            b.betweenness
+                .total_cmp(&a.betweenness)
+                .then_with(|| b.degree.total_cmp(&a.degree))
+                .then_with(|| a.node.cmp(&b.node))
+        });
+        centrality
+    }
+
     /// Calculate graph density
     pub fn density(&self) -> f64 {
         let n = self.graph.node_count();
@@ -114,3 +298,17 @@ pub struct CouplingMetrics {
     pub instability: f64, // Efferent / (Afferent + Efferent)
 }
 
+
+/// How central a node is to the graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Centrality {
+    pub node: Arc<str>,
+    /// Distinct nodes depending on this one
+    pub fan_in: usize,
+    /// Distinct nodes this one depends on
+    pub fan_out: usize,
+    /// (fan-in + fan-out) / (n - 1)
+    pub degree: f64,
+    /// Share of shortest paths between other nodes that pass through this one, 0 to 1
+    pub betweenness: f64,
+}
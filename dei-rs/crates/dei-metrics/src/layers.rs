@@ -0,0 +1,111 @@
+//! Layering rules from `dei.toml`
+//!
+//! ```toml
+//! [architecture]
+//! layers = ["ui", "service", "data"]   # ui -> service -> data, highest first
+//! ```
+//!
+//! A class belongs to the layer its file was assigned, by a `[[metadata]]` path rule, or
+//! else to the first layer naming a directory on its path (relative to the analyzed root)
+//! or a segment of its namespace; `*` and `?` wildcards are allowed. A layer may depend on
+//! the layers below it, never on those above.
+
+use dei_core::{metrics::ClassMetrics, thresholds::wildcard_match};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::coupling::CouplingAnalyzer;
+
+/// Layers from highest to lowest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayerRules {
+    layers: Vec<Arc<str>>,
+    /// Layers assigned to files outright, by path
+    assigned: HashMap<Arc<str>, Arc<str>>,
+}
+
+/// A dependency from a lower layer up into a higher one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayerViolation {
+    pub class: Arc<str>,
+    pub file: Arc<str>,
+    pub layer: Arc<str>,
+    /// Class in the higher layer it depends on
+    pub target: Arc<str>,
+    pub target_layer: Arc<str>,
+}
+
+impl LayerRules {
+    pub fn new(layers: impl IntoIterator<Item = impl Into<Arc<str>>>) -> Self {
+        Self {
+            layers: layers.into_iter().map(Into::into).collect(),
+            assigned: HashMap::new(),
+        }
+    }
+
+    /// Put every class in `file` in `layer`, whatever its path and namespace say
+    pub fn assign(&mut self, file: impl Into<Arc<str>>, layer: impl Into<Arc<str>>) {
+        self.assigned.insert(file.into(), layer.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn layers(&self) -> &[Arc<str>] {
+        &self.layers
+    }
+
+    /// Position of the class's layer, 0 being the highest
+    pub fn layer_of(&self, class: &ClassMetrics, root: &Path) -> Option<usize> {
+        if let Some(layer) = self.assigned.get(&class.file_path) {
+            return self.layers.iter().position(|l| wildcard_match(l, layer));
+        }
+        let file = Path::new(class.file_path.as_ref());
+        let dir = file.parent().unwrap_or(Path::new(""));
+        let dir = dir.strip_prefix(root).unwrap_or(dir);
+        let directories = dir.components().filter_map(|c| c.as_os_str().to_str());
+        let namespace = class
+            .fully_qualified_name
+            .split("::")
+            .flat_map(|segment| segment.split('.'))
+            .filter(|segment| *segment != class.name.as_ref());
+        let segments: Vec<&str> = directories.chain(namespace).collect();
+
+        self.layers
+            .iter()
+            .position(|layer| segments.iter().any(|segment| wildcard_match(layer, segment)))
+    }
+
+    /// Dependencies in the class graph pointing up the layers, by class and target
+    pub fn check(&self, coupling: &CouplingAnalyzer, classes: &[ClassMetrics], root: &Path) -> Vec<LayerViolation> {
+        let mut placed: HashMap<&str, (usize, &ClassMetrics)> = HashMap::new();
+        for class in classes {
+            if let Some(layer) = self.layer_of(class, root) {
+                // Namesakes resolve to the first declaration, like the class graph does
+                placed.entry(class.name.as_ref()).or_insert((layer, class));
+            }
+        }
+
+        let mut violations: Vec<LayerViolation> = coupling
+            .graph()
+            .edges()
+            .filter_map(|(from, to, _)| {
+                let &(layer, class) = placed.get(from.as_ref())?;
+                let &(target_layer, _) = placed.get(to.as_ref())?;
+                (target_layer < layer).then(|| LayerViolation {
+                    class: class.name.clone(),
+                    file: class.file_path.clone(),
+                    layer: self.layers[layer].clone(),
+                    target: to.clone(),
+                    target_layer: self.layers[target_layer].clone(),
+                })
+            })
+            .collect();
+        violations.sort_by(|a, b| (&a.class, &a.target).cmp(&(&b.class, &b.target)));
+        violations.dedup();
+        violations
+    }
+}
@@ -1,10 +1,22 @@
 //! Advanced metrics beyond basic complexity
 //! 
-//! Includes graph-based analysis for coupling detection
+//! Includes graph-based analysis for coupling detection (per class and per package),
+//! layering rules, LCOM4 cohesion, package manifest classification, and Cargo
+//! workspace dependency direction checks
 
+pub mod cohesion;
 pub mod coupling;
 pub mod graph;
+pub mod layers;
+pub mod manifest;
+pub mod packages;
+pub mod workspace;
 
-pub use coupling::CouplingAnalyzer;
-pub use graph::DependencyGraph;
+pub use cohesion::{Cohesion, CohesionAnalyzer};
+pub use coupling::{ArchitectureMetrics, CouplingAnalyzer};
+pub use graph::{Centrality, DependencyGraph, Edge, EdgeSite};
+pub use layers::{LayerRules, LayerViolation};
+pub use manifest::{DependencyOrigin, PackageManifests};
+pub use packages::{PackageAnalysis, PackageCoupling, PackageGrouping};
+pub use workspace::{CargoWorkspace, DirectionViolation, DirectionViolationKind};
 
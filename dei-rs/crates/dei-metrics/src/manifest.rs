@@ -0,0 +1,211 @@
+//! Package manifests (npm, NuGet, Maven, Cargo) for telling third-party
+//! imports apart from the project's own code
+
+use dei_core::error::Result;
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::workspace::{CargoWorkspace, CrateManifest};
+
+static PACKAGE_REFERENCE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<PackageReference\s+Include\s*=\s*"([^"]+)""#).unwrap()
+});
+static PACKAGES_CONFIG_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<package\s+id\s*=\s*"([^"]+)""#).unwrap());
+static MAVEN_DEPENDENCY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<dependency>.*?<groupId>\s*([^<\s]+)\s*</groupId>.*?</dependency>").unwrap()
+});
+static MAVEN_NESTED_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<(parent|dependencies|dependencyManagement|build|plugins)>.*?</(parent|dependencies|dependencyManagement|build|plugins)>").unwrap()
+});
+static MAVEN_GROUP_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<groupId>\s*([^<\s]+)\s*</groupId>").unwrap());
+
+/// Built-in modules of Node.js, imported without a manifest entry
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "buffer", "child_process", "crypto", "events", "fs", "http", "https", "net",
+    "os", "path", "process", "stream", "url", "util", "zlib",
+];
+
+/// Where an import points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyOrigin {
+    /// Part of the analyzed project
+    Internal,
+    /// A package declared in a manifest
+    ThirdParty(Arc<str>),
+    /// Language standard library
+    Standard,
+}
+
+/// Declared third-party packages found under a project root
+#[derive(Debug, Clone, Default)]
+pub struct PackageManifests {
+    npm: BTreeSet<Arc<str>>,
+    nuget: BTreeSet<Arc<str>>,
+    maven_groups: BTreeSet<Arc<str>>,
+    maven_internal: BTreeSet<Arc<str>>,
+    cargo: BTreeSet<Arc<str>>,
+    /// Library names of every crate found, which stay internal whichever manifest names them
+    cargo_internal: BTreeSet<Arc<str>>,
+}
+
+impl PackageManifests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find and read every supported manifest below `root` (respecting .gitignore)
+    pub fn discover(root: &Path) -> Result<Self> {
+        let mut manifests = Self::new();
+
+        for entry in WalkBuilder::new(root).build().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy();
+
+            match name.as_ref() {
+                "package.json" => manifests.add_package_json(&fs::read_to_string(path)?),
+                "packages.config" => manifests.add_nuget_xml(&fs::read_to_string(path)?),
+                "pom.xml" => manifests.add_pom(&fs::read_to_string(path)?),
+                "Cargo.toml" => {
+                    // Each crate of a workspace is walked too, so only packages are read here;
+                    // unreadable manifests just contribute nothing
+                    if let Ok(member) = CrateManifest::load(path) {
+                        manifests.add_cargo(&CargoWorkspace { members: vec![member] });
+                    }
+                }
+                n if n.ends_with(".csproj") => manifests.add_nuget_xml(&fs::read_to_string(path)?),
+                _ => {}
+            }
+        }
+
+        Ok(manifests)
+    }
+
+    pub fn add_package_json(&mut self, content: &str) {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+            return;
+        };
+        for section in ["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"] {
+            if let Some(deps) = json.get(section).and_then(|d| d.as_object()) {
+                self.npm.extend(deps.keys().map(|k| Arc::from(k.as_str())));
+            }
+        }
+    }
+
+    /// `.csproj` `<PackageReference>` or legacy `packages.config`
+    pub fn add_nuget_xml(&mut self, content: &str) {
+        for re in [&*PACKAGE_REFERENCE_RE, &*PACKAGES_CONFIG_RE] {
+            self.nuget
+                .extend(re.captures_iter(content).map(|c| Arc::from(&c[1])));
+        }
+    }
+
+    pub fn add_pom(&mut self, content: &str) {
+        self.maven_groups.extend(
+            MAVEN_DEPENDENCY_RE
+                .captures_iter(content)
+                .map(|c| Arc::from(&c[1])),
+        );
+        // The first groupId outside nested sections is the project's own
+        let top_level = MAVEN_NESTED_RE.replace_all(content, "");
+        if let Some(own) = MAVEN_GROUP_RE.captures(&top_level) {
+            self.maven_internal.insert(Arc::from(&own[1]));
+        }
+    }
+
+    /// Dependencies of the workspace's crates; the crates themselves, from this workspace or
+    /// any other added, are internal
+    pub fn add_cargo(&mut self, workspace: &CargoWorkspace) {
+        for member in &workspace.members {
+            self.cargo_internal.insert(member.lib.clone());
+            self.cargo
+                .extend(member.dependencies.iter().chain(member.dev_dependencies.iter()).cloned());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.npm.is_empty() && self.nuget.is_empty() && self.maven_groups.is_empty() && self.cargo.is_empty()
+    }
+
+    /// Classify an import path as written in source
+    pub fn classify(&self, import: &str) -> DependencyOrigin {
+        if is_standard(import) {
+            return DependencyOrigin::Standard;
+        }
+
+        // npm: `lodash/fp`, `@scope/pkg/sub`; relative paths are always ours
+        if import.starts_with('.') || import.starts_with('/') {
+            return DependencyOrigin::Internal;
+        }
+        let npm_name = if import.starts_with('@') {
+            import.splitn(3, '/').take(2).collect::<Vec<_>>().join("/")
+        } else {
+            import.split('/').next().unwrap_or(import).to_string()
+        };
+        if let Some(pkg) = self.npm.get(npm_name.as_str()) {
+            return DependencyOrigin::ThirdParty(pkg.clone());
+        }
+
+        // Cargo: `serde::Deserialize`
+        let crate_root = import.trim_start_matches("::").split("::").next().unwrap_or(import);
+        if let Some(pkg) = self.cargo.get(crate_root).filter(|_| !self.cargo_internal.contains(crate_root)) {
+            return DependencyOrigin::ThirdParty(pkg.clone());
+        }
+
+        // NuGet: namespaces start with the package id (`Newtonsoft.Json.Linq`)
+        if let Some(pkg) = longest_dotted_prefix(&self.nuget, import) {
+            return DependencyOrigin::ThirdParty(pkg);
+        }
+
+        // Maven: packages usually start with the groupId, or at least its first two segments
+        if longest_dotted_prefix(&self.maven_internal, import).is_none() {
+            if let Some(group) = longest_dotted_prefix(&self.maven_groups, import) {
+                return DependencyOrigin::ThirdParty(group);
+            }
+            if let Some(group) = self
+                .maven_groups
+                .iter()
+                .find(|g| dotted_prefix(&two_segments(g), import))
+            {
+                return DependencyOrigin::ThirdParty(group.clone());
+            }
+        }
+
+        DependencyOrigin::Internal
+    }
+}
+
+fn is_standard(import: &str) -> bool {
+    let root = import.trim_start_matches("::");
+    let first_path = root.split("::").next().unwrap_or(root);
+    let first_dotted = root.split('.').next().unwrap_or(root);
+
+    matches!(first_path, "std" | "core" | "alloc")
+        || matches!(first_dotted, "System" | "java" | "javax")
+        || root.starts_with("node:")
+        || NODE_BUILTINS.contains(&root.split('/').next().unwrap_or(root))
+}
+
+/// `prefix` matches `import` on a `.` boundary
+fn dotted_prefix(prefix: &str, import: &str) -> bool {
+    import == prefix
+        || (import.starts_with(prefix) && import.as_bytes().get(prefix.len()) == Some(&b'.'))
+}
+
+fn longest_dotted_prefix(candidates: &BTreeSet<Arc<str>>, import: &str) -> Option<Arc<str>> {
+    candidates
+        .iter()
+        .filter(|c| dotted_prefix(c, import))
+        .max_by_key(|c| c.len())
+        .cloned()
+}
+
+fn two_segments(group: &str) -> String {
+    group.splitn(3, '.').take(2).collect::<Vec<_>>().join(".")
+}
@@ -0,0 +1,157 @@
+//! Coupling aggregated from classes to packages
+//!
+//! A package is a directory or a namespace. Afferent and efferent coupling follow Robert
+//! Martin's definitions: the classes outside a package that depend on classes inside it,
+//! and the classes inside it that depend on classes outside it. Abstractness is the share
+//! of a package's classes that are interfaces, traits or abstract classes, and distance
+//! from the main sequence, `|A + I - 1|`, is how far a package strays from balancing the
+//! two: 0 for stable abstractions and unstable concrete code, 1 for a concrete package
+//! everything depends on or an abstract one nothing uses.
+
+use dei_core::metrics::ClassMetrics;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::coupling::CouplingAnalyzer;
+use crate::graph::{DependencyGraph, EdgeKind};
+
+/// Package of classes without a namespace
+pub const GLOBAL_NAMESPACE: &str = "(global)";
+
+/// How classes are grouped into packages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PackageGrouping {
+    /// Directory of the declaring file, relative to the analyzed root
+    #[default]
+    Directory,
+    /// Qualified name up to the class name (`billing::invoices`, `Shop.Orders`)
+    Namespace,
+}
+
+impl PackageGrouping {
+    pub fn package_of(self, class: &ClassMetrics, root: &Path) -> Arc<str> {
+        match self {
+            PackageGrouping::Directory => {
+                let file = Path::new(class.file_path.as_ref());
+                let dir = file.parent().unwrap_or(Path::new(""));
+                let dir = dir.strip_prefix(root).unwrap_or(dir).to_string_lossy().replace('\\', "/");
+                if dir.is_empty() { ".".into() } else { dir.into() }
+            }
+            PackageGrouping::Namespace => {
+                let name = class.fully_qualified_name.as_ref();
+                let namespace = name
+                    .rsplit_once("::")
+                    .or_else(|| name.rsplit_once('.'))
+                    .map(|(namespace, _)| namespace)
+                    .filter(|namespace| !namespace.is_empty());
+                namespace.unwrap_or(GLOBAL_NAMESPACE).into()
+            }
+        }
+    }
+}
+
+/// Coupling of one package
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageCoupling {
+    pub name: Arc<str>,
+    pub classes: usize,
+    /// Classes elsewhere depending on this package
+    pub afferent: usize,
+    /// Classes here depending on other packages
+    pub efferent: usize,
+    /// Efferent / (Afferent + Efferent), 0 for an isolated package
+    pub instability: f64,
+    /// Abstract classes / classes
+    pub abstractness: f64,
+    /// Distance from the main sequence, |Abstractness + Instability - 1|
+    pub distance: f64,
+}
+
+/// Package-level view of a class dependency graph
+#[derive(Debug, Clone, Default)]
+pub struct PackageAnalysis {
+    /// Every package, by name
+    pub packages: Vec<PackageCoupling>,
+    /// Dependencies between packages; one edge per depending pair
+    pub graph: DependencyGraph,
+}
+
+impl PackageAnalysis {
+    /// Groups of packages depending on each other in a cycle
+    pub fn cycles(&self) -> Vec<Vec<Arc<str>>> {
+        let mut cycles: Vec<Vec<Arc<str>>> = self
+            .graph
+            .find_cycles()
+            .into_iter()
+            .map(|mut cycle| {
+                cycle.sort();
+                cycle
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+}
+
+impl CouplingAnalyzer {
+    /// Aggregate the class graph built from `classes` into packages
+    ///
+    /// Dependencies on names that aren't among `classes` (unresolved or external) are left
+    /// out, as are those between classes of the same package.
+    pub fn packages(&self, classes: &[ClassMetrics], grouping: PackageGrouping, root: &Path) -> PackageAnalysis {
+        let mut package_of: HashMap<&str, Arc<str>> = HashMap::new();
+        let mut members: BTreeMap<Arc<str>, (usize, usize)> = BTreeMap::new();
+        for class in classes {
+            let package = grouping.package_of(class, root);
+            // Namesakes resolve to the first declaration, like the class graph does
+            if !package_of.contains_key(class.name.as_ref()) {
+                package_of.insert(class.name.as_ref(), package.clone());
+            }
+            let (count, abstract_count) = members.entry(package).or_default();
+            *count += 1;
+            *abstract_count += usize::from(class.is_abstract);
+        }
+
+        let mut afferent: HashMap<Arc<str>, BTreeSet<&Arc<str>>> = HashMap::new();
+        let mut efferent: HashMap<Arc<str>, BTreeSet<&Arc<str>>> = HashMap::new();
+        let mut links: BTreeSet<(Arc<str>, Arc<str>)> = BTreeSet::new();
+        for (from, to, _) in self.graph().edges() {
+            let (Some(source), Some(target)) = (package_of.get(from.as_ref()), package_of.get(to.as_ref())) else {
+                continue;
+            };
+            if source == target {
+                continue;
+            }
+            efferent.entry(source.clone()).or_default().insert(from);
+            afferent.entry(target.clone()).or_default().insert(from);
+            links.insert((source.clone(), target.clone()));
+        }
+
+        let mut graph = DependencyGraph::new();
+        for package in members.keys() {
+            graph.add_node(package.clone());
+        }
+        for (source, target) in links {
+            graph.add_edge(source, target, EdgeKind::Uses);
+        }
+
+        let packages = members
+            .into_iter()
+            .map(|(name, (classes, abstract_classes))| {
+                let afferent = afferent.get(&name).map_or(0, BTreeSet::len);
+                let efferent = efferent.get(&name).map_or(0, BTreeSet::len);
+                let instability = if afferent + efferent > 0 {
+                    efferent as f64 / (afferent + efferent) as f64
+                } else {
+                    0.0
+                };
+                let abstractness = abstract_classes as f64 / classes as f64;
+                let distance = (abstractness + instability - 1.0).abs();
+                PackageCoupling { name, classes, afferent, efferent, instability, abstractness, distance }
+            })
+            .collect();
+
+        PackageAnalysis { packages, graph }
+    }
+}
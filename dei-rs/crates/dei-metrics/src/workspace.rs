@@ -0,0 +1,345 @@
+//! Cargo workspace manifests and dependency direction enforcement
+//!
+//! Compares what each crate declares in `Cargo.toml` against what its sources
+//! actually `use`, and checks optional intra-crate module layering declared as
+//!
+//! ```toml
+//! [package.metadata.dei]
+//! layers = ["models", "services", "api"]   # lowest first
+//! ```
+//!
+//! Sources under `src/` may only use `[dependencies]`, except inside an item marked
+//! `#[cfg(test)]` (typically `mod tests { .. }`), which may use `[dev-dependencies]` as
+//! `tests/`, `benches/` and `examples/` can. The marker is matched line by line, so a test
+//! module declared as `#[cfg(test)] mod tests;` in a file of its own still counts as
+//! production code there.
+
+use dei_core::{error::Result, Error};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use walkdir::WalkDir;
+
+/// Path roots that never need a manifest entry
+const BUILTIN_ROOTS: &[&str] = &[
+    "std", "core", "alloc", "proc_macro", "test", "crate", "self", "super", "Self",
+];
+
+static USE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:use\s+(?:::)?|extern\s+crate\s+)([A-Za-z_][A-Za-z0-9_]*)")
+        .unwrap()
+});
+static MOD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+});
+/// `#[cfg(test)]` or `#[cfg(all(test, ..))]`, but not `#[cfg(not(test))]`
+static CFG_TEST_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*#\[cfg\((?:test|all\([^)]*\btest\b)").unwrap());
+static CRATE_PATH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bcrate::([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+
+/// A single crate from a workspace
+#[derive(Debug, Clone)]
+pub struct CrateManifest {
+    pub name: Arc<str>,
+    /// `[lib] name`, or else the package name with `-` → `_`
+    pub lib: Arc<str>,
+    pub root: PathBuf,
+    /// Library names (`-` → `_`) usable from `src/`
+    pub dependencies: HashSet<Arc<str>>,
+    /// Additionally usable from `tests/`, `benches/` and `examples/`
+    pub dev_dependencies: HashSet<Arc<str>>,
+    /// Top-level modules from lowest to highest layer
+    pub layers: Vec<Arc<str>>,
+}
+
+impl CrateManifest {
+    pub fn load(manifest_path: &Path) -> Result<Self> {
+        let doc = read_toml(manifest_path)?;
+        let name = doc
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| Error::Config(format!("{}: missing package.name", manifest_path.display())))?;
+
+        let layers = doc
+            .get("package")
+            .and_then(|p| p.get("metadata"))
+            .and_then(|m| m.get("dei"))
+            .and_then(|d| d.get("layers"))
+            .and_then(|l| l.as_array())
+            .map(|l| l.iter().filter_map(|v| v.as_str()).map(Arc::from).collect())
+            .unwrap_or_default();
+
+        let lib = doc
+            .get("lib")
+            .and_then(|l| l.get("name"))
+            .and_then(|n| n.as_str())
+            .map_or_else(|| name.replace('-', "_"), str::to_string);
+
+        Ok(Self {
+            name: name.into(),
+            lib: lib.into(),
+            root: manifest_path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            dependencies: dependency_names(&doc, &["dependencies", "build-dependencies"]),
+            dev_dependencies: dependency_names(&doc, &["dev-dependencies"]),
+            layers,
+        })
+    }
+
+    /// Name other crates use to refer to this one
+    pub fn lib_name(&self) -> &str {
+        &self.lib
+    }
+
+    fn allows(&self, root: &str, in_dev_target: bool) -> bool {
+        BUILTIN_ROOTS.contains(&root)
+            || root == self.lib_name()
+            || self.dependencies.contains(root)
+            || (in_dev_target && self.dev_dependencies.contains(root))
+    }
+
+    fn layer_of(&self, module: &str) -> Option<usize> {
+        self.layers.iter().position(|l| l.as_ref() == module)
+    }
+}
+
+/// Kind of dependency-direction problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DirectionViolationKind {
+    /// `use` of a crate that is not declared in Cargo.toml
+    UndeclaredCrate,
+    /// A lower layer module reaching up into a higher one
+    LayerViolation,
+}
+
+/// A `use` that goes against the declared dependency direction
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectionViolation {
+    pub kind: DirectionViolationKind,
+    pub crate_name: Arc<str>,
+    pub file: PathBuf,
+    pub line: usize,
+    /// The crate or module being reached for
+    pub target: Arc<str>,
+}
+
+/// Workspace members and their manifests
+#[derive(Debug, Clone)]
+pub struct CargoWorkspace {
+    pub members: Vec<CrateManifest>,
+}
+
+impl CargoWorkspace {
+    /// Load from a directory holding a `Cargo.toml` (workspace root or single crate)
+    pub fn load(root: &Path) -> Result<Self> {
+        let manifest_path = root.join("Cargo.toml");
+        if !manifest_path.is_file() {
+            return Err(Error::PathNotFound(manifest_path));
+        }
+
+        let doc = read_toml(&manifest_path)?;
+        let mut members = Vec::new();
+
+        if doc.get("package").is_some() {
+            members.push(CrateManifest::load(&manifest_path)?);
+        }
+
+        let patterns = doc
+            .get("workspace")
+            .and_then(|w| w.get("members"))
+            .and_then(|m| m.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for pattern in patterns.iter().filter_map(|p| p.as_str()) {
+            for dir in expand_member(root, pattern) {
+                let member_manifest = dir.join("Cargo.toml");
+                if member_manifest.is_file() {
+                    members.push(CrateManifest::load(&member_manifest)?);
+                }
+            }
+        }
+
+        Ok(Self { members })
+    }
+
+    /// Names of all workspace crates as used in paths
+    pub fn lib_names(&self) -> HashSet<String> {
+        self.members.iter().map(|m| m.lib_name().to_string()).collect()
+    }
+
+    /// Scan every member's sources for `use`s against the declared direction
+    pub fn check_direction(&self) -> Vec<DirectionViolation> {
+        let mut violations: Vec<DirectionViolation> = self
+            .members
+            .iter()
+            .flat_map(|member| self.check_member(member))
+            .collect();
+        violations.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+        violations
+    }
+
+    fn check_member(&self, member: &CrateManifest) -> Vec<DirectionViolation> {
+        let mut violations = Vec::new();
+        let nested_roots: Vec<&Path> = self
+            .members
+            .iter()
+            .filter(|m| m.root != member.root && m.root.starts_with(&member.root))
+            .map(|m| m.root.as_path())
+            .collect();
+
+        for entry in WalkDir::new(&member.root)
+            .into_iter()
+            .filter_entry(|e| {
+                let name = e.file_name().to_string_lossy();
+                e.depth() == 0 || (name != "target" && !name.starts_with('.') && !nested_roots.contains(&e.path()))
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        {
+            let Ok(source) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let relative = entry.path().strip_prefix(&member.root).unwrap_or(entry.path());
+            let in_dev_target = !relative.starts_with("src");
+            let own_layer = layer_module(relative).and_then(|m| member.layer_of(&m));
+
+            violations.extend(check_file(member, entry.path(), &source, in_dev_target, own_layer));
+        }
+
+        violations
+    }
+}
+
+fn check_file(
+    member: &CrateManifest,
+    path: &Path,
+    source: &str,
+    in_dev_target: bool,
+    own_layer: Option<usize>,
+) -> Vec<DirectionViolation> {
+    // Modules declared in this file shadow crate names in 2018+ paths
+    let local_mods: HashSet<&str> = source
+        .lines()
+        .filter_map(|l| MOD_RE.captures(l))
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        .collect();
+
+    let mut violations = Vec::new();
+    // Brace depth inside the `#[cfg(test)]` item being read, and whether one is about to start
+    let mut test_item: Option<i64> = None;
+    let mut cfg_test = false;
+    let violation = |kind, line: usize, target: &str| DirectionViolation {
+        kind,
+        crate_name: member.name.clone(),
+        file: path.to_path_buf(),
+        line: line + 1,
+        target: target.into(),
+    };
+
+    for (idx, line) in source.lines().enumerate() {
+        let in_test = in_dev_target || cfg_test || test_item.is_some();
+
+        if let Some(root) = USE_RE.captures(line).and_then(|c| c.get(1)).map(|m| m.as_str()) {
+            // Only lowercase roots can be crates; `use Enum::*` and friends are local
+            let is_crate_like = root.chars().next().is_some_and(|c| c.is_ascii_lowercase());
+            if is_crate_like && !local_mods.contains(root) && !member.allows(root, in_test) {
+                violations.push(violation(DirectionViolationKind::UndeclaredCrate, idx, root));
+            }
+        }
+
+        if let Some(own) = own_layer {
+            for target in CRATE_PATH_RE.captures_iter(line).filter_map(|c| c.get(1)) {
+                if member.layer_of(target.as_str()).is_some_and(|layer| layer > own) {
+                    violations.push(violation(DirectionViolationKind::LayerViolation, idx, target.as_str()));
+                }
+            }
+        }
+
+        let trimmed = line.trim_start();
+        test_item = match test_item {
+            Some(depth) => Some(depth + brace_balance(line)).filter(|&d| d > 0),
+            // The item the attribute is on; a block item runs until its braces balance
+            None if cfg_test && !trimmed.is_empty() && !trimmed.starts_with("#[") && !trimmed.starts_with("//") => {
+                cfg_test = false;
+                Some(brace_balance(line)).filter(|&d| d > 0)
+            }
+            None => {
+                cfg_test |= CFG_TEST_RE.is_match(line);
+                None
+            }
+        };
+    }
+
+    violations
+}
+
+/// Opening minus closing braces on a line, before any trailing `//` comment
+fn brace_balance(line: &str) -> i64 {
+    let code = line.split("//").next().unwrap_or(line);
+    code.chars().fold(0, |depth, c| match c {
+        '{' => depth + 1,
+        '}' => depth - 1,
+        _ => depth,
+    })
+}
+
+/// Top-level module a source file belongs to (`src/services/billing.rs` → `services`)
+fn layer_module(relative: &Path) -> Option<String> {
+    let mut parts = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string());
+    if parts.next()? != "src" {
+        return None;
+    }
+    let first = parts.next()?;
+    Some(first.trim_end_matches(".rs").to_string())
+}
+
+fn read_toml(path: &Path) -> Result<toml::Value> {
+    let text = fs::read_to_string(path)?;
+    toml::from_str(&text).map_err(|e| Error::Parse {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+fn dependency_names(doc: &toml::Value, tables: &[&str]) -> HashSet<Arc<str>> {
+    let mut names = HashSet::new();
+    let sections = tables.iter().filter_map(|t| doc.get(*t)).chain(
+        // [target.'cfg(..)'.dependencies]
+        doc.get("target")
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flat_map(|targets| targets.values())
+            .flat_map(|target| tables.iter().filter_map(move |t| target.get(*t))),
+    );
+
+    for section in sections.filter_map(|s| s.as_table()) {
+        // Renamed deps (`alias = { package = "real" }`) are referenced by their key
+        for key in section.keys() {
+            names.insert(Arc::from(key.replace('-', "_")));
+        }
+    }
+    names
+}
+
+/// Expand a `members` entry, supporting a trailing `*` component
+fn expand_member(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    match pattern.strip_suffix("/*") {
+        Some(parent) => fs::read_dir(root.join(parent))
+            .map(|entries| {
+                let mut dirs: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir())
+                    .collect();
+                dirs.sort();
+                dirs
+            })
+            .unwrap_or_default(),
+        None => vec![root.join(pattern)],
+    }
+}
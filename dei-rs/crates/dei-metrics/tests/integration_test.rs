@@ -96,6 +96,12 @@ fn test_coupling_metrics() {
     let metrics = metrics.unwrap();
     assert_eq!(metrics.afferent, 1); // One incoming from C
     assert_eq!(metrics.efferent, 1); // One outgoing to B
+    
+    // Repeated edges name each neighbour once
+    graph.add_edge(c.clone(), a.clone(), EdgeKind::Calls);
+    assert_eq!(graph.dependents(&a), vec![c.clone()]);
+    assert_eq!(graph.dependencies(&a), vec![b.clone()]);
+    assert!(graph.dependents(&"Missing".into()).is_empty());
 }
 
 #[test]
@@ -129,3 +135,561 @@ fn test_complex_dependency_structure() {
     assert!(core_metrics.afferent >= 3);
 }
 
+
+#[test]
+fn test_coupling_analyzer_with_resolver() {
+    use dei_core::{metrics::*, thresholds::*};
+
+    let class = |name: &str, calls: &[&str]| ClassMetrics {
+        name: name.into(),
+        fully_qualified_name: name.into(),
+        file_path: format!("{name}.rs").into(),
+        lines: Lines(10),
+        method_count: MethodCount(1),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(1),
+        methods: Arc::new([MethodMetrics {
+            name: "run".into(),
+            lines: Lines(5),
+            complexity: Complexity(1),
+            parameters: ParamCount(0),
+            params: Arc::new([]),
+            generics: Arc::new([]),
+            nesting_depth: NestingDepth(0),
+            chain_length: ChainLength(0),
+            exit_points: 0,
+            magic_literals: 0,
+            span: LineSpan::default(),
+            called_methods: calls.iter().map(|c| Arc::<str>::from(*c)).collect(),
+            accessed_fields: Arc::new([]),
+            return_type: "()".into(),
+            is_public: true,
+            is_static: false,
+            is_async: false,
+            tokens: Arc::new([]),
+            ast_snippet: None,
+        }]),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    let classes = [class("Orders", &["charge_card", "log"]), class("Billing", &[])];
+
+    // Lowercase calls are invisible to the heuristic but resolvable via symbols
+    let mut analyzer = CouplingAnalyzer::new().with_resolver(|name| match name {
+        "charge_card" => Some("Billing".into()),
+        _ => None,
+    });
+    analyzer.build_graph(&classes);
+
+    let billing = analyzer.get_coupling(&"Billing".into()).unwrap();
+    assert_eq!(billing.afferent, 1);
+    let orders = analyzer.get_coupling(&"Orders".into()).unwrap();
+    assert_eq!(orders.efferent, 1);
+    assert_eq!(analyzer.dependents(&"Billing".into()), vec![Arc::<str>::from("Orders")]);
+    assert_eq!(analyzer.dependencies(&"Orders".into()), vec![Arc::<str>::from("Billing")]);
+
+    // Without a symbol table names resolve against the classes themselves; both declare
+    // `run`, so calling it links nothing
+    let classes = [class("Orders", &["Billing", "run"]), class("Billing", &["run"])];
+    let mut analyzer = CouplingAnalyzer::new().with_class_resolver(&classes);
+    analyzer.build_graph(&classes);
+
+    let billing = analyzer.class_coupling(&"Billing".into()).unwrap();
+    assert_eq!((billing.afferent, billing.efferent, billing.instability), (1, 0, 0.0));
+    assert_eq!(analyzer.class_coupling(&"Orders".into()).unwrap().instability, 1.0);
+    assert!(analyzer.class_coupling(&"Missing".into()).is_none());
+}
+
+#[test]
+fn test_edge_weights_and_sites() {
+    use dei_core::metrics::LineSpan;
+    use dei_metrics::EdgeSite;
+
+    let site = |method: &str, line: usize| EdgeSite {
+        file: "orders.rs".into(),
+        method: Some(method.into()),
+        span: LineSpan::new(line, line + 5),
+    };
+    let mut graph = DependencyGraph::new();
+    graph.add_edge_at("Orders".into(), "Billing".into(), EdgeKind::Calls, site("checkout", 10));
+    graph.add_edge_at("Orders".into(), "Billing".into(), EdgeKind::Calls, site("checkout", 10));
+    graph.add_edge_at("Orders".into(), "Billing".into(), EdgeKind::Calls, site("refund", 20));
+    graph.add_edge("Orders".into(), "Billing".into(), EdgeKind::Uses);
+
+    let edges = graph.edges_between("Orders", "Billing");
+    assert_eq!(edges.len(), 2, "one edge per kind");
+    let calls = edges.iter().find(|e| e.kind == EdgeKind::Calls).unwrap();
+    assert_eq!(calls.weight, 3);
+    let methods: Vec<_> = calls.sites.iter().map(|s| s.method.as_deref().unwrap()).collect();
+    assert_eq!(methods, ["checkout", "refund"]);
+
+    assert_eq!(graph.weight("Orders", "Billing"), 4);
+    assert_eq!(graph.weight("Billing", "Orders"), 0);
+    assert_eq!(graph.coupling_metrics(&"Billing".into()).unwrap().afferent, 2);
+}
+
+#[test]
+fn test_lcom4_cohesion() {
+    use dei_core::{metrics::*, thresholds::*};
+    use dei_metrics::CohesionAnalyzer;
+
+    let method = |name: &str, fields: &[&str], calls: &[&str]| MethodMetrics {
+        name: name.into(),
+        lines: Lines(5),
+        complexity: Complexity(1),
+        parameters: ParamCount(0),
+        params: Arc::new([]),
+        generics: Arc::new([]),
+        nesting_depth: NestingDepth(0),
+        chain_length: ChainLength(0),
+        exit_points: 0,
+        magic_literals: 0,
+        span: LineSpan::default(),
+        called_methods: calls.iter().map(|c| Arc::<str>::from(*c)).collect(),
+        accessed_fields: fields.iter().map(|f| Arc::<str>::from(*f)).collect(),
+        return_type: "()".into(),
+        is_public: true,
+        is_static: false,
+        is_async: false,
+        tokens: Arc::new([]),
+        ast_snippet: None,
+    };
+    let class = |methods: Vec<MethodMetrics>| ClassMetrics {
+        name: "Mixed".into(),
+        fully_qualified_name: "Mixed".into(),
+        file_path: "mixed.rs".into(),
+        lines: Lines(40),
+        method_count: MethodCount(methods.len()),
+        property_count: 0,
+        field_count: 3,
+        complexity: Complexity(methods.len()),
+        methods: methods.into(),
+        dependencies: Arc::new([]),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+
+    // Two islands: {deposit, balance} share `total`; {send, retry} linked by a call
+    let mixed = class(vec![
+        method("deposit", &["total"], &[]),
+        method("balance", &["total"], &[]),
+        method("send", &["socket"], &[]),
+        method("retry", &[], &["send"]),
+        method("helper", &[], &[]),
+    ]);
+    let cohesion = CohesionAnalyzer::lcom4(&mixed).unwrap();
+    assert_eq!(cohesion.lcom4, 2);
+    assert!(cohesion.components.iter().all(|c| c.len() == 2));
+    assert!(!cohesion.is_low(&Thresholds::default()));
+    assert!(cohesion.is_low(&Thresholds { max_lcom4: 1, ..Thresholds::default() }));
+
+    // Linking the islands makes the class cohesive
+    let linked = class(vec![
+        method("deposit", &["total"], &[]),
+        method("balance", &["total"], &["send"]),
+        method("send", &["socket"], &[]),
+    ]);
+    assert_eq!(CohesionAnalyzer::lcom4(&linked).unwrap().lcom4, 1);
+
+    // Nothing to judge without instance state
+    assert!(CohesionAnalyzer::lcom4(&class(vec![method("helper", &[], &[])])).is_none());
+}
+
+#[test]
+fn test_workspace_dependency_direction() {
+    use dei_metrics::{CargoWorkspace, DirectionViolationKind};
+    use std::fs;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let write = |path: &str, content: &str| {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    };
+
+    write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+    write("crates/base/Cargo.toml", r#"
+[package]
+name = "base"
+
+[package.metadata.dei]
+layers = ["models", "services"]
+"#);
+    write("crates/base/src/lib.rs", "pub mod models;\npub mod services;\nuse std::sync::Arc;\n");
+    write("crates/base/src/models.rs", "use crate::services::Billing;\n");
+    write("crates/base/src/services.rs", "use crate::models::Order;\n");
+    write("crates/app/Cargo.toml", r#"
+[package]
+name = "my-app"
+
+[dependencies]
+base = { path = "../base" }
+serde-json = "1"
+
+[dev-dependencies]
+tempfile = "3"
+"#);
+    write("crates/app/src/main.rs", "use base::models;\nuse serde_json::Value;\nuse regex::Regex;\nuse tempfile::tempdir;\n");
+    write("crates/app/tests/it.rs", "use tempfile::tempdir;\nuse my_app::run;\n");
+
+    let workspace = CargoWorkspace::load(root).unwrap();
+    assert_eq!(workspace.members.len(), 2);
+
+    let violations = workspace.check_direction();
+    let found: Vec<(DirectionViolationKind, &str)> = violations
+        .iter()
+        .map(|v| (v.kind, v.target.as_ref()))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (DirectionViolationKind::UndeclaredCrate, "regex"),
+            (DirectionViolationKind::UndeclaredCrate, "tempfile"),
+            (DirectionViolationKind::LayerViolation, "services"),
+        ]
+    );
+}
+
+#[test]
+fn test_workspace_lib_names_and_test_modules() {
+    use dei_metrics::CargoWorkspace;
+    use std::fs;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let write = |path: &str, content: &str| {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    };
+
+    write("Cargo.toml", r#"
+[package]
+name = "dei-ffi"
+
+[lib]
+name = "dei"
+
+[dev-dependencies]
+tempfile = "3"
+"#);
+    write("src/lib.rs", r#"
+pub fn analyze() {}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    fn helper() {
+        let _ = 1;
+    }
+}
+
+use tempfile::NamedTempFile;
+"#);
+    write("tests/ffi_test.rs", "use dei::analyze;\nuse dei_ffi::analyze;\n");
+
+    let workspace = CargoWorkspace::load(root).unwrap();
+    assert_eq!(workspace.members[0].lib_name(), "dei");
+
+    // `dei` is the library; the test module may use dev-dependencies, the code after it may not
+    let violations = workspace.check_direction();
+    let found: Vec<(String, usize, &str)> = violations
+        .iter()
+        .map(|v| (v.file.file_name().unwrap().to_string_lossy().to_string(), v.line, v.target.as_ref()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("lib.rs".to_string(), 13, "tempfile"),
+            ("ffi_test.rs".to_string(), 2, "dei_ffi"),
+        ]
+    );
+}
+
+#[test]
+fn test_manifest_aware_third_party_fan_out() {
+    use dei_core::{metrics::*, thresholds::*};
+    use dei_metrics::{DependencyOrigin, PackageManifests};
+
+    let mut manifests = PackageManifests::new();
+    manifests.add_package_json(r#"{"dependencies": {"lodash": "4", "@scope/ui": "1"}}"#);
+    manifests.add_nuget_xml(r#"<PackageReference Include="Newtonsoft.Json" Version="13.0.1" />"#);
+    manifests.add_pom(
+        "<project><groupId>com.acme</groupId><dependencies><dependency>\
+         <groupId>org.apache.commons</groupId><artifactId>commons-lang3</artifactId>\
+         </dependency></dependencies></project>",
+    );
+
+    assert_eq!(manifests.classify("lodash/fp"), DependencyOrigin::ThirdParty("lodash".into()));
+    assert_eq!(manifests.classify("@scope/ui/button"), DependencyOrigin::ThirdParty("@scope/ui".into()));
+    assert_eq!(manifests.classify("./orders"), DependencyOrigin::Internal);
+    assert_eq!(manifests.classify("fs"), DependencyOrigin::Standard);
+    assert_eq!(
+        manifests.classify("Newtonsoft.Json.Linq"),
+        DependencyOrigin::ThirdParty("Newtonsoft.Json".into())
+    );
+    assert_eq!(manifests.classify("System.Text"), DependencyOrigin::Standard);
+    assert_eq!(
+        manifests.classify("org.apache.commons.lang3.StringUtils"),
+        DependencyOrigin::ThirdParty("org.apache.commons".into())
+    );
+    assert_eq!(manifests.classify("com.acme.billing.Invoice"), DependencyOrigin::Internal);
+
+    let class = |name: &str, deps: &[&str]| ClassMetrics {
+        name: name.into(),
+        fully_qualified_name: name.into(),
+        file_path: format!("{name}.js").into(),
+        lines: Lines(10),
+        method_count: MethodCount(0),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(1),
+        methods: Arc::new([]),
+        dependencies: deps.iter().map(|d| Arc::<str>::from(*d)).collect(),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    let classes = [
+        class("Orders", &["lodash", "lodash/fp", "@scope/ui/button", "fs", "./billing"]),
+        class("Billing", &[]),
+    ];
+
+    let mut analyzer = CouplingAnalyzer::new()
+        .with_resolver(|name| (name == "./billing").then(|| "Billing".into()))
+        .with_manifests(manifests);
+    analyzer.build_graph(&classes);
+
+    // Packages are tallied separately and never become graph nodes
+    let orders = analyzer.get_coupling(&"Orders".into()).unwrap();
+    assert_eq!(orders.efferent, 1);
+    assert_eq!(
+        analyzer.third_party_dependencies("Orders"),
+        vec![Arc::<str>::from("@scope/ui"), Arc::<str>::from("lodash")]
+    );
+    assert_eq!(analyzer.excessive_third_party(1), vec![(Arc::<str>::from("Orders"), 2)]);
+    assert!(analyzer.excessive_third_party(2).is_empty());
+}
+
+#[test]
+fn test_manifests_discover_workspace_siblings() {
+    use dei_metrics::{DependencyOrigin, PackageManifests};
+    use std::fs;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let write = |path: &str, content: &str| {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    };
+
+    write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+    write("crates/core/Cargo.toml", "[package]\nname = \"acme-core\"\n\n[dependencies]\nserde = \"1\"\n");
+    write("crates/cli/Cargo.toml", r#"
+[package]
+name = "acme-cli"
+
+[dependencies]
+acme-core = { path = "../core" }
+acme-ffi = { path = "../ffi" }
+clap = "4"
+"#);
+    write("crates/ffi/Cargo.toml", "[package]\nname = \"acme-ffi\"\n\n[lib]\nname = \"acme\"\n");
+
+    // Each crate's siblings are part of the project, whichever manifest was read first
+    let manifests = PackageManifests::discover(root).unwrap();
+    assert_eq!(manifests.classify("acme_core::Config"), DependencyOrigin::Internal);
+    assert_eq!(manifests.classify("acme::analyze"), DependencyOrigin::Internal);
+    assert_eq!(manifests.classify("clap::Parser"), DependencyOrigin::ThirdParty("clap".into()));
+    assert_eq!(manifests.classify("serde::Serialize"), DependencyOrigin::ThirdParty("serde".into()));
+}
+
+#[test]
+fn test_betweenness_centrality() {
+    let edge = |graph: &mut DependencyGraph, from: &str, to: &str| {
+        graph.add_edge(from.into(), to.into(), EdgeKind::Uses);
+    };
+
+    // Everything from A and B reaches C and D through Hub
+    let mut graph = DependencyGraph::new();
+    for (from, to) in [("A", "Hub"), ("B", "Hub"), ("Hub", "C"), ("Hub", "D"), ("Hub", "D")] {
+        edge(&mut graph, from, to);
+    }
+    let centrality = graph.centrality();
+    let hub = &centrality[0];
+    assert_eq!(hub.node.as_ref(), "Hub");
+    assert_eq!((hub.fan_in, hub.fan_out), (2, 2), "parallel edges count once");
+    assert_eq!(hub.degree, 1.0);
+    assert!((hub.betweenness - 4.0 / 12.0).abs() < 1e-9);
+    assert!(centrality[1..].iter().all(|c| c.betweenness == 0.0));
+
+    // Two equally short routes share the credit
+    let mut diamond = DependencyGraph::new();
+    for (from, to) in [("A", "B"), ("A", "C"), ("B", "D"), ("C", "D")] {
+        edge(&mut diamond, from, to);
+    }
+    let centrality = diamond.centrality();
+    let names: Vec<&str> = centrality.iter().map(|c| c.node.as_ref()).collect();
+    assert_eq!(names[..2], ["B", "C"]);
+    assert!((centrality[0].betweenness - 0.5 / 6.0).abs() < 1e-9);
+    assert_eq!(centrality[0].betweenness, centrality[1].betweenness);
+}
+
+#[test]
+fn test_package_coupling() {
+    use dei_core::{metrics::*, thresholds::*};
+    use dei_metrics::PackageGrouping;
+    use std::path::Path;
+
+    let class = |qualified: &str, file: &str, deps: &[&str]| ClassMetrics {
+        name: qualified.rsplit("::").next().unwrap().into(),
+        fully_qualified_name: qualified.into(),
+        file_path: file.into(),
+        lines: Lines(10),
+        method_count: MethodCount(0),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(1),
+        methods: Arc::new([]),
+        dependencies: deps.iter().map(|d| Arc::<str>::from(*d)).collect(),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    // orders ⇄ billing form a cycle; shared is depended upon by both
+    let classes = [
+        class("orders::Order", "/repo/src/orders/order.rs", &["Invoice", "Money"]),
+        class("orders::Cart", "/repo/src/orders/cart.rs", &["Order", "Money"]),
+        class("billing::Invoice", "/repo/src/billing/invoice.rs", &["Cart", "Money"]),
+        class("shared::Money", "/repo/src/shared/money.rs", &[]),
+    ];
+    let mut analyzer = CouplingAnalyzer::new();
+    analyzer.build_graph(&classes);
+
+    let analysis = analyzer.packages(&classes, PackageGrouping::Directory, Path::new("/repo"));
+    let names: Vec<&str> = analysis.packages.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["src/billing", "src/orders", "src/shared"]);
+
+    let orders = &analysis.packages[1];
+    assert_eq!(orders.classes, 2);
+    assert_eq!(orders.efferent, 2, "both order classes reach outside");
+    assert_eq!(orders.afferent, 1, "only Invoice reaches in");
+    let shared = &analysis.packages[2];
+    assert_eq!((shared.afferent, shared.efferent, shared.instability), (3, 0, 0.0));
+
+    assert_eq!(analysis.cycles(), vec![vec![Arc::<str>::from("src/billing"), Arc::from("src/orders")]]);
+
+    let by_namespace = analyzer.packages(&classes, PackageGrouping::Namespace, Path::new("/repo"));
+    let names: Vec<&str> = by_namespace.packages.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["billing", "orders", "shared"]);
+}
+
+#[test]
+fn test_package_distance_from_main_sequence() {
+    use dei_core::{metrics::*, thresholds::*};
+    use dei_metrics::PackageGrouping;
+    use std::path::Path;
+
+    let class = |name: &str, file: &str, deps: &[&str], is_abstract: bool| ClassMetrics {
+        name: name.into(),
+        fully_qualified_name: name.into(),
+        file_path: file.into(),
+        lines: Lines(10),
+        method_count: MethodCount(0),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(1),
+        methods: Arc::new([]),
+        dependencies: deps.iter().map(|d| Arc::<str>::from(*d)).collect(),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract,
+        is_public: true,
+    };
+    // ports is abstract and only depended upon; adapters is concrete and only depends
+    let classes = [
+        class("Repo", "/repo/ports/repo.rs", &[], true),
+        class("Clock", "/repo/ports/clock.rs", &[], true),
+        class("SqlRepo", "/repo/adapters/sql.rs", &["Repo"], false),
+        class("SystemClock", "/repo/adapters/clock.rs", &["Clock"], false),
+        class("Money", "/repo/core/money.rs", &["Repo"], false),
+        class("Ledger", "/repo/core/ledger.rs", &[], true),
+    ];
+    let mut analyzer = CouplingAnalyzer::new();
+    analyzer.build_graph(&classes);
+
+    let analysis = analyzer.packages(&classes, PackageGrouping::Directory, Path::new("/repo"));
+    let metrics = |name: &str| {
+        let p = analysis.packages.iter().find(|p| p.name.as_ref() == name).unwrap();
+        (p.abstractness, p.instability, p.distance)
+    };
+    assert_eq!(metrics("ports"), (1.0, 0.0, 0.0), "stable abstractions sit on the main sequence");
+    assert_eq!(metrics("adapters"), (0.0, 1.0, 0.0), "unstable concrete code does too");
+    assert_eq!(metrics("core"), (0.5, 1.0, 0.5));
+
+    let architecture = analyzer.architecture_quality();
+    assert!(architecture.packages.is_empty());
+    assert_eq!(architecture.mean_distance(), None);
+    let architecture = architecture.with_packages(&analysis);
+    assert_eq!(architecture.packages.len(), 3);
+    assert_eq!(architecture.mean_distance(), Some(0.5 / 3.0));
+}
+
+#[test]
+fn test_layer_rules() {
+    use dei_core::{metrics::*, thresholds::*};
+    use dei_metrics::LayerRules;
+    use std::path::Path;
+
+    let class = |qualified: &str, file: &str, deps: &[&str]| ClassMetrics {
+        name: qualified.rsplit("::").next().unwrap().into(),
+        fully_qualified_name: qualified.into(),
+        file_path: file.into(),
+        lines: Lines(10),
+        method_count: MethodCount(0),
+        property_count: 0,
+        field_count: 0,
+        complexity: Complexity(1),
+        methods: Arc::new([]),
+        dependencies: deps.iter().map(|d| Arc::<str>::from(*d)).collect(),
+        span: LineSpan::default(),
+        entry_point: None,
+        is_abstract: false,
+        is_public: true,
+    };
+    let classes = [
+        class("View", "/repo/src/ui/view.rs", &["OrderService", "Repo"]),
+        class("app::services::OrderService", "/repo/src/app.rs", &["Repo"]),
+        class("Repo", "/repo/src/data/repo.rs", &["View", "Unplaced"]),
+        class("Unplaced", "/repo/src/misc/unplaced.rs", &["View"]),
+    ];
+    let mut analyzer = CouplingAnalyzer::new();
+    analyzer.build_graph(&classes);
+
+    let rules = LayerRules::new(["ui", "serv*", "data"]);
+    let root = Path::new("/repo");
+    assert_eq!(rules.layer_of(&classes[0], root), Some(0));
+    assert_eq!(rules.layer_of(&classes[1], root), Some(1), "placed by namespace");
+    assert_eq!(rules.layer_of(&classes[3], root), None);
+
+    // Downward dependencies and unplaced classes are fine; data reaching up into ui isn't
+    let violations = rules.check(&analyzer, &classes, root);
+    assert_eq!(violations.len(), 1);
+    assert_eq!((violations[0].class.as_ref(), violations[0].target.as_ref()), ("Repo", "View"));
+    assert_eq!((violations[0].layer.as_ref(), violations[0].target_layer.as_ref()), ("data", "ui"));
+
+    assert!(LayerRules::default().check(&analyzer, &classes, root).is_empty());
+}